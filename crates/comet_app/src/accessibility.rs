@@ -0,0 +1,54 @@
+use std::collections::HashSet;
+use comet_input::keyboard::Key;
+
+/// The state of the keys that `Accessibility::with_hold_to_toggle` turned into toggles.
+pub(crate) struct HoldToggles {
+	down: HashSet<Key>,
+	on: HashSet<Key>,
+	switched: HashSet<Key>
+}
+
+impl HoldToggles {
+	pub fn new() -> Self {
+		Self {
+			down: HashSet::new(),
+			on: HashSet::new(),
+			switched: HashSet::new()
+		}
+	}
+
+	/// Flips the toggles whose key went down since the last tick. `is_down` tells whether a key is physically down.
+	pub fn update(&mut self, keys: &[Key], is_down: impl Fn(Key) -> bool) {
+		self.switched.clear();
+		self.on.retain(|key| keys.contains(key));
+		self.down.retain(|key| keys.contains(key));
+
+		for &key in keys {
+			// the edge is tracked here, since several ticks can run in the frame of a single key press
+			if !is_down(key) {
+				self.down.remove(&key);
+				continue;
+			}
+			if self.down.insert(key) {
+				if !self.on.remove(&key) {
+					self.on.insert(key);
+				}
+				self.switched.insert(key);
+			}
+		}
+	}
+
+	pub fn is_on(&self, key: Key) -> bool {
+		self.on.contains(&key)
+	}
+
+	/// Whether the toggle was switched on this tick.
+	pub fn switched_on(&self, key: Key) -> bool {
+		self.switched.contains(&key) && self.on.contains(&key)
+	}
+
+	/// Whether the toggle was switched off this tick.
+	pub fn switched_off(&self, key: Key) -> bool {
+		self.switched.contains(&key) && !self.on.contains(&key)
+	}
+}
@@ -1,8 +1,9 @@
 use std::any::{type_name, Any};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use comet_ecs::{Component, ComponentSet, Render, Transform2D, World};
+use comet_ecs::{Component, ComponentSet, Render, Theme, Transform2D, World};
 use comet_resources::{ResourceManager, Vertex};
+use comet_resources::video::{Video, VideoError};
 use comet_renderer::renderer2d::Renderer2D;
 
 use winit::{
@@ -11,16 +12,50 @@ use winit::{
 	keyboard::{KeyCode, PhysicalKey},
 	window::{Icon, Window},
 };
-use comet_colors::LinearRgba;
+use comet_colors::{Color, LinearRgba};
 use comet_ecs::math::Point3;
+use comet_ecs::math::noise::{NoiseHandle, NoiseJob};
 use comet_log::*;
 use winit::dpi::{LogicalSize, PhysicalSize};
 use winit::event_loop::ControlFlow;
 use winit_input_helper::WinitInputHelper;
 use comet_input::input_handler::InputHandler;
 use comet_input::keyboard::Key;
-use comet_renderer::renderer::Renderer;
-use crate::GameState;
+use comet_renderer::accessibility::Accessibility;
+use comet_renderer::camera::ScalePolicy;
+use comet_renderer::renderer::{Backends, PowerPreference, Renderer, RendererSettings, SupportReport};
+use comet_save::{SaveData, SaveError, SaveRegistry};
+use comet_sound::{Audio, SoundHandle, MUSIC_BUS};
+use crate::thread_pool::EventChannel;
+use crate::test_app::SimulatedInput;
+use crate::hit_stop::{advance_flashes, advance_materials, HitStopTimer};
+use crate::accessibility::HoldToggles;
+use crate::day_night::{Dawn, DayNightCycle, Dusk};
+use crate::preload::Preloader;
+use crate::watchdog::{report_startup, SystemTimings, Watchdog};
+use crate::pointer::{MouseEdges, PointerInteraction};
+use crate::drag_drop::DragAndDrop;
+use crate::ghost::advance_ghosts;
+use crate::input_recording::InputRecorder;
+#[cfg(feature = "hot_reload")]
+use crate::hot_reload::{GameLibrary, Reload};
+use crate::{crash_handler, AppExit, CometError, PreloadGroup, HitStop, Stats, GhostRecorder, GhostRecording, Console, LogOverlay, Inspector, AppResumed, AppSuspended, CrashReport, ExitReason, FileDropped, FileHoverCancelled, FileHovered, FocusPolicy, GameState, LaunchOptions, TaskContext, TaskExecutor, TaskHandle, ThreadPool, EventSender, WindowFocused, WindowOccluded, TouchInput, TouchPhase, InputRecording};
+
+/// The name the sound track of a cutscene is loaded under.
+const CUTSCENE_AUDIO: &str = "cutscene";
+
+/// Embeds an icon image into the binary for `App::with_icon_bytes` and `App::set_icon`,
+/// so the game does not need the file next to it. The path is relative to the file the macro is used in.
+///
+/// ```ignore
+/// App::new(App2D).with_icon_bytes(include_icon!("../res/icon.png"))
+/// ```
+#[macro_export]
+macro_rules! include_icon {
+	($path:expr) => {
+		include_bytes!($path).as_slice()
+	};
+}
 
 pub enum ApplicationType {
 	App2D,
@@ -30,15 +65,61 @@ pub enum ApplicationType {
 pub struct App<'a> {
 	title: &'a str,
 	icon: Option<Icon>,
+	icon_changed: bool,
 	size: Option<LogicalSize<u32>>,
-	clear_color: Option<LinearRgba>,
+	renderer_settings: RendererSettings,
 	input_manager: WinitInputHelper,
+	simulated_input: SimulatedInput,
+	input_recorder: Option<InputRecorder>,
+	input_replay: Option<InputRecording>,
 	delta_time: f32,
 	update_timer: f32,
 	game_state: Option<Box<dyn Any>>,
 	world: World,
 	fullscreen: bool,
-	should_quit: bool
+	launch_options: LaunchOptions,
+	tasks: TaskExecutor,
+	thread_pool: Option<ThreadPool>,
+	event_channel: EventChannel,
+	save_registry: SaveRegistry,
+	console: Console,
+	log_overlay: LogOverlay,
+	inspector: Inspector,
+	audio: Audio,
+	preloader: Preloader,
+	cutscene_sound: Option<SoundHandle>,
+	deterministic: bool,
+	simulation_frame: u64,
+	hit_stop: HitStopTimer,
+	accessibility: Accessibility,
+	accessibility_changed: bool,
+	hold_toggles: HoldToggles,
+	interpolate: bool,
+	day_night: Option<DayNightCycle>,
+	reset_tint: bool,
+	touches: std::collections::HashMap<u64, comet_ecs::math::Vec2>,
+	watchdog: Watchdog,
+	pointer: Option<PointerInteraction>,
+	mouse_edges: MouseEdges,
+	drag_and_drop: Option<DragAndDrop>,
+	stats: Stats,
+	ghost_recorder: Option<GhostRecorder>,
+	#[cfg(target_os = "android")]
+	android_app: Option<winit::platform::android::activity::AndroidApp>,
+	#[cfg(feature = "scripting")]
+	scripts: comet_script::ScriptEngine,
+	/// Declared after `world` and `game_state`, so they are dropped while the game library they may use code of is still loaded.
+	#[cfg(feature = "hot_reload")]
+	game_library: Option<GameLibrary>,
+	focused: bool,
+	occluded: bool,
+	suspended: bool,
+	focus_policy: FocusPolicy,
+	exit_request: Option<AppExit>,
+	exit_request_handlers: Vec<fn(&mut App<'a>, &mut AppExit)>,
+	exit_handlers: Vec<fn(&mut App<'a>)>,
+	crash_report_directory: std::path::PathBuf,
+	on_crash: Option<fn(&CrashReport)>
 }
 
 impl<'a> App<'a> {
@@ -48,18 +129,68 @@ impl<'a> App<'a> {
 			ApplicationType::App3D => World::new("3D"),
 		};
 
+		let launch_options = LaunchOptions::from_env().unwrap_or_else(|e| {
+			warn!("Ignoring launch options: {}", e);
+			LaunchOptions::new()
+		});
+
 		Self {
 			title: "Untitled",
 			icon: None,
+			icon_changed: false,
 			size: None,
-			clear_color: None,
+			renderer_settings: RendererSettings::new(),
 			input_manager: WinitInputHelper::new(),
+			simulated_input: SimulatedInput::new(),
+			input_recorder: None,
+			input_replay: None,
 			delta_time: 0.0,
 			update_timer: 0.0166667,
 			game_state: None,
 			world,
 			fullscreen: false,
-			should_quit: false
+			launch_options,
+			tasks: TaskExecutor::new(),
+			thread_pool: None,
+			event_channel: EventChannel::new(),
+			save_registry: SaveRegistry::new(),
+			console: Console::new(),
+			log_overlay: LogOverlay::new(),
+			inspector: Inspector::new(),
+			audio: Audio::new(),
+			preloader: Preloader::new(),
+			cutscene_sound: None,
+			deterministic: false,
+			simulation_frame: 0,
+			hit_stop: HitStopTimer::new(),
+			accessibility: Accessibility::new(),
+			accessibility_changed: true,
+			hold_toggles: HoldToggles::new(),
+			interpolate: true,
+			day_night: None,
+			reset_tint: false,
+			touches: std::collections::HashMap::new(),
+			watchdog: Watchdog::new(),
+			pointer: None,
+			mouse_edges: MouseEdges::new(),
+			drag_and_drop: None,
+			stats: Stats::new(),
+			ghost_recorder: None,
+			#[cfg(target_os = "android")]
+			android_app: None,
+			#[cfg(feature = "scripting")]
+			scripts: comet_script::ScriptEngine::new(),
+			#[cfg(feature = "hot_reload")]
+			game_library: None,
+			focused: true,
+			occluded: false,
+			suspended: false,
+			focus_policy: FocusPolicy::default(),
+			exit_request: None,
+			exit_request_handlers: Vec::new(),
+			exit_handlers: Vec::new(),
+			crash_report_directory: std::path::PathBuf::from("crash_reports"),
+			on_crash: None
 		}
 	}
 
@@ -73,13 +204,63 @@ impl<'a> App<'a> {
 		self
 	}
 
+	/// Uses the encoded image in `bytes` as the window icon, e.g. one embedded with `include_icon!`.
+	pub fn with_icon_bytes(mut self, bytes: &[u8]) -> Self {
+		self.icon = Self::icon_from_bytes(bytes);
+		self
+	}
+
 	pub fn with_size(mut self, width: u32, height: u32) -> Self {
 		self.size = Some(LogicalSize::new(width, height));
 		self
 	}
 
-	pub fn with_clear_color(mut self, clear_color: LinearRgba) -> Self {
-		self.clear_color = Some(clear_color);
+	/// Sets the background color, e.g. `sRgba::CORNFLOWER_BLUE` or any other color type.
+	pub fn with_clear_color(mut self, clear_color: impl Color) -> Self {
+		self.renderer_settings = self.renderer_settings.with_clear_color(clear_color.to_linear());
+		self
+	}
+
+	/// Whether to prefer the integrated (`LowPower`) or the dedicated (`HighPerformance`) GPU.
+	pub fn with_power_preference(mut self, power_preference: PowerPreference) -> Self {
+		self.renderer_settings = self.renderer_settings.with_power_preference(power_preference);
+		self
+	}
+
+	/// Limits the graphics APIs the renderer may use, e.g. `Backends::VULKAN` when another driver shows a black screen.
+	pub fn with_backend(mut self, backends: Backends) -> Self {
+		self.renderer_settings = self.renderer_settings.with_backends(backends);
+		self
+	}
+
+	/// Whether the window surface should use an sRGB format. It does by default.
+	pub fn with_srgb_surface(mut self, srgb_surface: bool) -> Self {
+		self.renderer_settings = self.renderer_settings.with_srgb_surface(srgb_surface);
+		self
+	}
+
+	/// Designs the game for a window of `width` by `height` pixels: the camera shows that much of the world at a zoom of 1
+	/// whatever the size and DPI of the window, scaled by the policy of `with_scale_policy`.
+	pub fn with_virtual_resolution(mut self, width: u32, height: u32) -> Self {
+		self.renderer_settings = self.renderer_settings.with_virtual_resolution(width, height);
+		self
+	}
+
+	/// How the virtual resolution fills windows of other aspect ratios, `ScalePolicy::Fit` by default.
+	pub fn with_scale_policy(mut self, scale_policy: ScalePolicy) -> Self {
+		self.renderer_settings = self.renderer_settings.with_scale_policy(scale_policy);
+		self
+	}
+
+	/// Culls sprites on the GPU and draws them indirectly where the GPU supports it, for scenes with many thousands of sprites.
+	pub fn with_gpu_culling(mut self, gpu_culling: bool) -> Self {
+		self.renderer_settings = self.renderer_settings.with_gpu_culling(gpu_culling);
+		self
+	}
+
+	/// Warns once a frame batches more than `vertices` vertices, 65536 by default. The renderer reports the size in its `RenderStats`.
+	pub fn with_batch_budget(mut self, vertices: u32) -> Self {
+		self.renderer_settings = self.renderer_settings.with_batch_budget(vertices);
 		self
 	}
 
@@ -88,6 +269,52 @@ impl<'a> App<'a> {
 		self
 	}
 
+	/// Replaces the launch options parsed from the command line.
+	pub fn with_launch_options(mut self, launch_options: LaunchOptions) -> Self {
+		self.launch_options = launch_options;
+		self
+	}
+
+	/// Sets how the app behaves while its window is unfocused or hidden.
+	pub fn with_focus_policy(mut self, focus_policy: FocusPolicy) -> Self {
+		self.focus_policy = focus_policy;
+		self
+	}
+
+	/// Hands the activity from `android_main` to the app. Required on Android.
+	#[cfg(target_os = "android")]
+	pub fn with_android_app(mut self, android_app: winit::platform::android::activity::AndroidApp) -> Self {
+		comet_resources::assets::set_android_app(android_app.clone());
+		self.android_app = Some(android_app);
+		self
+	}
+
+	/// Makes the simulation reproducible: seeds the engine random number generator, runs exactly one
+	/// fixed update per frame instead of catching up with the wall clock and turns off behaviors that depend on
+	/// the outside world, like background update rates and script hot reloading.
+	/// Use `frame_hash` to compare the state of two runs.
+	pub fn deterministic(mut self, seed: u64) -> Self {
+		comet_ecs::math::random::seed_rng(seed);
+		self.deterministic = true;
+		#[cfg(feature = "scripting")]
+		self.scripts.set_hot_reload(false);
+		info!("Running deterministically with seed {}", seed);
+		self
+	}
+
+	/// Sets the directory the crash report is written to if the app panics.
+	/// Defaults to `crash_reports` in the working directory.
+	pub fn with_crash_report_directory(mut self, directory: &'a str) -> Self {
+		self.crash_report_directory = std::path::PathBuf::from(directory);
+		self
+	}
+
+	/// Sets a function that is called with the crash report after it was written, e.g. to show an error dialog.
+	pub fn with_crash_callback(mut self, on_crash: fn(&CrashReport)) -> Self {
+		self.on_crash = Some(on_crash);
+		self
+	}
+
 	fn load_icon(path: &std::path::Path) -> Option<Icon> {
 		let image = image::open(path).expect("Failed to open icon image");
 		let rgba_image = image.to_rgba8();
@@ -95,6 +322,27 @@ impl<'a> App<'a> {
 		Some(Icon::from_rgba(rgba_image.into_raw(), width, height).unwrap())
 	}
 
+	fn icon_from_bytes(bytes: &[u8]) -> Option<Icon> {
+		let image = match image::load_from_memory(bytes) {
+			Ok(image) => image.to_rgba8(),
+			Err(e) => {
+				error!("Failed to decode the icon image: {}", e);
+				return None;
+			}
+		};
+		let (width, height) = image.dimensions();
+		Icon::from_rgba(image.into_raw(), width, height)
+			.map_err(|e| error!("Invalid icon image: {}", e))
+			.ok()
+	}
+
+	/// Changes the window icon while the app runs, e.g. to show a badge on it, or removes it with `None`.
+	/// Takes the encoded image like `with_icon_bytes`.
+	pub fn set_icon(&mut self, bytes: Option<&[u8]>) {
+		self.icon = bytes.and_then(Self::icon_from_bytes);
+		self.icon_changed = true;
+	}
+
 	pub fn game_state<T: 'static>(&self) -> Option<&T> {
 		self.game_state.as_ref()?.downcast_ref::<T>()
 	}
@@ -111,24 +359,527 @@ impl<'a> App<'a> {
 		&mut self.world
 	}
 
+	/// Spawns a task that is polled once after every update until it completes.
+	/// Use `wait_seconds`, `wait_until` and `next_frame` inside of it to spread logic over multiple frames,
+	/// and a `TaskContext` from `task_context` to reach the world.
+	pub fn spawn_task<F: std::future::Future<Output = ()> + 'static>(&mut self, task: F) -> TaskHandle {
+		self.tasks.spawn(task)
+	}
+
+	/// A handle to move into tasks, so they can change the world after every update they are polled in.
+	pub fn task_context(&self) -> TaskContext {
+		self.tasks.context()
+	}
+
+	pub fn cancel_task(&mut self, handle: TaskHandle) {
+		self.tasks.cancel(handle);
+	}
+
+	pub fn is_task_running(&self, handle: TaskHandle) -> bool {
+		self.tasks.is_running(handle)
+	}
+
+	/// Runs a blocking job on the thread pool of the app. The pool is created on first use.
+	/// Use an `EventSender` to get the results back into the world.
+	pub fn spawn_blocking<F: FnOnce() + Send + 'static>(&mut self, job: F) {
+		self.thread_pool
+			.get_or_insert_with(ThreadPool::with_default_size)
+			.execute(job);
+	}
+
+	/// Generates noise on the thread pool of the app, e.g. `app.spawn_noise(noise.generate_async())`.
+	/// Poll the returned handle for the progress and the noise, or await it in a task.
+	pub fn spawn_noise(&mut self, (handle, job): (NoiseHandle, NoiseJob)) -> NoiseHandle {
+		self.spawn_blocking(move || job.run());
+		handle
+	}
+
+	/// Declares a group of assets `preload` can load ahead of time under `name`, e.g. "level1_assets".
+	pub fn add_preload_group(&mut self, name: &str, group: PreloadGroup) {
+		self.preloader.add_group(name, group);
+	}
+
+	/// Starts loading the assets of the group and the groups it depends on in the background.
+	/// Poll `preload_progress` for a loading bar, `PreloadFinished` is sent into the world once it is done.
+	pub fn preload(&mut self, name: &str) {
+		for job in self.preloader.preload(name) {
+			self.spawn_blocking(job);
+		}
+	}
+
+	/// How much of the group and its dependencies is loaded, from 0 to 1.
+	pub fn preload_progress(&self, name: &str) -> f32 {
+		self.preloader.progress(name)
+	}
+
+	pub fn is_preloaded(&self, name: &str) -> bool {
+		self.preloader.is_loaded(name)
+	}
+
+	/// Releases the assets of the group that no other preloaded group uses, e.g. after leaving a level.
+	pub fn unload_preload_group(&mut self, name: &str) {
+		self.preloader.unload(name, &mut self.audio);
+	}
+
+	/// Creates a sender that can be moved into other threads to send events of type `T` into the world.
+	/// Sent events are delivered before the next update.
+	pub fn event_sender<T: Send + 'static>(&self) -> EventSender<T> {
+		self.event_channel.sender::<T>()
+	}
+
+	/// Includes every `T` in the world in save games, stored under `name`.
+	pub fn register_saveable<T: Component + SaveData>(&mut self, name: &str) {
+		self.save_registry.register::<T>(name);
+	}
+
+	/// Gives access to the save version, migrations, compression and custom save hooks.
+	pub fn save_registry_mut(&mut self) -> &mut SaveRegistry {
+		&mut self.save_registry
+	}
+
+	/// Saves the world into the given slot in the data directory of the app and returns the path of the save file.
+	pub fn save_game(&self, slot: &str) -> Result<std::path::PathBuf, SaveError> {
+		let path = comet_save::slot_path(self.title, slot);
+		self.save_registry.save_to(&self.world, &path)?;
+		Ok(path)
+	}
+
+	/// Replaces the world with the contents of the given slot, migrating older saves if needed.
+	pub fn load_game(&mut self, slot: &str) -> Result<(), SaveError> {
+		let path = comet_save::slot_path(self.title, slot);
+		self.save_registry.load_from(&mut self.world, &path)
+	}
+
+	/// Loads the translations in `directory` (see `Localization::load_directory`) and switches to `locale`.
+	pub fn with_translations(self, directory: &str, locale: &str) -> Self {
+		let mut localization = comet_i18n::localization_mut();
+		if let Err(e) = localization.load_directory(std::path::Path::new(directory)) {
+			error!("{}", e);
+		}
+		localization.set_locale(locale);
+		drop(localization);
+		self
+	}
+
+	/// Loads the component defaults in the TOML file at `path` (see `Theme`) and applies them to every component
+	/// constructed with `Component::new` from now on, and the `[Camera]` zoom to the camera.
+	pub fn with_theme(self, path: &str) -> Self {
+		match Theme::load(path) {
+			Ok(theme) => theme.install(),
+			Err(e) => error!("{}", e)
+		}
+		self
+	}
+
+	/// Loads the stat and achievement definitions in the TOML file at `path` (see `Stats`) and the progress saved
+	/// in the data directory of the app. Call it after `with_title`, the title names the data directory.
+	pub fn with_stats(mut self, path: &str) -> Self {
+		match Stats::load(path) {
+			Ok(stats) => self.stats = stats,
+			Err(e) => error!("{}", e)
+		}
+		let saved = self.stats_path();
+		if saved.exists() {
+			if let Err(e) = self.stats.load_from(&saved) {
+				error!("Failed to load the saved stats: {}", e);
+			}
+		}
+		self
+	}
+
+	/// The stats and achievements of the player, e.g. `app.stats().add("enemies_killed", 1)`.
+	pub fn stats(&mut self) -> &mut Stats {
+		&mut self.stats
+	}
+
+	/// Saves the stats and achievements into the data directory of the app and returns the path of the file.
+	/// Changed stats are also saved on exit.
+	pub fn save_stats(&mut self) -> Result<std::path::PathBuf, SaveError> {
+		let path = self.stats_path();
+		self.stats.save_to(&path)?;
+		Ok(path)
+	}
+
+	fn stats_path(&self) -> std::path::PathBuf {
+		comet_save::data_dir(self.title).join("stats.sav")
+	}
+
+	pub fn locale(&self) -> String {
+		comet_i18n::localization().locale().to_string()
+	}
+
+	/// Switches the language of every `tr!` call and `LocalizedText` and sends a `LocaleChanged` event.
+	pub fn set_locale(&mut self, locale: &str) {
+		comet_i18n::localization_mut().set_locale(locale);
+		self.world.send_event(comet_i18n::LocaleChanged(locale.to_string()));
+	}
+
+	/// Loads a Rhai script that runs once now and has its `update(dt)` function called on every update.
+	#[cfg(feature = "scripting")]
+	pub fn load_script(&mut self, path: &str) {
+		self.scripts.load(&mut self.world, path);
+	}
+
+	/// Gives access to the script engine, e.g. to register components and functions for scripts.
+	#[cfg(feature = "scripting")]
+	pub fn script_engine_mut(&mut self) -> &mut comet_script::ScriptEngine {
+		&mut self.scripts
+	}
+
+	/// Runs the setup and update functions exported with `export_game!` from the library at `library_path`
+	/// instead of the ones given to `run`, and loads the library again whenever it is rebuilt.
+	/// The world is kept across reloads unless the layout version of the library changes, see `export_game!`.
+	/// The functions given to `run` are used while the library can not be loaded.
+	#[cfg(feature = "hot_reload")]
+	pub fn with_hot_reload(mut self, library_path: &str) -> Self {
+		self.game_library = Some(GameLibrary::new(library_path));
+		self
+	}
+
+	/// Registers `T` in the world and offers its name for completion in the console.
+	pub fn register_component<T: Component + 'static>(&mut self) {
+		self.world.register_component::<T>();
+		let name = T::type_name();
+		self.console.add_completion(name.rsplit("::").next().unwrap_or(&name));
+	}
+
+	pub fn console(&self) -> &Console {
+		&self.console
+	}
+
+	/// Gives access to the developer console, e.g. to register commands.
+	pub fn console_mut(&mut self) -> &mut Console {
+		&mut self.console
+	}
+
+	pub fn log_overlay(&self) -> &LogOverlay {
+		&self.log_overlay
+	}
+
+	/// Gives access to the in-game log viewer, e.g. to change its toggle key.
+	pub fn log_overlay_mut(&mut self) -> &mut LogOverlay {
+		&mut self.log_overlay
+	}
+
+	pub fn inspector(&self) -> &Inspector {
+		&self.inspector
+	}
+
+	/// Gives access to the entity inspector, e.g. to register the components of the game.
+	pub fn inspector_mut(&mut self) -> &mut Inspector {
+		&mut self.inspector
+	}
+
+	/// The audio system, e.g. `app.audio().bus("music").set_volume(0.5)`.
+	pub fn audio(&mut self) -> &mut Audio {
+		&mut self.audio
+	}
+
+	/// Plays the video at `path` over the whole window with its sound track on the music bus, e.g. an intro cutscene.
+	/// `renderer.is_playing_video()` tells when it is over. See `Video` for the files that can be played.
+	pub fn play_cutscene(&mut self, renderer: &mut Renderer2D, path: &str) -> Result<(), VideoError> {
+		let mut video = Video::open(path)?;
+		self.skip_cutscene(renderer);
+		if let Some(audio) = video.take_audio() {
+			self.audio.load_samples(CUTSCENE_AUDIO, audio.sample_rate(), audio.channels(), audio.samples());
+			self.cutscene_sound = self.audio.play_audio_on(CUTSCENE_AUDIO, MUSIC_BUS);
+		}
+		renderer.play_video(video);
+		Ok(())
+	}
+
+	/// Stops the cutscene and its sound track, e.g. when the player presses a key to skip it.
+	pub fn skip_cutscene(&mut self, renderer: &mut Renderer2D) {
+		renderer.stop_video();
+		if let Some(sound) = self.cutscene_sound.take() {
+			sound.stop(0.1);
+		}
+		self.audio.unload_audio(CUTSCENE_AUDIO);
+	}
+
+	/// Sets the accessibility options the app starts with, e.g. loaded from the settings of the player.
+	pub fn with_accessibility(mut self, accessibility: Accessibility) -> Self {
+		self.set_accessibility(accessibility);
+		self
+	}
+
+	pub fn accessibility(&self) -> &Accessibility {
+		&self.accessibility
+	}
+
+	/// Changes the accessibility options, e.g. from an options menu. The renderer picks them up on the next update.
+	pub fn set_accessibility(&mut self, accessibility: Accessibility) {
+		self.accessibility = accessibility;
+		self.accessibility_changed = true;
+	}
+
+	/// Whether sprites are drawn between their positions after the last two fixed updates, so they move smoothly
+	/// on displays refreshing faster than the update rate. On by default, deterministic apps never interpolate.
+	/// Single entities opt out with `NoInterpolation`.
+	pub fn with_interpolation(mut self, interpolate: bool) -> Self {
+		self.interpolate = interpolate;
+		self
+	}
+
+	/// Warns, at most once a second per system, when a system takes longer than `budget` in a fixed update,
+	/// e.g. "update" for the update function of the game, or "audio", "scripts" and "events" for those of the engine.
+	pub fn with_system_budget(mut self, budget: Duration) -> Self {
+		self.watchdog.set_budget(Some(budget));
+		self
+	}
+
+	/// Gives `system` a budget other than the one of `with_system_budget`, e.g. more time for "update".
+	pub fn with_budget_for(mut self, system: &'static str, budget: Duration) -> Self {
+		self.watchdog.set_budget_for(system, budget);
+		self
+	}
+
+	/// Sends `Clicked`, `HoverEnter`, `HoverExit` and `Dragged` into the world for the sprites under the mouse.
+	pub fn with_pointer_interaction(mut self) -> Self {
+		self.pointer = Some(PointerInteraction::new());
+		self
+	}
+
+	/// The state of the pointer events, `None` unless turned on with `with_pointer_interaction`.
+	pub fn pointer_interaction(&self) -> Option<&PointerInteraction> {
+		self.pointer.as_ref()
+	}
+
+	/// Lets the left mouse button drag `Draggable` entities onto `DropTarget`s, sending `DroppedOn` into the world.
+	/// Turns on the pointer events as well.
+	pub fn with_drag_and_drop(mut self) -> Self {
+		self.pointer.get_or_insert_with(PointerInteraction::new);
+		self.drag_and_drop = Some(DragAndDrop::new());
+		self
+	}
+
+	/// The `Draggable` entity being dragged right now.
+	pub fn dragged_entity(&self) -> Option<u32> {
+		self.drag_and_drop.as_ref().and_then(DragAndDrop::dragged)
+	}
+
+	/// Starts sampling the entities of `recorder` after every update, replacing a recording that is still running.
+	pub fn start_ghost_recording(&mut self, recorder: GhostRecorder) {
+		if self.ghost_recorder.is_some() {
+			warn!("A ghost recording was still running, it is dropped for the new one");
+		}
+		self.ghost_recorder = Some(recorder);
+	}
+
+	/// The recording that is running, e.g. to add entities to it or take a `GhostRecorder::snapshot` for a kill-cam.
+	pub fn ghost_recorder_mut(&mut self) -> Option<&mut GhostRecorder> {
+		self.ghost_recorder.as_mut()
+	}
+
+	/// Ends the running recording and hands out what was recorded.
+	pub fn stop_ghost_recording(&mut self) -> Option<GhostRecording> {
+		self.ghost_recorder.take().map(GhostRecorder::finish)
+	}
+
+	/// Runs `cycle` from the start, tinting the world by the time of day and sending `Dawn` and `Dusk` into the world.
+	pub fn with_day_night_cycle(mut self, cycle: DayNightCycle) -> Self {
+		self.set_day_night_cycle(Some(cycle));
+		self
+	}
+
+	pub fn day_night_cycle(&self) -> Option<&DayNightCycle> {
+		self.day_night.as_ref()
+	}
+
+	/// Gives access to the running day/night cycle, e.g. to skip to the morning.
+	pub fn day_night_cycle_mut(&mut self) -> Option<&mut DayNightCycle> {
+		self.day_night.as_mut()
+	}
+
+	/// Swaps the day/night cycle. `None` stops it and takes the tint off the world on the next update.
+	pub fn set_day_night_cycle(&mut self, cycle: Option<DayNightCycle>) {
+		self.reset_tint = cycle.is_none() && self.day_night.is_some();
+		self.day_night = cycle;
+	}
+
+	/// The positions of all fingers currently on the screen, by touch id.
+	pub fn touches(&self) -> &std::collections::HashMap<u64, comet_ecs::math::Vec2> {
+		&self.touches
+	}
+
 	pub fn input_manager(&self) -> &WinitInputHelper {
 		&self.input_manager
 	}
 
+	/// Keys set to hold to toggle count as pressed when they are switched on.
 	pub fn key_pressed(&self, key: Key) -> bool {
-		self.input_manager.key_pressed(key)
+		if self.accessibility.is_hold_to_toggle(key) {
+			return self.hold_toggles.switched_on(key);
+		}
+		self.input_manager.key_pressed(key) || self.simulated_input.key_pressed(key)
 	}
 
+	/// Keys set to hold to toggle count as held from one press to the next.
 	pub fn key_held(&self, key: Key) -> bool {
-		self.input_manager.key_held(key)
+		if self.accessibility.is_hold_to_toggle(key) {
+			return self.hold_toggles.is_on(key);
+		}
+		self.is_key_down(key)
 	}
 
+	/// Keys set to hold to toggle count as released when they are switched off.
 	pub fn key_released(&self, key: Key) -> bool {
-		self.input_manager.key_released(key)
+		if self.accessibility.is_hold_to_toggle(key) {
+			return self.hold_toggles.switched_off(key);
+		}
+		self.input_manager.key_released(key) || self.simulated_input.key_released(key)
 	}
 
+	/// Whether the key is physically down, ignoring the accessibility options.
+	fn is_key_down(&self, key: Key) -> bool {
+		self.input_manager.key_held(key) || self.simulated_input.key_held(key)
+	}
+
+	/// Key presses fed in by code instead of the keyboard, e.g. by a `TestApp`.
+	pub(crate) fn simulated_input(&mut self) -> &mut SimulatedInput {
+		&mut self.simulated_input
+	}
+
+	/// Requests the app to exit. The request is passed through the handlers registered with `on_exit_request`
+	/// at the start of the next frame and the app only shuts down if none of them cancels it.
 	pub fn quit(&mut self) {
-		self.should_quit = true;
+		self.exit_request = Some(AppExit::new(ExitReason::Quit));
+	}
+
+	/// Registers a handler that is called whenever the app is asked to exit, either through `quit` or by closing the window.
+	/// The handler can cancel the exit with `AppExit::cancel`.
+	pub fn on_exit_request(&mut self, handler: fn(&mut App<'a>, &mut AppExit)) {
+		self.exit_request_handlers.push(handler);
+	}
+
+	/// Registers a callback that runs once the app is shutting down, before the renderer and the window are dropped.
+	/// This is the place to save any unsaved state.
+	pub fn on_exit(&mut self, callback: fn(&mut App<'a>)) {
+		self.exit_handlers.push(callback);
+	}
+
+	/// Runs the exit request handlers and returns whether the app should actually exit.
+	fn handle_exit_request(&mut self, mut request: AppExit) -> bool {
+		for handler in self.exit_request_handlers.clone() {
+			handler(self, &mut request);
+			if request.is_cancelled() {
+				info!("Exit ({:?}) was cancelled", request.reason());
+				return false;
+			}
+		}
+		true
+	}
+
+	/// The shutdown sequence of the app.
+	/// First the audio fades out and the exit callbacks run, then the renderer releases its GPU resources and only then the window is dropped.
+	fn shutdown<R: Renderer>(&mut self, renderer: Option<R>, window: Arc<Window>) {
+		info!("Shutting down {}!", self.title);
+
+		// fades every sound out, so nothing keeps playing or is cut off while the rest is torn down
+		self.audio.set_paused(true);
+
+		for callback in std::mem::take(&mut self.exit_handlers) {
+			callback(self);
+		}
+		if self.stats.has_changes() {
+			if let Err(e) = self.save_stats() {
+				error!("Failed to save the stats: {}", e);
+			}
+		}
+
+		if let Some(recorder) = self.input_recorder.take() {
+			match recorder.save() {
+				Ok(path) => info!("Recorded the input into {}", path.display()),
+				Err(e) => error!("Failed to save the input recording: {}", e)
+			}
+		}
+
+		if let Some(thread_pool) = self.thread_pool.take() {
+			drop(thread_pool);
+		}
+		drop(renderer);
+		drop(window);
+
+		info!("Shutdown complete!");
+		comet_log::logger::flush();
+	}
+
+	/// The standard flags the app was started with, see `LaunchOptions`.
+	pub fn launch_options(&self) -> &LaunchOptions {
+		&self.launch_options
+	}
+
+	pub fn is_focused(&self) -> bool {
+		self.focused
+	}
+
+	/// Whether the window is currently fully hidden, e.g. minimized.
+	pub fn is_occluded(&self) -> bool {
+		self.occluded
+	}
+
+	pub fn is_suspended(&self) -> bool {
+		self.suspended
+	}
+
+	pub fn focus_policy(&self) -> &FocusPolicy {
+		&self.focus_policy
+	}
+
+	pub fn set_focus_policy(&mut self, focus_policy: FocusPolicy) {
+		self.focus_policy = focus_policy;
+	}
+
+	/// Whether the update function is currently skipped because of the focus policy.
+	pub fn is_paused(&self) -> bool {
+		self.suspended || (!self.focused && self.focus_policy.pause_on_focus_loss)
+	}
+
+	/// Whether the audio should currently be muted because of the focus policy.
+	pub fn is_muted_by_focus_policy(&self) -> bool {
+		(self.occluded || self.suspended) && self.focus_policy.mute_when_minimized
+	}
+
+	/// Offers a key to the console, the log overlay and the inspector in that order.
+	/// Returns true if one of them used it, so the game does not see it.
+	fn handle_tool_key(&mut self, event: &KeyEvent) -> bool {
+		self.console.handle_key(event) || self.log_overlay.handle_key(event) || self.inspector.handle_key(event)
+	}
+
+	/// The time between two updates, taking a lower background update rate into account.
+	fn effective_update_timer(&self) -> f32 {
+		if self.deterministic {
+			return self.update_timer;
+		}
+		match self.focus_policy.background_update_rate {
+			Some(rate) if !self.focused => if rate == 0 { f32::INFINITY } else { self.update_timer.max(1.0/rate as f32) },
+			_ => self.update_timer
+		}
+	}
+
+	pub fn is_deterministic(&self) -> bool {
+		self.deterministic
+	}
+
+	/// The number of updates that ran so far.
+	pub fn simulation_frame(&self) -> u64 {
+		self.simulation_frame
+	}
+
+	/// Slows down or freezes the updates for a moment, e.g. `app.hit_stop(HitStop::new(0.08))` when a heavy attack lands.
+	/// A running hit stop is only replaced by a longer one.
+	pub fn hit_stop(&mut self, hit_stop: HitStop) {
+		self.hit_stop.start(hit_stop);
+	}
+
+	pub fn is_hit_stopped(&self) -> bool {
+		self.hit_stop.is_active()
+	}
+
+	/// A hash of the entities and all components registered with `register_saveable`.
+	/// Stable across runs and machines, so it can be compared between lockstep peers or against a recorded replay.
+	pub fn frame_hash(&self) -> u64 {
+		self.save_registry.state_hash(&self.world)
 	}
 
 	pub fn dt(&self) -> f32 {
@@ -144,7 +895,13 @@ impl<'a> App<'a> {
 		self.update_timer = 1.0/update_rate as f32;
 	}
 
-	fn create_window(app_title: &str, app_icon: &Option<Icon>, window_size: &Option<LogicalSize<u32>>, event_loop: &EventLoop<()>) -> Window {
+	/// Looks for the GPU the app would render on with its current settings, without opening a window,
+	/// and reports the features the settings ask for that it lacks.
+	pub fn check_support(&self) -> Result<SupportReport, CometError> {
+		Ok(pollster::block_on(Renderer2D::check_support(&self.renderer_settings))?)
+	}
+
+	fn create_window(app_title: &str, app_icon: &Option<Icon>, window_size: &Option<LogicalSize<u32>>, event_loop: &EventLoop<()>) -> Result<Window, CometError> {
 		let winit_window = winit::window::WindowBuilder::new()
 			.with_title(app_title);
 
@@ -160,60 +917,345 @@ impl<'a> App<'a> {
 			winit_window
 		};
 
-		winit_window.build(event_loop).unwrap()
+		Ok(winit_window.build(event_loop)?)
 	}
 
-	pub fn run<R: Renderer>(mut self, setup: fn(&mut App, &mut R), update: fn(&mut App, &mut R, f32)) {
-		info!("Starting up {}!", self.title);
+	/// Runs the fixed updates that are due when the event loop delivers `event`, carrying the leftover time in `time_stack`.
+	/// Deterministic apps step exactly once per rendered frame, on `RedrawRequested`, however many other events arrive,
+	/// so their simulation does not depend on input or on when the OS sends events.
+	pub(crate) fn advance<R: Renderer>(&mut self, event: &Event<()>, renderer: &mut R, update: fn(&mut App, &mut R, f32), time_stack: &mut f32) {
+		self.delta_time = renderer.update();
+		let update_timer = self.effective_update_timer();
+		// how far the frame is between the last two updates, where the sprites are drawn
+		let mut interpolation = 1.0;
+		if self.is_paused() {
+			*time_stack = 0.0;
+		}
+		else if update_timer != f32::INFINITY {
+			if self.deterministic {
+				let frame = matches!(event, Event::WindowEvent { event: WindowEvent::RedrawRequested, .. });
+				*time_stack = if frame && self.hit_stop.deterministic_step(update_timer) { update_timer * 1.5 } else { 0.0 };
+			} else {
+				*time_stack += self.hit_stop.scale(self.delta_time);
+			}
+			while *time_stack > update_timer {
+				self.step(renderer, update, update_timer);
+				*time_stack -= update_timer;
+			}
+			if !self.deterministic && self.interpolate {
+				interpolation = *time_stack / update_timer;
+			}
+		}
+		renderer.set_interpolation(interpolation);
+	}
 
-		pollster::block_on(async {
-			let event_loop = EventLoop::new().unwrap();
-			let window = Arc::new(Self::create_window(self.title, &self.icon, &self.size ,&event_loop));
-			let mut renderer = R::new(window.clone(), self.clear_color.clone()).await; // Pass Arc<Mutex<Window>> to renderer
-			info!("Renderer created! ({})", type_name::<R>());
+	/// Runs one fixed update of the simulation, `time` seconds long.
+	pub(crate) fn step<R: Renderer>(&mut self, renderer: &mut R, update: fn(&mut App, &mut R, f32), time: f32) {
+		profile_scope!("update");
+		let mut timings = SystemTimings::start();
+		renderer.store_previous_transforms(&self.world);
+		renderer.clear_submitted();
+		self.event_channel.drain_into(&mut self.world);
+		timings.lap("renderer");
+		self.console.update(&mut self.world);
+		self.log_overlay.update();
+		self.inspector.update(&mut self.world);
+		timings.lap("tools");
+		self.mouse_edges.begin_tick();
+		if let Some(pointer) = &mut self.pointer {
+			pointer.update(&*renderer, &self.input_manager, &self.mouse_edges, &mut self.world);
+			if let Some(drag_and_drop) = &mut self.drag_and_drop {
+				drag_and_drop.update(&*renderer, &self.input_manager, &self.mouse_edges, pointer, &mut self.world, time);
+			}
+			timings.lap("pointer");
+		}
+		if std::mem::take(&mut self.accessibility_changed) {
+			renderer.set_accessibility(&self.accessibility);
+		}
+		if let Some(recorder) = &mut self.input_recorder {
+			recorder.update(self.simulation_frame, |key| self.input_manager.key_held(key));
+		}
+		if let Some(replay) = &self.input_replay {
+			for input in replay.inputs_at(self.simulation_frame) {
+				self.simulated_input.apply(*input);
+			}
+		}
+		let (input_manager, simulated_input) = (&self.input_manager, &self.simulated_input);
+		self.hold_toggles.update(self.accessibility.hold_to_toggle(), |key| input_manager.key_held(key) || simulated_input.key_held(key));
+		if let Some(cycle) = &mut self.day_night {
+			let (dawn, dusk) = cycle.advance(time);
+			if dawn {
+				self.world.send_event(Dawn);
+			}
+			if dusk {
+				self.world.send_event(Dusk);
+			}
+			renderer.set_global_tint(cycle.tint());
+		} else if std::mem::take(&mut self.reset_tint) {
+			renderer.set_global_tint(LinearRgba::new(1.0, 1.0, 1.0, 1.0));
+		}
+		timings.lap("accessibility and tint");
+		#[cfg(feature = "hot_reload")]
+		let update = self.hot_reloaded_update(renderer, time).unwrap_or(update);
+		update(self, renderer, time);
+		timings.lap("update");
+		#[cfg(feature = "scripting")]
+		{
+			let input = comet_script::ScriptInput::capture(
+				|key| self.key_pressed(key),
+				|key| self.key_held(key),
+				|key| self.key_released(key)
+			);
+			self.scripts.update(&mut self.world, input, time);
+			timings.lap("scripts");
+		}
+		self.stats.update();
+		for unlocked in self.stats.drain_unlocked() {
+			self.world.send_event(unlocked);
+		}
+		timings.lap("stats");
+		self.tasks.update(time, &mut self.world);
+		timings.lap("tasks");
+		self.preloader.update(&mut self.world, &mut self.audio);
+		timings.lap("preloader");
+		self.audio.update_sources(&mut self.world);
+		self.audio.update(time);
+		for beat in self.audio.beats() {
+			self.world.send_event(*beat);
+		}
+		timings.lap("audio");
+		advance_flashes(&mut self.world, time);
+		advance_materials(&mut self.world, time);
+		timings.lap("sprite effects");
+		advance_ghosts(&mut self.world, time);
+		if let Some(recorder) = &mut self.ghost_recorder {
+			recorder.update(&self.world, time);
+		}
+		timings.lap("ghosts");
+		self.world.propagate_transforms_3d();
+		timings.lap("transforms");
+		self.world.despawn_pending();
+		self.world.update_events();
+		timings.lap("events");
+		if self.watchdog.is_watching() {
+			self.watchdog.check(&timings);
+		}
+		self.simulated_input.end_tick();
+		self.simulation_frame += 1;
+	}
+
+	/// Reloads the game library if it was rebuilt and hands out its update function.
+	#[cfg(feature = "hot_reload")]
+	fn hot_reloaded_update<R: Renderer>(&mut self, renderer: &mut R, time: f32) -> Option<fn(&mut App, &mut R, f32)> {
+		let game_library = self.game_library.as_mut()?;
+		if game_library.poll(time) == Reload::LayoutChanged {
+			if let Some(setup) = game_library.setup::<R>() {
+				info!("The game library changed its layout version, setting the world up again");
+				// the old world and game state may use code of the previous build, which stays loaded until the app is dropped
+				self.world = World::new(&self.world.dimension().clone());
+				self.game_state = None;
+				setup(self, renderer);
+			}
+		}
+		self.game_library.as_ref()?.update::<R>()
+	}
+
+	/// Creates the renderer for `window` and sets the game up with it.
+	async fn start<R: Renderer>(&mut self, window: &Arc<Window>, setup: fn(&mut App, &mut R), started: Instant) -> Result<R, CometError> {
+		let renderer_creation = profiler::ProfileGuard::new("renderer creation");
+		let mut renderer = R::try_new(window.clone(), self.renderer_settings.clone()).await?;
+		drop(renderer_creation);
+		info!("Renderer created! ({})", type_name::<R>());
+		if !self.launch_options.windowed() && !cfg!(any(target_os = "android", target_os = "ios")) {
 			window.set_maximized(true);
+		}
 
-			info!("Setting up!");
-			setup(&mut self, &mut renderer);
+		info!("Setting up!");
+		#[cfg(feature = "hot_reload")]
+		let setup = self.game_library.as_ref().and_then(|game_library| game_library.setup::<R>()).unwrap_or(setup);
+		{
+			profile_scope!("setup");
+			setup(self, &mut renderer);
+		}
+		if let Some(scene) = self.launch_options.scene().map(str::to_string) {
+			match self.load_game(&scene) {
+				Ok(()) => info!("Started in the scene saved in slot \"{}\"", scene),
+				Err(e) => error!("Failed to load the scene \"{}\": {}", scene, e)
+			}
+		}
+		if let Some(path) = self.launch_options.record_input() {
+			info!("Recording the input into {}", path.display());
+			self.input_recorder = Some(InputRecorder::new(path.clone()));
+		}
+		if let Some(path) = self.launch_options.replay_input() {
+			match InputRecording::load_from(path) {
+				Ok(recording) => {
+					info!("Replaying the input recorded in {}", path.display());
+					self.input_replay = Some(recording);
+				}
+				Err(e) => error!("Failed to load the input recording {}: {}", path.display(), e)
+			}
+		}
+		if self.launch_options.profile_startup() {
+			report_startup(started);
+		}
+		Ok(renderer)
+	}
+
+	/// Opens the window and runs the game until it exits. Panics if the window or the renderer cannot be set up,
+	/// see `try_run` to handle that instead.
+	pub fn run<R: Renderer>(self, setup: fn(&mut App, &mut R), update: fn(&mut App, &mut R, f32)) {
+		if let Err(e) = self.try_run(setup, update) {
+			panic!("{}", e);
+		}
+	}
+
+	/// Like `run`, but returns why the app could not start, e.g. to show a message box about an unsupported GPU.
+	pub fn try_run<R: Renderer>(mut self, setup: fn(&mut App, &mut R), update: fn(&mut App, &mut R, f32)) -> Result<(), CometError> {
+		if let Some(filters) = self.launch_options.log_level() {
+			comet_log::logger::set_filters(filters);
+		}
+		info!("Starting up {}!", self.title);
+		let started = Instant::now();
+		crash_handler::install(self.title, self.crash_report_directory.clone(), self.on_crash);
+
+		pollster::block_on(async {
+			#[cfg(target_os = "android")]
+			let event_loop = {
+				use winit::platform::android::EventLoopBuilderExtAndroid;
+				let android_app = self.android_app.clone().expect("Call App::with_android_app before running on Android");
+				winit::event_loop::EventLoopBuilder::new().with_android_app(android_app).build()?
+			};
+			#[cfg(not(target_os = "android"))]
+			let event_loop = EventLoop::new()?;
+			if self.launch_options.width().is_some() || self.launch_options.height().is_some() {
+				let size = self.size.unwrap_or(LogicalSize::new(1280, 720));
+				self.size = Some(LogicalSize::new(
+					self.launch_options.width().unwrap_or(size.width),
+					self.launch_options.height().unwrap_or(size.height)
+				));
+			}
+			if let Some(size) = self.size.filter(|size| size.width == 0 || size.height == 0) {
+				return Err(CometError::InvalidWindowSize { width: size.width, height: size.height });
+			}
+			let window = Arc::new(Self::create_window(self.title, &self.icon, &self.size ,&event_loop)?);
+			// Android only hands out the native window once the activity is resumed,
+			// so the renderer and its surface are created on the first `Resumed` there.
+			let mut renderer = if cfg!(target_os = "android") {
+				None
+			} else {
+				Some(self.start(&window, setup, started).await?)
+			};
+			let mut startup_error = None;
 
 			let mut time_stack = 0.0;
 
 			info!("Starting event loop!");
 			event_loop.run(|event, elwt| {
-				self.delta_time = renderer.update();
-
-				if self.should_quit {
-					elwt.exit()
+				if matches!(event, Event::Resumed) && renderer.is_none() {
+					match pollster::block_on(self.start(&window, setup, started)) {
+						Ok(started_renderer) => renderer = Some(started_renderer),
+						Err(e) => {
+							startup_error = Some(e);
+							elwt.exit();
+						}
+					}
 				}
+				let Some(renderer) = renderer.as_mut() else { return };
 
-				self.input_manager.update(&event);
-
-				if self.dt() != f32::INFINITY {
-					time_stack += self.delta_time;
-					while time_stack > self.update_timer {
-						let time = self.dt();
-						update(&mut self, &mut renderer, time);
-						time_stack -= self.update_timer;
+				if let Some(request) = self.exit_request.take() {
+					if self.handle_exit_request(request) {
+						elwt.exit();
 					}
 				}
 
+				// keys used by the developer tools never reach the game
+				let consumed = match &event {
+					Event::WindowEvent { event: WindowEvent::KeyboardInput { event, .. }, .. } => self.handle_tool_key(event),
+					_ => false
+				};
+				if !consumed && self.input_manager.update(&event) {
+					self.mouse_edges.latch(&self.input_manager);
+				}
+				if std::mem::take(&mut self.icon_changed) {
+					window.set_window_icon(self.icon.clone());
+				}
+				self.audio.set_paused(self.is_muted_by_focus_policy());
+
+				self.advance(&event, renderer, update, &mut time_stack);
+
 				match event {
 					Event::WindowEvent { ref event, window_id} => {
 						match event {
-							WindowEvent::CloseRequested {} => elwt.exit(),
+							WindowEvent::CloseRequested {} => {
+								if self.handle_exit_request(AppExit::new(ExitReason::CloseRequested)) {
+									elwt.exit();
+								}
+							}
 							WindowEvent::Resized(physical_size) => {
 								renderer.resize(*physical_size);
 							}
+							WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+								renderer.set_scale_factor(*scale_factor);
+							}
+							WindowEvent::Touch(touch) => {
+								let position = comet_ecs::math::Vec2::new(touch.location.x as f32, touch.location.y as f32);
+								let phase = match touch.phase {
+									winit::event::TouchPhase::Started => TouchPhase::Started,
+									winit::event::TouchPhase::Moved => TouchPhase::Moved,
+									winit::event::TouchPhase::Ended => TouchPhase::Ended,
+									winit::event::TouchPhase::Cancelled => TouchPhase::Cancelled
+								};
+								match phase {
+									TouchPhase::Started | TouchPhase::Moved => { self.touches.insert(touch.id, position); }
+									TouchPhase::Ended | TouchPhase::Cancelled => { self.touches.remove(&touch.id); }
+								}
+								self.event_channel.send(TouchInput { id: touch.id, phase, position });
+							}
+							WindowEvent::Focused(focused) => {
+								self.focused = *focused;
+								self.event_channel.send(WindowFocused(*focused));
+							}
+							WindowEvent::Occluded(occluded) => {
+								self.occluded = *occluded;
+								self.event_channel.send(WindowOccluded(*occluded));
+							}
+							WindowEvent::DroppedFile(path) => {
+								self.event_channel.send(FileDropped(path.clone()));
+							}
+							WindowEvent::HoveredFile(path) => {
+								self.event_channel.send(FileHovered(path.clone()));
+							}
+							WindowEvent::HoveredFileCancelled => {
+								self.event_channel.send(FileHoverCancelled);
+							}
 							WindowEvent::RedrawRequested => {
 								window.request_redraw();
-								match renderer.render() {
-									Ok(_) => {},
-									Err(e) => error!("Error rendering: {}", e)
+								{
+									profile_scope!("render");
+									renderer.prepare(&self.world);
+									match renderer.render() {
+										Ok(_) => {},
+										Err(e) => error!("Error rendering: {}", e)
+									}
 								}
+								profiler::new_frame();
 							}
 							_ => {}
 						}
 					}
+					Event::Suspended => {
+						renderer.suspend();
+						self.suspended = true;
+						self.event_channel.send(AppSuspended);
+					}
+					Event::Resumed => {
+						if self.suspended {
+							renderer.resume();
+							self.event_channel.send(AppResumed);
+						}
+						self.suspended = false;
+					}
 					_ => {}
 				}
 
@@ -243,8 +1285,13 @@ impl<'a> App<'a> {
 						}
 					_ => {}
 				*/
-			}).unwrap()
-		}
-		);
+			})?;
+
+			self.shutdown(renderer, window);
+			match startup_error {
+				Some(e) => Err(e),
+				None => Ok(())
+			}
+		})
 	}
 }
\ No newline at end of file
@@ -0,0 +1,37 @@
+/// Why the app is about to exit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+	/// `App::quit` was called.
+	Quit,
+	/// The window was asked to close, e.g. by clicking the close button.
+	CloseRequested
+}
+
+/// An exit request that is handed to every exit request handler before the app shuts down.
+/// Cancelling it keeps the app running, which allows things like "save before quitting?" prompts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppExit {
+	reason: ExitReason,
+	cancelled: bool
+}
+
+impl AppExit {
+	pub fn new(reason: ExitReason) -> Self {
+		Self {
+			reason,
+			cancelled: false
+		}
+	}
+
+	pub fn reason(&self) -> ExitReason {
+		self.reason
+	}
+
+	pub fn cancel(&mut self) {
+		self.cancelled = true;
+	}
+
+	pub fn is_cancelled(&self) -> bool {
+		self.cancelled
+	}
+}
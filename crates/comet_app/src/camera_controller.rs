@@ -0,0 +1,418 @@
+use comet_ecs::math::{Vec2, Vec3};
+use comet_ecs::math::quaternion::Quat;
+use comet_ecs::{Rectangle2D, Transform3D};
+use comet_input::keyboard::Key;
+use comet_input::mouse::Button;
+use comet_renderer::camera::Camera;
+use crate::App;
+
+/// How far the pitch of an `OrbitCameraController` may go by default, just short of looking straight up or down.
+const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+/// Below this speed a gliding camera stops, in pixels of the window per second.
+const MIN_GLIDE_SPEED: f32 = 1.0;
+
+/// How much of a velocity is left after `dt` seconds of gliding, with `inertia` being how long the glide lasts.
+fn glide_decay(inertia: f32, dt: f32) -> f32 {
+	if inertia > 0.0 { (-dt / inertia).exp() } else { 0.0 }
+}
+
+/// Lets the player or a tool move a 2D `Camera` around: dragging with a mouse button pans the view,
+/// the mouse wheel zooms towards the cursor and the arrow keys pan if given a speed.
+/// Call `update` every frame with the camera of the renderer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PanZoomController {
+	pan_button: Button,
+	key_speed: f32,
+	zoom_sensitivity: f32,
+	zoom_to_cursor: bool,
+	inertia: f32,
+	bounds: Option<Rectangle2D>,
+	/// The velocity of the glide after a drag, in pixels of the window per second.
+	velocity: Vec2,
+	was_dragging: bool
+}
+
+impl PanZoomController {
+	pub fn new() -> Self {
+		Self {
+			pan_button: Button::Middle,
+			key_speed: 0.0,
+			zoom_sensitivity: 0.1,
+			zoom_to_cursor: true,
+			inertia: 0.0,
+			bounds: None,
+			velocity: Vec2::ZERO,
+			was_dragging: false
+		}
+	}
+
+	/// The mouse button to drag the view with, the middle one by default.
+	pub fn with_pan_button(mut self, pan_button: Button) -> Self {
+		self.pan_button = pan_button;
+		self
+	}
+
+	/// Pans with the arrow keys at `key_speed` pixels of the window per second, whatever the zoom. 0 turns it off, which is the default.
+	pub fn with_key_speed(mut self, key_speed: f32) -> Self {
+		self.key_speed = key_speed.max(0.0);
+		self
+	}
+
+	/// How much one step of the mouse wheel zooms, 0.1 by default for 10%.
+	pub fn with_zoom_sensitivity(mut self, zoom_sensitivity: f32) -> Self {
+		self.zoom_sensitivity = zoom_sensitivity.max(0.0);
+		self
+	}
+
+	/// Whether zooming keeps the point under the cursor in place instead of the center of the view, on by default.
+	pub fn with_zoom_to_cursor(mut self, zoom_to_cursor: bool) -> Self {
+		self.zoom_to_cursor = zoom_to_cursor;
+		self
+	}
+
+	/// Lets the view keep gliding after a drag is released, slowing down to about a third every `inertia` seconds.
+	/// 0 stops it right away, which is the default.
+	pub fn with_inertia(mut self, inertia: f32) -> Self {
+		self.inertia = inertia.max(0.0);
+		self
+	}
+
+	/// Keeps the view inside `bounds` in world units. A view larger than the bounds is centered on them.
+	pub fn with_bounds(mut self, bounds: Rectangle2D) -> Self {
+		self.bounds = Some(bounds);
+		self
+	}
+
+	pub fn pan_button(&self) -> Button {
+		self.pan_button
+	}
+
+	pub fn key_speed(&self) -> f32 {
+		self.key_speed
+	}
+
+	pub fn zoom_sensitivity(&self) -> f32 {
+		self.zoom_sensitivity
+	}
+
+	pub fn inertia(&self) -> f32 {
+		self.inertia
+	}
+
+	pub fn bounds(&self) -> Option<&Rectangle2D> {
+		self.bounds.as_ref()
+	}
+
+	pub fn set_bounds(&mut self, bounds: Option<Rectangle2D>) {
+		self.bounds = bounds;
+	}
+
+	/// Whether the view is still gliding after a drag.
+	pub fn is_gliding(&self) -> bool {
+		!self.was_dragging && self.velocity.length() >= MIN_GLIDE_SPEED
+	}
+
+	/// Stops any glide, e.g. before moving the camera to a point of interest.
+	pub fn stop(&mut self) {
+		self.velocity = Vec2::ZERO;
+	}
+
+	/// Moves and zooms `camera` by the input of the last frame.
+	pub fn update(&mut self, app: &App, camera: &mut Camera, dt: f32) {
+		let input = app.input_manager();
+		let dragging = input.mouse_held(self.pan_button);
+
+		// the camera moves against the drag, so the world follows the cursor
+		let mut pixels = Vec2::ZERO;
+		if dragging {
+			let (dx, dy) = input.mouse_diff();
+			pixels = Vec2::new(-dx, dy);
+			if dt > 0.0 {
+				self.velocity = pixels * (1.0 / dt);
+			}
+		}
+		else if self.was_dragging && self.inertia <= 0.0 {
+			self.stop();
+		}
+		else if self.is_gliding() {
+			pixels = self.velocity * dt;
+			self.velocity = self.velocity * glide_decay(self.inertia, dt);
+		}
+		self.was_dragging = dragging;
+
+		if self.key_speed > 0.0 {
+			let axis = |positive: Key, negative: Key| {
+				(app.key_held(positive) as i32 - app.key_held(negative) as i32) as f32
+			};
+			let direction = Vec2::new(axis(Key::ArrowRight, Key::ArrowLeft), axis(Key::ArrowUp, Key::ArrowDown));
+			pixels += direction * (self.key_speed * dt);
+		}
+
+		if pixels.x() != 0.0 || pixels.y() != 0.0 {
+			let units = pixels * world_per_pixel(camera);
+			let (sin, cos) = camera.rotation().sin_cos();
+			let position = camera.position();
+			camera.set_position(Vec3::new(
+				position.x() + units.x() * cos - units.y() * sin,
+				position.y() + units.x() * sin + units.y() * cos,
+				position.z()
+			));
+		}
+
+		let (_, scroll) = input.scroll_diff();
+		if scroll != 0.0 && self.zoom_sensitivity > 0.0 {
+			let window = camera.dimension();
+			let cursor = input.cursor()
+				.filter(|_| self.zoom_to_cursor)
+				.map(|(x, y)| Vec2::new(x, y));
+			let before = cursor.map(|cursor| camera.screen_to_world(cursor, window));
+			camera.set_zoom(camera.zoom() * (1.0 + self.zoom_sensitivity).powf(scroll));
+			if let (Some(cursor), Some(before)) = (cursor, before) {
+				let after = camera.screen_to_world(cursor, window);
+				let position = camera.position();
+				camera.set_position(Vec3::new(
+					position.x() + before.x() - after.x(),
+					position.y() + before.y() - after.y(),
+					position.z()
+				));
+			}
+		}
+
+		if let Some(bounds) = &self.bounds {
+			clamp_to_bounds(camera, bounds);
+		}
+	}
+}
+
+impl Default for PanZoomController {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// How many world units one pixel of the window spans at the current zoom.
+fn world_per_pixel(camera: &Camera) -> f32 {
+	if camera.dimension().x() > 0.0 {
+		camera.view_size().x() / camera.dimension().x()
+	}
+	else {
+		0.0
+	}
+}
+
+fn clamp_to_bounds(camera: &mut Camera, bounds: &Rectangle2D) {
+	let view = camera.visible_world_rect();
+	let position = camera.position();
+	let clamp = |center: f32, half_view: f32, min: f32, size: f32| {
+		if half_view * 2.0 >= size {
+			min + size / 2.0
+		}
+		else {
+			center.clamp(min + half_view, min + size - half_view)
+		}
+	};
+	let (min, size) = (bounds.position(), bounds.size());
+	camera.set_position(Vec3::new(
+		clamp(position.x(), view.size().x() / 2.0, min.x(), size.x()),
+		clamp(position.y(), view.size().y() / 2.0, min.y(), size.y()),
+		position.z()
+	));
+}
+
+/// Turns a 3D camera around a target point: dragging with one mouse button orbits, with another one pans the target
+/// and the mouse wheel moves closer or further away. The orientation is kept as a quaternion, built from a yaw around
+/// the world up axis and a pitch that is limited so the camera never flips over.
+/// Call `update` every frame with the `Transform3D` of the camera entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrbitCameraController {
+	target: Vec3,
+	distance: f32,
+	yaw: f32,
+	pitch: f32,
+	orbit_button: Button,
+	pan_button: Button,
+	sensitivity: f32,
+	zoom_sensitivity: f32,
+	inertia: f32,
+	min_distance: f32,
+	max_distance: f32,
+	min_pitch: f32,
+	max_pitch: f32,
+	/// The velocity of the glide after an orbit, in pixels of mouse movement per second.
+	velocity: Vec2,
+	was_orbiting: bool
+}
+
+impl OrbitCameraController {
+	/// A camera `distance` units away from `target`, looking at it slightly from above.
+	pub fn new(target: Vec3, distance: f32) -> Self {
+		Self {
+			target,
+			distance: distance.max(f32::EPSILON),
+			yaw: 0.0,
+			pitch: 0.3,
+			orbit_button: Button::Left,
+			pan_button: Button::Middle,
+			sensitivity: 0.005,
+			zoom_sensitivity: 0.1,
+			inertia: 0.0,
+			min_distance: 0.1,
+			max_distance: f32::MAX,
+			min_pitch: -MAX_PITCH,
+			max_pitch: MAX_PITCH,
+			velocity: Vec2::ZERO,
+			was_orbiting: false
+		}
+	}
+
+	/// The angles in radians to look at the target from. A positive pitch looks down at it from above.
+	pub fn with_angles(mut self, yaw: f32, pitch: f32) -> Self {
+		self.yaw = yaw;
+		self.pitch = pitch.clamp(self.min_pitch, self.max_pitch);
+		self
+	}
+
+	/// The mouse buttons to orbit and pan with, left and middle by default.
+	pub fn with_buttons(mut self, orbit_button: Button, pan_button: Button) -> Self {
+		self.orbit_button = orbit_button;
+		self.pan_button = pan_button;
+		self
+	}
+
+	/// How many radians one pixel of mouse movement turns the camera by, 0.005 by default.
+	pub fn with_sensitivity(mut self, sensitivity: f32) -> Self {
+		self.sensitivity = sensitivity.max(0.0);
+		self
+	}
+
+	/// How much one step of the mouse wheel changes the distance, 0.1 by default for 10%.
+	pub fn with_zoom_sensitivity(mut self, zoom_sensitivity: f32) -> Self {
+		self.zoom_sensitivity = zoom_sensitivity.max(0.0);
+		self
+	}
+
+	/// Lets the camera keep turning after an orbit is released, slowing down to about a third every `inertia` seconds.
+	/// 0 stops it right away, which is the default.
+	pub fn with_inertia(mut self, inertia: f32) -> Self {
+		self.inertia = inertia.max(0.0);
+		self
+	}
+
+	pub fn with_distance_limits(mut self, min_distance: f32, max_distance: f32) -> Self {
+		self.min_distance = min_distance.max(f32::EPSILON);
+		self.max_distance = max_distance.max(self.min_distance);
+		self.distance = self.distance.clamp(self.min_distance, self.max_distance);
+		self
+	}
+
+	/// Limits the pitch in radians, e.g. `(0.0, 1.2)` to never look from below the ground. It never reaches straight up or down.
+	pub fn with_pitch_limits(mut self, min_pitch: f32, max_pitch: f32) -> Self {
+		self.min_pitch = min_pitch.clamp(-MAX_PITCH, MAX_PITCH);
+		self.max_pitch = max_pitch.clamp(self.min_pitch, MAX_PITCH);
+		self.pitch = self.pitch.clamp(self.min_pitch, self.max_pitch);
+		self
+	}
+
+	pub fn target(&self) -> Vec3 {
+		self.target
+	}
+
+	pub fn set_target(&mut self, target: Vec3) {
+		self.target = target;
+	}
+
+	pub fn distance(&self) -> f32 {
+		self.distance
+	}
+
+	pub fn set_distance(&mut self, distance: f32) {
+		self.distance = distance.clamp(self.min_distance, self.max_distance);
+	}
+
+	pub fn yaw(&self) -> f32 {
+		self.yaw
+	}
+
+	pub fn pitch(&self) -> f32 {
+		self.pitch
+	}
+
+	pub fn sensitivity(&self) -> f32 {
+		self.sensitivity
+	}
+
+	pub fn inertia(&self) -> f32 {
+		self.inertia
+	}
+
+	/// The orientation of the camera, turning its forward direction (-z) towards the target.
+	pub fn rotation(&self) -> Quat {
+		Quat::from_axis_angle(Vec3::Y, self.yaw) * Quat::from_axis_angle(Vec3::X, -self.pitch)
+	}
+
+	/// Where the camera is, `distance` units behind the target along its forward direction.
+	pub fn eye(&self) -> Vec3 {
+		self.target + self.rotation().rotate(Vec3::Z) * self.distance
+	}
+
+	/// Stops any glide, e.g. before flying the camera to another target.
+	pub fn stop(&mut self) {
+		self.velocity = Vec2::ZERO;
+	}
+
+	/// Turns the camera by mouse movement of `pixels`, right and down being positive like on screen.
+	pub fn orbit(&mut self, pixels: Vec2) {
+		self.yaw = (self.yaw - pixels.x() * self.sensitivity).rem_euclid(std::f32::consts::TAU);
+		self.pitch = (self.pitch + pixels.y() * self.sensitivity).clamp(self.min_pitch, self.max_pitch);
+	}
+
+	/// Moves the target along the view plane, so the world follows a drag of `pixels`.
+	pub fn pan(&mut self, pixels: Vec2) {
+		let rotation = self.rotation();
+		let speed = self.distance * self.sensitivity * 0.2;
+		let right = rotation.rotate(Vec3::X) * (-pixels.x() * speed);
+		let up = rotation.rotate(Vec3::Y) * (pixels.y() * speed);
+		self.target = self.target + right + up;
+	}
+
+	/// Turns and moves `transform` by the input of the last frame, placing it at `eye` facing the target.
+	pub fn update(&mut self, app: &App, transform: &mut Transform3D, dt: f32) {
+		let input = app.input_manager();
+		let (dx, dy) = input.mouse_diff();
+		let orbiting = input.mouse_held(self.orbit_button);
+
+		if orbiting {
+			let pixels = Vec2::new(dx, dy);
+			self.orbit(pixels);
+			if dt > 0.0 {
+				self.velocity = pixels * (1.0 / dt);
+			}
+		}
+		else if self.was_orbiting && self.inertia <= 0.0 {
+			self.stop();
+		}
+		else if self.velocity.length() >= MIN_GLIDE_SPEED {
+			self.orbit(self.velocity * dt);
+			self.velocity = self.velocity * glide_decay(self.inertia, dt);
+		}
+		self.was_orbiting = orbiting;
+
+		if input.mouse_held(self.pan_button) && !orbiting {
+			self.pan(Vec2::new(dx, dy));
+		}
+
+		let (_, scroll) = input.scroll_diff();
+		if scroll != 0.0 {
+			self.set_distance(self.distance / (1.0 + self.zoom_sensitivity).powf(scroll));
+		}
+
+		*transform.position_mut() = self.eye();
+		*transform.rotation_mut() = self.rotation();
+	}
+}
+
+impl Default for OrbitCameraController {
+	fn default() -> Self {
+		Self::new(Vec3::ZERO, 10.0)
+	}
+}
@@ -0,0 +1,267 @@
+use std::collections::{BTreeMap, VecDeque};
+use comet_ecs::World;
+use comet_input::keyboard::Key;
+use comet_log::history;
+use winit::event::{ElementState, KeyEvent};
+use winit::keyboard::PhysicalKey;
+
+const MAX_OUTPUT_LINES: usize = 512;
+
+/// A console command. Gets the world and the arguments after the command name
+/// and returns the text to print or an error message.
+pub type ConsoleCommand = Box<dyn FnMut(&mut World, &[&str]) -> Result<String, String>>;
+
+/// A developer console that is toggled with the backtick key. It keeps the recent log
+/// output and runs registered commands against the world, and takes all keys while open.
+/// The engine has no text rendering yet, so it is not drawn; games show `lines` themselves.
+pub struct Console {
+	enabled: bool,
+	open: bool,
+	toggle_key: Key,
+	input: String,
+	history: Vec<String>,
+	history_index: Option<usize>,
+	output: VecDeque<String>,
+	commands: BTreeMap<String, ConsoleCommand>,
+	completions: Vec<String>,
+	pending: Vec<String>,
+	log_cursor: u64
+}
+
+impl Console {
+	pub fn new() -> Self {
+		Self {
+			enabled: cfg!(debug_assertions),
+			open: false,
+			toggle_key: Key::Backquote,
+			input: String::new(),
+			history: Vec::new(),
+			history_index: None,
+			output: VecDeque::new(),
+			commands: BTreeMap::new(),
+			completions: Vec::new(),
+			pending: Vec::new(),
+			log_cursor: 0
+		}
+	}
+
+	/// The console is enabled in debug builds by default.
+	pub fn is_enabled(&self) -> bool {
+		self.enabled
+	}
+
+	pub fn set_enabled(&mut self, enabled: bool) {
+		self.enabled = enabled;
+		if !enabled {
+			self.open = false;
+		}
+	}
+
+	pub fn is_open(&self) -> bool {
+		self.open
+	}
+
+	pub fn open(&mut self) {
+		self.open = self.enabled;
+	}
+
+	pub fn close(&mut self) {
+		self.open = false;
+	}
+
+	pub fn toggle(&mut self) {
+		if self.open {
+			self.close();
+		} else {
+			self.open();
+		}
+	}
+
+	pub fn set_toggle_key(&mut self, key: Key) {
+		self.toggle_key = key;
+	}
+
+	/// Registers a command that is run when a line starting with `name` is entered.
+	pub fn register<F: FnMut(&mut World, &[&str]) -> Result<String, String> + 'static>(&mut self, name: &str, command: F) {
+		self.commands.insert(name.to_string(), Box::new(command));
+	}
+
+	pub fn commands(&self) -> Vec<&str> {
+		self.commands.keys().map(|name| name.as_str()).collect()
+	}
+
+	/// Adds a word that is offered when completing command arguments, e.g. a component name.
+	pub fn add_completion(&mut self, word: &str) {
+		if !self.completions.iter().any(|completion| completion == word) {
+			self.completions.push(word.to_string());
+		}
+	}
+
+	pub fn input(&self) -> &str {
+		&self.input
+	}
+
+	pub fn print(&mut self, line: &str) {
+		for line in line.lines() {
+			if self.output.len() >= MAX_OUTPUT_LINES {
+				self.output.pop_front();
+			}
+			self.output.push_back(line.to_string());
+		}
+	}
+
+	pub fn clear(&mut self) {
+		self.output.clear();
+	}
+
+	/// The last `count` lines of output followed by the prompt, ready to be drawn top to bottom.
+	pub fn lines(&self, count: usize) -> Vec<String> {
+		let mut lines = self.output.iter()
+			.skip(self.output.len().saturating_sub(count.saturating_sub(1)))
+			.cloned()
+			.collect::<Vec<String>>();
+		lines.push(format!("> {}", self.input));
+		lines
+	}
+
+	/// Queues a line as if it was typed in and submitted. It runs on the next update.
+	pub fn submit(&mut self, line: &str) {
+		let line = line.trim();
+		if line.is_empty() {
+			return;
+		}
+		if self.history.last().map(|last| last.as_str()) != Some(line) {
+			self.history.push(line.to_string());
+		}
+		self.history_index = None;
+		self.pending.push(line.to_string());
+	}
+
+	/// Handles a key press. Returns true if the console used the key.
+	pub fn handle_key(&mut self, event: &KeyEvent) -> bool {
+		if !self.enabled || event.state != ElementState::Pressed {
+			return false;
+		}
+
+		let PhysicalKey::Code(code) = event.physical_key else { return self.open };
+		if code == self.toggle_key {
+			if !event.repeat {
+				self.toggle();
+			}
+			return true;
+		}
+		if !self.open {
+			return false;
+		}
+
+		match code {
+			Key::Escape => self.close(),
+			Key::Enter | Key::NumpadEnter => {
+				let line = std::mem::take(&mut self.input);
+				self.submit(&line);
+			}
+			Key::Backspace => {
+				self.input.pop();
+			}
+			Key::Tab => self.autocomplete(),
+			Key::ArrowUp => self.browse_history(true),
+			Key::ArrowDown => self.browse_history(false),
+			_ => {
+				if let Some(text) = &event.text {
+					self.input.extend(text.chars().filter(|c| !c.is_control()));
+				}
+			}
+		}
+		true
+	}
+
+	/// Pulls in new log lines and runs the submitted commands.
+	pub fn update(&mut self, world: &mut World) {
+		let (lines, cursor) = history::lines_since(self.log_cursor);
+		self.log_cursor = cursor;
+		for line in lines {
+			self.print(&line);
+		}
+
+		for line in std::mem::take(&mut self.pending) {
+			self.print(&format!("> {}", line));
+			self.execute(world, &line);
+		}
+	}
+
+	fn execute(&mut self, world: &mut World, line: &str) {
+		let words = line.split_whitespace().collect::<Vec<&str>>();
+		let Some((&name, args)) = words.split_first() else { return };
+
+		match name {
+			"help" => {
+				let commands = self.commands().join(", ");
+				self.print(&format!("Commands: help, clear, {}", commands));
+			}
+			"clear" => self.clear(),
+			_ => match self.commands.get_mut(name) {
+				Some(command) => match command(world, args) {
+					Ok(output) => self.print(&output),
+					Err(e) => self.print(&format!("Error: {}", e))
+				},
+				None => self.print(&format!("Unknown command {}", name))
+			}
+		}
+	}
+
+	fn browse_history(&mut self, older: bool) {
+		if self.history.is_empty() {
+			return;
+		}
+
+		let index = match (self.history_index, older) {
+			(None, true) => Some(self.history.len() - 1),
+			(None, false) => None,
+			(Some(index), true) => Some(index.saturating_sub(1)),
+			(Some(index), false) if index + 1 < self.history.len() => Some(index + 1),
+			(Some(_), false) => None
+		};
+
+		self.history_index = index;
+		self.input = index.map(|index| self.history[index].clone()).unwrap_or_default();
+	}
+
+	fn autocomplete(&mut self) {
+		let (head, word) = match self.input.rfind(' ') {
+			Some(space) => (self.input[..=space].to_string(), self.input[space + 1..].to_string()),
+			None => (String::new(), self.input.clone())
+		};
+
+		let candidates = if head.is_empty() {
+			["help", "clear"].into_iter()
+				.chain(self.commands.keys().map(|name| name.as_str()))
+				.filter(|candidate| candidate.starts_with(&word))
+				.map(|candidate| candidate.to_string())
+				.collect::<Vec<String>>()
+		} else {
+			self.completions.iter()
+				.filter(|candidate| candidate.starts_with(&word))
+				.cloned()
+				.collect::<Vec<String>>()
+		};
+
+		match candidates.len() {
+			0 => {}
+			1 => self.input = format!("{}{} ", head, candidates[0]),
+			_ => {
+				let prefix = common_prefix(&candidates);
+				self.input = format!("{}{}", head, prefix);
+				self.print(&candidates.join("  "));
+			}
+		}
+	}
+}
+
+fn common_prefix(words: &[String]) -> String {
+	let first = &words[0];
+	let mut len = first.len();
+	for word in &words[1..] {
+		len = len.min(first.chars().zip(word.chars()).take_while(|(a, b)| a == b).map(|(a, _)| a.len_utf8()).sum());
+	}
+	first[..len].to_string()
+}
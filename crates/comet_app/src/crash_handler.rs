@@ -0,0 +1,140 @@
+use std::backtrace::Backtrace;
+use std::io::Write;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use comet_log::*;
+
+/// Everything that is known about a crash at the time the panic hook runs.
+#[derive(Debug, Clone)]
+pub struct CrashReport {
+	app_title: String,
+	timestamp: String,
+	message: String,
+	location: String,
+	thread: String,
+	backtrace: String,
+	system_info: Vec<(&'static str, String)>,
+	recent_log: Vec<String>
+}
+
+impl CrashReport {
+	fn from_panic(app_title: &str, info: &PanicHookInfo) -> Self {
+		let message = if let Some(message) = info.payload().downcast_ref::<&str>() {
+			message.to_string()
+		} else if let Some(message) = info.payload().downcast_ref::<String>() {
+			message.clone()
+		} else {
+			"Unknown panic payload".to_string()
+		};
+
+		let location = match info.location() {
+			Some(location) => format!("{}:{}:{}", location.file(), location.line(), location.column()),
+			None => "unknown location".to_string()
+		};
+
+		let thread = std::thread::current().name().unwrap_or("<unnamed>").to_string();
+		comet_log::logger::flush();
+
+		Self {
+			app_title: app_title.to_string(),
+			timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+			message,
+			location,
+			thread,
+			backtrace: Backtrace::force_capture().to_string(),
+			system_info: Self::system_info(),
+			recent_log: history::recent_lines()
+		}
+	}
+
+	fn system_info() -> Vec<(&'static str, String)> {
+		vec![
+			("os", std::env::consts::OS.to_string()),
+			("family", std::env::consts::FAMILY.to_string()),
+			("arch", std::env::consts::ARCH.to_string()),
+			("cpus", std::thread::available_parallelism().map(|n| n.get().to_string()).unwrap_or("unknown".to_string())),
+			("executable", std::env::current_exe().map(|p| p.display().to_string()).unwrap_or("unknown".to_string())),
+			("comet_app", env!("CARGO_PKG_VERSION").to_string())
+		]
+	}
+
+	pub fn app_title(&self) -> &str {
+		&self.app_title
+	}
+
+	pub fn message(&self) -> &str {
+		&self.message
+	}
+
+	pub fn location(&self) -> &str {
+		&self.location
+	}
+
+	pub fn backtrace(&self) -> &str {
+		&self.backtrace
+	}
+
+	pub fn recent_log(&self) -> &Vec<String> {
+		&self.recent_log
+	}
+
+	/// Renders the report as the plain text that is written to the report file.
+	pub fn to_text(&self) -> String {
+		let mut text = String::new();
+
+		text.push_str(&format!("{} crashed at {}\n\n", self.app_title, self.timestamp));
+		text.push_str(&format!("Panic in thread '{}' at {}:\n{}\n\n", self.thread, self.location, self.message));
+
+		text.push_str("System\n");
+		for (key, value) in &self.system_info {
+			text.push_str(&format!("  {}: {}\n", key, value));
+		}
+
+		text.push_str("\nRecent log\n");
+		for line in &self.recent_log {
+			text.push_str(&format!("  {}\n", line));
+		}
+
+		text.push_str("\nBacktrace\n");
+		text.push_str(&self.backtrace);
+		text.push('\n');
+
+		text
+	}
+
+	/// Writes the report into the given directory and returns the path of the written file.
+	pub fn write_to(&self, directory: &Path) -> std::io::Result<PathBuf> {
+		std::fs::create_dir_all(directory)?;
+		let path = directory.join(format!("crash_{}.txt", chrono::Local::now().format("%Y%m%d_%H%M%S")));
+		let mut file = std::fs::File::create(&path)?;
+		file.write_all(self.to_text().as_bytes())?;
+		Ok(path)
+	}
+}
+
+/// Replaces the default panic hook with one that logs the panic through comet_log,
+/// writes a `CrashReport` into `report_directory` and finally calls `on_crash`, if one was given.
+/// `on_crash` is the place to show a native error dialog.
+pub(crate) fn install(app_title: &str, report_directory: PathBuf, on_crash: Option<fn(&CrashReport)>) {
+	let app_title = app_title.to_string();
+
+	std::panic::set_hook(Box::new(move |info| {
+		let report = CrashReport::from_panic(&app_title, info);
+
+		error!("{} panicked at {}: {}", report.app_title(), report.location(), report.message());
+		error!("Backtrace:\n{}", report.backtrace());
+
+		match report.write_to(&report_directory) {
+			Ok(path) => error!("Crash report written to {}", path.display()),
+			Err(e) => error!("Failed to write crash report: {}", e)
+		}
+
+		// the logger writes from a background thread, so the lines above may not have reached the sinks yet
+		comet_log::logger::flush();
+		let _ = std::io::stderr().flush();
+
+		if let Some(on_crash) = on_crash {
+			on_crash(&report);
+		}
+	}));
+}
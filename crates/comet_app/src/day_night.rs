@@ -0,0 +1,113 @@
+use comet_colors::{Gradient, LinearRgba};
+
+/// Sent into the `World` when the day/night cycle passes dawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dawn;
+
+/// Sent into the `World` when the day/night cycle passes dusk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dusk;
+
+/// Tints the sprites of the world through `gradient` over the course of a day, e.g. from a dark blue
+/// at midnight over a warm orange at dawn to white at noon. The time of day runs from 0 at midnight to 1 at the next one.
+#[derive(Debug, Clone)]
+pub struct DayNightCycle {
+	gradient: Gradient,
+	day_length: f32,
+	time_of_day: f32,
+	dawn: f32,
+	dusk: f32,
+	paused: bool
+}
+
+impl DayNightCycle {
+	/// A cycle through `gradient` taking `day_length` seconds, starting at noon with dawn at 0.25 and dusk at 0.75.
+	pub fn new(gradient: Gradient, day_length: f32) -> Self {
+		Self {
+			gradient,
+			day_length: day_length.max(f32::EPSILON),
+			time_of_day: 0.5,
+			dawn: 0.25,
+			dusk: 0.75,
+			paused: false
+		}
+	}
+
+	/// The times of day the `Dawn` and `Dusk` events are sent at.
+	pub fn with_dawn_and_dusk(mut self, dawn: f32, dusk: f32) -> Self {
+		self.dawn = dawn.rem_euclid(1.0);
+		self.dusk = dusk.rem_euclid(1.0);
+		self
+	}
+
+	pub fn with_time_of_day(mut self, time_of_day: f32) -> Self {
+		self.set_time_of_day(time_of_day);
+		self
+	}
+
+	pub fn time_of_day(&self) -> f32 {
+		self.time_of_day
+	}
+
+	/// Jumps to another time of day without sending `Dawn` or `Dusk`, e.g. after sleeping.
+	pub fn set_time_of_day(&mut self, time_of_day: f32) {
+		self.time_of_day = time_of_day.rem_euclid(1.0);
+	}
+
+	/// How many seconds a whole day takes.
+	pub fn day_length(&self) -> f32 {
+		self.day_length
+	}
+
+	pub fn set_day_length(&mut self, day_length: f32) {
+		self.day_length = day_length.max(f32::EPSILON);
+	}
+
+	pub fn is_paused(&self) -> bool {
+		self.paused
+	}
+
+	/// Stops the time of day from moving on, e.g. indoors.
+	pub fn set_paused(&mut self, paused: bool) {
+		self.paused = paused;
+	}
+
+	/// Whether it is between dawn and dusk.
+	pub fn is_day(&self) -> bool {
+		if self.dawn <= self.dusk {
+			(self.dawn..self.dusk).contains(&self.time_of_day)
+		} else {
+			self.time_of_day >= self.dawn || self.time_of_day < self.dusk
+		}
+	}
+
+	/// The tint at the current time of day.
+	pub fn tint(&self) -> LinearRgba {
+		self.gradient.sample(self.time_of_day)
+	}
+
+	/// Moves the time of day on by `dt` seconds. Returns whether it passed dawn and whether it passed dusk.
+	pub(crate) fn advance(&mut self, dt: f32) -> (bool, bool) {
+		if self.paused {
+			return (false, false);
+		}
+
+		let from = self.time_of_day;
+		let passed = dt / self.day_length;
+		self.time_of_day = (from + passed).rem_euclid(1.0);
+		// a step of a whole day or more passes both of them
+		if passed >= 1.0 {
+			return (true, true);
+		}
+		(passes(from, self.time_of_day, self.dawn), passes(from, self.time_of_day, self.dusk))
+	}
+}
+
+/// Whether moving from `from` to `to`, wrapping around at midnight, crosses `mark`.
+fn passes(from: f32, to: f32, mark: f32) -> bool {
+	if from <= to {
+		from < mark && mark <= to
+	} else {
+		mark > from || mark <= to
+	}
+}
@@ -0,0 +1,194 @@
+use comet_ecs::math::{ease_out_cubic, Vec2};
+use comet_ecs::{Component, Draggable, DropTarget, Position2D, Render, Render2D, Transform2D, World};
+use comet_input::mouse::Button;
+use comet_renderer::renderer::Renderer;
+use winit_input_helper::WinitInputHelper;
+use crate::PointerInteraction;
+use crate::pointer::MouseEdges;
+
+/// How long a dropped entity takes to slide back to where it was picked up, in seconds.
+const SNAP_BACK_TIME: f32 = 0.15;
+
+/// Sent into the `World` when a `Draggable` entity is dropped on a `DropTarget` accepting it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DroppedOn {
+	pub item: u32,
+	pub target: u32
+}
+
+/// The entity being dragged right now.
+struct Drag {
+	item: u32,
+	/// The entity following the cursor, the item itself or its ghost.
+	moving: u32,
+	start: Vec2,
+	/// Where the moving entity is relative to the cursor, so it does not jump to it when picked up off center.
+	grab_offset: Vec2
+}
+
+/// An entity sliding back to where it was picked up after a drop missed.
+struct SnapBack {
+	entity: u32,
+	from: Vec2,
+	to: Vec2,
+	elapsed: f32,
+	/// Ghosts are removed once they are back.
+	despawn: bool
+}
+
+/// Picks `Draggable` entities up with the left mouse button and drops them on `DropTarget`s, sending `DroppedOn`.
+pub(crate) struct DragAndDrop {
+	drag: Option<Drag>,
+	snap_backs: Vec<SnapBack>
+}
+
+impl DragAndDrop {
+	pub(crate) fn new() -> Self {
+		Self {
+			drag: None,
+			snap_backs: Vec::new()
+		}
+	}
+
+	/// The entity being dragged, not its ghost.
+	pub(crate) fn dragged(&self) -> Option<u32> {
+		self.drag.as_ref().map(|drag| drag.item)
+	}
+
+	pub(crate) fn update<R: Renderer>(&mut self, renderer: &R, input: &WinitInputHelper, edges: &MouseEdges, pointer: &PointerInteraction, world: &mut World, dt: f32) {
+		self.advance_snap_backs(world, dt);
+
+		let Some(cursor) = input.cursor().map(|(x, y)| Vec2::new(x, y)) else {
+			return;
+		};
+		let position = renderer.screen_to_world(cursor);
+
+		match &self.drag {
+			None if edges.pressed(Button::Left) => {
+				if let Some(item) = pointer.hovered() {
+					self.pick_up(item, position, world);
+				}
+			}
+			Some(drag) if !is_alive(world, drag.moving) || !is_alive(world, drag.item) => {
+				self.drag = None;
+			}
+			Some(drag) if input.mouse_held(Button::Left) && !edges.released(Button::Left) => {
+				set_position(world, drag.moving, position + drag.grab_offset);
+			}
+			Some(_) => self.drop(renderer, cursor, world),
+			None => {}
+		}
+	}
+
+	fn pick_up(&mut self, item: u32, cursor: Vec2, world: &mut World) {
+		let Some(draggable) = world.components().typed::<Draggable>().and_then(|draggables| draggables.get(item as usize)).copied() else {
+			return;
+		};
+		if !draggable.is_enabled() || !has::<Transform2D>(world, item) {
+			return;
+		}
+		// picking an item up again while it slides back takes it from where it is now, but still sends it home on a miss
+		let sliding_home = self.snap_backs.iter()
+			.position(|snap_back| snap_back.entity == item)
+			.map(|index| self.snap_backs.remove(index).to);
+
+		let current = world.get_component::<Transform2D>(item as usize).position().as_vec();
+		let start = sliding_home.unwrap_or(current);
+		let moving = if draggable.is_ghost() && has::<Render2D>(world, item) {
+			let ghost = world.new_entity();
+			let transform = *world.get_component::<Transform2D>(item as usize);
+			let render = *world.get_component::<Render2D>(item as usize);
+			world.add_component(ghost as usize, transform);
+			world.add_component(ghost as usize, render);
+			ghost
+		}
+		else {
+			item
+		};
+
+		self.drag = Some(Drag {
+			item,
+			moving,
+			start,
+			grab_offset: current - cursor
+		});
+	}
+
+	fn drop<R: Renderer>(&mut self, renderer: &R, cursor: Vec2, world: &mut World) {
+		let Some(drag) = self.drag.take() else {
+			return;
+		};
+		let target = target_under(renderer, cursor, world, drag.moving);
+		let accepted = target.filter(|target| *target != drag.item).and_then(|target| {
+			let draggable = world.get_component::<Draggable>(drag.item as usize);
+			let drop_target = world.components().typed::<DropTarget>()?.get(target as usize)?;
+			drop_target.accepts(draggable).then_some((target, drop_target.snaps()))
+		});
+
+		let is_ghost = drag.moving != drag.item;
+		match accepted {
+			Some((target, snaps)) => {
+				world.send_event(DroppedOn { item: drag.item, target });
+				if is_ghost {
+					world.despawn_deferred(drag.moving as usize);
+				}
+				if snaps && has::<Transform2D>(world, target) {
+					let target_position = world.get_component::<Transform2D>(target as usize).position().as_vec();
+					set_position(world, drag.item, target_position);
+				}
+			}
+			None => {
+				let from = world.get_component::<Transform2D>(drag.moving as usize).position().as_vec();
+				self.snap_backs.push(SnapBack {
+					entity: drag.moving,
+					from,
+					to: drag.start,
+					elapsed: 0.0,
+					despawn: is_ghost
+				});
+			}
+		}
+	}
+
+	fn advance_snap_backs(&mut self, world: &mut World, dt: f32) {
+		self.snap_backs.retain_mut(|snap_back| {
+			if !is_alive(world, snap_back.entity) {
+				return false;
+			}
+			snap_back.elapsed += dt;
+			let t = ease_out_cubic((snap_back.elapsed / SNAP_BACK_TIME).min(1.0));
+			set_position(world, snap_back.entity, snap_back.from + (snap_back.to - snap_back.from) * t);
+			if t < 1.0 {
+				return true;
+			}
+			if snap_back.despawn {
+				world.despawn_deferred(snap_back.entity as usize);
+			}
+			false
+		});
+	}
+}
+
+/// The entity under the cursor, looking through the sprite following it.
+fn target_under<R: Renderer>(renderer: &R, cursor: Vec2, world: &mut World, moving: u32) -> Option<u32> {
+	if !has::<Render2D>(world, moving) {
+		return renderer.pick(cursor, world);
+	}
+	let was_visible = world.get_component::<Render2D>(moving as usize).is_visible();
+	world.get_component_mut::<Render2D>(moving as usize).set_visibility(false);
+	let target = renderer.pick(cursor, world);
+	world.get_component_mut::<Render2D>(moving as usize).set_visibility(was_visible);
+	target
+}
+
+fn has<T: Component>(world: &World, entity: u32) -> bool {
+	world.components().typed::<T>().is_some_and(|components| components.contains(entity as usize))
+}
+
+fn is_alive(world: &World, entity: u32) -> bool {
+	has::<Transform2D>(world, entity) && !world.is_despawning(entity as usize)
+}
+
+fn set_position(world: &mut World, entity: u32, position: Vec2) {
+	*world.get_component_mut::<Transform2D>(entity as usize).position_mut() = Position2D::from_vec(position);
+}
@@ -0,0 +1,52 @@
+use std::fmt;
+use comet_renderer::renderer::RendererError;
+
+/// Why an app could not start or keep running, see `App::try_run`.
+#[derive(Debug)]
+pub enum CometError {
+	/// The window size set with `with_size` or the launch options has no area.
+	InvalidWindowSize { width: u32, height: u32 },
+	EventLoop(winit::error::EventLoopError),
+	Window(winit::error::OsError),
+	Renderer(RendererError)
+}
+
+impl fmt::Display for CometError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			CometError::InvalidWindowSize { width, height } => write!(f, "The window cannot be {}x{} pixels large", width, height),
+			CometError::EventLoop(e) => write!(f, "The event loop failed: {}", e),
+			CometError::Window(e) => write!(f, "Failed to create the window: {}", e),
+			CometError::Renderer(e) => write!(f, "Failed to set up the renderer: {}", e)
+		}
+	}
+}
+
+impl std::error::Error for CometError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			CometError::InvalidWindowSize { .. } => None,
+			CometError::EventLoop(e) => Some(e),
+			CometError::Window(e) => Some(e),
+			CometError::Renderer(e) => Some(e)
+		}
+	}
+}
+
+impl From<winit::error::EventLoopError> for CometError {
+	fn from(e: winit::error::EventLoopError) -> Self {
+		CometError::EventLoop(e)
+	}
+}
+
+impl From<winit::error::OsError> for CometError {
+	fn from(e: winit::error::OsError) -> Self {
+		CometError::Window(e)
+	}
+}
+
+impl From<RendererError> for CometError {
+	fn from(e: RendererError) -> Self {
+		CometError::Renderer(e)
+	}
+}
@@ -0,0 +1,538 @@
+use std::f32::consts::{PI, TAU};
+use std::path::Path;
+use std::sync::Arc;
+use comet_ecs::math::quaternion::Quat;
+use comet_ecs::math::{Vec2, Vec3};
+use comet_ecs::{Component, Position2D, Transform2D, Transform3D, World};
+use comet_log::*;
+use comet_save::{SaveData, SaveError, SaveFile, SaveReader, SaveWriter};
+
+const GHOST_SECTION: &str = "ghost";
+
+/// Sent into the `World` when a `GhostPlayback` that does not loop reaches the end of its track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GhostFinished {
+	pub entity: u32
+}
+
+/// Which transform a track recorded, and with it how many floats a sample takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GhostKind {
+	/// Position and angle, 3 floats.
+	#[default]
+	Transform2D,
+	/// Position, rotation and scale, 10 floats.
+	Transform3D
+}
+
+impl GhostKind {
+	fn stride(&self) -> usize {
+		match self {
+			GhostKind::Transform2D => 3,
+			GhostKind::Transform3D => 10
+		}
+	}
+}
+
+/// The transform of a recorded entity at one point in time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GhostPose {
+	Transform2D {
+		position: Vec2,
+		angle: f32
+	},
+	Transform3D {
+		position: Vec3,
+		rotation: Quat,
+		scale: Vec3
+	}
+}
+
+impl GhostPose {
+	fn read(kind: GhostKind, sample: &[f32]) -> Self {
+		match kind {
+			GhostKind::Transform2D => GhostPose::Transform2D {
+				position: Vec2::new(sample[0], sample[1]),
+				angle: sample[2]
+			},
+			GhostKind::Transform3D => GhostPose::Transform3D {
+				position: Vec3::new(sample[0], sample[1], sample[2]),
+				rotation: Quat::new(sample[3], Vec3::new(sample[4], sample[5], sample[6])),
+				scale: Vec3::new(sample[7], sample[8], sample[9])
+			}
+		}
+	}
+
+	fn lerp(&self, other: &Self, t: f32) -> Self {
+		match (*self, *other) {
+			(GhostPose::Transform2D { position: a, angle: from }, GhostPose::Transform2D { position: b, angle: to }) => {
+				// the short way around, so an angle wrapping from PI to -PI does not spin all the way back
+				let difference = (to - from + PI).rem_euclid(TAU) - PI;
+				GhostPose::Transform2D { position: a * (1.0 - t) + b * t, angle: from + difference * t }
+			}
+			(GhostPose::Transform3D { position: a, rotation: from, scale: scale_a }, GhostPose::Transform3D { position: b, rotation: to, scale: scale_b }) => {
+				GhostPose::Transform3D { position: a * (1.0 - t) + b * t, rotation: from.slerp(&to, t), scale: scale_a * (1.0 - t) + scale_b * t }
+			}
+			_ => *self
+		}
+	}
+
+	/// Moves the entity to the pose, if it has the transform the pose is of.
+	pub fn apply(&self, world: &mut World, entity: u32) {
+		match self {
+			GhostPose::Transform2D { position, angle } => {
+				if !has::<Transform2D>(world, entity) {
+					return;
+				}
+				let transform = world.get_component_mut::<Transform2D>(entity as usize);
+				*transform.position_mut() = Position2D::from_vec(*position);
+				transform.rotation_mut().set_angle(*angle);
+			}
+			GhostPose::Transform3D { position, rotation, scale } => {
+				if !has::<Transform3D>(world, entity) {
+					return;
+				}
+				let transform = world.get_component_mut::<Transform3D>(entity as usize);
+				*transform.position_mut() = *position;
+				*transform.rotation_mut() = *rotation;
+				*transform.scale_mut() = *scale;
+			}
+		}
+	}
+}
+
+/// The transforms of one entity, sampled every `interval` seconds from `start` on and stored back to back in one buffer.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GhostTrack {
+	entity: u32,
+	kind: GhostKind,
+	start: f32,
+	interval: f32,
+	samples: Vec<f32>
+}
+
+impl GhostTrack {
+	/// The entity the track was recorded from, which may not exist anymore when it is played back.
+	pub fn entity(&self) -> u32 {
+		self.entity
+	}
+
+	pub fn kind(&self) -> GhostKind {
+		self.kind
+	}
+
+	/// When the first sample was taken, in seconds since the recording started.
+	pub fn start(&self) -> f32 {
+		self.start
+	}
+
+	/// When the last sample was taken, in seconds since the recording started.
+	pub fn end(&self) -> f32 {
+		self.start + self.duration()
+	}
+
+	pub fn duration(&self) -> f32 {
+		self.len().saturating_sub(1) as f32 * self.interval
+	}
+
+	pub fn interval(&self) -> f32 {
+		self.interval
+	}
+
+	/// How many samples there are.
+	pub fn len(&self) -> usize {
+		self.samples.len() / self.kind.stride()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.samples.is_empty()
+	}
+
+	/// The pose at `time` seconds since the recording started, interpolated between the samples around it.
+	/// Before the start and after the end the first and last sample are held.
+	pub fn pose_at(&self, time: f32) -> Option<GhostPose> {
+		let last = self.len().checked_sub(1)?;
+		let position = ((time - self.start) / self.interval).clamp(0.0, last as f32);
+		let index = (position.floor() as usize).min(last);
+		let from = self.pose(index);
+		if index == last {
+			return Some(from);
+		}
+		Some(from.lerp(&self.pose(index + 1), position - index as f32))
+	}
+
+	fn pose(&self, index: usize) -> GhostPose {
+		let stride = self.kind.stride();
+		GhostPose::read(self.kind, &self.samples[index * stride..(index + 1) * stride])
+	}
+
+	fn push(&mut self, pose: GhostPose) {
+		match pose {
+			GhostPose::Transform2D { position, angle } => self.samples.extend_from_slice(&[position.x(), position.y(), angle]),
+			GhostPose::Transform3D { position, rotation, scale } => self.samples.extend_from_slice(&[
+				position.x(), position.y(), position.z(),
+				rotation.s, rotation.v.x(), rotation.v.y(), rotation.v.z(),
+				scale.x(), scale.y(), scale.z()
+			])
+		}
+	}
+
+	/// Drops the oldest samples until at most `count` are left.
+	fn keep_last(&mut self, count: usize) {
+		let excess = self.len().saturating_sub(count);
+		if excess > 0 {
+			self.samples.drain(..excess * self.kind.stride());
+			self.start += excess as f32 * self.interval;
+		}
+	}
+}
+
+impl SaveData for GhostTrack {
+	fn write(&self, writer: &mut SaveWriter) {
+		writer.write(&self.entity);
+		writer.write(&(self.kind as u8));
+		writer.write(&self.start);
+		writer.write(&self.interval);
+		writer.write(&self.samples);
+	}
+
+	fn read(reader: &mut SaveReader) -> Result<Self, SaveError> {
+		let entity = reader.read()?;
+		let kind = match reader.read::<u8>()? {
+			0 => GhostKind::Transform2D,
+			1 => GhostKind::Transform3D,
+			kind => return Err(SaveError::Corrupted(format!("{} is not a kind of ghost track", kind)))
+		};
+		let track = Self {
+			entity,
+			kind,
+			start: reader.read()?,
+			interval: reader.read()?,
+			samples: reader.read()?
+		};
+		if !track.samples.len().is_multiple_of(kind.stride()) || track.interval <= 0.0 {
+			return Err(SaveError::Corrupted(format!("The ghost track of entity {} is malformed", entity)));
+		}
+		Ok(track)
+	}
+}
+
+/// The tracks of every entity a `GhostRecorder` recorded.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GhostRecording {
+	tracks: Vec<Arc<GhostTrack>>
+}
+
+impl GhostRecording {
+	pub fn tracks(&self) -> &[Arc<GhostTrack>] {
+		&self.tracks
+	}
+
+	/// The track recorded from `entity`.
+	pub fn track(&self, entity: u32) -> Option<&Arc<GhostTrack>> {
+		self.tracks.iter().find(|track| track.entity == entity)
+	}
+
+	/// How long the recording is, from the first sample of any track to the last.
+	pub fn duration(&self) -> f32 {
+		let start = self.tracks.iter().map(|track| track.start).fold(f32::INFINITY, f32::min);
+		let end = self.tracks.iter().map(|track| track.end()).fold(f32::NEG_INFINITY, f32::max);
+		(end - start).max(0.0)
+	}
+
+	/// A `GhostPlayback` of the track recorded from `entity`, to add to the entity that should replay it.
+	pub fn playback(&self, entity: u32) -> Option<GhostPlayback> {
+		self.track(entity).map(|track| GhostPlayback::new(track.clone()))
+	}
+
+	/// Writes the recording into its own save file at `path`, e.g. the best lap of a track.
+	pub fn save_to(&self, path: &Path) -> Result<(), SaveError> {
+		let mut save_file = SaveFile::new(1);
+		save_file.set(GHOST_SECTION, self);
+		save_file.write_to(path, true)
+	}
+
+	pub fn load_from(path: &Path) -> Result<Self, SaveError> {
+		SaveFile::read_from(path)?.get(GHOST_SECTION)
+			.ok_or_else(|| SaveError::Corrupted("The file has no ghost recording".to_string()))?
+	}
+}
+
+impl SaveData for GhostRecording {
+	fn write(&self, writer: &mut SaveWriter) {
+		writer.write(&(self.tracks.len() as u32));
+		for track in &self.tracks {
+			track.write(writer);
+		}
+	}
+
+	fn read(reader: &mut SaveReader) -> Result<Self, SaveError> {
+		let tracks: Vec<GhostTrack> = reader.read()?;
+		Ok(Self {
+			tracks: tracks.into_iter().map(Arc::new).collect()
+		})
+	}
+}
+
+/// Samples the transforms of selected entities at a fixed rate, for racing game ghosts, kill-cams and replays.
+/// Started with `App::start_ghost_recording`, which samples after every update until `App::stop_ghost_recording`.
+pub struct GhostRecorder {
+	interval: f32,
+	max_duration: Option<f32>,
+	time: f32,
+	next_sample: f32,
+	entities: Vec<u32>,
+	tracks: Vec<GhostTrack>
+}
+
+impl GhostRecorder {
+	/// A recorder taking `tick_rate` samples per second.
+	pub fn new(tick_rate: f32) -> Self {
+		Self {
+			interval: 1.0 / tick_rate.max(f32::EPSILON),
+			max_duration: None,
+			time: 0.0,
+			next_sample: 0.0,
+			entities: Vec::new(),
+			tracks: Vec::new()
+		}
+	}
+
+	pub fn with_entity(mut self, entity: u32) -> Self {
+		self.record(entity);
+		self
+	}
+
+	/// Only keeps the last `seconds` of every track, e.g. the moments before a death for a kill-cam.
+	pub fn with_max_duration(mut self, seconds: f32) -> Self {
+		self.max_duration = Some(seconds);
+		self
+	}
+
+	/// Starts recording `entity` from the next sample on.
+	pub fn record(&mut self, entity: u32) {
+		if !self.entities.contains(&entity) {
+			self.entities.push(entity);
+		}
+	}
+
+	/// Stops recording `entity`, keeping what was recorded of it.
+	pub fn forget(&mut self, entity: u32) {
+		self.entities.retain(|recorded| *recorded != entity);
+	}
+
+	pub fn is_recording(&self, entity: u32) -> bool {
+		self.entities.contains(&entity)
+	}
+
+	/// Seconds since the recording started.
+	pub fn time(&self) -> f32 {
+		self.time
+	}
+
+	pub fn tick_rate(&self) -> f32 {
+		1.0 / self.interval
+	}
+
+	/// Takes every sample that is due and advances the recording by `dt` seconds. Entities that lost their
+	/// transform are no longer recorded.
+	pub fn update(&mut self, world: &World, dt: f32) {
+		// a little slack, so an update rate matching the tick rate does not skip samples to rounding
+		while self.next_sample <= self.time + self.interval * 0.001 {
+			let tick = self.next_sample;
+			self.entities.retain(|&entity| {
+				let Some(pose) = current_pose(world, entity) else {
+					info!("Entity {} has no transform anymore, the ghost recording of it ends", entity);
+					return false;
+				};
+				let kind = match pose {
+					GhostPose::Transform2D { .. } => GhostKind::Transform2D,
+					GhostPose::Transform3D { .. } => GhostKind::Transform3D
+				};
+				let index = match self.tracks.iter().position(|track| track.entity == entity && track.kind == kind) {
+					Some(index) => index,
+					None => {
+						self.tracks.push(GhostTrack { entity, kind, start: tick, interval: self.interval, samples: Vec::new() });
+						self.tracks.len() - 1
+					}
+				};
+				self.tracks[index].push(pose);
+				true
+			});
+			if let Some(max_duration) = self.max_duration {
+				let count = (max_duration / self.interval).ceil() as usize + 1;
+				for track in &mut self.tracks {
+					track.keep_last(count);
+				}
+			}
+			self.next_sample += self.interval;
+		}
+		self.time += dt;
+	}
+
+	/// What was recorded so far, while the recording goes on.
+	pub fn snapshot(&self) -> GhostRecording {
+		GhostRecording {
+			tracks: self.tracks.iter().cloned().map(Arc::new).collect()
+		}
+	}
+
+	pub fn finish(self) -> GhostRecording {
+		GhostRecording {
+			tracks: self.tracks.into_iter().map(Arc::new).collect()
+		}
+	}
+}
+
+/// Replays a `GhostTrack` on the transform of its entity, interpolating between the samples.
+/// Tracks of 2D transforms need a `Transform2D` on the entity, tracks of 3D transforms a `Transform3D`.
+#[derive(Component)]
+#[derive(Clone)]
+pub struct GhostPlayback {
+	track: Arc<GhostTrack>,
+	time: f32,
+	#[component(default = 1.0)]
+	speed: f32,
+	looping: bool,
+	#[component(default = true)]
+	playing: bool
+}
+
+impl GhostPlayback {
+	/// Plays `track` from its start.
+	pub fn new(track: Arc<GhostTrack>) -> Self {
+		Self {
+			time: track.start(),
+			track,
+			speed: 1.0,
+			looping: false,
+			playing: true
+		}
+	}
+
+	/// Plays the track `speed` times as fast, e.g. 0.25 for a slow motion kill-cam.
+	pub fn with_speed(mut self, speed: f32) -> Self {
+		self.speed = speed;
+		self
+	}
+
+	/// Starts over at the start of the track instead of stopping at its end.
+	pub fn with_looping(mut self, looping: bool) -> Self {
+		self.looping = looping;
+		self
+	}
+
+	pub fn track(&self) -> &Arc<GhostTrack> {
+		&self.track
+	}
+
+	/// The time in the recording the playback is at, in seconds since the recording started.
+	pub fn time(&self) -> f32 {
+		self.time
+	}
+
+	/// Jumps to `time` seconds since the recording started.
+	pub fn seek(&mut self, time: f32) {
+		self.time = time.clamp(self.track.start(), self.track.end());
+	}
+
+	pub fn speed(&self) -> f32 {
+		self.speed
+	}
+
+	pub fn set_speed(&mut self, speed: f32) {
+		self.speed = speed;
+	}
+
+	pub fn is_looping(&self) -> bool {
+		self.looping
+	}
+
+	pub fn set_looping(&mut self, looping: bool) {
+		self.looping = looping;
+	}
+
+	pub fn is_playing(&self) -> bool {
+		self.playing
+	}
+
+	pub fn play(&mut self) {
+		if self.is_finished() {
+			self.time = self.track.start();
+		}
+		self.playing = true;
+	}
+
+	pub fn pause(&mut self) {
+		self.playing = false;
+	}
+
+	/// Whether the playback stopped at the end of the track.
+	pub fn is_finished(&self) -> bool {
+		!self.looping && self.time >= self.track.end()
+	}
+
+	/// The pose the entity is in right now.
+	pub fn pose(&self) -> Option<GhostPose> {
+		self.track.pose_at(self.time)
+	}
+
+	/// Advances the playback, returning whether it just reached the end.
+	fn advance(&mut self, dt: f32) -> bool {
+		if !self.playing || self.track.is_empty() {
+			return false;
+		}
+		self.time += dt * self.speed;
+		let (start, end) = (self.track.start(), self.track.end());
+		if self.time < end {
+			self.time = self.time.max(start);
+			return false;
+		}
+		if self.looping && end > start {
+			self.time = start + (self.time - start) % (end - start);
+			return false;
+		}
+		self.time = end;
+		self.playing = false;
+		true
+	}
+}
+
+/// Moves every entity with a `GhostPlayback` along its track.
+pub(crate) fn advance_ghosts(world: &mut World, dt: f32) {
+	let Some(playbacks) = world.components_mut().typed_mut::<GhostPlayback>() else {
+		return;
+	};
+	let mut poses = Vec::new();
+	let mut finished = Vec::new();
+	for (entity, playback) in playbacks.iter_mut() {
+		if playback.advance(dt) {
+			finished.push(entity as u32);
+		}
+		if let Some(pose) = playback.pose() {
+			poses.push((entity as u32, pose));
+		}
+	}
+	for (entity, pose) in poses {
+		pose.apply(world, entity);
+	}
+	for entity in finished {
+		world.send_event(GhostFinished { entity });
+	}
+}
+
+fn current_pose(world: &World, entity: u32) -> Option<GhostPose> {
+	if has::<Transform2D>(world, entity) {
+		let transform = world.get_component::<Transform2D>(entity as usize);
+		return Some(GhostPose::Transform2D { position: transform.position().as_vec(), angle: transform.rotation().angle() });
+	}
+	if has::<Transform3D>(world, entity) {
+		let transform = world.get_component::<Transform3D>(entity as usize);
+		return Some(GhostPose::Transform3D { position: *transform.position(), rotation: *transform.rotation(), scale: *transform.scale() });
+	}
+	None
+}
+
+fn has<T: Component>(world: &World, entity: u32) -> bool {
+	world.components().typed::<T>().is_some_and(|components| components.contains(entity as usize)) && !world.is_despawning(entity as usize)
+}
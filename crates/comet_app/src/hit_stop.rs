@@ -0,0 +1,105 @@
+use comet_ecs::{FlashOnHit, SpriteMaterial, World};
+
+/// Slows the simulation down for a moment, freezing it by default, so a hit lands with weight.
+/// Only the fixed updates are affected, rendering keeps running.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HitStop {
+	duration: f32,
+	time_scale: f32
+}
+
+impl HitStop {
+	/// Freezes the updates for `duration` seconds of real time.
+	pub fn new(duration: f32) -> Self {
+		Self {
+			duration,
+			time_scale: 0.0
+		}
+	}
+
+	/// Lets time run at `time_scale` instead of stopping it, e.g. 0.25 for a short slow motion.
+	pub fn with_time_scale(mut self, time_scale: f32) -> Self {
+		self.time_scale = time_scale.max(0.0);
+		self
+	}
+
+	pub fn duration(&self) -> f32 {
+		self.duration
+	}
+
+	pub fn time_scale(&self) -> f32 {
+		self.time_scale
+	}
+}
+
+/// The hit stop currently running in an `App`.
+pub(crate) struct HitStopTimer {
+	remaining: f32,
+	time_scale: f32,
+	carry: f32
+}
+
+impl HitStopTimer {
+	pub fn new() -> Self {
+		Self {
+			remaining: 0.0,
+			time_scale: 1.0,
+			carry: 0.0
+		}
+	}
+
+	/// Starts `hit_stop` unless a longer one is already running.
+	pub fn start(&mut self, hit_stop: HitStop) {
+		if hit_stop.duration >= self.remaining {
+			self.remaining = hit_stop.duration;
+			self.time_scale = hit_stop.time_scale;
+			self.carry = 0.0;
+		}
+	}
+
+	pub fn is_active(&self) -> bool {
+		self.remaining > 0.0
+	}
+
+	/// The simulation time that passes during a frame of `frame_time` seconds.
+	pub fn scale(&mut self, frame_time: f32) -> f32 {
+		if !self.is_active() {
+			return frame_time;
+		}
+		self.remaining -= frame_time;
+		frame_time * self.time_scale
+	}
+
+	/// Whether a deterministic frame runs its update. While active only every `1/time_scale`th frame does,
+	/// and the hit stop counts down in frames of `update_timer` so runs stay reproducible.
+	pub fn deterministic_step(&mut self, update_timer: f32) -> bool {
+		if !self.is_active() {
+			return true;
+		}
+		self.remaining -= update_timer;
+		self.carry += self.time_scale;
+		if self.carry >= 1.0 {
+			self.carry -= 1.0;
+			return true;
+		}
+		false
+	}
+}
+
+/// Fades out the running `FlashOnHit`s.
+pub(crate) fn advance_flashes(world: &mut World, dt: f32) {
+	if let Some(flashes) = world.components_mut().typed_mut::<FlashOnHit>() {
+		for (_, flash) in flashes.iter_mut() {
+			flash.advance(dt);
+		}
+	}
+}
+
+/// Runs the tweens of the `SpriteMaterial`s, e.g. a dissolve when an entity dies.
+pub(crate) fn advance_materials(world: &mut World, dt: f32) {
+	if let Some(materials) = world.components_mut().typed_mut::<SpriteMaterial>() {
+		for (_, material) in materials.iter_mut() {
+			material.advance(dt);
+		}
+	}
+}
@@ -0,0 +1,181 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use libloading::Library;
+use comet_log::*;
+use crate::App;
+
+const SETUP_SYMBOL: &[u8] = b"comet_setup";
+const UPDATE_SYMBOL: &[u8] = b"comet_update";
+const LAYOUT_VERSION_SYMBOL: &[u8] = b"comet_layout_version";
+/// How many seconds pass between looking for a newer build of the library.
+const CHECK_INTERVAL: f32 = 0.5;
+
+/// What happened when the game library was looked at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Reload {
+	Unchanged,
+	/// A new build was loaded that works with the same components as the last one.
+	Reloaded,
+	/// A build was loaded that needs the world set up from scratch, as its `comet_layout_version` differs
+	/// or no build could be loaded before.
+	LayoutChanged
+}
+
+/// The library holding the setup and update functions of a game while it is hot reloaded.
+/// It is loaded from a copy, so the build can overwrite the original while the game runs.
+/// Older builds are never unloaded while the app runs: components in the world drop through functions in the build
+/// that registered them and the game state keeps its vtables there, so unloading one would leave them pointing
+/// into unmapped code.
+pub(crate) struct GameLibrary {
+	path: PathBuf,
+	library: Option<(Library, PathBuf)>,
+	/// Every build loaded before the current one, kept until the app is dropped.
+	retired: Vec<(Library, PathBuf)>,
+	modified: Option<SystemTime>,
+	failed: Option<SystemTime>,
+	layout_version: u32,
+	generation: u32,
+	check_timer: f32
+}
+
+impl GameLibrary {
+	pub fn new(path: &str) -> Self {
+		let mut game_library = Self {
+			path: PathBuf::from(path),
+			library: None,
+			retired: Vec::new(),
+			modified: None,
+			failed: None,
+			layout_version: 0,
+			generation: 0,
+			check_timer: 0.0
+		};
+		game_library.load();
+		game_library
+	}
+
+	/// Loads the library again if it was rebuilt, checking at most every `CHECK_INTERVAL` seconds.
+	pub fn poll(&mut self, dt: f32) -> Reload {
+		self.check_timer += dt;
+		if self.check_timer < CHECK_INTERVAL {
+			return Reload::Unchanged;
+		}
+		self.check_timer = 0.0;
+
+		let modified = modified_time(&self.path);
+		if modified.is_none() || modified == self.modified || modified == self.failed {
+			return Reload::Unchanged;
+		}
+		self.load()
+	}
+
+	fn load(&mut self) -> Reload {
+		let modified = modified_time(&self.path);
+		self.generation += 1;
+		let copy = copy_path(&self.path, self.generation);
+		let library = std::fs::create_dir_all(copy.parent().unwrap_or(Path::new(".")))
+			.and_then(|_| std::fs::copy(&self.path, &copy))
+			.map_err(|e| e.to_string())
+			// the library runs code on load, which is what the game asked for by hot reloading it
+			.and_then(|_| unsafe { Library::new(&copy) }.map_err(|e| e.to_string()));
+
+		let library = match library {
+			Ok(library) => library,
+			Err(e) => {
+				error!("Failed to load the game library {}: {}", self.path.display(), e);
+				let _ = std::fs::remove_file(&copy);
+				self.failed = modified;
+				return Reload::Unchanged;
+			}
+		};
+
+		let layout_version = unsafe { library.get::<fn() -> u32>(LAYOUT_VERSION_SYMBOL) }.map_or(0, |layout_version| layout_version());
+		let first = self.library.is_none();
+		if let Some(old) = self.library.replace((library, copy)) {
+			self.retired.push(old);
+		}
+		self.modified = modified;
+		self.failed = None;
+		info!("Loaded the game library {}", self.path.display());
+
+		let changed = layout_version != self.layout_version;
+		self.layout_version = layout_version;
+		if first || changed {
+			Reload::LayoutChanged
+		} else {
+			Reload::Reloaded
+		}
+	}
+
+	/// The `comet_setup` function of the library, if it is loaded and exports one.
+	pub fn setup<R>(&self) -> Option<fn(&mut App, &mut R)> {
+		let (library, _) = self.library.as_ref()?;
+		// the library promises the signature by exporting the symbol, see `export_game!`
+		unsafe { library.get::<fn(&mut App, &mut R)>(SETUP_SYMBOL) }.ok().map(|setup| *setup)
+	}
+
+	/// The `comet_update` function of the library, if it is loaded and exports one.
+	pub fn update<R>(&self) -> Option<fn(&mut App, &mut R, f32)> {
+		let (library, _) = self.library.as_ref()?;
+		unsafe { library.get::<fn(&mut App, &mut R, f32)>(UPDATE_SYMBOL) }.ok().map(|update| *update)
+	}
+}
+
+impl Drop for GameLibrary {
+	fn drop(&mut self) {
+		// the app drops its world and game state before this, see the field order of `App`
+		for (library, copy) in self.library.take().into_iter().chain(self.retired.drain(..)) {
+			drop(library);
+			let _ = std::fs::remove_file(copy);
+		}
+	}
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+	std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Where the copy of a build is loaded from, numbered so no two builds share a path.
+fn copy_path(path: &Path, generation: u32) -> PathBuf {
+	let stem = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+	let name = match path.extension() {
+		Some(extension) => format!("{}-{}-{}.{}", stem, std::process::id(), generation, extension.to_string_lossy()),
+		None => format!("{}-{}-{}", stem, std::process::id(), generation)
+	};
+	std::env::temp_dir().join("comet_hot_reload").join(name)
+}
+
+/// Exports the setup and update functions of a game from a `cdylib` for `App::with_hot_reload`.
+/// The library has to be built with the same compiler and the same comet version and features as the game.
+/// Bump the optional layout version whenever components change, so the world is set up from scratch
+/// instead of being kept across the reload.
+///
+/// `TypeId`s are not stable across rebuilds, so a reload with the same layout version only keeps working with the
+/// components of the engine. A game registering its own components or game state has to bump the layout version on
+/// every build it hot reloads, or the world no longer finds them.
+///
+/// ```ignore
+/// comet::app::export_game!(Renderer2D, setup, update, 1);
+/// ```
+#[macro_export]
+macro_rules! export_game {
+	($renderer:ty, $setup:path, $update:path) => {
+		$crate::export_game!($renderer, $setup, $update, 0);
+	};
+	($renderer:ty, $setup:path, $update:path, $layout_version:expr) => {
+		#[no_mangle]
+		pub fn comet_setup(app: &mut $crate::App, renderer: &mut $renderer) {
+			$setup(app, renderer)
+		}
+
+		#[no_mangle]
+		pub fn comet_update(app: &mut $crate::App, renderer: &mut $renderer, dt: f32) {
+			$update(app, renderer, dt)
+		}
+
+		#[no_mangle]
+		pub fn comet_layout_version() -> u32 {
+			$layout_version
+		}
+	};
+}
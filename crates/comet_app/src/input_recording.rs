@@ -0,0 +1,194 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use comet_input::keyboard::{key_from_name, key_name, Key, KEYS};
+use comet_save::{SaveData, SaveError, SaveFile, SaveReader, SaveWriter};
+
+const INPUT_SECTION: &str = "input";
+
+/// A key going down or up at the start of a tick.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordedInput {
+	Press(Key),
+	Release(Key)
+}
+
+/// The keys pressed and released on each tick of a run. Recorded with `--record-input <file>` and replayed
+/// with `--replay-input <file>` or in a `TestApp` with `TestApp::with_recording`.
+/// Only deterministic apps are guaranteed to play out the same way again.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct InputRecording {
+	inputs: BTreeMap<u64, Vec<RecordedInput>>
+}
+
+impl InputRecording {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Presses `key` at the start of tick `tick`.
+	pub fn press(&mut self, tick: u64, key: Key) {
+		self.push(tick, RecordedInput::Press(key));
+	}
+
+	/// Releases `key` at the start of tick `tick`.
+	pub fn release(&mut self, tick: u64, key: Key) {
+		self.push(tick, RecordedInput::Release(key));
+	}
+
+	pub fn push(&mut self, tick: u64, input: RecordedInput) {
+		self.inputs.entry(tick).or_default().push(input);
+	}
+
+	/// The inputs at the start of tick `tick`, in the order they happened.
+	pub fn inputs_at(&self, tick: u64) -> &[RecordedInput] {
+		self.inputs.get(&tick).map_or(&[], Vec::as_slice)
+	}
+
+	/// Every input with its tick, in order.
+	pub fn iter(&self) -> impl Iterator<Item = (u64, RecordedInput)> + '_ {
+		self.inputs.iter().flat_map(|(tick, inputs)| inputs.iter().map(move |input| (*tick, *input)))
+	}
+
+	/// The tick of the last input.
+	pub fn last_tick(&self) -> Option<u64> {
+		self.inputs.keys().next_back().copied()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.inputs.is_empty()
+	}
+
+	pub fn save_to(&self, path: &Path) -> Result<(), SaveError> {
+		let mut save_file = SaveFile::new(1);
+		save_file.set(INPUT_SECTION, self);
+		save_file.write_to(path, true)
+	}
+
+	pub fn load_from(path: &Path) -> Result<Self, SaveError> {
+		SaveFile::read_from(path)?.get(INPUT_SECTION)
+			.ok_or_else(|| SaveError::Corrupted("The file has no input recording".to_string()))?
+	}
+}
+
+impl SaveData for InputRecording {
+	fn write(&self, writer: &mut SaveWriter) {
+		writer.write(&(self.iter().count() as u32));
+		for (tick, input) in self.iter() {
+			let (pressed, key) = match input {
+				RecordedInput::Press(key) => (true, key),
+				RecordedInput::Release(key) => (false, key)
+			};
+			writer.write(&tick);
+			writer.write(&pressed);
+			writer.write(&key_name(key));
+		}
+	}
+
+	fn read(reader: &mut SaveReader) -> Result<Self, SaveError> {
+		let mut recording = Self::new();
+		for _ in 0..reader.read::<u32>()? {
+			let tick = reader.read()?;
+			let pressed = reader.read()?;
+			let name: String = reader.read()?;
+			let key = key_from_name(&name)
+				.ok_or_else(|| SaveError::Corrupted(format!("\"{}\" is not a key", name)))?;
+			recording.push(tick, if pressed { RecordedInput::Press(key) } else { RecordedInput::Release(key) });
+		}
+		Ok(recording)
+	}
+}
+
+/// Writes down which keys of the keyboard go down and up on every tick, for `--record-input`.
+pub(crate) struct InputRecorder {
+	path: PathBuf,
+	held: HashSet<Key>,
+	recording: InputRecording
+}
+
+impl InputRecorder {
+	pub fn new(path: PathBuf) -> Self {
+		Self {
+			path,
+			held: HashSet::new(),
+			recording: InputRecording::new()
+		}
+	}
+
+	/// Compares the keys held in tick `tick` with the ones held in the tick before.
+	pub fn update(&mut self, tick: u64, is_held: impl Fn(Key) -> bool) {
+		for &key in KEYS {
+			if is_held(key) {
+				if self.held.insert(key) {
+					self.recording.press(tick, key);
+				}
+			} else if self.held.remove(&key) {
+				self.recording.release(tick, key);
+			}
+		}
+	}
+
+	/// Writes the recording to the file it was started for and returns its path.
+	pub fn save(&self) -> Result<&Path, SaveError> {
+		self.recording.save_to(&self.path)?;
+		Ok(&self.path)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use comet_ecs::Transform2D;
+	use comet_ecs::math::Vec2;
+	use comet_renderer::headless::HeadlessRenderer;
+	use crate::{App, TestApp};
+	use super::*;
+
+	#[test]
+	fn the_recorder_writes_down_changes_of_the_held_keys() {
+		let ticks: [&[Key]; 4] = [&[], &[Key::KeyW], &[Key::KeyW, Key::Space], &[Key::Space]];
+		let mut recorder = InputRecorder::new(PathBuf::new());
+		for (tick, held) in ticks.iter().enumerate() {
+			recorder.update(tick as u64, |key| held.contains(&key));
+		}
+
+		let inputs: Vec<_> = recorder.recording.iter().collect();
+		assert_eq!(inputs, vec![
+			(1, RecordedInput::Press(Key::KeyW)),
+			(2, RecordedInput::Press(Key::Space)),
+			(3, RecordedInput::Release(Key::KeyW))
+		]);
+		assert_eq!(recorder.recording.last_tick(), Some(3));
+	}
+
+	#[test]
+	fn recordings_survive_a_round_trip_through_a_file() {
+		let mut recording = InputRecording::new();
+		recording.press(0, Key::ArrowLeft);
+		recording.press(0, Key::F35);
+		recording.release(120, Key::ArrowLeft);
+
+		let path = std::env::temp_dir().join(format!("comet_input_recording_{}.sav", std::process::id()));
+		recording.save_to(&path).unwrap();
+		let loaded = InputRecording::load_from(&path);
+		let _ = std::fs::remove_file(&path);
+		assert_eq!(loaded.unwrap(), recording);
+	}
+
+	fn walk(app: &mut App, _renderer: &mut HeadlessRenderer, _dt: f32) {
+		if app.key_held(Key::ArrowRight) {
+			app.world_mut().get_component_mut::<Transform2D>(0).translate(Vec2::new(1.0, 0.0));
+		}
+	}
+
+	#[test]
+	fn a_test_app_replays_a_recording() {
+		let mut recording = InputRecording::new();
+		recording.press(1, Key::ArrowRight);
+		recording.release(4, Key::ArrowRight);
+
+		let mut test = TestApp::new()
+			.with_setup(|app, _| { app.world_mut().new_entity(); })
+			.with_recording(&recording);
+		test.run(6, walk, |_, _| {});
+		assert_eq!(test.world().get_component::<Transform2D>(0).position().x(), 3.0);
+	}
+}
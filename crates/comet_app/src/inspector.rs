@@ -0,0 +1,337 @@
+use comet_colors::LinearRgba;
+use comet_ecs::{Component, Emissive, FieldValue, FlashOnHit, Inspect, Mesh2D, ParallaxLayer, Render2D, Shadow2D, SpriteEffect, SpriteMaterial, Trail2D, Transform2D, World, YSort};
+use comet_input::keyboard::Key;
+use winit::event::{ElementState, KeyEvent};
+use winit::keyboard::PhysicalKey;
+
+const CHANNELS: [&str; 4] = ["r", "g", "b", "a"];
+
+struct InspectableComponent {
+	name: String,
+	has: fn(&World, usize) -> bool,
+	fields: fn(&World, usize) -> Vec<(&'static str, FieldValue)>,
+	set_field: fn(&mut World, usize, &str, FieldValue) -> bool
+}
+
+/// An editable line of the inspector. Colors get one row per channel.
+struct Row {
+	component: usize,
+	field: &'static str,
+	channel: Option<usize>
+}
+
+enum Action {
+	Entity(isize),
+	Row(isize),
+	Change(f32),
+	Toggle
+}
+
+/// An in-game entity inspector for tuning gameplay while it runs. It is toggled with F3 and only shows
+/// the components registered with it. Page up and down pick the entity, tab filters the entities by component,
+/// the up and down arrows pick a field and the left and right arrows change it by the step,
+/// which minus and plus make ten times smaller or larger. Enter flips a bool.
+pub struct Inspector {
+	enabled: bool,
+	open: bool,
+	toggle_key: Key,
+	components: Vec<InspectableComponent>,
+	filter: Option<usize>,
+	entity: Option<u32>,
+	row: usize,
+	step: f32,
+	pending: Vec<Action>,
+	lines: Vec<(LinearRgba, String)>
+}
+
+impl Inspector {
+	/// An inspector that already knows the built-in components.
+	pub fn new() -> Self {
+		let mut inspector = Self {
+			enabled: cfg!(debug_assertions),
+			open: false,
+			toggle_key: Key::F3,
+			components: Vec::new(),
+			filter: None,
+			entity: None,
+			row: 0,
+			step: 0.1,
+			pending: Vec::new(),
+			lines: Vec::new()
+		};
+
+		inspector.register::<Transform2D>("Transform2D");
+		inspector.register::<Render2D>("Render2D");
+		inspector.register::<ParallaxLayer>("ParallaxLayer");
+		inspector.register::<Trail2D>("Trail2D");
+		inspector.register::<Mesh2D>("Mesh2D");
+		inspector.register::<FlashOnHit>("FlashOnHit");
+		inspector.register::<SpriteMaterial>("SpriteMaterial");
+		inspector.register::<SpriteEffect>("SpriteEffect");
+		inspector.register::<YSort>("YSort");
+		inspector.register::<Emissive>("Emissive");
+		inspector.register::<Shadow2D>("Shadow2D");
+		inspector
+	}
+
+	/// Shows the fields of every `T` under `name`.
+	pub fn register<T: Component + Inspect>(&mut self, name: &str) {
+		if self.components.iter().any(|component| component.name == name) {
+			return;
+		}
+
+		self.components.push(InspectableComponent {
+			name: name.to_string(),
+			has: has_component::<T>,
+			fields: component_fields::<T>,
+			set_field: set_component_field::<T>
+		});
+	}
+
+	/// The inspector is enabled in debug builds by default.
+	pub fn is_enabled(&self) -> bool {
+		self.enabled
+	}
+
+	pub fn set_enabled(&mut self, enabled: bool) {
+		self.enabled = enabled;
+		if !enabled {
+			self.open = false;
+		}
+	}
+
+	pub fn is_open(&self) -> bool {
+		self.open
+	}
+
+	pub fn open(&mut self) {
+		self.open = self.enabled;
+	}
+
+	pub fn close(&mut self) {
+		self.open = false;
+	}
+
+	pub fn toggle(&mut self) {
+		if self.open {
+			self.close();
+		} else {
+			self.open();
+		}
+	}
+
+	pub fn set_toggle_key(&mut self, key: Key) {
+		self.toggle_key = key;
+	}
+
+	pub fn selected_entity(&self) -> Option<u32> {
+		self.entity
+	}
+
+	/// Shows `entity`, e.g. the one that was clicked on.
+	pub fn select(&mut self, entity: u32) {
+		self.entity = Some(entity);
+		self.row = 0;
+	}
+
+	/// Only lists entities with the component registered as `name`. `None` lists all entities.
+	pub fn set_filter(&mut self, name: Option<&str>) {
+		self.filter = name.and_then(|name| self.components.iter().position(|component| component.name == name));
+	}
+
+	pub fn filter(&self) -> Option<&str> {
+		self.filter.map(|index| self.components[index].name.as_str())
+	}
+
+	/// How much the arrow keys change a number by. Whole numbers change by at least 1.
+	pub fn step(&self) -> f32 {
+		self.step
+	}
+
+	pub fn set_step(&mut self, step: f32) {
+		self.step = step.clamp(0.0001, 10000.0);
+	}
+
+	/// The lines of the inspector as of the last update, top to bottom, with the color to draw them in.
+	pub fn lines(&self) -> &[(LinearRgba, String)] {
+		&self.lines
+	}
+
+	/// Handles a key press. Returns true if the inspector used the key.
+	pub fn handle_key(&mut self, event: &KeyEvent) -> bool {
+		if !self.enabled || event.state != ElementState::Pressed {
+			return false;
+		}
+
+		let PhysicalKey::Code(code) = event.physical_key else { return false };
+		if code == self.toggle_key {
+			if !event.repeat {
+				self.toggle();
+			}
+			return true;
+		}
+		if !self.open {
+			return false;
+		}
+
+		match code {
+			Key::PageUp => self.pending.push(Action::Entity(-1)),
+			Key::PageDown => self.pending.push(Action::Entity(1)),
+			Key::ArrowUp => self.pending.push(Action::Row(-1)),
+			Key::ArrowDown => self.pending.push(Action::Row(1)),
+			Key::ArrowLeft => self.pending.push(Action::Change(-1.0)),
+			Key::ArrowRight => self.pending.push(Action::Change(1.0)),
+			Key::Enter | Key::NumpadEnter => self.pending.push(Action::Toggle),
+			Key::Minus | Key::NumpadSubtract => self.set_step(self.step / 10.0),
+			Key::Equal | Key::NumpadAdd => self.set_step(self.step * 10.0),
+			Key::Tab => {
+				self.filter = match self.filter {
+					None if !self.components.is_empty() => Some(0),
+					Some(index) if index + 1 < self.components.len() => Some(index + 1),
+					_ => None
+				};
+			}
+			_ => return false
+		}
+		true
+	}
+
+	/// Applies the edits made since the last update and refreshes the lines.
+	pub fn update(&mut self, world: &mut World) {
+		if !self.open {
+			self.pending.clear();
+			return;
+		}
+
+		let entities = self.listed_entities(world);
+		let mut position = self.entity.and_then(|entity| entities.iter().position(|&listed| listed == entity));
+		if position.is_none() {
+			position = (!entities.is_empty()).then_some(0);
+		}
+
+		for action in std::mem::take(&mut self.pending) {
+			match action {
+				Action::Entity(offset) => {
+					if let Some(index) = position {
+						position = Some((index as isize + offset).rem_euclid(entities.len() as isize) as usize);
+						self.row = 0;
+					}
+				}
+				Action::Row(offset) => self.row = self.row.saturating_add_signed(offset),
+				Action::Change(sign) => {
+					if let Some(index) = position {
+						self.edit(world, entities[index], Some(sign));
+					}
+				}
+				Action::Toggle => {
+					if let Some(index) = position {
+						self.edit(world, entities[index], None);
+					}
+				}
+			}
+		}
+
+		self.entity = position.map(|index| entities[index]);
+		self.refresh(world, entities.len(), position);
+	}
+
+	fn listed_entities(&self, world: &World) -> Vec<u32> {
+		world.entities().iter()
+			.enumerate()
+			.filter(|(_, entity)| entity.is_some())
+			.map(|(id, _)| id)
+			.filter(|&id| match self.filter {
+				Some(index) => (self.components[index].has)(world, id),
+				None => true
+			})
+			.map(|id| id as u32)
+			.collect()
+	}
+
+	fn rows(&self, world: &World, entity: usize) -> Vec<Row> {
+		let mut rows = Vec::new();
+		for (index, component) in self.components.iter().enumerate() {
+			if !(component.has)(world, entity) {
+				continue;
+			}
+			for (field, value) in (component.fields)(world, entity) {
+				match value {
+					FieldValue::Color(_) => rows.extend((0..4).map(|channel| Row { component: index, field, channel: Some(channel) })),
+					_ => rows.push(Row { component: index, field, channel: None })
+				}
+			}
+		}
+		rows
+	}
+
+	/// Changes the selected field by a step in the direction of `sign`, or flips it if it is a bool and `sign` is `None`.
+	fn edit(&mut self, world: &mut World, entity: u32, sign: Option<f32>) {
+		let entity = entity as usize;
+		let rows = self.rows(world, entity);
+		let Some(row) = rows.get(self.row.min(rows.len().saturating_sub(1))) else { return };
+		let component = &self.components[row.component];
+		let Some(value) = (component.fields)(world, entity).into_iter().find(|(field, _)| *field == row.field).map(|(_, value)| value) else { return };
+
+		let value = match (value, sign) {
+			(FieldValue::Bool(value), _) => FieldValue::Bool(!value),
+			(FieldValue::Float(value), Some(sign)) => FieldValue::Float(value + sign * self.step),
+			(FieldValue::Int(value), Some(sign)) => FieldValue::Int(value + sign as i64 * (self.step.round() as i64).max(1)),
+			(FieldValue::Color(mut color), Some(sign)) => {
+				let channel = row.channel.unwrap_or(0);
+				color[channel] = (color[channel] + sign * self.step).max(0.0);
+				FieldValue::Color(color)
+			}
+			_ => return
+		};
+		(component.set_field)(world, entity, row.field, value);
+	}
+
+	fn refresh(&mut self, world: &World, entity_count: usize, position: Option<usize>) {
+		self.lines.clear();
+		let header_color = LinearRgba::new(0.4, 0.7, 1.0, 1.0);
+		let field_color = LinearRgba::new(0.85, 0.85, 0.85, 1.0);
+		let selected_color = LinearRgba::new(1.0, 0.8, 0.1, 1.0);
+		let filter = self.filter().unwrap_or("all").to_string();
+		let Some(index) = position else {
+			self.lines.push((header_color.clone(), format!("No entities  filter: {}", filter)));
+			return;
+		};
+
+		let entity = self.entity.unwrap_or(0) as usize;
+		self.lines.push((header_color.clone(), format!("Entity {} ({}/{})  filter: {}  step: {}", entity, index + 1, entity_count, filter, self.step)));
+
+		let rows = self.rows(world, entity);
+		self.row = self.row.min(rows.len().saturating_sub(1));
+		let mut shown = None;
+		for (index, row) in rows.iter().enumerate() {
+			let component = &self.components[row.component];
+			if shown != Some(row.component) {
+				shown = Some(row.component);
+				self.lines.push((header_color.clone(), component.name.clone()));
+			}
+
+			let value = (component.fields)(world, entity).into_iter().find(|(field, _)| *field == row.field).map(|(_, value)| value);
+			let text = match (value, row.channel) {
+				(Some(FieldValue::Float(value)), _) => format!("  {}  {:.3}", row.field, value),
+				(Some(FieldValue::Int(value)), _) => format!("  {}  {}", row.field, value),
+				(Some(FieldValue::Bool(value)), _) => format!("  {}  {}", row.field, value),
+				(Some(FieldValue::Color(color)), Some(channel)) => format!("  {}.{}  {:.3}", row.field, CHANNELS[channel], color[channel]),
+				_ => continue
+			};
+			let color = if index == self.row { selected_color.clone() } else { field_color.clone() };
+			self.lines.push((color, text));
+		}
+	}
+}
+
+fn has_component<T: Component + Inspect>(world: &World, entity: usize) -> bool {
+	world.components().contains_component(&T::type_id()) && world.components().get_component::<T>(entity).is_some()
+}
+
+fn component_fields<T: Component + Inspect>(world: &World, entity: usize) -> Vec<(&'static str, FieldValue)> {
+	world.components().get_component::<T>(entity).map(|component| component.fields()).unwrap_or_default()
+}
+
+fn set_component_field<T: Component + Inspect>(world: &mut World, entity: usize, name: &str, value: FieldValue) -> bool {
+	world.components_mut().get_component_mut::<T>(entity).is_some_and(|component| component.set_field(name, value))
+}
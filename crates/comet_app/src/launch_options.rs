@@ -0,0 +1,116 @@
+use std::path::PathBuf;
+
+/// The standard command line flags every comet app understands.
+///
+/// `--windowed`, `--width <px>`, `--height <px>`, `--scene <slot>`, `--log-level <filters>`, `--record-input <file>`,
+/// `--replay-input <file>` and `--profile-startup`.
+/// `--scene` loads the world saved in a slot after setup, e.g. to jump straight into a level while developing,
+/// and `--log-level` takes a level or filters like `COMET_LOG`, e.g. `warn,comet_app=debug`.
+/// `--record-input` writes the keys pressed on every tick into an `InputRecording` on exit, which `--replay-input` plays back.
+/// Values can be passed either as the next argument or with an equals sign (`--width=1280`).
+/// Every argument that is not one of the standard flags is kept in `extra_args` for the game itself.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LaunchOptions {
+	windowed: bool,
+	width: Option<u32>,
+	height: Option<u32>,
+	scene: Option<String>,
+	log_level: Option<String>,
+	record_input: Option<PathBuf>,
+	replay_input: Option<PathBuf>,
+	profile_startup: bool,
+	extra_args: Vec<String>
+}
+
+impl LaunchOptions {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Parses the arguments the process was started with.
+	pub fn from_env() -> Result<Self, String> {
+		Self::parse(std::env::args().skip(1))
+	}
+
+	/// Parses a list of arguments, without the program name.
+	pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+		let mut options = Self::new();
+		let mut args = args.into_iter();
+
+		while let Some(arg) = args.next() {
+			let (flag, inline_value) = match arg.split_once('=') {
+				Some((flag, value)) if arg.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+				_ => (arg.clone(), None)
+			};
+
+			let mut value = |name: &str| -> Result<String, String> {
+				match inline_value.clone().or_else(|| args.next()) {
+					Some(value) => Ok(value),
+					None => Err(format!("Missing value for {}", name))
+				}
+			};
+
+			match flag.as_str() {
+				"--windowed" => options.windowed = true,
+				"--width" => options.width = Some(Self::parse_size("--width", value("--width")?)?),
+				"--height" => options.height = Some(Self::parse_size("--height", value("--height")?)?),
+				"--scene" => options.scene = Some(value("--scene")?),
+				"--log-level" => options.log_level = Some(value("--log-level")?),
+				"--record-input" => options.record_input = Some(PathBuf::from(value("--record-input")?)),
+				"--replay-input" => options.replay_input = Some(PathBuf::from(value("--replay-input")?)),
+				"--profile-startup" => options.profile_startup = true,
+				_ => options.extra_args.push(arg)
+			}
+		}
+
+		Ok(options)
+	}
+
+	fn parse_size(flag: &str, value: String) -> Result<u32, String> {
+		match value.parse::<u32>() {
+			Ok(size) if size > 0 => Ok(size),
+			_ => Err(format!("{} expects a positive number, got \"{}\"", flag, value))
+		}
+	}
+
+	pub fn windowed(&self) -> bool {
+		self.windowed
+	}
+
+	pub fn width(&self) -> Option<u32> {
+		self.width
+	}
+
+	pub fn height(&self) -> Option<u32> {
+		self.height
+	}
+
+	/// The save slot to load after setup.
+	pub fn scene(&self) -> Option<&str> {
+		self.scene.as_deref()
+	}
+
+	pub fn log_level(&self) -> Option<&str> {
+		self.log_level.as_deref()
+	}
+
+	/// The file to record the keyboard input of the run into.
+	pub fn record_input(&self) -> Option<&PathBuf> {
+		self.record_input.as_ref()
+	}
+
+	/// The file with an `InputRecording` to play back instead of the keyboard.
+	pub fn replay_input(&self) -> Option<&PathBuf> {
+		self.replay_input.as_ref()
+	}
+
+	/// Whether to log where the time until the end of setup went, e.g. to packing the atlas or compiling shaders.
+	pub fn profile_startup(&self) -> bool {
+		self.profile_startup
+	}
+
+	/// All arguments that are not standard comet flags.
+	pub fn extra_args(&self) -> &Vec<String> {
+		&self.extra_args
+	}
+}
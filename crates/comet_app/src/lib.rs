@@ -1,4 +1,50 @@
 pub use app::*;
 pub use game_state::*;
+pub use crash_handler::CrashReport;
+pub use app_exit::*;
+pub use window_events::*;
+pub use launch_options::*;
+pub use tasks::*;
+pub use thread_pool::{ThreadPool, EventSender};
+pub use console::*;
+pub use log_overlay::*;
+pub use inspector::Inspector;
+pub use test_app::TestApp;
+pub use input_recording::{InputRecording, RecordedInput};
+pub use hit_stop::HitStop;
+pub use day_night::*;
+pub use preload::{PreloadAsset, PreloadFinished, PreloadGroup};
+pub use error::CometError;
+pub use comet_renderer::accessibility::{Accessibility, ColorBlindFilter};
+pub use comet_renderer::camera::ScalePolicy;
+pub use camera_controller::*;
+pub use pointer::*;
+pub use drag_drop::DroppedOn;
+pub use stats::*;
+pub use ghost::{GhostFinished, GhostKind, GhostPlayback, GhostPose, GhostRecorder, GhostRecording, GhostTrack};
 mod app;
-mod game_state;
\ No newline at end of file
+mod game_state;
+mod crash_handler;
+mod app_exit;
+mod window_events;
+mod launch_options;
+mod tasks;
+mod thread_pool;
+mod console;
+mod log_overlay;
+mod inspector;
+mod test_app;
+mod input_recording;
+mod hit_stop;
+mod accessibility;
+mod day_night;
+mod preload;
+mod error;
+mod watchdog;
+mod camera_controller;
+mod pointer;
+mod drag_drop;
+mod stats;
+mod ghost;
+#[cfg(feature = "hot_reload")]
+mod hot_reload;
\ No newline at end of file
@@ -0,0 +1,180 @@
+use std::collections::VecDeque;
+use comet_colors::LinearRgba;
+use comet_input::keyboard::Key;
+use comet_log::{history, Level};
+use winit::event::{ElementState, KeyEvent};
+use winit::keyboard::PhysicalKey;
+
+const MAX_ENTRIES: usize = 1024;
+const LEVELS: [Level; 6] = [Level::Trace, Level::Debug, Level::Info, Level::Warn, Level::Error, Level::Fatal];
+
+/// Keeps the recent log output for an in-game viewer on devices without a terminal.
+/// It is toggled with F2, scrolled with the page and arrow keys, and the keys 1 to 6
+/// show or hide the levels from trace to fatal. These keys do not reach the game while it is open.
+/// The engine has no text rendering yet, so it is not drawn; games show `lines` themselves.
+pub struct LogOverlay {
+	enabled: bool,
+	open: bool,
+	toggle_key: Key,
+	visible_lines: usize,
+	scroll: usize,
+	shown: [bool; 6],
+	entries: VecDeque<(Level, String)>,
+	log_cursor: u64
+}
+
+/// The color to draw the lines of a level in.
+pub fn level_color(level: Level) -> LinearRgba {
+	match level {
+		Level::Trace => LinearRgba::new(0.6, 0.3, 0.8, 1.0),
+		Level::Debug => LinearRgba::new(0.3, 0.5, 1.0, 1.0),
+		Level::Info => LinearRgba::new(0.3, 0.9, 0.3, 1.0),
+		Level::Warn => LinearRgba::new(1.0, 0.8, 0.1, 1.0),
+		Level::Error => LinearRgba::new(1.0, 0.25, 0.2, 1.0),
+		Level::Fatal => LinearRgba::new(1.0, 0.0, 0.0, 1.0)
+	}
+}
+
+impl LogOverlay {
+	pub fn new() -> Self {
+		Self {
+			enabled: cfg!(debug_assertions),
+			open: false,
+			toggle_key: Key::F2,
+			visible_lines: 20,
+			scroll: 0,
+			shown: [true; 6],
+			entries: VecDeque::new(),
+			log_cursor: 0
+		}
+	}
+
+	/// The overlay is enabled in debug builds by default.
+	pub fn is_enabled(&self) -> bool {
+		self.enabled
+	}
+
+	pub fn set_enabled(&mut self, enabled: bool) {
+		self.enabled = enabled;
+		if !enabled {
+			self.open = false;
+		}
+	}
+
+	pub fn is_open(&self) -> bool {
+		self.open
+	}
+
+	pub fn open(&mut self) {
+		self.open = self.enabled;
+	}
+
+	pub fn close(&mut self) {
+		self.open = false;
+	}
+
+	pub fn toggle(&mut self) {
+		if self.open {
+			self.close();
+		} else {
+			self.open();
+		}
+	}
+
+	pub fn set_toggle_key(&mut self, key: Key) {
+		self.toggle_key = key;
+	}
+
+	/// How many lines are shown at once.
+	pub fn set_visible_lines(&mut self, lines: usize) {
+		self.visible_lines = lines.max(1);
+	}
+
+	pub fn is_shown(&self, level: Level) -> bool {
+		self.shown[level as usize]
+	}
+
+	/// Shows or hides the lines of a level.
+	pub fn set_shown(&mut self, level: Level, shown: bool) {
+		self.shown[level as usize] = shown;
+		self.scroll = self.scroll.min(self.max_scroll());
+	}
+
+	/// Scrolls up towards older lines by the given amount. Negative amounts scroll back down.
+	pub fn scroll(&mut self, lines: isize) {
+		self.scroll = self.scroll.saturating_add_signed(lines).min(self.max_scroll());
+	}
+
+	/// The lines that are currently scrolled into view, oldest first, with the color to draw them in.
+	pub fn lines(&self) -> Vec<(LinearRgba, &str)> {
+		let filtered = self.filtered().collect::<Vec<&(Level, String)>>();
+		let end = filtered.len() - self.scroll.min(filtered.len());
+		let start = end.saturating_sub(self.visible_lines);
+		filtered[start..end].iter().map(|(level, line)| (level_color(*level), line.as_str())).collect()
+	}
+
+	/// Handles a key press. Returns true if the overlay used the key.
+	pub fn handle_key(&mut self, event: &KeyEvent) -> bool {
+		if !self.enabled || event.state != ElementState::Pressed {
+			return false;
+		}
+
+		let PhysicalKey::Code(code) = event.physical_key else { return false };
+		if code == self.toggle_key {
+			if !event.repeat {
+				self.toggle();
+			}
+			return true;
+		}
+		if !self.open {
+			return false;
+		}
+
+		let page = self.visible_lines as isize;
+		match code {
+			Key::PageUp => self.scroll(page),
+			Key::PageDown => self.scroll(-page),
+			Key::ArrowUp => self.scroll(1),
+			Key::ArrowDown => self.scroll(-1),
+			Key::Home => self.scroll = self.max_scroll(),
+			Key::End => self.scroll = 0,
+			Key::Digit1 | Key::Digit2 | Key::Digit3 | Key::Digit4 | Key::Digit5 | Key::Digit6 => {
+				let index = match code {
+					Key::Digit1 => 0,
+					Key::Digit2 => 1,
+					Key::Digit3 => 2,
+					Key::Digit4 => 3,
+					Key::Digit5 => 4,
+					_ => 5
+				};
+				self.set_shown(LEVELS[index], !self.shown[index]);
+			}
+			_ => return false
+		}
+		true
+	}
+
+	/// Pulls in the new log lines. The view stays put if it is scrolled up.
+	pub fn update(&mut self) {
+		let (entries, cursor) = history::entries_since(self.log_cursor);
+		self.log_cursor = cursor;
+		for entry in entries {
+			if self.scroll > 0 && self.is_shown(entry.0) {
+				self.scroll += 1;
+			}
+			if self.entries.len() >= MAX_ENTRIES {
+				self.entries.pop_front();
+			}
+			self.entries.push_back(entry);
+		}
+		self.scroll = self.scroll.min(self.max_scroll());
+	}
+
+	fn filtered(&self) -> impl Iterator<Item = &(Level, String)> {
+		self.entries.iter().filter(|(level, _)| self.shown[*level as usize])
+	}
+
+	fn max_scroll(&self) -> usize {
+		self.filtered().count().saturating_sub(self.visible_lines)
+	}
+}
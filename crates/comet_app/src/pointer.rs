@@ -0,0 +1,175 @@
+use comet_ecs::math::Vec2;
+use comet_ecs::World;
+use comet_input::mouse::Button;
+use comet_renderer::renderer::Renderer;
+use winit_input_helper::WinitInputHelper;
+
+/// How far in pixels the mouse has to move with a button held before a press turns into a drag instead of a click.
+const DRAG_THRESHOLD: f32 = 4.0;
+const BUTTONS: [Button; 3] = [Button::Left, Button::Right, Button::Middle];
+
+/// Sent into the `World` when a mouse button is pressed and released over the same entity without dragging it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Clicked {
+	pub entity: u32,
+	pub button: Button,
+	/// Where the click happened in the world.
+	pub position: Vec2
+}
+
+/// Sent into the `World` when the mouse moves onto an entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HoverEnter {
+	pub entity: u32
+}
+
+/// Sent into the `World` when the mouse leaves an entity it was over, also when another entity is drawn over it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HoverExit {
+	pub entity: u32
+}
+
+/// Sent into the `World` every update the mouse moves while dragging an entity it was pressed on,
+/// even once the cursor is no longer over it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dragged {
+	pub entity: u32,
+	pub button: Button,
+	/// Where the cursor is in the world.
+	pub position: Vec2,
+	/// How far the cursor moved in the world since the last `Dragged` of this drag.
+	pub delta: Vec2
+}
+
+/// The mouse button presses and releases of the finished frames that no tick has seen yet.
+/// A frame can run no tick or several, so the edges are kept until the next tick takes them,
+/// instead of being read from the input of the current frame.
+pub(crate) struct MouseEdges {
+	latched_pressed: Vec<Button>,
+	latched_released: Vec<Button>,
+	pressed: Vec<Button>,
+	released: Vec<Button>
+}
+
+impl MouseEdges {
+	pub fn new() -> Self {
+		Self {
+			latched_pressed: Vec::new(),
+			latched_released: Vec::new(),
+			pressed: Vec::new(),
+			released: Vec::new()
+		}
+	}
+
+	/// Keeps the edges of the frame `input` just finished.
+	pub fn latch(&mut self, input: &WinitInputHelper) {
+		for button in BUTTONS {
+			if input.mouse_pressed(button) && !self.latched_pressed.contains(&button) {
+				self.latched_pressed.push(button);
+			}
+			if input.mouse_released(button) && !self.latched_released.contains(&button) {
+				self.latched_released.push(button);
+			}
+		}
+	}
+
+	/// Hands the latched edges to the tick that is starting, the ticks after it see none until the next frame.
+	pub fn begin_tick(&mut self) {
+		self.pressed = std::mem::take(&mut self.latched_pressed);
+		self.released = std::mem::take(&mut self.latched_released);
+	}
+
+	pub fn pressed(&self, button: Button) -> bool {
+		self.pressed.contains(&button)
+	}
+
+	pub fn released(&self, button: Button) -> bool {
+		self.released.contains(&button)
+	}
+}
+
+/// A mouse button held down since it was pressed on an entity.
+struct Press {
+	button: Button,
+	entity: u32,
+	start: Vec2,
+	last: Vec2,
+	dragging: bool
+}
+
+/// Finds the entity under the mouse every update and sends `Clicked`, `HoverEnter`, `HoverExit` and `Dragged`
+/// into the `World`. The topmost visible sprite is hit, by the same order and layers it is drawn in.
+/// Turned on with `App::with_pointer_interaction`.
+pub struct PointerInteraction {
+	hovered: Option<u32>,
+	presses: Vec<Press>
+}
+
+impl PointerInteraction {
+	pub(crate) fn new() -> Self {
+		Self {
+			hovered: None,
+			presses: Vec::new()
+		}
+	}
+
+	/// The entity the mouse was over in the last update.
+	pub fn hovered(&self) -> Option<u32> {
+		self.hovered
+	}
+
+	/// Whether an entity is being dragged with any button.
+	pub fn is_dragging(&self) -> bool {
+		self.presses.iter().any(|press| press.dragging)
+	}
+
+	pub(crate) fn update<R: Renderer>(&mut self, renderer: &R, input: &WinitInputHelper, edges: &MouseEdges, world: &mut World) {
+		let cursor = input.cursor().map(|(x, y)| Vec2::new(x, y));
+		let hit = cursor.and_then(|cursor| renderer.pick(cursor, world));
+
+		if hit != self.hovered {
+			if let Some(entity) = self.hovered {
+				world.send_event(HoverExit { entity });
+			}
+			if let Some(entity) = hit {
+				world.send_event(HoverEnter { entity });
+			}
+			self.hovered = hit;
+		}
+
+		let Some(cursor) = cursor else {
+			return;
+		};
+		let position = renderer.screen_to_world(cursor);
+
+		for press in &mut self.presses {
+			if !press.dragging && (cursor - press.start).length() >= DRAG_THRESHOLD {
+				press.dragging = true;
+			}
+			let delta = position - press.last;
+			if press.dragging && (delta.x() != 0.0 || delta.y() != 0.0) {
+				world.send_event(Dragged { entity: press.entity, button: press.button, position, delta });
+				press.last = position;
+			}
+		}
+
+		for button in BUTTONS {
+			if edges.pressed(button) {
+				self.presses.retain(|press| press.button != button);
+				if let Some(entity) = hit {
+					self.presses.push(Press { button, entity, start: cursor, last: position, dragging: false });
+				}
+			}
+			if !edges.released(button) {
+				continue;
+			}
+			let Some(index) = self.presses.iter().position(|press| press.button == button) else {
+				continue;
+			};
+			let press = self.presses.remove(index);
+			if !press.dragging && hit == Some(press.entity) {
+				world.send_event(Clicked { entity: press.entity, button, position });
+			}
+		}
+	}
+}
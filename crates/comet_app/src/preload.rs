@@ -0,0 +1,253 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Cursor;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use comet_ecs::World;
+use comet_log::*;
+use comet_resources::assets;
+use comet_sound::Audio;
+use comet_sound::kira::sound::static_sound::{StaticSoundData, StaticSoundSettings};
+
+/// An asset of a `PreloadGroup`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PreloadAsset {
+	/// An image, read into memory so the texture atlas is built without touching the disk.
+	Texture(String),
+	Font(String),
+	/// An audio file, decoded and loaded under `name` so it can be played right away.
+	Sound { name: String, path: String }
+}
+
+impl PreloadAsset {
+	/// What the asset is known by once it is loaded, the same for every group listing it.
+	fn key(&self) -> String {
+		match self {
+			PreloadAsset::Texture(path) | PreloadAsset::Font(path) => path.clone(),
+			PreloadAsset::Sound { name, .. } => format!("sound:{}", name)
+		}
+	}
+}
+
+/// A named set of assets loaded together with `App::preload`, e.g. everything a level needs.
+/// Groups can depend on other groups, which are preloaded along with them.
+#[derive(Debug, Clone, Default)]
+pub struct PreloadGroup {
+	assets: Vec<PreloadAsset>,
+	dependencies: Vec<String>
+}
+
+impl PreloadGroup {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn with_texture(mut self, path: &str) -> Self {
+		self.assets.push(PreloadAsset::Texture(path.to_string()));
+		self
+	}
+
+	pub fn with_font(mut self, path: &str) -> Self {
+		self.assets.push(PreloadAsset::Font(path.to_string()));
+		self
+	}
+
+	/// Loads the audio file at `path` under `name`, like `Audio::load_audio`.
+	pub fn with_sound(mut self, name: &str, path: &str) -> Self {
+		self.assets.push(PreloadAsset::Sound { name: name.to_string(), path: path.to_string() });
+		self
+	}
+
+	/// Preloads the group called `group` whenever this one is, e.g. assets shared by all levels.
+	pub fn with_dependency(mut self, group: &str) -> Self {
+		self.dependencies.push(group.to_string());
+		self
+	}
+
+	pub fn assets(&self) -> &[PreloadAsset] {
+		&self.assets
+	}
+
+	pub fn dependencies(&self) -> &[String] {
+		&self.dependencies
+	}
+}
+
+/// Sent into the `World` once every asset of a preloaded group is loaded or failed to load.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreloadFinished(pub String);
+
+/// An asset a worker is done with.
+struct Loaded {
+	key: String,
+	result: Result<Option<(String, StaticSoundData)>, String>
+}
+
+/// Keeps track of the preload groups of the app and loads their assets on the thread pool.
+pub(crate) struct Preloader {
+	groups: HashMap<String, PreloadGroup>,
+	/// The groups `preload` was called for, directly or as a dependency, and not unloaded since.
+	requested: HashSet<String>,
+	/// The requested groups that did not finish yet.
+	pending: Vec<String>,
+	loaded: HashSet<String>,
+	failed: HashSet<String>,
+	loading: HashSet<String>,
+	sender: Sender<Loaded>,
+	receiver: Receiver<Loaded>
+}
+
+impl Preloader {
+	pub fn new() -> Self {
+		let (sender, receiver) = channel();
+		Self {
+			groups: HashMap::new(),
+			requested: HashSet::new(),
+			pending: Vec::new(),
+			loaded: HashSet::new(),
+			failed: HashSet::new(),
+			loading: HashSet::new(),
+			sender,
+			receiver
+		}
+	}
+
+	pub fn add_group(&mut self, name: &str, group: PreloadGroup) {
+		self.groups.insert(name.to_string(), group);
+	}
+
+	/// `name` and every group it depends on, each once.
+	fn with_dependencies(&self, name: &str) -> Vec<String> {
+		let mut groups = Vec::new();
+		let mut stack = vec![name.to_string()];
+		while let Some(group) = stack.pop() {
+			if groups.contains(&group) {
+				continue;
+			}
+			if let Some(preload_group) = self.groups.get(&group) {
+				stack.extend(preload_group.dependencies.iter().cloned());
+			}
+			groups.push(group);
+		}
+		groups
+	}
+
+	/// Marks the group and its dependencies as requested and hands out the jobs loading what is not in memory yet.
+	pub fn preload(&mut self, name: &str) -> Vec<Box<dyn FnOnce() + Send>> {
+		if !self.groups.contains_key(name) {
+			error!("There is no preload group called {}", name);
+			return Vec::new();
+		}
+
+		let mut jobs: Vec<Box<dyn FnOnce() + Send>> = Vec::new();
+		for group in self.with_dependencies(name) {
+			let Some(preload_group) = self.groups.get(&group) else {
+				warn!("The preload group {} depends on {}, which does not exist", name, group);
+				continue;
+			};
+			for asset in &preload_group.assets {
+				let key = asset.key();
+				if self.loaded.contains(&key) || self.loading.contains(&key) {
+					continue;
+				}
+				self.failed.remove(&key);
+				self.loading.insert(key.clone());
+				let (asset, sender) = (asset.clone(), self.sender.clone());
+				jobs.push(Box::new(move || {
+					let _ = sender.send(Loaded { key, result: load(&asset) });
+				}));
+			}
+			if self.requested.insert(group.clone()) {
+				self.pending.push(group);
+			}
+		}
+		jobs
+	}
+
+	/// How much of the group is loaded, from 0 to 1. Failed assets count as done.
+	pub fn progress(&self, name: &str) -> f32 {
+		let keys = self.keys(name);
+		if keys.is_empty() {
+			return if self.requested.contains(name) { 1.0 } else { 0.0 };
+		}
+		let done = keys.iter().filter(|key| self.loaded.contains(*key) || self.failed.contains(*key)).count();
+		done as f32 / keys.len() as f32
+	}
+
+	pub fn is_loaded(&self, name: &str) -> bool {
+		self.requested.contains(name) && self.keys(name).iter().all(|key| self.loaded.contains(key))
+	}
+
+	/// The keys of the assets of the group and its dependencies.
+	fn keys(&self, name: &str) -> HashSet<String> {
+		self.with_dependencies(name).iter()
+			.filter_map(|group| self.groups.get(group))
+			.flat_map(|group| group.assets.iter().map(PreloadAsset::key))
+			.collect()
+	}
+
+	/// Releases the assets of the group that no other requested group lists. Its dependencies stay loaded.
+	pub fn unload(&mut self, name: &str, audio: &mut Audio) {
+		if !self.requested.remove(name) {
+			return;
+		}
+		self.pending.retain(|group| group != name);
+		let Some(group) = self.groups.get(name) else { return };
+		let shared: HashSet<String> = self.requested.iter()
+			.filter_map(|other| self.groups.get(other))
+			.flat_map(|other| other.assets.iter().map(PreloadAsset::key))
+			.collect();
+
+		for asset in &group.assets {
+			let key = asset.key();
+			if shared.contains(&key) {
+				continue;
+			}
+			self.loaded.remove(&key);
+			self.failed.remove(&key);
+			match asset {
+				PreloadAsset::Texture(path) | PreloadAsset::Font(path) => assets::release_asset(path),
+				PreloadAsset::Sound { name, .. } => audio.unload_audio(name)
+			}
+		}
+	}
+
+	/// Takes in what the workers loaded since the last update and sends `PreloadFinished` for the groups that are done.
+	pub fn update(&mut self, world: &mut World, audio: &mut Audio) {
+		while let Ok(loaded) = self.receiver.try_recv() {
+			self.loading.remove(&loaded.key);
+			match loaded.result {
+				Ok(sound) => {
+					if let Some((name, data)) = sound {
+						audio.insert_audio(&name, data);
+					}
+					self.loaded.insert(loaded.key);
+				}
+				Err(e) => {
+					error!("Failed to preload {}: {}", loaded.key, e);
+					self.failed.insert(loaded.key);
+				}
+			}
+		}
+
+		let finished: Vec<String> = self.pending.iter().filter(|group| self.progress(group) >= 1.0).cloned().collect();
+		for group in finished {
+			self.pending.retain(|pending| *pending != group);
+			info!("Preloaded {}", group);
+			world.send_event(PreloadFinished(group));
+		}
+	}
+}
+
+/// Reads the asset into memory, decoding sounds right away so the main thread only has to hand them to the mixer.
+fn load(asset: &PreloadAsset) -> Result<Option<(String, StaticSoundData)>, String> {
+	match asset {
+		PreloadAsset::Texture(path) | PreloadAsset::Font(path) => {
+			assets::cache_asset(path).map_err(|e| e.to_string())?;
+			Ok(None)
+		}
+		PreloadAsset::Sound { name, path } => {
+			let bytes = assets::read_asset(path).map_err(|e| e.to_string())?;
+			let data = StaticSoundData::from_cursor(Cursor::new(bytes), StaticSoundSettings::default()).map_err(|e| e.to_string())?;
+			Ok(Some((name.clone(), data)))
+		}
+	}
+}
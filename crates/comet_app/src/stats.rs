@@ -0,0 +1,449 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use comet_log::*;
+use comet_save::{SaveData, SaveError, SaveFile, SaveReader, SaveWriter};
+
+const STATS_SECTION: &str = "stats";
+
+/// Sent into the `World` when an achievement is unlocked, by its stat reaching the threshold or by `Stats::unlock`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AchievementUnlocked {
+	pub id: String
+}
+
+/// Why the stat and achievement definitions could not be loaded.
+#[derive(Debug)]
+pub enum StatsError {
+	Io(std::io::Error),
+	Toml(toml::de::Error),
+	/// The definitions are valid TOML but not valid stats, with a description of what is wrong.
+	Invalid(String)
+}
+
+impl fmt::Display for StatsError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			StatsError::Io(e) => write!(f, "Failed to read the stats: {}", e),
+			StatsError::Toml(e) => write!(f, "Invalid TOML in the stats: {}", e),
+			StatsError::Invalid(reason) => write!(f, "Invalid stats: {}", reason)
+		}
+	}
+}
+
+impl std::error::Error for StatsError {}
+
+impl From<std::io::Error> for StatsError {
+	fn from(e: std::io::Error) -> Self {
+		StatsError::Io(e)
+	}
+}
+
+impl From<toml::de::Error> for StatsError {
+	fn from(e: toml::de::Error) -> Self {
+		StatsError::Toml(e)
+	}
+}
+
+/// Unlocked once the stat `stat` reaches `threshold`, or by hand with `Stats::unlock` if it has no stat.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Achievement {
+	id: String,
+	stat: Option<String>,
+	threshold: i64,
+	unlocked: bool
+}
+
+impl Achievement {
+	pub fn id(&self) -> &str {
+		&self.id
+	}
+
+	pub fn stat(&self) -> Option<&str> {
+		self.stat.as_deref()
+	}
+
+	pub fn threshold(&self) -> i64 {
+		self.threshold
+	}
+
+	pub fn is_unlocked(&self) -> bool {
+		self.unlocked
+	}
+}
+
+/// Mirrors stats and achievements to a platform like Steam, see `SteamStats` behind the `steamworks` feature.
+pub trait StatsBackend {
+	fn set_stat(&mut self, name: &str, value: i64);
+	fn unlock(&mut self, achievement: &str);
+	/// Called every update, e.g. to run the callbacks of the platform.
+	fn update(&mut self) {}
+	/// Called when the stats are saved, to upload what changed since the last time.
+	fn store(&mut self) {}
+}
+
+/// Counters and the achievements unlocked by them, defined in a TOML file:
+///
+/// ```toml
+/// [stats]
+/// enemies_killed = 0
+/// distance_walked = 0
+///
+/// [achievements.first_blood]
+/// stat = "enemies_killed"
+/// threshold = 1
+///
+/// [achievements.secret_room]
+/// ```
+///
+/// Reached from the app with `App::stats`, which sends `AchievementUnlocked` for every achievement unlocked during an update
+/// and saves the progress next to the save games on exit.
+pub struct Stats {
+	values: HashMap<String, i64>,
+	achievements: Vec<Achievement>,
+	newly_unlocked: Vec<String>,
+	backend: Option<Box<dyn StatsBackend>>,
+	changed: bool
+}
+
+impl Stats {
+	pub fn new() -> Self {
+		Self {
+			values: HashMap::new(),
+			achievements: Vec::new(),
+			newly_unlocked: Vec::new(),
+			backend: None,
+			changed: false
+		}
+	}
+
+	pub fn load(path: &str) -> Result<Self, StatsError> {
+		let source = comet_resources::assets::read_asset_to_string(path)?;
+		Self::from_toml(&source)
+	}
+
+	pub fn from_toml(source: &str) -> Result<Self, StatsError> {
+		let table: toml::Table = source.parse()?;
+		let mut stats = Self::new();
+
+		if let Some(values) = table.get("stats") {
+			let toml::Value::Table(values) = values else {
+				return Err(StatsError::Invalid("`stats` has to be a table of names and starting values".to_string()));
+			};
+			for (name, value) in values {
+				let value = value.as_integer().ok_or_else(|| StatsError::Invalid(format!("the stat {} does not start at a whole number", name)))?;
+				stats = stats.with_stat(name, value);
+			}
+		}
+
+		if let Some(achievements) = table.get("achievements") {
+			let toml::Value::Table(achievements) = achievements else {
+				return Err(StatsError::Invalid("`achievements` has to be a table with one table per achievement".to_string()));
+			};
+			for (id, achievement) in achievements {
+				let invalid = |what: &str| StatsError::Invalid(format!("the achievement {} {}", id, what));
+				let achievement = achievement.as_table().ok_or_else(|| invalid("is not a table"))?;
+				let stat = match achievement.get("stat") {
+					Some(stat) => Some(stat.as_str().ok_or_else(|| invalid("names its stat with something other than a string"))?),
+					None => None
+				};
+				let threshold = match achievement.get("threshold") {
+					Some(threshold) => threshold.as_integer().ok_or_else(|| invalid("has a threshold that is not a whole number"))?,
+					None if stat.is_some() => return Err(invalid("has a stat but no threshold")),
+					None => 0
+				};
+				stats = match stat {
+					Some(stat) => stats.with_achievement(id, stat, threshold),
+					None => stats.with_manual_achievement(id)
+				};
+			}
+		}
+
+		Ok(stats)
+	}
+
+	/// Adds a stat starting at `value`.
+	pub fn with_stat(mut self, name: &str, value: i64) -> Self {
+		self.values.insert(name.to_string(), value);
+		self
+	}
+
+	/// Adds an achievement unlocked once the stat `stat` reaches `threshold`.
+	pub fn with_achievement(mut self, id: &str, stat: &str, threshold: i64) -> Self {
+		if !self.values.contains_key(stat) {
+			self.values.insert(stat.to_string(), 0);
+		}
+		self.push_achievement(Achievement { id: id.to_string(), stat: Some(stat.to_string()), threshold, unlocked: false });
+		self
+	}
+
+	/// Adds an achievement that is only unlocked with `unlock`.
+	pub fn with_manual_achievement(mut self, id: &str) -> Self {
+		self.push_achievement(Achievement { id: id.to_string(), stat: None, threshold: 0, unlocked: false });
+		self
+	}
+
+	/// Mirrors every change to `backend` from now on.
+	pub fn with_backend(mut self, backend: impl StatsBackend + 'static) -> Self {
+		self.set_backend(backend);
+		self
+	}
+
+	pub fn set_backend(&mut self, backend: impl StatsBackend + 'static) {
+		self.backend = Some(Box::new(backend));
+	}
+
+	pub fn get(&self, name: &str) -> i64 {
+		self.values.get(name).copied().unwrap_or(0)
+	}
+
+	/// Adds `amount` to the stat `name`, unlocking the achievements it reaches.
+	pub fn add(&mut self, name: &str, amount: i64) {
+		let value = self.get(name).saturating_add(amount);
+		self.set(name, value);
+	}
+
+	/// Sets the stat `name` to `value`, unlocking the achievements it reaches.
+	pub fn set(&mut self, name: &str, value: i64) {
+		match self.values.get_mut(name) {
+			Some(existing) if *existing == value => return,
+			Some(existing) => *existing = value,
+			None => {
+				warn!("The stat {} is not defined, it is added now", name);
+				self.values.insert(name.to_string(), value);
+			}
+		}
+		self.changed = true;
+		if let Some(backend) = &mut self.backend {
+			backend.set_stat(name, value);
+		}
+
+		let reached: Vec<String> = self.achievements.iter()
+			.filter(|achievement| !achievement.unlocked && achievement.stat.as_deref() == Some(name) && value >= achievement.threshold)
+			.map(|achievement| achievement.id.clone())
+			.collect();
+		for id in reached {
+			self.unlock(&id);
+		}
+	}
+
+	/// Sets the stat `name` to `value` if that is higher, e.g. for a best score.
+	pub fn set_max(&mut self, name: &str, value: i64) {
+		if value > self.get(name) || !self.values.contains_key(name) {
+			self.set(name, value);
+		}
+	}
+
+	pub fn stats(&self) -> impl Iterator<Item = (&str, i64)> {
+		self.values.iter().map(|(name, value)| (name.as_str(), *value))
+	}
+
+	pub fn achievement(&self, id: &str) -> Option<&Achievement> {
+		self.achievements.iter().find(|achievement| achievement.id == id)
+	}
+
+	pub fn achievements(&self) -> &[Achievement] {
+		&self.achievements
+	}
+
+	/// How far the achievement `id` is from being unlocked, between 0 and 1.
+	pub fn progress(&self, id: &str) -> f32 {
+		let Some(achievement) = self.achievement(id) else {
+			return 0.0;
+		};
+		match &achievement.stat {
+			_ if achievement.unlocked => 1.0,
+			Some(stat) if achievement.threshold > 0 => (self.get(stat) as f32 / achievement.threshold as f32).clamp(0.0, 1.0),
+			_ => 0.0
+		}
+	}
+
+	pub fn is_unlocked(&self, id: &str) -> bool {
+		self.achievement(id).is_some_and(Achievement::is_unlocked)
+	}
+
+	/// Unlocks the achievement `id` no matter its stat. Does nothing if it already is.
+	pub fn unlock(&mut self, id: &str) {
+		let Some(achievement) = self.achievements.iter_mut().find(|achievement| achievement.id == id) else {
+			warn!("There is no achievement called {}", id);
+			return;
+		};
+		if achievement.unlocked {
+			return;
+		}
+		achievement.unlocked = true;
+		self.changed = true;
+		self.newly_unlocked.push(id.to_string());
+		if let Some(backend) = &mut self.backend {
+			backend.unlock(id);
+		}
+		info!("Unlocked the achievement {}", id);
+	}
+
+	/// Resets every stat to 0 and locks every achievement again, e.g. for a debug menu.
+	pub fn reset(&mut self) {
+		for value in self.values.values_mut() {
+			*value = 0;
+		}
+		for achievement in &mut self.achievements {
+			achievement.unlocked = false;
+		}
+		self.newly_unlocked.clear();
+		self.changed = true;
+	}
+
+	/// Whether anything changed since the stats were last saved or loaded.
+	pub fn has_changes(&self) -> bool {
+		self.changed
+	}
+
+	/// Writes the stats and unlocked achievements into their own save file at `path`.
+	pub fn save_to(&mut self, path: &Path) -> Result<(), SaveError> {
+		let mut save_file = SaveFile::new(1);
+		let saved = SavedStats {
+			values: self.values.iter().map(|(name, value)| (name.clone(), *value)).collect(),
+			unlocked: self.achievements.iter().filter(|achievement| achievement.unlocked).map(|achievement| achievement.id.clone()).collect()
+		};
+		save_file.set(STATS_SECTION, &saved);
+		save_file.write_to(path, true)?;
+		if let Some(backend) = &mut self.backend {
+			backend.store();
+		}
+		self.changed = false;
+		Ok(())
+	}
+
+	/// Restores the progress saved with `save_to`. Achievements unlocked back then are unlocked again without
+	/// `AchievementUnlocked`, but ones whose stat reached a threshold lowered since are sent.
+	pub fn load_from(&mut self, path: &Path) -> Result<(), SaveError> {
+		let save_file = SaveFile::read_from(path)?;
+		let saved = save_file.get::<SavedStats>(STATS_SECTION)
+			.ok_or_else(|| SaveError::Corrupted("The stats file has no stats".to_string()))??;
+
+		for id in &saved.unlocked {
+			match self.achievements.iter_mut().find(|achievement| &achievement.id == id) {
+				Some(achievement) => achievement.unlocked = true,
+				None => warn!("The saved achievement {} is not defined anymore", id)
+			}
+		}
+		for (name, value) in saved.values {
+			self.values.insert(name, value);
+		}
+		let reached: Vec<String> = self.achievements.iter()
+			.filter(|achievement| !achievement.unlocked)
+			.filter(|achievement| achievement.stat.as_ref().is_some_and(|stat| self.get(stat) >= achievement.threshold))
+			.map(|achievement| achievement.id.clone())
+			.collect();
+		for id in reached {
+			self.unlock(&id);
+		}
+		self.changed = false;
+		Ok(())
+	}
+
+	fn push_achievement(&mut self, achievement: Achievement) {
+		match self.achievements.iter_mut().find(|existing| existing.id == achievement.id) {
+			Some(existing) => {
+				warn!("The achievement {} is defined twice, the later one is kept", achievement.id);
+				*existing = achievement;
+			}
+			None => self.achievements.push(achievement)
+		}
+	}
+
+	pub(crate) fn update(&mut self) {
+		if let Some(backend) = &mut self.backend {
+			backend.update();
+		}
+	}
+
+	pub(crate) fn drain_unlocked(&mut self) -> impl Iterator<Item = AchievementUnlocked> + '_ {
+		self.newly_unlocked.drain(..).map(|id| AchievementUnlocked { id })
+	}
+}
+
+impl Default for Stats {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// The progress of `Stats` as it is saved, without the definitions.
+struct SavedStats {
+	values: Vec<(String, i64)>,
+	unlocked: Vec<String>
+}
+
+impl SaveData for SavedStats {
+	fn write(&self, writer: &mut SaveWriter) {
+		writer.write(&(self.values.len() as u32));
+		for (name, value) in &self.values {
+			writer.write(name);
+			writer.write(value);
+		}
+		writer.write(&self.unlocked);
+	}
+
+	fn read(reader: &mut SaveReader) -> Result<Self, SaveError> {
+		let count = reader.read::<u32>()?;
+		let mut values = Vec::with_capacity((count as usize).min(1 << 16));
+		for _ in 0..count {
+			values.push((reader.read::<String>()?, reader.read::<i64>()?));
+		}
+		Ok(Self {
+			values,
+			unlocked: reader.read()?
+		})
+	}
+}
+
+/// Mirrors stats and achievements to Steam. Stats and achievements need the same names as in the Steamworks settings
+/// of the game, and stats are sent as 32 bit integers.
+#[cfg(feature = "steamworks")]
+pub struct SteamStats {
+	client: steamworks::Client,
+	single: steamworks::SingleClient
+}
+
+#[cfg(feature = "steamworks")]
+impl SteamStats {
+	/// Connects to the running Steam client, `None` if Steam is not running or the game is not started through it.
+	pub fn init() -> Option<Self> {
+		match steamworks::Client::init() {
+			Ok((client, single)) => {
+				client.user_stats().request_current_stats();
+				Some(Self { client, single })
+			}
+			Err(e) => {
+				warn!("Steam is not available, achievements are only tracked locally: {:?}", e);
+				None
+			}
+		}
+	}
+}
+
+#[cfg(feature = "steamworks")]
+impl StatsBackend for SteamStats {
+	fn set_stat(&mut self, name: &str, value: i64) {
+		let value = value.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+		if self.client.user_stats().set_stat_i32(name, value).is_err() {
+			warn!("Steam does not know the stat {}", name);
+		}
+	}
+
+	fn unlock(&mut self, achievement: &str) {
+		if self.client.user_stats().achievement(achievement).set().is_err() {
+			warn!("Steam does not know the achievement {}", achievement);
+		}
+	}
+
+	fn update(&mut self) {
+		self.single.run_callbacks();
+	}
+
+	fn store(&mut self) {
+		if self.client.user_stats().store_stats().is_err() {
+			warn!("Failed to upload the stats to Steam");
+		}
+	}
+}
@@ -0,0 +1,233 @@
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+use comet_ecs::World;
+
+thread_local! {
+	// The clock of the executor that is currently polling, read by the wait futures.
+	static TASK_TIME: Cell<f64> = const { Cell::new(0.0) };
+	static TASK_FRAME: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Identifies a task spawned with `App::spawn_task`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TaskHandle(u64);
+
+struct Task {
+	handle: TaskHandle,
+	future: Pin<Box<dyn Future<Output = ()>>>
+}
+
+type WorldCommand = Box<dyn FnOnce(&mut World)>;
+
+/// Lets tasks change the world. Cloned into a task before spawning it with `App::task_context`.
+/// Commands run in the order they were queued, right after every task was polled in the update.
+///
+/// ```ignore
+/// let context = app.task_context();
+/// app.spawn_task(async move {
+///     wait_seconds(2.0).await;
+///     let door = context.with_world(|world| world.new_entity()).await;
+///     context.run(move |world| world.add_component(door as usize, Door::new()));
+/// });
+/// ```
+#[derive(Clone)]
+pub struct TaskContext {
+	commands: Rc<RefCell<Vec<WorldCommand>>>
+}
+
+impl TaskContext {
+	/// Queues `command` to run on the world at the end of the current update.
+	pub fn run<F: FnOnce(&mut World) + 'static>(&self, command: F) {
+		self.commands.borrow_mut().push(Box::new(command));
+	}
+
+	/// Runs `query` on the world at the end of the current update and completes with its result on the next one.
+	pub fn with_world<T: 'static, F: FnOnce(&mut World) -> T + Unpin + 'static>(&self, query: F) -> WithWorld<T, F> {
+		WithWorld {
+			context: self.clone(),
+			query: Some(query),
+			result: Rc::new(RefCell::new(None))
+		}
+	}
+}
+
+/// A single threaded executor that polls every task once per update.
+/// Tasks are driven by the update clock of the app, so they pause together with the simulation.
+pub struct TaskExecutor {
+	tasks: Vec<Task>,
+	context: TaskContext,
+	next_handle: u64,
+	time: f64,
+	frame: u64
+}
+
+impl Default for TaskExecutor {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl TaskExecutor {
+	pub fn new() -> Self {
+		Self {
+			tasks: Vec::new(),
+			context: TaskContext {
+				commands: Rc::new(RefCell::new(Vec::new()))
+			},
+			next_handle: 0,
+			time: 0.0,
+			frame: 0
+		}
+	}
+
+	pub fn spawn<F: Future<Output = ()> + 'static>(&mut self, future: F) -> TaskHandle {
+		let handle = TaskHandle(self.next_handle);
+		self.next_handle += 1;
+		self.tasks.push(Task {
+			handle,
+			future: Box::pin(future)
+		});
+		handle
+	}
+
+	/// A handle the tasks can change the world through.
+	pub fn context(&self) -> TaskContext {
+		self.context.clone()
+	}
+
+	/// Drops the task without polling it again.
+	pub fn cancel(&mut self, handle: TaskHandle) {
+		self.tasks.retain(|task| task.handle != handle);
+	}
+
+	pub fn is_running(&self, handle: TaskHandle) -> bool {
+		self.tasks.iter().any(|task| task.handle == handle)
+	}
+
+	pub fn task_count(&self) -> usize {
+		self.tasks.len()
+	}
+
+	/// Advances the clock by `dt` seconds and polls every task once, then runs the commands they queued on `world`.
+	/// Finished tasks are removed.
+	pub fn update(&mut self, dt: f32, world: &mut World) {
+		self.time += dt as f64;
+		self.frame += 1;
+		TASK_TIME.with(|time| time.set(self.time));
+		TASK_FRAME.with(|frame| frame.set(self.frame));
+
+		let mut context = Context::from_waker(Waker::noop());
+		self.tasks.retain_mut(|task| task.future.as_mut().poll(&mut context).is_pending());
+
+		// taken out first, so commands can queue more for the next update
+		let commands = std::mem::take(&mut *self.context.commands.borrow_mut());
+		for command in commands {
+			command(world);
+		}
+	}
+}
+
+/// Completes after the given amount of update time has passed.
+pub fn wait_seconds(seconds: f32) -> WaitSeconds {
+	WaitSeconds {
+		seconds: seconds as f64,
+		until: None
+	}
+}
+
+/// Completes once `condition` returns true. The condition is checked once per update.
+pub fn wait_until<F: FnMut() -> bool + Unpin>(condition: F) -> WaitUntil<F> {
+	WaitUntil {
+		condition
+	}
+}
+
+/// Completes on the next update.
+pub fn next_frame() -> NextFrame {
+	NextFrame {
+		frame: None
+	}
+}
+
+pub struct WaitSeconds {
+	seconds: f64,
+	until: Option<f64>
+}
+
+impl Future for WaitSeconds {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+		let now = TASK_TIME.with(|time| time.get());
+		let seconds = self.seconds;
+		let until = *self.until.get_or_insert(now + seconds);
+
+		if now >= until {
+			Poll::Ready(())
+		} else {
+			Poll::Pending
+		}
+	}
+}
+
+pub struct WaitUntil<F> {
+	condition: F
+}
+
+impl<F: FnMut() -> bool + Unpin> Future for WaitUntil<F> {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+		if (self.condition)() {
+			Poll::Ready(())
+		} else {
+			Poll::Pending
+		}
+	}
+}
+
+pub struct NextFrame {
+	frame: Option<u64>
+}
+
+impl Future for NextFrame {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+		let current = TASK_FRAME.with(|frame| frame.get());
+
+		match self.frame {
+			Some(frame) if current > frame => Poll::Ready(()),
+			Some(_) => Poll::Pending,
+			None => {
+				self.frame = Some(current);
+				Poll::Pending
+			}
+		}
+	}
+}
+
+pub struct WithWorld<T, F> {
+	context: TaskContext,
+	query: Option<F>,
+	result: Rc<RefCell<Option<T>>>
+}
+
+impl<T: 'static, F: FnOnce(&mut World) -> T + Unpin + 'static> Future for WithWorld<T, F> {
+	type Output = T;
+
+	fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<T> {
+		if let Some(query) = self.query.take() {
+			let result = self.result.clone();
+			self.context.run(move |world| *result.borrow_mut() = Some(query(world)));
+			return Poll::Pending;
+		}
+		match self.result.borrow_mut().take() {
+			Some(result) => Poll::Ready(result),
+			None => Poll::Pending
+		}
+	}
+}
@@ -0,0 +1,235 @@
+use std::collections::HashSet;
+use comet_ecs::World;
+use comet_input::keyboard::Key;
+use comet_renderer::headless::HeadlessRenderer;
+use comet_renderer::renderer::Renderer;
+use winit::event::{Event, WindowEvent};
+use winit::window::WindowId;
+use crate::{App, ApplicationType, InputRecording, RecordedInput};
+
+/// The keys pressed by code instead of the keyboard, merged into the input queries of the `App`.
+pub(crate) struct SimulatedInput {
+	pressed: HashSet<Key>,
+	held: HashSet<Key>,
+	released: HashSet<Key>
+}
+
+impl SimulatedInput {
+	pub fn new() -> Self {
+		Self {
+			pressed: HashSet::new(),
+			held: HashSet::new(),
+			released: HashSet::new()
+		}
+	}
+
+	pub fn press(&mut self, key: Key) {
+		if self.held.insert(key) {
+			self.pressed.insert(key);
+		}
+	}
+
+	pub fn release(&mut self, key: Key) {
+		if self.held.remove(&key) {
+			self.released.insert(key);
+		}
+	}
+
+	/// Forgets the presses and releases of the last tick, the held keys stay down.
+	pub fn end_tick(&mut self) {
+		self.pressed.clear();
+		self.released.clear();
+	}
+
+	pub fn key_pressed(&self, key: Key) -> bool {
+		self.pressed.contains(&key)
+	}
+
+	pub fn key_held(&self, key: Key) -> bool {
+		self.held.contains(&key)
+	}
+
+	pub fn key_released(&self, key: Key) -> bool {
+		self.released.contains(&key)
+	}
+
+	pub fn apply(&mut self, input: RecordedInput) {
+		match input {
+			RecordedInput::Press(key) => self.press(key),
+			RecordedInput::Release(key) => self.release(key)
+		}
+	}
+}
+
+/// Runs the app loop without a window, one fixed update per tick, so gameplay can be tested.
+/// Inputs are scripted ahead of time for the tick they should happen on.
+///
+/// ```ignore
+/// let mut test = TestApp::new()
+///     .with_setup(setup)
+///     .press(0, Key::ArrowRight)
+///     .release(30, Key::ArrowRight);
+/// test.run(60, update, |app, tick| {
+///     assert!(app.world().get_component::<Transform2D>(player).position().x() >= tick as f32);
+/// });
+/// ```
+pub struct TestApp<'a> {
+	app: App<'a>,
+	renderer: HeadlessRenderer,
+	inputs: InputRecording,
+	time_stack: f32
+}
+
+impl<'a> TestApp<'a> {
+	/// A 2D app running deterministically with seed 0.
+	pub fn new() -> Self {
+		Self::from_app(App::new(ApplicationType::App2D).deterministic(0))
+	}
+
+	/// Tests an app configured by hand, e.g. with another seed or update rate.
+	pub fn from_app(app: App<'a>) -> Self {
+		let renderer = HeadlessRenderer::headless(1280, 720, app.dt());
+		Self {
+			app,
+			renderer,
+			inputs: InputRecording::new(),
+			time_stack: 0.0
+		}
+	}
+
+	pub fn with_setup(mut self, setup: fn(&mut App, &mut HeadlessRenderer)) -> Self {
+		setup(&mut self.app, &mut self.renderer);
+		self
+	}
+
+	/// Presses `key` at the start of tick `tick`. It stays held until it is released.
+	pub fn press(mut self, tick: u64, key: Key) -> Self {
+		self.inputs.press(tick, key);
+		self
+	}
+
+	/// Releases `key` at the start of tick `tick`.
+	pub fn release(mut self, tick: u64, key: Key) -> Self {
+		self.inputs.release(tick, key);
+		self
+	}
+
+	/// Replays the inputs of a run recorded with `--record-input`, on top of the scripted ones.
+	pub fn with_recording(mut self, recording: &InputRecording) -> Self {
+		for (tick, input) in recording.iter() {
+			self.inputs.push(tick, input);
+		}
+		self
+	}
+
+	/// Presses `key` for the single tick `tick`.
+	pub fn tap(self, tick: u64, key: Key) -> Self {
+		self.press(tick, key).release(tick + 1, key)
+	}
+
+	/// Runs one update with the inputs scripted for it.
+	pub fn tick(&mut self, update: fn(&mut App, &mut HeadlessRenderer, f32)) {
+		let tick = self.app.simulation_frame();
+		for input in self.inputs.inputs_at(tick) {
+			self.app.simulated_input().apply(*input);
+		}
+
+		self.renderer.update();
+		let time = self.app.dt();
+		self.app.step(&mut self.renderer, update, time);
+		self.renderer.prepare(self.app.world());
+		// the headless renderer never fails
+		let _ = self.renderer.render();
+	}
+
+	/// Hands `event` to the app the way the event loop of `App::run` does, running the updates that are due for it.
+	/// A deterministic app only steps on `WindowEvent::RedrawRequested`.
+	pub fn window_event(&mut self, event: WindowEvent, update: fn(&mut App, &mut HeadlessRenderer, f32)) {
+		// SAFETY: the id is only compared against the window of the app, which the test does not have
+		let window_id = unsafe { WindowId::dummy() };
+		self.app.advance(&Event::WindowEvent { window_id, event }, &mut self.renderer, update, &mut self.time_stack);
+	}
+
+	/// Runs `ticks` updates, calling `check` after each with the app and the number of the tick that just ran.
+	pub fn run(&mut self, ticks: u64, update: fn(&mut App, &mut HeadlessRenderer, f32), mut check: impl FnMut(&App, u64)) {
+		for _ in 0..ticks {
+			let tick = self.app.simulation_frame();
+			self.tick(update);
+			check(&self.app, tick);
+		}
+	}
+
+	/// The number of ticks that ran so far.
+	pub fn current_tick(&self) -> u64 {
+		self.app.simulation_frame()
+	}
+
+	pub fn app(&self) -> &App<'a> {
+		&self.app
+	}
+
+	pub fn app_mut(&mut self) -> &mut App<'a> {
+		&mut self.app
+	}
+
+	pub fn world(&self) -> &World {
+		self.app.world()
+	}
+
+	pub fn renderer(&mut self) -> &mut HeadlessRenderer {
+		&mut self.renderer
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use comet_ecs::Transform2D;
+	use comet_ecs::math::Vec2;
+	use winit::dpi::{PhysicalPosition, PhysicalSize};
+	use super::*;
+
+	fn setup(app: &mut App, _renderer: &mut HeadlessRenderer) {
+		app.world_mut().new_entity();
+	}
+
+	/// Walks right while the arrow key is held and jumps ahead when it is let go.
+	fn update(app: &mut App, _renderer: &mut HeadlessRenderer, _dt: f32) {
+		let (held, released) = (app.key_held(Key::ArrowRight), app.key_released(Key::ArrowRight));
+		let transform = app.world_mut().get_component_mut::<Transform2D>(0);
+		if held {
+			transform.translate(Vec2::new(1.0, 0.0));
+		}
+		if released {
+			transform.translate(Vec2::new(100.0, 0.0));
+		}
+	}
+
+	#[test]
+	fn scripted_keys_reach_the_update_on_their_ticks() {
+		let mut test = TestApp::new()
+			.with_setup(setup)
+			.press(2, Key::ArrowRight)
+			.release(5, Key::ArrowRight);
+		test.run(8, update, |app, tick| {
+			let expected = match tick {
+				0..=1 => 0.0,
+				2..=4 => (tick - 1) as f32,
+				_ => 103.0
+			};
+			assert_eq!(app.world().get_component::<Transform2D>(0).position().x(), expected, "tick {}", tick);
+		});
+	}
+
+	#[test]
+	fn deterministic_apps_step_once_per_frame_whatever_other_events_arrive() {
+		let mut test = TestApp::new().with_setup(setup);
+		for frame in 1..=3u64 {
+			test.window_event(WindowEvent::Focused(frame % 2 == 0), update);
+			test.window_event(WindowEvent::Resized(PhysicalSize::new(640, 480)), update);
+			test.window_event(WindowEvent::Moved(PhysicalPosition::new(frame as i32, 0)), update);
+			assert_eq!(test.current_tick(), frame - 1);
+			test.window_event(WindowEvent::RedrawRequested, update);
+			assert_eq!(test.current_tick(), frame);
+		}
+	}
+}
@@ -0,0 +1,136 @@
+use std::marker::PhantomData;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use comet_ecs::World;
+use comet_log::*;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+type WorldMessage = Box<dyn FnOnce(&mut World) + Send + 'static>;
+
+/// A fixed number of worker threads that run blocking jobs off the main thread.
+pub struct ThreadPool {
+	sender: Option<Sender<Job>>,
+	workers: Vec<JoinHandle<()>>
+}
+
+impl ThreadPool {
+	pub fn new(worker_count: usize) -> Self {
+		let worker_count = worker_count.max(1);
+		let (sender, receiver) = channel::<Job>();
+		let receiver = Arc::new(Mutex::new(receiver));
+
+		let workers = (0..worker_count).map(|i| {
+			let receiver = receiver.clone();
+			std::thread::Builder::new()
+				.name(format!("comet-worker-{}", i))
+				.spawn(move || loop {
+					let job = match receiver.lock() {
+						Ok(receiver) => receiver.recv(),
+						Err(_) => break
+					};
+					match job {
+						Ok(job) => job(),
+						Err(_) => break
+					}
+				})
+				.expect("Failed to spawn worker thread")
+		}).collect();
+
+		Self {
+			sender: Some(sender),
+			workers
+		}
+	}
+
+	/// Creates a pool with one worker less than the available cores, leaving one for the main thread.
+	pub fn with_default_size() -> Self {
+		let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(2);
+		Self::new(cores.saturating_sub(1))
+	}
+
+	pub fn worker_count(&self) -> usize {
+		self.workers.len()
+	}
+
+	pub fn execute<F: FnOnce() + Send + 'static>(&self, job: F) {
+		if let Some(sender) = &self.sender {
+			if sender.send(Box::new(job)).is_err() {
+				error!("Failed to queue job, the thread pool is shut down");
+			}
+		}
+	}
+}
+
+impl Drop for ThreadPool {
+	fn drop(&mut self) {
+		// Closing the channel lets every worker finish its current job and stop.
+		self.sender.take();
+		for worker in self.workers.drain(..) {
+			let _ = worker.join();
+		}
+	}
+}
+
+/// A handle that can be cloned into other threads to send events of type `T` into the `World`.
+/// The events are delivered at the start of the next update on the main thread.
+pub struct EventSender<T> {
+	sender: Sender<WorldMessage>,
+	_marker: PhantomData<fn(T)>
+}
+
+impl<T: Send + 'static> EventSender<T> {
+	pub(crate) fn new(sender: Sender<WorldMessage>) -> Self {
+		Self {
+			sender,
+			_marker: PhantomData
+		}
+	}
+
+	/// Sends the event. Returns false if the app is no longer running.
+	pub fn send(&self, event: T) -> bool {
+		self.sender.send(Box::new(move |world: &mut World| world.send_event(event))).is_ok()
+	}
+}
+
+impl<T> Clone for EventSender<T> {
+	fn clone(&self) -> Self {
+		Self {
+			sender: self.sender.clone(),
+			_marker: PhantomData
+		}
+	}
+}
+
+/// The receiving end of all `EventSender`s of an app.
+pub(crate) struct EventChannel {
+	sender: Sender<WorldMessage>,
+	receiver: Receiver<WorldMessage>
+}
+
+impl EventChannel {
+	pub(crate) fn new() -> Self {
+		let (sender, receiver) = channel();
+		Self {
+			sender,
+			receiver
+		}
+	}
+
+	pub(crate) fn sender<T: Send + 'static>(&self) -> EventSender<T> {
+		EventSender::new(self.sender.clone())
+	}
+
+	/// Queues an event from the main thread, so it is delivered at the start of the next update
+	/// together with the ones from other threads instead of piling up in the world while no update runs.
+	pub(crate) fn send<T: Send + 'static>(&self, event: T) {
+		let _ = self.sender.send(Box::new(move |world: &mut World| world.send_event(event)));
+	}
+
+	/// Moves every event that arrived since the last call into the world.
+	pub(crate) fn drain_into(&self, world: &mut World) {
+		while let Ok(message) = self.receiver.try_recv() {
+			message(world);
+		}
+	}
+}
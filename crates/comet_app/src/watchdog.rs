@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use comet_log::*;
+use comet_log::throttle::{with_suppressed, Throttle};
+
+/// The time every system of one fixed update took, measured lap by lap.
+pub(crate) struct SystemTimings {
+	last: Instant,
+	laps: Vec<(&'static str, Duration)>
+}
+
+impl SystemTimings {
+	pub(crate) fn start() -> Self {
+		Self {
+			last: Instant::now(),
+			laps: Vec::new()
+		}
+	}
+
+	/// Records the time since the last lap as the time `system` took.
+	pub(crate) fn lap(&mut self, system: &'static str) {
+		let now = Instant::now();
+		self.laps.push((system, now.duration_since(self.last)));
+		self.last = now;
+	}
+}
+
+/// Warns about systems that take longer than their budget in a fixed update, at most once a second per system.
+/// Set up with `App::with_system_budget` and `App::with_budget_for`.
+#[derive(Default)]
+pub(crate) struct Watchdog {
+	budget: Option<Duration>,
+	budgets: HashMap<&'static str, Duration>,
+	throttles: HashMap<&'static str, Throttle>
+}
+
+impl Watchdog {
+	pub(crate) fn new() -> Self {
+		Self::default()
+	}
+
+	pub(crate) fn set_budget(&mut self, budget: Option<Duration>) {
+		self.budget = budget;
+	}
+
+	pub(crate) fn set_budget_for(&mut self, system: &'static str, budget: Duration) {
+		self.budgets.insert(system, budget);
+	}
+
+	pub(crate) fn is_watching(&self) -> bool {
+		self.budget.is_some() || !self.budgets.is_empty()
+	}
+
+	pub(crate) fn check(&mut self, timings: &SystemTimings) {
+		for (system, time) in &timings.laps {
+			let Some(budget) = self.budgets.get(system).copied().or(self.budget) else {
+				continue;
+			};
+			if *time <= budget {
+				continue;
+			}
+			let throttle = self.throttles.entry(system).or_default();
+			if let Some(suppressed) = throttle.pass(Some(Duration::from_secs(1))) {
+				warn!("{}", with_suppressed(format!(
+					"The system {} took {:.2} ms, over its budget of {:.2} ms",
+					system,
+					time.as_secs_f64() * 1000.0,
+					budget.as_secs_f64() * 1000.0
+				), suppressed));
+			}
+		}
+	}
+}
+
+/// Logs where the time from starting the app until the end of setup went, by profiled scope, longest first.
+/// Only scopes recorded with `profile_scope!` are listed, e.g. "renderer creation", "shader compile" and "atlas packing".
+pub(crate) fn report_startup(started: Instant) {
+	profiler::new_frame();
+	info!("Startup took {:.1} ms:", started.elapsed().as_secs_f64() * 1000.0);
+	for (name, time) in profiler::last_frame_totals() {
+		info!("    {:<24} {:>8.1} ms", name, time.as_secs_f64() * 1000.0);
+	}
+}
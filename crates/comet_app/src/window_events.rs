@@ -0,0 +1,81 @@
+/// Sent into the `World` when the window gains (`true`) or loses (`false`) the keyboard focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowFocused(pub bool);
+
+/// Sent into the `World` when the window becomes fully hidden (`true`), e.g. when minimized, or visible again (`false`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowOccluded(pub bool);
+
+/// Sent into the `World` when the platform suspends the app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppSuspended;
+
+/// Sent into the `World` when the platform resumes the app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppResumed;
+
+/// Describes how the app behaves while it is in the background.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FocusPolicy {
+	/// Stops calling the update function while the window is unfocused.
+	pub pause_on_focus_loss: bool,
+	/// The updates per second used while the window is unfocused. `None` keeps the regular update rate.
+	pub background_update_rate: Option<u32>,
+	/// Mutes the audio while the window is minimized or otherwise hidden.
+	pub mute_when_minimized: bool
+}
+
+impl FocusPolicy {
+	/// Keeps the app running as if it was focused.
+	pub fn keep_running() -> Self {
+		Self {
+			pause_on_focus_loss: false,
+			background_update_rate: None,
+			mute_when_minimized: false
+		}
+	}
+
+	/// Pauses the simulation on focus loss and mutes the audio when minimized.
+	pub fn pause_in_background() -> Self {
+		Self {
+			pause_on_focus_loss: true,
+			background_update_rate: None,
+			mute_when_minimized: true
+		}
+	}
+}
+
+impl Default for FocusPolicy {
+	fn default() -> Self {
+		Self::keep_running()
+	}
+}
+
+/// Sent into the `World` when a file is dropped onto the window.
+/// Dropping multiple files at once sends one event per file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDropped(pub std::path::PathBuf);
+
+/// Sent into the `World` while a file is dragged over the window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileHovered(pub std::path::PathBuf);
+
+/// Sent into the `World` when the hovered files are dragged away from the window without being dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileHoverCancelled;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchPhase {
+	Started,
+	Moved,
+	Ended,
+	Cancelled
+}
+
+/// Sent for every touch on a touch screen. `id` stays the same for one finger until the touch ends.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchInput {
+	pub id: u64,
+	pub phase: TouchPhase,
+	pub position: comet_ecs::math::Vec2
+}
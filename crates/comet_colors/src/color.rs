@@ -0,0 +1,180 @@
+use crate::{sRgba, ColorError, InterpolationSpace, LinearRgba, Oklaba};
+
+/// Functionality shared by all color types.
+/// Everything goes through `LinearRgba`, so the helpers work the same no matter which space a color is stored in.
+pub trait Color: Sized + Clone {
+	fn to_linear(&self) -> LinearRgba;
+
+	fn from_linear(linear: LinearRgba) -> Self;
+
+	/// The opacity from 0 (transparent) to 1 (opaque).
+	fn alpha(&self) -> f32;
+
+	fn with_alpha(self, alpha: f32) -> Self;
+
+	/// The color as 8 bit sRGB channels, the way images store it.
+	fn to_u8_array(&self) -> [u8; 4] {
+		let rgba = self.to_linear().to_rgba8();
+		[rgba.red(), rgba.green(), rgba.blue(), rgba.alpha()]
+	}
+
+	fn from_u8_array(rgba: [u8; 4]) -> Self {
+		Self::from_linear(sRgba::<u8>::rgba(rgba[0], rgba[1], rgba[2], rgba[3]).to_linear())
+	}
+
+	/// The color as linear RGBA, the way shaders expect it in vertex buffers and uniforms.
+	fn to_f32_array(&self) -> [f32; 4] {
+		let linear = self.to_linear();
+		[linear.red(), linear.green(), linear.blue(), linear.alpha()]
+	}
+
+	fn from_f32_array(rgba: [f32; 4]) -> Self {
+		let [red, green, blue, alpha] = rgba.map(|value| value.max(0.0));
+		Self::from_linear(LinearRgba::new(red, green, blue, alpha.min(1.0)))
+	}
+
+	/// Parses `#RGB`, `#RGBA`, `#RRGGBB` or `#RRGGBBAA`, the `#` being optional.
+	fn from_hex(hex: &str) -> Result<Self, ColorError> {
+		let digits = hex.trim().trim_start_matches('#');
+		let invalid = || ColorError::InvalidHexDigit(hex.to_string());
+		if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+			return Err(invalid());
+		}
+
+		let channel = |i: usize, width: usize| {
+			let value = u8::from_str_radix(&digits[i * width..(i + 1) * width], 16).map_err(|_| invalid())?;
+			// a single digit is repeated, so "f" means "ff"
+			Ok(if width == 1 { value * 17 } else { value })
+		};
+		let rgba = match digits.len() {
+			3 => sRgba::<u8>::rgb(channel(0, 1)?, channel(1, 1)?, channel(2, 1)?),
+			4 => sRgba::<u8>::rgba(channel(0, 1)?, channel(1, 1)?, channel(2, 1)?, channel(3, 1)?),
+			6 => sRgba::<u8>::rgb(channel(0, 2)?, channel(1, 2)?, channel(2, 2)?),
+			8 => sRgba::<u8>::rgba(channel(0, 2)?, channel(1, 2)?, channel(2, 2)?, channel(3, 2)?),
+			len => return Err(ColorError::InvalidHexLength(len))
+		};
+		Ok(Self::from_linear(rgba.to_linear()))
+	}
+
+	/// Formats the color as `#rrggbb`, or `#rrggbbaa` if it is not fully opaque.
+	fn to_hex(&self) -> String {
+		let rgba = self.to_linear().to_rgba8();
+		if rgba.alpha() == 255 {
+			format!("#{:02x}{:02x}{:02x}", rgba.red(), rgba.green(), rgba.blue())
+		}
+		else {
+			format!("#{:02x}{:02x}{:02x}{:02x}", rgba.red(), rgba.green(), rgba.blue(), rgba.alpha())
+		}
+	}
+
+	/// Blends `self` and `other` in the given space, `t` = 0 being `self` and `t` = 1 being `other`.
+	fn mix(&self, other: &Self, t: f32, space: InterpolationSpace) -> Self {
+		Self::from_linear(mix_linear(&self.to_linear(), &other.to_linear(), t, space))
+	}
+
+	/// Makes the color brighter by `amount` in perceptual lightness (0..1).
+	fn lighten(&self, amount: f32) -> Self {
+		map_oklab(self, |lightness, a, b| (lightness + amount, a, b))
+	}
+
+	fn darken(&self, amount: f32) -> Self {
+		self.lighten(-amount)
+	}
+
+	/// Makes the color more vivid, e.g. 0.5 increases the chroma by half.
+	fn saturate(&self, amount: f32) -> Self {
+		let factor = (1.0 + amount).max(0.0);
+		map_oklab(self, |lightness, a, b| (lightness, a * factor, b * factor))
+	}
+
+	/// Moves the color towards gray, 1.0 removes all of its chroma.
+	fn desaturate(&self, amount: f32) -> Self {
+		self.saturate(-amount)
+	}
+
+	/// The relative luminance as defined by WCAG, from 0 (black) to 1 (white).
+	fn luminance(&self) -> f32 {
+		let linear = self.to_linear();
+		0.2126 * linear.red() + 0.7152 * linear.green() + 0.0722 * linear.blue()
+	}
+
+	/// The WCAG contrast ratio between two colors, from 1 (no contrast) to 21 (black on white).
+	/// Text should have a ratio of at least 4.5 to its background.
+	fn contrast_ratio<C: Color>(&self, other: &C) -> f32 {
+		let a = self.luminance();
+		let b = other.luminance();
+		(a.max(b) + 0.05) / (a.min(b) + 0.05)
+	}
+}
+
+fn map_oklab<C: Color, F: FnOnce(f32, f32, f32) -> (f32, f32, f32)>(color: &C, f: F) -> C {
+	let oklab = Oklaba::from_linear(color.to_linear());
+	let (lightness, a, b) = f(oklab.lightness(), oklab.a(), oklab.b());
+	C::from_linear(Oklaba::new(lightness.clamp(0.0, 1.0), a.clamp(-1.0, 1.0), b.clamp(-1.0, 1.0), oklab.alpha().clamp(0.0, 1.0)).to_linear())
+}
+
+pub(crate) fn mix_linear(a: &LinearRgba, b: &LinearRgba, t: f32, space: InterpolationSpace) -> LinearRgba {
+	let lerp = |a: f32, b: f32| a + (b - a) * t;
+	let alpha = lerp(a.alpha(), b.alpha()).clamp(0.0, 1.0);
+
+	match space {
+		InterpolationSpace::Linear => LinearRgba::new(
+			lerp(a.red(), b.red()).max(0.0),
+			lerp(a.green(), b.green()).max(0.0),
+			lerp(a.blue(), b.blue()).max(0.0),
+			alpha
+		),
+		InterpolationSpace::Srgb => {
+			let a = sRgba::<f32>::from_linear(a.clone());
+			let b = sRgba::<f32>::from_linear(b.clone());
+			LinearRgba::from_rgba(sRgba::<f32>::new(
+				lerp(a.red(), b.red()).clamp(0.0, 1.0),
+				lerp(a.green(), b.green()).clamp(0.0, 1.0),
+				lerp(a.blue(), b.blue()).clamp(0.0, 1.0),
+				alpha
+			))
+		}
+		InterpolationSpace::Oklab => {
+			let a = Oklaba::from_linear(a.clone());
+			let b = Oklaba::from_linear(b.clone());
+			Oklaba::new(
+				lerp(a.lightness(), b.lightness()).clamp(0.0, 1.0),
+				lerp(a.a(), b.a()).clamp(-1.0, 1.0),
+				lerp(a.b(), b.b()).clamp(-1.0, 1.0),
+				alpha
+			).to_linear()
+		}
+	}
+}
+
+/// Implements `Color` and the conversion into `[f32; 4]` for a type with an `alpha: f32` field and inherent `to_linear`, given how to build it from linear RGB.
+macro_rules! impl_color {
+	($color:ty, $from_linear:expr) => {
+		impl crate::Color for $color {
+			fn to_linear(&self) -> crate::LinearRgba {
+				<$color>::to_linear(self)
+			}
+
+			fn from_linear(linear: crate::LinearRgba) -> Self {
+				$from_linear(linear)
+			}
+
+			fn alpha(&self) -> f32 {
+				self.alpha
+			}
+
+			fn with_alpha(mut self, alpha: f32) -> Self {
+				self.alpha = alpha.clamp(0.0, 1.0);
+				self
+			}
+		}
+
+		impl From<$color> for [f32; 4] {
+			fn from(color: $color) -> Self {
+				crate::Color::to_f32_array(&color)
+			}
+		}
+	};
+}
+
+pub(crate) use impl_color;
@@ -0,0 +1,84 @@
+/// A 3x3 matrix in row-major order that converts a color from one space into another.
+pub type ColorMatrix = [[f32; 3]; 3];
+
+/// The XYZ values of the D65 white point, used by sRGB, Display-P3 and Rec.2020.
+pub const D65: [f32; 3] = [0.95047, 1.0, 1.08883];
+/// The XYZ values of the D50 white point, used by print and ICC profiles.
+pub const D50: [f32; 3] = [0.96422, 1.0, 0.82521];
+
+pub const SRGB_TO_XYZ: ColorMatrix = [
+	[0.4124564, 0.3575761, 0.1804375],
+	[0.2126729, 0.7151522, 0.0721750],
+	[0.0193339, 0.1191920, 0.9503041]
+];
+
+pub const XYZ_TO_SRGB: ColorMatrix = [
+	[3.2404542, -1.5371385, -0.4985314],
+	[-0.9692660, 1.8760108, 0.0415560],
+	[0.0556434, -0.2040259, 1.0572252]
+];
+
+pub const DISPLAY_P3_TO_XYZ: ColorMatrix = [
+	[0.4865709, 0.2656677, 0.1982173],
+	[0.2289746, 0.6917385, 0.0792869],
+	[0.0000000, 0.0451134, 1.0439444]
+];
+
+pub const XYZ_TO_DISPLAY_P3: ColorMatrix = [
+	[2.4934969, -0.9313836, -0.4027108],
+	[-0.8294890, 1.7626641, 0.0236247],
+	[0.0358458, -0.0761724, 0.9568845]
+];
+
+pub const REC2020_TO_XYZ: ColorMatrix = [
+	[0.6369580, 0.1446169, 0.1688810],
+	[0.2627002, 0.6779981, 0.0593017],
+	[0.0000000, 0.0280727, 1.0609851]
+];
+
+pub const XYZ_TO_REC2020: ColorMatrix = [
+	[1.7166512, -0.3556708, -0.2533663],
+	[-0.6666844, 1.6164812, 0.0157685],
+	[0.0176399, -0.0427706, 0.9421031]
+];
+
+/// Converts XYZ into the cone response space of the Bradford chromatic adaptation.
+pub const BRADFORD: ColorMatrix = [
+	[0.8951, 0.2664, -0.1614],
+	[-0.7502, 1.7135, 0.0367],
+	[0.0389, -0.0685, 1.0296]
+];
+
+pub const BRADFORD_INVERSE: ColorMatrix = [
+	[0.9869929, -0.1470543, 0.1599627],
+	[0.4323053, 0.5183603, 0.0492912],
+	[-0.0085287, 0.0400428, 0.9684867]
+];
+
+/// Applies `matrix` to the three channels of a color.
+pub fn transform(matrix: &ColorMatrix, channels: [f32; 3]) -> [f32; 3] {
+	matrix.map(|row| row[0] * channels[0] + row[1] * channels[1] + row[2] * channels[2])
+}
+
+/// The matrix doing `b` first and `a` second.
+pub fn multiply(a: &ColorMatrix, b: &ColorMatrix) -> ColorMatrix {
+	let mut result = [[0.0; 3]; 3];
+	for (row, result_row) in result.iter_mut().enumerate() {
+		for (column, value) in result_row.iter_mut().enumerate() {
+			*value = (0..3).map(|i| a[row][i] * b[i][column]).sum();
+		}
+	}
+	result
+}
+
+/// The Bradford matrix that moves XYZ colors seen under the white point `from` to how they look under `to`.
+pub fn chromatic_adaptation(from: [f32; 3], to: [f32; 3]) -> ColorMatrix {
+	let source = transform(&BRADFORD, from);
+	let destination = transform(&BRADFORD, to);
+	let scale = [
+		[destination[0] / source[0], 0.0, 0.0],
+		[0.0, destination[1] / source[1], 0.0],
+		[0.0, 0.0, destination[2] / source[2]]
+	];
+	multiply(&BRADFORD_INVERSE, &multiply(&scale, &BRADFORD))
+}
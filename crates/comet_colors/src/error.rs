@@ -0,0 +1,85 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColorError {
+	/// A hex color needs 3, 4, 6 or 8 digits.
+	InvalidHexLength(usize),
+	/// The string contains something that is not a hex digit.
+	InvalidHexDigit(String)
+}
+
+impl fmt::Display for ColorError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ColorError::InvalidHexLength(len) => write!(f, "A hex color needs 3, 4, 6 or 8 digits, but {} were given", len),
+			ColorError::InvalidHexDigit(hex) => write!(f, "{} is not a valid hex color", hex)
+		}
+	}
+}
+
+impl std::error::Error for ColorError {}
+
+#[derive(Debug)]
+pub enum PaletteError {
+	Io(std::io::Error),
+	/// The extension of the file is not one of `gpl`, `json` or `png`.
+	UnsupportedFormat(String),
+	/// The PNG could not be decoded.
+	Image(String),
+	/// The file is malformed, with a description of what went wrong.
+	Parse(String),
+	Color(ColorError)
+}
+
+impl fmt::Display for PaletteError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			PaletteError::Io(e) => write!(f, "Failed to read palette file: {}", e),
+			PaletteError::UnsupportedFormat(path) => write!(f, "{} is not a gpl, json or png palette", path),
+			PaletteError::Image(e) => write!(f, "Failed to decode palette image: {}", e),
+			PaletteError::Parse(e) => write!(f, "Failed to parse palette: {}", e),
+			PaletteError::Color(e) => write!(f, "Invalid color in palette: {}", e)
+		}
+	}
+}
+
+impl std::error::Error for PaletteError {}
+
+impl From<std::io::Error> for PaletteError {
+	fn from(e: std::io::Error) -> Self {
+		PaletteError::Io(e)
+	}
+}
+
+impl From<ColorError> for PaletteError {
+	fn from(e: ColorError) -> Self {
+		PaletteError::Color(e)
+	}
+}
+
+#[derive(Debug)]
+pub enum LutError {
+	Io(std::io::Error),
+	/// The PNG could not be decoded.
+	Image(String),
+	/// The width and height of the image, which is not a strip of square slices.
+	InvalidSize(u32, u32)
+}
+
+impl fmt::Display for LutError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			LutError::Io(e) => write!(f, "Failed to read LUT file: {}", e),
+			LutError::Image(e) => write!(f, "Failed to decode LUT image: {}", e),
+			LutError::InvalidSize(width, height) => write!(f, "A LUT strip of {}x{} pixels is not a row of square slices, e.g. 256x16 or 1024x32", width, height)
+		}
+	}
+}
+
+impl std::error::Error for LutError {}
+
+impl From<std::io::Error> for LutError {
+	fn from(e: std::io::Error) -> Self {
+		LutError::Io(e)
+	}
+}
@@ -0,0 +1,161 @@
+use crate::{color::mix_linear, sRgba, LinearRgba};
+
+/// The color space a `Gradient` blends its stops in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InterpolationSpace {
+	/// Blends the gamma encoded values, like most image editors do.
+	Srgb,
+	/// Blends the physical light values, which keeps the brightness of the mix correct.
+	#[default]
+	Linear,
+	/// Blends in a perceptual space, giving the most even looking transitions.
+	Oklab
+}
+
+/// A color at a position on a `Gradient`.
+/// The easing shapes the transition from this stop to the next one.
+#[derive(Debug, Clone)]
+pub struct ColorStop {
+	position: f32,
+	color: LinearRgba,
+	easing: fn(f32) -> f32
+}
+
+impl ColorStop {
+	pub fn new(position: f32, color: LinearRgba) -> Self {
+		Self {
+			position,
+			color,
+			easing: linear
+		}
+	}
+
+	pub fn with_easing(mut self, easing: fn(f32) -> f32) -> Self {
+		self.easing = easing;
+		self
+	}
+
+	pub fn position(&self) -> f32 {
+		self.position
+	}
+
+	pub fn color(&self) -> &LinearRgba {
+		&self.color
+	}
+
+	pub fn easing(&self) -> fn(f32) -> f32 {
+		self.easing
+	}
+}
+
+fn linear(t: f32) -> f32 {
+	t
+}
+
+/// A sequence of colors that can be sampled anywhere between 0 and 1.
+/// Useful for particle colors over their lifetime, sky tints over the day or health bars.
+#[derive(Debug, Clone, Default)]
+pub struct Gradient {
+	stops: Vec<ColorStop>,
+	space: InterpolationSpace
+}
+
+impl Gradient {
+	pub fn new(space: InterpolationSpace) -> Self {
+		Self {
+			stops: Vec::new(),
+			space
+		}
+	}
+
+	/// Spreads the colors evenly from 0 to 1.
+	pub fn from_colors(colors: Vec<LinearRgba>, space: InterpolationSpace) -> Self {
+		let last = (colors.len().max(2) - 1) as f32;
+		Self {
+			stops: colors.into_iter()
+				.enumerate()
+				.map(|(i, color)| ColorStop::new(i as f32 / last, color))
+				.collect(),
+			space
+		}
+	}
+
+	/// Adds a stop with linear easing to the gradient.
+	pub fn with_stop(self, position: f32, color: LinearRgba) -> Self {
+		self.with_color_stop(ColorStop::new(position, color))
+	}
+
+	pub fn with_color_stop(mut self, stop: ColorStop) -> Self {
+		self.add_stop(stop);
+		self
+	}
+
+	/// Adds a stop, keeping the stops ordered by their position.
+	pub fn add_stop(&mut self, stop: ColorStop) {
+		let index = self.stops.partition_point(|other| other.position <= stop.position);
+		self.stops.insert(index, stop);
+	}
+
+	pub fn remove_stop(&mut self, index: usize) -> ColorStop {
+		self.stops.remove(index)
+	}
+
+	pub fn stops(&self) -> &[ColorStop] {
+		&self.stops
+	}
+
+	pub fn space(&self) -> InterpolationSpace {
+		self.space
+	}
+
+	pub fn set_space(&mut self, space: InterpolationSpace) {
+		self.space = space;
+	}
+
+	pub fn with_space(mut self, space: InterpolationSpace) -> Self {
+		self.space = space;
+		self
+	}
+
+	/// The color at `t`. Before the first and after the last stop the gradient keeps the color of that stop.
+	/// An empty gradient is transparent.
+	pub fn sample(&self, t: f32) -> LinearRgba {
+		let (first, last) = match (self.stops.first(), self.stops.last()) {
+			(Some(first), Some(last)) => (first, last),
+			_ => return LinearRgba::new(0.0, 0.0, 0.0, 0.0)
+		};
+
+		if t <= first.position {
+			return first.color.clone();
+		}
+		if t >= last.position {
+			return last.color.clone();
+		}
+
+		let next = self.stops.partition_point(|stop| stop.position <= t);
+		let from = &self.stops[next - 1];
+		let to = &self.stops[next];
+		let local = (t - from.position) / (to.position - from.position).max(f32::EPSILON);
+		mix_linear(&from.color, &to.color, (from.easing)(local.clamp(0.0, 1.0)), self.space)
+	}
+
+	/// The perceptually uniform viridis colormap, from dark purple over teal to yellow.
+	pub fn viridis() -> Self {
+		Self::from_hex_colors(&[0x440154, 0x482878, 0x3e4989, 0x31688e, 0x26828e, 0x1f9e89, 0x35b779, 0x6ece58, 0xb5de2b, 0xfde725], InterpolationSpace::Srgb)
+	}
+
+	/// Black over red and yellow to white, like glowing metal or fire.
+	pub fn heat() -> Self {
+		Self::from_hex_colors(&[0x000000, 0x8b0000, 0xff4500, 0xffcc00, 0xffffff], InterpolationSpace::Linear)
+	}
+
+	fn from_hex_colors(colors: &[u32], space: InterpolationSpace) -> Self {
+		Self::from_colors(
+			colors.iter()
+				.map(|hex| sRgba::<u8>::new((hex >> 16) as u8, (hex >> 8) as u8, *hex as u8, 255).to_linear())
+				.collect(),
+			space
+		)
+	}
+}
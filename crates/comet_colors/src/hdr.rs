@@ -0,0 +1,82 @@
+use crate::{Color, LinearRgba};
+
+/// A linear color that can be brighter than white, e.g. for glowing sprites, light sources and bloom.
+/// The renderer brings it back into the displayable range with tonemapping.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hdr {
+	red: f32,
+	green: f32,
+	blue: f32,
+	alpha: f32
+}
+
+impl Hdr {
+	pub fn new(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
+		assert!(red >= 0.0 && green >= 0.0 && blue >= 0.0 && (0.0..=1.0).contains(&alpha), "Red needs to be positive\nGreen needs to be positive\nBlue needs to be positive\nAlpha needs to be in range 0..1");
+		Self {
+			red,
+			green,
+			blue,
+			alpha
+		}
+	}
+
+	/// Any color made `intensity` times as bright.
+	pub fn from_color(color: impl Color, intensity: f32) -> Self {
+		let linear = color.to_linear();
+		let intensity = intensity.max(0.0);
+		Self {
+			red: linear.red() * intensity,
+			green: linear.green() * intensity,
+			blue: linear.blue() * intensity,
+			alpha: linear.alpha()
+		}
+	}
+
+	pub fn red(&self) -> f32 {
+		self.red
+	}
+
+	pub fn green(&self) -> f32 {
+		self.green
+	}
+
+	pub fn blue(&self) -> f32 {
+		self.blue
+	}
+
+	pub fn alpha(&self) -> f32 {
+		self.alpha
+	}
+
+	/// The value of the brightest channel. Everything above 1.0 is brighter than white.
+	pub fn intensity(&self) -> f32 {
+		self.red.max(self.green).max(self.blue)
+	}
+
+	/// Scales the color so its brightest channel has the given value.
+	pub fn with_intensity(&self, intensity: f32) -> Self {
+		let current = self.intensity();
+		if current <= 0.0 {
+			return self.clone();
+		}
+		Self::from_color(self.to_linear(), intensity.max(0.0) / current)
+	}
+
+	pub fn from_linear(linear: LinearRgba) -> Self {
+		Self {
+			red: linear.red(),
+			green: linear.green(),
+			blue: linear.blue(),
+			alpha: linear.alpha()
+		}
+	}
+
+	/// Keeps the values above 1.0, unlike the conversions into the other color spaces.
+	pub fn to_linear(&self) -> LinearRgba {
+		LinearRgba::new(self.red, self.green, self.blue, self.alpha)
+	}
+}
+
+crate::color::impl_color!(Hdr, Hdr::from_linear);
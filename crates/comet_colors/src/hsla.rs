@@ -1,6 +1,7 @@
 use crate::{sRgba, Hsva, Hwba, Laba, Lcha, LinearRgba, Oklaba, Oklcha, Xyza};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hsla {
 	hue: f32,
 	saturation: f32,
@@ -98,4 +99,6 @@ impl Hsla {
 	pub fn to_oklcha(&self) -> Oklcha {
 		self.to_oklaba().to_oklcha()
 	}
-}
\ No newline at end of file
+}
+
+crate::color::impl_color!(Hsla, |linear: LinearRgba| linear.to_hsla());
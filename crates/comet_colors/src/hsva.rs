@@ -1,6 +1,7 @@
 use crate::{sRgba, Hsla, Hwba, Laba, Lcha, LinearRgba, Oklaba, Oklcha, Xyza};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hsva {
 	hue: f32,
 	saturation: f32,
@@ -96,4 +97,6 @@ impl Hsva {
 	pub fn to_oklcha(&self) -> Oklcha {
 		self.to_oklaba().to_oklcha()
 	}
-}
\ No newline at end of file
+}
+
+crate::color::impl_color!(Hsva, |linear: LinearRgba| linear.to_hsva());
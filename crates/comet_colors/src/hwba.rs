@@ -1,6 +1,7 @@
 use crate::{sRgba, Hsla, Hsva, Laba, Lcha, LinearRgba, Oklaba, Oklcha, Xyza};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hwba {
 	hue: f32,
 	whiteness: f32,
@@ -120,7 +121,7 @@ impl Hwba {
 	pub fn to_hsva(&self) -> Hsva {
 		Hsva::new(
 			self.hue,
-			1.0 - self.hue / (1.0 - self.blackness),
+			if self.blackness >= 1.0 { 0.0 } else { (1.0 - self.whiteness / (1.0 - self.blackness)).clamp(0.0, 1.0) },
 			1.0 - self.blackness,
 			self.alpha
 		)
@@ -154,4 +155,6 @@ impl Hwba {
 		self.to_oklaba().to_oklcha()
 	}
 
-}
\ No newline at end of file
+}
+
+crate::color::impl_color!(Hwba, |linear: LinearRgba| linear.to_hwba());
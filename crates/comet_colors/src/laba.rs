@@ -1,6 +1,7 @@
 use crate::{sRgba, Hsla, Hsva, Hwba, Lcha, LinearRgba, Oklaba, Oklcha, Xyza};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Laba {
 	lightness: f32,
 	a: f32,
@@ -140,4 +141,6 @@ impl Laba {
 	pub fn to_hsla(&self) -> Hsla {
 		self.to_hsva().to_hsla()
 	}
-}
\ No newline at end of file
+}
+
+crate::color::impl_color!(Laba, |linear: LinearRgba| linear.to_laba());
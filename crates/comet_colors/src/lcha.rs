@@ -1,6 +1,7 @@
 use crate::{sRgba, Hsla, Hsva, Hwba, Laba, LinearRgba, Oklaba, Oklcha, Xyza};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Lcha {
 	lightness: f32,
 	chroma: f32,
@@ -90,4 +91,6 @@ impl Lcha {
 		self.to_hsva().to_hsla()
 	}
 
-}
\ No newline at end of file
+}
+
+crate::color::impl_color!(Lcha, |linear: LinearRgba| linear.to_lcha());
@@ -1,4 +1,5 @@
 pub use comet_math as math;
+pub mod color_space;
 pub use linear_rgba::*;
 pub use rgba::*;
 pub use hwba::*;
@@ -9,6 +10,14 @@ pub use laba::*;
 pub use lcha::*;
 pub use oklaba::*;
 pub use oklcha::*;
+pub use gradient::*;
+pub use color::*;
+pub use hdr::*;
+pub use display_p3::*;
+pub use rec2020::*;
+pub use palette::*;
+pub use lut::*;
+pub use error::*;
 
 mod rgba;
 mod linear_rgba;
@@ -19,4 +28,13 @@ mod xyza;
 mod laba;
 mod lcha;
 mod oklaba;
-mod oklcha;
\ No newline at end of file
+mod oklcha;
+mod gradient;
+mod color;
+mod hdr;
+mod display_p3;
+mod rec2020;
+mod palette;
+mod lut;
+mod named;
+mod error;
\ No newline at end of file
@@ -1,7 +1,10 @@
 use wgpu::Color;
-use crate::{sRgba, Hsla, Hsva, Hwba, Laba, Lcha, Oklaba, Oklcha, Xyza};
+use crate::{color_space::{transform, SRGB_TO_XYZ}, sRgba, Hsla, Hsva, Hwba, Laba, Lcha, Oklaba, Oklcha, Xyza};
 
+/// Linear light RGB. Values above 1.0 are brighter than white and need tonemapping to be displayed,
+/// conversions into other color spaces clip them.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LinearRgba {
 	red: f32,
 	green: f32,
@@ -11,7 +14,7 @@ pub struct LinearRgba {
 
 impl LinearRgba {
 	pub fn new(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
-		assert!((0.0..=1.0).contains(&red) && (0.0..=1.0).contains(&green) && (0.0..=1.0).contains(&blue) && (0.0..=1.0).contains(&alpha), "Red needs to be in range 0..1\nGreen needs to be in range 0..1\nBlue needs to be in range 0..1\nAlpha needs to be in range 0..1");
+		assert!(red >= 0.0 && green >= 0.0 && blue >= 0.0 && (0.0..=1.0).contains(&alpha), "Red needs to be positive\nGreen needs to be positive\nBlue needs to be positive\nAlpha needs to be in range 0..1");
 		Self {
 			red,
 			green,
@@ -46,38 +49,43 @@ impl LinearRgba {
 	}
 
 	pub fn from_xyza(xyz: Xyza) -> Self {
+		xyz.to_linear()
+	}
+
+	/// Whether any channel is brighter than white.
+	pub fn is_hdr(&self) -> bool {
+		self.red > 1.0 || self.green > 1.0 || self.blue > 1.0
+	}
+
+	/// The color with every channel limited to 1.0.
+	pub fn clipped(&self) -> Self {
 		Self {
-			red:  3.2404542 * xyz.x() + -1.5371385 * xyz.y() + -0.4985314 * xyz.z(),
-			green: -0.9692660 * xyz.x() +  1.8760108 * xyz.y() +  0.0415560 * xyz.z(),
-			blue:  0.0556434 * xyz.x() + -0.2040259 * xyz.y() +  1.0572252 * xyz.z(),
-			alpha:  1.0
+			red: self.red.min(1.0),
+			green: self.green.min(1.0),
+			blue: self.blue.min(1.0),
+			alpha: self.alpha
 		}
 	}
 
 	pub fn to_rgba(&self) -> sRgba<f32> {
+		let color = self.clipped();
 		sRgba::<f32>::new(
-			if self.red <= 0.0031308 { self.red * 12.92 } else { 1.055 * self.red.powf( 1.0 / 2.4 ) - 0.055 },
-			if self.green <= 0.0031308 { self.green * 12.92 } else { 1.055 * self.green.powf( 1.0 / 2.4 ) - 0.055 },
-			if self.blue <= 0.0031308 { self.blue * 12.92 } else { 1.055 * self.blue.powf( 1.0 / 2.4 ) - 0.055 },
-			self.alpha
+			if color.red <= 0.0031308 { color.red * 12.92 } else { (1.055 * color.red.powf( 1.0 / 2.4 ) - 0.055).min(1.0) },
+			if color.green <= 0.0031308 { color.green * 12.92 } else { (1.055 * color.green.powf( 1.0 / 2.4 ) - 0.055).min(1.0) },
+			if color.blue <= 0.0031308 { color.blue * 12.92 } else { (1.055 * color.blue.powf( 1.0 / 2.4 ) - 0.055).min(1.0) },
+			color.alpha
 		)
 	}
 
 	pub fn to_rgba8(&self) -> sRgba<u8> {
-		let color = self.to_rgba();
-
-		sRgba::<u8>::new(
-			(color.red() * 255.0) as u8,
-			(color.green() * 255.0) as u8,
-			(color.blue() * 255.0) as u8,
-			(color.alpha() * 255.0) as u8,
-		)
+		sRgba::<f32>::from_linear(self.clone()).to_rgba8()
 	}
 
 	pub fn to_oklaba(&self) -> Oklaba {
-		let l = 0.4122214708 * self.red + 0.5363325363 * self.green + 0.0514459929 * self.blue;
-		let m = 0.2119034982 * self.red + 0.6806995451 * self.green + 0.1073969566 * self.blue;
-		let s = 0.0883024619 * self.red + 0.2817188376 * self.green + 0.6299787005 * self.blue;
+		let Self { red, green, blue, alpha } = self.clipped();
+		let l = 0.4122214708 * red + 0.5363325363 * green + 0.0514459929 * blue;
+		let m = 0.2119034982 * red + 0.6806995451 * green + 0.1073969566 * blue;
+		let s = 0.0883024619 * red + 0.2817188376 * green + 0.6299787005 * blue;
 
 		let l_ = l.cbrt();
 		let m_ = m.cbrt();
@@ -87,7 +95,7 @@ impl LinearRgba {
 			0.2104542553*l_ + 0.7936177850*m_ - 0.0040720468*s_,
 			1.9779984951*l_ - 2.4285922050*m_ + 0.4505937099*s_,
 			0.0259040371*l_ + 0.7827717662*m_ - 0.8086757660*s_,
-			self.alpha
+			alpha
 		)
 	}
 
@@ -96,12 +104,9 @@ impl LinearRgba {
 	}
 
 	pub fn to_xyza(&self) -> Xyza {
-		Xyza::new(
-			0.4124564 * self.red + 0.3575761 * self.green + 0.1804375 * self.blue,
-			0.2126729 * self.red + 0.7151522 * self.green + 0.0721750 * self.blue,
-			0.0193339 * self.red + 0.1191920 * self.green + 0.9503041 * self.blue,
-			self.alpha
-		)
+		let Self { red, green, blue, alpha } = self.clipped();
+		let [x, y, z] = transform(&SRGB_TO_XYZ, [red, green, blue]);
+		Xyza::new(x, y, z, alpha)
 	}
 
 	pub fn to_laba(&self) -> Laba {
@@ -132,4 +137,29 @@ impl LinearRgba {
 			a: self.alpha as f64
 		}
 	}
-}
\ No newline at end of file
+}
+
+impl crate::Color for LinearRgba {
+	fn to_linear(&self) -> LinearRgba {
+		self.clone()
+	}
+
+	fn from_linear(linear: LinearRgba) -> Self {
+		linear
+	}
+
+	fn alpha(&self) -> f32 {
+		self.alpha
+	}
+
+	fn with_alpha(mut self, alpha: f32) -> Self {
+		self.alpha = alpha.clamp(0.0, 1.0);
+		self
+	}
+}
+
+impl From<LinearRgba> for [f32; 4] {
+	fn from(color: LinearRgba) -> Self {
+		crate::Color::to_f32_array(&color)
+	}
+}
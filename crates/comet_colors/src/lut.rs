@@ -0,0 +1,76 @@
+use std::path::Path;
+use crate::LutError;
+
+/// A 3D color lookup table for color grading, mapping every color on screen to a new one.
+/// LUTs are usually made by grading a screenshot with the neutral strip pasted in and cutting the strip out again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorLut {
+	size: u32,
+	texels: Vec<[u8; 4]>
+}
+
+impl ColorLut {
+	/// The largest number of steps per channel.
+	pub const MAX_SIZE: u32 = 64;
+
+	/// The LUT leaving every color as it is, with `size` steps per channel.
+	pub fn identity(size: u32) -> Self {
+		let size = size.clamp(2, Self::MAX_SIZE);
+		let step = |i: u32| (i * 255 / (size - 1)) as u8;
+		let mut texels = Vec::with_capacity((size * size * size) as usize);
+		for blue in 0..size {
+			for green in 0..size {
+				for red in 0..size {
+					texels.push([step(red), step(green), step(blue), 255]);
+				}
+			}
+		}
+		Self {
+			size,
+			texels
+		}
+	}
+
+	/// Loads a LUT from a strip PNG, see `from_png`.
+	pub fn load(path: impl AsRef<Path>) -> Result<Self, LutError> {
+		Self::from_png(&std::fs::read(path)?)
+	}
+
+	/// Reads a strip of `size` squares of `size` x `size` pixels side by side, e.g. 256 x 16 or 1024 x 32.
+	/// Red grows to the right and green downwards inside a square, blue grows from square to square.
+	/// This is the layout of the neutral LUTs Unreal and most image editors use.
+	pub fn from_png(bytes: &[u8]) -> Result<Self, LutError> {
+		let image = image::load_from_memory_with_format(bytes, image::ImageFormat::Png)
+			.map_err(|e| LutError::Image(e.to_string()))?
+			.to_rgba8();
+
+		let (width, height) = image.dimensions();
+		if !(2..=Self::MAX_SIZE).contains(&height) || width != height * height {
+			return Err(LutError::InvalidSize(width, height));
+		}
+
+		let size = height;
+		let mut texels = Vec::with_capacity((size * size * size) as usize);
+		for blue in 0..size {
+			for green in 0..size {
+				for red in 0..size {
+					texels.push(image.get_pixel(blue * size + red, green).0);
+				}
+			}
+		}
+		Ok(Self {
+			size,
+			texels
+		})
+	}
+
+	/// The number of steps per channel.
+	pub fn size(&self) -> u32 {
+		self.size
+	}
+
+	/// The colors of the table, red changing fastest and blue slowest.
+	pub fn texels(&self) -> &[[u8; 4]] {
+		&self.texels
+	}
+}
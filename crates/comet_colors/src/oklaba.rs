@@ -1,6 +1,7 @@
 use crate::{sRgba, Hsla, Hsva, Hwba, Laba, Lcha, LinearRgba, Oklcha, Xyza};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Oklaba {
 	lightness: f32,
 	a: f32,
@@ -61,10 +62,11 @@ impl Oklaba {
 		let m = m_*m_*m_;
 		let s = s_*s_*s_;
 
+		// colors outside of the sRGB gamut are clamped to it
 		LinearRgba::new(
-			4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
-			-1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
-			-0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+			(4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s).clamp(0.0, 1.0),
+			(-1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s).clamp(0.0, 1.0),
+			(-0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s).clamp(0.0, 1.0),
 			self.alpha
 		)
 	}
@@ -109,4 +111,6 @@ impl Oklaba {
 	pub fn to_hsla(&self) -> Hsla {
 		self.to_hsva().to_hsla()
 	}
-}
\ No newline at end of file
+}
+
+crate::color::impl_color!(Oklaba, Oklaba::from_linear);
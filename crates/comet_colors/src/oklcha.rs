@@ -1,6 +1,7 @@
 use crate::{sRgba, Hsla, Hsva, Hwba, Laba, Lcha, LinearRgba, Oklaba, Xyza};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Oklcha {
 	lightness: f32,
 	chroma: f32,
@@ -88,4 +89,6 @@ impl Oklcha {
 	pub fn to_hsla(&self) -> Hsla {
 		self.to_hsva().to_hsla()
 	}
-}
\ No newline at end of file
+}
+
+crate::color::impl_color!(Oklcha, |linear: LinearRgba| linear.to_oklcha());
@@ -0,0 +1,157 @@
+use std::path::Path;
+use crate::{sRgba, Color, Oklaba, PaletteError};
+
+/// A fixed set of colors, e.g. for the art style of a retro game.
+/// Palettes can be loaded from GIMP palettes (`.gpl`), lospec JSON files and PNG strips.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Palette {
+	name: Option<String>,
+	colors: Vec<sRgba<u8>>
+}
+
+impl Palette {
+	pub fn new(colors: Vec<sRgba<u8>>) -> Self {
+		Self {
+			name: None,
+			colors
+		}
+	}
+
+	pub fn with_name(mut self, name: &str) -> Self {
+		self.name = Some(name.to_string());
+		self
+	}
+
+	pub fn name(&self) -> Option<&str> {
+		self.name.as_deref()
+	}
+
+	pub fn colors(&self) -> &[sRgba<u8>] {
+		&self.colors
+	}
+
+	pub fn len(&self) -> usize {
+		self.colors.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.colors.is_empty()
+	}
+
+	pub fn get(&self, index: usize) -> Option<&sRgba<u8>> {
+		self.colors.get(index)
+	}
+
+	/// Loads a palette, picking the format from the file extension (`gpl`, `json` or `png`).
+	pub fn load(path: impl AsRef<Path>) -> Result<Self, PaletteError> {
+		let path = path.as_ref();
+		match path.extension().and_then(|extension| extension.to_str()).map(|extension| extension.to_ascii_lowercase()).as_deref() {
+			Some("gpl") => Self::from_gpl(&std::fs::read_to_string(path)?),
+			Some("json") => Self::from_lospec_json(&std::fs::read_to_string(path)?),
+			Some("png") => Self::from_png(&std::fs::read(path)?),
+			_ => Err(PaletteError::UnsupportedFormat(path.display().to_string()))
+		}
+	}
+
+	/// Parses a GIMP palette. Every line after the header holds the red, green and blue value of one color.
+	pub fn from_gpl(source: &str) -> Result<Self, PaletteError> {
+		let mut lines = source.lines();
+		if lines.next().map(str::trim) != Some("GIMP Palette") {
+			return Err(PaletteError::Parse("missing the \"GIMP Palette\" header".to_string()));
+		}
+
+		let mut palette = Self::default();
+		for (number, line) in lines.enumerate() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+			if let Some(name) = line.strip_prefix("Name:") {
+				palette.name = Some(name.trim().to_string());
+				continue;
+			}
+			if line.starts_with("Columns:") {
+				continue;
+			}
+
+			let mut channels = line.split_whitespace().map(str::parse::<u8>);
+			match (channels.next(), channels.next(), channels.next()) {
+				(Some(Ok(red)), Some(Ok(green)), Some(Ok(blue))) => palette.colors.push(sRgba::<u8>::rgb(red, green, blue)),
+				_ => return Err(PaletteError::Parse(format!("line {} is not a color: {}", number + 2, line)))
+			}
+		}
+		Ok(palette)
+	}
+
+	/// Parses the JSON format of lospec.com, e.g. `{"name": "PICO-8", "colors": ["000000", "1d2b53", ...]}`.
+	pub fn from_lospec_json(source: &str) -> Result<Self, PaletteError> {
+		let colors = json_value(source, "colors")
+			.and_then(|value| value.strip_prefix('['))
+			.and_then(|value| value.split_once(']'))
+			.map(|(colors, _)| colors)
+			.ok_or_else(|| PaletteError::Parse("missing the \"colors\" array".to_string()))?;
+
+		let mut palette = Self::default();
+		for color in colors.split(',').map(|color| color.trim().trim_matches('"')).filter(|color| !color.is_empty()) {
+			palette.colors.push(sRgba::<u8>::from_hex(color)?);
+		}
+		palette.name = json_value(source, "name")
+			.and_then(|value| value.strip_prefix('"'))
+			.and_then(|value| value.split_once('"'))
+			.map(|(name, _)| name.to_string());
+		Ok(palette)
+	}
+
+	/// Reads every pixel of a PNG from left to right and top to bottom, skipping repeated and transparent ones.
+	/// This loads the palette strips (one pixel or one block per color) many palette sites offer.
+	pub fn from_png(bytes: &[u8]) -> Result<Self, PaletteError> {
+		let image = image::load_from_memory_with_format(bytes, image::ImageFormat::Png)
+			.map_err(|e| PaletteError::Image(e.to_string()))?
+			.to_rgba8();
+
+		let mut palette = Self::default();
+		for pixel in image.pixels() {
+			let color = sRgba::<u8>::rgba(pixel[0], pixel[1], pixel[2], pixel[3]);
+			if color.alpha() > 0 && !palette.colors.contains(&color) {
+				palette.colors.push(color);
+			}
+		}
+		Ok(palette)
+	}
+
+	/// The index of the palette color that looks the most like `color`. None if the palette is empty.
+	pub fn nearest_index<C: Color>(&self, color: &C) -> Option<usize> {
+		let target = Oklaba::from_linear(color.to_linear());
+		let distance = |other: &sRgba<u8>| {
+			let other = Oklaba::from_linear(other.to_linear());
+			let (lightness, a, b) = (other.lightness() - target.lightness(), other.a() - target.a(), other.b() - target.b());
+			lightness * lightness + a * a + b * b
+		};
+
+		self.colors.iter()
+			.map(distance)
+			.enumerate()
+			.min_by(|(_, a), (_, b)| a.total_cmp(b))
+			.map(|(index, _)| index)
+	}
+
+	/// The palette color that looks the most like `color`, measured in Oklab.
+	pub fn nearest<C: Color>(&self, color: &C) -> Option<&sRgba<u8>> {
+		self.nearest_index(color).map(|index| &self.colors[index])
+	}
+
+	/// Replaces `color` by its nearest palette color, converted back into the type of `color`.
+	pub fn quantize<C: Color>(&self, color: &C) -> C {
+		match self.nearest(color) {
+			Some(nearest) => C::from_linear(nearest.to_linear()).with_alpha(color.alpha()),
+			None => color.clone()
+		}
+	}
+}
+
+/// The raw text after `"key":`, good enough for the flat objects palette files use.
+fn json_value<'a>(source: &'a str, key: &str) -> Option<&'a str> {
+	let start = source.find(&format!("\"{}\"", key))? + key.len() + 2;
+	Some(source[start..].trim_start().strip_prefix(':')?.trim_start())
+}
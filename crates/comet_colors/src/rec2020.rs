@@ -0,0 +1,99 @@
+use crate::{color_space::{transform, SRGB_TO_XYZ, XYZ_TO_SRGB, REC2020_TO_XYZ, XYZ_TO_REC2020}, sRgba, LinearRgba, Xyza};
+
+/// The very wide gamut of UHD television, covering about three quarters of the visible colors.
+/// The channels are gamma encoded and in range 0..1, converting into sRGB clips the colors it can not show.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rec2020 {
+	red: f32,
+	green: f32,
+	blue: f32,
+	alpha: f32
+}
+
+impl Rec2020 {
+	pub fn new(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
+		assert!((0.0..=1.0).contains(&red) && (0.0..=1.0).contains(&green) && (0.0..=1.0).contains(&blue) && (0.0..=1.0).contains(&alpha), "Red needs to be in range 0..1\nGreen needs to be in range 0..1\nBlue needs to be in range 0..1\nAlpha needs to be in range 0..1");
+		Self {
+			red,
+			green,
+			blue,
+			alpha
+		}
+	}
+
+	pub fn red(&self) -> f32 {
+		self.red
+	}
+
+	pub fn green(&self) -> f32 {
+		self.green
+	}
+
+	pub fn blue(&self) -> f32 {
+		self.blue
+	}
+
+	pub fn alpha(&self) -> f32 {
+		self.alpha
+	}
+
+	/// The channels without the transfer function, i.e. proportional to the light of the Rec.2020 primaries.
+	pub fn to_linear_channels(&self) -> [f32; 3] {
+		[self.red, self.green, self.blue].map(decode)
+	}
+
+	/// Builds the color from linear light of the Rec.2020 primaries, clipping what lies outside of the gamut.
+	pub fn from_linear_channels(channels: [f32; 3], alpha: f32) -> Self {
+		let [red, green, blue] = channels.map(|channel| encode(channel.clamp(0.0, 1.0)));
+		Self {
+			red,
+			green,
+			blue,
+			alpha
+		}
+	}
+
+	pub fn from_xyza(xyza: Xyza) -> Self {
+		Self::from_linear_channels(transform(&XYZ_TO_REC2020, xyza.to_array()), xyza.alpha())
+	}
+
+	pub fn to_xyza(&self) -> Xyza {
+		Xyza::from_array(transform(&REC2020_TO_XYZ, self.to_linear_channels()), self.alpha)
+	}
+
+	/// Values above 1.0 are clipped, everything else inside of the Rec.2020 gamut is kept.
+	pub fn from_linear(linear: LinearRgba) -> Self {
+		let linear = linear.clipped();
+		let xyz = transform(&SRGB_TO_XYZ, [linear.red(), linear.green(), linear.blue()]);
+		Self::from_linear_channels(transform(&XYZ_TO_REC2020, xyz), linear.alpha())
+	}
+
+	/// Colors outside of the sRGB gamut are clipped.
+	pub fn to_linear(&self) -> LinearRgba {
+		let [red, green, blue] = transform(&XYZ_TO_SRGB, transform(&REC2020_TO_XYZ, self.to_linear_channels()));
+		LinearRgba::new(red.max(0.0), green.max(0.0), blue.max(0.0), self.alpha)
+	}
+
+	pub fn to_rgba(&self) -> sRgba<f32> {
+		self.to_linear().clipped().to_rgba()
+	}
+
+	pub fn to_rgba8(&self) -> sRgba<u8> {
+		self.to_linear().clipped().to_rgba8()
+	}
+}
+
+const ALPHA: f32 = 1.0992968;
+const BETA: f32 = 0.018053968;
+
+// the transfer function of ITU-R BT.2020
+fn decode(value: f32) -> f32 {
+	if value < 4.5 * BETA { value / 4.5 } else { ((value + ALPHA - 1.0) / ALPHA).powf(1.0 / 0.45) }
+}
+
+fn encode(value: f32) -> f32 {
+	if value < BETA { value * 4.5 } else { ALPHA * value.powf(0.45) - (ALPHA - 1.0) }
+}
+
+crate::color::impl_color!(Rec2020, Rec2020::from_linear);
@@ -5,6 +5,7 @@ use crate::{math::Vec4, Hsla, Hsva, Hwba, Laba, Lcha, LinearRgba, Oklaba, Oklcha
 /// The first one is your standard 0..255 RGB and the second is the normalized version with range 0..1
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct sRgba<T> {
 	red: T,
 	green: T,
@@ -23,6 +24,20 @@ impl sRgba<u8> {
 		}
 	}
 
+	/// An opaque color, usable in constants.
+	pub const fn rgb(red: u8, green: u8, blue: u8) -> Self {
+		Self::rgba(red, green, blue, 255)
+	}
+
+	pub const fn rgba(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
+		Self {
+			red,
+			green,
+			blue,
+			alpha
+		}
+	}
+
 	pub fn red(&self) -> u8 {
 		self.red
 	}
@@ -39,41 +54,6 @@ impl sRgba<u8> {
 		self.alpha
 	}
 
-	pub fn from_hex(hex: &str) -> Self {
-		let hex = hex.trim_start_matches("#");
-
-		if hex.len() != 8 {
-			panic!("The length of the hex string is not equal to 8!");
-		}
-
-		let red = match u8::from_str_radix(&hex[0..2], 16).map_err(|_| "Red part is not a hex value!") {
-			Ok(v) => v,
-			Err(err) => panic!("{}", err)
-		};
-
-		let green = match u8::from_str_radix(&hex[2..4], 16).map_err(|_| "Green part is not a hex value!") {
-			Ok(v) => v,
-			Err(err) => panic!("{}", err)
-		};
-
-		let blue = match u8::from_str_radix(&hex[4..6], 16).map_err(|_| "Blue part is not a hex value!") {
-			Ok(v) => v,
-			Err(err) => panic!("{}", err)
-		};
-
-		let alpha = match u8::from_str_radix(&hex[6..8], 16).map_err(|_| "Alpha part is not a hex value!") {
-			Ok(v) => v,
-			Err(err) => panic!("{}", err)
-		};
-
-		Self {
-			red,
-			green,
-			blue,
-			alpha
-		}
-	}
-
 	pub fn from_rgba(rgba: sRgba<f32>) -> Self {
 		rgba.to_rgba8()
 	}
@@ -151,7 +131,7 @@ impl sRgba<u8> {
 
 impl sRgba<f32> {
 	pub fn new(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
-		assert!((0.0..1.0).contains(&red) && (0.0..1.0).contains(&green) && (0.0..1.0).contains(&blue) && (0.0..1.0).contains(&alpha), "Red needs to be in range 0..1\nGreen needs to be in range 0..1\nBlue needs to be in range 0..1\nAlpha needs to be in range 0..1");
+		assert!((0.0..=1.0).contains(&red) && (0.0..=1.0).contains(&green) && (0.0..=1.0).contains(&blue) && (0.0..=1.0).contains(&alpha), "Red needs to be in range 0..1\nGreen needs to be in range 0..1\nBlue needs to be in range 0..1\nAlpha needs to be in range 0..1");
 		Self {
 			red,
 			green,
@@ -176,41 +156,6 @@ impl sRgba<f32> {
 		self.alpha
 	}
 
-	pub fn from_hex(hex: &str) -> Self {
-		let hex = hex.trim_start_matches("#");
-
-		if hex.len() != 8 {
-			panic!("The length of the hex string is not equal to 6!");
-		}
-
-		let r = match u8::from_str_radix(&hex[0..2], 16).map_err(|_| "Red is not a hex value!") {
-			Ok(v) => v,
-			Err(err) => panic!("{}", err)
-		};
-
-		let g = match u8::from_str_radix(&hex[2..4], 16).map_err(|_| "Green is not a hex value!") {
-			Ok(v) => v,
-			Err(err) => panic!("{}", err)
-		};
-
-		let b = match u8::from_str_radix(&hex[4..6], 16).map_err(|_| "Blue is not a hex value!") {
-			Ok(v) => v,
-			Err(err) => panic!("{}", err)
-		};
-
-		let a = match u8::from_str_radix(&hex[6..8], 16).map_err(|_| "Alpha is not a hex value!") {
-			Ok(v) => v,
-			Err(err) => panic!("{}", err)
-		};
-
-		Self {
-			red: r as f32 / 255.0,
-			green: g as f32 / 255.0,
-			blue: b as f32 / 255.0,
-			alpha: a as f32 / 255.0
-		}
-	}
-
 	pub fn from_linear(linear: LinearRgba) -> Self {
 		Self {
 			red: if linear.red() <= 0.0031308 { 12.92 * linear.red() } else { 1.055 * linear.red().powf(1.0 / 2.4) - 0.055 },
@@ -262,11 +207,12 @@ impl sRgba<f32> {
 	}
 
 	pub fn to_rgba8(&self) -> sRgba<u8> {
+		let to_u8 = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
 		sRgba {
-			red: (self.red * 255.0) as u8,
-			green: (self.green * 255.0) as u8,
-			blue: (self.blue * 255.0) as u8,
-			alpha: (self.alpha * 255.0) as u8
+			red: to_u8(self.red),
+			green: to_u8(self.green),
+			blue: to_u8(self.blue),
+			alpha: to_u8(self.alpha)
 		}
 	}
 
@@ -336,7 +282,7 @@ impl sRgba<f32> {
 			h,
 			w,
 			b,
-			self.alpha() as f32 / 255.0
+			self.alpha()
 		)
 	}
 
@@ -356,4 +302,31 @@ impl sRgba<f32> {
 			self.alpha
 		)
 	}
-}
\ No newline at end of file
+}
+
+impl crate::Color for sRgba<u8> {
+	fn to_linear(&self) -> LinearRgba {
+		sRgba::<u8>::to_linear(self)
+	}
+
+	fn from_linear(linear: LinearRgba) -> Self {
+		linear.to_rgba8()
+	}
+
+	fn alpha(&self) -> f32 {
+		self.alpha as f32 / 255.0
+	}
+
+	fn with_alpha(mut self, alpha: f32) -> Self {
+		self.alpha = (alpha.clamp(0.0, 1.0) * 255.0).round() as u8;
+		self
+	}
+}
+
+impl From<sRgba<u8>> for [f32; 4] {
+	fn from(color: sRgba<u8>) -> Self {
+		crate::Color::to_f32_array(&color)
+	}
+}
+
+crate::color::impl_color!(sRgba<f32>, sRgba::<f32>::from_linear);
@@ -1,6 +1,7 @@
-use crate::{sRgba, Hsla, Hsva, Hwba, Laba, Lcha, LinearRgba, Oklaba, Oklcha};
+use crate::{color_space::{chromatic_adaptation, transform, D65, SRGB_TO_XYZ, XYZ_TO_SRGB}, sRgba, Hsla, Hsva, Hwba, Laba, Lcha, LinearRgba, Oklaba, Oklcha};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Xyza {
 	x: f32,
 	y: f32,
@@ -36,25 +37,38 @@ impl Xyza {
 	}
 
 	pub fn from_linear(linear: LinearRgba) -> Self {
+		Self::from_array(transform(&SRGB_TO_XYZ, [linear.red(), linear.green(), linear.blue()]), linear.alpha())
+	}
+
+	/// Colors outside of the sRGB gamut are clipped.
+	pub fn to_linear(&self) -> LinearRgba {
+		let [red, green, blue] = transform(&XYZ_TO_SRGB, self.to_array());
+		LinearRgba::new(red.max(0.0), green.max(0.0), blue.max(0.0), self.alpha)
+	}
+
+	/// The X, Y and Z values, ready to be multiplied with the matrices in `color_space`.
+	pub fn to_array(&self) -> [f32; 3] {
+		[self.x, self.y, self.z]
+	}
+
+	/// Unlike `new` this accepts any values, since other gamuts and white points leave the range of sRGB.
+	pub fn from_array(xyz: [f32; 3], alpha: f32) -> Self {
 		Self {
-			x: 0.4124564 * linear.red() + 0.3575761 * linear.green() + 0.1804375 * linear.blue(),
-			y: 0.2126729 * linear.red() + 0.7151522 * linear.green() + 0.0721750 * linear.blue(),
-			z: 0.0193339 * linear.red() + 0.1191920 * linear.green() + 0.9503041 * linear.blue(),
-			alpha: linear.alpha()
+			x: xyz[0],
+			y: xyz[1],
+			z: xyz[2],
+			alpha
 		}
 	}
 
-	pub fn to_linear(&self) -> LinearRgba {
-		LinearRgba::new(
-			3.2404542 * self.x + -1.5371385 * self.y + -0.4985314 * self.z,
-			-0.9692660 * self.x +  1.8760108 * self.y +  0.0415560 * self.z,
-			0.0556434 * self.x + -0.2040259 * self.y +  1.0572252 * self.z,
-			self.alpha
-		)
+	/// Moves the color from the white point `from` to `to` with the Bradford transform,
+	/// e.g. `adapt(D65, D50)` before handing it to something expecting D50 colors.
+	pub fn adapt(&self, from: [f32; 3], to: [f32; 3]) -> Self {
+		Self::from_array(transform(&chromatic_adaptation(from, to), self.to_array()), self.alpha)
 	}
 
 	pub fn to_laba(&self) -> Laba {
-		let reference_white = Xyza::new(0.95047, 1.0, 1.08883, 1.0);
+		let reference_white = Xyza::new(D65[0], D65[1], D65[2], 1.0);
 
 		let x_r = self.x / reference_white.x;
 		let y_r = self.y / reference_white.y;
@@ -106,4 +120,6 @@ impl Xyza {
 	pub fn to_hsla(&self) -> Hsla {
 		self.to_hsva().to_hsla()
 	}
-}
\ No newline at end of file
+}
+
+crate::color::impl_color!(Xyza, Xyza::from_linear);
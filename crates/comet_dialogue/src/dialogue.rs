@@ -0,0 +1,348 @@
+use crate::{DialogueError, DialogueValue, DialogueVariables};
+
+/// Where a choice or divert leads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Target {
+	Node(usize),
+	End
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SetOp {
+	Assign,
+	Add,
+	Subtract
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompareOp {
+	Equal,
+	NotEqual,
+	Less,
+	LessEqual,
+	Greater,
+	GreaterEqual
+}
+
+/// The right side of a comparison or assignment, a literal or the value of another variable.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Operand {
+	Literal(DialogueValue),
+	Variable(String)
+}
+
+impl Operand {
+	fn parse(operand: &str) -> Option<Self> {
+		let operand = operand.trim();
+		DialogueValue::parse_literal(operand)
+			.map(Operand::Literal)
+			.or_else(|| is_identifier(operand).then(|| Operand::Variable(operand.to_string())))
+	}
+
+	pub(crate) fn value(&self, variables: &DialogueVariables) -> Option<DialogueValue> {
+		match self {
+			Operand::Literal(value) => Some(value.clone()),
+			Operand::Variable(name) => variables.get(name).cloned()
+		}
+	}
+}
+
+/// `name`, `!name` or `name <op> operand` in `[if ...]`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Condition {
+	variable: String,
+	negated: bool,
+	comparison: Option<(CompareOp, Operand)>
+}
+
+impl Condition {
+	fn parse(condition: &str) -> Option<Self> {
+		let condition = condition.trim();
+		const OPERATORS: [(&str, CompareOp); 6] = [
+			(">=", CompareOp::GreaterEqual),
+			("<=", CompareOp::LessEqual),
+			("==", CompareOp::Equal),
+			("!=", CompareOp::NotEqual),
+			(">", CompareOp::Greater),
+			("<", CompareOp::Less)
+		];
+		let operator = OPERATORS.iter()
+			.filter_map(|(symbol, op)| condition.find(symbol).map(|index| (index, *symbol, *op)))
+			.min_by_key(|(index, symbol, _)| (*index, usize::MAX - symbol.len()));
+
+		match operator {
+			Some((index, symbol, op)) => {
+				let variable = condition[..index].trim();
+				let operand = Operand::parse(&condition[index + symbol.len()..])?;
+				is_identifier(variable).then(|| Condition {
+					variable: variable.to_string(),
+					negated: false,
+					comparison: Some((op, operand))
+				})
+			}
+			None => {
+				let (variable, negated) = match condition.strip_prefix('!') {
+					Some(variable) => (variable.trim(), true),
+					None => (condition, false)
+				};
+				is_identifier(variable).then(|| Condition {
+					variable: variable.to_string(),
+					negated,
+					comparison: None
+				})
+			}
+		}
+	}
+
+	/// Missing variables are false, and never equal to anything.
+	pub(crate) fn holds(&self, variables: &DialogueVariables) -> bool {
+		let value = variables.get(&self.variable);
+		let Some((op, operand)) = &self.comparison else {
+			return value.is_some_and(DialogueValue::is_truthy) != self.negated;
+		};
+		let (Some(value), Some(other)) = (value, operand.value(variables)) else {
+			return *op == CompareOp::NotEqual;
+		};
+		match (op, value, &other) {
+			(CompareOp::Equal, _, _) => *value == other,
+			(CompareOp::NotEqual, _, _) => *value != other,
+			(_, DialogueValue::Number(a), DialogueValue::Number(b)) => match op {
+				CompareOp::Less => a < b,
+				CompareOp::LessEqual => a <= b,
+				CompareOp::Greater => a > b,
+				_ => a >= b
+			},
+			_ => false
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Step {
+	Line {
+		speaker: Option<String>,
+		text: String
+	},
+	Choice {
+		text: String,
+		target: Target
+	},
+	Divert(Target),
+	Set {
+		variable: String,
+		op: SetOp,
+		operand: Operand
+	},
+	Command {
+		name: String,
+		args: Vec<String>
+	}
+}
+
+/// A step of a node, only taken if its condition holds.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Entry {
+	pub(crate) condition: Option<Condition>,
+	pub(crate) step: Step
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Node {
+	pub(crate) name: String,
+	pub(crate) entries: Vec<Entry>
+}
+
+/// A branching dialogue, made of named nodes that are read line by line.
+///
+/// ```text
+/// // comments start with two slashes
+/// == gate
+/// Guard: Halt! Who goes there?
+/// * A friend. -> friend
+/// * [if gold >= 10] Someone with gold. -> bribe
+/// * Nobody. -> END
+///
+/// == friend
+/// ~ trust += 1
+/// Guard: Pass then, {name}.
+/// >> open_gate north
+/// [if trust > 2] -> old_friends
+/// -> END
+/// ```
+///
+/// - `== name` starts a node, a dialogue starts at the node it is told to.
+/// - `Speaker: text` is a line said by someone, lines without a speaker are narration. `{name}` shows a variable.
+/// - `* text -> node` is a choice. Choices next to each other are offered together and the dialogue waits for one.
+/// - `-> node` jumps to another node, `-> END` ends the dialogue. Running past the end of a node ends it too.
+/// - `~ name = value` sets a variable, `+=` and `-=` add to and subtract from a number.
+/// - `>> name args` sends a `DialogueCommand` to the game, e.g. to play a sound.
+/// - `[if condition]` in front of any of these only takes it if the condition holds, e.g. `[if met_guard]`,
+///   `[if !met_guard]` or `[if gold >= 10]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dialogue {
+	nodes: Vec<Node>
+}
+
+impl Dialogue {
+	/// Loads a dialogue file through the asset cache.
+	pub fn load(path: &str) -> Result<Self, DialogueError> {
+		let source = comet_resources::assets::read_asset_to_string(path)?;
+		Self::parse(&source)
+	}
+
+	pub fn parse<'a>(source: &'a str) -> Result<Self, DialogueError> {
+		let mut nodes: Vec<Node> = Vec::new();
+		// (node, entry, name) of every choice and divert, pointed at their nodes once all are known
+		let mut diverts: Vec<(usize, usize, String)> = Vec::new();
+
+		for (index, raw) in source.lines().enumerate() {
+			let error = |message: &str| DialogueError::Parse { line: index + 1, message: message.to_string() };
+			let line = strip_comment(raw).trim();
+			if line.is_empty() {
+				continue;
+			}
+
+			if let Some(name) = line.strip_prefix("==") {
+				let name = name.trim_matches(|c: char| c == '=' || c.is_whitespace());
+				if !is_identifier(name) || name == "END" {
+					return Err(error("a node needs a name made of letters, digits and underscores"));
+				}
+				if nodes.iter().any(|node| node.name == name) {
+					return Err(error(&format!("there already is a node called {}", name)));
+				}
+				nodes.push(Node { name: name.to_string(), entries: Vec::new() });
+				continue;
+			}
+			if nodes.is_empty() {
+				return Err(error("everything has to be inside a node, start one with `== name`"));
+			}
+
+			let split_condition = |line: &'a str| -> Result<(Option<Condition>, &'a str), DialogueError> {
+				let Some(rest) = line.strip_prefix("[if") else {
+					return Ok((None, line));
+				};
+				let Some(end) = rest.find(']') else {
+					return Err(error("`[if` is missing its `]`"));
+				};
+				let condition = Condition::parse(&rest[..end]).ok_or_else(|| error("the condition is not `name`, `!name` or `name <op> value`"))?;
+				Ok((Some(condition), rest[end + 1..].trim()))
+			};
+			let (mut condition, line) = split_condition(line)?;
+
+			let target = |name: &str| -> Result<(Target, Option<String>), DialogueError> {
+				match name.trim() {
+					"END" => Ok((Target::End, None)),
+					name if is_identifier(name) => Ok((Target::End, Some(name.to_string()))),
+					_ => Err(error("`->` needs the name of a node or END"))
+				}
+			};
+
+			let (step, divert) = if let Some(choice) = line.strip_prefix('*') {
+				// choices may also have their condition after the `*`
+				let (choice_condition, choice) = split_condition(choice.trim())?;
+				condition = condition.or(choice_condition);
+				let Some((text, to)) = choice.rsplit_once("->") else {
+					return Err(error("a choice needs a `-> node` it leads to"));
+				};
+				let (target, divert) = target(to)?;
+				(Step::Choice { text: text.trim().to_string(), target }, divert)
+			}
+			else if let Some(to) = line.strip_prefix("->") {
+				let (target, divert) = target(to)?;
+				(Step::Divert(target), divert)
+			}
+			else if let Some(assignment) = line.strip_prefix('~') {
+				let (variable, op, operand) = [("+=", SetOp::Add), ("-=", SetOp::Subtract), ("=", SetOp::Assign)].iter()
+					.find_map(|(symbol, op)| assignment.split_once(symbol).map(|(variable, operand)| (variable.trim(), *op, operand)))
+					.ok_or_else(|| error("`~` needs `name = value`, `name += value` or `name -= value`"))?;
+				if !is_identifier(variable) {
+					return Err(error("variable names are made of letters, digits and underscores"));
+				}
+				let operand = Operand::parse(operand).ok_or_else(|| error("the value is not a number, true, false, a \"string\" or a variable"))?;
+				(Step::Set { variable: variable.to_string(), op, operand }, None)
+			}
+			else if let Some(command) = line.strip_prefix(">>") {
+				let mut words = command.split_whitespace().map(str::to_string);
+				let Some(name) = words.next() else {
+					return Err(error("`>>` needs the name of a command"));
+				};
+				(Step::Command { name, args: words.collect() }, None)
+			}
+			else {
+				let (speaker, text) = split_speaker(line);
+				(Step::Line { speaker: speaker.map(str::to_string), text: text.to_string() }, None)
+			};
+
+			let node = nodes.len() - 1;
+			if let Some(name) = divert {
+				diverts.push((node, nodes[node].entries.len(), name));
+			}
+			nodes[node].entries.push(Entry { condition, step });
+		}
+
+		for (node, entry, name) in diverts {
+			let index = nodes.iter().position(|node| node.name == name).ok_or(DialogueError::UnknownNode(name))?;
+			if let Step::Choice { target, .. } | Step::Divert(target) = &mut nodes[node].entries[entry].step {
+				*target = Target::Node(index);
+			}
+		}
+
+		Ok(Self { nodes })
+	}
+
+	/// The names of all nodes in the order they are written in.
+	pub fn node_names(&self) -> impl Iterator<Item = &str> {
+		self.nodes.iter().map(|node| node.name.as_str())
+	}
+
+	pub fn has_node(&self, name: &str) -> bool {
+		self.node_index(name).is_some()
+	}
+
+	pub(crate) fn node_index(&self, name: &str) -> Option<usize> {
+		self.nodes.iter().position(|node| node.name == name)
+	}
+
+	pub(crate) fn node(&self, index: usize) -> &Node {
+		&self.nodes[index]
+	}
+}
+
+fn is_identifier(name: &str) -> bool {
+	!name.is_empty()
+		&& !name.starts_with(|c: char| c.is_ascii_digit())
+		&& name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Cuts off a `//` comment, unless it is inside a string.
+fn strip_comment(line: &str) -> &str {
+	let mut in_string = false;
+	let bytes = line.as_bytes();
+	for i in 0..bytes.len() {
+		match bytes[i] {
+			b'"' => in_string = !in_string,
+			b'/' if !in_string && bytes.get(i + 1) == Some(&b'/') => return &line[..i],
+			_ => {}
+		}
+	}
+	line
+}
+
+/// Splits `Speaker: text` into its speaker and text. Speakers are a few words without punctuation,
+/// so a line like `Note: the door is locked` still counts, but `It was 5:30.` does not.
+fn split_speaker(line: &str) -> (Option<&str>, &str) {
+	let Some((speaker, text)) = line.split_once(':') else {
+		return (None, line);
+	};
+	let is_speaker = !speaker.is_empty()
+		&& !speaker.starts_with(char::is_whitespace)
+		&& !speaker.ends_with(char::is_whitespace)
+		&& speaker.chars().all(|c| c.is_alphanumeric() || c == ' ' || c == '_' || c == '-' || c == '\'')
+		&& text.starts_with(char::is_whitespace);
+	if is_speaker {
+		(Some(speaker), text.trim())
+	}
+	else {
+		(None, line)
+	}
+}
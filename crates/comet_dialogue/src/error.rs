@@ -0,0 +1,32 @@
+use std::fmt;
+
+/// Why a dialogue could not be loaded.
+#[derive(Debug)]
+pub enum DialogueError {
+	Io(std::io::Error),
+	/// A line of the dialogue file is malformed, `line` counting from 1.
+	Parse {
+		line: usize,
+		message: String
+	},
+	/// A divert or choice leads to a node that does not exist.
+	UnknownNode(String)
+}
+
+impl fmt::Display for DialogueError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			DialogueError::Io(e) => write!(f, "Failed to read the dialogue: {}", e),
+			DialogueError::Parse { line, message } => write!(f, "Invalid dialogue in line {}: {}", line, message),
+			DialogueError::UnknownNode(node) => write!(f, "The dialogue leads to the node {}, which does not exist", node)
+		}
+	}
+}
+
+impl std::error::Error for DialogueError {}
+
+impl From<std::io::Error> for DialogueError {
+	fn from(e: std::io::Error) -> Self {
+		DialogueError::Io(e)
+	}
+}
@@ -0,0 +1,9 @@
+pub use error::*;
+pub use dialogue::*;
+pub use value::*;
+pub use runner::*;
+
+mod error;
+mod dialogue;
+mod value;
+mod runner;
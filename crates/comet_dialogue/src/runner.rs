@@ -0,0 +1,252 @@
+use comet_ecs::World;
+use comet_log::*;
+use crate::dialogue::{SetOp, Step, Target};
+use crate::{Dialogue, DialogueValue, DialogueVariables};
+
+/// How many steps one `advance` takes at most before it gives up, so nodes diverting to each other
+/// without a line in between do not hang the game.
+const MAX_STEPS: usize = 10_000;
+
+/// Sent into the `World` when the dialogue reaches a line, it stays on it until `DialogueRunner::advance`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DialogueLine {
+	/// Who says the line, `None` for narration.
+	pub speaker: Option<String>,
+	/// The line with its variables filled in.
+	pub text: String
+}
+
+/// Sent into the `World` when the dialogue waits for the player to pick one of `choices` with `DialogueRunner::choose`.
+/// Choices whose condition does not hold are left out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DialogueChoices {
+	pub choices: Vec<String>
+}
+
+/// Sent into the `World` for every `>> name args` the dialogue runs through.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DialogueCommand {
+	pub name: String,
+	pub args: Vec<String>
+}
+
+/// Sent into the `World` when a dialogue ends, by `-> END`, running past the end of a node or `DialogueRunner::stop`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DialogueEnded;
+
+/// Runs a `Dialogue` and tells the game what happens in it through `DialogueLine`, `DialogueChoices`,
+/// `DialogueCommand` and `DialogueEnded` events in the `World`, so presenting it is up to the systems reading them.
+pub struct DialogueRunner {
+	dialogue: Dialogue,
+	variables: DialogueVariables,
+	/// The node and the entry in it the dialogue continues from, `None` when it is not running.
+	position: Option<(usize, usize)>,
+	line: Option<DialogueLine>,
+	choices: Vec<(String, Target)>
+}
+
+impl DialogueRunner {
+	pub fn new(dialogue: Dialogue) -> Self {
+		Self {
+			dialogue,
+			variables: DialogueVariables::new(),
+			position: None,
+			line: None,
+			choices: Vec::new()
+		}
+	}
+
+	/// Starts with these variables instead of none.
+	pub fn with_variables(mut self, variables: DialogueVariables) -> Self {
+		self.variables = variables;
+		self
+	}
+
+	pub fn dialogue(&self) -> &Dialogue {
+		&self.dialogue
+	}
+
+	pub fn variables(&self) -> &DialogueVariables {
+		&self.variables
+	}
+
+	pub fn variables_mut(&mut self) -> &mut DialogueVariables {
+		&mut self.variables
+	}
+
+	pub fn is_running(&self) -> bool {
+		self.position.is_some()
+	}
+
+	/// The name of the node the dialogue is in.
+	pub fn current_node(&self) -> Option<&str> {
+		self.position.map(|(node, _)| self.dialogue.node(node).name.as_str())
+	}
+
+	/// The line the dialogue is on, also while choices for it are shown.
+	pub fn current_line(&self) -> Option<&DialogueLine> {
+		self.line.as_ref()
+	}
+
+	/// The choices the dialogue is waiting for one of, empty while it is not.
+	pub fn choices(&self) -> impl Iterator<Item = &str> {
+		self.choices.iter().map(|(text, _)| text.as_str())
+	}
+
+	pub fn is_waiting_for_choice(&self) -> bool {
+		!self.choices.is_empty()
+	}
+
+	/// Starts the dialogue at the node called `node` and runs it to its first line or choices.
+	/// A dialogue that is already running is ended first.
+	pub fn start(&mut self, node: &str, world: &mut World) {
+		let Some(index) = self.dialogue.node_index(node) else {
+			error!("The dialogue has no node called {}", node);
+			return;
+		};
+		if self.is_running() {
+			self.stop(world);
+		}
+		self.position = Some((index, 0));
+		self.run(world);
+	}
+
+	/// Moves on from the current line to the next line or choices. Does nothing while waiting for a choice.
+	pub fn advance(&mut self, world: &mut World) {
+		if self.is_waiting_for_choice() {
+			warn!("The dialogue waits for a choice and cannot advance without one");
+			return;
+		}
+		if self.is_running() {
+			self.run(world);
+		}
+	}
+
+	/// Picks the choice at `index` of the last `DialogueChoices` and runs on to the next line or choices.
+	pub fn choose(&mut self, index: usize, world: &mut World) {
+		if index >= self.choices.len() {
+			warn!("There is no choice {} to pick, there are {}", index, self.choices.len());
+			return;
+		}
+		let target = self.choices[index].1;
+		self.choices.clear();
+		self.jump(target, world);
+		if self.is_running() {
+			self.run(world);
+		}
+	}
+
+	/// Ends the dialogue where it is.
+	pub fn stop(&mut self, world: &mut World) {
+		if self.position.take().is_some() {
+			self.line = None;
+			self.choices.clear();
+			world.send_event(DialogueEnded);
+		}
+	}
+
+	fn jump(&mut self, target: Target, world: &mut World) {
+		match target {
+			Target::Node(node) => self.position = Some((node, 0)),
+			Target::End => self.stop(world)
+		}
+	}
+
+	/// Takes steps until the dialogue reaches a line, choices or its end.
+	fn run(&mut self, world: &mut World) {
+		self.line = None;
+		for _ in 0..MAX_STEPS {
+			let Some((node, entry)) = self.position else {
+				return;
+			};
+			let Some(current) = self.dialogue.node(node).entries.get(entry).cloned() else {
+				self.stop(world);
+				return;
+			};
+			self.position = Some((node, entry + 1));
+			if current.condition.as_ref().is_some_and(|condition| !condition.holds(&self.variables)) {
+				continue;
+			}
+
+			match current.step {
+				Step::Line { speaker, text } => {
+					let line = DialogueLine { speaker, text: interpolate(&text, &self.variables) };
+					world.send_event(line.clone());
+					self.line = Some(line);
+					// choices right after a line are shown along with it
+					self.collect_choices(world);
+					return;
+				}
+				Step::Choice { .. } => {
+					self.position = Some((node, entry));
+					self.collect_choices(world);
+					if self.is_waiting_for_choice() {
+						return;
+					}
+				}
+				Step::Divert(target) => self.jump(target, world),
+				Step::Set { variable, op, operand } => {
+					let Some(value) = operand.value(&self.variables) else {
+						warn!("The dialogue sets {} to a variable that is not set", variable);
+						continue;
+					};
+					let value = match (op, self.variables.get(&variable), value) {
+						(SetOp::Assign, _, value) => value,
+						(SetOp::Add, None, DialogueValue::Number(b)) => DialogueValue::Number(b),
+						(SetOp::Subtract, None, DialogueValue::Number(b)) => DialogueValue::Number(-b),
+						(SetOp::Add, Some(DialogueValue::Number(a)), DialogueValue::Number(b)) => DialogueValue::Number(a + b),
+						(SetOp::Subtract, Some(DialogueValue::Number(a)), DialogueValue::Number(b)) => DialogueValue::Number(a - b),
+						_ => {
+							warn!("The dialogue adds to or subtracts from {}, which is not a number", variable);
+							continue;
+						}
+					};
+					self.variables.set(&variable, value);
+				}
+				Step::Command { name, args } => {
+					world.send_event(DialogueCommand { name, args });
+				}
+			}
+		}
+		error!("The dialogue ran {} steps without reaching a line, it probably diverts in a circle", MAX_STEPS);
+		self.stop(world);
+	}
+
+	/// Gathers the choices from the current entry on and sends them if any of them are available.
+	/// The position is left after the last of them.
+	fn collect_choices(&mut self, world: &mut World) {
+		let Some((node, mut entry)) = self.position else {
+			return;
+		};
+		let entries = &self.dialogue.node(node).entries;
+		while let Some(Step::Choice { text, target }) = entries.get(entry).map(|entry| &entry.step) {
+			if entries[entry].condition.as_ref().is_none_or(|condition| condition.holds(&self.variables)) {
+				self.choices.push((interpolate(text, &self.variables), *target));
+			}
+			entry += 1;
+		}
+		self.position = Some((node, entry));
+		if self.is_waiting_for_choice() {
+			world.send_event(DialogueChoices { choices: self.choices.iter().map(|(text, _)| text.clone()).collect() });
+		}
+	}
+}
+
+/// Replaces every `{name}` in `text` with the value of the variable, or nothing if it is not set.
+fn interpolate(text: &str, variables: &DialogueVariables) -> String {
+	let mut result = String::with_capacity(text.len());
+	let mut rest = text;
+	while let Some(start) = rest.find('{') {
+		let Some(end) = rest[start..].find('}') else {
+			break;
+		};
+		result.push_str(&rest[..start]);
+		let name = rest[start + 1..start + end].trim();
+		if let Some(value) = variables.get(name) {
+			result.push_str(&value.to_string());
+		}
+		rest = &rest[start + end + 1..];
+	}
+	result.push_str(rest);
+	result
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::fmt;
+
+/// The value of a dialogue variable.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DialogueValue {
+	Number(f32),
+	Bool(bool),
+	Text(String)
+}
+
+impl DialogueValue {
+	/// Reads a literal as written in a dialogue file: a number, `true`, `false` or a string in double quotes.
+	pub(crate) fn parse_literal(literal: &str) -> Option<Self> {
+		let literal = literal.trim();
+		match literal {
+			"true" => Some(DialogueValue::Bool(true)),
+			"false" => Some(DialogueValue::Bool(false)),
+			_ if literal.len() >= 2 && literal.starts_with('"') && literal.ends_with('"') => {
+				Some(DialogueValue::Text(literal[1..literal.len() - 1].to_string()))
+			}
+			_ => literal.parse().ok().map(DialogueValue::Number)
+		}
+	}
+
+	/// Numbers other than 0, `true` and strings that are not empty count as true in conditions.
+	pub fn is_truthy(&self) -> bool {
+		match self {
+			DialogueValue::Number(number) => *number != 0.0,
+			DialogueValue::Bool(value) => *value,
+			DialogueValue::Text(text) => !text.is_empty()
+		}
+	}
+
+	pub fn as_number(&self) -> Option<f32> {
+		match self {
+			DialogueValue::Number(number) => Some(*number),
+			_ => None
+		}
+	}
+
+	pub fn as_bool(&self) -> Option<bool> {
+		match self {
+			DialogueValue::Bool(value) => Some(*value),
+			_ => None
+		}
+	}
+
+	pub fn as_text(&self) -> Option<&str> {
+		match self {
+			DialogueValue::Text(text) => Some(text),
+			_ => None
+		}
+	}
+}
+
+impl fmt::Display for DialogueValue {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			DialogueValue::Number(number) => write!(f, "{}", number),
+			DialogueValue::Bool(value) => write!(f, "{}", value),
+			DialogueValue::Text(text) => write!(f, "{}", text)
+		}
+	}
+}
+
+impl From<f32> for DialogueValue {
+	fn from(number: f32) -> Self {
+		DialogueValue::Number(number)
+	}
+}
+
+impl From<i32> for DialogueValue {
+	fn from(number: i32) -> Self {
+		DialogueValue::Number(number as f32)
+	}
+}
+
+impl From<bool> for DialogueValue {
+	fn from(value: bool) -> Self {
+		DialogueValue::Bool(value)
+	}
+}
+
+impl From<&str> for DialogueValue {
+	fn from(text: &str) -> Self {
+		DialogueValue::Text(text.to_string())
+	}
+}
+
+impl From<String> for DialogueValue {
+	fn from(text: String) -> Self {
+		DialogueValue::Text(text)
+	}
+}
+
+/// The variables a dialogue reads in conditions and `{name}` in text, and changes with `~` lines.
+/// The game can set them before a dialogue runs and read what the player chose afterwards.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DialogueVariables {
+	values: HashMap<String, DialogueValue>
+}
+
+impl DialogueVariables {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn get(&self, name: &str) -> Option<&DialogueValue> {
+		self.values.get(name)
+	}
+
+	pub fn set(&mut self, name: &str, value: impl Into<DialogueValue>) {
+		self.values.insert(name.to_string(), value.into());
+	}
+
+	pub fn remove(&mut self, name: &str) -> Option<DialogueValue> {
+		self.values.remove(name)
+	}
+
+	pub fn contains(&self, name: &str) -> bool {
+		self.values.contains_key(name)
+	}
+
+	pub fn clear(&mut self) {
+		self.values.clear();
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &DialogueValue)> {
+		self.values.iter().map(|(name, value)| (name.as_str(), value))
+	}
+}
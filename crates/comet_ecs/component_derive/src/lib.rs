@@ -2,17 +2,86 @@ extern crate proc_macro;
 
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, DeriveInput, Data, Fields};
+use syn::{parse_macro_input, Attribute, DeriveInput, Data, Expr, Fields, Index, Member};
+
+/// Which of the standard impls the derive should leave to the user.
+/// Set with `#[component(skip_default, skip_debug, skip_clone, skip_partial_eq)]` on the struct.
+/// Impls the struct derives itself in a `#[derive(...)]` below `#[derive(Component)]`, e.g. `#[derive(Debug)]`, are skipped as well.
+/// Fields can get a default other than `Default::default()` with `#[component(default = 1.0)]`.
+#[derive(Default)]
+struct Skips {
+    default: bool,
+    debug: bool,
+    clone: bool,
+    partial_eq: bool
+}
+
+impl Skips {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut skips = Skips::default();
+        for attr in attrs {
+            if attr.path().is_ident("component") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("skip_default") {
+                        skips.default = true;
+                    } else if meta.path.is_ident("skip_debug") {
+                        skips.debug = true;
+                    } else if meta.path.is_ident("skip_clone") {
+                        skips.clone = true;
+                    } else if meta.path.is_ident("skip_partial_eq") {
+                        skips.partial_eq = true;
+                    } else {
+                        return Err(meta.error("expected `skip_default`, `skip_debug`, `skip_clone` or `skip_partial_eq`"));
+                    }
+                    Ok(())
+                })?;
+            } else if attr.path().is_ident("derive") {
+                attr.parse_nested_meta(|meta| {
+                    let derived = meta.path.segments.last().map(|segment| segment.ident.to_string());
+                    match derived.as_deref() {
+                        Some("Default") => skips.default = true,
+                        Some("Debug") => skips.debug = true,
+                        Some("Clone") => skips.clone = true,
+                        Some("PartialEq") => skips.partial_eq = true,
+                        _ => {}
+                    }
+                    Ok(())
+                })?;
+            }
+        }
+        Ok(skips)
+    }
+}
 
-// This is the procedural macro for `derive(MyTrait)`
-#[proc_macro_derive(Component)]
+/// The value of `#[component(default = ...)]` on a field, if there is one.
+fn field_default(field: &syn::Field) -> syn::Result<Option<Expr>> {
+    let mut default = None;
+    for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("component")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("default") {
+                default = Some(meta.value()?.parse::<Expr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `default = <expression>`"))
+            }
+        })?;
+    }
+    Ok(default)
+}
+
+// This is the procedural macro for `derive(Component)`
+#[proc_macro_derive(Component, attributes(component))]
 pub fn my_trait_derive(input: TokenStream) -> TokenStream {
     // Parse the input tokens into a syntax tree (AST)
     let input = parse_macro_input!(input as DeriveInput);
 
     // Get the name of the struct
     let name = &input.ident;
-    let name = &input.ident;
+
+    let skips = match Skips::parse(&input.attrs) {
+        Ok(skips) => skips,
+        Err(error) => return error.to_compile_error().into()
+    };
 
     let fields = if let Data::Struct(data) = &input.data {
         match &data.fields {
@@ -24,107 +93,112 @@ pub fn my_trait_derive(input: TokenStream) -> TokenStream {
         panic!("Component derive macro only works on structs");
     };
 
-    let field_comparisons = fields.iter().map(|field| {
-        let field_name = &field.ident; // Name of the field
-        quote! {
-            self.#field_name == other.#field_name
-        }
-    });
-
-    let default_fields = if let Data::Struct(data) = &input.data {
-        match &data.fields {
-            Fields::Named(fields) => {
-                // Generate code to assign each field a default value
-                fields.named.iter().map(|field| {
-                    let field_name = &field.ident;
-                    quote! { #field_name: Default::default() }
-                }).collect::<Vec<_>>()
-            },
-            Fields::Unnamed(fields) => {
-                // Generate default values for tuple structs
-                fields.unnamed.iter().map(|_field| {
-                    quote! { Default::default() }
-                }).collect::<Vec<_>>()
-            },
-            Fields::Unit => Vec::new(),
-        }
-    } else {
-        panic!("Default can only be derived for structs");
-    };
+    // Names of named fields, indices of tuple fields
+    let members = fields.iter().enumerate().map(|(index, field)| match &field.ident {
+        Some(ident) => Member::Named(ident.clone()),
+        None => Member::Unnamed(Index::from(index))
+    }).collect::<Vec<_>>();
 
-    let debug_fields = fields.iter().map(|field| {
-        let field_name = field.ident.as_ref().expect("Expected named fields");
+    let field_comparisons = members.iter().map(|field_name| {
         quote! {
-            .field(stringify!(#field_name), &self.#field_name)
+            self.#field_name == other.#field_name
         }
     });
 
+    let mut default_fields = Vec::new();
     for field in &fields {
-        if !is_copy_field(&field) {
-            panic!("All fields in the struct must implement Copy");
-        }
+        let value = match field_default(field) {
+            Ok(Some(value)) => quote! { #value },
+            Ok(None) => quote! { Default::default() },
+            Err(error) => return error.to_compile_error().into()
+        };
+        // Generate default values for named and tuple structs
+        default_fields.push(match &field.ident {
+            Some(field_name) => quote! { #field_name: #value },
+            None => value
+        });
     }
 
-    // Generate the implementation of MyTrait for the given struct
-    let expanded = quote! {
-        impl Component for #name {
-            fn new() -> Self {
-                Default::default()
-            }
+    let default_body = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(_) => quote! { Self { #(#default_fields),* } },
+            Fields::Unnamed(_) => quote! { Self ( #(#default_fields),* ) },
+            Fields::Unit => quote! { Self },
+        },
+        _ => panic!("Default can only be derived for structs")
+    };
 
-            fn type_id() -> std::any::TypeId {
-                std::any::TypeId::of::<Self>()
-            }
+    let debug_fields = members.iter().map(|field_name| match field_name {
+        Member::Named(ident) => quote! { .field(stringify!(#ident), &self.#ident) },
+        Member::Unnamed(_) => quote! { .field(&self.#field_name) }
+    });
 
-            fn type_name() -> String {
-                std::any::type_name::<Self>().to_string()
-            }
-        }
+    let debug_builder = match members.first() {
+        Some(Member::Unnamed(_)) => quote! { debug_tuple },
+        _ => quote! { debug_struct }
+    };
 
+    let default_impl = (!skips.default).then(|| quote! {
         impl Default for #name {
             fn default() -> Self {
                 // Construct the struct with default values
-                Self {
-                    #(#default_fields),*
-                }
+                #default_body
             }
         }
+    });
 
+    let clone_impl = (!skips.clone).then(|| quote! {
         impl Clone for #name {
             fn clone(&self) -> Self {
-                Self {
-                    ..*self
-                }
+                *self
             }
         }
 
         impl Copy for #name {}
+    });
 
+    let debug_impl = (!skips.debug).then(|| quote! {
         impl std::fmt::Debug for #name {
             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                f.debug_struct(stringify!(#name))
+                f.#debug_builder(stringify!(#name))
                     #(#debug_fields)*
                     .finish()
             }
         }
+    });
 
+    let partial_eq_impl = (!skips.partial_eq).then(|| quote! {
         impl PartialEq for #name {
             fn eq(&self, other: &Self) -> bool {
                 true #(&& #field_comparisons)*
             }
         }
+    });
+
+    // Generate the implementation of Component for the given struct
+    let expanded = quote! {
+        impl Component for #name {
+            fn new() -> Self {
+                let mut component: Self = Default::default();
+                component.apply_theme();
+                component
+            }
+
+            fn type_id() -> std::any::TypeId {
+                std::any::TypeId::of::<Self>()
+            }
+
+            fn type_name() -> String {
+                std::any::type_name::<Self>().to_string()
+            }
+        }
+
+        #default_impl
+        #clone_impl
+        #debug_impl
+        #partial_eq_impl
     };
 
     // Convert the generated code into a TokenStream and return it
     TokenStream::from(expanded)
 }
-
-
-fn is_copy_field(field: &syn::Field) -> bool {
-    // Logic to check if the field type implements Copy (this is simplified)
-    // You might need more sophisticated logic to check the actual type of the field.
-    let field_type = &field.ty;
-    // Implement a check for Copy trait for the field type if needed
-    // Return true if it implements Copy; false otherwise
-    true // Assuming it does, just for simplicity
-}
\ No newline at end of file
@@ -0,0 +1,278 @@
+use crate::{Component, ComponentStorage, SparseSet, TypedSparseSet};
+use std::{
+	any::TypeId,
+	collections::HashMap,
+	fmt,
+	marker::PhantomData,
+	ops::{Deref, DerefMut},
+	ptr::NonNull,
+	sync::atomic::{AtomicIsize, Ordering}
+};
+
+#[cfg(debug_assertions)]
+use std::sync::Mutex;
+
+const WRITING: isize = -1;
+
+/// Why a column could not be borrowed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessConflict {
+	/// The component is not registered in the storage.
+	Unregistered(String),
+	/// Someone is writing to the column, so it can neither be read nor written.
+	Writing(String),
+	/// Someone is reading from the column, so it can not be written.
+	Reading(String)
+}
+
+impl fmt::Display for AccessConflict {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			AccessConflict::Unregistered(name) => write!(f, "the component {} is not registered", name),
+			AccessConflict::Writing(name) => write!(f, "the component {} is already borrowed mutably", name),
+			AccessConflict::Reading(name) => write!(f, "the component {} is already borrowed immutably", name)
+		}
+	}
+}
+
+impl std::error::Error for AccessConflict {}
+
+/// The component columns of a `ComponentStorage`, shareable between threads.
+/// Every column can be borrowed by any number of readers or by a single writer at a time,
+/// so systems touching different components (or only reading the same ones) can run concurrently.
+/// Conflicting borrows are caught at runtime. Debug builds also report the thread holding the column.
+pub struct ParallelComponents<'a> {
+	index_map: &'a HashMap<TypeId, usize>,
+	columns: Vec<NonNull<SparseSet>>,
+	borrows: Vec<AtomicIsize>,
+	#[cfg(debug_assertions)]
+	writers: Vec<Mutex<Option<String>>>,
+	marker: PhantomData<&'a mut ComponentStorage>
+}
+
+// The columns are only handed out through `read` and `write`, which require `T: Sync` and `T: Send`
+// and track the borrows of every column with an atomic counter.
+unsafe impl Send for ParallelComponents<'_> {}
+unsafe impl Sync for ParallelComponents<'_> {}
+
+impl<'a> ParallelComponents<'a> {
+	pub(crate) fn new(index_map: &'a HashMap<TypeId, usize>, components: &'a mut [SparseSet]) -> Self {
+		let columns: Vec<NonNull<SparseSet>> = components.iter_mut().map(NonNull::from).collect();
+		Self {
+			index_map,
+			borrows: columns.iter().map(|_| AtomicIsize::new(0)).collect(),
+			#[cfg(debug_assertions)]
+			writers: columns.iter().map(|_| Mutex::new(None)).collect(),
+			columns,
+			marker: PhantomData
+		}
+	}
+
+	fn index<T: Component + 'static>(&self) -> Result<usize, AccessConflict> {
+		self.index_map.get(&T::type_id()).copied().ok_or_else(|| AccessConflict::Unregistered(T::type_name()))
+	}
+
+	/// Borrows the column of `T` for reading.
+	pub fn try_read<T: Component + Sync + 'static>(&self) -> Result<ColumnRead<'_, T>, AccessConflict> {
+		let index = self.index::<T>()?;
+		let borrow = &self.borrows[index];
+		let mut current = borrow.load(Ordering::Relaxed);
+		loop {
+			if current == WRITING {
+				return Err(AccessConflict::Writing(T::type_name()));
+			}
+			match borrow.compare_exchange_weak(current, current + 1, Ordering::Acquire, Ordering::Relaxed) {
+				Ok(_) => break,
+				Err(actual) => current = actual
+			}
+		}
+
+		Ok(ColumnRead {
+			// SAFETY: the counter is positive until the guard is dropped, so nobody can write to the column
+			column: TypedSparseSet::from_untyped_ref(unsafe { self.columns[index].as_ref() }),
+			borrow
+		})
+	}
+
+	/// Borrows the column of `T` for writing.
+	pub fn try_write<T: Component + Send + 'static>(&self) -> Result<ColumnWrite<'_, T>, AccessConflict> {
+		let index = self.index::<T>()?;
+		let borrow = &self.borrows[index];
+		if let Err(current) = borrow.compare_exchange(0, WRITING, Ordering::Acquire, Ordering::Relaxed) {
+			return Err(if current == WRITING {
+				AccessConflict::Writing(T::type_name())
+			}
+			else {
+				AccessConflict::Reading(T::type_name())
+			});
+		}
+
+		#[cfg(debug_assertions)]
+		{
+			let thread = std::thread::current();
+			*self.writers[index].lock().unwrap() = Some(thread.name().map(str::to_string).unwrap_or_else(|| format!("{:?}", thread.id())));
+		}
+
+		Ok(ColumnWrite {
+			// SAFETY: the counter is set to WRITING until the guard is dropped, so this is the only borrow of the column
+			column: TypedSparseSet::from_untyped_mut(unsafe { &mut *self.columns[index].as_ptr() }),
+			borrow,
+			#[cfg(debug_assertions)]
+			writer: &self.writers[index]
+		})
+	}
+
+	/// Borrows the column of `T` for reading and panics if it is being written to or not registered.
+	pub fn read<T: Component + Sync + 'static>(&self) -> ColumnRead<'_, T> {
+		self.try_read::<T>().unwrap_or_else(|conflict| self.conflict::<T>(conflict))
+	}
+
+	/// Borrows the column of `T` for writing and panics if it is already borrowed or not registered.
+	pub fn write<T: Component + Send + 'static>(&self) -> ColumnWrite<'_, T> {
+		self.try_write::<T>().unwrap_or_else(|conflict| self.conflict::<T>(conflict))
+	}
+
+	#[cfg(debug_assertions)]
+	fn conflict<T: Component + 'static>(&self, conflict: AccessConflict) -> ! {
+		let writer = self.index::<T>().ok().and_then(|index| self.writers[index].lock().unwrap().clone());
+		match (&conflict, writer) {
+			(AccessConflict::Writing(_), Some(writer)) => panic!("Access conflict: {} (held by thread {})", conflict, writer),
+			_ => panic!("Access conflict: {}", conflict)
+		}
+	}
+
+	#[cfg(not(debug_assertions))]
+	fn conflict<T: Component + 'static>(&self, conflict: AccessConflict) -> ! {
+		panic!("Access conflict: {}", conflict)
+	}
+}
+
+/// Shared access to the column of `T`, released when dropped.
+pub struct ColumnRead<'a, T: 'static> {
+	column: &'a TypedSparseSet<T>,
+	borrow: &'a AtomicIsize
+}
+
+impl<T: 'static> Deref for ColumnRead<'_, T> {
+	type Target = TypedSparseSet<T>;
+
+	fn deref(&self) -> &Self::Target {
+		self.column
+	}
+}
+
+impl<T: 'static> Drop for ColumnRead<'_, T> {
+	fn drop(&mut self) {
+		self.borrow.fetch_sub(1, Ordering::Release);
+	}
+}
+
+/// Exclusive access to the column of `T`, released when dropped.
+pub struct ColumnWrite<'a, T: 'static> {
+	column: &'a mut TypedSparseSet<T>,
+	borrow: &'a AtomicIsize,
+	#[cfg(debug_assertions)]
+	writer: &'a Mutex<Option<String>>
+}
+
+impl<T: 'static> Deref for ColumnWrite<'_, T> {
+	type Target = TypedSparseSet<T>;
+
+	fn deref(&self) -> &Self::Target {
+		self.column
+	}
+}
+
+impl<T: 'static> DerefMut for ColumnWrite<'_, T> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		self.column
+	}
+}
+
+impl<T: 'static> Drop for ColumnWrite<'_, T> {
+	fn drop(&mut self) {
+		#[cfg(debug_assertions)]
+		{
+			*self.writer.lock().unwrap() = None;
+		}
+		self.borrow.store(0, Ordering::Release);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{Rotation2D, Rotation3D, Transform2D, World};
+	use crate::math::Vec2;
+	use super::*;
+
+	fn world() -> World {
+		let mut world = World::new("2D");
+		world.register_component::<Rotation2D>();
+		for _ in 0..4 {
+			let entity = world.new_entity() as usize;
+			world.add_component(entity, Rotation2D::from_angle(entity as f32));
+		}
+		world
+	}
+
+	#[test]
+	fn columns_are_cast_to_their_component() {
+		let mut world = world();
+		{
+			let components = world.parallel_components();
+			let rotations = components.read::<Rotation2D>();
+			let mut transforms = components.write::<Transform2D>();
+			for (entity, rotation) in rotations.iter() {
+				transforms.get_mut(entity).unwrap().translate(Vec2::new(rotation.angle(), 0.0));
+			}
+		}
+		for entity in 0..4 {
+			assert_eq!(world.get_component::<Transform2D>(entity).position().x(), entity as f32);
+		}
+	}
+
+	#[test]
+	fn conflicting_borrows_are_refused_until_released() {
+		let mut world = world();
+		let components = world.parallel_components();
+
+		let first = components.read::<Rotation2D>();
+		let second = components.try_read::<Rotation2D>();
+		assert!(second.is_ok());
+		assert!(matches!(components.try_write::<Rotation2D>(), Err(AccessConflict::Reading(_))));
+		drop((first, second));
+
+		let write = components.write::<Rotation2D>();
+		assert!(matches!(components.try_read::<Rotation2D>(), Err(AccessConflict::Writing(_))));
+		assert!(matches!(components.try_write::<Rotation2D>(), Err(AccessConflict::Writing(_))));
+		assert!(components.try_write::<Transform2D>().is_ok());
+		drop(write);
+
+		assert!(components.try_write::<Rotation2D>().is_ok());
+		assert!(matches!(components.try_read::<Rotation3D>(), Err(AccessConflict::Unregistered(_))));
+	}
+
+	#[test]
+	fn columns_are_written_from_several_threads() {
+		let mut world = world();
+		{
+			let components = world.parallel_components();
+			std::thread::scope(|scope| {
+				scope.spawn(|| {
+					for (_, rotation) in components.write::<Rotation2D>().iter_mut() {
+						*rotation = Rotation2D::from_angle(rotation.angle() * 2.0);
+					}
+				});
+				scope.spawn(|| {
+					for (_, transform) in components.write::<Transform2D>().iter_mut() {
+						transform.translate(Vec2::new(0.0, 1.0));
+					}
+				});
+			});
+		}
+		for entity in 0..4 {
+			assert_eq!(world.get_component::<Rotation2D>(entity).angle(), entity as f32 * 2.0);
+			assert_eq!(world.get_component::<Transform2D>(entity).position().y(), 1.0);
+		}
+	}
+}
@@ -4,10 +4,18 @@
 // so it's a collection of components bundled together (like Transform2D)
 
 use crate::math::{
+	quaternion::Quat,
+	Mat4,
+	Occluder,
+	Tween,
+	triangulate,
 	Vec2,
 	Vec3
 };
+use comet_resources::texture_atlas::TextureHandle;
+use comet_resources::vector::Tessellation;
 use component_derive::Component;
+use crate::inspect::{FieldValue, Inspect};
 
 // ##################################################
 // #                    BASIC                       #
@@ -41,11 +49,209 @@ pub struct Rectangle2D{
 	size: Vec2
 }
 
+/// The sprite of an entity. The texture can be shifted, repeated and scrolled across the sprite through its texture coordinates,
+/// e.g. for conveyor belts, flowing water or flipbooks stepping `uv_offset` through the frames of a sheet.
 #[derive(Component)]
 pub struct Render2D {
 	is_visible: bool,
-	texture: &'static str,
-	scale: Vec2
+	texture: TextureHandle,
+	#[component(default = Vec2::new(1.0, 1.0))]
+	scale: Vec2,
+	uv_offset: Vec2,
+	#[component(default = Vec2::new(1.0, 1.0))]
+	uv_scale: Vec2,
+	uv_scroll: Vec2
+}
+
+/// A bitmask of the layers an entity is drawn on. Cameras only draw entities sharing a layer with them,
+/// e.g. a minimap camera showing only the map layer. Layer 0 is bit 1 of the mask, and entities without
+/// the component or with a default one are on it, like `RenderLayers::DEFAULT`.
+#[derive(Component)]
+pub struct RenderLayers {
+	#[component(default = 1)]
+	mask: u32
+}
+
+/// A background texture scrolling slower than the world to fake depth, drawn behind all sprites.
+/// `factor` is how fast it follows the camera compared to the world: 0 stays fixed on the screen, 1 moves like a sprite.
+/// The `Transform2D` of the entity offsets it. Layers with a greater `depth` are drawn further back.
+#[derive(Component)]
+pub struct ParallaxLayer {
+	texture: TextureHandle,
+	factor: Vec2,
+	repeat_x: bool,
+	repeat_y: bool,
+	depth: i32
+}
+
+/// Leaves a ribbon behind the entity along the path it moved, e.g. for sword slashes and projectiles.
+/// The ribbon narrows from `start_width` to `end_width` world units and fades out as its points reach `lifetime` seconds.
+/// `taper` bends the width curve: 1 narrows linearly, higher values narrow sooner.
+#[derive(Component)]
+pub struct Trail2D {
+	texture: TextureHandle,
+	#[component(default = 0.25)]
+	lifetime: f32,
+	#[component(default = 16.0)]
+	start_width: f32,
+	end_width: f32,
+	#[component(default = 1.0)]
+	taper: f32,
+	#[component(default = 2.0)]
+	min_distance: f32,
+	#[component(default = true)]
+	emitting: bool
+}
+
+/// A corner of a `Mesh2D`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct MeshVertex2D {
+	/// In world units, relative to the `Transform2D` of the entity.
+	pub position: Vec2,
+	/// From 0 to 1 across the texture of the mesh. Coordinates past 1 repeat it, e.g. along a long strip of terrain.
+	pub uv: Vec2,
+	/// A linear rgba color multiplied with the texture.
+	pub color: [f32; 4]
+}
+
+impl MeshVertex2D {
+	pub fn new(position: Vec2, uv: Vec2, color: [f32; 4]) -> Self {
+		Self {
+			position,
+			uv,
+			color
+		}
+	}
+}
+
+/// Arbitrary triangles drawn at the `Transform2D` of the entity, e.g. polygon terrain, water surfaces or triangulated vector art.
+/// Meshes are batched with the sprites: a negative `layer` draws them below all sprites, zero and above on top of them.
+/// Without a texture the vertex colors are drawn as they are.
+#[derive(Component)]
+#[derive(Clone)]
+pub struct Mesh2D {
+	vertices: Vec<MeshVertex2D>,
+	/// Three indices into `vertices` per triangle.
+	indices: Vec<u32>,
+	#[component(default = TextureHandle::WHITE)]
+	texture: TextureHandle,
+	#[component(default = -1)]
+	layer: i32,
+	#[component(default = true)]
+	is_visible: bool
+}
+
+/// Briefly tints the sprite of the entity towards `color` when triggered, e.g. white when it takes damage.
+/// The flash fades out over `duration` seconds, the alpha of `color` is how strong it starts.
+#[derive(Component)]
+pub struct FlashOnHit {
+	#[component(default = [1.0, 1.0, 1.0, 1.0])]
+	color: [f32; 4],
+	#[component(default = 0.1)]
+	duration: f32,
+	remaining: f32
+}
+
+/// Shader parameters of the sprite of one entity: how far it dissolved and the outline around its opaque pixels.
+/// The parameters can be animated by name with tweens, e.g. `"dissolve"` from 0 to 1 when the entity dies.
+#[derive(Component)]
+#[derive(Clone)]
+#[component(skip_partial_eq)]
+pub struct SpriteMaterial {
+	dissolve: f32,
+	/// In texels of the sprite.
+	outline_width: f32,
+	#[component(default = [1.0, 1.0, 1.0, 1.0])]
+	outline_color: [f32; 4],
+	tweens: Vec<(String, Tween)>
+}
+
+/// The built-in effects a `SpriteEffect` can apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpriteEffectKind {
+	#[default]
+	None,
+	Outline,
+	Dissolve,
+	Grayscale,
+	/// Mixes the sprite towards a color for as long as the effect is on, unlike the short `FlashOnHit`.
+	Flash
+}
+
+/// A built-in shader effect on the sprite of the entity, so common effects need no WGSL:
+/// an outline, a noisy dissolve, grayscale or a flat color flash. It is drawn on top of a `SpriteMaterial`.
+#[derive(Component)]
+pub struct SpriteEffect {
+	kind: SpriteEffectKind,
+	#[component(default = [1.0, 1.0, 1.0, 1.0])]
+	color: [f32; 4],
+	/// The outline width in texels, the dissolve threshold or the grayscale strength from 0 to 1.
+	amount: f32
+}
+
+/// Draws a sprite in front of or behind other y-sorted sprites depending on how low its bottom edge is,
+/// so characters walk in front of and behind props in a top-down view. `bias` moves the edge used for sorting,
+/// e.g. up for a tree whose trunk starts above the bottom of its sprite.
+/// Sprites without it are drawn first, in the order they were created.
+#[derive(Component)]
+pub struct YSort {
+	bias: f32
+}
+
+/// Draws the sprite of the entity additively and brightened by `intensity`, so it lights up what is behind it
+/// and glows with bloom. Emissive sprites are drawn after all other sprites.
+#[derive(Component)]
+pub struct Emissive {
+	#[component(default = 2.0)]
+	intensity: f32
+}
+
+/// Grounds a sprite with a dark shape at its bottom edge, drawn above the background but below all sprites.
+/// By default it is a soft ellipse as wide as the sprite and squashed by `scale`, `silhouette` draws the sprite itself
+/// darkened and squashed instead. `offset` moves it in world units, `softness` is the part of the ellipse fading out towards its rim.
+#[derive(Component)]
+pub struct Shadow2D {
+	offset: Vec2,
+	#[component(default = Vec2::new(1.0, 0.3))]
+	scale: Vec2,
+	#[component(default = 0.4)]
+	opacity: f32,
+	#[component(default = 0.5)]
+	softness: f32,
+	silhouette: bool
+}
+
+/// Draws the sprite of the entity exactly where its `Transform2D` is instead of interpolating between the last two updates,
+/// e.g. for a cursor that has to follow the mouse without lagging behind.
+#[derive(Component)]
+pub struct NoInterpolation;
+
+/// Makes the transform of an entity relative to the transform of another one, e.g. a sword held in a hand.
+#[derive(Component)]
+pub struct Parent {
+	entity: u32
+}
+
+/// Lets the mouse drag the entity onto `DropTarget`s accepting one of its `groups`, once drag and drop is turned on in the app.
+/// The entity follows the cursor and slides back if dropped anywhere else. With `ghost` it stays in place
+/// and a copy of its sprite follows the cursor instead.
+#[derive(Component)]
+pub struct Draggable {
+	#[component(default = u32::MAX)]
+	groups: u32,
+	ghost: bool,
+	#[component(default = true)]
+	is_enabled: bool
+}
+
+/// A place `Draggable` entities can be dropped on, e.g. an inventory slot. Dropped entities are moved onto it
+/// unless `snap` is off. Found by its sprite, so it needs a `Render2D` too.
+#[derive(Component)]
+pub struct DropTarget {
+	#[component(default = u32::MAX)]
+	accepts: u32,
+	#[component(default = true)]
+	snap: bool
 }
 
 // ##################################################
@@ -60,8 +266,18 @@ pub struct Transform2D {
 
 #[derive(Component)]
 pub struct Transform3D {
-	position: Position3D,
-	rotation: Rotation3D
+	position: Vec3,
+	rotation: Quat,
+	#[component(default = Vec3::new(1.0, 1.0, 1.0))]
+	scale: Vec3
+}
+
+/// Where an entity with a `Transform3D` ends up in the world after applying the transforms of all its parents.
+/// Written by `World::propagate_transforms_3d`, so it is one update behind changes made after that.
+#[derive(Component)]
+pub struct GlobalTransform3D {
+	#[component(default = Mat4::IDENTITY)]
+	matrix: Mat4
 }
 
 // ##################################################
@@ -71,6 +287,11 @@ pub struct Transform3D {
 pub trait Component: Send + Sync + PartialEq + Default +  'static {
 	fn new() -> Self where Self: Sized;
 
+	/// Gives a freshly constructed component the defaults of the installed `Theme`. Called by the derived `new`.
+	fn apply_theme(&mut self) where Self: Sized {
+		crate::theme::apply_theme(self)
+	}
+
 	fn type_id() -> std::any::TypeId {
 		std::any::TypeId::of::<Self>()
 	}
@@ -87,8 +308,8 @@ pub trait Collider {
 pub trait Render {
 	fn is_visible(&self) -> bool;
 	fn set_visibility(&mut self, is_visible: bool);
-	fn get_texture(&self) -> String;
-	fn set_texture(&mut self, texture: &'static str);
+	fn get_texture(&self) -> TextureHandle;
+	fn set_texture(&mut self, texture: TextureHandle);
 }
 
 // ##################################################
@@ -159,6 +380,24 @@ impl Position3D {
 	}
 }
 
+impl Rotation2D {
+	/// A rotation by `theta` radians, counterclockwise.
+	pub fn from_angle(theta: f32) -> Self {
+		Self {
+			theta
+		}
+	}
+
+	/// The angle in radians, counterclockwise.
+	pub fn angle(&self) -> f32 {
+		self.theta
+	}
+
+	pub fn set_angle(&mut self, theta: f32) {
+		self.theta = theta;
+	}
+}
+
 impl Rectangle2D {
 	pub fn new(position: Position2D, size: Vec2) -> Self {
 		Self {
@@ -178,6 +417,11 @@ impl Rectangle2D {
 	pub fn size(&self) -> Vec2 {
 		self.size
 	}
+
+	/// The sides of the rectangle as walls for `VisibilityPolygon`.
+	pub fn occluders(&self) -> [Occluder; 4] {
+		Occluder::from_rect(self.position.as_vec(), self.size)
+	}
 }
 
 impl Collider for Rectangle2D {
@@ -208,18 +452,186 @@ impl Render for Render2D {
 		self.is_visible = is_visible;
 	}
 
-	fn get_texture(&self) -> String {
-		self.texture.clone().parse().unwrap()
+	fn get_texture(&self) -> TextureHandle {
+		self.texture
 	}
 
-	/// Use the actual file name of the texture instead of the path
-	/// e.g. "comet_icon.png" instead of "resources/textures/comet_icon.png"
-	/// The resource manager will already look in the resources/textures folder
-	fn set_texture(&mut self, texture: &'static str) {
+	fn set_texture(&mut self, texture: TextureHandle) {
 		self.texture = texture;
 	}
 }
 
+impl Render2D {
+	/// A visible sprite showing the texture loaded from `path`,
+	/// e.g. "resources/textures/comet_icon.png" like the paths given to the texture atlas.
+	pub fn with_texture(path: &str) -> Self {
+		Self {
+			is_visible: true,
+			texture: TextureHandle::from_name(path),
+			scale: Vec2::new(1.0, 1.0),
+			..Self::new()
+		}
+	}
+
+	/// Stretches the sprite around the pivot of its texture.
+	pub fn with_scale(mut self, scale: Vec2) -> Self {
+		self.scale = scale;
+		self
+	}
+
+	pub fn scale(&self) -> Vec2 {
+		self.scale
+	}
+
+	pub fn set_scale(&mut self, scale: Vec2) {
+		self.scale = scale;
+	}
+
+	/// Shifts the texture across the sprite, in sizes of the texture.
+	pub fn with_uv_offset(mut self, uv_offset: Vec2) -> Self {
+		self.uv_offset = uv_offset;
+		self
+	}
+
+	/// How often the texture repeats across the sprite, e.g. 0.25 to show a quarter of a sheet with four frames.
+	pub fn with_uv_scale(mut self, uv_scale: Vec2) -> Self {
+		self.uv_scale = uv_scale;
+		self
+	}
+
+	/// How many sizes of the texture it moves across the sprite per second. The GPU scrolls it, so it costs nothing per frame.
+	pub fn with_uv_scroll(mut self, uv_scroll: Vec2) -> Self {
+		self.uv_scroll = uv_scroll;
+		self
+	}
+
+	pub fn uv_offset(&self) -> Vec2 {
+		self.uv_offset
+	}
+
+	pub fn set_uv_offset(&mut self, uv_offset: Vec2) {
+		self.uv_offset = uv_offset;
+	}
+
+	pub fn uv_scale(&self) -> Vec2 {
+		self.uv_scale
+	}
+
+	pub fn set_uv_scale(&mut self, uv_scale: Vec2) {
+		self.uv_scale = uv_scale;
+	}
+
+	pub fn uv_scroll(&self) -> Vec2 {
+		self.uv_scroll
+	}
+
+	pub fn set_uv_scroll(&mut self, uv_scroll: Vec2) {
+		self.uv_scroll = uv_scroll;
+	}
+
+	/// Whether the texture coordinates differ from showing the texture once, which draws the sprite through its atlas region.
+	pub fn has_uv_transform(&self) -> bool {
+		self.uv_offset != Vec2::ZERO || self.uv_scale != Vec2::new(1.0, 1.0) || self.uv_scroll != Vec2::ZERO
+	}
+}
+
+impl ParallaxLayer {
+	/// A layer showing the texture loaded from `path` once, fixed on the screen.
+	pub fn with_texture(path: &str) -> Self {
+		Self {
+			texture: TextureHandle::from_name(path),
+			..Self::new()
+		}
+	}
+
+	pub fn with_factor(mut self, factor: Vec2) -> Self {
+		self.factor = factor;
+		self
+	}
+
+	/// Tiles the texture horizontally and/or vertically so it fills the screen.
+	pub fn with_repeat(mut self, repeat_x: bool, repeat_y: bool) -> Self {
+		self.repeat_x = repeat_x;
+		self.repeat_y = repeat_y;
+		self
+	}
+
+	pub fn with_depth(mut self, depth: i32) -> Self {
+		self.depth = depth;
+		self
+	}
+
+	pub fn texture(&self) -> TextureHandle {
+		self.texture
+	}
+
+	pub fn set_texture(&mut self, texture: TextureHandle) {
+		self.texture = texture;
+	}
+
+	pub fn factor(&self) -> Vec2 {
+		self.factor
+	}
+
+	pub fn set_factor(&mut self, factor: Vec2) {
+		self.factor = factor;
+	}
+
+	pub fn repeat_x(&self) -> bool {
+		self.repeat_x
+	}
+
+	pub fn repeat_y(&self) -> bool {
+		self.repeat_y
+	}
+
+	pub fn depth(&self) -> i32 {
+		self.depth
+	}
+}
+
+impl RenderLayers {
+	pub const DEFAULT: RenderLayers = RenderLayers { mask: 1 };
+	pub const ALL: RenderLayers = RenderLayers { mask: u32::MAX };
+	pub const NONE: RenderLayers = RenderLayers { mask: 0 };
+
+	/// Only the given layer, from 0 to 31.
+	pub fn layer(layer: u8) -> Self {
+		Self::NONE.with(layer)
+	}
+
+	pub fn from_mask(mask: u32) -> Self {
+		Self {
+			mask
+		}
+	}
+
+	pub fn mask(&self) -> u32 {
+		self.mask
+	}
+
+	pub fn with(mut self, layer: u8) -> Self {
+		assert!(layer < 32, "There are only 32 render layers");
+		self.mask |= 1 << layer;
+		self
+	}
+
+	pub fn without(mut self, layer: u8) -> Self {
+		assert!(layer < 32, "There are only 32 render layers");
+		self.mask &= !(1 << layer);
+		self
+	}
+
+	pub fn contains(&self, layer: u8) -> bool {
+		layer < 32 && self.mask & (1 << layer) != 0
+	}
+
+	/// Whether both share at least one layer.
+	pub fn intersects(&self, other: &RenderLayers) -> bool {
+		self.mask & other.mask != 0
+	}
+}
+
 impl Transform2D {
 	pub fn position(&self) -> &Position2D {
 		&self.position
@@ -245,20 +657,863 @@ impl Transform2D {
 	}
 }
 
+impl Trail2D {
+	/// A trail stretching the texture loaded from `path` along its length.
+	pub fn with_texture(path: &str) -> Self {
+		Self {
+			texture: TextureHandle::from_name(path),
+			..Self::new()
+		}
+	}
+
+	pub fn with_lifetime(mut self, lifetime: f32) -> Self {
+		self.lifetime = lifetime;
+		self
+	}
+
+	pub fn with_width(mut self, start_width: f32, end_width: f32) -> Self {
+		self.start_width = start_width;
+		self.end_width = end_width;
+		self
+	}
+
+	pub fn with_taper(mut self, taper: f32) -> Self {
+		self.taper = taper;
+		self
+	}
+
+	/// How far in world units the entity has to move before a new point is recorded.
+	pub fn with_min_distance(mut self, min_distance: f32) -> Self {
+		self.min_distance = min_distance;
+		self
+	}
+
+	pub fn texture(&self) -> TextureHandle {
+		self.texture
+	}
+
+	pub fn lifetime(&self) -> f32 {
+		self.lifetime
+	}
+
+	pub fn min_distance(&self) -> f32 {
+		self.min_distance
+	}
+
+	/// The width at `t`, going from 0 at the head to 1 at the end of the lifetime.
+	pub fn width_at(&self, t: f32) -> f32 {
+		self.end_width + (self.start_width - self.end_width) * (1.0 - t.clamp(0.0, 1.0)).powf(self.taper)
+	}
+
+	pub fn is_emitting(&self) -> bool {
+		self.emitting
+	}
+
+	/// Stops recording new points, the recorded ones still fade out.
+	pub fn set_emitting(&mut self, emitting: bool) {
+		self.emitting = emitting;
+	}
+}
+
+impl Mesh2D {
+	pub fn with_geometry(vertices: Vec<MeshVertex2D>, indices: Vec<u32>) -> Self {
+		Self {
+			vertices,
+			indices,
+			..Self::new()
+		}
+	}
+
+	/// A filled polygon in `color`, which may be concave as long as its edges do not cross.
+	/// The texture coordinates span its bounding box.
+	pub fn polygon(points: &[Vec2], color: [f32; 4]) -> Self {
+		Self::with_geometry(Self::spanning_vertices(points, color), triangulate(points))
+	}
+
+	/// The triangles of a tessellated vector path in `color`, see `VectorTessellator`.
+	/// The texture coordinates span its bounding box.
+	pub fn from_tessellation(tessellation: &Tessellation, color: [f32; 4]) -> Self {
+		let points: Vec<Vec2> = tessellation.positions().iter().map(|[x, y]| Vec2::new(*x, *y)).collect();
+		Self::with_geometry(Self::spanning_vertices(&points, color), tessellation.indices().to_vec())
+	}
+
+	/// Vertices at `points` whose texture coordinates span their bounding box, with the texture upright.
+	fn spanning_vertices(points: &[Vec2], color: [f32; 4]) -> Vec<MeshVertex2D> {
+		let (mut min, mut max) = (Vec2::new(f32::MAX, f32::MAX), Vec2::new(f32::MIN, f32::MIN));
+		for point in points {
+			min = Vec2::new(min.x().min(point.x()), min.y().min(point.y()));
+			max = Vec2::new(max.x().max(point.x()), max.y().max(point.y()));
+		}
+		let size = Vec2::new((max.x() - min.x()).max(f32::EPSILON), (max.y() - min.y()).max(f32::EPSILON));
+		points.iter()
+			.map(|point| MeshVertex2D::new(*point, Vec2::new((point.x() - min.x()) / size.x(), (max.y() - point.y()) / size.y()), color))
+			.collect()
+	}
+
+	/// A rectangle of `size` centered on the entity.
+	pub fn rectangle(size: Vec2, color: [f32; 4]) -> Self {
+		let (x, y) = (size.x() * 0.5, size.y() * 0.5);
+		Self::polygon(&[Vec2::new(-x, -y), Vec2::new(x, -y), Vec2::new(x, y), Vec2::new(-x, y)], color)
+	}
+
+	/// A circle centered on the entity, approximated by `segments` triangles.
+	pub fn circle(radius: f32, segments: u32, color: [f32; 4]) -> Self {
+		let segments = segments.max(3);
+		let points: Vec<Vec2> = (0..segments)
+			.map(|i| {
+				let angle = i as f32 / segments as f32 * std::f32::consts::TAU;
+				Vec2::new(angle.cos() * radius, angle.sin() * radius)
+			})
+			.collect();
+		Self::polygon(&points, color)
+	}
+
+	/// Draws the mesh with the texture loaded from `path`.
+	pub fn with_texture(mut self, path: &str) -> Self {
+		self.texture = TextureHandle::from_name(path);
+		self
+	}
+
+	pub fn with_layer(mut self, layer: i32) -> Self {
+		self.layer = layer;
+		self
+	}
+
+	pub fn vertices(&self) -> &[MeshVertex2D] {
+		&self.vertices
+	}
+
+	/// The vertices to change in place, e.g. to move the surface of water every update.
+	pub fn vertices_mut(&mut self) -> &mut [MeshVertex2D] {
+		&mut self.vertices
+	}
+
+	pub fn indices(&self) -> &[u32] {
+		&self.indices
+	}
+
+	/// Replaces all triangles of the mesh.
+	pub fn set_geometry(&mut self, vertices: Vec<MeshVertex2D>, indices: Vec<u32>) {
+		self.vertices = vertices;
+		self.indices = indices;
+	}
+
+	pub fn texture(&self) -> TextureHandle {
+		self.texture
+	}
+
+	pub fn set_texture(&mut self, texture: TextureHandle) {
+		self.texture = texture;
+	}
+
+	pub fn layer(&self) -> i32 {
+		self.layer
+	}
+
+	pub fn set_layer(&mut self, layer: i32) {
+		self.layer = layer;
+	}
+
+	pub fn is_visible(&self) -> bool {
+		self.is_visible
+	}
+
+	pub fn set_visibility(&mut self, is_visible: bool) {
+		self.is_visible = is_visible;
+	}
+}
+
+impl FlashOnHit {
+	/// A flash towards the linear rgba `color`, lasting `duration` seconds.
+	pub fn with_color(color: [f32; 4], duration: f32) -> Self {
+		Self {
+			color,
+			duration,
+			remaining: 0.0
+		}
+	}
+
+	pub fn color(&self) -> [f32; 4] {
+		self.color
+	}
+
+	pub fn duration(&self) -> f32 {
+		self.duration
+	}
+
+	/// Starts the flash over, even if one is still running.
+	pub fn trigger(&mut self) {
+		self.remaining = self.duration;
+	}
+
+	/// Counts the running flash down by `dt` seconds.
+	pub fn advance(&mut self, dt: f32) {
+		self.remaining = (self.remaining - dt).max(0.0);
+	}
+
+	pub fn is_flashing(&self) -> bool {
+		self.remaining > 0.0
+	}
+
+	/// The color to mix the sprite towards right now, its alpha being how much.
+	pub fn current(&self) -> [f32; 4] {
+		let strength = if self.duration > 0.0 { self.remaining / self.duration } else { 0.0 };
+		[self.color[0], self.color[1], self.color[2], self.color[3] * strength]
+	}
+}
+
+impl SpriteMaterial {
+	/// The names `parameter`, `set_parameter` and `tween` take.
+	pub const PARAMETERS: [&'static str; 3] = ["dissolve", "outline_width", "outline_alpha"];
+
+	/// Removes the fraction `dissolve` of the pixels of the sprite in a noisy pattern, from 0 for none to 1 for all.
+	pub fn with_dissolve(mut self, dissolve: f32) -> Self {
+		self.dissolve = dissolve;
+		self
+	}
+
+	/// Outlines the sprite `width` texels wide in the linear rgba `color`.
+	pub fn with_outline(mut self, color: [f32; 4], width: f32) -> Self {
+		self.outline_color = color;
+		self.outline_width = width;
+		self
+	}
+
+	pub fn dissolve(&self) -> f32 {
+		self.dissolve
+	}
+
+	pub fn outline_width(&self) -> f32 {
+		self.outline_width
+	}
+
+	pub fn outline_color(&self) -> [f32; 4] {
+		self.outline_color
+	}
+
+	pub fn set_outline_color(&mut self, color: [f32; 4]) {
+		self.outline_color = color;
+	}
+
+	pub fn parameter(&self, name: &str) -> Option<f32> {
+		match name {
+			"dissolve" => Some(self.dissolve),
+			"outline_width" => Some(self.outline_width),
+			"outline_alpha" => Some(self.outline_color[3]),
+			_ => None
+		}
+	}
+
+	/// Sets the parameter `name`. Returns false if there is no such parameter.
+	pub fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+		match name {
+			"dissolve" => self.dissolve = value.clamp(0.0, 1.0),
+			"outline_width" => self.outline_width = value.max(0.0),
+			"outline_alpha" => self.outline_color[3] = value.clamp(0.0, 1.0),
+			_ => return false
+		}
+		true
+	}
+
+	/// Animates the parameter `name` with `tween`, replacing a tween already running on it.
+	/// Returns false if there is no such parameter.
+	pub fn tween(&mut self, name: &str, tween: Tween) -> bool {
+		if !self.set_parameter(name, tween.value()) {
+			return false;
+		}
+		self.tweens.retain(|(parameter, _)| parameter != name);
+		self.tweens.push((name.to_string(), tween));
+		true
+	}
+
+	pub fn is_tweening(&self, name: &str) -> bool {
+		self.tweens.iter().any(|(parameter, _)| parameter == name)
+	}
+
+	pub fn stop_tweens(&mut self) {
+		self.tweens.clear();
+	}
+
+	/// Moves the running tweens `dt` seconds along and drops the finished ones.
+	pub fn advance(&mut self, dt: f32) {
+		let mut tweens = std::mem::take(&mut self.tweens);
+		for (parameter, tween) in tweens.iter_mut() {
+			tween.advance(dt);
+			self.set_parameter(parameter, tween.value());
+		}
+		tweens.retain(|(_, tween)| !tween.is_finished());
+		self.tweens = tweens;
+	}
+}
+
+/// Tweens compare by the parameters they animate, as their easing functions cannot be compared.
+impl PartialEq for SpriteMaterial {
+	fn eq(&self, other: &Self) -> bool {
+		self.dissolve == other.dissolve
+			&& self.outline_width == other.outline_width
+			&& self.outline_color == other.outline_color
+			&& self.tweens.iter().map(|(parameter, _)| parameter).eq(other.tweens.iter().map(|(parameter, _)| parameter))
+	}
+}
+
+impl SpriteEffect {
+	/// Outlines the opaque pixels of the sprite `width` texels wide in the linear rgba `color`.
+	pub fn outline(color: [f32; 4], width: f32) -> Self {
+		Self {
+			kind: SpriteEffectKind::Outline,
+			color,
+			amount: width
+		}
+	}
+
+	/// Removes the pixels whose noise value is below `threshold`, from 0 for none to 1 for all.
+	pub fn dissolve(threshold: f32) -> Self {
+		Self {
+			kind: SpriteEffectKind::Dissolve,
+			amount: threshold.clamp(0.0, 1.0),
+			..Self::new()
+		}
+	}
+
+	pub fn grayscale(amount: f32) -> Self {
+		Self {
+			kind: SpriteEffectKind::Grayscale,
+			amount: amount.clamp(0.0, 1.0),
+			..Self::new()
+		}
+	}
+
+	/// Mixes the sprite towards the rgb of the linear rgba `color` by its alpha.
+	pub fn flash(color: [f32; 4]) -> Self {
+		Self {
+			kind: SpriteEffectKind::Flash,
+			color,
+			amount: 0.0
+		}
+	}
+
+	pub fn kind(&self) -> SpriteEffectKind {
+		self.kind
+	}
+
+	pub fn color(&self) -> [f32; 4] {
+		self.color
+	}
+
+	pub fn set_color(&mut self, color: [f32; 4]) {
+		self.color = color;
+	}
+
+	pub fn amount(&self) -> f32 {
+		self.amount
+	}
+
+	pub fn set_amount(&mut self, amount: f32) {
+		self.amount = amount;
+	}
+}
+
+impl YSort {
+	pub fn with_bias(bias: f32) -> Self {
+		Self {
+			bias
+		}
+	}
+
+	pub fn bias(&self) -> f32 {
+		self.bias
+	}
+
+	pub fn set_bias(&mut self, bias: f32) {
+		self.bias = bias;
+	}
+}
+
+impl Draggable {
+	/// Only lets the entity be dropped on targets accepting one of the bits of `groups`, e.g. 0b01 for weapons.
+	pub fn with_groups(mut self, groups: u32) -> Self {
+		self.groups = groups;
+		self
+	}
+
+	pub fn with_ghost(mut self, ghost: bool) -> Self {
+		self.ghost = ghost;
+		self
+	}
+
+	pub fn groups(&self) -> u32 {
+		self.groups
+	}
+
+	pub fn is_ghost(&self) -> bool {
+		self.ghost
+	}
+
+	pub fn is_enabled(&self) -> bool {
+		self.is_enabled
+	}
+
+	/// Disabled entities cannot be picked up, e.g. items the player cannot afford.
+	pub fn set_enabled(&mut self, is_enabled: bool) {
+		self.is_enabled = is_enabled;
+	}
+}
+
+impl DropTarget {
+	/// A target only accepting draggables sharing a bit with `groups`.
+	pub fn accepting(groups: u32) -> Self {
+		Self {
+			accepts: groups,
+			snap: true
+		}
+	}
+
+	pub fn with_snap(mut self, snap: bool) -> Self {
+		self.snap = snap;
+		self
+	}
+
+	pub fn accepted_groups(&self) -> u32 {
+		self.accepts
+	}
+
+	pub fn set_accepted_groups(&mut self, groups: u32) {
+		self.accepts = groups;
+	}
+
+	pub fn accepts(&self, draggable: &Draggable) -> bool {
+		self.accepts & draggable.groups != 0
+	}
+
+	pub fn snaps(&self) -> bool {
+		self.snap
+	}
+}
+
+impl Emissive {
+	pub fn with_intensity(intensity: f32) -> Self {
+		Self {
+			intensity: intensity.max(0.0)
+		}
+	}
+
+	pub fn intensity(&self) -> f32 {
+		self.intensity
+	}
+
+	pub fn set_intensity(&mut self, intensity: f32) {
+		self.intensity = intensity.max(0.0);
+	}
+}
+
+impl Shadow2D {
+	/// A soft ellipse under the sprite.
+	pub fn blob() -> Self {
+		Self::new()
+	}
+
+	/// The darkened sprite itself, squashed under it.
+	pub fn silhouette() -> Self {
+		Self {
+			silhouette: true,
+			..Self::new()
+		}
+	}
+
+	pub fn with_offset(mut self, offset: Vec2) -> Self {
+		self.offset = offset;
+		self
+	}
+
+	/// The size relative to the sprite, e.g. `(1.0, 0.3)` for as wide as the sprite and a third of its height.
+	pub fn with_scale(mut self, scale: Vec2) -> Self {
+		self.scale = scale;
+		self
+	}
+
+	pub fn with_opacity(mut self, opacity: f32) -> Self {
+		self.opacity = opacity.clamp(0.0, 1.0);
+		self
+	}
+
+	/// Silhouettes always have hard edges.
+	pub fn with_softness(mut self, softness: f32) -> Self {
+		self.softness = softness.clamp(0.0, 1.0);
+		self
+	}
+
+	pub fn offset(&self) -> Vec2 {
+		self.offset
+	}
+
+	pub fn scale(&self) -> Vec2 {
+		self.scale
+	}
+
+	pub fn opacity(&self) -> f32 {
+		self.opacity
+	}
+
+	pub fn set_opacity(&mut self, opacity: f32) {
+		self.opacity = opacity.clamp(0.0, 1.0);
+	}
+
+	pub fn softness(&self) -> f32 {
+		self.softness
+	}
+
+	pub fn is_silhouette(&self) -> bool {
+		self.silhouette
+	}
+}
+
+impl Parent {
+	pub fn from_entity(entity: u32) -> Self {
+		Self {
+			entity
+		}
+	}
+
+	pub fn entity(&self) -> u32 {
+		self.entity
+	}
+}
+
 impl Transform3D {
-	pub fn position(&self) -> &Position3D {
+	pub fn from_position(position: Vec3) -> Self {
+		Self {
+			position,
+			..Self::new()
+		}
+	}
+
+	pub fn with_rotation(mut self, rotation: Quat) -> Self {
+		self.rotation = rotation;
+		self
+	}
+
+	pub fn with_scale(mut self, scale: Vec3) -> Self {
+		self.scale = scale;
+		self
+	}
+
+	pub fn position(&self) -> &Vec3 {
 		&self.position
 	}
 
-	pub fn position_mut(&mut self) -> &mut Position3D {
+	pub fn position_mut(&mut self) -> &mut Vec3 {
 		&mut self.position
 	}
 
-	pub fn rotation(&self) -> &Rotation3D {
+	pub fn rotation(&self) -> &Quat {
 		&self.rotation
 	}
 
-	pub fn rotation_mut(&mut self) -> &mut Rotation3D {
+	pub fn rotation_mut(&mut self) -> &mut Quat {
 		&mut self.rotation
 	}
-}
\ No newline at end of file
+
+	pub fn scale(&self) -> &Vec3 {
+		&self.scale
+	}
+
+	pub fn scale_mut(&mut self) -> &mut Vec3 {
+		&mut self.scale
+	}
+
+	pub fn translate(&mut self, displacement: Vec3) {
+		self.position += displacement;
+	}
+
+	/// Rotates by `rotation` on top of the current rotation.
+	pub fn rotate(&mut self, rotation: Quat) {
+		self.rotation = (rotation * self.rotation).normalize();
+	}
+
+	/// Turns the forward direction towards `target`, keeping the top as close to `up` as possible.
+	pub fn look_at(&mut self, target: Vec3, up: Vec3) {
+		self.rotation = Quat::look_rotation(target - self.position, up);
+	}
+
+	/// The direction the entity faces, -z in its local space.
+	pub fn forward(&self) -> Vec3 {
+		self.rotation.rotate(Vec3::Z * -1.0)
+	}
+
+	pub fn right(&self) -> Vec3 {
+		self.rotation.rotate(Vec3::X)
+	}
+
+	pub fn up(&self) -> Vec3 {
+		self.rotation.rotate(Vec3::Y)
+	}
+
+	/// Scales, then rotates, then moves, as a matrix acting on column vectors.
+	pub fn to_matrix(&self) -> Mat4 {
+		Mat4::from_translation(self.position) * self.rotation.to_matrix() * Mat4::from_scale(self.scale)
+	}
+}
+
+impl GlobalTransform3D {
+	pub fn from_matrix(matrix: Mat4) -> Self {
+		Self {
+			matrix
+		}
+	}
+
+	pub fn matrix(&self) -> &Mat4 {
+		&self.matrix
+	}
+
+	/// The position in the world.
+	pub fn position(&self) -> Vec3 {
+		Vec3::new(
+			self.matrix.get(0, 3).unwrap(),
+			self.matrix.get(1, 3).unwrap(),
+			self.matrix.get(2, 3).unwrap()
+		)
+	}
+
+	/// The direction the entity faces in the world.
+	pub fn forward(&self) -> Vec3 {
+		Vec3::new(
+			-self.matrix.get(0, 2).unwrap(),
+			-self.matrix.get(1, 2).unwrap(),
+			-self.matrix.get(2, 2).unwrap()
+		).normalize()
+	}
+}
+// ##################################################
+// #                   INSPECT                      #
+// ##################################################
+
+impl Inspect for Transform2D {
+	fn fields(&self) -> Vec<(&'static str, FieldValue)> {
+		vec![
+			("position.x", FieldValue::Float(self.position.x())),
+			("position.y", FieldValue::Float(self.position.y())),
+			("rotation", FieldValue::Float(self.rotation.theta))
+		]
+	}
+
+	fn set_field(&mut self, name: &str, value: FieldValue) -> bool {
+		match (name, value) {
+			("position.x", FieldValue::Float(x)) => self.position.set_x(x),
+			("position.y", FieldValue::Float(y)) => self.position.set_y(y),
+			("rotation", FieldValue::Float(theta)) => self.rotation.theta = theta,
+			_ => return false
+		}
+		true
+	}
+}
+
+impl Inspect for Render2D {
+	fn fields(&self) -> Vec<(&'static str, FieldValue)> {
+		vec![
+			("is_visible", FieldValue::Bool(self.is_visible)),
+			("scale.x", FieldValue::Float(self.scale.x())),
+			("scale.y", FieldValue::Float(self.scale.y())),
+			("uv_offset.x", FieldValue::Float(self.uv_offset.x())),
+			("uv_offset.y", FieldValue::Float(self.uv_offset.y())),
+			("uv_scale.x", FieldValue::Float(self.uv_scale.x())),
+			("uv_scale.y", FieldValue::Float(self.uv_scale.y())),
+			("uv_scroll.x", FieldValue::Float(self.uv_scroll.x())),
+			("uv_scroll.y", FieldValue::Float(self.uv_scroll.y()))
+		]
+	}
+
+	fn set_field(&mut self, name: &str, value: FieldValue) -> bool {
+		match (name, value) {
+			("is_visible", FieldValue::Bool(is_visible)) => self.is_visible = is_visible,
+			("scale.x", FieldValue::Float(x)) => self.scale.set_x(x),
+			("scale.y", FieldValue::Float(y)) => self.scale.set_y(y),
+			("uv_offset.x", FieldValue::Float(x)) => self.uv_offset.set_x(x),
+			("uv_offset.y", FieldValue::Float(y)) => self.uv_offset.set_y(y),
+			("uv_scale.x", FieldValue::Float(x)) => self.uv_scale.set_x(x),
+			("uv_scale.y", FieldValue::Float(y)) => self.uv_scale.set_y(y),
+			("uv_scroll.x", FieldValue::Float(x)) => self.uv_scroll.set_x(x),
+			("uv_scroll.y", FieldValue::Float(y)) => self.uv_scroll.set_y(y),
+			_ => return false
+		}
+		true
+	}
+}
+
+impl Inspect for ParallaxLayer {
+	fn fields(&self) -> Vec<(&'static str, FieldValue)> {
+		vec![
+			("factor.x", FieldValue::Float(self.factor.x())),
+			("factor.y", FieldValue::Float(self.factor.y())),
+			("repeat_x", FieldValue::Bool(self.repeat_x)),
+			("repeat_y", FieldValue::Bool(self.repeat_y)),
+			("depth", FieldValue::Int(self.depth as i64))
+		]
+	}
+
+	fn set_field(&mut self, name: &str, value: FieldValue) -> bool {
+		match (name, value) {
+			("factor.x", FieldValue::Float(x)) => self.factor.set_x(x),
+			("factor.y", FieldValue::Float(y)) => self.factor.set_y(y),
+			("repeat_x", FieldValue::Bool(repeat)) => self.repeat_x = repeat,
+			("repeat_y", FieldValue::Bool(repeat)) => self.repeat_y = repeat,
+			("depth", FieldValue::Int(depth)) => self.depth = depth.clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+			_ => return false
+		}
+		true
+	}
+}
+
+impl Inspect for Trail2D {
+	fn fields(&self) -> Vec<(&'static str, FieldValue)> {
+		vec![
+			("lifetime", FieldValue::Float(self.lifetime)),
+			("start_width", FieldValue::Float(self.start_width)),
+			("end_width", FieldValue::Float(self.end_width)),
+			("taper", FieldValue::Float(self.taper)),
+			("min_distance", FieldValue::Float(self.min_distance)),
+			("emitting", FieldValue::Bool(self.emitting))
+		]
+	}
+
+	fn set_field(&mut self, name: &str, value: FieldValue) -> bool {
+		match (name, value) {
+			("lifetime", FieldValue::Float(lifetime)) => self.lifetime = lifetime,
+			("start_width", FieldValue::Float(width)) => self.start_width = width,
+			("end_width", FieldValue::Float(width)) => self.end_width = width,
+			("taper", FieldValue::Float(taper)) => self.taper = taper,
+			("min_distance", FieldValue::Float(distance)) => self.min_distance = distance,
+			("emitting", FieldValue::Bool(emitting)) => self.emitting = emitting,
+			_ => return false
+		}
+		true
+	}
+}
+
+impl Inspect for Mesh2D {
+	fn fields(&self) -> Vec<(&'static str, FieldValue)> {
+		vec![
+			("is_visible", FieldValue::Bool(self.is_visible)),
+			("layer", FieldValue::Int(self.layer as i64))
+		]
+	}
+
+	fn set_field(&mut self, name: &str, value: FieldValue) -> bool {
+		match (name, value) {
+			("is_visible", FieldValue::Bool(is_visible)) => self.is_visible = is_visible,
+			("layer", FieldValue::Int(layer)) => self.layer = layer.clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+			_ => return false
+		}
+		true
+	}
+}
+
+impl Inspect for FlashOnHit {
+	fn fields(&self) -> Vec<(&'static str, FieldValue)> {
+		vec![
+			("color", FieldValue::Color(self.color)),
+			("duration", FieldValue::Float(self.duration))
+		]
+	}
+
+	fn set_field(&mut self, name: &str, value: FieldValue) -> bool {
+		match (name, value) {
+			("color", FieldValue::Color(color)) => self.color = color,
+			("duration", FieldValue::Float(duration)) => self.duration = duration,
+			_ => return false
+		}
+		true
+	}
+}
+
+impl Inspect for SpriteMaterial {
+	fn fields(&self) -> Vec<(&'static str, FieldValue)> {
+		vec![
+			("dissolve", FieldValue::Float(self.dissolve)),
+			("outline_width", FieldValue::Float(self.outline_width)),
+			("outline_color", FieldValue::Color(self.outline_color))
+		]
+	}
+
+	fn set_field(&mut self, name: &str, value: FieldValue) -> bool {
+		match (name, value) {
+			("outline_color", FieldValue::Color(color)) => self.outline_color = color,
+			(name, FieldValue::Float(value)) => return self.set_parameter(name, value),
+			_ => return false
+		}
+		true
+	}
+}
+
+impl Inspect for SpriteEffect {
+	fn fields(&self) -> Vec<(&'static str, FieldValue)> {
+		vec![
+			("color", FieldValue::Color(self.color)),
+			("amount", FieldValue::Float(self.amount))
+		]
+	}
+
+	fn set_field(&mut self, name: &str, value: FieldValue) -> bool {
+		match (name, value) {
+			("color", FieldValue::Color(color)) => self.color = color,
+			("amount", FieldValue::Float(amount)) => self.amount = amount,
+			_ => return false
+		}
+		true
+	}
+}
+
+impl Inspect for YSort {
+	fn fields(&self) -> Vec<(&'static str, FieldValue)> {
+		vec![("bias", FieldValue::Float(self.bias))]
+	}
+
+	fn set_field(&mut self, name: &str, value: FieldValue) -> bool {
+		match (name, value) {
+			("bias", FieldValue::Float(bias)) => self.bias = bias,
+			_ => return false
+		}
+		true
+	}
+}
+
+impl Inspect for Emissive {
+	fn fields(&self) -> Vec<(&'static str, FieldValue)> {
+		vec![("intensity", FieldValue::Float(self.intensity))]
+	}
+
+	fn set_field(&mut self, name: &str, value: FieldValue) -> bool {
+		match (name, value) {
+			("intensity", FieldValue::Float(intensity)) => self.intensity = intensity,
+			_ => return false
+		}
+		true
+	}
+}
+
+impl Inspect for Shadow2D {
+	fn fields(&self) -> Vec<(&'static str, FieldValue)> {
+		vec![
+			("offset.x", FieldValue::Float(self.offset.x())),
+			("offset.y", FieldValue::Float(self.offset.y())),
+			("scale.x", FieldValue::Float(self.scale.x())),
+			("scale.y", FieldValue::Float(self.scale.y())),
+			("opacity", FieldValue::Float(self.opacity)),
+			("softness", FieldValue::Float(self.softness)),
+			("silhouette", FieldValue::Bool(self.silhouette))
+		]
+	}
+
+	fn set_field(&mut self, name: &str, value: FieldValue) -> bool {
+		match (name, value) {
+			("offset.x", FieldValue::Float(x)) => self.offset.set_x(x),
+			("offset.y", FieldValue::Float(y)) => self.offset.set_y(y),
+			("scale.x", FieldValue::Float(x)) => self.scale.set_x(x),
+			("scale.y", FieldValue::Float(y)) => self.scale.set_y(y),
+			("opacity", FieldValue::Float(opacity)) => self.opacity = opacity,
+			("softness", FieldValue::Float(softness)) => self.softness = softness,
+			("silhouette", FieldValue::Bool(silhouette)) => self.silhouette = silhouette,
+			_ => return false
+		}
+		true
+	}
+}
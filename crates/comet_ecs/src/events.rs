@@ -0,0 +1,92 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+trait EventQueue {
+	fn update(&mut self);
+	fn as_any(&self) -> &dyn Any;
+	fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// Double buffered queue of a single event type.
+/// Events stay readable for the frame they were sent in and the following one.
+struct Queue<T> {
+	previous: Vec<T>,
+	current: Vec<T>
+}
+
+impl<T: 'static> EventQueue for Queue<T> {
+	fn update(&mut self) {
+		self.previous = std::mem::take(&mut self.current);
+	}
+
+	fn as_any(&self) -> &dyn Any {
+		self
+	}
+
+	fn as_any_mut(&mut self) -> &mut dyn Any {
+		self
+	}
+}
+
+/// Sent by the `World` for every entity `despawn_deferred` marked, once it is deleted at the end of the frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Despawned(pub u32);
+
+/// Storage for all events sent through the `World`, keyed by the type of the event.
+pub struct Events {
+	queues: HashMap<TypeId, Box<dyn EventQueue>>
+}
+
+impl Default for Events {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Events {
+	pub fn new() -> Self {
+		Self {
+			queues: HashMap::new()
+		}
+	}
+
+	fn queue<T: 'static>(&self) -> Option<&Queue<T>> {
+		self.queues.get(&TypeId::of::<T>())?.as_any().downcast_ref::<Queue<T>>()
+	}
+
+	fn queue_mut<T: 'static>(&mut self) -> &mut Queue<T> {
+		self.queues
+			.entry(TypeId::of::<T>())
+			.or_insert_with(|| Box::new(Queue::<T> { previous: Vec::new(), current: Vec::new() }))
+			.as_any_mut()
+			.downcast_mut::<Queue<T>>()
+			.unwrap()
+	}
+
+	pub fn send<T: 'static>(&mut self, event: T) {
+		self.queue_mut::<T>().current.push(event);
+	}
+
+	/// Returns all events of type `T` sent in the current and the previous frame, oldest first.
+	pub fn read<T: 'static>(&self) -> Vec<&T> {
+		match self.queue::<T>() {
+			Some(queue) => queue.previous.iter().chain(queue.current.iter()).collect(),
+			None => Vec::new()
+		}
+	}
+
+	/// Removes and returns all pending events of type `T`.
+	pub fn drain<T: 'static>(&mut self) -> Vec<T> {
+		let queue = self.queue_mut::<T>();
+		let mut events = std::mem::take(&mut queue.previous);
+		events.append(&mut queue.current);
+		events
+	}
+
+	/// Swaps the buffers of every event queue. Events older than one frame are dropped.
+	pub fn update(&mut self) {
+		for queue in self.queues.values_mut() {
+			queue.update();
+		}
+	}
+}
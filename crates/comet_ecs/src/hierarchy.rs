@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+use crate::math::Mat4;
+use crate::{GlobalTransform3D, Parent, Transform3D, World};
+use comet_log::*;
+
+impl World {
+	/// Writes the `GlobalTransform3D` of every entity having one from its `Transform3D` and the transforms of its parents.
+	/// Parents without a `Transform3D` end the chain, cycles are cut at the entity closing them.
+	pub fn propagate_transforms_3d(&mut self) {
+		let (Some(global_transforms), Some(transforms)) = (self.components().typed::<GlobalTransform3D>(), self.components().typed::<Transform3D>()) else {
+			return;
+		};
+
+		let entities: Vec<u32> = global_transforms.entries().iter()
+			.filter(|entity| transforms.contains(**entity))
+			.map(|entity| *entity as u32)
+			.collect();
+		let mut globals = HashMap::new();
+		for entity in &entities {
+			self.global_matrix(*entity, &mut globals, &mut Vec::new());
+		}
+
+		let global_transforms = self.components_mut().typed_mut::<GlobalTransform3D>().unwrap();
+		for entity in entities {
+			global_transforms.set(entity as usize, GlobalTransform3D::from_matrix(globals[&entity]));
+		}
+	}
+
+	fn global_matrix(&self, entity: u32, globals: &mut HashMap<u32, Mat4>, chain: &mut Vec<u32>) -> Mat4 {
+		if let Some(matrix) = globals.get(&entity) {
+			return *matrix;
+		}
+
+		let transforms = self.components().typed::<Transform3D>().unwrap();
+		let local = transforms.get(entity as usize).map(Transform3D::to_matrix).unwrap_or(Mat4::IDENTITY);
+		let parent = self.components().typed::<Parent>()
+			.and_then(|parents| parents.get(entity as usize))
+			.map(Parent::entity)
+			.filter(|parent| transforms.get(*parent as usize).is_some());
+
+		chain.push(entity);
+		let matrix = match parent {
+			Some(parent) if chain.contains(&parent) => {
+				warn_once!("Entity {} is its own ancestor, ignoring its parent", entity);
+				local
+			}
+			Some(parent) => self.global_matrix(parent, globals, chain) * local,
+			None => local
+		};
+		chain.pop();
+
+		globals.insert(entity, matrix);
+		matrix
+	}
+}
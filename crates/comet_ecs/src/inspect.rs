@@ -0,0 +1,18 @@
+/// The value of a component field as an inspector shows and edits it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldValue {
+	Float(f32),
+	Int(i64),
+	Bool(bool),
+	/// A linear RGBA color.
+	Color([f32; 4])
+}
+
+/// Components whose fields can be read and changed by name while the game runs, e.g. from the entity inspector.
+/// Vectors are split into one field per axis like `position.x`.
+pub trait Inspect {
+	fn fields(&self) -> Vec<(&'static str, FieldValue)>;
+
+	/// Sets the field `name`. Returns false if there is no such field or `value` is of another kind.
+	fn set_field(&mut self, name: &str, value: FieldValue) -> bool;
+}
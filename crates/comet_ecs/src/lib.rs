@@ -3,6 +3,11 @@ pub use entity::*;
 pub use component::*;
 pub use world::*;
 pub use id::*;
+pub use events::*;
+pub use access::*;
+pub use inspect::*;
+pub use query::*;
+pub use theme::*;
 pub use component_derive::*;
 pub use comet_math as math;
 
@@ -10,4 +15,10 @@ mod storage;
 mod entity;
 mod component;
 mod world;
-mod id;
\ No newline at end of file
+mod id;
+mod events;
+mod access;
+mod inspect;
+mod query;
+mod theme;
+mod hierarchy;
\ No newline at end of file
@@ -0,0 +1,65 @@
+use std::cmp::Ordering;
+
+/// The entities `World::query` found, in the order of their archetype unless sorted.
+/// That order changes as entities gain and lose components, so systems that need a fixed order sort the query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+	entities: Vec<u32>
+}
+
+impl Query {
+	pub fn new(entities: Vec<u32>) -> Self {
+		Self {
+			entities
+		}
+	}
+
+	/// Sorts the entities by their ids, which stay the same for as long as an entity lives.
+	pub fn stable(mut self) -> Self {
+		self.entities.sort_unstable();
+		self
+	}
+
+	/// Sorts the entities by `key`, e.g. the depth of their sprite. Entities with equal keys are ordered by their ids.
+	pub fn sorted_by_key<K: Ord>(self, mut key: impl FnMut(u32) -> K) -> Self {
+		let mut query = self.stable();
+		query.entities.sort_by_key(|entity| key(*entity));
+		query
+	}
+
+	/// Sorts the entities with `compare`, e.g. by a float with `f32::total_cmp`. Entities comparing equal are ordered by their ids.
+	pub fn sorted_by(self, mut compare: impl FnMut(u32, u32) -> Ordering) -> Self {
+		let mut query = self.stable();
+		query.entities.sort_by(|a, b| compare(*a, *b));
+		query
+	}
+
+	pub fn entities(&self) -> &[u32] {
+		&self.entities
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+		self.entities.iter().copied()
+	}
+
+	pub fn len(&self) -> usize {
+		self.entities.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entities.is_empty()
+	}
+
+	pub fn into_vec(self) -> Vec<u32> {
+		self.entities
+	}
+}
+
+impl IntoIterator for Query {
+	type Item = u32;
+	type IntoIter = std::vec::IntoIter<u32>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.entities.into_iter()
+	}
+}
@@ -1,4 +1,5 @@
-use crate::{Component};
+use crate::{Component, ParallelComponents};
+use comet_structs::SmallVec;
 use std::{
 	alloc::{
 		handle_alloc_error,
@@ -14,6 +15,7 @@ use std::{
 		Hash,
 		Hasher
 	},
+	marker::PhantomData,
 	mem::MaybeUninit,
 	ptr::NonNull
 };
@@ -66,6 +68,37 @@ impl BlobVec {
 		}
 	}
 
+	/// Makes room for at least `additional` more elements, at least doubling the capacity when it grows.
+	pub fn reserve(&mut self, additional: usize) {
+		let available_space = self.capacity - self.len;
+		if available_space < additional {
+			let required = self.len.checked_add(additional).expect("capacity overflow");
+			let new_capacity = required.max(self.capacity * 2).max(4);
+			self.grow_exact(new_capacity - self.capacity);
+		}
+	}
+
+	/// Frees the memory that is not used by any element.
+	pub fn shrink_to_fit(&mut self) {
+		if self.item_layout.size() == 0 || self.capacity == self.len {
+			return;
+		}
+
+		let old_layout = array_layout(&self.item_layout, self.capacity).expect("array layout should be valid");
+		if self.len == 0 {
+			unsafe { std::alloc::dealloc(self.get_ptr().as_ptr(), old_layout) };
+			self.data = NonNull::dangling();
+		}
+		else {
+			let new_layout = array_layout(&self.item_layout, self.len).expect("array layout should be valid");
+			unsafe {
+				let new_data = std::alloc::realloc(self.get_ptr().as_ptr(), old_layout, new_layout.size());
+				self.data = NonNull::new(new_data).unwrap_or_else(|| handle_alloc_error(new_layout));
+			}
+		}
+		self.capacity = self.len;
+	}
+
 	fn grow_exact(&mut self, increment: usize) {
 		debug_assert!(self.item_layout.size() != 0);
 
@@ -113,7 +146,7 @@ impl BlobVec {
 
 	#[inline]
 	pub unsafe fn push_uninit(&mut self) -> usize {
-		self.reserve_exact(1);
+		self.reserve(1);
 		let index = self.len;
 		self.len += 1;
 		index
@@ -137,6 +170,27 @@ impl BlobVec {
 		ptr::write(ptr,element);
 	}
 
+	/// Copies all `elements` to the end in one go.
+	///
+	/// # Safety
+	/// The vec has to store elements of type `T`.
+	pub unsafe fn extend_from_slice<T: Copy>(&mut self, elements: &[T]) {
+		self.reserve(elements.len());
+		if self.item_layout.size() != 0 {
+			let end = self.get_ptr().as_ptr().add(self.len * self.item_layout.size()) as *mut T;
+			ptr::copy_nonoverlapping(elements.as_ptr(), end, elements.len());
+		}
+		self.len += elements.len();
+	}
+
+	/// Moves the element at `index` out and fills the gap with the last one.
+	///
+	/// # Safety
+	/// The vec has to store elements of type `T` and `index` has to be in bounds.
+	pub unsafe fn swap_remove<T>(&mut self, index: usize) -> T {
+		ptr::read(self.swap_remove_and_forget_unchecked(index) as *const T)
+	}
+
 	pub fn clear(&mut self) {
 		let len = self.len;
 		// We set len to 0 _before_ dropping elements for unwind safety. This ensures we don't
@@ -182,13 +236,16 @@ impl BlobVec {
 impl Drop for BlobVec {
 	fn drop(&mut self) {
 		self.clear();
+		if self.item_layout.size() == 0 {
+			return;
+		}
 		let array_layout =
 			array_layout(&self.item_layout, self.capacity).expect("array layout should be valid");
-		if array_layout.size() > 0 {
-			unsafe {
+		unsafe {
+			if array_layout.size() > 0 {
 				std::alloc::dealloc(self.get_ptr().as_ptr(), array_layout);
-				std::alloc::dealloc(self.swap_scratch.as_ptr(), self.item_layout);
 			}
+			std::alloc::dealloc(self.swap_scratch.as_ptr(), self.item_layout);
 		}
 	}
 }
@@ -219,7 +276,9 @@ const fn padding_needed_for(layout: &Layout, align: usize) -> usize {
 
 #[derive(Debug, Clone)]
 pub struct Column {
-	pub data: BlobVec
+	pub data: BlobVec,
+	type_id: TypeId,
+	type_name: &'static str
 }
 
 impl Column {
@@ -230,15 +289,31 @@ impl Column {
 		};
 		Self {
 			data: BlobVec::new(layout, drop_fn, capacity),
+			type_id: TypeId::of::<T>(),
+			type_name: std::any::type_name::<T>()
 		}
 	}
 
+	/// The type of the elements stored in the column.
+	pub fn type_id(&self) -> TypeId {
+		self.type_id
+	}
+
+	/// Checks that the column stores `T`. Only done in debug builds, as the typed wrappers already guarantee it.
+	#[inline]
+	fn check_type<T: 'static>(&self) {
+		debug_assert!(
+			TypeId::of::<T>() == self.type_id,
+			"Type mismatch: the column stores {} but was accessed as {}", self.type_name, std::any::type_name::<T>()
+		);
+	}
+
 	pub fn data(&self) -> BlobVec {
 		self.data.clone()
 	}
 
 	pub fn push<T: 'static>(&mut self, item: T) {
-		assert_eq!(TypeId::of::<T>(), TypeId::of::<T>(), "Type mismatch");
+		self.check_type::<T>();
 		unsafe {
 			let index = self.data.push_uninit();
 			let ptr = self.data.get_unchecked(index);
@@ -247,7 +322,7 @@ impl Column {
 	}
 
 	pub fn get<T: 'static>(&self, index: usize) -> Option<&T> {
-		assert_eq!(TypeId::of::<T>(), TypeId::of::<T>(), "Type mismatch");
+		self.check_type::<T>();
 		if index >= self.data.len() {
 			return None;
 		}
@@ -258,7 +333,7 @@ impl Column {
 	}
 
 	pub fn get_mut<T: 'static>(&mut self, index: usize) -> Option<&mut T> {
-		assert_eq!(TypeId::of::<T>(), TypeId::of::<T>(), "Type mismatch");
+		self.check_type::<T>();
 
 		if index >= self.data.len() {
 			return None;
@@ -273,14 +348,43 @@ impl Column {
 	}
 
 	pub fn remove<T: 'static>(&mut self, index: usize) -> Option<T> {
-		assert_eq!(TypeId::of::<T>(), TypeId::of::<T>(), "Type mismatch");
+		self.swap_remove::<T>(index)
+	}
+
+	/// Removes the element at `index` and moves the last element into its place.
+	pub fn swap_remove<T: 'static>(&mut self, index: usize) -> Option<T> {
+		self.check_type::<T>();
 		if index >= self.data.len() {
 			return None;
 		}
-		unsafe {
-			let ptr = self.data.swap_remove_and_forget_unchecked(index);
-			Some(ptr::read(ptr as *const T))
-		}
+		Some(unsafe { self.data.swap_remove::<T>(index) })
+	}
+
+	/// Appends copies of all `items`.
+	pub fn extend_from_slice<T: Copy + 'static>(&mut self, items: &[T]) {
+		self.check_type::<T>();
+		unsafe { self.data.extend_from_slice(items) };
+	}
+
+	pub fn len(&self) -> usize {
+		self.data.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.data.is_empty()
+	}
+
+	pub fn capacity(&self) -> usize {
+		self.data.capacity()
+	}
+
+	/// Makes room for at least `additional` more elements.
+	pub fn reserve(&mut self, additional: usize) {
+		self.data.reserve(additional);
+	}
+
+	pub fn shrink_to_fit(&mut self) {
+		self.data.shrink_to_fit();
 	}
 
 	fn swap(&mut self, index1: usize, index2: usize) {
@@ -320,6 +424,7 @@ impl<'a, K, V> Iterator for IterMut<'a, K, V> {
 pub struct SparseSet {
 	sparse: Vec<Option<usize>>,
 	dense: Column,
+	ids: Vec<usize>,
 	len: usize
 }
 
@@ -328,11 +433,45 @@ impl SparseSet {
 		Self {
 			sparse: Vec::with_capacity(capacity),
 			dense: Column::new::<T>(capacity),
+			ids: Vec::with_capacity(capacity),
 			len: 0
 		}
 	}
 
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	pub fn contains(&self, index: usize) -> bool {
+		self.sparse.get(index).is_some_and(|column_index| column_index.is_some())
+	}
+
+	pub fn capacity(&self) -> usize {
+		self.dense.capacity()
+	}
+
+	/// Makes room for at least `additional` more elements without reallocating.
+	pub fn reserve(&mut self, additional: usize) {
+		self.dense.reserve(additional);
+		self.ids.reserve(additional);
+	}
+
+	/// Frees the memory that is not used by any element.
+	pub fn shrink_to_fit(&mut self) {
+		while self.sparse.last().is_some_and(|column_index| column_index.is_none()) {
+			self.sparse.pop();
+		}
+		self.sparse.shrink_to_fit();
+		self.dense.shrink_to_fit();
+		self.ids.shrink_to_fit();
+	}
+
 	pub fn set<T: 'static>(&mut self, index: usize, element: T) {
+		self.dense.check_type::<T>();
 		if index >= self.sparse.len() {
 			self.sparse.resize_with(index + 1, || None);
 		}
@@ -346,44 +485,220 @@ impl SparseSet {
 			}
 		} else {
 			let column_index = unsafe { self.dense.data.push_uninit() };
+			let element = std::mem::ManuallyDrop::new(element);
 			unsafe {
-				self.dense.data.initialize_unchecked(column_index, &element as *const T as *mut u8);
+				self.dense.data.initialize_unchecked(column_index, &*element as *const T as *mut u8);
 			}
 			self.sparse[index] = Some(column_index);
+			self.ids.push(index);
 			self.len += 1;
 		}
 	}
 
 	pub fn remove<T: 'static>(&mut self, index: usize) -> Option<T> {
-		if index >= self.sparse.len() || self.sparse[index] == None {
-			return None;
+		self.dense.check_type::<T>();
+		let element = self.take(index)?;
+		Some(unsafe { ptr::read(element as *const T) })
+	}
+
+	/// Removes and drops the element at `index` without knowing its type. Returns false if there was none.
+	pub fn delete(&mut self, index: usize) -> bool {
+		match self.take(index) {
+			Some(element) => {
+				unsafe { (self.dense.data.drop)(element) };
+				true
+			}
+			None => false
 		}
+	}
 
-		let column_index = self.sparse[index];
+	/// Removes the element from the set and returns a pointer to it that is valid until the next removal.
+	fn take(&mut self, index: usize) -> Option<*mut u8> {
+		let column_index = (*self.sparse.get(index)?)?;
 		let element = unsafe {
-			self.dense.data.swap_remove_and_forget_unchecked(column_index.unwrap())
+			self.dense.data.swap_remove_and_forget_unchecked(column_index)
 		};
 
+		// the last element was moved into the freed slot
+		self.ids.swap_remove(column_index);
+		if let Some(&moved) = self.ids.get(column_index) {
+			self.sparse[moved] = Some(column_index);
+		}
 		self.sparse[index] = None;
 		self.len -= 1;
 
-		Some(unsafe { ptr::read(element as *const T) })
+		Some(element)
 	}
 
 	pub fn get<T: 'static>(&self, index: usize) -> Option<&T> {
-		if index >= self.sparse.len() || self.sparse[index] == None {
-			return None;
+		let column_index = (*self.sparse.get(index)?)?;
+		self.dense.get::<T>(column_index)
+	}
+
+	pub fn get_mut<T: 'static>(&mut self, index: usize) -> Option<&mut T> {
+		let column_index = (*self.sparse.get(index)?)?;
+		self.dense.get_mut::<T>(column_index)
+	}
+
+	/// The indices that have an element, in storage order.
+	pub fn entries(&self) -> &[usize] {
+		&self.ids
+	}
+
+	/// Iterates over the indices and elements in storage order.
+	pub fn iter<T: 'static>(&self) -> impl Iterator<Item = (usize, &T)> + '_ {
+		self.ids.iter()
+			.enumerate()
+			.filter_map(|(column_index, &index)| Some((index, self.dense.get::<T>(column_index)?)))
+	}
+
+	/// Iterates over the indices and mutable elements in storage order.
+	pub fn iter_mut<T: 'static>(&mut self) -> impl Iterator<Item = (usize, &mut T)> + '_ {
+		self.dense.check_type::<T>();
+		let size = self.dense.data.item_layout.size();
+		let data = unsafe { self.dense.data.get_ptr().as_ptr() };
+		self.ids.iter()
+			.enumerate()
+			// every column index is a distinct element, so the references never alias
+			.map(move |(column_index, &index)| (index, unsafe { &mut *(data.add(column_index * size) as *mut T) }))
+	}
+
+	/// Removes and drops every element for which `keep` returns false.
+	pub fn retain<T: 'static, F: FnMut(usize, &mut T) -> bool>(&mut self, mut keep: F) {
+		// walking backwards, the element swapped into a removed slot was already visited
+		for column_index in (0..self.ids.len()).rev() {
+			let index = self.ids[column_index];
+			let Some(element) = self.dense.get_mut::<T>(column_index) else { continue };
+			if !keep(index, element) {
+				drop(self.remove::<T>(index));
+			}
 		}
+	}
 
-		self.dense.get::<T>(index)
+	/// Removes every element and returns them together with their indices.
+	pub fn drain<T: 'static>(&mut self) -> Vec<(usize, T)> {
+		let mut drained = Vec::with_capacity(self.len);
+		while let Some(&index) = self.ids.last() {
+			if let Some(element) = self.remove::<T>(index) {
+				drained.push((index, element));
+			}
+		}
+		drained.reverse();
+		drained
 	}
+}
 
-	pub fn get_mut<T: 'static>(&mut self, index: usize) -> Option<&mut T> {
-		if index >= self.sparse.len() || self.sparse[index] == None {
-			return None;
+/// A `SparseSet` that only holds `T`, so the element type doesn't have to be given on every call
+/// and can't be mixed up.
+#[derive(Debug, Clone)]
+#[repr(transparent)]
+pub struct TypedSparseSet<T: 'static> {
+	set: SparseSet,
+	marker: PhantomData<T>
+}
+
+impl<T: 'static> TypedSparseSet<T> {
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			set: SparseSet::new::<T>(capacity),
+			marker: PhantomData
 		}
+	}
+
+	/// Wraps an untyped set. Returns `None` if it doesn't store `T`.
+	pub fn from_untyped(set: SparseSet) -> Option<Self> {
+		(set.dense.type_id() == TypeId::of::<T>()).then_some(Self {
+			set,
+			marker: PhantomData
+		})
+	}
 
-		self.dense.get_mut::<T>(index)
+	pub(crate) fn from_untyped_ref(set: &SparseSet) -> &Self {
+		set.dense.check_type::<T>();
+		// repr(transparent) makes both types share their layout
+		unsafe { &*(set as *const SparseSet as *const Self) }
+	}
+
+	pub(crate) fn from_untyped_mut(set: &mut SparseSet) -> &mut Self {
+		set.dense.check_type::<T>();
+		unsafe { &mut *(set as *mut SparseSet as *mut Self) }
+	}
+
+	pub fn as_untyped(&self) -> &SparseSet {
+		&self.set
+	}
+
+	pub fn into_untyped(self) -> SparseSet {
+		self.set
+	}
+
+	pub fn len(&self) -> usize {
+		self.set.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.set.is_empty()
+	}
+
+	pub fn contains(&self, index: usize) -> bool {
+		self.set.contains(index)
+	}
+
+	pub fn capacity(&self) -> usize {
+		self.set.capacity()
+	}
+
+	pub fn reserve(&mut self, additional: usize) {
+		self.set.reserve(additional);
+	}
+
+	pub fn shrink_to_fit(&mut self) {
+		self.set.shrink_to_fit();
+	}
+
+	pub fn set(&mut self, index: usize, element: T) {
+		self.set.set(index, element);
+	}
+
+	/// Sets every element, reserving the space for all of them up front.
+	pub fn extend<I: IntoIterator<Item = (usize, T)>>(&mut self, elements: I) {
+		let elements = elements.into_iter();
+		self.reserve(elements.size_hint().0);
+		for (index, element) in elements {
+			self.set(index, element);
+		}
+	}
+
+	pub fn remove(&mut self, index: usize) -> Option<T> {
+		self.set.remove::<T>(index)
+	}
+
+	pub fn get(&self, index: usize) -> Option<&T> {
+		self.set.get::<T>(index)
+	}
+
+	pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+		self.set.get_mut::<T>(index)
+	}
+
+	pub fn entries(&self) -> &[usize] {
+		self.set.entries()
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> + '_ {
+		self.set.iter::<T>()
+	}
+
+	pub fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut T)> + '_ {
+		self.set.iter_mut::<T>()
+	}
+
+	pub fn retain<F: FnMut(usize, &mut T) -> bool>(&mut self, keep: F) {
+		self.set.retain::<T, F>(keep);
+	}
+
+	pub fn drain(&mut self) -> Vec<(usize, T)> {
+		self.set.drain::<T>()
 	}
 }
 
@@ -438,39 +753,52 @@ impl ComponentStorage {
 		self.components.push(SparseSet::new::<T>(capacity));
 	}
 
+	/// The storage of `T`, if it is registered.
+	pub fn typed<T: Component + 'static>(&self) -> Option<&TypedSparseSet<T>> {
+		self.index_map.get(&T::type_id())
+			.and_then(|&index| self.components.get(index))
+			.map(TypedSparseSet::from_untyped_ref)
+	}
+
+	pub fn typed_mut<T: Component + 'static>(&mut self) -> Option<&mut TypedSparseSet<T>> {
+		self.index_map.get(&T::type_id())
+			.and_then(|&index| self.components.get_mut(index))
+			.map(TypedSparseSet::from_untyped_mut)
+	}
+
 	pub fn get_component<T: Component + 'static>(&self, entity_id: usize) -> Option<&T> {
-		//self.storage.get(&T::type_id()).unwrap().get::<T>(*entity.id() as usize)
-		self.components.get(*self.index_map.get(&T::type_id()).unwrap()).unwrap().get::<T>(entity_id)
+		self.typed::<T>().unwrap().get(entity_id)
 	}
 
 	pub fn get_component_mut<T: Component + 'static>(&mut self, entity_id: usize) -> Option<&mut T> {
-		self.components.get_mut(*self.index_map.get(&T::type_id()).unwrap()).unwrap().get_mut::<T>(entity_id)
+		self.typed_mut::<T>().unwrap().get_mut(entity_id)
 	}
 
-	pub fn set_component<T: Component + 'static>(&mut self, entity_id: usize, component: T) {
-		let index = *self.index_map.get(&T::type_id()).unwrap();
-		let sparse_set = self.components.get_mut(index).unwrap();
+	/// Makes room for at least `additional` more components of type `T`, e.g. before spawning many entities.
+	pub fn reserve<T: Component + 'static>(&mut self, additional: usize) {
+		if let Some(column) = self.typed_mut::<T>() {
+			column.reserve(additional);
+		}
+	}
 
-		// Check if a component already exists for this entity
-		if let Some(existing_component) = sparse_set.get_mut::<T>(entity_id) {
-			// Explicitly drop the existing component before overwriting it
-			std::mem::drop(existing_component);
+	/// Frees the memory that is not used by any component.
+	pub fn shrink_to_fit(&mut self) {
+		for column in self.components.iter_mut() {
+			column.shrink_to_fit();
 		}
+	}
 
-		// Set the new component
-		sparse_set.set(entity_id, component);
+	pub fn set_component<T: Component + 'static>(&mut self, entity_id: usize, component: T) {
+		// set drops a component the entity already has
+		self.typed_mut::<T>().unwrap().set(entity_id, component);
 	}
 
 	pub fn deregister_component<T: Component + 'static>(&mut self) {
 		let type_id = T::type_id();
 		if let Some(&index) = self.index_map.get(&type_id) {
 			// Before removing the SparseSet, ensure all elements are properly dropped
-			let sparse_set = self.components.get_mut(index).unwrap();
-			for i in 0..sparse_set.sparse.len() {
-				if sparse_set.sparse[i].is_some() {
-					sparse_set.remove::<T>(i);
-				}
-			}
+			let sparse_set = TypedSparseSet::<T>::from_untyped_mut(self.components.get_mut(index).unwrap());
+			sparse_set.drain();
 
 			self.components.remove(index);
 			self.index_map.remove(&type_id);
@@ -479,24 +807,13 @@ impl ComponentStorage {
 	}
 
 	pub fn remove_component<T: Component + 'static>(&mut self, entity_id: usize) {
-		if let Some(index) = self.index_map.get(&T::type_id()) {
-			let sparse_set = self.components.get_mut(*index).unwrap();
-			sparse_set.remove::<T>(entity_id);
+		if let Some(sparse_set) = self.typed_mut::<T>() {
+			sparse_set.remove(entity_id);
 		}
 	}
 
 	pub(crate) fn get_dense_list_as_vec<T: Component + Clone + 'static>(&self) -> Option<Vec<T>> {
-		let mut resulting_vec: Vec<T> = Vec::new();
-
-		if let Some(sparse_set) = self.components.get(*self.index_map.get(&T::type_id()).unwrap()) {
-			for i in 0..sparse_set.dense.data.len() {
-				let item: T = sparse_set.dense.get::<T>(i)?.clone();
-				resulting_vec.push(item);
-			}
-			Some(resulting_vec)
-		} else {
-			None
-		}
+		self.typed::<T>().map(|sparse_set| sparse_set.iter().map(|(_, component)| component.clone()).collect())
 	}
 
 	pub fn iter_mut(&mut self) -> IterMut<'_, TypeId, SparseSet> {
@@ -505,6 +822,44 @@ impl ComponentStorage {
 			values_iter: self.components.iter_mut(),
 		}
 	}
+
+	/// Iterates over the registered component types and their storage.
+	pub fn iter(&self) -> impl Iterator<Item = (&TypeId, &SparseSet)> {
+		self.keys.iter().zip(self.components.iter())
+	}
+
+	/// The ids of the entities that have a `T`. Empty if `T` is not registered.
+	pub fn entries<T: Component + 'static>(&self) -> &[usize] {
+		self.typed::<T>().map(|column| column.entries()).unwrap_or(&[])
+	}
+
+	/// Iterates over the entity ids and components of type `T`.
+	pub fn iter_component<T: Component + 'static>(&self) -> impl Iterator<Item = (usize, &T)> {
+		self.typed::<T>().into_iter().flat_map(|column| column.iter())
+	}
+
+	pub fn iter_component_mut<T: Component + 'static>(&mut self) -> impl Iterator<Item = (usize, &mut T)> {
+		self.typed_mut::<T>().into_iter().flat_map(|column| column.iter_mut())
+	}
+
+	/// Removes every `T` for which `keep` returns false.
+	/// This does not update the archetypes, use `World::retain_components` for entities in a world.
+	pub fn retain<T: Component + 'static, F: FnMut(usize, &mut T) -> bool>(&mut self, keep: F) {
+		if let Some(column) = self.typed_mut::<T>() {
+			column.retain(keep);
+		}
+	}
+
+	/// Removes every `T` and returns them with their entity ids.
+	/// This does not update the archetypes, use `World::drain_components` for entities in a world.
+	pub fn drain<T: Component + 'static>(&mut self) -> Vec<(usize, T)> {
+		self.typed_mut::<T>().map(|column| column.drain()).unwrap_or_default()
+	}
+
+	/// Splits the storage into columns that can be borrowed from several threads at once.
+	pub fn parallel(&mut self) -> ParallelComponents<'_> {
+		ParallelComponents::new(&self.index_map, &mut self.components)
+	}
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -530,9 +885,17 @@ impl ComponentSet {
 	}
 }
 
+impl FromIterator<TypeId> for ComponentSet {
+	fn from_iter<I: IntoIterator<Item = TypeId>>(ids: I) -> Self {
+		Self {
+			set: ids.into_iter().collect()
+		}
+	}
+}
+
 impl Hash for ComponentSet {
 	fn hash<H: Hasher>(&self, state: &mut H) {
-		let mut types: Vec<TypeId> = self.set.iter().cloned().collect();
+		let mut types: SmallVec<TypeId, 8> = self.set.iter().cloned().collect();
 		types.sort();
 		types.hash(state);
 	}
@@ -554,6 +917,14 @@ impl Archetypes {
 		self.archetypes.keys().cloned().collect()
 	}
 
+	/// The component sets of all archetypes that are a subset of `components`.
+	pub fn subsets_of(&self, components: &ComponentSet) -> SmallVec<ComponentSet, 8> {
+		self.archetypes.keys()
+			.filter(|set| set.is_subset(components))
+			.cloned()
+			.collect()
+	}
+
 	pub fn create_archetype(&mut self, components: ComponentSet) {
 		self.archetypes.insert(components, Vec::new());
 	}
@@ -586,3 +957,108 @@ impl Archetypes {
 		self.archetypes.contains_key(components)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::cell::Cell;
+	use std::rc::Rc;
+	use super::*;
+
+	/// Counts how often a value was dropped.
+	struct Counted(Rc<Cell<usize>>, u32);
+
+	impl Drop for Counted {
+		fn drop(&mut self) {
+			self.0.set(self.0.get() + 1);
+		}
+	}
+
+	#[test]
+	fn pushing_grows_by_doubling() {
+		let mut column = Column::new::<u64>(0);
+		let (mut capacity, mut growths) = (column.capacity(), 0);
+		for i in 0..1000u64 {
+			column.push(i);
+			if column.capacity() != capacity {
+				capacity = column.capacity();
+				growths += 1;
+			}
+		}
+		assert!(growths <= 10, "grew {} times", growths);
+		assert_eq!(column.get::<u64>(999), Some(&999));
+	}
+
+	#[test]
+	fn reserving_and_shrinking_keep_the_elements() {
+		let mut column = Column::new::<u32>(0);
+		column.extend_from_slice(&[1u32, 2, 3]);
+		column.reserve(100);
+		assert!(column.capacity() >= 103);
+		column.shrink_to_fit();
+		assert_eq!(column.capacity(), 3);
+		assert_eq!((0..3).map(|i| *column.get::<u32>(i).unwrap()).collect::<Vec<u32>>(), vec![1, 2, 3]);
+
+		assert_eq!(column.swap_remove::<u32>(0), Some(1));
+		assert_eq!(column.get::<u32>(0), Some(&3));
+		column.swap_remove::<u32>(0);
+		column.swap_remove::<u32>(0);
+		assert_eq!(column.swap_remove::<u32>(0), None);
+		column.shrink_to_fit();
+		assert_eq!(column.capacity(), 0);
+
+		column.push(7u32);
+		assert_eq!(column.get::<u32>(0), Some(&7));
+	}
+
+	#[test]
+	fn zero_sized_elements_need_no_memory() {
+		let mut column = Column::new::<()>(0);
+		column.extend_from_slice(&[(), ()]);
+		column.push(());
+		column.shrink_to_fit();
+		assert_eq!(column.len(), 3);
+		assert_eq!(column.swap_remove::<()>(1), Some(()));
+		assert_eq!(column.len(), 2);
+	}
+
+	#[test]
+	fn elements_are_dropped_once() {
+		let drops = Rc::new(Cell::new(0));
+		let mut set = TypedSparseSet::<Counted>::new(0);
+		set.extend((0..5).map(|i| (i * 2, Counted(drops.clone(), i as u32))));
+
+		set.set(2, Counted(drops.clone(), 10));
+		assert_eq!(drops.get(), 1);
+
+		let removed = set.remove(4).unwrap();
+		assert_eq!((removed.1, drops.get()), (2, 1));
+		drop(removed);
+		assert_eq!(drops.get(), 2);
+
+		set.retain(|_, counted| counted.1 != 3);
+		assert_eq!(drops.get(), 3);
+		assert_eq!(set.iter().map(|(index, counted)| (index, counted.1)).collect::<Vec<(usize, u32)>>(), vec![(0, 0), (2, 10), (8, 4)]);
+
+		drop(set);
+		assert_eq!(drops.get(), 6);
+	}
+
+	#[test]
+	fn typed_sets_only_wrap_their_own_type() {
+		let mut set = SparseSet::new::<u32>(0);
+		TypedSparseSet::<u32>::from_untyped_mut(&mut set).set(3, 5);
+		assert_eq!(TypedSparseSet::<u32>::from_untyped_ref(&set).get(3), Some(&5));
+
+		let set = TypedSparseSet::<u32>::from_untyped(set).unwrap();
+		assert_eq!(set.get(3), Some(&5));
+		assert!(TypedSparseSet::<u64>::from_untyped(set.into_untyped()).is_none());
+	}
+
+	#[test]
+	#[cfg(debug_assertions)]
+	#[should_panic(expected = "Type mismatch")]
+	fn typed_views_check_the_type_in_debug_builds() {
+		let set = SparseSet::new::<u32>(0);
+		TypedSparseSet::<u64>::from_untyped_ref(&set);
+	}
+}
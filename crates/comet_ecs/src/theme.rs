@@ -0,0 +1,197 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{OnceLock, RwLock};
+use comet_log::*;
+use crate::{Component, Emissive, FieldValue, FlashOnHit, Inspect, Mesh2D, ParallaxLayer, Render2D, Shadow2D, SpriteEffect, SpriteMaterial, Trail2D, Transform2D, YSort};
+
+/// Why a theme file could not be loaded.
+#[derive(Debug)]
+pub enum ThemeError {
+	Io(std::io::Error),
+	Toml(toml::de::Error)
+}
+
+impl fmt::Display for ThemeError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ThemeError::Io(e) => write!(f, "Failed to read the theme: {}", e),
+			ThemeError::Toml(e) => write!(f, "Invalid TOML in the theme: {}", e)
+		}
+	}
+}
+
+impl std::error::Error for ThemeError {}
+
+impl From<std::io::Error> for ThemeError {
+	fn from(e: std::io::Error) -> Self {
+		ThemeError::Io(e)
+	}
+}
+
+impl From<toml::de::Error> for ThemeError {
+	fn from(e: toml::de::Error) -> Self {
+		ThemeError::Toml(e)
+	}
+}
+
+/// Default field values per component, read from a TOML file with one table per component:
+///
+/// ```toml
+/// [Render2D]
+/// scale = { x = 2.0, y = 2.0 }
+///
+/// [FlashOnHit]
+/// color = [1.0, 0.2, 0.2, 1.0]
+/// ```
+///
+/// Fields are named like in the `Inspect` impl of the component, nested tables become `scale.x`.
+/// Arrays of three or four numbers are linear colors. Once installed, `Component::new` applies the theme.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Theme {
+	sections: HashMap<String, Vec<(String, FieldValue)>>
+}
+
+impl Theme {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn load(path: &str) -> Result<Self, ThemeError> {
+		let bytes = comet_resources::assets::read_asset(path)?;
+		Self::from_toml(&String::from_utf8_lossy(&bytes))
+	}
+
+	pub fn from_toml(source: &str) -> Result<Self, ThemeError> {
+		let table: toml::Table = source.parse()?;
+		let mut theme = Self::new();
+		for (section, value) in table {
+			let toml::Value::Table(fields) = value else {
+				warn!("The theme value {} is not in a table of a component, it is ignored", section);
+				continue;
+			};
+			let mut values = Vec::new();
+			flatten("", &fields, &mut values);
+			theme.sections.insert(section, values);
+		}
+		Ok(theme)
+	}
+
+	/// Sets the default of `field` for the component or section `section`, e.g. `("Camera", "zoom")`.
+	pub fn with_value(mut self, section: &str, field: &str, value: FieldValue) -> Self {
+		let fields = self.sections.entry(section.to_string()).or_default();
+		fields.retain(|(name, _)| name != field);
+		fields.push((field.to_string(), value));
+		self
+	}
+
+	pub fn value(&self, section: &str, field: &str) -> Option<FieldValue> {
+		self.fields(section).iter().find(|(name, _)| name == field).map(|(_, value)| *value)
+	}
+
+	pub fn fields(&self, section: &str) -> &[(String, FieldValue)] {
+		self.sections.get(section).map(Vec::as_slice).unwrap_or(&[])
+	}
+
+	/// Makes this the theme every `Component::new` from now on applies, replacing the previous one.
+	pub fn install(self) {
+		*global().write().unwrap_or_else(|e| e.into_inner()) = Some(self);
+	}
+}
+
+/// Collects the values of `table` as `FieldValue`s, naming those of nested tables `prefix.name`.
+fn flatten(prefix: &str, table: &toml::Table, values: &mut Vec<(String, FieldValue)>) {
+	for (key, value) in table {
+		let name = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+		let number = |value: &toml::Value| value.as_float().or_else(|| value.as_integer().map(|integer| integer as f64));
+		let field = match value {
+			toml::Value::Float(float) => FieldValue::Float(*float as f32),
+			toml::Value::Integer(integer) => FieldValue::Int(*integer),
+			toml::Value::Boolean(boolean) => FieldValue::Bool(*boolean),
+			toml::Value::Array(array) if (3..=4).contains(&array.len()) && array.iter().all(|value| number(value).is_some()) => {
+				let channel = |i: usize| array.get(i).and_then(number).unwrap_or(1.0) as f32;
+				FieldValue::Color([channel(0), channel(1), channel(2), channel(3)])
+			}
+			toml::Value::Table(nested) => {
+				flatten(&name, nested, values);
+				continue;
+			}
+			_ => {
+				warn!("The theme value {} is neither a number, a bool nor a color, it is ignored", name);
+				continue;
+			}
+		};
+		values.push((name, field));
+	}
+}
+
+static THEME: OnceLock<RwLock<Option<Theme>>> = OnceLock::new();
+
+fn global() -> &'static RwLock<Option<Theme>> {
+	THEME.get_or_init(|| RwLock::new(None))
+}
+
+/// The default of `field` in the section `section` of the installed theme, e.g. `("Camera", "zoom")` for settings
+/// that are no components.
+pub fn theme_value(section: &str, field: &str) -> Option<FieldValue> {
+	global().read().unwrap_or_else(|e| e.into_inner()).as_ref().and_then(|theme| theme.value(section, field))
+}
+
+/// Sets the themed fields of a component through its `Inspect` impl.
+type Applier = fn(&mut dyn Any, &[(String, FieldValue)]);
+
+static THEMED: OnceLock<RwLock<HashMap<TypeId, (String, Applier)>>> = OnceLock::new();
+
+fn themed() -> &'static RwLock<HashMap<TypeId, (String, Applier)>> {
+	THEMED.get_or_init(|| {
+		let mut themed = HashMap::new();
+		let mut add = |name: &str, type_id: TypeId, applier: Applier| {
+			themed.insert(type_id, (name.to_string(), applier));
+		};
+		add("Transform2D", TypeId::of::<Transform2D>(), apply::<Transform2D>);
+		add("Render2D", TypeId::of::<Render2D>(), apply::<Render2D>);
+		add("ParallaxLayer", TypeId::of::<ParallaxLayer>(), apply::<ParallaxLayer>);
+		add("Trail2D", TypeId::of::<Trail2D>(), apply::<Trail2D>);
+		add("Mesh2D", TypeId::of::<Mesh2D>(), apply::<Mesh2D>);
+		add("FlashOnHit", TypeId::of::<FlashOnHit>(), apply::<FlashOnHit>);
+		add("SpriteMaterial", TypeId::of::<SpriteMaterial>(), apply::<SpriteMaterial>);
+		add("SpriteEffect", TypeId::of::<SpriteEffect>(), apply::<SpriteEffect>);
+		add("YSort", TypeId::of::<YSort>(), apply::<YSort>);
+		add("Emissive", TypeId::of::<Emissive>(), apply::<Emissive>);
+		add("Shadow2D", TypeId::of::<Shadow2D>(), apply::<Shadow2D>);
+		RwLock::new(themed)
+	})
+}
+
+/// Lets themes set the defaults of `T` in the table `name`. The built-in components are registered already.
+pub fn register_themed<T: Component + Inspect>(name: &str) {
+	themed().write().unwrap_or_else(|e| e.into_inner()).insert(TypeId::of::<T>(), (name.to_string(), apply::<T>));
+}
+
+fn apply<T: Inspect + 'static>(component: &mut dyn Any, fields: &[(String, FieldValue)]) {
+	let Some(component) = component.downcast_mut::<T>() else {
+		return;
+	};
+	for (name, value) in fields {
+		let applied = component.set_field(name, *value) || match value {
+			// whole numbers in TOML are integers, even for float fields
+			FieldValue::Int(integer) => component.set_field(name, FieldValue::Float(*integer as f32)),
+			_ => false
+		};
+		if !applied {
+			warn_once!("The theme sets the field {} of {}, which it does not have or of another type", name, std::any::type_name::<T>());
+		}
+	}
+}
+
+/// Gives `component` the defaults of the installed theme, if its type is registered with `register_themed`.
+pub(crate) fn apply_theme<T: 'static>(component: &mut T) {
+	let theme = global().read().unwrap_or_else(|e| e.into_inner());
+	let Some(theme) = theme.as_ref() else {
+		return;
+	};
+	let themed = themed().read().unwrap_or_else(|e| e.into_inner());
+	if let Some((name, applier)) = themed.get(&TypeId::of::<T>()) {
+		applier(component, theme.fields(name));
+	}
+}
@@ -1,4 +1,3 @@
-use std::any::TypeId;
 use bit_set::BitSet;
 use crate::{
 	Entity,
@@ -9,7 +8,11 @@ use crate::{
 	SparseSet,
 	IdQueue,
 	Archetypes,
-	ComponentSet
+	ComponentSet,
+	Despawned,
+	Events,
+	ParallelComponents,
+	Query
 };
 use comet_log::*;
 
@@ -19,7 +22,12 @@ pub struct World {
 	next_id: u32,
 	entities: Vec<Option<Entity>>,
 	components: ComponentStorage,
-	archetypes: Archetypes
+	archetypes: Archetypes,
+	events: Events,
+	/// Entities marked by `despawn_deferred`, deleted by `despawn_pending`.
+	despawning: BitSet,
+	despawn_queue: Vec<u32>,
+	stable_iteration: bool
 }
 
 impl World {
@@ -37,7 +45,11 @@ impl World {
 			next_id: 0,
 			entities: Vec::new(),
 			components: component_storage,
-			archetypes: Archetypes::new()
+			archetypes: Archetypes::new(),
+			events: Events::new(),
+			despawning: BitSet::new(),
+			despawn_queue: Vec::new(),
+			stable_iteration: false
 		}
 	}
 
@@ -75,6 +87,11 @@ impl World {
 		&mut self.components
 	}
 
+	/// The component columns for systems running on several threads, see `ParallelComponents`.
+	pub fn parallel_components(&mut self) -> ParallelComponents<'_> {
+		self.components.parallel()
+	}
+
 	pub fn new_entity(&mut self) -> u32 {
 		let id = self.next_id;
 		if (self.next_id as usize) >= self.entities.len() {
@@ -109,18 +126,52 @@ impl World {
 		//self.entities.get_mut(id).unwrap()
 	}
 
+	/// Deletes the entity and its components right away. Use `despawn_deferred` while other systems may still use them this frame.
 	pub fn delete_entity(&mut self, entity_id: usize) {
+		if self.despawning.remove(entity_id) {
+			self.despawn_queue.retain(|queued| *queued as usize != entity_id);
+		}
+		// the archetypes are looked up through the components of the entity, so it has to leave them first
+		self.remove_entity_from_archetype_subsets(entity_id as u32, self.get_component_set(entity_id));
 		self.entities[entity_id] = None;
 		//self.get_entity(id);
-		for (key, value) in self.components.iter_mut() {
-			value.remove::<u8>(entity_id);
+		for (_, value) in self.components.iter_mut() {
+			value.delete(entity_id);
 		}
 		self.id_queue.sorted_enqueue(entity_id as u32);
 		self.get_next_id();
-		self.remove_entity_from_archetype_subsets(entity_id as u32, self.get_component_set(entity_id));
 		info!("Deleted entity! ID: {}", entity_id);
 	}
 
+	/// Marks the entity as dead without touching its components, so systems holding on to them this frame keep working.
+	/// Queries skip it from now on and `despawn_pending` deletes it at the end of the frame, sending `Despawned`.
+	pub fn despawn_deferred(&mut self, entity_id: usize) {
+		if self.entities.get(entity_id).is_none_or(Option::is_none) {
+			warn!("There is no entity with this ID ({}) to despawn!", entity_id);
+			return;
+		}
+		if self.despawning.insert(entity_id) {
+			self.despawn_queue.push(entity_id as u32);
+		}
+	}
+
+	/// Whether the entity was marked by `despawn_deferred` and is waiting to be deleted.
+	pub fn is_despawning(&self, entity_id: usize) -> bool {
+		self.despawning.contains(entity_id)
+	}
+
+	/// Deletes the entities marked by `despawn_deferred` and sends a `Despawned` for each.
+	/// This is called by the `App` at the end of every update.
+	pub fn despawn_pending(&mut self) {
+		for entity_id in std::mem::take(&mut self.despawn_queue) {
+			if self.entities.get(entity_id as usize).is_some_and(Option::is_some) {
+				self.delete_entity(entity_id as usize);
+				self.events.send(Despawned(entity_id));
+			}
+		}
+		self.despawning.clear();
+	}
+
 	fn create_archetype(&mut self, components: ComponentSet) {
 		self.archetypes.create_archetype(components);
 	}
@@ -130,14 +181,7 @@ impl World {
 	}
 
 	fn remove_archetype_subsets(&mut self, components: ComponentSet) {
-		let component_sets = self.archetypes.component_sets();
-		let keys: Vec<ComponentSet> = component_sets.iter()
-			.enumerate()
-			.filter_map(|(i, &ref elem)| if elem.is_subset(&components) { Some(i) } else { None })
-			.collect::<Vec<usize>>()
-			.iter()
-			.map(|index| component_sets[*index].clone())
-			.collect::<Vec<ComponentSet>>();
+		let keys = self.archetypes.subsets_of(&components);
 
 		for key in keys {
 			self.remove_archetype(key.clone());
@@ -153,14 +197,7 @@ impl World {
 	}
 
 	fn remove_entity_from_archetype_subsets(&mut self, entity_id: u32, components: ComponentSet) {
-		let component_sets = self.archetypes.component_sets();
-		let keys: Vec<ComponentSet> = component_sets.iter()
-			.enumerate()
-			.filter_map(|(i, &ref elem)| if elem.is_subset(&components) { Some(i) } else { None })
-			.collect::<Vec<usize>>()
-			.iter()
-			.map(|index| component_sets[*index].clone())
-			.collect::<Vec<ComponentSet>>();
+		let keys = self.archetypes.subsets_of(&components);
 
 		for key in keys {
 			self.remove_entity_from_archetype(entity_id, key.clone());
@@ -171,9 +208,9 @@ impl World {
 	}
 
 	fn get_component_set(&self, entity_id: usize) -> ComponentSet {
-		let components = self.entities.get(entity_id).unwrap().as_ref().unwrap().get_components().iter().collect::<Vec<usize>>();
-		let type_ids = components.iter().map(|index| self.components.keys[*index]).collect::<Vec<TypeId>>();
-		ComponentSet::from_ids(type_ids)
+		self.entities.get(entity_id).unwrap().as_ref().unwrap().get_components().iter()
+			.map(|index| self.components.keys[index])
+			.collect()
 	}
 
 	pub fn register_component<T: Component + 'static>(&mut self) {
@@ -222,9 +259,75 @@ impl World {
 		self.components.get_component_mut::<T>(entity_id).unwrap()
 	}
 
+	/// Removes `T` from every entity for which `keep` returns false.
+	pub fn retain_components<T: Component + 'static, F: FnMut(usize, &mut T) -> bool>(&mut self, mut keep: F) {
+		let mut removed = Vec::new();
+		for (entity_id, component) in self.components.iter_component_mut::<T>() {
+			if !keep(entity_id, component) {
+				removed.push(entity_id);
+			}
+		}
+		for entity_id in removed {
+			self.remove_component::<T>(entity_id);
+		}
+	}
+
+	/// Removes `T` from every entity and returns the components with their entity ids.
+	pub fn drain_components<T: Component + 'static>(&mut self) -> Vec<(usize, T)> {
+		let drained = self.components.drain::<T>();
+		for (entity_id, _) in &drained {
+			self.remove_entity_from_archetype_subsets(*entity_id as u32, self.get_component_set(*entity_id));
+		}
+		drained
+	}
+
 	pub fn get_entities_with(&self, components: ComponentSet) -> Vec<u32> {
 		assert!(self.archetypes.contains_archetype(&components), "The given components {:?} are not registered in the world!", components);
 		//debug!(format!("Querying entities with components: {:?}", components));
-		self.archetypes.get_archetype(&components).unwrap().clone()
+		let entities = self.archetypes.get_archetype(&components).unwrap();
+		let mut entities: Vec<u32> = if self.despawn_queue.is_empty() {
+			entities.clone()
+		} else {
+			entities.iter().copied().filter(|entity| !self.despawning.contains(*entity as usize)).collect()
+		};
+		if self.stable_iteration {
+			entities.sort_unstable();
+		}
+		entities
+	}
+
+	/// The entities with all of `components`, to be sorted with `Query::sorted_by_key` or `Query::stable` if the order matters.
+	pub fn query(&self, components: ComponentSet) -> Query {
+		Query::new(self.get_entities_with(components))
+	}
+
+	/// Makes every query list its entities by id instead of in the order of their archetype, which changes
+	/// as entities gain and lose components. Costs a sort per query, e.g. for lockstep games and replays.
+	pub fn set_stable_iteration(&mut self, stable: bool) {
+		self.stable_iteration = stable;
+	}
+
+	pub fn is_stable_iteration(&self) -> bool {
+		self.stable_iteration
+	}
+
+	/// Sends an event that can be read by everyone with access to the world during this and the next frame.
+	pub fn send_event<T: 'static>(&mut self, event: T) {
+		self.events.send(event);
+	}
+
+	/// Returns all events of type `T` sent in this or the previous frame.
+	pub fn read_events<T: 'static>(&self) -> Vec<&T> {
+		self.events.read::<T>()
+	}
+
+	/// Removes and returns all pending events of type `T`.
+	pub fn drain_events<T: 'static>(&mut self) -> Vec<T> {
+		self.events.drain::<T>()
+	}
+
+	/// Advances the event queues by one frame. This is called by the `App` after every update.
+	pub fn update_events(&mut self) {
+		self.events.update();
 	}
 }
@@ -0,0 +1,29 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum I18nError {
+	Io(std::io::Error),
+	InvalidLocale(String),
+	Parse {
+		locale: String,
+		errors: Vec<String>
+	}
+}
+
+impl fmt::Display for I18nError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			I18nError::Io(e) => write!(f, "Failed to read translation file: {}", e),
+			I18nError::InvalidLocale(locale) => write!(f, "{} is not a valid locale", locale),
+			I18nError::Parse { locale, errors } => write!(f, "Failed to parse translations for {}: {}", locale, errors.join(", "))
+		}
+	}
+}
+
+impl std::error::Error for I18nError {}
+
+impl From<std::io::Error> for I18nError {
+	fn from(e: std::io::Error) -> Self {
+		I18nError::Io(e)
+	}
+}
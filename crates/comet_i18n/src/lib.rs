@@ -0,0 +1,44 @@
+pub use error::*;
+pub use localization::*;
+pub use localized_text::*;
+pub use fluent_bundle::{FluentArgs, FluentValue};
+
+mod error;
+mod localization;
+mod localized_text;
+
+use std::sync::{OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+static LOCALIZATION: OnceLock<RwLock<Localization>> = OnceLock::new();
+
+fn global() -> &'static RwLock<Localization> {
+	LOCALIZATION.get_or_init(|| RwLock::new(Localization::new()))
+}
+
+/// The localization used by `tr!`.
+pub fn localization() -> RwLockReadGuard<'static, Localization> {
+	global().read().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn localization_mut() -> RwLockWriteGuard<'static, Localization> {
+	global().write().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Translates a message of the current locale.
+/// Arguments are passed as `name = value` pairs and can be used in the message as `{ $name }`.
+///
+/// ```ignore
+/// tr!("menu-start");
+/// tr!("items-collected", count = 3);
+/// ```
+#[macro_export]
+macro_rules! tr {
+	($key:expr) => {
+		$crate::localization().translate($key, None)
+	};
+	($key:expr, $($name:ident = $value:expr),+ $(,)?) => {{
+		let mut args = $crate::FluentArgs::new();
+		$(args.set(stringify!($name), $value);)+
+		$crate::localization().translate($key, Some(&args))
+	}};
+}
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::path::Path;
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use unic_langid::LanguageIdentifier;
+use comet_log::*;
+use crate::I18nError;
+
+/// Sent into the world by `App::set_locale`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocaleChanged(pub String);
+
+/// Holds the translations of every loaded locale and resolves messages for the current one.
+/// Messages missing in the current locale are looked up in the fallback locales in order.
+pub struct Localization {
+	bundles: HashMap<String, FluentBundle<FluentResource>>,
+	locale: String,
+	fallbacks: Vec<String>,
+	generation: u64
+}
+
+impl Default for Localization {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Localization {
+	pub fn new() -> Self {
+		Self {
+			bundles: HashMap::new(),
+			locale: "en-US".to_string(),
+			fallbacks: Vec::new(),
+			generation: 0
+		}
+	}
+
+	pub fn locale(&self) -> &str {
+		&self.locale
+	}
+
+	pub fn set_locale(&mut self, locale: &str) {
+		if !self.bundles.contains_key(locale) {
+			warn!("No translations loaded for {}", locale);
+		}
+		self.locale = locale.to_string();
+		self.generation += 1;
+	}
+
+	pub fn fallbacks(&self) -> &Vec<String> {
+		&self.fallbacks
+	}
+
+	pub fn set_fallbacks(&mut self, fallbacks: Vec<&str>) {
+		self.fallbacks = fallbacks.into_iter().map(|locale| locale.to_string()).collect();
+		self.generation += 1;
+	}
+
+	pub fn available_locales(&self) -> Vec<&str> {
+		let mut locales = self.bundles.keys().map(|locale| locale.as_str()).collect::<Vec<&str>>();
+		locales.sort();
+		locales
+	}
+
+	/// Increases every time the locale or the loaded translations change.
+	/// Used by `LocalizedText` to know when to resolve its string again.
+	pub fn generation(&self) -> u64 {
+		self.generation
+	}
+
+	/// Adds the messages of a .ftl source to the given locale. Messages that already exist are replaced.
+	pub fn add_ftl(&mut self, locale: &str, source: &str) -> Result<(), I18nError> {
+		let resource = FluentResource::try_new(source.to_string()).map_err(|(_, errors)| I18nError::Parse {
+			locale: locale.to_string(),
+			errors: errors.iter().map(|e| e.to_string()).collect()
+		})?;
+
+		if !self.bundles.contains_key(locale) {
+			let language = locale.parse::<LanguageIdentifier>().map_err(|_| I18nError::InvalidLocale(locale.to_string()))?;
+			let mut bundle = FluentBundle::new_concurrent(vec![language]);
+			// The invisible isolation marks around arguments only get in the way when rendering game text.
+			bundle.set_use_isolating(false);
+			self.bundles.insert(locale.to_string(), bundle);
+		}
+
+		self.bundles.get_mut(locale).unwrap().add_resource_overriding(resource);
+		self.generation += 1;
+		Ok(())
+	}
+
+	pub fn load_file(&mut self, locale: &str, path: &Path) -> Result<(), I18nError> {
+		let source = std::fs::read_to_string(path)?;
+		self.add_ftl(locale, &source)
+	}
+
+	/// Loads every locale in `directory`. A locale is either a folder of .ftl files (`en-US/menu.ftl`)
+	/// or a single file named after it (`en-US.ftl`).
+	pub fn load_directory(&mut self, directory: &Path) -> Result<(), I18nError> {
+		for entry in std::fs::read_dir(directory)? {
+			let path = entry?.path();
+
+			if path.is_dir() {
+				let Some(locale) = path.file_name().and_then(|name| name.to_str()).map(|name| name.to_string()) else { continue };
+				for file in std::fs::read_dir(&path)? {
+					let file = file?.path();
+					if file.extension().is_some_and(|extension| extension == "ftl") {
+						self.load_file(&locale, &file)?;
+					}
+				}
+			} else if path.extension().is_some_and(|extension| extension == "ftl") {
+				let Some(locale) = path.file_stem().and_then(|name| name.to_str()).map(|name| name.to_string()) else { continue };
+				self.load_file(&locale, &path)?;
+			}
+		}
+
+		info!("Loaded translations for {}", self.available_locales().join(", "));
+		Ok(())
+	}
+
+	pub fn has_message(&self, key: &str) -> bool {
+		self.locale_chain().any(|bundle| bundle.has_message(key))
+	}
+
+	/// Resolves `key` in the current locale, falling back to the fallback locales.
+	/// Returns the key itself if no locale knows the message, so missing translations stay visible.
+	pub fn translate(&self, key: &str, args: Option<&FluentArgs>) -> String {
+		for bundle in self.locale_chain() {
+			let Some(pattern) = bundle.get_message(key).and_then(|message| message.value()) else { continue };
+
+			let mut errors = Vec::new();
+			let value = bundle.format_pattern(pattern, args, &mut errors);
+			for e in errors {
+				warn!("Error while translating {}: {}", key, e);
+			}
+			return value.into_owned();
+		}
+
+		warn!("Missing translation for {} in {}", key, self.locale);
+		key.to_string()
+	}
+
+	fn locale_chain(&self) -> impl Iterator<Item = &FluentBundle<FluentResource>> {
+		std::iter::once(&self.locale)
+			.chain(self.fallbacks.iter())
+			.filter_map(|locale| self.bundles.get(locale))
+	}
+}
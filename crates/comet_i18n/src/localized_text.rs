@@ -0,0 +1,60 @@
+use fluent_bundle::{FluentArgs, FluentValue};
+use crate::localization;
+
+/// A translated string that resolves itself again whenever the locale changes.
+/// Keep one around for every piece of UI text and call `text` when drawing it.
+#[derive(Debug, Clone)]
+pub struct LocalizedText {
+	key: String,
+	args: Vec<(String, FluentValue<'static>)>,
+	resolved: String,
+	generation: Option<u64>
+}
+
+impl LocalizedText {
+	pub fn new(key: &str) -> Self {
+		Self {
+			key: key.to_string(),
+			args: Vec::new(),
+			resolved: String::new(),
+			generation: None
+		}
+	}
+
+	pub fn with_arg(mut self, name: &str, value: impl Into<FluentValue<'static>>) -> Self {
+		self.set_arg(name, value);
+		self
+	}
+
+	pub fn key(&self) -> &str {
+		&self.key
+	}
+
+	pub fn set_key(&mut self, key: &str) {
+		self.key = key.to_string();
+		self.generation = None;
+	}
+
+	pub fn set_arg(&mut self, name: &str, value: impl Into<FluentValue<'static>>) {
+		let value = value.into();
+		match self.args.iter_mut().find(|(n, _)| n == name) {
+			Some((_, existing)) => *existing = value,
+			None => self.args.push((name.to_string(), value))
+		}
+		self.generation = None;
+	}
+
+	/// The translated text, resolved again if the locale, the translations or the arguments changed.
+	pub fn text(&mut self) -> &str {
+		let localization = localization();
+		if self.generation != Some(localization.generation()) {
+			let mut args = FluentArgs::new();
+			for (name, value) in &self.args {
+				args.set(name.as_str(), value.clone());
+			}
+			self.resolved = localization.translate(&self.key, Some(&args));
+			self.generation = Some(localization.generation());
+		}
+		&self.resolved
+	}
+}
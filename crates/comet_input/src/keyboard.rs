@@ -3,6 +3,45 @@ use winit::keyboard::{ KeyCode, PhysicalKey };
 
 pub type Key = KeyCode;
 
+/// Every key of the keyboard, e.g. to check each of them once per update.
+pub const KEYS: &[Key] = &[
+	Key::Backquote, Key::Backslash, Key::BracketLeft, Key::BracketRight, Key::Comma, Key::Digit0, Key::Digit1,
+	Key::Digit2, Key::Digit3, Key::Digit4, Key::Digit5, Key::Digit6, Key::Digit7, Key::Digit8, Key::Digit9,
+	Key::Equal, Key::IntlBackslash, Key::IntlRo, Key::IntlYen, Key::KeyA, Key::KeyB, Key::KeyC, Key::KeyD,
+	Key::KeyE, Key::KeyF, Key::KeyG, Key::KeyH, Key::KeyI, Key::KeyJ, Key::KeyK, Key::KeyL, Key::KeyM, Key::KeyN,
+	Key::KeyO, Key::KeyP, Key::KeyQ, Key::KeyR, Key::KeyS, Key::KeyT, Key::KeyU, Key::KeyV, Key::KeyW, Key::KeyX,
+	Key::KeyY, Key::KeyZ, Key::Minus, Key::Period, Key::Quote, Key::Semicolon, Key::Slash, Key::AltLeft, Key::AltRight,
+	Key::Backspace, Key::CapsLock, Key::ContextMenu, Key::ControlLeft, Key::ControlRight, Key::Enter, Key::SuperLeft,
+	Key::SuperRight, Key::ShiftLeft, Key::ShiftRight, Key::Space, Key::Tab, Key::Convert, Key::KanaMode, Key::Lang1,
+	Key::Lang2, Key::Lang3, Key::Lang4, Key::Lang5, Key::NonConvert, Key::Delete, Key::End, Key::Help, Key::Home,
+	Key::Insert, Key::PageDown, Key::PageUp, Key::ArrowDown, Key::ArrowLeft, Key::ArrowRight, Key::ArrowUp,
+	Key::NumLock, Key::Numpad0, Key::Numpad1, Key::Numpad2, Key::Numpad3, Key::Numpad4, Key::Numpad5, Key::Numpad6,
+	Key::Numpad7, Key::Numpad8, Key::Numpad9, Key::NumpadAdd, Key::NumpadBackspace, Key::NumpadClear, Key::NumpadClearEntry,
+	Key::NumpadComma, Key::NumpadDecimal, Key::NumpadDivide, Key::NumpadEnter, Key::NumpadEqual, Key::NumpadHash,
+	Key::NumpadMemoryAdd, Key::NumpadMemoryClear, Key::NumpadMemoryRecall, Key::NumpadMemoryStore, Key::NumpadMemorySubtract,
+	Key::NumpadMultiply, Key::NumpadParenLeft, Key::NumpadParenRight, Key::NumpadStar, Key::NumpadSubtract,
+	Key::Escape, Key::Fn, Key::FnLock, Key::PrintScreen, Key::ScrollLock, Key::Pause, Key::BrowserBack, Key::BrowserFavorites,
+	Key::BrowserForward, Key::BrowserHome, Key::BrowserRefresh, Key::BrowserSearch, Key::BrowserStop, Key::Eject,
+	Key::LaunchApp1, Key::LaunchApp2, Key::LaunchMail, Key::MediaPlayPause, Key::MediaSelect, Key::MediaStop,
+	Key::MediaTrackNext, Key::MediaTrackPrevious, Key::Power, Key::Sleep, Key::AudioVolumeDown, Key::AudioVolumeMute,
+	Key::AudioVolumeUp, Key::WakeUp, Key::Meta, Key::Hyper, Key::Turbo, Key::Abort, Key::Resume, Key::Suspend,
+	Key::Again, Key::Copy, Key::Cut, Key::Find, Key::Open, Key::Paste, Key::Props, Key::Select, Key::Undo,
+	Key::Hiragana, Key::Katakana, Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6, Key::F7, Key::F8, Key::F9,
+	Key::F10, Key::F11, Key::F12, Key::F13, Key::F14, Key::F15, Key::F16, Key::F17, Key::F18, Key::F19, Key::F20,
+	Key::F21, Key::F22, Key::F23, Key::F24, Key::F25, Key::F26, Key::F27, Key::F28, Key::F29, Key::F30, Key::F31,
+	Key::F32, Key::F33, Key::F34, Key::F35
+];
+
+/// The name of `key` as it is written in files, the name of its variant like `KeyA` or `ArrowUp`.
+pub fn key_name(key: Key) -> String {
+	format!("{:?}", key)
+}
+
+/// The key called `name` by `key_name`.
+pub fn key_from_name(name: &str) -> Option<Key> {
+	KEYS.iter().copied().find(|key| key_name(*key) == name)
+}
+
 pub fn key_pressed(event: &WindowEvent, key_code: Key) -> bool {
 	match event {
 		WindowEvent::KeyboardInput {
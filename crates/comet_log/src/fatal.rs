@@ -0,0 +1,57 @@
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use crate::{logger, Level};
+
+type Hook = Box<dyn FnOnce() + Send>;
+
+static HOOKS: Mutex<Vec<Hook>> = Mutex::new(Vec::new());
+static ABORTING: AtomicBool = AtomicBool::new(false);
+
+/// Registers a hook that runs when `fatal!` is hit, e.g. to write an emergency save.
+/// Hooks run once, in the order they were added, before the panic.
+pub fn on_fatal<F: FnOnce() + Send + 'static>(hook: F) {
+	if let Ok(mut hooks) = HOOKS.lock() {
+		hooks.push(Box::new(hook));
+	}
+}
+
+/// Logs the message, flushes the sinks, runs the fatal hooks and panics. Used by `fatal!`.
+#[track_caller]
+pub fn fatal(target: &str, message: String) -> ! {
+	logger::log(Level::Fatal, target, message.clone());
+	logger::flush();
+
+	// a hook that hits fatal! itself must not run the hooks again
+	if !ABORTING.swap(true, Ordering::SeqCst) {
+		let hooks = HOOKS.lock().map(|mut hooks| std::mem::take(&mut *hooks)).unwrap_or_default();
+		for hook in hooks {
+			hook();
+		}
+		logger::flush();
+	}
+
+	panic!("{}", message)
+}
+
+/// Values `expect_or_fatal!` can unwrap.
+pub trait OrFatal {
+	type Output;
+	fn or_fatal(self) -> Result<Self::Output, String>;
+}
+
+impl<T> OrFatal for Option<T> {
+	type Output = T;
+
+	fn or_fatal(self) -> Result<T, String> {
+		self.ok_or_else(|| "None".to_string())
+	}
+}
+
+impl<T, E: Debug> OrFatal for Result<T, E> {
+	type Output = T;
+
+	fn or_fatal(self) -> Result<T, String> {
+		self.map_err(|e| format!("{:?}", e))
+	}
+}
@@ -0,0 +1,88 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+use crate::Level;
+
+const DEFAULT_CAPACITY: usize = 256;
+
+struct History {
+	capacity: usize,
+	lines: VecDeque<(Level, String)>,
+	total: u64
+}
+
+static HISTORY: OnceLock<Mutex<History>> = OnceLock::new();
+
+fn history() -> &'static Mutex<History> {
+	HISTORY.get_or_init(|| Mutex::new(History {
+		capacity: DEFAULT_CAPACITY,
+		lines: VecDeque::with_capacity(DEFAULT_CAPACITY),
+		total: 0
+	}))
+}
+
+/// Removes the ANSI escape sequences the log macros use for coloring the level.
+pub fn strip_ansi(line: &str) -> String {
+	let mut stripped = String::with_capacity(line.len());
+	let mut chars = line.chars();
+
+	while let Some(c) = chars.next() {
+		if c == '\x1b' {
+			for c in chars.by_ref() {
+				if c == 'm' {
+					break;
+				}
+			}
+			continue;
+		}
+		stripped.push(c);
+	}
+
+	stripped
+}
+
+/// Stores a line in the in-memory history of recent log lines.
+/// Once the capacity is reached, the oldest line is dropped.
+pub fn record(line: &str) {
+	record_with_level(line, Level::Info);
+}
+
+/// Stores a line together with the level it was logged at.
+pub fn record_with_level(line: &str, level: Level) {
+	if let Ok(mut history) = history().lock() {
+		if history.lines.len() >= history.capacity {
+			history.lines.pop_front();
+		}
+		history.lines.push_back((level, strip_ansi(line)));
+		history.total += 1;
+	}
+}
+
+/// Returns the most recent log lines, oldest first.
+pub fn recent_lines() -> Vec<String> {
+	history().lock().map(|h| h.lines.iter().map(|(_, line)| line.clone()).collect()).unwrap_or_default()
+}
+
+/// Returns the lines recorded after `cursor` lines had been recorded in total, together with the new total.
+/// Pass the returned total back in to only get new lines on the next call.
+pub fn lines_since(cursor: u64) -> (Vec<String>, u64) {
+	let (entries, total) = entries_since(cursor);
+	(entries.into_iter().map(|(_, line)| line).collect(), total)
+}
+
+/// Like `lines_since`, but with the level every line was logged at.
+pub fn entries_since(cursor: u64) -> (Vec<(Level, String)>, u64) {
+	history().lock().map(|h| {
+		let new = (h.total.saturating_sub(cursor) as usize).min(h.lines.len());
+		(h.lines.iter().skip(h.lines.len() - new).cloned().collect(), h.total)
+	}).unwrap_or((Vec::new(), cursor))
+}
+
+/// Sets how many lines are kept in the history.
+pub fn set_capacity(capacity: usize) {
+	if let Ok(mut history) = history().lock() {
+		history.capacity = capacity.max(1);
+		while history.lines.len() > history.capacity {
+			history.lines.pop_front();
+		}
+	}
+}
@@ -1,83 +1,161 @@
+pub use logger::{Level, Record, Sink, StderrSink, HistorySink, FileSink};
+
+pub mod fatal;
+pub mod history;
+pub mod logger;
+pub mod profiler;
+pub mod throttle;
+
+/// Logs a message at the given level through the logger.
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $fmt:expr $(, $args:expr)*) => {{
+        let level = $level;
+        if $crate::logger::enabled(level, module_path!()) {
+            $crate::logger::log(level, module_path!(), format!($fmt $(, $args)*));
+        }
+    }};
+}
+
 #[macro_export]
 macro_rules! info {
-    ($fmt:expr $(, $args:expr)*) => {
-        eprintln!(
-            "{} [{}::{}] [{}] : {}",
-            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
-            std::env::var("CARGO_PKG_NAME").unwrap(),
-            module_path!(),
-            "\x1b[32m\x1b[1mINFO\x1b[0m",
-            format!($fmt $(, $args)*)
-        );
-    };
+    ($fmt:expr $(, $args:expr)*) => {{
+        $crate::log!($crate::Level::Info, $fmt $(, $args)*);
+    }};
 }
 
 #[macro_export]
 macro_rules! debug {
-    ($fmt:expr $(, $args:expr)*) => {
-        eprintln!(
-            "{} [{}::{}] [{}] : {}",
-            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
-            std::env::var("CARGO_PKG_NAME").unwrap(),
-            module_path!(),
-            "\x1b[34m\x1b[1mDEBUG\x1b[0m",
-            format!($fmt $(, $args)*)
-        );
-    };
+    ($fmt:expr $(, $args:expr)*) => {{
+        $crate::log!($crate::Level::Debug, $fmt $(, $args)*);
+    }};
 }
 
 #[macro_export]
 macro_rules! warn {
-    ($fmt:expr $(, $args:expr)*) => {
-        eprintln!(
-            "{} [{}::{}] [{}] : {}",
-            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
-            std::env::var("CARGO_PKG_NAME").unwrap(),
-            module_path!(),
-            "\x1b[33m\x1b[1mWARNING\x1b[0m",
-            format!($fmt $(, $args)*)
-        );
-    };
+    ($fmt:expr $(, $args:expr)*) => {{
+        $crate::log!($crate::Level::Warn, $fmt $(, $args)*);
+    }};
 }
 
 #[macro_export]
 macro_rules! error {
-    ($fmt:expr $(, $args:expr)*) => {
-        eprintln!(
-            "{} [{}::{}] [{}] : {}",
-            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
-            std::env::var("CARGO_PKG_NAME").unwrap(),
-            module_path!(),
-            "\x1b[31m\x1b[1mERROR\x1b[0m",
-            format!($fmt $(, $args)*)
-        );
-    };
+    ($fmt:expr $(, $args:expr)*) => {{
+        $crate::log!($crate::Level::Error, $fmt $(, $args)*);
+    }};
 }
 
+/// Logs a fatal error, flushes the log, runs the hooks registered with `fatal::on_fatal` and panics.
 #[macro_export]
 macro_rules! fatal {
-    ($fmt:expr $(, $args:expr)*) => {
-        eprintln!(
-            "{} [{}::{}] [{}] : {}",
-            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
-            std::env::var("CARGO_PKG_NAME").unwrap(),
-            module_path!(),
-            "\x1b[41mFATAL\x1b[0m",
-            format!($fmt $(, $args)*)
-        );
-    };
+    ($fmt:expr $(, $args:expr)*) => {{
+        $crate::fatal::fatal(module_path!(), format!($fmt $(, $args)*))
+    }};
+}
+
+/// Unwraps an `Option` or `Result`, or hits `fatal!` with the message and the error.
+#[macro_export]
+macro_rules! expect_or_fatal {
+    ($value:expr, $fmt:expr $(, $args:expr)*) => {{
+        match $crate::fatal::OrFatal::or_fatal($value) {
+            Ok(value) => value,
+            Err(e) => $crate::fatal!("{}: {}", format!($fmt $(, $args)*), e)
+        }
+    }};
 }
 
 #[macro_export]
 macro_rules! trace {
-    ($fmt:expr $(, $args:expr)*) => {
-        eprintln!(
-            "{} [{}::{}] [{}] : {}",
-            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
-            std::env::var("CARGO_PKG_NAME").unwrap(),
-            module_path!(),
-            "\x1b[35m\x1b[1mTRACE\x1b[0m",
-            format!($fmt $(, $args)*)
-        );
+    ($fmt:expr $(, $args:expr)*) => {{
+        $crate::log!($crate::Level::Trace, $fmt $(, $args)*);
+    }};
+}
+
+/// Logs a message at the given level only the first time this line is reached.
+#[macro_export]
+macro_rules! log_once {
+    ($level:expr, $fmt:expr $(, $args:expr)*) => {{
+        static THROTTLE: $crate::throttle::Throttle = $crate::throttle::Throttle::new();
+        if THROTTLE.pass(None).is_some() {
+            $crate::log!($level, $fmt $(, $args)*);
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! warn_once {
+    ($fmt:expr $(, $args:expr)*) => {{
+        $crate::log_once!($crate::Level::Warn, $fmt $(, $args)*);
+    }};
+}
+
+#[macro_export]
+macro_rules! error_once {
+    ($fmt:expr $(, $args:expr)*) => {{
+        $crate::log_once!($crate::Level::Error, $fmt $(, $args)*);
+    }};
+}
+
+/// Logs a message at the given level at most once every `seconds` from this line,
+/// e.g. `log_every!(1.0, Level::Warn, "Missing glyph {}", c)`. Skipped repeats are counted in the next message.
+#[macro_export]
+macro_rules! log_every {
+    ($seconds:expr, $level:expr, $fmt:expr $(, $args:expr)*) => {{
+        static THROTTLE: $crate::throttle::Throttle = $crate::throttle::Throttle::new();
+        let level = $level;
+        if $crate::logger::enabled(level, module_path!()) {
+            if let Some(suppressed) = THROTTLE.pass(Some(std::time::Duration::from_secs_f64($seconds as f64))) {
+                $crate::logger::log(level, module_path!(), $crate::throttle::with_suppressed(format!($fmt $(, $args)*), suppressed));
+            }
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! warn_every {
+    ($seconds:expr, $fmt:expr $(, $args:expr)*) => {{
+        $crate::log_every!($seconds, $crate::Level::Warn, $fmt $(, $args)*);
+    }};
+}
+
+#[macro_export]
+macro_rules! error_every {
+    ($seconds:expr, $fmt:expr $(, $args:expr)*) => {{
+        $crate::log_every!($seconds, $crate::Level::Error, $fmt $(, $args)*);
+    }};
+}
+
+/// Logs an error instead of panicking if the condition is false. Only checked in debug builds.
+#[macro_export]
+macro_rules! debug_assert_log {
+    ($cond:expr) => {{
+        if cfg!(debug_assertions) && !$cond {
+            $crate::error!("Assertion failed: {}", stringify!($cond));
+        }
+    }};
+    ($cond:expr, $fmt:expr $(, $args:expr)*) => {{
+        if cfg!(debug_assertions) && !$cond {
+            $crate::error!("Assertion failed: {}: {}", stringify!($cond), format!($fmt $(, $args)*));
+        }
+    }};
+}
+
+/// Times the rest of the enclosing scope and records it in the frame profiler under the given name.
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {
+        let _profile_guard = $crate::profiler::ProfileGuard::new($name);
     };
-}
\ No newline at end of file
+}
+
+/// Times the rest of the enclosing function and records it in the frame profiler under the function's path.
+#[macro_export]
+macro_rules! profile_function {
+    () => {
+        let _profile_guard = $crate::profiler::ProfileGuard::new({
+            fn f() {}
+            let name = std::any::type_name_of_val(&f);
+            &name[..name.len() - 3]
+        });
+    };
+}
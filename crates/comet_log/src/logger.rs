@@ -0,0 +1,353 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::thread;
+use chrono::{DateTime, Local};
+use crate::history;
+
+/// How severe a log record is. Levels are ordered from the most verbose to the most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+	Trace,
+	Debug,
+	Info,
+	Warn,
+	Error,
+	Fatal
+}
+
+impl Level {
+	pub fn name(&self) -> &'static str {
+		match self {
+			Level::Trace => "TRACE",
+			Level::Debug => "DEBUG",
+			Level::Info => "INFO",
+			Level::Warn => "WARNING",
+			Level::Error => "ERROR",
+			Level::Fatal => "FATAL"
+		}
+	}
+
+	fn colored_name(&self) -> &'static str {
+		match self {
+			Level::Trace => "\x1b[35m\x1b[1mTRACE\x1b[0m",
+			Level::Debug => "\x1b[34m\x1b[1mDEBUG\x1b[0m",
+			Level::Info => "\x1b[32m\x1b[1mINFO\x1b[0m",
+			Level::Warn => "\x1b[33m\x1b[1mWARNING\x1b[0m",
+			Level::Error => "\x1b[31m\x1b[1mERROR\x1b[0m",
+			Level::Fatal => "\x1b[41mFATAL\x1b[0m"
+		}
+	}
+
+	/// Parses a level name like `debug` or `warn`. Case is ignored.
+	pub fn parse(name: &str) -> Option<Level> {
+		match name.trim().to_ascii_lowercase().as_str() {
+			"trace" => Some(Level::Trace),
+			"debug" => Some(Level::Debug),
+			"info" => Some(Level::Info),
+			"warn" | "warning" => Some(Level::Warn),
+			"error" => Some(Level::Error),
+			"fatal" => Some(Level::Fatal),
+			_ => None
+		}
+	}
+}
+
+/// A single log line before it is formatted.
+#[derive(Debug, Clone)]
+pub struct Record {
+	pub level: Level,
+	/// The module the record was logged from.
+	pub target: String,
+	pub message: String,
+	pub time: DateTime<Local>
+}
+
+impl Record {
+	/// Formats the record as a single line, with the level colored for terminals if `colored` is set.
+	pub fn format(&self, colored: bool) -> String {
+		format!(
+			"{} [{}] [{}] : {}",
+			self.time.format("%Y-%m-%d %H:%M:%S"),
+			self.target,
+			if colored { self.level.colored_name() } else { self.level.name() },
+			self.message
+		)
+	}
+}
+
+/// Somewhere log records are written to. Sinks run on the logging thread, so they
+/// must not log themselves.
+pub trait Sink: Send {
+	fn write(&mut self, record: &Record);
+	fn flush(&mut self) {}
+}
+
+/// Writes colored lines to stderr.
+pub struct StderrSink;
+
+impl Sink for StderrSink {
+	fn write(&mut self, record: &Record) {
+		let _ = writeln!(io::stderr(), "{}", record.format(true));
+	}
+
+	fn flush(&mut self) {
+		let _ = io::stderr().flush();
+	}
+}
+
+/// Keeps the recent lines in memory for the console and the log overlay. See the `history` module.
+pub struct HistorySink;
+
+impl Sink for HistorySink {
+	fn write(&mut self, record: &Record) {
+		history::record_with_level(&record.format(false), record.level);
+	}
+}
+
+/// Writes plain lines to a file. With rotation, the file is moved to `<path>.1` once it
+/// grows past the size limit, `<path>.1` to `<path>.2` and so on, keeping at most `max_files` old files.
+pub struct FileSink {
+	path: PathBuf,
+	file: File,
+	size: u64,
+	max_bytes: Option<u64>,
+	max_files: usize
+}
+
+impl FileSink {
+	/// Appends to the file at `path`, creating it if needed.
+	pub fn new<P: Into<PathBuf>>(path: P) -> io::Result<Self> {
+		let path = path.into();
+		if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+			fs::create_dir_all(parent)?;
+		}
+		let file = OpenOptions::new().create(true).append(true).open(&path)?;
+		let size = file.metadata()?.len();
+		Ok(Self {
+			path,
+			file,
+			size,
+			max_bytes: None,
+			max_files: 0
+		})
+	}
+
+	pub fn with_rotation(mut self, max_bytes: u64, max_files: usize) -> Self {
+		self.max_bytes = Some(max_bytes.max(1));
+		self.max_files = max_files;
+		self
+	}
+
+	fn rotated(&self, index: usize) -> PathBuf {
+		let mut path = self.path.clone().into_os_string();
+		path.push(format!(".{}", index));
+		PathBuf::from(path)
+	}
+
+	fn rotate(&mut self) -> io::Result<()> {
+		self.file.flush()?;
+		if self.max_files == 0 {
+			self.file = File::create(&self.path)?;
+		} else {
+			let _ = fs::remove_file(self.rotated(self.max_files));
+			for index in (1..self.max_files).rev() {
+				let _ = fs::rename(self.rotated(index), self.rotated(index + 1));
+			}
+			fs::rename(&self.path, self.rotated(1))?;
+			self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+		}
+		self.size = 0;
+		Ok(())
+	}
+}
+
+impl Sink for FileSink {
+	fn write(&mut self, record: &Record) {
+		let line = format!("{}\n", record.format(false));
+		if self.max_bytes.is_some_and(|max| self.size + line.len() as u64 > max) && self.size > 0 {
+			if let Err(e) = self.rotate() {
+				eprintln!("Failed to rotate the log file {}: {}", self.path.display(), e);
+			}
+		}
+		if self.file.write_all(line.as_bytes()).is_ok() {
+			self.size += line.len() as u64;
+		}
+	}
+
+	fn flush(&mut self) {
+		let _ = self.file.flush();
+	}
+}
+
+/// Which records get logged: a default level and more specific levels for module paths.
+/// `None` turns logging off.
+struct Filter {
+	default: Option<Level>,
+	modules: Vec<(String, Option<Level>)>
+}
+
+impl Filter {
+	/// Parses filters like `info,comet_renderer=debug,comet_ecs::world=off`.
+	fn parse(spec: &str) -> Self {
+		let mut filter = Filter {
+			default: Some(Level::Trace),
+			modules: Vec::new()
+		};
+
+		for directive in spec.split(',').map(str::trim).filter(|directive| !directive.is_empty()) {
+			let (module, level) = match directive.split_once('=') {
+				Some((module, level)) => (Some(module.trim()), level),
+				None => match Level::parse(directive) {
+					Some(_) => (None, directive),
+					None if directive == "off" => (None, directive),
+					// a bare module name logs everything from that module
+					None => (Some(directive), "trace")
+				}
+			};
+			let level = if level.trim() == "off" { None } else {
+				match Level::parse(level) {
+					Some(level) => Some(level),
+					None => {
+						eprintln!("Unknown log level {} in COMET_LOG", level);
+						continue;
+					}
+				}
+			};
+			match module {
+				Some(module) => filter.set_module(module, level),
+				None => filter.default = level
+			}
+		}
+		filter
+	}
+
+	fn set_module(&mut self, module: &str, level: Option<Level>) {
+		self.modules.retain(|(name, _)| name != module);
+		self.modules.push((module.to_string(), level));
+		// the most specific module path has to be found first
+		self.modules.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+	}
+
+	fn enabled(&self, level: Level, target: &str) -> bool {
+		let min = self.modules.iter()
+			.find(|(module, _)| target == module || target.strip_prefix(module.as_str()).is_some_and(|rest| rest.starts_with("::")))
+			.map(|(_, level)| *level)
+			.unwrap_or(self.default);
+		min.is_some_and(|min| level >= min)
+	}
+}
+
+enum Message {
+	Record(Record),
+	AddSink(Box<dyn Sink>),
+	ClearSinks,
+	Flush(Sender<()>)
+}
+
+struct Logger {
+	filter: RwLock<Filter>,
+	sender: Mutex<Sender<Message>>
+}
+
+const LOGGER_THREAD: &str = "comet_log";
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+fn logger() -> &'static Logger {
+	LOGGER.get_or_init(|| {
+		let (sender, receiver) = mpsc::channel::<Message>();
+		let mut sinks: Vec<Box<dyn Sink>> = vec![Box::new(StderrSink), Box::new(HistorySink)];
+
+		let spawned = thread::Builder::new().name(LOGGER_THREAD.to_string()).spawn(move || {
+			for message in receiver {
+				match message {
+					Message::Record(record) => sinks.iter_mut().for_each(|sink| sink.write(&record)),
+					Message::AddSink(sink) => sinks.push(sink),
+					Message::ClearSinks => sinks.clear(),
+					Message::Flush(done) => {
+						sinks.iter_mut().for_each(|sink| sink.flush());
+						let _ = done.send(());
+					}
+				}
+			}
+		});
+		if let Err(e) = spawned {
+			eprintln!("Failed to start the logging thread: {}", e);
+		}
+
+		Logger {
+			filter: RwLock::new(Filter::parse(&std::env::var("COMET_LOG").unwrap_or_default())),
+			sender: Mutex::new(sender)
+		}
+	})
+}
+
+fn send(message: Message) {
+	if let Ok(sender) = logger().sender.lock() {
+		let _ = sender.send(message);
+	}
+}
+
+/// Whether a record of the given level from the given module would be logged.
+pub fn enabled(level: Level, target: &str) -> bool {
+	logger().filter.read().map(|filter| filter.enabled(level, target)).unwrap_or(true)
+}
+
+/// Logs a message. Records are written by a background thread, so this never waits for the sinks.
+/// Used by the log macros.
+pub fn log(level: Level, target: &str, message: String) {
+	if !enabled(level, target) {
+		return;
+	}
+	send(Message::Record(Record {
+		level,
+		target: target.to_string(),
+		message,
+		time: Local::now()
+	}));
+}
+
+/// Only logs records of at least the given level, unless a module has its own level.
+/// `None` turns logging off. The initial filters are read from the `COMET_LOG` environment variable.
+pub fn set_level(level: Option<Level>) {
+	if let Ok(mut filter) = logger().filter.write() {
+		filter.default = level;
+	}
+}
+
+/// Sets the level for a module and its submodules, e.g. `set_module_level("comet_renderer", Some(Level::Debug))`.
+pub fn set_module_level(module: &str, level: Option<Level>) {
+	if let Ok(mut filter) = logger().filter.write() {
+		filter.set_module(module, level);
+	}
+}
+
+/// Replaces the filters with ones parsed like `COMET_LOG`, e.g. `warn,comet_app=debug`.
+pub fn set_filters(spec: &str) {
+	if let Ok(mut filter) = logger().filter.write() {
+		*filter = Filter::parse(spec);
+	}
+}
+
+pub fn add_sink<S: Sink + 'static>(sink: S) {
+	send(Message::AddSink(Box::new(sink)));
+}
+
+/// Removes every sink, including the default stderr and history sinks.
+pub fn clear_sinks() {
+	send(Message::ClearSinks);
+}
+
+/// Waits until every record logged so far has been written and flushes the sinks.
+pub fn flush() {
+	// the logging thread would wait for itself
+	if thread::current().name() == Some(LOGGER_THREAD) {
+		return;
+	}
+	let (done, wait) = mpsc::channel();
+	send(Message::Flush(done));
+	let _ = wait.recv();
+}
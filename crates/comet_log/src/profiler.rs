@@ -0,0 +1,209 @@
+use std::cell::Cell;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// A single timed scope recorded by the profiler.
+/// `start` is measured relative to the start of the profiler, `depth` is the nesting level on the recording thread.
+#[derive(Debug, Clone)]
+pub struct ProfileSpan {
+	name: &'static str,
+	thread: u64,
+	depth: u32,
+	start: Duration,
+	duration: Duration
+}
+
+impl ProfileSpan {
+	pub fn name(&self) -> &'static str {
+		self.name
+	}
+
+	pub fn thread(&self) -> u64 {
+		self.thread
+	}
+
+	pub fn depth(&self) -> u32 {
+		self.depth
+	}
+
+	pub fn start(&self) -> Duration {
+		self.start
+	}
+
+	pub fn duration(&self) -> Duration {
+		self.duration
+	}
+}
+
+struct Profiler {
+	epoch: Instant,
+	enabled: bool,
+	capturing: bool,
+	current_frame: Vec<ProfileSpan>,
+	last_frame: Vec<ProfileSpan>,
+	captured: Vec<ProfileSpan>
+}
+
+impl Profiler {
+	fn new() -> Self {
+		Self {
+			epoch: Instant::now(),
+			enabled: true,
+			capturing: false,
+			current_frame: Vec::new(),
+			last_frame: Vec::new(),
+			captured: Vec::new()
+		}
+	}
+}
+
+static PROFILER: OnceLock<Mutex<Profiler>> = OnceLock::new();
+static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+	static THREAD_ID: u64 = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+	static DEPTH: Cell<u32> = const { Cell::new(0) };
+}
+
+fn profiler() -> &'static Mutex<Profiler> {
+	PROFILER.get_or_init(|| Mutex::new(Profiler::new()))
+}
+
+/// Guard created by `profile_scope!`. The scope is recorded once the guard is dropped.
+pub struct ProfileGuard {
+	name: &'static str,
+	start: Instant,
+	depth: u32,
+	active: bool
+}
+
+impl ProfileGuard {
+	pub fn new(name: &'static str) -> Self {
+		let active = profiler().lock().map(|p| p.enabled).unwrap_or(false);
+		let depth = DEPTH.with(|d| {
+			let depth = d.get();
+			if active {
+				d.set(depth + 1);
+			}
+			depth
+		});
+
+		Self {
+			name,
+			start: Instant::now(),
+			depth,
+			active
+		}
+	}
+}
+
+impl Drop for ProfileGuard {
+	fn drop(&mut self) {
+		if !self.active {
+			return;
+		}
+
+		let duration = self.start.elapsed();
+		DEPTH.with(|d| d.set(self.depth));
+		let thread = THREAD_ID.with(|id| *id);
+
+		if let Ok(mut profiler) = profiler().lock() {
+			let span = ProfileSpan {
+				name: self.name,
+				thread,
+				depth: self.depth,
+				start: self.start.saturating_duration_since(profiler.epoch),
+				duration
+			};
+			if profiler.capturing {
+				profiler.captured.push(span.clone());
+			}
+			profiler.current_frame.push(span);
+		}
+	}
+}
+
+/// Enables or disables the recording of new scopes. Scopes that are already running are still recorded.
+pub fn set_enabled(enabled: bool) {
+	if let Ok(mut profiler) = profiler().lock() {
+		profiler.enabled = enabled;
+	}
+}
+
+pub fn is_enabled() -> bool {
+	profiler().lock().map(|p| p.enabled).unwrap_or(false)
+}
+
+/// Marks the end of a frame. The spans recorded since the last call become available through `last_frame`.
+/// This is called by the `App` once per rendered frame.
+pub fn new_frame() {
+	if let Ok(mut profiler) = profiler().lock() {
+		let mut spans = std::mem::take(&mut profiler.current_frame);
+		spans.sort_by_key(|span| (span.thread, span.start));
+		profiler.last_frame = spans;
+	}
+}
+
+/// Returns the spans of the last completed frame sorted by thread and start time.
+pub fn last_frame() -> Vec<ProfileSpan> {
+	profiler().lock().map(|p| p.last_frame.clone()).unwrap_or_default()
+}
+
+/// Returns the accumulated time per scope name of the last completed frame, longest first.
+pub fn last_frame_totals() -> Vec<(&'static str, Duration)> {
+	let mut totals: Vec<(&'static str, Duration)> = Vec::new();
+
+	for span in last_frame() {
+		match totals.iter_mut().find(|(name, _)| *name == span.name) {
+			Some((_, total)) => *total += span.duration,
+			None => totals.push((span.name, span.duration))
+		}
+	}
+
+	totals.sort_by_key(|(_, total)| std::cmp::Reverse(*total));
+	totals
+}
+
+/// Starts collecting every recorded span until `stop_capture` is called, for the export to chrome://tracing.
+/// Any previously captured spans are discarded.
+pub fn start_capture() {
+	if let Ok(mut profiler) = profiler().lock() {
+		profiler.captured.clear();
+		profiler.capturing = true;
+	}
+}
+
+pub fn stop_capture() {
+	if let Ok(mut profiler) = profiler().lock() {
+		profiler.capturing = false;
+	}
+}
+
+/// Serializes the captured spans into the Trace Event Format understood by chrome://tracing and Perfetto.
+pub fn chrome_trace() -> String {
+	let spans = profiler().lock().map(|p| p.captured.clone()).unwrap_or_default();
+	let pid = std::process::id();
+
+	let events = spans.iter().map(|span| {
+		format!(
+			"{{\"name\":\"{}\",\"cat\":\"comet\",\"ph\":\"X\",\"ts\":{},\"dur\":{},\"pid\":{},\"tid\":{}}}",
+			span.name.replace('\\', "\\\\").replace('"', "\\\""),
+			span.start.as_micros(),
+			span.duration.as_micros(),
+			pid,
+			span.thread
+		)
+	}).collect::<Vec<String>>();
+
+	format!("{{\"traceEvents\":[{}],\"displayTimeUnit\":\"ms\"}}", events.join(","))
+}
+
+/// Writes the captured spans as a chrome://tracing JSON file to the given path.
+pub fn write_chrome_trace<P: AsRef<Path>>(path: P) -> std::io::Result<()> {
+	let mut file = File::create(path)?;
+	file.write_all(chrome_trace().as_bytes())
+}
@@ -0,0 +1,65 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static SUPPRESSED: AtomicU64 = AtomicU64::new(0);
+
+/// The state of a single throttled or once-only log call site. Created by the log macros.
+pub struct Throttle {
+	last: Mutex<Option<Instant>>,
+	suppressed: AtomicU64
+}
+
+impl Throttle {
+	pub const fn new() -> Self {
+		Self {
+			last: Mutex::new(None),
+			suppressed: AtomicU64::new(0)
+		}
+	}
+
+	/// Whether the call site may log now. With no interval it may only log once.
+	/// Returns the number of repeats suppressed since it last logged.
+	pub fn pass(&self, interval: Option<Duration>) -> Option<u64> {
+		let Ok(mut last) = self.last.lock() else { return None };
+		let now = Instant::now();
+		let allowed = match (*last, interval) {
+			(None, _) => true,
+			(Some(_), None) => false,
+			(Some(last), Some(interval)) => now.duration_since(last) >= interval
+		};
+
+		if allowed {
+			*last = Some(now);
+			Some(self.suppressed.swap(0, Ordering::Relaxed))
+		} else {
+			self.suppressed.fetch_add(1, Ordering::Relaxed);
+			SUPPRESSED.fetch_add(1, Ordering::Relaxed);
+			None
+		}
+	}
+
+	pub fn suppressed(&self) -> u64 {
+		self.suppressed.load(Ordering::Relaxed)
+	}
+}
+
+impl Default for Throttle {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// How many log lines were dropped by once-only and throttled macros in total.
+pub fn suppressed_total() -> u64 {
+	SUPPRESSED.load(Ordering::Relaxed)
+}
+
+/// Appends the number of suppressed repeats to a throttled message.
+pub fn with_suppressed(message: String, suppressed: u64) -> String {
+	if suppressed == 0 {
+		message
+	} else {
+		format!("{} ({} similar messages suppressed)", message, suppressed)
+	}
+}
@@ -4,6 +4,9 @@ pub use vector::*;
 pub use matrix::*;
 pub use bezier::*;
 pub use easings::*;
+pub use visibility::*;
+pub use tween::*;
+pub use triangulation::*;
 
 mod utilities;
 pub mod point;
@@ -12,4 +15,9 @@ pub mod matrix;
 pub mod quaternion;
 pub mod bezier;
 pub mod easings;
-pub mod noise;
\ No newline at end of file
+pub mod noise;
+pub mod random;
+pub mod visibility;
+pub mod tween;
+pub mod triangulation;
+pub mod stats;
\ No newline at end of file
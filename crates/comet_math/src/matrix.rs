@@ -555,6 +555,25 @@ impl Mat4 {
 		}
 	}
 
+	/// Moves column vectors by `translation`.
+	pub fn from_translation(translation: Vec3) -> Self {
+		Self::new(
+			1.0, 0.0, 0.0, translation.x(),
+			0.0, 1.0, 0.0, translation.y(),
+			0.0, 0.0, 1.0, translation.z(),
+			0.0, 0.0, 0.0, 1.0
+		)
+	}
+
+	pub fn from_scale(scale: Vec3) -> Self {
+		Self::new(
+			scale.x(), 0.0, 0.0, 0.0,
+			0.0, scale.y(), 0.0, 0.0,
+			0.0, 0.0, scale.z(), 0.0,
+			0.0, 0.0, 0.0, 1.0
+		)
+	}
+
 	pub fn rh_look_to(camera: Point3, dir: Vec3, up: Vec3) -> Self {
 		let f = dir.normalize();
 		let s = cross(f, up).normalize();
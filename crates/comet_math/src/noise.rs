@@ -1,4 +1,8 @@
-use rand::{Rng, SeedableRng};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
 use comet_log::debug;
 use crate::utilities::{lerp, lerp2, PI};
 use crate::{dot, InnerSpace, Vec2};
@@ -36,13 +40,12 @@ impl WhiteNoise {
 
 	/// Generates white noise as a `Vec<f32>`. Size of the vector is `width * height`.
 	pub fn generate(&self) -> Vec<f32> {
-		let mut rng = rand::rng();
 		let mut noise = Vec::with_capacity(self.size.0 * self.size.1);
 
 		let dot_vec2 = Vec2::new(12.9898, 78.233);
 
 		for i in 0..self.size.0 * self.size.1 {
-			noise.push(rng.random_range(0.0..1.0));
+			noise.push(crate::random::random());
 			//noise.push(((dot(&Vec2::new(x,y), &dot_vec2)).sin() * 43758.5453).fract());
 		}
 
@@ -50,6 +53,7 @@ impl WhiteNoise {
 	}
 }
 
+#[derive(Debug, Clone)]
 pub struct PerlinNoise {
 	size: (usize, usize),
 	frequency: f64,
@@ -129,6 +133,53 @@ impl PerlinNoise {
 		noise
 	}
 
+	/// Generates the same noise as `generate` in chunks of rows on another thread, without blocking the caller.
+	/// Run the returned job with `App::spawn_noise` or on any thread and poll or await the handle for the result.
+	pub fn generate_async(&self) -> (NoiseHandle, NoiseJob) {
+		self.generate_with_octaves_async(1, 1.0)
+	}
+
+	/// Generates the same noise as `generate_with_octaves` in chunks of rows, see `generate_async`.
+	pub fn generate_with_octaves_async(&self, octaves: u32, persistence: f64) -> (NoiseHandle, NoiseJob) {
+		let handle = NoiseHandle::new(self.size.1);
+		let job = NoiseJob {
+			noise: self.clone(),
+			octaves,
+			persistence,
+			state: handle.state.clone()
+		};
+		(handle, job)
+	}
+
+	/// The noise of every point in `rows`, the octaves summed up per point like `generate_with_octaves` does.
+	fn generate_rows(&self, rows: std::ops::Range<usize>, octaves: u32, persistence: f64, noise: &mut Vec<f32>) {
+		for y in rows {
+			for x in 0..self.size.0 {
+				let nx = x as f64 / self.size.0 as f64;
+				let ny = y as f64 / self.size.1 as f64;
+				noise.push(self.sample_with_octaves(nx, ny, octaves, persistence));
+			}
+		}
+	}
+
+	/// The noise at a single point of an endless plane, from 0 to 1. The point is scaled by the frequency,
+	/// so with a frequency of 1 the noise changes over a distance of about 1. The size of the noise is ignored.
+	pub fn sample(&self, x: f64, y: f64) -> f32 {
+		self.sample_with_octaves(x, y, 1, 1.0)
+	}
+
+	/// Like `sample`, but sums up `octaves` layers of noise, each with twice the frequency and `persistence` times the amplitude.
+	pub fn sample_with_octaves(&self, x: f64, y: f64, octaves: u32, persistence: f64) -> f32 {
+		let max_value: f64 = (0..octaves).map(|octave| persistence.powi(octave as i32)).sum();
+		let (mut value, mut amplitude, mut frequency) = (0.0, 1.0, self.frequency);
+		for _ in 0..octaves {
+			value += self.perlin(x * frequency, y * frequency) * amplitude as f32;
+			amplitude *= persistence;
+			frequency *= 2.0;
+		}
+		(value / max_value as f32 + 1.0) * 0.5
+	}
+
 
 	/// A raw Perlin noise function implementation.
 	fn perlin(&self, x: f64, y: f64) -> f32 {
@@ -189,6 +240,125 @@ impl PerlinNoise {
 	}
 }
 
+/// How many rows of noise a `NoiseJob` generates between checking for cancellation and reporting progress.
+const CHUNK_ROWS: usize = 16;
+
+/// What a `NoiseHandle` and its `NoiseJob` share across threads.
+struct NoiseState {
+	rows: usize,
+	rows_done: AtomicUsize,
+	cancelled: AtomicBool,
+	finished: AtomicBool,
+	result: Mutex<Option<Vec<f32>>>,
+	waker: Mutex<Option<Waker>>
+}
+
+/// The noise being generated by a `NoiseJob`. Poll it with `progress` and `try_take`,
+/// or await it inside an app task to get the noise, or `None` if it was cancelled.
+#[derive(Clone)]
+pub struct NoiseHandle {
+	state: Arc<NoiseState>
+}
+
+impl NoiseHandle {
+	fn new(rows: usize) -> Self {
+		Self {
+			state: Arc::new(NoiseState {
+				rows,
+				rows_done: AtomicUsize::new(0),
+				cancelled: AtomicBool::new(false),
+				finished: AtomicBool::new(false),
+				result: Mutex::new(None),
+				waker: Mutex::new(None)
+			})
+		}
+	}
+
+	/// How much of the noise is generated, from 0 to 1.
+	pub fn progress(&self) -> f32 {
+		if self.state.rows == 0 {
+			return if self.is_finished() { 1.0 } else { 0.0 };
+		}
+		self.state.rows_done.load(Ordering::Acquire) as f32 / self.state.rows as f32
+	}
+
+	pub fn is_finished(&self) -> bool {
+		self.state.finished.load(Ordering::Acquire)
+	}
+
+	/// Stops the job after the chunk it is working on. The noise generated so far is thrown away.
+	pub fn cancel(&self) {
+		self.state.cancelled.store(true, Ordering::Release);
+		self.state.wake();
+	}
+
+	pub fn is_cancelled(&self) -> bool {
+		self.state.cancelled.load(Ordering::Acquire)
+	}
+
+	/// The generated noise once it is finished. It can only be taken once.
+	pub fn try_take(&self) -> Option<Vec<f32>> {
+		self.state.result.lock().unwrap_or_else(|e| e.into_inner()).take()
+	}
+}
+
+impl Future for NoiseHandle {
+	type Output = Option<Vec<f32>>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		if self.is_cancelled() {
+			return Poll::Ready(None);
+		}
+		if let Some(noise) = self.try_take() {
+			return Poll::Ready(Some(noise));
+		}
+		*self.state.waker.lock().unwrap_or_else(|e| e.into_inner()) = Some(cx.waker().clone());
+		// the job may have finished between taking and storing the waker
+		match self.try_take() {
+			Some(noise) => Poll::Ready(Some(noise)),
+			None => Poll::Pending
+		}
+	}
+}
+
+impl NoiseState {
+	fn wake(&self) {
+		if let Some(waker) = self.waker.lock().unwrap_or_else(|e| e.into_inner()).take() {
+			waker.wake();
+		}
+	}
+}
+
+/// The work of generating noise asynchronously, created together with its `NoiseHandle`.
+pub struct NoiseJob {
+	noise: PerlinNoise,
+	octaves: u32,
+	persistence: f64,
+	state: Arc<NoiseState>
+}
+
+impl NoiseJob {
+	/// Generates the noise on the current thread, chunk by chunk, until it is done or cancelled.
+	pub fn run(self) {
+		let (width, height) = self.noise.size;
+		let mut noise = Vec::with_capacity(width * height);
+		let mut row = 0;
+		while row < height {
+			if self.state.cancelled.load(Ordering::Acquire) {
+				debug!("Noise generation was cancelled after {} of {} rows", row, height);
+				return;
+			}
+			let end = (row + CHUNK_ROWS).min(height);
+			self.noise.generate_rows(row..end, self.octaves, self.persistence, &mut noise);
+			row = end;
+			self.state.rows_done.store(row, Ordering::Release);
+		}
+		*self.state.result.lock().unwrap_or_else(|e| e.into_inner()) = Some(noise);
+		self.state.finished.store(true, Ordering::Release);
+		self.state.wake();
+	}
+}
+
 pub struct ValueNoise {
 	size: (usize, usize),
 	frequency: f64,
@@ -1,14 +1,26 @@
 use std::ops::Mul;
 
-use crate::vector::Vec3;
+use crate::matrix::Mat4;
+use crate::vector::{cross, dot, Vec3};
 
 /// Representation of a quaternion in scalar/vector form
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Quat {
 	pub s: f32,
 	pub v: Vec3,
 }
 
 impl Quat {
+	/// No rotation at all.
+	pub const IDENTITY: Quat = Quat {
+		s: 1.0,
+		v: Vec3 {
+			x: 0.0,
+			y: 0.0,
+			z: 0.0,
+		},
+	};
+
 	pub const fn zero() -> Self {
 		Self {
 			s: 0.0,
@@ -42,6 +54,101 @@ impl Quat {
 			z: self.v.z,
 		}
 	}
+
+	/// A rotation of `angle` radians counterclockwise around `axis`.
+	pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+		let (sin, cos) = (angle * 0.5).sin_cos();
+		Self::new(cos, axis.normalize() * sin)
+	}
+
+	/// A rotation around the x axis first, then the y axis and then the z axis, all in radians.
+	pub fn from_euler(x: f32, y: f32, z: f32) -> Self {
+		Self::from_axis_angle(Vec3::Z, z) * Self::from_axis_angle(Vec3::Y, y) * Self::from_axis_angle(Vec3::X, x)
+	}
+
+	/// The rotation turning the forward direction (-z) towards `forward`, keeping the top as close to `up` as possible.
+	pub fn look_rotation(forward: Vec3, up: Vec3) -> Self {
+		let back = (forward * -1.0).normalize();
+		let right = cross(up, back).normalize();
+		let up = cross(back, right);
+		Self::from_basis(right, up, back)
+	}
+
+	/// The rotation mapping the x, y and z axes onto the given orthonormal axes.
+	fn from_basis(x: Vec3, y: Vec3, z: Vec3) -> Self {
+		let trace = x.x + y.y + z.z;
+		let quat = if trace > 0.0 {
+			let s = (trace + 1.0).sqrt() * 2.0;
+			Self::new(0.25 * s, Vec3::new((y.z - z.y) / s, (z.x - x.z) / s, (x.y - y.x) / s))
+		}
+		else if x.x > y.y && x.x > z.z {
+			let s = (1.0 + x.x - y.y - z.z).sqrt() * 2.0;
+			Self::new((y.z - z.y) / s, Vec3::new(0.25 * s, (y.x + x.y) / s, (z.x + x.z) / s))
+		}
+		else if y.y > z.z {
+			let s = (1.0 + y.y - x.x - z.z).sqrt() * 2.0;
+			Self::new((z.x - x.z) / s, Vec3::new((y.x + x.y) / s, 0.25 * s, (z.y + y.z) / s))
+		}
+		else {
+			let s = (1.0 + z.z - x.x - y.y).sqrt() * 2.0;
+			Self::new((x.y - y.x) / s, Vec3::new((z.x + x.z) / s, (z.y + y.z) / s, 0.25 * s))
+		};
+		quat.normalize()
+	}
+
+	pub fn dot(&self, other: &Quat) -> f32 {
+		self.s * other.s + dot(&self.v, &other.v)
+	}
+
+	pub fn length(&self) -> f32 {
+		self.dot(self).sqrt()
+	}
+
+	/// The opposite rotation, assuming this one is normalized.
+	pub fn inverse(&self) -> Self {
+		self.conjugate()
+	}
+
+	/// Rotates `vector` by this (normalized) quaternion.
+	pub fn rotate(&self, vector: Vec3) -> Vec3 {
+		let t = cross(self.v, vector) * 2.0;
+		vector + t * self.s + cross(self.v, t)
+	}
+
+	/// Spherical interpolation along the shortest arc, `t` going from 0 (`self`) to 1 (`other`).
+	pub fn slerp(&self, other: &Quat, t: f32) -> Self {
+		let mut other = *other;
+		let mut cos = self.dot(&other);
+		if cos < 0.0 {
+			other = Self::new(-other.s, other.v * -1.0);
+			cos = -cos;
+		}
+		if cos > 0.9995 {
+			return Self::new(self.s + (other.s - self.s) * t, self.v + (other.v - self.v) * t).normalize();
+		}
+		let angle = cos.acos();
+		let sin = angle.sin();
+		let a = ((1.0 - t) * angle).sin() / sin;
+		let b = (t * angle).sin() / sin;
+		Self::new(self.s * a + other.s * b, self.v * a + other.v * b)
+	}
+
+	/// The rotation as a matrix acting on column vectors.
+	pub fn to_matrix(&self) -> Mat4 {
+		let (s, x, y, z) = (self.s, self.v.x, self.v.y, self.v.z);
+		Mat4::new(
+			1.0 - 2.0*(y*y + z*z), 2.0*(x*y - s*z), 2.0*(x*z + s*y), 0.0,
+			2.0*(x*y + s*z), 1.0 - 2.0*(x*x + z*z), 2.0*(y*z - s*x), 0.0,
+			2.0*(x*z - s*y), 2.0*(y*z + s*x), 1.0 - 2.0*(x*x + y*y), 0.0,
+			0.0, 0.0, 0.0, 1.0
+		)
+	}
+}
+
+impl Default for Quat {
+	fn default() -> Self {
+		Self::IDENTITY
+	}
 }
 
 impl Mul<Quat> for Quat {
@@ -58,3 +165,11 @@ impl Mul<Quat> for Quat {
 		}
 	}
 }
+
+impl Mul<Vec3> for Quat {
+	type Output = Vec3;
+
+	fn mul(self, other: Vec3) -> Vec3 {
+		self.rotate(other)
+	}
+}
@@ -0,0 +1,49 @@
+use std::ops::Range;
+use std::sync::{Mutex, OnceLock};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+struct EngineRng {
+	rng: StdRng,
+	seed: Option<u64>
+}
+
+static ENGINE_RNG: OnceLock<Mutex<EngineRng>> = OnceLock::new();
+
+fn engine_rng() -> &'static Mutex<EngineRng> {
+	ENGINE_RNG.get_or_init(|| Mutex::new(EngineRng {
+		rng: StdRng::from_rng(&mut rand::rng()),
+		seed: None
+	}))
+}
+
+/// Reseeds the random number generator every engine system draws from.
+/// The same seed always produces the same sequence of numbers.
+pub fn seed_rng(seed: u64) {
+	let mut engine_rng = engine_rng().lock().unwrap_or_else(|e| e.into_inner());
+	engine_rng.rng = StdRng::seed_from_u64(seed);
+	engine_rng.seed = Some(seed);
+}
+
+/// The seed set with `seed_rng`, if any.
+pub fn rng_seed() -> Option<u64> {
+	engine_rng().lock().unwrap_or_else(|e| e.into_inner()).seed
+}
+
+/// Gives access to the engine random number generator.
+pub fn with_rng<R>(f: impl FnOnce(&mut StdRng) -> R) -> R {
+	f(&mut engine_rng().lock().unwrap_or_else(|e| e.into_inner()).rng)
+}
+
+/// A random number in `0.0..1.0`.
+pub fn random() -> f32 {
+	with_rng(|rng| rng.random_range(0.0..1.0))
+}
+
+pub fn random_range(range: Range<f32>) -> f32 {
+	with_rng(|rng| rng.random_range(range))
+}
+
+pub fn random_int(range: Range<i32>) -> i32 {
+	with_rng(|rng| rng.random_range(range))
+}
@@ -0,0 +1,175 @@
+use std::ops::Range;
+
+/// The average of `values`, `None` if there are none.
+pub fn mean(values: &[f32]) -> Option<f32> {
+	if values.is_empty() {
+		return None;
+	}
+	Some((values.iter().map(|&value| value as f64).sum::<f64>() / values.len() as f64) as f32)
+}
+
+/// The mean squared distance of `values` from their mean, `None` if there are none.
+pub fn variance(values: &[f32]) -> Option<f32> {
+	let mean = mean(values)? as f64;
+	Some((values.iter().map(|&value| (value as f64 - mean).powi(2)).sum::<f64>() / values.len() as f64) as f32)
+}
+
+pub fn std_dev(values: &[f32]) -> Option<f32> {
+	variance(values).map(f32::sqrt)
+}
+
+/// The smallest and the largest of `values`, ignoring NaNs.
+pub fn min_max(values: &[f32]) -> Option<(f32, f32)> {
+	values.iter()
+		.filter(|value| !value.is_nan())
+		.fold(None, |range, &value| match range {
+			Some((min, max)) => Some((value.min(min), value.max(max))),
+			None => Some((value, value))
+		})
+}
+
+/// The value below which `percent` of `values` lie, from 0 for the smallest to 100 for the largest,
+/// interpolated linearly between the two closest values. NaNs are ignored.
+pub fn percentile(values: &[f32], percent: f32) -> Option<f32> {
+	let mut sorted: Vec<f32> = values.iter().copied().filter(|value| !value.is_nan()).collect();
+	if sorted.is_empty() {
+		return None;
+	}
+	sorted.sort_by(f32::total_cmp);
+	let rank = percent.clamp(0.0, 100.0) / 100.0 * (sorted.len() - 1) as f32;
+	let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+	Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f32))
+}
+
+pub fn median(values: &[f32]) -> Option<f32> {
+	percentile(values, 50.0)
+}
+
+/// Maps `value` from `in_range` to `out_range`, e.g. noise from -1..1 to a height from 0..64.
+/// Values outside of `in_range` end up outside of `out_range`, see `remap_clamped`.
+pub fn remap(value: f32, in_range: Range<f32>, out_range: Range<f32>) -> f32 {
+	let span = in_range.end - in_range.start;
+	let t = if span != 0.0 { (value - in_range.start) / span } else { 0.0 };
+	out_range.start + (out_range.end - out_range.start) * t
+}
+
+/// Like `remap`, but keeps the result inside of `out_range`.
+pub fn remap_clamped(value: f32, in_range: Range<f32>, out_range: Range<f32>) -> f32 {
+	let (low, high) = (out_range.start.min(out_range.end), out_range.start.max(out_range.end));
+	remap(value, in_range, out_range).clamp(low, high)
+}
+
+/// Stretches `values` in place so the smallest becomes 0 and the largest 1. Values that are all equal become 0.
+pub fn normalize(values: &mut [f32]) {
+	let Some((min, max)) = min_max(values) else {
+		return;
+	};
+	values.iter_mut().for_each(|value| *value = remap(*value, min..max, 0.0..1.0));
+}
+
+/// 0 below `edge0`, 1 above `edge1` and a smooth S curve in between, with a flat slope at both edges.
+pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+	let t = remap_clamped(x, edge0..edge1, 0.0..1.0);
+	t * t * (3.0 - 2.0 * t)
+}
+
+/// Like `smoothstep`, but the curvature is flat at both edges too, which avoids visible bands in gradients.
+pub fn smootherstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+	let t = remap_clamped(x, edge0..edge1, 0.0..1.0);
+	t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+/// How many values fall into each of a number of equally wide bins, e.g. to check how a noise map
+/// or a loot table is distributed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+	range: Range<f32>,
+	counts: Vec<usize>,
+	/// Values outside of the range, which no bin counts.
+	outside: usize
+}
+
+impl Histogram {
+	/// An empty histogram of `bins` bins spanning `range`.
+	pub fn new(bins: usize, range: Range<f32>) -> Self {
+		Self {
+			range,
+			counts: vec![0; bins.max(1)],
+			outside: 0
+		}
+	}
+
+	/// A histogram of `values` with `bins` bins spanning from the smallest to the largest of them.
+	pub fn from_values(values: &[f32], bins: usize) -> Self {
+		let (min, max) = min_max(values).unwrap_or((0.0, 1.0));
+		let mut histogram = Self::new(bins, min..max);
+		histogram.extend(values);
+		histogram
+	}
+
+	/// Counts `value` in its bin. The end of the range counts in the last bin.
+	pub fn add(&mut self, value: f32) {
+		match self.bin_of(value) {
+			Some(bin) => self.counts[bin] += 1,
+			None => self.outside += 1
+		}
+	}
+
+	pub fn extend(&mut self, values: &[f32]) {
+		values.iter().for_each(|&value| self.add(value));
+	}
+
+	/// The bin `value` falls into, `None` if it is outside of the range.
+	pub fn bin_of(&self, value: f32) -> Option<usize> {
+		if !(self.range.start..=self.range.end).contains(&value) {
+			return None;
+		}
+		let bin = (remap(value, self.range.clone(), 0.0..self.counts.len() as f32) as usize).min(self.counts.len() - 1);
+		Some(bin)
+	}
+
+	pub fn counts(&self) -> &[usize] {
+		&self.counts
+	}
+
+	pub fn bin_count(&self) -> usize {
+		self.counts.len()
+	}
+
+	pub fn range(&self) -> Range<f32> {
+		self.range.clone()
+	}
+
+	pub fn bin_width(&self) -> f32 {
+		(self.range.end - self.range.start) / self.counts.len() as f32
+	}
+
+	/// The values the bin `bin` counts.
+	pub fn bin_range(&self, bin: usize) -> Range<f32> {
+		let start = self.range.start + self.bin_width() * bin as f32;
+		start..start + self.bin_width()
+	}
+
+	/// How many values were counted in any bin.
+	pub fn total(&self) -> usize {
+		self.counts.iter().sum()
+	}
+
+	/// How many values were outside of the range.
+	pub fn outside(&self) -> usize {
+		self.outside
+	}
+
+	/// The share of the counted values in the bin `bin`, from 0 to 1.
+	pub fn fraction(&self, bin: usize) -> f32 {
+		match self.total() {
+			0 => 0.0,
+			total => self.counts.get(bin).copied().unwrap_or(0) as f32 / total as f32
+		}
+	}
+
+	/// The bin with the most values, the first one on a tie.
+	pub fn peak(&self) -> usize {
+		self.counts.iter().enumerate().fold(0, |peak, (bin, &count)| if count > self.counts[peak] { bin } else { peak })
+	}
+}
@@ -0,0 +1,50 @@
+use crate::Vec2;
+
+/// Splits a simple polygon into triangles by clipping ears, e.g. the outline of a piece of terrain.
+/// The points may wind either way and the polygon may be concave, but its edges must not cross.
+/// Returns three indices into `polygon` per triangle, wound counterclockwise.
+pub fn triangulate(polygon: &[Vec2]) -> Vec<u32> {
+	if polygon.len() < 3 {
+		return Vec::new();
+	}
+
+	let mut remaining: Vec<usize> = (0..polygon.len()).collect();
+	if signed_area(polygon) < 0.0 {
+		remaining.reverse();
+	}
+
+	let mut indices = Vec::with_capacity((polygon.len() - 2) * 3);
+	while remaining.len() > 3 {
+		let count = remaining.len();
+		let ear = (0..count).find(|&i| {
+			let (a, b, c) = (polygon[remaining[(i + count - 1) % count]], polygon[remaining[i]], polygon[remaining[(i + 1) % count]]);
+			cross(a, b, c) > 0.0 && !remaining.iter()
+				.map(|&index| polygon[index])
+				.any(|point| point != a && point != b && point != c && contains(a, b, c, point))
+		});
+		// collinear or self intersecting leftovers have no ear, cutting any corner keeps the loop going
+		let i = ear.unwrap_or(0);
+		indices.extend_from_slice(&[remaining[(i + count - 1) % count] as u32, remaining[i] as u32, remaining[(i + 1) % count] as u32]);
+		remaining.remove(i);
+	}
+	indices.extend(remaining.iter().map(|&index| index as u32));
+	indices
+}
+
+/// Twice the area of the polygon, positive if it winds counterclockwise.
+fn signed_area(polygon: &[Vec2]) -> f32 {
+	let mut area = 0.0;
+	for (i, point) in polygon.iter().enumerate() {
+		let next = polygon[(i + 1) % polygon.len()];
+		area += point.x() * next.y() - next.x() * point.y();
+	}
+	area
+}
+
+fn cross(a: Vec2, b: Vec2, c: Vec2) -> f32 {
+	(b.x() - a.x()) * (c.y() - a.y()) - (b.y() - a.y()) * (c.x() - a.x())
+}
+
+fn contains(a: Vec2, b: Vec2, c: Vec2, point: Vec2) -> bool {
+	cross(a, b, point) >= 0.0 && cross(b, c, point) >= 0.0 && cross(c, a, point) >= 0.0
+}
@@ -0,0 +1,78 @@
+/// Animates a value from `from` to `to` over `duration` seconds, shaped by an easing function like `ease_out_cubic`.
+#[derive(Debug, Clone, Copy)]
+pub struct Tween {
+	from: f32,
+	to: f32,
+	duration: f32,
+	elapsed: f32,
+	easing: fn(f32) -> f32,
+	looping: bool
+}
+
+impl Tween {
+	/// A linear tween, starting right away.
+	pub fn new(from: f32, to: f32, duration: f32) -> Self {
+		Self {
+			from,
+			to,
+			duration,
+			elapsed: 0.0,
+			easing: linear,
+			looping: false
+		}
+	}
+
+	/// Shapes the tween with `easing`, which maps the progress from 0 to 1 onto how far along the value is.
+	pub fn with_easing(mut self, easing: fn(f32) -> f32) -> Self {
+		self.easing = easing;
+		self
+	}
+
+	/// Starts over once it reached `to`, instead of stopping there.
+	pub fn with_looping(mut self, looping: bool) -> Self {
+		self.looping = looping;
+		self
+	}
+
+	pub fn from(&self) -> f32 {
+		self.from
+	}
+
+	pub fn to(&self) -> f32 {
+		self.to
+	}
+
+	pub fn duration(&self) -> f32 {
+		self.duration
+	}
+
+	pub fn is_looping(&self) -> bool {
+		self.looping
+	}
+
+	/// Moves the tween `dt` seconds along.
+	pub fn advance(&mut self, dt: f32) {
+		self.elapsed += dt;
+		if self.looping && self.duration > 0.0 {
+			self.elapsed %= self.duration;
+		}
+	}
+
+	/// How far along the tween is, from 0 to 1, before easing.
+	pub fn progress(&self) -> f32 {
+		if self.duration > 0.0 { (self.elapsed / self.duration).clamp(0.0, 1.0) } else { 1.0 }
+	}
+
+	pub fn value(&self) -> f32 {
+		self.from + (self.to - self.from) * (self.easing)(self.progress())
+	}
+
+	/// Whether it reached `to`. Looping tweens never finish.
+	pub fn is_finished(&self) -> bool {
+		!self.looping && self.progress() >= 1.0
+	}
+}
+
+fn linear(x: f32) -> f32 {
+	x
+}
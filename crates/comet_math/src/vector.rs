@@ -3126,7 +3126,7 @@ pub fn cross(v1: Vec3, v2: Vec3) -> Vec3 {
 	Vec3 {
 		x: v1.y * v2.z - v1.z * v2.y,
 		y: v1.z * v2.x - v1.x * v2.z,
-		z: v1.x * v2.y - v2.y * v1.x,
+		z: v1.x * v2.y - v1.y * v2.x,
 	}
 }
 
@@ -0,0 +1,236 @@
+use crate::Vec2;
+
+/// How many rays fill the gaps between occluders, so the edge of the view radius is round.
+const RADIUS_SEGMENTS: usize = 64;
+/// The angle rays are cast beside every corner, to see past it.
+const CORNER_OFFSET: f32 = 0.0001;
+
+/// A wall blocking the view, e.g. one side of a collider or a tile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Occluder {
+	start: Vec2,
+	end: Vec2
+}
+
+impl Occluder {
+	pub fn new(start: Vec2, end: Vec2) -> Self {
+		Self {
+			start,
+			end
+		}
+	}
+
+	pub fn start(&self) -> Vec2 {
+		self.start
+	}
+
+	pub fn end(&self) -> Vec2 {
+		self.end
+	}
+
+	/// The four sides of an axis aligned rectangle given by its bottom left corner and size.
+	pub fn from_rect(position: Vec2, size: Vec2) -> [Occluder; 4] {
+		let (x0, y0) = (position.x(), position.y());
+		let (x1, y1) = (x0 + size.x(), y0 + size.y());
+		[
+			Occluder::new(Vec2::new(x0, y0), Vec2::new(x1, y0)),
+			Occluder::new(Vec2::new(x1, y0), Vec2::new(x1, y1)),
+			Occluder::new(Vec2::new(x1, y1), Vec2::new(x0, y1)),
+			Occluder::new(Vec2::new(x0, y1), Vec2::new(x0, y0))
+		]
+	}
+
+	/// The walls between the solid and the open tiles of a `width` by `height` grid, e.g. of a tilemap.
+	/// Tile `(x, y)` spans from `origin + (x, y) * tile_size` one tile up and right. Tiles outside the grid are open,
+	/// and walls continuing each other on a line are merged, so fewer rays are cast.
+	pub fn from_tiles(origin: Vec2, tile_size: Vec2, width: usize, height: usize, is_solid: impl Fn(usize, usize) -> bool) -> Vec<Occluder> {
+		let solid = |x: usize, y: usize| x < width && y < height && is_solid(x, y);
+		let corner = |x: usize, y: usize| Vec2::new(origin.x() + x as f32 * tile_size.x(), origin.y() + y as f32 * tile_size.y());
+		let mut occluders = Vec::new();
+
+		// walls along the bottom of every row, then along the left of every column
+		for y in 0..=height {
+			let mut start = None;
+			for x in 0..=width {
+				let wall = x < width && solid(x, y) != (y > 0 && solid(x, y - 1));
+				match (wall, start) {
+					(true, None) => start = Some(x),
+					(false, Some(from)) => {
+						occluders.push(Occluder::new(corner(from, y), corner(x, y)));
+						start = None;
+					}
+					_ => {}
+				}
+			}
+		}
+		for x in 0..=width {
+			let mut start = None;
+			for y in 0..=height {
+				let wall = y < height && solid(x, y) != (x > 0 && solid(x - 1, y));
+				match (wall, start) {
+					(true, None) => start = Some(y),
+					(false, Some(from)) => {
+						occluders.push(Occluder::new(corner(x, from), corner(x, y)));
+						start = None;
+					}
+					_ => {}
+				}
+			}
+		}
+		occluders
+	}
+
+	/// The distance along the ray from `origin` in `direction` (normalized) to this occluder, if the ray hits it.
+	fn intersect(&self, origin: Vec2, direction: Vec2) -> Option<f32> {
+		let edge = self.end - self.start;
+		let denominator = cross(direction, edge);
+		if denominator.abs() < f32::EPSILON {
+			return None;
+		}
+
+		let offset = self.start - origin;
+		let distance = cross(offset, edge) / denominator;
+		let along_edge = cross(offset, direction) / denominator;
+		(distance >= 0.0 && (0.0..=1.0).contains(&along_edge)).then_some(distance)
+	}
+}
+
+fn cross(a: Vec2, b: Vec2) -> f32 {
+	a.x() * b.y() - a.y() * b.x()
+}
+
+/// The area a viewer or light at `origin` can see, bounded by occluders and a view radius.
+/// Useful for fog of war, stealth checks and 2D light shapes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VisibilityPolygon {
+	origin: Vec2,
+	points: Vec<Vec2>
+}
+
+impl VisibilityPolygon {
+	/// Casts rays at every occluder corner (and just beside it) and around the view radius, keeping the nearest hits.
+	pub fn compute(origin: Vec2, occluders: &[Occluder], radius: f32) -> Self {
+		let mut angles: Vec<f32> = (0..RADIUS_SEGMENTS)
+			.map(|i| i as f32 / RADIUS_SEGMENTS as f32 * std::f32::consts::TAU)
+			.collect();
+
+		for occluder in occluders {
+			for corner in [occluder.start, occluder.end] {
+				let offset = corner - origin;
+				if offset.length() > radius {
+					continue;
+				}
+				let angle = offset.y().atan2(offset.x());
+				angles.extend_from_slice(&[angle - CORNER_OFFSET, angle, angle + CORNER_OFFSET]);
+			}
+		}
+
+		let mut angles: Vec<f32> = angles.into_iter().map(|angle| angle.rem_euclid(std::f32::consts::TAU)).collect();
+		angles.sort_by(f32::total_cmp);
+		angles.dedup();
+
+		let points = angles.into_iter()
+			.map(|angle| {
+				let direction = Vec2::new(angle.cos(), angle.sin());
+				let distance = occluders.iter()
+					.filter_map(|occluder| occluder.intersect(origin, direction))
+					.fold(radius, f32::min);
+				origin + direction * distance
+			})
+			.collect();
+
+		Self {
+			origin,
+			points
+		}
+	}
+
+	pub fn origin(&self) -> Vec2 {
+		self.origin
+	}
+
+	/// The outline of the visible area, counterclockwise around the origin.
+	pub fn points(&self) -> &[Vec2] {
+		&self.points
+	}
+
+	/// Whether `point` is visible from the origin.
+	pub fn contains(&self, point: Vec2) -> bool {
+		let mut inside = false;
+		let mut previous = match self.points.last() {
+			Some(last) => *last,
+			None => return false
+		};
+		for current in &self.points {
+			if (current.y() > point.y()) != (previous.y() > point.y()) {
+				let crossing = previous.x() + (point.y() - previous.y()) / (current.y() - previous.y()) * (current.x() - previous.x());
+				if point.x() < crossing {
+					inside = !inside;
+				}
+			}
+			previous = *current;
+		}
+		inside
+	}
+
+	/// The area as a fan of triangles around the origin, ready to be drawn.
+	pub fn triangles(&self) -> Vec<[Vec2; 3]> {
+		let count = self.points.len();
+		if count < 2 {
+			return Vec::new();
+		}
+		(0..count)
+			.map(|i| [self.origin, self.points[i], self.points[(i + 1) % count]])
+			.collect()
+	}
+
+	/// Everything between `min` and `max` that is not visible as triangles, e.g. to darken the rest of the view for fog of war.
+	/// The bounds grow to contain the whole polygon.
+	pub fn hidden_triangles(&self, min: Vec2, max: Vec2) -> Vec<[Vec2; 3]> {
+		let count = self.points.len();
+		if count < 2 {
+			return vec![[min, Vec2::new(max.x(), min.y()), max], [min, max, Vec2::new(min.x(), max.y())]];
+		}
+
+		// a margin keeps the origin off the border, so every ray leaves the bounds at a distance
+		let (mut min, mut max) = (min, max);
+		for point in self.points.iter().chain(std::iter::once(&self.origin)) {
+			min = Vec2::new(min.x().min(point.x() - 1.0), min.y().min(point.y() - 1.0));
+			max = Vec2::new(max.x().max(point.x() + 1.0), max.y().max(point.y() + 1.0));
+		}
+		let corners = [min, Vec2::new(max.x(), min.y()), max, Vec2::new(min.x(), max.y())];
+		let angle = |point: Vec2| (point.y() - self.origin.y()).atan2(point.x() - self.origin.x());
+		let on_border = |point: Vec2| {
+			let direction = point - self.origin;
+			let reach = |offset: f32, low: f32, high: f32| match offset {
+				offset if offset > 0.0 => high / offset,
+				offset if offset < 0.0 => low / offset,
+				_ => f32::INFINITY
+			};
+			let scale = reach(direction.x(), min.x() - self.origin.x(), max.x() - self.origin.x())
+				.min(reach(direction.y(), min.y() - self.origin.y(), max.y() - self.origin.y()));
+			self.origin + direction * scale
+		};
+
+		let mut triangles = Vec::new();
+		for i in 0..count {
+			let (from, to) = (self.points[i], self.points[(i + 1) % count]);
+			let sweep = (angle(to) - angle(from)).rem_euclid(std::f32::consts::TAU);
+			let mut between = corners.iter()
+				.map(|corner| ((angle(*corner) - angle(from)).rem_euclid(std::f32::consts::TAU), *corner))
+				.filter(|(offset, _)| *offset > 0.0 && *offset < sweep)
+				.collect::<Vec<(f32, Vec2)>>();
+			between.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+			// the part of the bounds in this slice beyond the polygon edge is convex, so it fans out from `from`
+			let mut outline = vec![on_border(from)];
+			outline.extend(between.into_iter().map(|(_, corner)| corner));
+			outline.push(on_border(to));
+			outline.push(to);
+			for pair in outline.windows(2) {
+				triangles.push([from, pair[0], pair[1]]);
+			}
+		}
+		triangles
+	}
+}
@@ -0,0 +1,99 @@
+use winit::keyboard::KeyCode;
+
+/// The kind of color blindness the colors on screen are corrected for.
+/// Colors the player cannot tell apart are shifted towards ones they can, which is known as daltonization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorBlindFilter {
+	#[default]
+	None,
+	/// Red blindness.
+	Protanopia,
+	/// Green blindness, the most common kind.
+	Deuteranopia,
+	/// Blue blindness.
+	Tritanopia
+}
+
+impl ColorBlindFilter {
+	pub(crate) fn mode(&self) -> u32 {
+		match self {
+			ColorBlindFilter::None => 0,
+			ColorBlindFilter::Protanopia => 1,
+			ColorBlindFilter::Deuteranopia => 2,
+			ColorBlindFilter::Tritanopia => 3
+		}
+	}
+}
+
+/// Options that make a game playable for more people, usually picked by the player in an options menu.
+/// The `App` hands them to the renderer and applies the input options itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Accessibility {
+	ui_scale: f32,
+	color_blind_filter: ColorBlindFilter,
+	shake_intensity: f32,
+	hold_to_toggle: Vec<KeyCode>
+}
+
+impl Accessibility {
+	pub fn new() -> Self {
+		Self {
+			ui_scale: 1.0,
+			color_blind_filter: ColorBlindFilter::None,
+			shake_intensity: 1.0,
+			hold_to_toggle: Vec::new()
+		}
+	}
+
+	/// How much larger text and UI are drawn, 1.0 is their normal size.
+	pub fn with_ui_scale(mut self, ui_scale: f32) -> Self {
+		self.ui_scale = ui_scale.clamp(0.25, 4.0);
+		self
+	}
+
+	pub fn with_color_blind_filter(mut self, color_blind_filter: ColorBlindFilter) -> Self {
+		self.color_blind_filter = color_blind_filter;
+		self
+	}
+
+	/// Scales how far the camera shakes, 0.0 turns shaking off for players who get motion sick.
+	pub fn with_shake_intensity(mut self, shake_intensity: f32) -> Self {
+		self.shake_intensity = shake_intensity.max(0.0);
+		self
+	}
+
+	/// Turns holding `key` into pressing it once to hold it down and once more to let go, e.g. for sprinting.
+	pub fn with_hold_to_toggle(mut self, key: KeyCode) -> Self {
+		if !self.hold_to_toggle.contains(&key) {
+			self.hold_to_toggle.push(key);
+		}
+		self
+	}
+
+	pub fn ui_scale(&self) -> f32 {
+		self.ui_scale
+	}
+
+	pub fn color_blind_filter(&self) -> ColorBlindFilter {
+		self.color_blind_filter
+	}
+
+	pub fn shake_intensity(&self) -> f32 {
+		self.shake_intensity
+	}
+
+	/// The keys that are toggled instead of held.
+	pub fn hold_to_toggle(&self) -> &[KeyCode] {
+		&self.hold_to_toggle
+	}
+
+	pub fn is_hold_to_toggle(&self, key: KeyCode) -> bool {
+		self.hold_to_toggle.contains(&key)
+	}
+}
+
+impl Default for Accessibility {
+	fn default() -> Self {
+		Self::new()
+	}
+}
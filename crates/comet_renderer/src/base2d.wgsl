@@ -1,6 +1,7 @@
 // Vertex shader
 struct CameraUniform {
     view_proj: mat4x4<f32>,
+    time: f32,
 };
 @group(1) @binding(0) // 1.
 var<uniform> camera: CameraUniform;
@@ -9,12 +10,21 @@ struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) tex_coords: vec2<f32>,
     @location(2) color: vec4<f32>,
+    @location(3) flash: vec4<f32>,
+    @location(4) region: vec4<f32>,
+    @location(5) scroll: vec2<f32>,
+    @location(6) material: vec3<f32>,
+    @location(7) outline: vec4<f32>,
 }
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) tex_coords: vec2<f32>,
     @location(1) color: vec4<f32>,
+    @location(2) flash: vec4<f32>,
+    @location(3) region: vec4<f32>,
+    @location(4) material: vec3<f32>,
+    @location(5) outline: vec4<f32>,
 }
 
 @vertex
@@ -22,8 +32,12 @@ fn vs_main(
     model: VertexInput,
 ) -> VertexOutput {
     var out: VertexOutput;
-    out.tex_coords = model.tex_coords;
+    out.tex_coords = model.tex_coords + model.scroll * camera.time;
     out.color = model.color;
+    out.flash = model.flash;
+    out.region = model.region;
+    out.material = model.material;
+    out.outline = model.outline;
     out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0);
     return out;
 }
@@ -37,5 +51,30 @@ var s_diffuse: sampler;
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    // vertices with a region repeat the texture inside it instead of pointing into the atlas
+    let repeated = in.region.xy + fract(in.tex_coords) * (in.region.zw - in.region.xy);
+    let tex_coords = select(in.tex_coords, repeated, in.region.z > in.region.x);
+    var color = textureSample(t_diffuse, s_diffuse, tex_coords) * in.color;
+    color = vec4<f32>(mix(color.rgb, in.flash.rgb, in.flash.a), color.a);
+    let luminance = dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+    color = vec4<f32>(mix(color.rgb, vec3<f32>(luminance), in.material.z), color.a);
+
+    // transparent pixels next to opaque ones within the outline width take the outline color
+    let size = vec2<f32>(textureDimensions(t_diffuse));
+    let texel = in.material.y / size;
+    var neighbours = 0.0;
+    neighbours = max(neighbours, textureSampleLevel(t_diffuse, s_diffuse, tex_coords + vec2<f32>(texel.x, 0.0), 0.0).a);
+    neighbours = max(neighbours, textureSampleLevel(t_diffuse, s_diffuse, tex_coords - vec2<f32>(texel.x, 0.0), 0.0).a);
+    neighbours = max(neighbours, textureSampleLevel(t_diffuse, s_diffuse, tex_coords + vec2<f32>(0.0, texel.y), 0.0).a);
+    neighbours = max(neighbours, textureSampleLevel(t_diffuse, s_diffuse, tex_coords - vec2<f32>(0.0, texel.y), 0.0).a);
+    let outline = in.outline.a * step(0.5, neighbours) * (1.0 - step(0.5, color.a)) * step(0.0001, in.material.y);
+    color = mix(color, vec4<f32>(in.outline.rgb, 1.0), outline);
+
+    // a fixed noise value per texel, so the sprite dissolves in the same pattern every frame
+    let cell = floor(tex_coords * size);
+    let noise = fract(sin(dot(cell, vec2<f32>(12.9898, 78.233))) * 43758.5453);
+    if (noise < in.material.x) {
+        discard;
+    }
+    return color;
 }
\ No newline at end of file
@@ -0,0 +1,324 @@
+use crate::tonemapping::HDR_FORMAT;
+
+/// Makes the parts of the scene brighter than `threshold` glow, e.g. `Hdr` colors and `Emissive` sprites.
+/// The glow is blurred over `mip_count` ever smaller copies of the scene, more of them spread it further.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bloom {
+	threshold: f32,
+	softness: f32,
+	intensity: f32,
+	mip_count: u32
+}
+
+impl Bloom {
+	/// The largest number of downsampling steps.
+	pub const MAX_MIP_COUNT: u32 = 8;
+
+	pub fn new() -> Self {
+		Self {
+			threshold: 1.0,
+			softness: 0.5,
+			intensity: 0.3,
+			mip_count: 5
+		}
+	}
+
+	/// How bright a color has to be to glow, 1.0 is white.
+	pub fn with_threshold(mut self, threshold: f32) -> Self {
+		self.threshold = threshold.max(0.0);
+		self
+	}
+
+	/// How far below the threshold colors start to glow a little, as a fraction of the threshold.
+	pub fn with_softness(mut self, softness: f32) -> Self {
+		self.softness = softness.clamp(0.0, 1.0);
+		self
+	}
+
+	/// How strongly the glow is added to the scene.
+	pub fn with_intensity(mut self, intensity: f32) -> Self {
+		self.intensity = intensity.max(0.0);
+		self
+	}
+
+	pub fn with_mip_count(mut self, mip_count: u32) -> Self {
+		self.mip_count = mip_count.clamp(1, Self::MAX_MIP_COUNT);
+		self
+	}
+
+	pub fn threshold(&self) -> f32 {
+		self.threshold
+	}
+
+	pub fn softness(&self) -> f32 {
+		self.softness
+	}
+
+	pub fn intensity(&self) -> f32 {
+		self.intensity
+	}
+
+	pub fn mip_count(&self) -> u32 {
+		self.mip_count
+	}
+}
+
+impl Default for Bloom {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct BloomUniform {
+	threshold: f32,
+	knee: f32,
+	_padding: [f32; 2]
+}
+
+/// Blurs the bright parts of the HDR scene target into a half sized glow texture, which the tonemap pass adds back.
+pub(crate) struct BloomPass {
+	prefilter_pipeline: wgpu::RenderPipeline,
+	downsample_pipeline: wgpu::RenderPipeline,
+	upsample_pipeline: wgpu::RenderPipeline,
+	bind_group_layout: wgpu::BindGroupLayout,
+	sampler: wgpu::Sampler,
+	uniform_buffer: wgpu::Buffer,
+	settings: Bloom,
+	mips: Vec<wgpu::TextureView>,
+	scene_bind_group: wgpu::BindGroup,
+	mip_bind_groups: Vec<wgpu::BindGroup>
+}
+
+impl BloomPass {
+	pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, settings: Bloom, scene: &wgpu::TextureView, width: u32, height: u32) -> Self {
+		let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+			label: Some("Bloom Shader"),
+			source: wgpu::ShaderSource::Wgsl(include_str!("bloom.wgsl").into()),
+		});
+
+		let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+			entries: &[
+				wgpu::BindGroupLayoutEntry {
+					binding: 0,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Texture {
+						multisampled: false,
+						view_dimension: wgpu::TextureViewDimension::D2,
+						sample_type: wgpu::TextureSampleType::Float { filterable: true },
+					},
+					count: None,
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 1,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+					count: None,
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 2,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Buffer {
+						ty: wgpu::BufferBindingType::Uniform,
+						has_dynamic_offset: false,
+						min_binding_size: None,
+					},
+					count: None,
+				},
+			],
+			label: Some("bloom_bind_group_layout"),
+		});
+
+		let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+			label: Some("Bloom Pipeline Layout"),
+			bind_group_layouts: &[&bind_group_layout],
+			push_constant_ranges: &[],
+		});
+
+		let create_pipeline = |label, entry_point, blend| device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+			label: Some(label),
+			layout: Some(&pipeline_layout),
+			vertex: wgpu::VertexState {
+				module: &shader,
+				entry_point: "vs_main",
+				buffers: &[],
+				compilation_options: Default::default(),
+			},
+			fragment: Some(wgpu::FragmentState {
+				module: &shader,
+				entry_point,
+				targets: &[Some(wgpu::ColorTargetState {
+					format: HDR_FORMAT,
+					blend,
+					write_mask: wgpu::ColorWrites::ALL,
+				})],
+				compilation_options: Default::default(),
+			}),
+			primitive: wgpu::PrimitiveState::default(),
+			depth_stencil: None,
+			multisample: wgpu::MultisampleState::default(),
+			multiview: None,
+			cache: None,
+		});
+
+		let additive = wgpu::BlendComponent {
+			src_factor: wgpu::BlendFactor::One,
+			dst_factor: wgpu::BlendFactor::One,
+			operation: wgpu::BlendOperation::Add,
+		};
+		let prefilter_pipeline = create_pipeline("Bloom Prefilter Pipeline", "fs_prefilter", None);
+		let downsample_pipeline = create_pipeline("Bloom Downsample Pipeline", "fs_downsample", None);
+		let upsample_pipeline = create_pipeline("Bloom Upsample Pipeline", "fs_upsample", Some(wgpu::BlendState { color: additive, alpha: additive }));
+
+		// clamped so the taps at the border do not wrap around to the other side
+		let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+			label: Some("Bloom Sampler"),
+			address_mode_u: wgpu::AddressMode::ClampToEdge,
+			address_mode_v: wgpu::AddressMode::ClampToEdge,
+			mag_filter: wgpu::FilterMode::Linear,
+			min_filter: wgpu::FilterMode::Linear,
+			..Default::default()
+		});
+
+		let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+			label: Some("Bloom Buffer"),
+			size: std::mem::size_of::<BloomUniform>() as u64,
+			usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+			mapped_at_creation: false,
+		});
+
+		let scene_bind_group = Self::create_bind_group(device, &bind_group_layout, scene, &sampler, &uniform_buffer);
+		let mut pass = Self {
+			prefilter_pipeline,
+			downsample_pipeline,
+			upsample_pipeline,
+			bind_group_layout,
+			sampler,
+			uniform_buffer,
+			settings,
+			mips: Vec::new(),
+			scene_bind_group,
+			mip_bind_groups: Vec::new()
+		};
+		pass.resize(device, scene, width, height);
+		pass.write_uniform(queue);
+		pass
+	}
+
+	fn create_bind_group(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, source: &wgpu::TextureView, sampler: &wgpu::Sampler, uniform_buffer: &wgpu::Buffer) -> wgpu::BindGroup {
+		device.create_bind_group(&wgpu::BindGroupDescriptor {
+			layout,
+			entries: &[
+				wgpu::BindGroupEntry {
+					binding: 0,
+					resource: wgpu::BindingResource::TextureView(source),
+				},
+				wgpu::BindGroupEntry {
+					binding: 1,
+					resource: wgpu::BindingResource::Sampler(sampler),
+				},
+				wgpu::BindGroupEntry {
+					binding: 2,
+					resource: uniform_buffer.as_entire_binding(),
+				},
+			],
+			label: Some("bloom_bind_group"),
+		})
+	}
+
+	/// Recreates the chain of targets for the new size of the scene target `scene`.
+	pub fn resize(&mut self, device: &wgpu::Device, scene: &wgpu::TextureView, width: u32, height: u32) {
+		self.mips.clear();
+		let (mut mip_width, mut mip_height) = ((width / 2).max(1), (height / 2).max(1));
+		for _ in 0..self.settings.mip_count {
+			self.mips.push(device.create_texture(&wgpu::TextureDescriptor {
+				label: Some("Bloom Target"),
+				size: wgpu::Extent3d {
+					width: mip_width,
+					height: mip_height,
+					depth_or_array_layers: 1,
+				},
+				mip_level_count: 1,
+				sample_count: 1,
+				dimension: wgpu::TextureDimension::D2,
+				format: HDR_FORMAT,
+				usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+				view_formats: &[],
+			}).create_view(&wgpu::TextureViewDescriptor::default()));
+
+			// tiny windows run out of pixels before the chain ends
+			if mip_width == 1 && mip_height == 1 {
+				break;
+			}
+			mip_width = (mip_width / 2).max(1);
+			mip_height = (mip_height / 2).max(1);
+		}
+
+		self.scene_bind_group = Self::create_bind_group(device, &self.bind_group_layout, scene, &self.sampler, &self.uniform_buffer);
+		self.mip_bind_groups = self.mips.iter()
+			.map(|mip| Self::create_bind_group(device, &self.bind_group_layout, mip, &self.sampler, &self.uniform_buffer))
+			.collect();
+	}
+
+	pub fn settings(&self) -> Bloom {
+		self.settings
+	}
+
+	/// Applies new settings, rebuilding the chain of targets if its length changed.
+	pub fn set_settings(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, settings: Bloom, scene: &wgpu::TextureView, width: u32, height: u32) {
+		let rebuild = settings.mip_count != self.settings.mip_count;
+		self.settings = settings;
+		if rebuild {
+			self.resize(device, scene, width, height);
+		}
+		self.write_uniform(queue);
+	}
+
+	fn write_uniform(&self, queue: &wgpu::Queue) {
+		let uniform = BloomUniform {
+			threshold: self.settings.threshold,
+			knee: self.settings.threshold * self.settings.softness,
+			_padding: [0.0; 2]
+		};
+		queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+	}
+
+	/// The blurred glow, half the size of the scene.
+	pub fn view(&self) -> &wgpu::TextureView {
+		&self.mips[0]
+	}
+
+	fn draw(encoder: &mut wgpu::CommandEncoder, label: &str, target: &wgpu::TextureView, load: wgpu::LoadOp<wgpu::Color>, pipeline: &wgpu::RenderPipeline, bind_group: &wgpu::BindGroup) {
+		let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+			label: Some(label),
+			color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+				view: target,
+				resolve_target: None,
+				ops: wgpu::Operations {
+					load,
+					store: wgpu::StoreOp::Store,
+				},
+			})],
+			depth_stencil_attachment: None,
+			occlusion_query_set: None,
+			timestamp_writes: None,
+		});
+
+		render_pass.set_pipeline(pipeline);
+		render_pass.set_bind_group(0, bind_group, &[]);
+		render_pass.draw(0..3, 0..1);
+	}
+
+	/// Records the passes filling the glow texture from the scene target.
+	pub fn render(&self, encoder: &mut wgpu::CommandEncoder) {
+		let clear = wgpu::LoadOp::Clear(wgpu::Color::BLACK);
+		Self::draw(encoder, "Bloom Prefilter Pass", &self.mips[0], clear, &self.prefilter_pipeline, &self.scene_bind_group);
+		for i in 1..self.mips.len() {
+			Self::draw(encoder, "Bloom Downsample Pass", &self.mips[i], clear, &self.downsample_pipeline, &self.mip_bind_groups[i - 1]);
+		}
+		for i in (1..self.mips.len()).rev() {
+			Self::draw(encoder, "Bloom Upsample Pass", &self.mips[i - 1], wgpu::LoadOp::Load, &self.upsample_pipeline, &self.mip_bind_groups[i]);
+		}
+	}
+}
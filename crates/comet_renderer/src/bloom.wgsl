@@ -0,0 +1,77 @@
+// Bloom: keeps the bright parts of the scene, blurs them down a chain of ever smaller targets and adds them back up
+struct BloomSettings {
+    threshold: f32,
+    knee: f32,
+    _padding: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var source_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> settings: BloomSettings;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// a single triangle that covers the whole target
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    out.clip_position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+fn sample_source(uv: vec2<f32>) -> vec3<f32> {
+    return textureSample(source_texture, source_sampler, uv).rgb;
+}
+
+// the center and four diagonal taps of the dual filter, each blending four texels of the bigger source
+fn downsample(uv: vec2<f32>) -> vec3<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(source_texture));
+    var color = sample_source(uv) * 4.0;
+    color += sample_source(uv + vec2<f32>(-texel.x, -texel.y));
+    color += sample_source(uv + vec2<f32>(texel.x, -texel.y));
+    color += sample_source(uv + vec2<f32>(-texel.x, texel.y));
+    color += sample_source(uv + vec2<f32>(texel.x, texel.y));
+    return color / 8.0;
+}
+
+// keeps what is brighter than the threshold, easing in over the knee instead of cutting off hard
+fn threshold(color: vec3<f32>) -> vec3<f32> {
+    let brightness = max(color.r, max(color.g, color.b));
+    var soft = clamp(brightness - settings.threshold + settings.knee, 0.0, 2.0 * settings.knee);
+    soft = soft * soft / (4.0 * settings.knee + 0.00001);
+    let contribution = max(soft, brightness - settings.threshold) / max(brightness, 0.00001);
+    return color * contribution;
+}
+
+@fragment
+fn fs_prefilter(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(threshold(downsample(in.uv)), 1.0);
+}
+
+@fragment
+fn fs_downsample(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(downsample(in.uv), 1.0);
+}
+
+// a tent of eight taps around the pixel, added onto the bigger target by the blend state
+@fragment
+fn fs_upsample(in: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = 0.5 / vec2<f32>(textureDimensions(source_texture));
+    var color = sample_source(in.uv + vec2<f32>(-texel.x * 2.0, 0.0));
+    color += sample_source(in.uv + vec2<f32>(texel.x * 2.0, 0.0));
+    color += sample_source(in.uv + vec2<f32>(0.0, -texel.y * 2.0));
+    color += sample_source(in.uv + vec2<f32>(0.0, texel.y * 2.0));
+    color += sample_source(in.uv + vec2<f32>(-texel.x, -texel.y)) * 2.0;
+    color += sample_source(in.uv + vec2<f32>(texel.x, -texel.y)) * 2.0;
+    color += sample_source(in.uv + vec2<f32>(-texel.x, texel.y)) * 2.0;
+    color += sample_source(in.uv + vec2<f32>(texel.x, texel.y)) * 2.0;
+    return vec4<f32>(color / 12.0, 1.0);
+}
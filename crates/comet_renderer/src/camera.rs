@@ -1,3 +1,5 @@
+use comet_colors::LinearRgba;
+use comet_ecs::{Position2D, Rectangle2D, RenderLayers};
 use comet_math::{Point3, Vec2, Vec3};
 
 #[rustfmt::skip]
@@ -9,14 +11,62 @@ pub const OPENGL_TO_WGPU_MATRIX: cgmath::Matrix4<f32> = cgmath::Matrix4::new(
 );
 
 const SAFE_FRAC_PI_2: f32 = std::f32::consts::FRAC_PI_2 - 0.0001;
+/// How much trauma the camera shake loses per second.
+const SHAKE_DECAY: f32 = 1.0;
+/// How far the camera moves at full trauma, as a fraction of the height of the view.
+const SHAKE_OFFSET: f32 = 0.03;
+/// How fast the shake wobbles back and forth.
+const SHAKE_FREQUENCY: f32 = 25.0;
+
+/// What the camera does with the target before drawing.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum ClearMode {
+	/// Clears with the clear color of the renderer.
+	#[default]
+	Background,
+	/// Clears with its own color, e.g. for a minimap with a different background.
+	Color(LinearRgba),
+	/// Draws over what is already there, e.g. for overlays or trail effects.
+	Keep
+}
+
+/// How a virtual design resolution is scaled onto windows of other sizes and aspect ratios.
+/// The world is never stretched: windows of another aspect ratio show more or less of it along one axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScalePolicy {
+	/// The whole design resolution stays visible, wider or taller windows show more of the world around it.
+	#[default]
+	Fit,
+	/// The design resolution covers the whole window, cutting off its edges along one axis.
+	Fill,
+	/// The design width always spans the window, the visible height follows the aspect ratio.
+	MatchWidth,
+	/// The design height always spans the window, the visible width follows the aspect ratio.
+	MatchHeight
+}
 
 pub struct Camera {
 	zoom: f32,
+	target_zoom: f32,
+	min_zoom: f32,
+	max_zoom: f32,
+	zoom_speed: f32,
+	rotation: f32,
+	pixels_per_unit: f32,
 	dimension: Vec2,
-	position: Vec3
+	virtual_resolution: Option<(Vec2, ScalePolicy)>,
+	scale_factor: f32,
+	position: Vec3,
+	render_layers: RenderLayers,
+	clear_mode: ClearMode,
+	trauma: f32,
+	shake_intensity: f32,
+	shake_time: f32,
+	shake_offset: Vec2
 }
 
 impl Camera {
+	/// A camera showing `dimension` pixels of the window, usually its whole size, around `position` in world units.
 	pub fn new(
 		zoom: f32,
 		dimension: Vec2,
@@ -24,18 +74,266 @@ impl Camera {
 	) -> Self {
 		Self {
 			zoom,
+			target_zoom: zoom,
+			min_zoom: 0.01,
+			max_zoom: 100.0,
+			zoom_speed: 8.0,
+			rotation: 0.0,
+			pixels_per_unit: 1.0,
 			dimension,
-			position
+			virtual_resolution: None,
+			scale_factor: 1.0,
+			position,
+			render_layers: RenderLayers::DEFAULT,
+			clear_mode: ClearMode::Background,
+			trauma: 0.0,
+			shake_intensity: 1.0,
+			shake_time: 0.0,
+			shake_offset: Vec2::new(0.0, 0.0)
+		}
+	}
+
+	pub fn clear_mode(&self) -> &ClearMode {
+		&self.clear_mode
+	}
+
+	pub fn set_clear_mode(&mut self, clear_mode: ClearMode) {
+		self.clear_mode = clear_mode;
+	}
+
+	/// The layers of the entities this camera draws.
+	pub fn render_layers(&self) -> RenderLayers {
+		self.render_layers
+	}
+
+	pub fn set_render_layers(&mut self, render_layers: RenderLayers) {
+		self.render_layers = render_layers;
+	}
+
+	pub fn zoom(&self) -> f32 {
+		self.zoom
+	}
+
+	/// Jumps to the zoom level, limited by the minimum and maximum zoom.
+	pub fn set_zoom(&mut self, zoom: f32) {
+		self.zoom = zoom.clamp(self.min_zoom, self.max_zoom);
+		self.target_zoom = self.zoom;
+	}
+
+	/// Zooms smoothly towards the zoom level over the next `update` calls.
+	pub fn zoom_to(&mut self, zoom: f32) {
+		self.target_zoom = zoom.clamp(self.min_zoom, self.max_zoom);
+	}
+
+	pub fn set_zoom_limits(&mut self, min_zoom: f32, max_zoom: f32) {
+		self.min_zoom = min_zoom;
+		self.max_zoom = max_zoom.max(min_zoom);
+		self.zoom = self.zoom.clamp(self.min_zoom, self.max_zoom);
+		self.target_zoom = self.target_zoom.clamp(self.min_zoom, self.max_zoom);
+	}
+
+	/// How fast `zoom_to` approaches its target, higher is snappier.
+	pub fn set_zoom_speed(&mut self, zoom_speed: f32) {
+		self.zoom_speed = zoom_speed.max(0.0);
+	}
+
+	/// The angle in radians the camera is turned by counterclockwise, which turns the world on screen clockwise.
+	pub fn rotation(&self) -> f32 {
+		self.rotation
+	}
+
+	pub fn set_rotation(&mut self, rotation: f32) {
+		self.rotation = rotation.rem_euclid(std::f32::consts::TAU);
+	}
+
+	/// Turns the camera further by `angle` radians, e.g. every frame for a slowly tilting screen.
+	pub fn rotate(&mut self, angle: f32) {
+		self.set_rotation(self.rotation + angle);
+	}
+
+	/// How many pixels of a texture or of the window one world unit spans at a zoom of 1.
+	/// `Transform2D`s, physics and the camera all work in world units, so with 32 a 32 pixel sprite is one unit large.
+	/// It is 1 by default, which makes world units pixels.
+	pub fn pixels_per_unit(&self) -> f32 {
+		self.pixels_per_unit
+	}
+
+	pub fn set_pixels_per_unit(&mut self, pixels_per_unit: f32) {
+		self.pixels_per_unit = pixels_per_unit.max(f32::EPSILON);
+	}
+
+	/// The size in world units of something `pixels` large, e.g. the dimensions of a texture.
+	pub fn pixels_to_units(&self, pixels: Vec2) -> Vec2 {
+		pixels * (1.0 / self.pixels_per_unit)
+	}
+
+	pub fn units_to_pixels(&self, units: Vec2) -> Vec2 {
+		units * self.pixels_per_unit
+	}
+
+	/// The size of the view in pixels.
+	pub fn dimension(&self) -> Vec2 {
+		self.dimension
+	}
+
+	/// Called by the renderer whenever the window is resized, so a pixel of a texture stays a pixel on screen.
+	pub fn set_dimension(&mut self, dimension: Vec2) {
+		self.dimension = dimension;
+	}
+
+	/// Makes the camera show `resolution` pixels of the world at a zoom of 1 whatever the size or DPI of the window,
+	/// scaled by `policy`, so one layout works across window sizes. `None` shows one pixel per pixel of the window.
+	pub fn set_virtual_resolution(&mut self, resolution: Option<(Vec2, ScalePolicy)>) {
+		self.virtual_resolution = resolution.filter(|(size, _)| size.x() > 0.0 && size.y() > 0.0);
+	}
+
+	pub fn virtual_resolution(&self) -> Option<(Vec2, ScalePolicy)> {
+		self.virtual_resolution
+	}
+
+	/// How many physical pixels of the window one logical pixel spans, e.g. 2 on a display set to 200%.
+	pub fn scale_factor(&self) -> f32 {
+		self.scale_factor
+	}
+
+	/// Called by the renderer whenever the window moves to a display with another scale factor.
+	pub fn set_scale_factor(&mut self, scale_factor: f32) {
+		self.scale_factor = if scale_factor > 0.0 { scale_factor } else { 1.0 };
+	}
+
+	/// How many pixels of the window one pixel of the virtual resolution spans. Without one it is the scale factor
+	/// of the window, so sprites keep their size on high DPI displays instead of shrinking.
+	/// Text and interface elements laid out in virtual pixels are scaled by it.
+	pub fn virtual_scale(&self) -> f32 {
+		let Some((resolution, policy)) = self.virtual_resolution else {
+			return self.scale_factor;
+		};
+		let (x, y) = (self.dimension.x() / resolution.x(), self.dimension.y() / resolution.y());
+		let scale = match policy {
+			ScalePolicy::Fit => x.min(y),
+			ScalePolicy::Fill => x.max(y),
+			ScalePolicy::MatchWidth => x,
+			ScalePolicy::MatchHeight => y
+		};
+		if scale > 0.0 { scale } else { 1.0 }
+	}
+
+	/// The size of the view in world units, before rotating it.
+	pub fn view_size(&self) -> Vec2 {
+		self.dimension * (1.0 / (self.virtual_scale() * self.zoom * self.pixels_per_unit))
+	}
+
+	pub fn position(&self) -> Vec3 {
+		self.position
+	}
+
+	pub fn set_position(&mut self, position: Vec3) {
+		self.position = position;
+	}
+
+	/// Shakes the camera by adding `trauma`, from 0.0 to 1.0, e.g. 0.3 for a hit and 1.0 for an explosion.
+	/// The shake grows with the square of the trauma, so small hits stay subtle, and wears off within a second.
+	pub fn shake(&mut self, trauma: f32) {
+		self.trauma = (self.trauma + trauma.max(0.0)).min(1.0);
+	}
+
+	pub fn trauma(&self) -> f32 {
+		self.trauma
+	}
+
+	pub fn shake_intensity(&self) -> f32 {
+		self.shake_intensity
+	}
+
+	/// Scales how far the camera shakes, 0.0 turns shaking off. Usually set through `Accessibility`.
+	pub fn set_shake_intensity(&mut self, shake_intensity: f32) {
+		self.shake_intensity = shake_intensity.max(0.0);
+	}
+
+	/// The center of the view, which is the position moved by the camera shake.
+	fn view_center(&self) -> Vec2 {
+		Vec2::new(self.position.x() + self.shake_offset.x(), self.position.y() + self.shake_offset.y())
+	}
+
+	/// The corners of the part of the world the camera currently shows, counterclockwise from the bottom left one.
+	pub fn visible_world_corners(&self) -> [Vec2; 4] {
+		let (half_width, half_height) = (self.view_size().x() / 2.0, self.view_size().y() / 2.0);
+		let center = self.view_center();
+		let (sin, cos) = self.rotation.sin_cos();
+		[(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].map(|(x, y): (f32, f32)| {
+			let (x, y) = (x * half_width, y * half_height);
+			Vec2::new(center.x() + x * cos - y * sin, center.y() + x * sin + y * cos)
+		})
+	}
+
+	/// The part of the world the camera currently shows, or the smallest rectangle around it while the camera is rotated.
+	pub fn visible_world_rect(&self) -> Rectangle2D {
+		let corners = self.visible_world_corners();
+		let (mut min, mut max) = (corners[0], corners[0]);
+		for corner in &corners[1..] {
+			min = Vec2::new(min.x().min(corner.x()), min.y().min(corner.y()));
+			max = Vec2::new(max.x().max(corner.x()), max.y().max(corner.y()));
 		}
+		Rectangle2D::new(Position2D::from_vec(min), max - min)
+	}
+
+	/// Centers the camera on `rect` and zooms so all of it is visible while the camera is not rotated.
+	pub fn fit_rect(&mut self, rect: &Rectangle2D) {
+		let size = rect.size();
+		self.position = Vec3::new(rect.position().x() + size.x() / 2.0, rect.position().y() + size.y() / 2.0, self.position.z());
+		if size.x() > 0.0 && size.y() > 0.0 {
+			let view = self.dimension * (1.0 / (self.virtual_scale() * self.pixels_per_unit));
+			self.set_zoom((view.x() / size.x()).min(view.y() / size.y()));
+		}
+	}
+
+	/// Moves the zoom towards the level given to `zoom_to` and lets the camera shake wear off.
+	pub fn update(&mut self, dt: f32) {
+		self.zoom += (self.target_zoom - self.zoom) * (1.0 - (-self.zoom_speed * dt).exp());
+
+		self.trauma = (self.trauma - SHAKE_DECAY * dt).max(0.0);
+		self.shake_time += dt;
+		let amount = self.trauma * self.trauma * self.shake_intensity * SHAKE_OFFSET * self.view_size().y();
+		let t = self.shake_time * SHAKE_FREQUENCY;
+		// two sines at odd ratios per axis, which wobble without an obvious rhythm
+		let wobble = |seed: f32| (t + seed).sin() * 0.6 + (t * 2.31 + seed * 3.7).sin() * 0.4;
+		self.shake_offset = Vec2::new(amount * wobble(0.0), amount * wobble(1.9));
 	}
 
 	pub fn build_view_projection_matrix(&self) -> cgmath::Matrix4<f32> {
-		OPENGL_TO_WGPU_MATRIX * cgmath::ortho(self.position.x() - self.dimension.x() / 2.0,
-											  self.position.x() + self.dimension.x() / 2.0,
-											  self.position.y() - self.dimension.y() / 2.0,
-											  self.position.y() + self.dimension.y() / 2.0,
-											  1.0,
-											  0.0)
+		let (half_width, half_height) = (self.view_size().x() / 2.0, self.view_size().y() / 2.0);
+		let center = self.view_center();
+		let view = cgmath::Matrix4::from_angle_z(cgmath::Rad(-self.rotation))
+			* cgmath::Matrix4::from_translation(cgmath::Vector3::new(-center.x(), -center.y(), 0.0));
+		OPENGL_TO_WGPU_MATRIX * cgmath::ortho(-half_width, half_width, -half_height, half_height, 1.0, 0.0) * view
+	}
+
+	/// Where a point in the world ends up on the screen, in pixels from the top left corner of the window.
+	pub fn world_to_screen(&self, point: Vec2, window_size: Vec2) -> Vec2 {
+		let clip = self.build_view_projection_matrix() * cgmath::Vector4::new(point.x(), point.y(), 0.0, 1.0);
+		let ndc = Vec2::new(clip.x / clip.w, clip.y / clip.w);
+		Vec2::new(
+			(ndc.x() + 1.0) * 0.5 * window_size.x(),
+			(1.0 - ndc.y()) * 0.5 * window_size.y()
+		)
+	}
+
+	/// The point in the world under a position on the screen, e.g. the mouse cursor.
+	pub fn screen_to_world(&self, point: Vec2, window_size: Vec2) -> Vec2 {
+		use cgmath::SquareMatrix;
+		let ndc = cgmath::Vector4::new(
+			point.x() / window_size.x() * 2.0 - 1.0,
+			1.0 - point.y() / window_size.y() * 2.0,
+			0.0,
+			1.0
+		);
+		match self.build_view_projection_matrix().invert() {
+			Some(inverse) => {
+				let world = inverse * ndc;
+				Vec2::new(world.x / world.w, world.y / world.w)
+			}
+			None => self.view_center()
+		}
 	}
 }
 
@@ -44,6 +342,7 @@ impl Camera {
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct CameraUniform {
 	view_proj: [[f32; 4]; 4],
+	time: [f32; 4],
 }
 
 impl CameraUniform {
@@ -51,12 +350,18 @@ impl CameraUniform {
 		use cgmath::SquareMatrix;
 		Self {
 			view_proj: cgmath::Matrix4::identity().into(),
+			time: [0.0; 4],
 		}
 	}
 
 	pub fn update_view_proj(&mut self, camera: &Camera) {
 		self.view_proj = camera.build_view_projection_matrix().into();
 	}
+
+	/// The seconds since the renderer started, which scrolling textures move by.
+	pub fn set_time(&mut self, time: f32) {
+		self.time[0] = time;
+	}
 }
 /*use comet_math::{Mat4, Point3, Vec3};
 
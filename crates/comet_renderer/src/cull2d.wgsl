@@ -0,0 +1,51 @@
+// Culls the quads of the sprite batch against the view, writing one indirect draw per quad
+struct CullParams {
+    view_min: vec2<f32>,
+    view_max: vec2<f32>,
+    first_index: u32,
+    quad_count: u32,
+}
+@group(0) @binding(0)
+var<uniform> params: CullParams;
+
+// the vertex buffer of the batch, read as floats since its packed layout does not match the alignment of wgsl vectors
+@group(0) @binding(1)
+var<storage, read> vertices: array<f32>;
+@group(0) @binding(2)
+var<storage, read> indices: array<u32>;
+
+struct DrawIndexedIndirect {
+    index_count: u32,
+    instance_count: u32,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+}
+@group(0) @binding(3)
+var<storage, read_write> commands: array<DrawIndexedIndirect>;
+
+// position, texture coordinates, color, flash, region and scroll
+const VERTEX_FLOATS: u32 = 26u;
+const QUAD_INDICES: u32 = 6u;
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let quad = id.x;
+    if (quad >= params.quad_count) {
+        return;
+    }
+
+    let first_index = params.first_index + quad * QUAD_INDICES;
+    var bounds_min = vec2<f32>(3.4e38);
+    var bounds_max = vec2<f32>(-3.4e38);
+    for (var i = 0u; i < QUAD_INDICES; i++) {
+        let vertex = indices[first_index + i] * VERTEX_FLOATS;
+        let position = vec2<f32>(vertices[vertex], vertices[vertex + 1u]);
+        bounds_min = min(bounds_min, position);
+        bounds_max = max(bounds_max, position);
+    }
+
+    let visible = all(bounds_max >= params.view_min) && all(bounds_min <= params.view_max);
+    // culled quads keep their command with no indices, so the draw order stays the same
+    commands[quad] = DrawIndexedIndirect(select(0u, QUAD_INDICES, visible), 1u, first_index, 0, 0u);
+}
@@ -0,0 +1,157 @@
+use std::ops::Range;
+use comet_math::Vec2;
+use crate::dynamic_buffer::DynamicBuffer;
+
+/// The indices of a quad in the sprite batch.
+const QUAD_INDICES: u32 = 6;
+const WORKGROUP_SIZE: u32 = 64;
+/// The size of a `DrawIndexedIndirect` command in bytes.
+const COMMAND_SIZE: u64 = 20;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct CullParams {
+	view_min: [f32; 2],
+	view_max: [f32; 2],
+	first_index: u32,
+	quad_count: u32
+}
+
+/// Culls the quads of the sprite batch against the view in a compute pass.
+/// It writes one indirect draw per quad, which are all drawn with a single `multi_draw_indexed_indirect`.
+pub(crate) struct GpuCulling {
+	pipeline: wgpu::ComputePipeline,
+	bind_group_layout: wgpu::BindGroupLayout,
+	params: wgpu::Buffer,
+	commands: DynamicBuffer,
+	quad_count: u32
+}
+
+impl GpuCulling {
+	/// The features the device has to be created with.
+	pub const FEATURES: wgpu::Features = wgpu::Features::MULTI_DRAW_INDIRECT;
+
+	/// Whether `adapter` can run the culling pass and draw its output.
+	pub fn is_supported(adapter: &wgpu::Adapter) -> bool {
+		let flags = adapter.get_downlevel_capabilities().flags;
+		adapter.features().contains(Self::FEATURES)
+			&& flags.contains(wgpu::DownlevelFlags::COMPUTE_SHADERS | wgpu::DownlevelFlags::INDIRECT_EXECUTION)
+	}
+
+	pub fn new(device: &wgpu::Device) -> Self {
+		let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+			label: Some("Cull Shader"),
+			source: wgpu::ShaderSource::Wgsl(include_str!("cull2d.wgsl").into()),
+		});
+
+		let storage = |binding, read_only| wgpu::BindGroupLayoutEntry {
+			binding,
+			visibility: wgpu::ShaderStages::COMPUTE,
+			ty: wgpu::BindingType::Buffer {
+				ty: wgpu::BufferBindingType::Storage { read_only },
+				has_dynamic_offset: false,
+				min_binding_size: None,
+			},
+			count: None,
+		};
+		let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+			label: Some("Cull Bind Group Layout"),
+			entries: &[
+				wgpu::BindGroupLayoutEntry {
+					binding: 0,
+					visibility: wgpu::ShaderStages::COMPUTE,
+					ty: wgpu::BindingType::Buffer {
+						ty: wgpu::BufferBindingType::Uniform,
+						has_dynamic_offset: false,
+						min_binding_size: None,
+					},
+					count: None,
+				},
+				storage(1, true),
+				storage(2, true),
+				storage(3, false)
+			],
+		});
+
+		let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+			label: Some("Cull Pipeline Layout"),
+			bind_group_layouts: &[&bind_group_layout],
+			push_constant_ranges: &[],
+		});
+
+		let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+			label: Some("Cull Pipeline"),
+			layout: Some(&pipeline_layout),
+			module: &shader,
+			entry_point: "cs_main",
+			compilation_options: Default::default(),
+			cache: None,
+		});
+
+		let params = device.create_buffer(&wgpu::BufferDescriptor {
+			label: Some("Cull Params Buffer"),
+			size: std::mem::size_of::<CullParams>() as u64,
+			usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+			mapped_at_creation: false,
+		});
+
+		Self {
+			pipeline,
+			bind_group_layout,
+			params,
+			commands: DynamicBuffer::new(device, "Indirect Draw Buffer", wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::INDIRECT),
+			quad_count: 0
+		}
+	}
+
+	/// Records the culling of the quads in `quads`, a range of the index buffer, against the rectangle from `view.0` to `view.1`.
+	/// `batch` holds the vertex and the index buffer of the sprite batch.
+	/// Returns false without recording anything if the range is not made of whole quads or the batch is too big to cull in one dispatch.
+	pub fn cull(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, encoder: &mut wgpu::CommandEncoder, batch: (&wgpu::Buffer, &wgpu::Buffer), quads: Range<u32>, view: (Vec2, Vec2)) -> bool {
+		let (vertices, indices) = batch;
+		let (view_min, view_max) = view;
+		let first_index = quads.start;
+		let index_count = quads.end.saturating_sub(quads.start);
+		let workgroups = (index_count / QUAD_INDICES).div_ceil(WORKGROUP_SIZE);
+		let limits = device.limits();
+		let too_big = workgroups > limits.max_compute_workgroups_per_dimension
+			|| vertices.size().max(indices.size()) > limits.max_storage_buffer_binding_size as u64;
+		if index_count == 0 || !index_count.is_multiple_of(QUAD_INDICES) || too_big {
+			return false;
+		}
+		self.quad_count = index_count / QUAD_INDICES;
+
+		queue.write_buffer(&self.params, 0, bytemuck::cast_slice(&[CullParams {
+			view_min: [view_min.x(), view_min.y()],
+			view_max: [view_max.x(), view_max.y()],
+			first_index,
+			quad_count: self.quad_count
+		}]));
+		self.commands.reserve(device, self.quad_count as u64 * COMMAND_SIZE);
+
+		let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+			label: Some("Cull Bind Group"),
+			layout: &self.bind_group_layout,
+			entries: &[
+				wgpu::BindGroupEntry { binding: 0, resource: self.params.as_entire_binding() },
+				wgpu::BindGroupEntry { binding: 1, resource: vertices.as_entire_binding() },
+				wgpu::BindGroupEntry { binding: 2, resource: indices.as_entire_binding() },
+				wgpu::BindGroupEntry { binding: 3, resource: self.commands.buffer().as_entire_binding() }
+			],
+		});
+
+		let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+			label: Some("Cull Pass"),
+			timestamp_writes: None,
+		});
+		compute_pass.set_pipeline(&self.pipeline);
+		compute_pass.set_bind_group(0, &bind_group, &[]);
+		compute_pass.dispatch_workgroups(workgroups, 1, 1);
+		true
+	}
+
+	/// Draws the quads that survived the last `cull` with the sprite pipeline and buffers already set.
+	pub fn draw(&self, render_pass: &mut wgpu::RenderPass) {
+		render_pass.multi_draw_indexed_indirect(self.commands.buffer(), 0, self.quad_count);
+	}
+}
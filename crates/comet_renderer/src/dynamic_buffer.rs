@@ -0,0 +1,73 @@
+/// A GPU buffer that is rewritten every frame without being recreated.
+/// It only reallocates when the data outgrows it, doubling its capacity so a growing scene settles quickly.
+pub(crate) struct DynamicBuffer {
+	label: &'static str,
+	usage: wgpu::BufferUsages,
+	buffer: wgpu::Buffer,
+	len: u64
+}
+
+impl DynamicBuffer {
+	const MIN_CAPACITY: u64 = 1024;
+
+	pub fn new(device: &wgpu::Device, label: &'static str, usage: wgpu::BufferUsages) -> Self {
+		let usage = usage | wgpu::BufferUsages::COPY_DST;
+		Self {
+			label,
+			usage,
+			buffer: Self::allocate(device, label, usage, Self::MIN_CAPACITY),
+			len: 0
+		}
+	}
+
+	fn allocate(device: &wgpu::Device, label: &'static str, usage: wgpu::BufferUsages, capacity: u64) -> wgpu::Buffer {
+		device.create_buffer(&wgpu::BufferDescriptor {
+			label: Some(label),
+			size: capacity,
+			usage,
+			mapped_at_creation: false,
+		})
+	}
+
+	/// Uploads `data`, replacing what was written before.
+	pub fn write(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, data: &[u8]) {
+		// copies have to be a multiple of four bytes
+		let len = (data.len() as u64).next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT);
+		if len > self.buffer.size() {
+			self.buffer = Self::allocate(device, self.label, self.usage, len.next_power_of_two());
+		}
+
+		if len as usize == data.len() {
+			queue.write_buffer(&self.buffer, 0, data);
+		}
+		else {
+			let mut padded = data.to_vec();
+			padded.resize(len as usize, 0);
+			queue.write_buffer(&self.buffer, 0, &padded);
+		}
+		self.len = data.len() as u64;
+	}
+
+	/// Makes room for `len` bytes that are written on the GPU, e.g. by a compute pass, without uploading anything.
+	pub fn reserve(&mut self, device: &wgpu::Device, len: u64) {
+		let len = len.next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT);
+		if len > self.buffer.size() {
+			self.buffer = Self::allocate(device, self.label, self.usage, len.next_power_of_two());
+		}
+		self.len = len;
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// The whole buffer, e.g. to bind it as storage. It is replaced when a write outgrows it.
+	pub fn buffer(&self) -> &wgpu::Buffer {
+		&self.buffer
+	}
+
+	/// The part of the buffer holding the data of the last `write`.
+	pub fn slice(&self) -> wgpu::BufferSlice<'_> {
+		self.buffer.slice(..self.len)
+	}
+}
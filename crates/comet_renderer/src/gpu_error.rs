@@ -0,0 +1,148 @@
+use std::fmt;
+use comet_log::*;
+
+/// What kind of error wgpu reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuErrorKind {
+	/// A bug in a pass, pipeline, shader or buffer the engine or the game set up.
+	Validation,
+	OutOfMemory,
+	Internal
+}
+
+/// A wgpu error broken down for the log: what went wrong and the labels of the passes, pipelines and
+/// other objects wgpu names in its description, e.g. "Render Pass" or "Render Pipeline".
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpuError {
+	kind: GpuErrorKind,
+	/// Where the error was caught, e.g. the operation an error scope wrapped.
+	scope: String,
+	labels: Vec<String>,
+	lines: Vec<String>
+}
+
+impl GpuError {
+	pub fn from_wgpu(scope: &str, error: &wgpu::Error) -> Self {
+		let (kind, description) = match error {
+			wgpu::Error::Validation { description, .. } => (GpuErrorKind::Validation, description.clone()),
+			wgpu::Error::OutOfMemory { source } => (GpuErrorKind::OutOfMemory, source.to_string()),
+			wgpu::Error::Internal { description, .. } => (GpuErrorKind::Internal, description.clone())
+		};
+		let lines: Vec<String> = description.lines()
+			.map(str::trim)
+			.filter(|line| !line.is_empty() && *line != "Caused by:")
+			.map(str::to_string)
+			.collect();
+		let mut labels = Vec::new();
+		for line in &lines {
+			for label in labels_in(line) {
+				if !labels.contains(&label) {
+					labels.push(label);
+				}
+			}
+		}
+
+		Self {
+			kind,
+			scope: scope.to_string(),
+			labels,
+			lines
+		}
+	}
+
+	pub fn kind(&self) -> GpuErrorKind {
+		self.kind
+	}
+
+	pub fn scope(&self) -> &str {
+		&self.scope
+	}
+
+	pub fn labels(&self) -> &[String] {
+		&self.labels
+	}
+
+	/// The description of wgpu, one cause per line from the outermost.
+	pub fn lines(&self) -> &[String] {
+		&self.lines
+	}
+
+	/// Logs the error as a headline naming the labels, followed by one line per cause.
+	pub fn log(&self) {
+		error!("{}", self);
+		for line in &self.lines {
+			error!("    {}", line);
+		}
+	}
+}
+
+impl fmt::Display for GpuError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let kind = match self.kind {
+			GpuErrorKind::Validation => "validation error",
+			GpuErrorKind::OutOfMemory => "out of memory",
+			GpuErrorKind::Internal => "internal error"
+		};
+		write!(f, "GPU {} in {}", kind, self.scope)?;
+		if !self.labels.is_empty() {
+			write!(f, " ({})", self.labels.join(", "))?;
+		}
+		Ok(())
+	}
+}
+
+impl std::error::Error for GpuError {}
+
+/// The labels on a line of a wgpu description, written as `label = 'Name'` or as `note: pipeline = `Name``.
+fn labels_in(line: &str) -> Vec<String> {
+	let mut labels = Vec::new();
+	if let Some((_, rest)) = line.split_once("label = '") {
+		if let Some((label, _)) = rest.split_once('\'') {
+			labels.push(label.to_string());
+		}
+	}
+	if let Some(note) = line.strip_prefix("note: ") {
+		if let Some((_, rest)) = note.split_once("= `") {
+			if let Some((label, _)) = rest.split_once('`') {
+				// unlabelled objects are written as their id, e.g. `<RenderPipeline-(0, 1, Vulkan)>`
+				if !label.starts_with('<') && !label.is_empty() {
+					labels.push(label.to_string());
+				}
+			}
+		}
+	}
+	labels
+}
+
+/// Logs the errors wgpu does not hand back through an error scope, instead of panicking on the first one.
+/// The same error is logged at most once a second, as a broken pipeline fails again every frame.
+pub(crate) fn log_uncaptured_errors(device: &wgpu::Device) {
+	device.on_uncaptured_error(Box::new(|error| {
+		static THROTTLE: throttle::Throttle = throttle::Throttle::new();
+		if THROTTLE.pass(Some(std::time::Duration::from_secs(1))).is_some() {
+			GpuError::from_wgpu("an uncaptured operation", &error).log();
+		}
+	}));
+}
+
+/// Runs `operation` inside wgpu error scopes and returns the first error it caused, already logged, e.g. around
+/// creating a pipeline from a shader of the game. `scope` names the operation in the log.
+pub fn catch_gpu_errors<T>(device: &wgpu::Device, scope: &str, operation: impl FnOnce() -> T) -> (T, Option<GpuError>) {
+	device.push_error_scope(wgpu::ErrorFilter::Internal);
+	device.push_error_scope(wgpu::ErrorFilter::OutOfMemory);
+	device.push_error_scope(wgpu::ErrorFilter::Validation);
+	let value = operation();
+	let errors = [
+		pollster::block_on(device.pop_error_scope()),
+		pollster::block_on(device.pop_error_scope()),
+		pollster::block_on(device.pop_error_scope())
+	];
+
+	let mut first = None;
+	for error in errors.into_iter().flatten() {
+		let error = GpuError::from_wgpu(scope, &error);
+		error.log();
+		first.get_or_insert(error);
+	}
+	(value, first)
+}
@@ -0,0 +1,58 @@
+use comet_structs::{new_key_type, SlotMap};
+
+new_key_type! {
+	/// Identifies a buffer added with `Renderer2D::add_buffer`.
+	pub struct BufferKey;
+}
+
+new_key_type! {
+	/// Identifies a bind group added with `Renderer2D::add_bind_group`.
+	pub struct BindGroupKey;
+}
+
+/// The buffers and bind groups of custom draws, kept by the renderer and handed to the draws while they are recorded.
+/// The keys stay valid until the resource is removed, and never point at another resource after that.
+pub struct GpuResources {
+	buffers: SlotMap<BufferKey, wgpu::Buffer>,
+	bind_groups: SlotMap<BindGroupKey, wgpu::BindGroup>
+}
+
+impl Default for GpuResources {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl GpuResources {
+	pub fn new() -> Self {
+		Self {
+			buffers: SlotMap::new(),
+			bind_groups: SlotMap::new()
+		}
+	}
+
+	pub fn add_buffer(&mut self, buffer: wgpu::Buffer) -> BufferKey {
+		self.buffers.insert(buffer)
+	}
+
+	pub fn buffer(&self, key: BufferKey) -> Option<&wgpu::Buffer> {
+		self.buffers.get(key)
+	}
+
+	/// Removes the buffer. It is freed once no bind group uses it anymore.
+	pub fn remove_buffer(&mut self, key: BufferKey) -> Option<wgpu::Buffer> {
+		self.buffers.remove(key)
+	}
+
+	pub fn add_bind_group(&mut self, bind_group: wgpu::BindGroup) -> BindGroupKey {
+		self.bind_groups.insert(bind_group)
+	}
+
+	pub fn bind_group(&self, key: BindGroupKey) -> Option<&wgpu::BindGroup> {
+		self.bind_groups.get(key)
+	}
+
+	pub fn remove_bind_group(&mut self, key: BindGroupKey) -> Option<wgpu::BindGroup> {
+		self.bind_groups.remove(key)
+	}
+}
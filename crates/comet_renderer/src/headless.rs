@@ -0,0 +1,102 @@
+use std::sync::Arc;
+use winit::dpi::PhysicalSize;
+use winit::window::Window;
+use comet_colors::LinearRgba;
+use comet_math::{Vec2, Vec3};
+use crate::accessibility::Accessibility;
+use crate::render_pass::PassHandle;
+use crate::camera::Camera;
+use crate::renderer::{AdapterInfo, CustomDraw, Renderer, RendererSettings};
+
+/// A renderer that draws nothing and needs neither a window nor a GPU.
+/// Used to run the app loop in tests and on servers.
+pub struct HeadlessRenderer {
+	size: PhysicalSize<u32>,
+	camera: Camera,
+	deltatime: f32,
+	global_tint: LinearRgba
+}
+
+impl HeadlessRenderer {
+	/// A renderer pretending to draw into a window of `width` x `height` pixels, advancing `deltatime` seconds per frame.
+	pub fn headless(width: u32, height: u32, deltatime: f32) -> Self {
+		Self {
+			size: PhysicalSize::new(width, height),
+			camera: Camera::new(1.0, Vec2::new(width as f32, height as f32), Vec3::new(0.0, 0.0, 0.0)),
+			deltatime,
+			global_tint: LinearRgba::new(1.0, 1.0, 1.0, 1.0)
+		}
+	}
+
+	pub fn camera(&self) -> &Camera {
+		&self.camera
+	}
+
+	pub fn camera_mut(&mut self) -> &mut Camera {
+		&mut self.camera
+	}
+
+	/// The tint last set through `Renderer::set_global_tint`, for tests to check.
+	pub fn global_tint(&self) -> &LinearRgba {
+		&self.global_tint
+	}
+
+	fn window_size(&self) -> Vec2 {
+		Vec2::new(self.size.width as f32, self.size.height as f32)
+	}
+}
+
+impl Renderer for HeadlessRenderer {
+	async fn new(window: Arc<Window>, _settings: RendererSettings) -> Self {
+		let size = window.inner_size();
+		Self::headless(size.width, size.height, 1.0/60.0)
+	}
+
+	fn size(&self) -> PhysicalSize<u32> {
+		self.size
+	}
+
+	fn resize(&mut self, new_size: PhysicalSize<u32>) {
+		self.size = new_size;
+		self.camera.set_dimension(self.window_size());
+	}
+
+	fn update(&mut self) -> f32 {
+		self.camera.update(self.deltatime);
+		self.deltatime
+	}
+
+	fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+		Ok(())
+	}
+
+	fn queue_custom(&mut self, _pass: PassHandle, _callback: CustomDraw) {}
+
+	fn set_accessibility(&mut self, accessibility: &Accessibility) {
+		self.camera.set_shake_intensity(accessibility.shake_intensity());
+	}
+
+	fn set_global_tint(&mut self, tint: LinearRgba) {
+		self.global_tint = tint;
+	}
+
+	fn adapter_info(&self) -> AdapterInfo {
+		AdapterInfo {
+			name: "Headless".to_string(),
+			vendor: 0,
+			device: 0,
+			device_type: wgpu::DeviceType::Other,
+			driver: String::new(),
+			driver_info: String::new(),
+			backend: wgpu::Backend::Empty
+		}
+	}
+
+	fn world_to_screen(&self, point: Vec2) -> Vec2 {
+		self.camera.world_to_screen(point, self.window_size())
+	}
+
+	fn screen_to_world(&self, point: Vec2) -> Vec2 {
+		self.camera.screen_to_world(point, self.window_size())
+	}
+}
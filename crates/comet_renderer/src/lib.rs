@@ -1,10 +1,21 @@
 use comet_math::Mat4;
 
-mod camera;
+pub mod accessibility;
+pub mod camera;
+pub mod bloom;
+mod culling;
+mod dynamic_buffer;
+pub mod gpu_error;
+pub mod gpu_resources;
+pub mod headless;
+mod mask;
 pub mod renderer;
 pub mod renderer2d;
-mod render_pass;
+pub mod screen_flash;
+pub mod render_pass;
 mod render_group;
+pub mod tonemapping;
+mod video;
 
 pub struct Projection {
     aspect: f32,
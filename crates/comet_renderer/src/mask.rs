@@ -0,0 +1,117 @@
+use comet_resources::Vertex;
+use crate::dynamic_buffer::DynamicBuffer;
+
+/// Draws untextured, single colored triangles over the scene, e.g. visibility polygons.
+pub(crate) struct MaskPass {
+	pipeline: wgpu::RenderPipeline,
+	vertices: Vec<Vertex>,
+	buffer: DynamicBuffer
+}
+
+impl MaskPass {
+	pub fn new(device: &wgpu::Device, camera_bind_group_layout: &wgpu::BindGroupLayout, format: wgpu::TextureFormat) -> Self {
+		let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+			label: Some("Mask Shader"),
+			source: wgpu::ShaderSource::Wgsl(include_str!("mask2d.wgsl").into()),
+		});
+
+		let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+			label: Some("Mask Pipeline Layout"),
+			bind_group_layouts: &[camera_bind_group_layout],
+			push_constant_ranges: &[],
+		});
+
+		let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+			label: Some("Mask Pipeline"),
+			layout: Some(&pipeline_layout),
+			vertex: wgpu::VertexState {
+				module: &shader,
+				entry_point: "vs_main",
+				buffers: &[Vertex::desc()],
+				compilation_options: Default::default(),
+			},
+			fragment: Some(wgpu::FragmentState {
+				module: &shader,
+				entry_point: "fs_main",
+				targets: &[Some(wgpu::ColorTargetState {
+					format,
+					blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+					write_mask: wgpu::ColorWrites::ALL,
+				})],
+				compilation_options: Default::default(),
+			}),
+			// the triangles of a polygon can wind either way
+			primitive: wgpu::PrimitiveState::default(),
+			depth_stencil: None,
+			multisample: wgpu::MultisampleState::default(),
+			multiview: None,
+			cache: None,
+		});
+
+		Self {
+			pipeline,
+			vertices: Vec::new(),
+			buffer: DynamicBuffer::new(device, "Mask Buffer", wgpu::BufferUsages::VERTEX)
+		}
+	}
+
+	pub fn push_triangle(&mut self, corners: [[f32; 2]; 3], color: [f32; 4]) {
+		self.push_gradient_triangle(corners, [color; 3]);
+	}
+
+	/// A triangle blending between the colors of its corners.
+	pub fn push_gradient_triangle(&mut self, corners: [[f32; 2]; 3], colors: [[f32; 4]; 3]) {
+		for ([x, y], color) in corners.into_iter().zip(colors) {
+			self.vertices.push(Vertex::new([x, y, 0.0], [0.0, 0.0], color));
+		}
+	}
+
+	/// Draws everything pushed since the last frame over `view`.
+	pub fn render(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView, camera_bind_group: &wgpu::BindGroup) {
+		if !self.upload(device, queue) {
+			return;
+		}
+
+		let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+			label: Some("Mask Pass"),
+			color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+				view,
+				resolve_target: None,
+				ops: wgpu::Operations {
+					load: wgpu::LoadOp::Load,
+					store: wgpu::StoreOp::Store,
+				},
+			})],
+			depth_stencil_attachment: None,
+			occlusion_query_set: None,
+			timestamp_writes: None,
+		});
+
+		self.draw(&mut render_pass, camera_bind_group);
+		drop(render_pass);
+
+		self.vertices.clear();
+	}
+
+	/// Writes the pushed triangles to the GPU, returning whether there are any to draw.
+	pub fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> bool {
+		if self.vertices.is_empty() {
+			return false;
+		}
+		self.buffer.write(device, queue, bytemuck::cast_slice(&self.vertices));
+		true
+	}
+
+	/// Records the uploaded triangles into a pass that is already running, e.g. between two parts of the sprite batch.
+	/// The pipeline stays set afterwards.
+	pub fn draw(&self, render_pass: &mut wgpu::RenderPass, camera_bind_group: &wgpu::BindGroup) {
+		render_pass.set_pipeline(&self.pipeline);
+		render_pass.set_bind_group(0, camera_bind_group, &[]);
+		render_pass.set_vertex_buffer(0, self.buffer.slice());
+		render_pass.draw(0..self.vertices.len() as u32, 0..1);
+	}
+
+	pub fn clear(&mut self) {
+		self.vertices.clear();
+	}
+}
@@ -1,4 +1,10 @@
 use comet_resources::Vertex;
+use comet_structs::new_key_type;
+
+new_key_type! {
+	/// Identifies a render pass added with `Renderer2D::add_render_pass`.
+	pub struct RenderPassKey;
+}
 
 pub struct RenderPassInfo {
 	shader: &'static str,
@@ -26,4 +32,47 @@ impl RenderPassInfo {
 	pub fn index_buffer(&self) -> &Vec<u16> {
 		&self.index_buffer
 	}
-}
\ No newline at end of file
+}
+/// A pass of the renderer that custom draws can be queued into with `queue_custom`.
+/// Only the engine defines passes, so a custom draw always ends up in one of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PassHandle(&'static str);
+
+impl PassHandle {
+	/// Draws after the sprites into the scene target, in the format of `Renderer2D::scene_format`,
+	/// so it is tonemapped and post-processed together with the sprites.
+	pub const SCENE: PassHandle = PassHandle("scene");
+	/// Draws onto the window after tonemapping, in the format of the surface, e.g. for debug overlays.
+	pub const OVERLAY: PassHandle = PassHandle("overlay");
+
+	/// The handle of the pass called `name`, e.g. when it comes from a script or the console.
+	pub fn from_name(name: &str) -> Option<PassHandle> {
+		[Self::SCENE, Self::OVERLAY].into_iter().find(|pass| pass.0 == name)
+	}
+
+	pub fn name(&self) -> &'static str {
+		self.0
+	}
+}
+
+/// A bind group index of the sprite pipeline. Custom pipelines drawing in the scene pass
+/// can reuse the engine bind groups at these slots and put their own after them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BindingSlot(u32);
+
+impl BindingSlot {
+	/// The texture atlas of the sprites and its sampler.
+	pub const TEXTURE: BindingSlot = BindingSlot(0);
+	/// The view projection of the camera, see `Renderer2D::camera_bind_group`.
+	pub const CAMERA: BindingSlot = BindingSlot(1);
+
+	/// The `index`th slot after the ones of the engine, which custom bind groups can use freely.
+	pub const fn custom(index: u32) -> BindingSlot {
+		BindingSlot(Self::CAMERA.0 + 1 + index)
+	}
+
+	/// The index to pass to `set_bind_group` and to use as `@group` in shaders.
+	pub const fn index(&self) -> u32 {
+		self.0
+	}
+}
@@ -1,12 +1,287 @@
+use std::fmt;
 use std::sync::Arc;
 use winit::dpi::PhysicalSize;
 use winit::window::Window;
 use comet_colors::LinearRgba;
+use comet_ecs::World;
+use comet_math::Vec2;
+use crate::accessibility::Accessibility;
+use crate::gpu_resources::GpuResources;
+use crate::camera::ScalePolicy;
+use crate::render_pass::PassHandle;
+
+pub use wgpu::{AdapterInfo, Backends, PowerPreference};
+
+/// Draw commands from user code, recorded into a render pass of the renderer.
+/// The buffers and bind groups added to the renderer are looked up by their keys in the `GpuResources`.
+pub type CustomDraw = Box<dyn for<'a> FnOnce(&mut wgpu::RenderPass<'a>, &'a GpuResources)>;
+
+/// How a renderer sets itself up, filled in through the builder of the `App`.
+#[derive(Debug, Clone)]
+pub struct RendererSettings {
+	clear_color: Option<LinearRgba>,
+	power_preference: PowerPreference,
+	backends: Backends,
+	srgb_surface: bool,
+	gpu_culling: bool,
+	virtual_resolution: Option<(u32, u32)>,
+	scale_policy: ScalePolicy,
+	batch_budget: u32
+}
+
+impl RendererSettings {
+	pub fn new() -> Self {
+		Self {
+			clear_color: None,
+			power_preference: PowerPreference::default(),
+			backends: Backends::PRIMARY,
+			srgb_surface: true,
+			gpu_culling: false,
+			virtual_resolution: None,
+			scale_policy: ScalePolicy::Fit,
+			batch_budget: 65536
+		}
+	}
+
+	pub fn with_clear_color(mut self, clear_color: LinearRgba) -> Self {
+		self.clear_color = Some(clear_color);
+		self
+	}
+
+	/// Whether to prefer the integrated (`LowPower`) or the dedicated (`HighPerformance`) GPU.
+	pub fn with_power_preference(mut self, power_preference: PowerPreference) -> Self {
+		self.power_preference = power_preference;
+		self
+	}
+
+	/// The graphics APIs to look for adapters in, e.g. `Backends::VULKAN` to rule out a broken DX12 driver.
+	pub fn with_backends(mut self, backends: Backends) -> Self {
+		self.backends = backends;
+		self
+	}
+
+	/// Whether the surface should be in an sRGB format, which converts the linear colors of the shaders on output.
+	/// Without it the shaders write their values to the screen as they are.
+	pub fn with_srgb_surface(mut self, srgb_surface: bool) -> Self {
+		self.srgb_surface = srgb_surface;
+		self
+	}
+
+	/// Culls the sprites against the camera in a compute pass and draws the visible ones indirectly, for scenes with a lot of sprites.
+	/// GPUs without support for it keep drawing the whole batch.
+	pub fn with_gpu_culling(mut self, gpu_culling: bool) -> Self {
+		self.gpu_culling = gpu_culling;
+		self
+	}
+
+	/// Lays the view out for a design resolution of `width` by `height` pixels, scaled onto the window by the
+	/// scale policy, see `Camera::set_virtual_resolution`.
+	pub fn with_virtual_resolution(mut self, width: u32, height: u32) -> Self {
+		self.virtual_resolution = Some((width, height));
+		self
+	}
+
+	/// How the virtual resolution is scaled onto the window, `ScalePolicy::Fit` by default.
+	pub fn with_scale_policy(mut self, scale_policy: ScalePolicy) -> Self {
+		self.scale_policy = scale_policy;
+		self
+	}
+
+	/// How many vertices a batch may have before the renderer warns about it, 65536 by default. See `RenderStats`.
+	pub fn with_batch_budget(mut self, vertices: u32) -> Self {
+		self.batch_budget = vertices;
+		self
+	}
+
+	pub fn clear_color(&self) -> Option<&LinearRgba> {
+		self.clear_color.as_ref()
+	}
+
+	pub fn power_preference(&self) -> PowerPreference {
+		self.power_preference
+	}
+
+	pub fn backends(&self) -> Backends {
+		self.backends
+	}
+
+	pub fn srgb_surface(&self) -> bool {
+		self.srgb_surface
+	}
+
+	pub fn gpu_culling(&self) -> bool {
+		self.gpu_culling
+	}
+
+	pub fn virtual_resolution(&self) -> Option<(u32, u32)> {
+		self.virtual_resolution
+	}
+
+	pub fn scale_policy(&self) -> ScalePolicy {
+		self.scale_policy
+	}
+
+	pub fn batch_budget(&self) -> u32 {
+		self.batch_budget
+	}
+}
+
+impl Default for RendererSettings {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// The size of the batch drawn in the last frame, to see how close a scene comes to the batch budget of the `RendererSettings`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderStats {
+	vertices: u32,
+	indices: u32,
+	batch_budget: u32
+}
+
+impl RenderStats {
+	pub fn new(vertices: u32, indices: u32, batch_budget: u32) -> Self {
+		Self {
+			vertices,
+			indices,
+			batch_budget
+		}
+	}
+
+	pub fn vertices(&self) -> u32 {
+		self.vertices
+	}
+
+	pub fn indices(&self) -> u32 {
+		self.indices
+	}
+
+	pub fn batch_budget(&self) -> u32 {
+		self.batch_budget
+	}
+
+	pub fn is_over_budget(&self) -> bool {
+		self.vertices > self.batch_budget
+	}
+}
+
+/// Which step of setting up the renderer failed.
+#[derive(Debug)]
+pub enum RendererError {
+	Surface(wgpu::CreateSurfaceError),
+	/// None of the GPUs works with the backends and the window.
+	NoAdapter { backends: Backends },
+	/// The adapter was found but would not hand out a device.
+	Device { adapter: String, error: wgpu::RequestDeviceError },
+	/// The surface has no texture format the adapter can draw to.
+	NoSurfaceFormat { adapter: String }
+}
+
+impl fmt::Display for RendererError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			RendererError::Surface(e) => write!(f, "Failed to create a surface for the window: {}", e),
+			RendererError::NoAdapter { backends } => write!(f, "No graphics adapter found for the backends {:?}", backends),
+			RendererError::Device { adapter, error } => write!(f, "Failed to request a device from {}: {}", adapter, error),
+			RendererError::NoSurfaceFormat { adapter } => write!(f, "{} cannot draw to the surface of the window", adapter)
+		}
+	}
+}
+
+impl std::error::Error for RendererError {}
+
+impl From<wgpu::CreateSurfaceError> for RendererError {
+	fn from(e: wgpu::CreateSurfaceError) -> Self {
+		RendererError::Surface(e)
+	}
+}
+
+/// What the GPU chosen for some `RendererSettings` can do, found before opening a window.
+#[derive(Debug, Clone)]
+pub struct SupportReport {
+	adapter_info: AdapterInfo,
+	missing: Vec<&'static str>
+}
+
+impl SupportReport {
+	pub fn new(adapter_info: AdapterInfo, missing: Vec<&'static str>) -> Self {
+		Self {
+			adapter_info,
+			missing
+		}
+	}
+
+	pub fn adapter_info(&self) -> &AdapterInfo {
+		&self.adapter_info
+	}
+
+	/// The features the settings ask for that the GPU lacks. The renderer still runs without them, but looks or performs worse.
+	pub fn missing(&self) -> &[&'static str] {
+		&self.missing
+	}
+
+	pub fn is_complete(&self) -> bool {
+		self.missing.is_empty()
+	}
+}
 
 pub trait Renderer: Sized {
-	async fn new(window: Arc<Window>, clear_color: Option<LinearRgba>) -> Self;
+	async fn new(window: Arc<Window>, settings: RendererSettings) -> Self;
+
+	/// Like `new`, but reports which step of the setup failed instead of panicking.
+	async fn try_new(window: Arc<Window>, settings: RendererSettings) -> Result<Self, RendererError> {
+		Ok(Self::new(window, settings).await)
+	}
 	fn size(&self) -> PhysicalSize<u32>;
 	fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>);
 	fn update(&mut self) -> f32;
+
+	/// Gathers what to draw from the world. The app calls it every frame right before `render`.
+	fn prepare(&mut self, _world: &World) {}
+
+	/// Submits everything prepared and queued for this frame.
 	fn render(&mut self) -> Result<(), wgpu::SurfaceError>;
+
+	/// Records `callback` into the pass called `pass` during the next `render`, e.g. for effects the renderer does not cover.
+	/// The callbacks run once in the order they were queued.
+	fn queue_custom(&mut self, pass: PassHandle, callback: CustomDraw);
+
+	/// The GPU and driver the renderer runs on, handy for bug reports about black screens.
+	fn adapter_info(&self) -> AdapterInfo;
+
+	/// Converts a point in the world into pixels from the top left corner of the window.
+	fn world_to_screen(&self, point: Vec2) -> Vec2;
+
+	/// Converts a position in the window, e.g. the mouse cursor, into a point in the world.
+	fn screen_to_world(&self, point: Vec2) -> Vec2;
+
+	/// The topmost entity drawn under a position in the window, e.g. the mouse cursor. Used for the pointer events of the app.
+	fn pick(&self, _screen_position: Vec2, _world: &World) -> Option<u32> {
+		None
+	}
+
+	/// Called by the app when the window moves to a display with another scale factor, e.g. from 100% to 200%.
+	fn set_scale_factor(&mut self, _scale_factor: f64) {}
+
+	/// Applies the accessibility options that concern drawing, called by the app whenever they change.
+	fn set_accessibility(&mut self, _accessibility: &Accessibility) {}
+
+	/// Multiplies the colors of the sprites in the world by `tint`, e.g. to darken the scene at night.
+	fn set_global_tint(&mut self, _tint: LinearRgba) {}
+
+	/// Called before every fixed update with the world as it is before it, to interpolate the drawn transforms from there.
+	fn store_previous_transforms(&mut self, _world: &World) {}
+
+	/// Called before every fixed update, dropping what was drawn immediately during the last one, e.g. with `submit_quad`.
+	fn clear_submitted(&mut self) {}
+
+	/// Called before drawing with how far the frame is between the last two fixed updates, from 0 to 1.
+	fn set_interpolation(&mut self, _alpha: f32) {}
+
+	/// Called when the app is sent to the background and its surface may be destroyed.
+	fn suspend(&mut self) {}
+
+	/// Called when the app comes back. Renderers should recreate their surface here.
+	fn resume(&mut self) {}
 }
\ No newline at end of file
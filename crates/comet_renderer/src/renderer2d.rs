@@ -1,3 +1,4 @@
+use std::collections::{HashMap, VecDeque};
 use std::iter;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -7,33 +8,62 @@ use wgpu::naga::ShaderStage;
 use wgpu::util::DeviceExt;
 use winit::dpi::PhysicalSize;
 use winit::window::Window;
-use comet_colors::LinearRgba;
-use comet_ecs::{Component, ComponentSet, Render, Render2D, Transform2D, World};
-use comet_log::{debug, info};
-use comet_math::{Point3, Vec2, Vec3};
+use comet_colors::{Color as ColorTrait, ColorLut, Gradient, LinearRgba, Palette};
+use comet_ecs::{theme_value, Component, ComponentSet, Emissive, FieldValue, FlashOnHit, Mesh2D, NoInterpolation, ParallaxLayer, Render, Render2D, RenderLayers, Shadow2D, SpriteEffect, SpriteEffectKind, SpriteMaterial, Trail2D, Transform2D, World, YSort};
+use comet_log::{debug, error, info, profile_scope, warn_once};
+use comet_log::profiler::ProfileGuard;
+use comet_math::{Point3, Vec2, Vec3, VisibilityPolygon};
 use comet_resources::{texture, graphic_resource_manager::GraphicResorceManager, Texture, Vertex};
-use comet_resources::texture_atlas::TextureRegion;
-use crate::camera::{Camera, CameraUniform};
-use crate::render_pass::RenderPassInfo;
-use crate::renderer::Renderer;
+use comet_resources::texture_atlas::{TextureHandle, TextureRegion};
+use comet_resources::sprite_metadata::{SpriteMetadata, SpriteMetadataError};
+use comet_resources::video::Video;
+use crate::accessibility::{Accessibility, ColorBlindFilter};
+use crate::bloom::Bloom;
+use crate::camera::{Camera, CameraUniform, ClearMode};
+use crate::culling::GpuCulling;
+use crate::dynamic_buffer::DynamicBuffer;
+use crate::gpu_error::{catch_gpu_errors, log_uncaptured_errors};
+use crate::gpu_resources::{BindGroupKey, BufferKey, GpuResources};
+use crate::mask::MaskPass;
+use crate::screen_flash::{ActiveFlash, ScreenFlash};
+use comet_structs::{Arena, SlotMap};
+use crate::render_pass::{BindingSlot, PassHandle, RenderPassInfo, RenderPassKey};
+use crate::renderer::{AdapterInfo, CustomDraw, RenderStats, Renderer, RendererError, RendererSettings, SupportReport};
+use crate::tonemapping::{Tonemapping, TonemapPass, HDR_FORMAT};
+use crate::video::VideoPlayer;
+
+/// How many copies of its texture a repeating parallax layer may draw, so a tiny texture cannot flood the batch when zoomed out.
+const MAX_PARALLAX_TILES: usize = 4096;
+/// The segments around the ellipse of a blob `Shadow2D`.
+const SHADOW_SEGMENTS: usize = 24;
 
 pub struct Renderer2D<'a> {
-	surface: wgpu::Surface<'a>,
+	window: Arc<Window>,
+	instance: wgpu::Instance,
+	/// Gone while the app is suspended, since mobile platforms destroy the window then.
+	surface: Option<wgpu::Surface<'a>>,
 	device: wgpu::Device,
 	queue: wgpu::Queue,
 	config: wgpu::SurfaceConfiguration,
 	size: winit::dpi::PhysicalSize<u32>,
 	render_pipeline_layout: wgpu::PipelineLayout,
 	pipelines: Vec<wgpu::RenderPipeline>,
-	render_pass: Vec<RenderPassInfo>,
+	emissive_pipeline: wgpu::RenderPipeline,
+	render_pass: SlotMap<RenderPassKey, RenderPassInfo>,
 	last_frame_time: Instant,
 	deltatime: f32,
-	vertex_buffer: wgpu::Buffer,
+	time: f32,
+	vertex_buffer: DynamicBuffer,
 	vertex_data: Vec<Vertex>,
-	index_buffer: wgpu::Buffer,
-	index_data: Vec<u16>,
+	index_buffer: DynamicBuffer,
+	index_data: Vec<u32>,
 	num_indices: u32,
-	clear_color: Color,
+	batch_budget: u32,
+	sprite_start: u32,
+	emissive_start: u32,
+	clear_color: LinearRgba,
+	clear_animation: Option<ClearAnimation>,
+	global_tint: LinearRgba,
 	diffuse_texture: texture::Texture,
 	diffuse_bind_group: wgpu::BindGroup,
 	graphic_resource_manager: GraphicResorceManager,
@@ -41,51 +71,146 @@ pub struct Renderer2D<'a> {
 	camera_uniform: CameraUniform,
 	camera_buffer: wgpu::Buffer,
 	camera_bind_group: wgpu::BindGroup,
+	tonemap_pass: Option<TonemapPass>,
+	mask_pass: MaskPass,
+	shadow_pass: MaskPass,
+	gpu_culling: Option<GpuCulling>,
+	trails: HashMap<u32, VecDeque<TrailPoint>>,
+	/// The paths the trails are drawn along this frame.
+	trail_paths: Arena<(Vec2, f32)>,
+	previous_positions: HashMap<u32, Vec2>,
+	interpolation: f32,
+	screen_flashes: Vec<ActiveFlash>,
+	video: Option<VideoPlayer>,
+	tonemapping: Tonemapping,
+	exposure: f32,
+	accessibility: Accessibility,
+	adapter_info: AdapterInfo,
+	scene_draws: Vec<CustomDraw>,
+	overlay_draws: Vec<CustomDraw>,
+	resources: GpuResources,
+	submitted: Vec<SubmittedQuad>
+}
+
+/// A quad drawn with `submit_quad` instead of through an entity.
+struct SubmittedQuad {
+	texture: TextureHandle,
+	position: Vec2,
+	angle: f32,
+	color: [f32; 4],
+	layer: i32
+}
+
+/// Something drawn in a layer of the batch instead of with the sprites.
+enum LayeredDraw<'a> {
+	Quad(&'a SubmittedQuad),
+	Mesh(u32, &'a Mesh2D)
+}
+
+/// A position a `Trail2D` passed through, in world units, and how many seconds ago.
+struct TrailPoint {
+	position: Vec2,
+	age: f32
+}
+
+/// A clear color running through a gradient over time.
+struct ClearAnimation {
+	gradient: Gradient,
+	duration: f32,
+	elapsed: f32,
+	looping: bool
+}
+
+impl ClearAnimation {
+	fn advance(&mut self, dt: f32) -> LinearRgba {
+		self.elapsed += dt;
+		let t = if self.looping { (self.elapsed / self.duration).fract() } else { (self.elapsed / self.duration).min(1.0) };
+		self.gradient.sample(t)
+	}
 }
 
 impl<'a> Renderer2D<'a> {
-	pub async fn new(window: Arc<Window>, clear_color: Option<LinearRgba>) -> Renderer2D<'a> {
+	pub async fn new(window: Arc<Window>, settings: RendererSettings) -> Renderer2D<'a> {
+		Self::try_new(window, settings).await.unwrap_or_else(|e| panic!("{}", e))
+	}
+
+	/// Looks for the GPU the renderer would pick for `settings` and reports the features it asks for that the GPU lacks,
+	/// e.g. to warn the player or to turn options off before the window opens.
+	pub async fn check_support(settings: &RendererSettings) -> Result<SupportReport, RendererError> {
+		let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+			backends: settings.backends(),
+			..Default::default()
+		});
+		let adapter = instance
+			.request_adapter(&wgpu::RequestAdapterOptions {
+				power_preference: settings.power_preference(),
+				compatible_surface: None,
+				force_fallback_adapter: false,
+			})
+			.await
+			.ok_or(RendererError::NoAdapter { backends: settings.backends() })?;
+
+		let mut missing = Vec::new();
+		if settings.gpu_culling() && !GpuCulling::is_supported(&adapter) {
+			missing.push("compute shaders and indirect drawing for GPU culling");
+		}
+		if !TonemapPass::is_supported(&adapter) {
+			missing.push("float render targets for HDR colors and tonemapping");
+		}
+		Ok(SupportReport::new(adapter.get_info(), missing))
+	}
+
+	pub async fn try_new(window: Arc<Window>, settings: RendererSettings) -> Result<Renderer2D<'a>, RendererError> {
 		let vertex_data: Vec<Vertex> = vec![];
-		let index_data: Vec<u16> = vec![];
+		let index_data: Vec<u32> = vec![];
 
 		let size = PhysicalSize::<u32>::new(1920, 1080);
 
 		let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-			backends: wgpu::Backends::PRIMARY,
+			backends: settings.backends(),
 			..Default::default()
 		});
 
-		let surface = instance.create_surface(window).unwrap();
+		let surface = instance.create_surface(window.clone())?;
 
 		let adapter = instance
 			.request_adapter(&wgpu::RequestAdapterOptions {
-				power_preference: wgpu::PowerPreference::default(),
+				power_preference: settings.power_preference(),
 				compatible_surface: Some(&surface),
 				force_fallback_adapter: false,
 			})
 			.await
-			.unwrap();
+			.ok_or(RendererError::NoAdapter { backends: settings.backends() })?;
+		let adapter_info = adapter.get_info();
+		info!("Rendering on {} ({:?}, driver {} {})", adapter_info.name, adapter_info.backend, adapter_info.driver, adapter_info.driver_info);
+
+		let gpu_culling_supported = settings.gpu_culling() && GpuCulling::is_supported(&adapter);
+		if settings.gpu_culling() && !gpu_culling_supported {
+			info!("The GPU does not support indirect drawing, drawing the whole sprite batch instead of culling it");
+		}
 
 		let (device, queue) = adapter
 			.request_device(
 				&wgpu::DeviceDescriptor {
 					label: None,
-					required_features: wgpu::Features::empty(),
+					required_features: if gpu_culling_supported { GpuCulling::FEATURES } else { wgpu::Features::empty() },
 					required_limits: wgpu::Limits::default(),
 					memory_hints: Default::default(),
 				},
 				None, // Trace path
 			)
 			.await
-			.unwrap();
+			.map_err(|error| RendererError::Device { adapter: adapter_info.name.clone(), error })?;
+		log_uncaptured_errors(&device);
 
 		let surface_caps = surface.get_capabilities(&adapter);
 		let surface_format = surface_caps
 			.formats
 			.iter()
 			.copied()
-			.find(|f| f.is_srgb())
-			.unwrap_or(surface_caps.formats[0]);
+			.find(|f| f.is_srgb() == settings.srgb_surface())
+			.or(surface_caps.formats.first().copied())
+			.ok_or_else(|| RendererError::NoSurfaceFormat { adapter: adapter_info.name.clone() })?;
 		let config = wgpu::SurfaceConfiguration {
 			usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
 			format: surface_format,
@@ -97,22 +222,30 @@ impl<'a> Renderer2D<'a> {
 			desired_maximum_frame_latency: 2,
 		};
 
+		// the scene is drawn to a float target first, so colors brighter than white survive until tonemapping
+		let tonemap_pass = if TonemapPass::is_supported(&adapter) {
+			let pass = TonemapPass::new(&device, &queue, config.format, config.width, config.height);
+			pass.update(&queue, Tonemapping::None, 1.0, ColorBlindFilter::None);
+			Some(pass)
+		}
+		else {
+			info!("Float render targets are not supported, HDR colors will be clipped");
+			None
+		};
+		let scene_format = if tonemap_pass.is_some() { HDR_FORMAT } else { config.format };
+
+		let shader_compile = ProfileGuard::new("shader compile");
 		let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
 			label: Some("Shader"),
 			source: wgpu::ShaderSource::Wgsl(include_str!("base2d.wgsl").into()),
 		});
+		drop(shader_compile);
 
-		let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-			label: Some("Vertex Buffer"),
-			contents: bytemuck::cast_slice(&vertex_data),
-			usage: wgpu::BufferUsages::VERTEX,
-		});
-
-		let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-			label: Some("Index Buffer"),
-			contents: bytemuck::cast_slice(&index_data),
-			usage: wgpu::BufferUsages::INDEX
-		});
+		let gpu_culling = gpu_culling_supported.then(|| GpuCulling::new(&device));
+		// the culling pass reads the batch as storage
+		let batch_usage = if gpu_culling.is_some() { wgpu::BufferUsages::STORAGE } else { wgpu::BufferUsages::empty() };
+		let vertex_buffer = DynamicBuffer::new(&device, "Vertex Buffer", wgpu::BufferUsages::VERTEX | batch_usage);
+		let index_buffer = DynamicBuffer::new(&device, "Index Buffer", wgpu::BufferUsages::INDEX | batch_usage);
 
 		let num_indices = index_data.len() as u32;
 
@@ -160,7 +293,16 @@ impl<'a> Renderer2D<'a> {
 			label: Some("diffuse_bind_group"),
 		});
 
-		let camera = Camera::new(1.0, Vec2::new(2.0, 2.0), Vec3::new(0.0, 0.0, 0.0));
+		let mut camera = Camera::new(1.0, Vec2::new(size.width as f32, size.height as f32), Vec3::new(0.0, 0.0, 0.0));
+		match theme_value("Camera", "zoom") {
+			Some(FieldValue::Float(zoom)) => camera.set_zoom(zoom),
+			Some(FieldValue::Int(zoom)) => camera.set_zoom(zoom as f32),
+			_ => {}
+		}
+		camera.set_scale_factor(window.scale_factor() as f32);
+		if let Some((width, height)) = settings.virtual_resolution() {
+			camera.set_virtual_resolution(Some((Vec2::new(width as f32, height as f32), settings.scale_policy())));
+		}
 
 		let mut camera_uniform = CameraUniform::new();
 		camera_uniform.update_view_proj(&camera);
@@ -195,6 +337,9 @@ impl<'a> Renderer2D<'a> {
 			label: Some("camera_bind_group"),
 		});
 
+		let mask_pass = MaskPass::new(&device, &camera_bind_group_layout, scene_format);
+		let shadow_pass = MaskPass::new(&device, &camera_bind_group_layout, scene_format);
+
 		let render_pipeline_layout =
 			device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
 				label: Some("Render Pipeline Layout"),
@@ -205,8 +350,8 @@ impl<'a> Renderer2D<'a> {
 				push_constant_ranges: &[],
 			});
 
-		let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-			label: Some("Render Pipeline"),
+		let create_sprite_pipeline = |label, blend| device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+			label: Some(label),
 			layout: Some(&render_pipeline_layout),
 			vertex: wgpu::VertexState {
 				module: &shader,
@@ -218,19 +363,8 @@ impl<'a> Renderer2D<'a> {
 				module: &shader,
 				entry_point: "fs_main",
 				targets: &[Some(wgpu::ColorTargetState {
-					format: config.format,
-					blend: Some(wgpu::BlendState {
-						color: wgpu::BlendComponent {
-							src_factor: wgpu::BlendFactor::SrcAlpha,
-							dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-							operation: wgpu::BlendOperation::Add,
-						},
-						alpha: wgpu::BlendComponent {
-							src_factor: wgpu::BlendFactor::One,
-							dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-							operation: wgpu::BlendOperation::Add,
-						},
-					}),
+					format: scene_format,
+					blend: Some(blend),
 					write_mask: wgpu::ColorWrites::ALL,
 				})],
 				compilation_options: Default::default(),
@@ -254,36 +388,68 @@ impl<'a> Renderer2D<'a> {
 			cache: None,
 		});
 
+		let shader_compile = ProfileGuard::new("shader compile");
+		let render_pipeline = create_sprite_pipeline("Render Pipeline", wgpu::BlendState {
+			color: wgpu::BlendComponent {
+				src_factor: wgpu::BlendFactor::SrcAlpha,
+				dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+				operation: wgpu::BlendOperation::Add,
+			},
+			alpha: wgpu::BlendComponent {
+				src_factor: wgpu::BlendFactor::One,
+				dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+				operation: wgpu::BlendOperation::Add,
+			},
+		});
+		// adds the light of emissive sprites onto the scene, keeping its alpha
+		let emissive_pipeline = create_sprite_pipeline("Emissive Pipeline", wgpu::BlendState {
+			color: wgpu::BlendComponent {
+				src_factor: wgpu::BlendFactor::SrcAlpha,
+				dst_factor: wgpu::BlendFactor::One,
+				operation: wgpu::BlendOperation::Add,
+			},
+			alpha: wgpu::BlendComponent {
+				src_factor: wgpu::BlendFactor::Zero,
+				dst_factor: wgpu::BlendFactor::One,
+				operation: wgpu::BlendOperation::Add,
+			},
+		});
+		drop(shader_compile);
+
 		let mut pipelines = Vec::new();
 		pipelines.push(render_pipeline);
 
-		let clear_color = match clear_color {
-			Some(color) => color.to_wgpu(),
-			None => wgpu::Color {
-				r: 0.1,
-				g: 0.2,
-				b: 0.3,
-				a: 1.0,
-			}
+		let clear_color = match settings.clear_color() {
+			Some(color) => color.clone(),
+			None => LinearRgba::new(0.1, 0.2, 0.3, 1.0)
 		};
 
-		Self {
-			surface,
+		Ok(Self {
+			window,
+			instance,
+			surface: Some(surface),
 			device,
 			queue,
 			config,
 			size,
 			render_pipeline_layout,
 			pipelines,
-			render_pass: vec![],
+			emissive_pipeline,
+			render_pass: SlotMap::new(),
 			last_frame_time: Instant::now(),
 			deltatime: 0.0,
+			time: 0.0,
 			vertex_buffer,
 			vertex_data,
 			index_buffer,
 			index_data,
 			num_indices,
+			batch_budget: settings.batch_budget(),
+			sprite_start: 0,
+			emissive_start: u32::MAX,
 			clear_color,
+			global_tint: LinearRgba::new(1.0, 1.0, 1.0, 1.0),
+			clear_animation: None,
 			diffuse_texture,
 			diffuse_bind_group,
 			graphic_resource_manager,
@@ -291,7 +457,25 @@ impl<'a> Renderer2D<'a> {
 			camera_uniform,
 			camera_buffer,
 			camera_bind_group,
-		}
+			tonemap_pass,
+			mask_pass,
+			shadow_pass,
+			gpu_culling,
+			trails: HashMap::new(),
+			trail_paths: Arena::new(),
+			previous_positions: HashMap::new(),
+			interpolation: 1.0,
+			screen_flashes: Vec::new(),
+			video: None,
+			tonemapping: Tonemapping::None,
+			exposure: 1.0,
+			accessibility: Accessibility::new(),
+			adapter_info,
+			scene_draws: Vec::new(),
+			overlay_draws: Vec::new(),
+			resources: GpuResources::new(),
+			submitted: Vec::new()
+		})
 	}
 
 	pub fn dt(&self) -> f32 {
@@ -312,12 +496,324 @@ impl<'a> Renderer2D<'a> {
 			self.size = new_size;
 			self.config.width = new_size.width;
 			self.config.height = new_size.height;
-			self.surface.configure(&self.device, &self.config);
+			self.camera.set_dimension(self.window_size());
+			if let Some(surface) = &self.surface {
+				surface.configure(&self.device, &self.config);
+			}
+			if let Some(tonemap_pass) = &mut self.tonemap_pass {
+				tonemap_pass.resize(&self.device, new_size.width, new_size.height);
+			}
+		}
+	}
+
+	/// The GPU and driver the renderer runs on.
+	pub fn adapter_info(&self) -> AdapterInfo {
+		self.adapter_info.clone()
+	}
+
+	/// The size of the batch drawn in the last frame.
+	pub fn render_stats(&self) -> RenderStats {
+		RenderStats::new(self.vertex_data.len() as u32, self.num_indices, self.batch_budget)
+	}
+
+	pub fn camera(&self) -> &Camera {
+		&self.camera
+	}
+
+	pub fn camera_mut(&mut self) -> &mut Camera {
+		&mut self.camera
+	}
+
+	fn window_size(&self) -> Vec2 {
+		Vec2::new(self.size.width as f32, self.size.height as f32)
+	}
+
+	/// Converts a point in the world into pixels from the top left corner of the window.
+	pub fn world_to_screen(&self, point: Vec2) -> Vec2 {
+		self.camera.world_to_screen(point, self.window_size())
+	}
+
+	/// Converts a position in the window, e.g. the mouse cursor, into a point in the world.
+	pub fn screen_to_world(&self, point: Vec2) -> Vec2 {
+		self.camera.screen_to_world(point, self.window_size())
+	}
+
+	pub fn clear_color(&self) -> &LinearRgba {
+		&self.clear_color
+	}
+
+	/// Changes the background color, stopping a running clear color animation.
+	pub fn set_clear_color(&mut self, clear_color: impl ColorTrait) {
+		self.clear_color = clear_color.to_linear();
+		self.clear_animation = None;
+	}
+
+	/// Runs the background color through `gradient` every `duration` seconds, e.g. for a sky going from dawn to night.
+	/// With `looping` off the color stays at the end of the gradient.
+	pub fn animate_clear_color(&mut self, gradient: Gradient, duration: f32, looping: bool) {
+		self.clear_color = gradient.sample(0.0);
+		self.clear_animation = Some(ClearAnimation {
+			gradient,
+			duration: duration.max(f32::EPSILON),
+			elapsed: 0.0,
+			looping
+		});
+	}
+
+	pub fn global_tint(&self) -> &LinearRgba {
+		&self.global_tint
+	}
+
+	/// Multiplies the colors of the sprites and parallax layers by `tint`, e.g. a dark blue at night.
+	/// Emissive sprites keep their colors, so lights stand out in the dark.
+	pub fn set_global_tint(&mut self, tint: impl ColorTrait) {
+		self.global_tint = tint.to_linear();
+	}
+
+	/// Whether the scene is rendered in HDR, which is needed for tonemapping to have an effect.
+	pub fn supports_hdr(&self) -> bool {
+		self.tonemap_pass.is_some()
+	}
+
+	pub fn tonemapping(&self) -> Tonemapping {
+		self.tonemapping
+	}
+
+	/// Sets how colors brighter than white, like `Hdr` colors, are mapped to the screen.
+	pub fn set_tonemapping(&mut self, tonemapping: Tonemapping) {
+		if self.tonemap_pass.is_none() && tonemapping != Tonemapping::None {
+			error!("Tonemapping needs float render targets, which are not supported on this device");
+		}
+		self.tonemapping = tonemapping;
+		self.update_tonemapping();
+	}
+
+	pub fn exposure(&self) -> f32 {
+		self.exposure
+	}
+
+	/// Scales the brightness of the scene before tonemapping, 1.0 leaves it unchanged.
+	pub fn set_exposure(&mut self, exposure: f32) {
+		self.exposure = exposure.max(0.0);
+		self.update_tonemapping();
+	}
+
+	pub fn bloom(&self) -> Option<Bloom> {
+		self.tonemap_pass.as_ref().and_then(TonemapPass::bloom)
+	}
+
+	/// Makes everything brighter than the threshold of `bloom` glow, e.g. `Hdr` colors and `Emissive` sprites.
+	/// `None` turns it off again.
+	pub fn set_bloom(&mut self, bloom: Option<Bloom>) {
+		match &mut self.tonemap_pass {
+			Some(tonemap_pass) => {
+				tonemap_pass.set_bloom(&self.device, &self.queue, bloom);
+				self.update_tonemapping();
+			}
+			None => error!("Bloom needs float render targets, which are not supported on this device")
+		}
+	}
+
+	/// Grades the colors on screen with `lut`, e.g. loaded with `ColorLut::load`, swapping out the one used before.
+	/// `None` turns the color grading off again.
+	pub fn set_color_grading(&mut self, lut: Option<&ColorLut>) {
+		match &mut self.tonemap_pass {
+			Some(tonemap_pass) => {
+				tonemap_pass.set_lut(&self.device, &self.queue, lut);
+				self.update_tonemapping();
+			}
+			None => error!("Color grading needs float render targets, which are not supported on this device")
+		}
+	}
+
+	/// Blends from the current color grading over to `lut` in `duration` seconds, e.g. from a day to a night look.
+	pub fn crossfade_color_grading(&mut self, lut: Option<&ColorLut>, duration: f32) {
+		match &mut self.tonemap_pass {
+			Some(tonemap_pass) => {
+				tonemap_pass.crossfade_lut(&self.device, &self.queue, lut, duration);
+				self.update_tonemapping();
+			}
+			None => error!("Color grading needs float render targets, which are not supported on this device")
+		}
+	}
+
+	/// Replaces every color on screen by the closest one of the palette, for a consistent retro look.
+	/// `None` turns the palette swap off again.
+	pub fn set_palette(&mut self, palette: Option<&Palette>) {
+		match &mut self.tonemap_pass {
+			Some(tonemap_pass) => {
+				let colors: Vec<[u8; 4]> = palette.map(|palette| palette.colors().iter().map(|color| color.to_u8_array()).collect()).unwrap_or_default();
+				tonemap_pass.set_palette(&self.device, &self.queue, &colors);
+				self.update_tonemapping();
+			}
+			None => error!("The palette swap needs float render targets, which are not supported on this device")
+		}
+	}
+
+	pub fn accessibility(&self) -> &Accessibility {
+		&self.accessibility
+	}
+
+	/// How many physical pixels one logical pixel of the window spans, see `Camera::scale_factor`.
+	pub fn scale_factor(&self) -> f32 {
+		self.camera.scale_factor()
+	}
+
+	/// Keeps sprites at their logical size when the window moves to a display with another scale factor.
+	pub fn set_scale_factor(&mut self, scale_factor: f64) {
+		self.camera.set_scale_factor(scale_factor as f32);
+	}
+
+	/// Applies the color blind filter and the shake intensity of `accessibility` and keeps its UI scale for text and UI.
+	pub fn set_accessibility(&mut self, accessibility: &Accessibility) {
+		if self.tonemap_pass.is_none() && accessibility.color_blind_filter() != ColorBlindFilter::None {
+			error!("The color blind filter needs float render targets, which are not supported on this device");
+		}
+		self.accessibility = accessibility.clone();
+		self.camera.set_shake_intensity(accessibility.shake_intensity());
+		self.update_tonemapping();
+	}
+
+	/// How much larger text and UI should be drawn, see `Accessibility::with_ui_scale`.
+	pub fn ui_scale(&self) -> f32 {
+		self.accessibility.ui_scale()
+	}
+
+	fn update_tonemapping(&self) {
+		if let Some(tonemap_pass) = &self.tonemap_pass {
+			tonemap_pass.update(&self.queue, self.tonemapping, self.exposure, self.accessibility.color_blind_filter());
+		}
+	}
+
+	pub fn device(&self) -> &wgpu::Device {
+		&self.device
+	}
+
+	pub fn queue(&self) -> &wgpu::Queue {
+		&self.queue
+	}
+
+	/// Holds the view projection of the camera, bound at `BindingSlot::CAMERA` in the scene pass.
+	pub fn camera_bind_group(&self) -> &wgpu::BindGroup {
+		&self.camera_bind_group
+	}
+
+	/// Queues draw commands for the next frame into `pass`, e.g. `PassHandle::OVERLAY` for debug drawing on top of everything.
+	pub fn queue_custom(&mut self, pass: PassHandle, callback: CustomDraw) {
+		match pass {
+			PassHandle::SCENE => self.scene_draws.push(callback),
+			_ => self.overlay_draws.push(callback)
+		}
+	}
+
+	/// Keeps `buffer` for custom draws, which find it in the `GpuResources` by the returned key.
+	pub fn add_buffer(&mut self, buffer: wgpu::Buffer) -> BufferKey {
+		self.resources.add_buffer(buffer)
+	}
+
+	/// Uploads `data` to the start of the buffer. Returns false if the buffer was removed.
+	pub fn write_buffer(&self, key: BufferKey, data: &[u8]) -> bool {
+		let Some(buffer) = self.resources.buffer(key) else {
+			return false;
+		};
+		self.queue.write_buffer(buffer, 0, data);
+		true
+	}
+
+	/// Keeps `bind_group` for custom draws, which find it in the `GpuResources` by the returned key.
+	pub fn add_bind_group(&mut self, bind_group: wgpu::BindGroup) -> BindGroupKey {
+		self.resources.add_bind_group(bind_group)
+	}
+
+	/// The buffers and bind groups added for custom draws, e.g. to create a bind group over an added buffer.
+	pub fn resources(&self) -> &GpuResources {
+		&self.resources
+	}
+
+	pub fn resources_mut(&mut self) -> &mut GpuResources {
+		&mut self.resources
+	}
+
+	/// Records the queued custom draws in a pass that keeps what is already in `view`.
+	fn record_custom_draws(encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView, label: &str, draws: Vec<CustomDraw>, resources: &GpuResources) {
+		if draws.is_empty() {
+			return;
+		}
+
+		let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+			label: Some(label),
+			color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+				view,
+				resolve_target: None,
+				ops: wgpu::Operations {
+					load: wgpu::LoadOp::Load,
+					store: wgpu::StoreOp::Store,
+				},
+			})],
+			depth_stencil_attachment: None,
+			occlusion_query_set: None,
+			timestamp_writes: None,
+		});
+
+		for draw in draws {
+			draw(&mut render_pass, resources);
+		}
+	}
+
+	/// The format the scene pipelines render to.
+	pub fn scene_format(&self) -> wgpu::TextureFormat {
+		if self.tonemap_pass.is_some() { HDR_FORMAT } else { self.config.format }
+	}
+
+	/// Drops the surface and stops rendering until `resume` is called.
+	/// Mobile platforms destroy the window when the app goes into the background, so the surface must not outlive it.
+	pub fn suspend(&mut self) {
+		self.surface = None;
+	}
+
+	/// Creates a new surface for the window, since the old one may be gone after a suspension.
+	pub fn resume(&mut self) {
+		if self.surface.is_some() {
+			return;
+		}
+
+		match self.instance.create_surface(self.window.clone()) {
+			Ok(surface) => {
+				let size = self.window.inner_size();
+				if size.width > 0 && size.height > 0 {
+					self.size = size;
+					self.config.width = size.width;
+					self.config.height = size.height;
+					self.camera.set_dimension(self.window_size());
+				}
+				surface.configure(&self.device, &self.config);
+				self.surface = Some(surface);
+				info!("Recreated the surface");
+			}
+			Err(e) => error!("Failed to recreate the surface: {}", e)
 		}
 	}
 
-	pub fn add_render_pass(&mut self, render_pass_info: RenderPassInfo) {
-		self.render_pass.push(render_pass_info);
+	/// Whether the sprites are culled on the GPU, which needs `RendererSettings::with_gpu_culling` and a GPU supporting indirect draws.
+	pub fn is_gpu_culling(&self) -> bool {
+		self.gpu_culling.is_some()
+	}
+
+	pub fn is_suspended(&self) -> bool {
+		self.surface.is_none()
+	}
+
+	/// Adds a render pass. The returned key stays valid until the pass is removed.
+	pub fn add_render_pass(&mut self, render_pass_info: RenderPassInfo) -> RenderPassKey {
+		self.render_pass.insert(render_pass_info)
+	}
+
+	pub fn remove_render_pass(&mut self, key: RenderPassKey) -> Option<RenderPassInfo> {
+		self.render_pass.remove(key)
+	}
+
+	pub fn render_pass(&self, key: RenderPassKey) -> Option<&RenderPassInfo> {
+		self.render_pass.get(key)
 	}
 
 	/// A function that loads a shader from the resources/shaders folder given the full name of the shader file.
@@ -382,54 +878,61 @@ impl<'a> Renderer2D<'a> {
 				push_constant_ranges: &[],
 			});
 
-		self.pipelines[0] = self.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-			label: Some("Render Pipeline"),
-			layout: Some(&render_pipeline_layout),
-			vertex: wgpu::VertexState {
-				module: &shader_module,
-				entry_point: "vs_main",
-				buffers: &[Vertex::desc()],
-				compilation_options: Default::default(),
-			},
-			fragment: Some(wgpu::FragmentState {
-				module: &shader_module,
-				entry_point: "fs_main",
-				targets: &[Some(wgpu::ColorTargetState {
-					format: self.config.format,
-					blend: Some(wgpu::BlendState {
-						color: wgpu::BlendComponent {
-							src_factor: wgpu::BlendFactor::SrcAlpha,
-							dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-							operation: wgpu::BlendOperation::Add,
-						},
-						alpha: wgpu::BlendComponent {
-							src_factor: wgpu::BlendFactor::One,
-							dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-							operation: wgpu::BlendOperation::Add,
-						},
-					}),
-					write_mask: wgpu::ColorWrites::ALL,
-				})],
-				compilation_options: Default::default(),
-			}),
-			primitive: wgpu::PrimitiveState {
-				topology: wgpu::PrimitiveTopology::TriangleList,
-				strip_index_format: None,
-				front_face: wgpu::FrontFace::Ccw,
-				cull_mode: Some(wgpu::Face::Back),
-				polygon_mode: wgpu::PolygonMode::Fill,
-				unclipped_depth: false,
-				conservative: false,
-			},
-			depth_stencil: None,
-			multisample: wgpu::MultisampleState {
-				count: 1,
-				mask: !0,
-				alpha_to_coverage_enabled: false,
-			},
-			multiview: None,
-			cache: None,
+		// a broken shader of the game is logged and leaves the current pipeline in place instead of panicking
+		let (pipeline, error) = catch_gpu_errors(&self.device, &format!("the pipeline of the shader {}", shader), || {
+			self.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+				label: Some("Render Pipeline"),
+				layout: Some(&render_pipeline_layout),
+				vertex: wgpu::VertexState {
+					module: &shader_module,
+					entry_point: "vs_main",
+					buffers: &[Vertex::desc()],
+					compilation_options: Default::default(),
+				},
+				fragment: Some(wgpu::FragmentState {
+					module: &shader_module,
+					entry_point: "fs_main",
+					targets: &[Some(wgpu::ColorTargetState {
+						format: self.scene_format(),
+						blend: Some(wgpu::BlendState {
+							color: wgpu::BlendComponent {
+								src_factor: wgpu::BlendFactor::SrcAlpha,
+								dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+								operation: wgpu::BlendOperation::Add,
+							},
+							alpha: wgpu::BlendComponent {
+								src_factor: wgpu::BlendFactor::One,
+								dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+								operation: wgpu::BlendOperation::Add,
+							},
+						}),
+						write_mask: wgpu::ColorWrites::ALL,
+					})],
+					compilation_options: Default::default(),
+				}),
+				primitive: wgpu::PrimitiveState {
+					topology: wgpu::PrimitiveTopology::TriangleList,
+					strip_index_format: None,
+					front_face: wgpu::FrontFace::Ccw,
+					cull_mode: Some(wgpu::Face::Back),
+					polygon_mode: wgpu::PolygonMode::Fill,
+					unclipped_depth: false,
+					conservative: false,
+				},
+				depth_stencil: None,
+				multisample: wgpu::MultisampleState {
+					count: 1,
+					mask: !0,
+					alpha_to_coverage_enabled: false,
+				},
+				multiview: None,
+				cache: None,
+			})
 		});
+		if error.is_some() {
+			return;
+		}
+		self.pipelines[0] = pipeline;
 
 		info!("Applied shader ({})!", shader);
 	}
@@ -500,7 +1003,7 @@ impl<'a> Renderer2D<'a> {
 				module: &shader,
 				entry_point: "fs_main",
 				targets: &[Some(wgpu::ColorTargetState {
-					format: self.config.format,
+					format: self.scene_format(),
 					blend: Some(wgpu::BlendState {
 						color: wgpu::BlendComponent {
 							src_factor: wgpu::BlendFactor::SrcAlpha,
@@ -539,20 +1042,29 @@ impl<'a> Renderer2D<'a> {
 	}
 
 	/// An interface for getting the location of the texture in the texture atlas.
-	pub fn get_texture_region(&self, texture_path: String) -> &TextureRegion {
-		assert!(self.graphic_resource_manager.texture_atlas().textures().contains_key(&texture_path), "Texture not found in atlas");
-		self.graphic_resource_manager.texture_atlas().textures().get(&texture_path).unwrap()
+	pub fn get_texture_region(&self, texture: TextureHandle) -> &TextureRegion {
+		self.graphic_resource_manager.texture_region(texture).expect("Texture not found in atlas")
+	}
+
+	/// How large a sprite of `texture` is in world units, see `Camera::pixels_per_unit`.
+	pub fn texture_world_size(&self, texture: TextureHandle) -> Vec2 {
+		self.region_world_size(self.get_texture_region(texture))
+	}
+
+	fn region_world_size(&self, region: &TextureRegion) -> Vec2 {
+		let (dim_x, dim_y) = region.dimensions();
+		self.camera.pixels_to_units(Vec2::new(dim_x as f32, dim_y as f32))
 	}
 
 	fn create_rectangle(&self, width: f32, height: f32) -> Vec<Vertex> {
-		let (bound_x, bound_y) =
-			((width/ self.config.width as f32) * 0.5, (height/ self.config.height as f32) * 0.5);
+		let size = self.camera.pixels_to_units(Vec2::new(width, height));
+		let (bound_x, bound_y) = (size.x() * 0.5, size.y() * 0.5);
 
 		vec![
-			Vertex :: new ( [-bound_x,  bound_y, 0.0], [0.0, 0.0], [0.0, 0.0, 0.0, 0.0] ),
-			Vertex :: new ( [-bound_x, -bound_y, 0.0], [0.0, 1.0], [0.0, 0.0, 0.0, 0.0] ),
-			Vertex :: new ( [ bound_x, -bound_y, 0.0], [1.0, 1.0], [0.0, 0.0, 0.0, 0.0] ),
-			Vertex :: new ( [ bound_x,  bound_y, 0.0], [1.0, 0.0], [0.0, 0.0, 0.0, 0.0] )
+			Vertex :: new ( [-bound_x,  bound_y, 0.0], [0.0, 0.0], [1.0, 1.0, 1.0, 1.0] ),
+			Vertex :: new ( [-bound_x, -bound_y, 0.0], [0.0, 1.0], [1.0, 1.0, 1.0, 1.0] ),
+			Vertex :: new ( [ bound_x, -bound_y, 0.0], [1.0, 1.0], [1.0, 1.0, 1.0, 1.0] ),
+			Vertex :: new ( [ bound_x,  bound_y, 0.0], [1.0, 0.0], [1.0, 1.0, 1.0, 1.0] )
 		]
 	}
 
@@ -603,6 +1115,16 @@ impl<'a> Renderer2D<'a> {
 		self.diffuse_bind_group = diffuse_bind_group;
 	}
 
+	/// Gives the textures of the atlas the pivots and trims of a TexturePacker JSON data file,
+	/// see `TextureAtlas::apply_metadata`. Load it after the atlas, as building a new one forgets them.
+	/// Returns how many textures it matched.
+	pub fn load_sprite_metadata(&mut self, path: &str) -> Result<usize, SpriteMetadataError> {
+		let metadata = SpriteMetadata::load(path)?;
+		let matched = self.graphic_resource_manager.texture_atlas_mut().apply_metadata(&metadata);
+		info!("Applied the sprite metadata of {} to {} textures", path, matched);
+		Ok(matched)
+	}
+
 	fn get_project_root() -> std::io::Result<PathBuf> {
 		let path = std::env::current_dir()?;
 		let mut path_ancestors = path.as_path().ancestors();
@@ -632,51 +1154,38 @@ impl<'a> Renderer2D<'a> {
 	}
 
 	/// A function that clears the buffers and sets the vertex and index buffer of the `Renderer2D` with the given data.
-	fn set_buffers(&mut self, new_vertex_buffer: Vec<Vertex>, new_index_buffer: Vec<u16>) {
-		match new_vertex_buffer == self.vertex_data {
-			true => return,
-			false => {
-				self.vertex_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-					label: Some("Updated Vertex Buffer"),
-					contents: bytemuck::cast_slice(&new_vertex_buffer),
-					usage: wgpu::BufferUsages::VERTEX,
-				});
-				self.vertex_data = new_vertex_buffer;
-			}
+	fn set_buffers(&mut self, new_vertex_buffer: Vec<Vertex>, new_index_buffer: Vec<u32>) {
+		// the buffers are reused, so unchanged data does not need to be uploaded again
+		if new_vertex_buffer != self.vertex_data {
+			self.vertex_buffer.write(&self.device, &self.queue, bytemuck::cast_slice(&new_vertex_buffer));
+			self.vertex_data = new_vertex_buffer;
 		}
 
-		match new_index_buffer == self.index_data {
-			true => return,
-			false => {
-				self.index_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-					label: Some("Updated Index Buffer"),
-					contents: bytemuck::cast_slice(&new_index_buffer),
-					usage: wgpu::BufferUsages::INDEX,
-				});
-				self.index_data = new_index_buffer.clone();
-				self.num_indices = new_index_buffer.len() as u32;
-			}
+		if new_index_buffer != self.index_data {
+			self.index_buffer.write(&self.device, &self.queue, bytemuck::cast_slice(&new_index_buffer));
+			self.num_indices = new_index_buffer.len() as u32;
+			self.index_data = new_index_buffer;
 		}
+		self.check_batch_budget();
 	}
 
 	/// A function that adds data to the already existing vertex and index buffers of the `Renderer2D`.
-	fn push_to_buffers(&mut self, new_vertex_buffer: &mut Vec<Vertex>, new_index_buffer: &mut Vec<u16>) {
+	fn push_to_buffers(&mut self, new_vertex_buffer: &mut Vec<Vertex>, new_index_buffer: &mut Vec<u32>) {
 		self.vertex_data.append(new_vertex_buffer);
 		self.index_data.append(new_index_buffer);
 
-		self.vertex_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-			label: Some("Updated Vertex Buffer"),
-			contents: bytemuck::cast_slice(&self.vertex_data),
-			usage: wgpu::BufferUsages::VERTEX,
-		});
-
-		self.index_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-			label: Some("Updated Index Buffer"),
-			contents: bytemuck::cast_slice(&self.index_data),
-			usage: wgpu::BufferUsages::INDEX,
-		});
+		self.vertex_buffer.write(&self.device, &self.queue, bytemuck::cast_slice(&self.vertex_data));
+		self.index_buffer.write(&self.device, &self.queue, bytemuck::cast_slice(&self.index_data));
 
 		self.num_indices = self.index_data.len() as u32;
+		self.check_batch_budget();
+	}
+
+	fn check_batch_budget(&self) {
+		let stats = self.render_stats();
+		if stats.is_over_budget() {
+			warn_once!("The batch of {} vertices is over the budget of {}, see `RendererSettings::with_batch_budget`", stats.vertices(), stats.batch_budget());
+		}
 	}
 
 	/// A function that clears the vertex and index buffers of the `Renderer2D`.
@@ -684,39 +1193,131 @@ impl<'a> Renderer2D<'a> {
 		self.vertex_data = vec![];
 		self.index_data = vec![];
 
-		self.vertex_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-			label: Some("Updated Vertex Buffer"),
-			contents: bytemuck::cast_slice(&self.vertex_data),
-			usage: wgpu::BufferUsages::VERTEX,
-		});
+		self.num_indices = self.index_data.len() as u32;
+	}
 
-		self.index_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-			label: Some("Updated Index Buffer"),
-			contents: bytemuck::cast_slice(&self.index_data),
-			usage: wgpu::BufferUsages::INDEX,
+	/// Draws the texture at `transform` without an entity, e.g. for prototypes and procedural drawing.
+	/// The quad is batched with the sprites of the world: a negative `layer` draws it below them, zero and above on top,
+	/// higher layers over lower ones. It stays until the next fixed update, so submit it again in every update.
+	pub fn submit_quad(&mut self, texture: TextureHandle, transform: &Transform2D, color: impl ColorTrait, layer: i32) {
+		self.submitted.push(SubmittedQuad {
+			texture,
+			position: transform.position().as_vec(),
+			angle: transform.rotation().angle(),
+			color: color.to_linear().to_f32_array(),
+			layer
 		});
+	}
 
-		self.num_indices = self.index_data.len() as u32;
+	/// Drops the quads submitted with `submit_quad`. The app calls it before every fixed update.
+	pub fn clear_submitted(&mut self) {
+		self.submitted.clear();
+	}
+
+	/// Appends the submitted quads and the `Mesh2D`s whose layer is within `layers`, in the order of their layers.
+	/// Within a layer meshes come first, then the quads in the order they were submitted.
+	fn push_layered(&self, world: &World, layers: impl std::ops::RangeBounds<i32>, vertex_buffer: &mut Vec<Vertex>, index_buffer: &mut Vec<u32>) {
+		let camera_layers = self.camera.render_layers();
+		let render_layers = world.components().typed::<RenderLayers>();
+		let meshes = world.components().typed::<Mesh2D>()
+			.into_iter()
+			.flat_map(|meshes| meshes.iter())
+			.filter(|(entity, mesh)| {
+				let entity_layers = render_layers.and_then(|layers| layers.get(*entity)).copied().unwrap_or(RenderLayers::DEFAULT);
+				mesh.is_visible() && layers.contains(&mesh.layer()) && entity_layers.intersects(&camera_layers)
+					&& world.components().typed::<Transform2D>().is_some_and(|transforms| transforms.contains(*entity))
+			})
+			.map(|(entity, mesh)| (mesh.layer(), LayeredDraw::Mesh(entity as u32, mesh)));
+		let quads = self.submitted.iter()
+			.filter(|quad| layers.contains(&quad.layer))
+			.map(|quad| (quad.layer, LayeredDraw::Quad(quad)));
+		let mut draws: Vec<(i32, LayeredDraw)> = meshes.chain(quads).collect();
+		draws.sort_by_key(|(layer, _)| *layer);
+
+		let tint = self.global_tint.to_f32_array();
+		for (_, draw) in draws {
+			match draw {
+				LayeredDraw::Quad(quad) => {
+					let Some(region) = self.graphic_resource_manager.texture_region(quad.texture) else {
+						warn_once!("A submitted quad shows a texture that is not in the atlas");
+						continue;
+					};
+					let corners = self.corners_at(quad.position, Vec2::new(1.0, 1.0), quad.angle, region);
+					let color = [quad.color[0] * tint[0], quad.color[1] * tint[1], quad.color[2] * tint[2], quad.color[3] * tint[3]];
+					Self::push_corners(vertex_buffer, index_buffer, corners, region, color, [0.0; 4]);
+				}
+				LayeredDraw::Mesh(entity, mesh) => self.push_mesh(world, entity, mesh, vertex_buffer, index_buffer)
+			}
+		}
+	}
+
+	/// Appends the triangles of a `Mesh2D` moved and rotated by the transform of its entity.
+	fn push_mesh(&self, world: &World, entity: u32, mesh: &Mesh2D, vertex_buffer: &mut Vec<Vertex>, index_buffer: &mut Vec<u32>) {
+		let Some(region) = self.graphic_resource_manager.texture_region(mesh.texture()) else {
+			warn_once!("The texture of a Mesh2D is not in the atlas, it is not drawn");
+			return;
+		};
+		let vertex_count = mesh.vertices().len() as u32;
+		if let Some(index) = mesh.indices().iter().find(|index| **index >= vertex_count) {
+			warn_once!("A Mesh2D has the index {} but only {} vertices, it is not drawn", index, vertex_count);
+			return;
+		}
+
+		let position = self.drawn_position(world, entity);
+		let (sin, cos) = world.get_component::<Transform2D>(entity as usize).rotation().angle().sin_cos();
+		let tint = self.global_tint.to_f32_array();
+		let untextured = mesh.texture() == TextureHandle::WHITE;
+		let center = [(region.x0() + region.x1()) * 0.5, (region.y0() + region.y1()) * 0.5];
+		let buffer_size = vertex_buffer.len() as u32;
+		vertex_buffer.extend(mesh.vertices().iter().map(|vertex| {
+			let local = vertex.position;
+			let world_position = position + Vec2::new(local.x() * cos - local.y() * sin, local.x() * sin + local.y() * cos);
+			let color = [vertex.color[0] * tint[0], vertex.color[1] * tint[1], vertex.color[2] * tint[2], vertex.color[3] * tint[3]];
+			if untextured {
+				Vertex::new([world_position.x(), world_position.y(), 0.0], center, color)
+			}
+			else {
+				// the region makes the shader repeat the texture for coordinates past 1
+				let mut textured = Vertex::new([world_position.x(), world_position.y(), 0.0], [vertex.uv.x(), vertex.uv.y()], color);
+				textured.set_region([region.x0(), region.y0(), region.x1(), region.y1()]);
+				textured
+			}
+		}));
+
+		let triangles = mesh.indices().len() / 3 * 3;
+		for triangle in mesh.indices()[..triangles].chunks_exact(3) {
+			let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| mesh.vertices()[index as usize].position);
+			// back faces are culled, so clockwise triangles, e.g. of tessellated SVG paths, are turned around
+			if (b.x() - a.x()) * (c.y() - a.y()) - (b.y() - a.y()) * (c.x() - a.x()) < 0.0 {
+				index_buffer.extend_from_slice(&[buffer_size + triangle[0], buffer_size + triangle[2], buffer_size + triangle[1]]);
+			}
+			else {
+				index_buffer.extend(triangle.iter().map(|index| buffer_size + index));
+			}
+		}
+		// GPU culling reads the batch in quads of six indices, a degenerate triangle keeps the quads after the mesh aligned
+		if !triangles.is_multiple_of(6) {
+			let last = *index_buffer.last().unwrap_or(&buffer_size);
+			index_buffer.extend_from_slice(&[last, last, last]);
+		}
 	}
 
 	/// A function to just draw a textured quad at a given position.
 	pub fn draw_texture_at(&mut self, texture_path: String, position: Point3) {
-		let region = self.graphic_resource_manager.texture_locations().get(&texture_path).unwrap();
-		let (dim_x, dim_y) = region.dimensions();
-
-		let (bound_x, bound_y) =
-			((dim_x as f32/ self.config.width as f32) * 0.5, (dim_y as f32/ self.config.height as f32) * 0.5);
+		let region = self.get_texture_region(TextureHandle::from_name(&texture_path));
+		let size = self.region_world_size(region);
+		let (bound_x, bound_y) = (size.x() * 0.5, size.y() * 0.5);
 
 		let vertices: &mut Vec<Vertex> = &mut vec![
-			Vertex :: new ( [-bound_x + position.x(),  bound_y + position.y(), 0.0 + position.z()], [region.x0(), region.y0()], [0.0, 0.0, 0.0, 0.0] ),
-			Vertex :: new ( [-bound_x + position.x(), -bound_y + position.y(), 0.0 + position.z()], [region.x0(), region.y1()], [0.0, 0.0, 0.0, 0.0] ),
-			Vertex :: new ( [ bound_x + position.x(), -bound_y + position.y(), 0.0 + position.z()], [region.x1(), region.y1()], [0.0, 0.0, 0.0, 0.0] ) ,
-			Vertex :: new ( [ bound_x + position.x(),  bound_y + position.y(), 0.0 + position.z()], [region.x1(), region.y0()], [0.0, 0.0, 0.0, 0.0] )
+			Vertex :: new ( [-bound_x + position.x(),  bound_y + position.y(), 0.0 + position.z()], [region.x0(), region.y0()], [1.0, 1.0, 1.0, 1.0] ),
+			Vertex :: new ( [-bound_x + position.x(), -bound_y + position.y(), 0.0 + position.z()], [region.x0(), region.y1()], [1.0, 1.0, 1.0, 1.0] ),
+			Vertex :: new ( [ bound_x + position.x(), -bound_y + position.y(), 0.0 + position.z()], [region.x1(), region.y1()], [1.0, 1.0, 1.0, 1.0] ) ,
+			Vertex :: new ( [ bound_x + position.x(),  bound_y + position.y(), 0.0 + position.z()], [region.x1(), region.y0()], [1.0, 1.0, 1.0, 1.0] )
 		];
 
-		let buffer_size = self.vertex_data.len() as u16;
+		let buffer_size = self.vertex_data.len() as u32;
 
-		let indices: &mut Vec<u16> = &mut vec![
+		let indices: &mut Vec<u32> = &mut vec![
 			0 + buffer_size, 1 + buffer_size, 3 + buffer_size,
 			1 + buffer_size, 2 + buffer_size, 3 + buffer_size
 		];
@@ -724,48 +1325,494 @@ impl<'a> Renderer2D<'a> {
 		self.push_to_buffers(vertices, indices)
 	}
 
+	/// Fills the visible area of `polygon` with `color` this frame, e.g. for a light cone.
+	/// The polygon is in the same units as `Transform2D`.
+	pub fn draw_visibility_polygon(&mut self, polygon: &VisibilityPolygon, color: impl ColorTrait) {
+		let color = color.to_f32_array();
+		for triangle in polygon.triangles() {
+			self.mask_pass.push_triangle(triangle.map(|corner| [corner.x(), corner.y()]), color);
+		}
+	}
+
+	/// Fills everything in view that `polygon` does not see with `color` this frame, e.g. a translucent black for fog of war.
+	pub fn draw_fog_of_war(&mut self, polygon: &VisibilityPolygon, color: impl ColorTrait) {
+		let color = color.to_f32_array();
+		let corners = self.camera.visible_world_corners();
+		let (min, max) = corners.iter().fold((corners[0], corners[0]), |(min, max), corner| (
+			Vec2::new(min.x().min(corner.x()), min.y().min(corner.y())),
+			Vec2::new(max.x().max(corner.x()), max.y().max(corner.y()))
+		));
+		for triangle in polygon.hidden_triangles(min, max) {
+			self.mask_pass.push_triangle(triangle.map(|corner| [corner.x(), corner.y()]), color);
+		}
+	}
+
+	/// Shows `flash` over the whole screen until it faded out, on top of any flashes still running.
+	pub fn flash_screen(&mut self, flash: ScreenFlash) {
+		self.screen_flashes.push(ActiveFlash::new(flash));
+	}
+
+	/// Plays `video` over the whole window, e.g. an intro cutscene, until it ends or `stop_video` is called.
+	/// Its sound track is not played here, `App::play_cutscene` takes care of both.
+	pub fn play_video(&mut self, video: Video) {
+		self.video = Some(VideoPlayer::new(&self.device, self.config.format, video));
+	}
+
+	pub fn stop_video(&mut self) {
+		self.video = None;
+	}
+
+	pub fn is_playing_video(&self) -> bool {
+		self.video.is_some()
+	}
+
+	/// How far into the playing video the renderer is, and how long it is, in seconds.
+	pub fn video_progress(&self) -> Option<(f32, f32)> {
+		self.video.as_ref().map(|video| (video.elapsed(), video.duration()))
+	}
+
+	/// Covers the view with the running screen flashes, as a fan from the center to points along its border.
+	fn push_screen_flashes(&mut self) {
+		const BORDER_SEGMENTS: usize = 8;
+		let corners = self.camera.visible_world_corners();
+		let middle_point = (corners[0] + corners[2]) * 0.5;
+		let center = [middle_point.x(), middle_point.y()];
+		// counterclockwise around the border, BORDER_SEGMENTS points per side
+		let border: Vec<[f32; 2]> = (0..BORDER_SEGMENTS * 4)
+			.map(|i| {
+				let t = (i % BORDER_SEGMENTS) as f32 / BORDER_SEGMENTS as f32;
+				let (from, to) = (corners[i / BORDER_SEGMENTS], corners[(i / BORDER_SEGMENTS + 1) % 4]);
+				let point = from + (to - from) * t;
+				[point.x(), point.y()]
+			})
+			.collect();
+
+		for flash in &self.screen_flashes {
+			let (edge, middle) = flash.colors();
+			for i in 0..border.len() {
+				let next = border[(i + 1) % border.len()];
+				self.mask_pass.push_gradient_triangle([center, border[i], next], [middle, edge, edge]);
+			}
+		}
+	}
+
 	/// A function to draw text at a given position.
 	pub fn draw_text_at(&mut self, text: &str, position: Point3) {
 		todo!()
 	}
 
-	/// A function to automatically render all the entities of the `World` struct.
-	/// The entities must have the `Render2D` and `Transform2D` components to be rendered as well as set visible.
-	pub fn render_scene_2d(&mut self, world: &World) {
-		let entities =  world.get_entities_with(ComponentSet::from_ids(vec![Render2D::type_id()]));
-		let mut vertex_buffer: Vec<Vertex> = Vec::new();
-		let mut index_buffer: Vec<u16> = Vec::new();
+	/// Appends a textured quad around `center` to the batch, tinted by `color`.
+	fn push_quad(vertex_buffer: &mut Vec<Vertex>, index_buffer: &mut Vec<u32>, center: Vec2, half_size: Vec2, region: &TextureRegion, color: [f32; 4], flash: [f32; 4]) {
+		let (x, y) = (center.x(), center.y());
+		let (bound_x, bound_y) = (half_size.x(), half_size.y());
+		let corners = [
+			Vec2::new(-bound_x + x,  bound_y + y),
+			Vec2::new(-bound_x + x, -bound_y + y),
+			Vec2::new( bound_x + x, -bound_y + y),
+			Vec2::new( bound_x + x,  bound_y + y)
+		];
+		Self::push_corners(vertex_buffer, index_buffer, corners, region, color, flash);
+	}
 
-		for entity in entities {
-			let renderer_component =  world.get_component::<Render2D>(entity as usize);
-			let transform_component = world.get_component::<Transform2D>(entity as usize);
-
-			if renderer_component.is_visible() {
-				//renderer.draw_texture_at(renderer_component.get_texture(), Point3::new(transform_component.position().x(), transform_component.position().y(), 0.0));
-				let mut position = transform_component.position().clone();
-				position.set_x(position.x() / self.config().width as f32);
-				position.set_y(position.y() / self.config().height as f32);
-				let region = self.get_texture_region(renderer_component.get_texture().to_string());
-				let (dim_x, dim_y) = region.dimensions();
-
-				let (bound_x, bound_y) =
-					((dim_x as f32/ self.config().width as f32) * 0.5, (dim_y as f32/ self.config().height as f32) * 0.5);
-
-				let buffer_size = vertex_buffer.len() as u16;
-
-				vertex_buffer.append(&mut vec![
-					Vertex :: new ( [-bound_x + position.x(),  bound_y + position.y(), 0.0], [region.x0(), region.y0()], [0.0, 0.0, 0.0, 0.0] ),
-					Vertex :: new ( [-bound_x + position.x(), -bound_y + position.y(), 0.0], [region.x0(), region.y1()], [0.0, 0.0, 0.0, 0.0] ),
-					Vertex :: new ( [ bound_x + position.x(), -bound_y + position.y(), 0.0], [region.x1(), region.y1()], [0.0, 0.0, 0.0, 0.0] ) ,
-					Vertex :: new ( [ bound_x + position.x(),  bound_y + position.y(), 0.0], [region.x1(), region.y0()], [0.0, 0.0, 0.0, 0.0] )
+	/// Appends a quad with the given corners, from the top left counterclockwise, showing all of `region`.
+	fn push_corners(vertex_buffer: &mut Vec<Vertex>, index_buffer: &mut Vec<u32>, corners: [Vec2; 4], region: &TextureRegion, color: [f32; 4], flash: [f32; 4]) {
+		let buffer_size = vertex_buffer.len() as u32;
+		let [top_left, bottom_left, bottom_right, top_right] = corners;
+
+		// quads go straight into the batch instead of through a temporary Vec per entity
+		vertex_buffer.extend_from_slice(&[
+			Vertex :: new ( [top_left.x(), top_left.y(), 0.0], [region.x0(), region.y0()], color ).with_flash(flash),
+			Vertex :: new ( [bottom_left.x(), bottom_left.y(), 0.0], [region.x0(), region.y1()], color ).with_flash(flash),
+			Vertex :: new ( [bottom_right.x(), bottom_right.y(), 0.0], [region.x1(), region.y1()], color ).with_flash(flash),
+			Vertex :: new ( [top_right.x(), top_right.y(), 0.0], [region.x1(), region.y0()], color ).with_flash(flash)
+		]);
+
+		index_buffer.extend_from_slice(&[
+			0 + buffer_size, 1 + buffer_size, 3 + buffer_size,
+			1 + buffer_size, 2 + buffer_size, 3 + buffer_size
+		]);
+	}
+
+	/// Appends the quads of all `ParallaxLayer`s to the batch, the deepest first, tiling repeating ones across the view.
+	fn push_parallax_layers(&self, world: &World, vertex_buffer: &mut Vec<Vertex>, index_buffer: &mut Vec<u32>) {
+		let Some(parallax_layers) = world.components().typed::<ParallaxLayer>() else {
+			return;
+		};
+		let render_layers = world.components().typed::<RenderLayers>();
+		let transforms = world.components().typed::<Transform2D>();
+		let camera_layers = self.camera.render_layers();
+		let camera_position = self.camera.position();
+		let view = self.camera.visible_world_rect();
+		let (view_min, view_max) = (view.position().as_vec(), view.position().as_vec() + view.size());
+		let tint = self.global_tint.to_f32_array();
+
+		let mut layers: Vec<(usize, &ParallaxLayer)> = parallax_layers.iter()
+			.filter(|(entity, _)| render_layers.and_then(|layers| layers.get(*entity)).copied().unwrap_or(RenderLayers::DEFAULT).intersects(&camera_layers))
+			.collect();
+		layers.sort_by_key(|(_, layer)| std::cmp::Reverse(layer.depth()));
+
+		for (entity, layer) in layers {
+			let region = self.get_texture_region(layer.texture());
+			let size = self.region_world_size(region);
+			if size.x() <= 0.0 || size.y() <= 0.0 {
+				continue;
+			}
+
+			let offset = transforms.and_then(|transforms| transforms.get(entity))
+				.map(|transform| transform.position().as_vec())
+				.unwrap_or(Vec2::ZERO);
+			let origin = Vec2::new(
+				camera_position.x() * (1.0 - layer.factor().x()),
+				camera_position.y() * (1.0 - layer.factor().y())
+			) + offset;
+
+			// the tiles along one axis covering the view, or just the one at the origin
+			let tiles = |repeat: bool, origin: f32, size: f32, min: f32, max: f32| {
+				if repeat {
+					((min - origin) / size - 0.5).floor() as i64..=((max - origin) / size + 0.5).ceil() as i64
+				} else {
+					0..=0
+				}
+			};
+			let tiles_x = tiles(layer.repeat_x(), origin.x(), size.x(), view_min.x(), view_max.x());
+			let tiles_y = tiles(layer.repeat_y(), origin.y(), size.y(), view_min.y(), view_max.y());
+			if tiles_x.clone().count() * tiles_y.clone().count() > MAX_PARALLAX_TILES {
+				warn_once!("A parallax layer needs more than {} tiles to fill the view, skipping it", MAX_PARALLAX_TILES);
+				continue;
+			}
+
+			for tile_y in tiles_y {
+				for tile_x in tiles_x.clone() {
+					let center = origin + Vec2::new(tile_x as f32 * size.x(), tile_y as f32 * size.y());
+					Self::push_quad(vertex_buffer, index_buffer, center, size * 0.5, region, tint, [0.0; 4]);
+				}
+			}
+		}
+	}
+
+	/// Records where every `Trail2D` moved since the last frame and appends their ribbons to the batch.
+	fn push_trails(&mut self, world: &World, vertex_buffer: &mut Vec<Vertex>, index_buffer: &mut Vec<u32>) {
+		let (Some(trails), Some(transforms)) = (world.components().typed::<Trail2D>(), world.components().typed::<Transform2D>()) else {
+			self.trails.clear();
+			return;
+		};
+		self.trails.retain(|entity, _| trails.contains(*entity as usize));
+		self.trail_paths.reset();
+
+		for (entity, trail) in trails.iter() {
+			if transforms.get(entity).is_none() {
+				continue;
+			}
+			let head = self.drawn_position(world, entity as u32);
+			let points = self.trails.entry(entity as u32).or_default();
+			for point in points.iter_mut() {
+				point.age += self.deltatime;
+			}
+			while points.back().is_some_and(|point| point.age >= trail.lifetime()) {
+				points.pop_back();
+			}
+			if trail.is_emitting() && points.front().is_none_or(|point| (point.position - head).length() >= trail.min_distance()) {
+				points.push_front(TrailPoint { position: head, age: 0.0 });
+			}
+
+			// the head always sits on the entity, even between recorded points
+			let path = self.trail_paths.alloc_extend(trail.is_emitting().then_some((head, 0.0)).into_iter()
+				.chain(points.iter()
+					.filter(|point| !trail.is_emitting() || point.position != head)
+					.map(|point| (point.position, point.age))));
+			if path.len() < 2 {
+				continue;
+			}
+
+			let Some(region) = self.graphic_resource_manager.texture_region(trail.texture()) else {
+				warn_once!("The texture of a Trail2D is not in the atlas, it is not drawn");
+				continue;
+			};
+			let buffer_size = vertex_buffer.len() as u32;
+			let last = path.len() - 1;
+			for (i, (position, age)) in path.iter().enumerate() {
+				let delta = path[i.saturating_sub(1)].0 - path[(i + 1).min(last)].0;
+				let direction = if delta.length() > f32::EPSILON { delta.normalize() } else { Vec2::X };
+				let t = (age / trail.lifetime()).clamp(0.0, 1.0);
+				let half_width = trail.width_at(t) * 0.5;
+				let offset = Vec2::new(-direction.y(), direction.x()) * half_width;
+				let u = region.x0() + (region.x1() - region.x0()) * i as f32 / last as f32;
+				let color = [1.0, 1.0, 1.0, 1.0 - t];
+				let left = *position + offset;
+				let right = *position - offset;
+				vertex_buffer.extend_from_slice(&[
+					Vertex::new([left.x(), left.y(), 0.0], [u, region.y0()], color),
+					Vertex::new([right.x(), right.y(), 0.0], [u, region.y1()], color)
+				]);
+			}
+			for i in 0..last as u32 {
+				let first = buffer_size + i * 2;
+				index_buffer.extend_from_slice(&[
+					first, first + 1, first + 2,
+					first + 1, first + 3, first + 2
 				]);
+			}
+		}
+	}
 
-				index_buffer.append(&mut vec![
+	/// Queues the blob shadows of the `sprites` for the shadow pass and appends their silhouette shadows to the batch.
+	fn push_shadows(&mut self, world: &World, sprites: &[u32], vertex_buffer: &mut Vec<Vertex>, index_buffer: &mut Vec<u32>) {
+		let Some(shadows) = world.components().typed::<Shadow2D>() else {
+			return;
+		};
+
+		for entity in sprites {
+			let Some(shadow) = shadows.get(*entity as usize) else {
+				continue;
+			};
+			let region = self.get_texture_region(world.get_component::<Render2D>(*entity as usize).get_texture());
+			let size = self.region_world_size(region);
+			let position = self.drawn_position(world, *entity);
+			let feet = Vec2::new(position.x(), position.y() - size.y() * 0.5) + shadow.offset();
+			let half_size = Vec2::new(size.x() * shadow.scale().x() * 0.5, size.y() * shadow.scale().y() * 0.5);
+			let color = [0.0, 0.0, 0.0, shadow.opacity()];
+
+			if shadow.is_silhouette() {
+				// mirrored below the feet, the top of the texture lands at the bottom
+				let (x, y) = (feet.x(), feet.y() - half_size.y());
+				let (bound_x, bound_y) = (half_size.x(), half_size.y());
+				let buffer_size = vertex_buffer.len() as u32;
+				vertex_buffer.extend_from_slice(&[
+					Vertex :: new ( [-bound_x + x,  bound_y + y, 0.0], [region.x0(), region.y1()], color ),
+					Vertex :: new ( [-bound_x + x, -bound_y + y, 0.0], [region.x0(), region.y0()], color ),
+					Vertex :: new ( [ bound_x + x, -bound_y + y, 0.0], [region.x1(), region.y0()], color ),
+					Vertex :: new ( [ bound_x + x,  bound_y + y, 0.0], [region.x1(), region.y1()], color )
+				]);
+				index_buffer.extend_from_slice(&[
 					0 + buffer_size, 1 + buffer_size, 3 + buffer_size,
 					1 + buffer_size, 2 + buffer_size, 3 + buffer_size
 				]);
+				continue;
+			}
+
+			// a solid inner ellipse and a ring fading out to the rim
+			let rim = [0.0, 0.0, 0.0, 0.0];
+			let solid = 1.0 - shadow.softness();
+			let point = |i: usize, radius: f32| {
+				let angle = i as f32 / SHADOW_SEGMENTS as f32 * std::f32::consts::TAU;
+				[feet.x() + angle.cos() * half_size.x() * radius, feet.y() + angle.sin() * half_size.y() * radius]
+			};
+			for i in 0..SHADOW_SEGMENTS {
+				let (inner, next_inner) = (point(i, solid), point(i + 1, solid));
+				let (outer, next_outer) = (point(i, 1.0), point(i + 1, 1.0));
+				if solid > 0.0 {
+					self.shadow_pass.push_triangle([[feet.x(), feet.y()], inner, next_inner], color);
+				}
+				if solid < 1.0 {
+					self.shadow_pass.push_gradient_triangle([inner, outer, next_outer], [color, rim, rim]);
+					self.shadow_pass.push_gradient_triangle([inner, next_outer, next_inner], [color, rim, color]);
+				}
 			}
 		}
+	}
+
+	/// The visible sprites the camera shows, in the order they are drawn.
+	fn sprite_draw_order(&self, world: &World) -> Vec<u32> {
+		if world.components().typed::<Render2D>().is_none() {
+			return Vec::new();
+		}
+		let entities = world.get_entities_with(ComponentSet::from_ids(vec![Render2D::type_id()]));
+		let layers = world.components().typed::<RenderLayers>();
+		let y_sorts = world.components().typed::<YSort>();
+		let camera_layers = self.camera.render_layers();
+
+		let mut sprites: Vec<(Option<f32>, u32)> = entities.into_iter()
+			.filter(|entity| {
+				let entity_layers = layers.and_then(|layers| layers.get(*entity as usize)).copied().unwrap_or(RenderLayers::DEFAULT);
+				world.get_component::<Render2D>(*entity as usize).is_visible() && entity_layers.intersects(&camera_layers)
+			})
+			.map(|entity| {
+				let sort_key = y_sorts.and_then(|y_sorts| y_sorts.get(entity as usize)).map(|y_sort| {
+					let height = self.texture_world_size(world.get_component::<Render2D>(entity as usize).get_texture()).y();
+					self.drawn_position(world, entity).y() - height * 0.5 + y_sort.bias()
+				});
+				(sort_key, entity)
+			})
+			.collect();
+		// unsorted sprites first, then the y-sorted ones from the top of the screen down
+		sprites.sort_by(|(a, _), (b, _)| match (a, b) {
+			(Some(a), Some(b)) => b.total_cmp(a),
+			_ => a.is_some().cmp(&b.is_some())
+		});
+
+		// emissive sprites are blended differently, so they are drawn together at the end
+		let emissives = world.components().typed::<Emissive>();
+		let (emissive, mut sprites): (Vec<u32>, Vec<u32>) = sprites.into_iter()
+			.map(|(_, entity)| entity)
+			.partition(|entity| emissives.is_some_and(|emissives| emissives.contains(*entity as usize)));
+		sprites.extend(emissive);
+		sprites
+	}
+
+	/// The topmost visible sprite under `screen_position`, e.g. the mouse cursor, by the bounds of its texture.
+	pub fn pick(&self, screen_position: Vec2, world: &World) -> Option<u32> {
+		self.pick_sprite(screen_position, world, None)
+	}
+
+	/// Like `pick`, but only counts pixels of the texture with at least `min_alpha`, so clicks through transparent corners miss.
+	pub fn pick_opaque(&self, screen_position: Vec2, world: &World, min_alpha: u8) -> Option<u32> {
+		self.pick_sprite(screen_position, world, Some(min_alpha))
+	}
+
+	fn pick_sprite(&self, screen_position: Vec2, world: &World, min_alpha: Option<u8>) -> Option<u32> {
+		if world.components().typed::<Render2D>().is_none() {
+			return None;
+		}
+		let point = self.screen_to_world(screen_position);
+		let atlas = self.graphic_resource_manager.texture_atlas();
+
+		self.sprite_draw_order(world).into_iter().rev().find(|entity| {
+			let position = self.drawn_position(world, *entity);
+			let region = self.get_texture_region(world.get_component::<Render2D>(*entity as usize).get_texture());
+			let scale = world.get_component::<Render2D>(*entity as usize).scale();
+			let (sin, cos) = world.get_component::<Transform2D>(*entity as usize).rotation().angle().sin_cos();
+			// the point relative to the pivot, undoing the rotation and scale of the sprite
+			let offset = self.camera.units_to_pixels(point - position);
+			let local = Vec2::new(offset.x() * cos + offset.y() * sin, offset.y() * cos - offset.x() * sin);
+			let (x, y) = (local.x() / scale.x(), local.y() / scale.y());
+			let ((left, bottom), (right, top)) = region.pivot_bounds();
+			// 0 to 1 across the sprite, from its top left corner like the texture coordinates
+			let u = (x - left) / (right - left);
+			let v = (top - y) / (top - bottom);
+			if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+				return false;
+			}
+			match min_alpha {
+				Some(min_alpha) => atlas.alpha_at(region, u, v) >= min_alpha,
+				None => true
+			}
+		})
+	}
+
+	/// Remembers where the entities are before a fixed update, so the frames drawn until the next one
+	/// can move them smoothly from there. Entities with `NoInterpolation` are left out.
+	pub fn store_previous_transforms(&mut self, world: &World) {
+		self.previous_positions.clear();
+		let Some(transforms) = world.components().typed::<Transform2D>() else {
+			return;
+		};
+		let opted_out = world.components().typed::<NoInterpolation>();
+		for (entity, transform) in transforms.iter() {
+			if opted_out.is_some_and(|opted_out| opted_out.contains(entity)) {
+				continue;
+			}
+			self.previous_positions.insert(entity as u32, transform.position().as_vec());
+		}
+	}
+
+	/// How far the next frame is between the previous fixed update and the last one, from 0 to 1.
+	/// The sprites are drawn that far along the way between their positions after both.
+	pub fn set_interpolation(&mut self, alpha: f32) {
+		self.interpolation = alpha.clamp(0.0, 1.0);
+	}
+
+	/// The corners of the sprite of `entity` in the world from the top left counterclockwise, scaled and rotated around the pivot of its texture.
+	fn sprite_corners(&self, world: &World, entity: u32, region: &TextureRegion) -> [Vec2; 4] {
+		let position = self.drawn_position(world, entity);
+		let scale = world.get_component::<Render2D>(entity as usize).scale();
+		let angle = world.get_component::<Transform2D>(entity as usize).rotation().angle();
+		self.corners_at(position, scale, angle, region)
+	}
+
+	/// The corners of a quad showing `region` with its pivot at `position`, scaled and then rotated by `angle` around it.
+	fn corners_at(&self, position: Vec2, scale: Vec2, angle: f32, region: &TextureRegion) -> [Vec2; 4] {
+		let (sin, cos) = angle.sin_cos();
+		let ((left, bottom), (right, top)) = region.pivot_bounds();
+
+		[(left, top), (left, bottom), (right, bottom), (right, top)].map(|(x, y)| {
+			let local = self.camera.pixels_to_units(Vec2::new(x * scale.x(), y * scale.y()));
+			position + Vec2::new(local.x() * cos - local.y() * sin, local.x() * sin + local.y() * cos)
+		})
+	}
+
+	/// Where the sprite of `entity` is drawn, in world units.
+	fn drawn_position(&self, world: &World, entity: u32) -> Vec2 {
+		let position = world.get_component::<Transform2D>(entity as usize).position().as_vec();
+		match self.previous_positions.get(&entity) {
+			Some(previous) if self.interpolation < 1.0 => *previous + (position - *previous) * self.interpolation,
+			_ => position
+		}
+	}
+
+	/// A function to automatically render all the entities of the `World` struct.
+	/// The entities must have the `Render2D` and `Transform2D` components to be rendered as well as set visible.
+	pub fn render_scene_2d(&mut self, world: &World) {
+		profile_scope!("render_scene_2d");
+		let sprites = self.sprite_draw_order(world);
+		let mut vertex_buffer: Vec<Vertex> = Vec::with_capacity(sprites.len() * 4);
+		let mut index_buffer: Vec<u32> = Vec::with_capacity(sprites.len() * 6);
+		let flashes = world.components().typed::<FlashOnHit>();
+		let materials = world.components().typed::<SpriteMaterial>();
+		let effects = world.components().typed::<SpriteEffect>();
+
+		self.push_parallax_layers(world, &mut vertex_buffer, &mut index_buffer);
+		self.push_trails(world, &mut vertex_buffer, &mut index_buffer);
+		self.sprite_start = index_buffer.len() as u32;
+		self.shadow_pass.clear();
+		self.push_shadows(world, &sprites, &mut vertex_buffer, &mut index_buffer);
+
+		let emissives = world.components().typed::<Emissive>();
+		let tint = self.global_tint.to_f32_array();
+		self.emissive_start = u32::MAX;
+		self.push_layered(world, ..0, &mut vertex_buffer, &mut index_buffer);
+		for entity in sprites {
+			let emissive = emissives.and_then(|emissives| emissives.get(entity as usize));
+			if emissive.is_some() && self.emissive_start == u32::MAX {
+				// submitted quads go on top of the sprites, but below the additive emissive ones
+				self.push_layered(world, 0.., &mut vertex_buffer, &mut index_buffer);
+				self.emissive_start = index_buffer.len() as u32;
+			}
+			let renderer_component =  world.get_component::<Render2D>(entity as usize);
+			let region = self.get_texture_region(renderer_component.get_texture());
+			let corners = self.sprite_corners(world, entity, region);
+
+			let effect = effects.and_then(|effects| effects.get(entity as usize));
+			let hit_flash = flashes.and_then(|flashes| flashes.get(entity as usize)).map_or([0.0; 4], FlashOnHit::current);
+			// a running hit flash wins over a flash effect that stays on
+			let flash = match effect {
+				Some(effect) if effect.kind() == SpriteEffectKind::Flash && hit_flash[3] <= 0.0 => effect.color(),
+				_ => hit_flash
+			};
+			let color = emissive.map_or(tint, |emissive| [emissive.intensity(), emissive.intensity(), emissive.intensity(), 1.0]);
+			Self::push_corners(&mut vertex_buffer, &mut index_buffer, corners, region, color, flash);
+
+			let material = materials.and_then(|materials| materials.get(entity as usize));
+			if material.is_some() || effect.is_some() {
+				let (mut dissolve, mut outline_width, mut outline) = material.map_or((0.0, 0.0, [0.0; 4]), |material| (material.dissolve(), material.outline_width(), material.outline_color()));
+				let mut grayscale = 0.0;
+				if let Some(effect) = effect {
+					match effect.kind() {
+						SpriteEffectKind::Outline => (outline_width, outline) = (effect.amount(), effect.color()),
+						SpriteEffectKind::Dissolve => dissolve = dissolve.max(effect.amount()),
+						SpriteEffectKind::Grayscale => grayscale = effect.amount(),
+						SpriteEffectKind::Flash | SpriteEffectKind::None => {}
+					}
+				}
+				let quad = vertex_buffer.len() - 4;
+				for vertex in vertex_buffer[quad..].iter_mut() {
+					vertex.set_material(dissolve, outline_width, outline);
+					vertex.set_grayscale(grayscale);
+				}
+			}
+			if renderer_component.has_uv_transform() {
+				let (offset, scale, scroll) = (renderer_component.uv_offset(), renderer_component.uv_scale(), renderer_component.uv_scroll());
+				let quad = vertex_buffer.len() - 4;
+				// the same corners as in `push_corners`, from the top left counterclockwise
+				for (vertex, (u, v)) in vertex_buffer[quad..].iter_mut().zip([(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)]) {
+					vertex.set_tex_coords([offset.x() + u * scale.x(), offset.y() + v * scale.y()]);
+					vertex.set_region([region.x0(), region.y0(), region.x1(), region.y1()]);
+					vertex.set_scroll([scroll.x(), scroll.y()]);
+				}
+			}
+		}
+		if self.emissive_start == u32::MAX {
+			self.push_layered(world, 0.., &mut vertex_buffer, &mut index_buffer);
+		}
 
 		self.set_buffers(vertex_buffer, index_buffer);
 	}
@@ -774,14 +1821,42 @@ impl<'a> Renderer2D<'a> {
 		let now = Instant::now();
 		self.deltatime = now.duration_since(self.last_frame_time).as_secs_f32();  // Time delta in seconds
 		self.last_frame_time = now;
+
+		if let Some(animation) = &mut self.clear_animation {
+			self.clear_color = animation.advance(self.deltatime);
+		}
+
+		let deltatime = self.deltatime;
+		self.screen_flashes.retain_mut(|flash| flash.advance(deltatime));
+		if self.tonemap_pass.as_mut().is_some_and(|tonemap_pass| tonemap_pass.advance_lut_fade(&self.device, deltatime)) {
+			self.update_tonemapping();
+		}
+		if self.video.as_mut().is_some_and(|video| !video.advance(&self.queue, deltatime)) {
+			self.video = None;
+		}
+
+		self.time += self.deltatime;
+		self.camera.update(self.deltatime);
+		self.camera_uniform.update_view_proj(&self.camera);
+		self.camera_uniform.set_time(self.time);
+		self.queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[self.camera_uniform]));
 		self.deltatime
 	}
 
 	pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-		let output = self.surface.get_current_texture()?;
-		let view = output
+		let Some(surface) = &self.surface else {
+			return Ok(());
+		};
+
+		let output = surface.get_current_texture()?;
+		self.push_screen_flashes();
+		let surface_view = output
 			.texture
 			.create_view(&wgpu::TextureViewDescriptor::default());
+		let view = match &self.tonemap_pass {
+			Some(tonemap_pass) => tonemap_pass.view(),
+			None => &surface_view
+		};
 
 		let mut encoder = self
 			.device
@@ -789,14 +1864,37 @@ impl<'a> Renderer2D<'a> {
 				label: Some("Render Encoder"),
 			});
 
+		let load = match self.camera.clear_mode() {
+			ClearMode::Background => wgpu::LoadOp::Clear(self.clear_color.to_wgpu()),
+			ClearMode::Color(color) => wgpu::LoadOp::Clear(color.to_wgpu()),
+			ClearMode::Keep => wgpu::LoadOp::Load
+		};
+
+		let has_shadows = self.shadow_pass.upload(&self.device, &self.queue);
+		let sprite_start = self.sprite_start.min(self.num_indices);
+		let emissive_start = self.emissive_start.clamp(sprite_start, self.num_indices);
+		let view_rect = self.camera.visible_world_rect();
+		let (view_min, view_max) = (view_rect.position().as_vec(), view_rect.position().as_vec() + view_rect.size());
+		let culled = match &mut self.gpu_culling {
+			Some(gpu_culling) if !self.index_buffer.is_empty() => gpu_culling.cull(
+				&self.device,
+				&self.queue,
+				&mut encoder,
+				(self.vertex_buffer.buffer(), self.index_buffer.buffer()),
+				sprite_start..emissive_start,
+				(view_min, view_max)
+			),
+			_ => false
+		};
+
 		for pipeline in &self.pipelines {
 			let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
 				label: Some("Render Pass"),
 				color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-					view: &view,
+					view,
 					resolve_target: None,
 					ops: wgpu::Operations {
-						load: wgpu::LoadOp::Clear(self.clear_color),
+						load,
 						store: wgpu::StoreOp::Store,
 					},
 				})],
@@ -805,14 +1903,52 @@ impl<'a> Renderer2D<'a> {
 				timestamp_writes: None,
 			});
 
-			render_pass.set_pipeline(pipeline);
-			render_pass.set_bind_group(0, &self.diffuse_bind_group, &[]);
-			render_pass.set_bind_group(1, &self.camera_bind_group, &[]);
-			render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-			render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-			render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+			// empty buffer slices are not allowed
+			if self.index_buffer.is_empty() {
+				if has_shadows {
+					self.shadow_pass.draw(&mut render_pass, &self.camera_bind_group);
+				}
+				continue;
+			}
+
+			let bind_batch = |render_pass: &mut wgpu::RenderPass| {
+				render_pass.set_pipeline(pipeline);
+				render_pass.set_bind_group(BindingSlot::TEXTURE.index(), &self.diffuse_bind_group, &[]);
+				render_pass.set_bind_group(BindingSlot::CAMERA.index(), &self.camera_bind_group, &[]);
+				render_pass.set_vertex_buffer(0, self.vertex_buffer.slice());
+				render_pass.set_index_buffer(self.index_buffer.slice(), wgpu::IndexFormat::Uint32);
+			};
+			// the background of the batch, the shadows on top of it, then the sprites
+			bind_batch(&mut render_pass);
+			if sprite_start > 0 {
+				render_pass.draw_indexed(0..sprite_start, 0, 0..1);
+			}
+			if has_shadows {
+				self.shadow_pass.draw(&mut render_pass, &self.camera_bind_group);
+				bind_batch(&mut render_pass);
+			}
+			match self.gpu_culling.as_ref().filter(|_| culled) {
+				Some(gpu_culling) => gpu_culling.draw(&mut render_pass),
+				None => render_pass.draw_indexed(sprite_start..emissive_start, 0, 0..1)
+			}
+			if emissive_start < self.num_indices {
+				render_pass.set_pipeline(&self.emissive_pipeline);
+				render_pass.draw_indexed(emissive_start..self.num_indices, 0, 0..1);
+			}
+		}
+
+		self.mask_pass.render(&self.device, &self.queue, &mut encoder, view, &self.camera_bind_group);
+		Self::record_custom_draws(&mut encoder, view, "Custom Scene Pass", std::mem::take(&mut self.scene_draws), &self.resources);
+
+		if let Some(tonemap_pass) = &self.tonemap_pass {
+			tonemap_pass.render(&mut encoder, &surface_view);
+		}
+		if let Some(video) = &self.video {
+			video.render(&self.queue, &mut encoder, &surface_view, (self.config.width, self.config.height));
 		}
 
+		Self::record_custom_draws(&mut encoder, &surface_view, "Custom Overlay Pass", std::mem::take(&mut self.overlay_draws), &self.resources);
+
 		self.queue.submit(iter::once(encoder.finish()));
 		output.present();
 
@@ -822,8 +1958,12 @@ impl<'a> Renderer2D<'a> {
 
 impl<'a> Renderer for Renderer2D<'a> {
 
-	async fn new(window: Arc<Window>, clear_color: Option<LinearRgba>) -> Renderer2D<'a> {
-		Self::new(window, clear_color).await
+	async fn new(window: Arc<Window>, settings: RendererSettings) -> Renderer2D<'a> {
+		Self::new(window, settings).await
+	}
+
+	async fn try_new(window: Arc<Window>, settings: RendererSettings) -> Result<Renderer2D<'a>, RendererError> {
+		Self::try_new(window, settings).await
 	}
 
 	fn size(&self) -> PhysicalSize<u32> {
@@ -841,4 +1981,63 @@ impl<'a> Renderer for Renderer2D<'a> {
 	fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
 		self.render()
 	}
+
+	fn adapter_info(&self) -> AdapterInfo {
+		self.adapter_info()
+	}
+
+	fn prepare(&mut self, world: &World) {
+		// worlds without sprites, meshes or submitted quads, e.g. of apps drawing only custom passes, have nothing to batch
+		if world.components().typed::<Render2D>().is_some() || world.components().typed::<Mesh2D>().is_some() || !self.submitted.is_empty() {
+			self.render_scene_2d(world)
+		}
+	}
+
+	fn queue_custom(&mut self, pass: PassHandle, callback: CustomDraw) {
+		self.queue_custom(pass, callback)
+	}
+
+	fn world_to_screen(&self, point: Vec2) -> Vec2 {
+		self.world_to_screen(point)
+	}
+
+	fn screen_to_world(&self, point: Vec2) -> Vec2 {
+		self.screen_to_world(point)
+	}
+
+	fn pick(&self, screen_position: Vec2, world: &World) -> Option<u32> {
+		self.pick(screen_position, world)
+	}
+
+	fn set_scale_factor(&mut self, scale_factor: f64) {
+		self.set_scale_factor(scale_factor)
+	}
+
+	fn set_accessibility(&mut self, accessibility: &Accessibility) {
+		self.set_accessibility(accessibility)
+	}
+
+	fn set_global_tint(&mut self, tint: LinearRgba) {
+		self.set_global_tint(tint)
+	}
+
+	fn store_previous_transforms(&mut self, world: &World) {
+		self.store_previous_transforms(world)
+	}
+
+	fn clear_submitted(&mut self) {
+		self.clear_submitted()
+	}
+
+	fn set_interpolation(&mut self, alpha: f32) {
+		self.set_interpolation(alpha)
+	}
+
+	fn suspend(&mut self) {
+		self.suspend()
+	}
+
+	fn resume(&mut self) {
+		self.resume()
+	}
 }
\ No newline at end of file
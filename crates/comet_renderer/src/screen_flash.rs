@@ -0,0 +1,69 @@
+use comet_colors::{Color, LinearRgba};
+
+/// A color washing over the whole screen and fading out, e.g. red when the player gets hurt.
+/// With a vignette the flash is strongest at the edges of the screen and clear in its center.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScreenFlash {
+	color: LinearRgba,
+	duration: f32,
+	vignette: f32
+}
+
+impl ScreenFlash {
+	/// A flash of `color` fading out over `duration` seconds, starting as opaque as the alpha of `color`.
+	pub fn new(color: impl Color, duration: f32) -> Self {
+		Self {
+			color: color.to_linear(),
+			duration,
+			vignette: 0.0
+		}
+	}
+
+	/// How clear the center of the screen stays, from 0 (an even flash) to 1 (only the edges).
+	pub fn with_vignette(mut self, vignette: f32) -> Self {
+		self.vignette = vignette.clamp(0.0, 1.0);
+		self
+	}
+
+	pub fn color(&self) -> &LinearRgba {
+		&self.color
+	}
+
+	pub fn duration(&self) -> f32 {
+		self.duration
+	}
+
+	pub fn vignette(&self) -> f32 {
+		self.vignette
+	}
+}
+
+/// A `ScreenFlash` that is currently shown.
+pub(crate) struct ActiveFlash {
+	flash: ScreenFlash,
+	elapsed: f32
+}
+
+impl ActiveFlash {
+	pub fn new(flash: ScreenFlash) -> Self {
+		Self {
+			flash,
+			elapsed: 0.0
+		}
+	}
+
+	/// Moves the flash on by `dt` seconds and returns whether it is still visible.
+	pub fn advance(&mut self, dt: f32) -> bool {
+		self.elapsed += dt;
+		self.elapsed < self.flash.duration
+	}
+
+	/// The colors at the edges and in the center of the screen right now.
+	pub fn colors(&self) -> ([f32; 4], [f32; 4]) {
+		let fade = if self.flash.duration > 0.0 { 1.0 - self.elapsed / self.flash.duration } else { 0.0 };
+		let [r, g, b, a] = self.flash.color.to_f32_array();
+		let edge = [r, g, b, a * fade.max(0.0)];
+		let center = [r, g, b, edge[3] * (1.0 - self.flash.vignette)];
+		(edge, center)
+	}
+}
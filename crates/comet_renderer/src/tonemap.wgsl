@@ -0,0 +1,150 @@
+struct TonemapSettings {
+    mode: u32,
+    exposure: f32,
+    palette_size: u32,
+    bloom_intensity: f32,
+    grading: u32,
+    lut_blend: f32,
+    lut_sizes: vec2<f32>,
+    color_blind_filter: u32,
+    _padding0: u32,
+    _padding1: u32,
+    _padding2: u32,
+};
+
+@group(0) @binding(0)
+var hdr_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var hdr_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> settings: TonemapSettings;
+@group(0) @binding(3)
+var palette_texture: texture_2d<f32>;
+// black when the bloom is off
+@group(0) @binding(4)
+var bloom_texture: texture_2d<f32>;
+// the color grading crossfades from the first to the second lut by lut_blend
+@group(0) @binding(5)
+var lut_from: texture_3d<f32>;
+@group(0) @binding(6)
+var lut_to: texture_3d<f32>;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// a single triangle that covers the whole screen
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    out.clip_position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+fn reinhard(color: vec3<f32>) -> vec3<f32> {
+    return color / (vec3<f32>(1.0) + color);
+}
+
+// Krzysztof Narkowicz's fit of the ACES filmic curve
+fn aces(color: vec3<f32>) -> vec3<f32> {
+    let a = 2.51;
+    let b = 0.03;
+    let c = 2.43;
+    let d = 0.59;
+    let e = 0.14;
+    return clamp((color * (a * color + b)) / (color * (c * color + d) + e), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+// replaces the color by the closest one in the palette, compared in gamma space since it is closer to how we see
+fn palette_swap(color: vec3<f32>) -> vec3<f32> {
+    let gamma = pow(color, vec3<f32>(1.0 / 2.2));
+    var closest = color;
+    var closest_distance = 1e9;
+    for (var i = 0u; i < settings.palette_size; i++) {
+        let candidate = textureLoad(palette_texture, vec2<i32>(i32(i), 0), 0).rgb;
+        let difference = pow(candidate, vec3<f32>(1.0 / 2.2)) - gamma;
+        let distance = dot(difference, difference);
+        if distance < closest_distance {
+            closest_distance = distance;
+            closest = candidate;
+        }
+    }
+    return closest;
+}
+
+// looks the color up between the texel centers, so the ends of the table map to black and white exactly
+fn lut_lookup(lut: texture_3d<f32>, size: f32, color: vec3<f32>) -> vec3<f32> {
+    let coordinates = color * ((size - 1.0) / size) + vec3<f32>(0.5 / size);
+    return textureSampleLevel(lut, hdr_sampler, coordinates, 0.0).rgb;
+}
+
+// luts are made from images, so they map gamma encoded colors
+fn color_grade(color: vec3<f32>) -> vec3<f32> {
+    let encoded = pow(clamp(color, vec3<f32>(0.0), vec3<f32>(1.0)), vec3<f32>(1.0 / 2.2));
+    let graded_from = lut_lookup(lut_from, settings.lut_sizes.x, encoded);
+    let graded_to = lut_lookup(lut_to, settings.lut_sizes.y, encoded);
+    return pow(mix(graded_from, graded_to, settings.lut_blend), vec3<f32>(2.2));
+}
+
+// shifts the colors a color blind player confuses towards ones they can tell apart, after Fidaner et al.
+fn daltonize(color: vec3<f32>, kind: u32) -> vec3<f32> {
+    let rgb = pow(clamp(color, vec3<f32>(0.0), vec3<f32>(1.0)), vec3<f32>(1.0 / 2.2));
+    let lms = vec3<f32>(
+        dot(rgb, vec3<f32>(17.8824, 43.5161, 4.11935)),
+        dot(rgb, vec3<f32>(3.45565, 27.1554, 3.86714)),
+        dot(rgb, vec3<f32>(0.0299566, 0.184309, 1.46709))
+    );
+    // what the player sees, with the missing kind of cone rebuilt from the other two
+    var seen = lms;
+    switch kind {
+        case 1u: {
+            seen.x = 2.02344 * lms.y - 2.52581 * lms.z;
+        }
+        case 2u: {
+            seen.y = 0.494207 * lms.x + 1.24827 * lms.z;
+        }
+        default: {
+            seen.z = -0.395913 * lms.x + 0.801109 * lms.y;
+        }
+    }
+    let simulated = vec3<f32>(
+        dot(seen, vec3<f32>(0.0809444479, -0.130504409, 0.116721066)),
+        dot(seen, vec3<f32>(-0.0102485335, 0.0540193266, -0.113614708)),
+        dot(seen, vec3<f32>(-0.000365296938, -0.00412161469, 0.693511405))
+    );
+    // the lost difference is moved into the channels the player still sees
+    let error = rgb - simulated;
+    let shift = vec3<f32>(0.0, 0.7 * error.r + error.g, 0.7 * error.r + error.b);
+    return pow(clamp(rgb + shift, vec3<f32>(0.0), vec3<f32>(1.0)), vec3<f32>(2.2));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let sample = textureSample(hdr_texture, hdr_sampler, in.uv);
+    let bloom = textureSample(bloom_texture, hdr_sampler, in.uv).rgb * settings.bloom_intensity;
+    var color = (sample.rgb + bloom) * settings.exposure;
+    switch settings.mode {
+        case 1u: {
+            color = reinhard(color);
+        }
+        case 2u: {
+            color = aces(color);
+        }
+        default: {
+            color = clamp(color, vec3<f32>(0.0), vec3<f32>(1.0));
+        }
+    }
+    if settings.grading > 0u {
+        color = color_grade(color);
+    }
+    if settings.palette_size > 0u {
+        color = palette_swap(color);
+    }
+    if settings.color_blind_filter > 0u {
+        color = daltonize(color, settings.color_blind_filter);
+    }
+    return vec4<f32>(color, sample.a);
+}
@@ -0,0 +1,492 @@
+use comet_colors::ColorLut;
+use crate::accessibility::ColorBlindFilter;
+use crate::bloom::{Bloom, BloomPass};
+
+/// How colors brighter than white are brought into the displayable range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tonemapping {
+	/// Everything brighter than white is clipped.
+	#[default]
+	None,
+	/// Compresses bright colors smoothly, but washes out the highlights a bit.
+	Reinhard,
+	/// The filmic curve of the Academy Color Encoding System, with more contrast and saturated highlights.
+	Aces
+}
+
+impl Tonemapping {
+	fn mode(&self) -> u32 {
+		match self {
+			Tonemapping::None => 0,
+			Tonemapping::Reinhard => 1,
+			Tonemapping::Aces => 2
+		}
+	}
+}
+
+/// The format of the target the scene is drawn to before tonemapping.
+pub(crate) const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct TonemapUniform {
+	mode: u32,
+	exposure: f32,
+	palette_size: u32,
+	bloom_intensity: f32,
+	grading: u32,
+	lut_blend: f32,
+	lut_sizes: [f32; 2],
+	color_blind_filter: u32,
+	_padding: [u32; 3]
+}
+
+/// A color grading LUT on the GPU.
+struct LutTexture {
+	view: wgpu::TextureView,
+	size: u32,
+	active: bool
+}
+
+impl LutTexture {
+	/// Uploads `lut`, or an identity LUT that is not applied for `None`.
+	fn new(device: &wgpu::Device, queue: &wgpu::Queue, lut: Option<&ColorLut>) -> Self {
+		let identity;
+		let texels = match lut {
+			Some(lut) => lut,
+			None => {
+				identity = ColorLut::identity(2);
+				&identity
+			}
+		};
+		let size = wgpu::Extent3d {
+			width: texels.size(),
+			height: texels.size(),
+			depth_or_array_layers: texels.size(),
+		};
+		let texture = device.create_texture(&wgpu::TextureDescriptor {
+			label: Some("LUT Texture"),
+			size,
+			mip_level_count: 1,
+			sample_count: 1,
+			dimension: wgpu::TextureDimension::D3,
+			format: wgpu::TextureFormat::Rgba8Unorm,
+			usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+			view_formats: &[],
+		});
+		queue.write_texture(
+			wgpu::ImageCopyTexture {
+				texture: &texture,
+				mip_level: 0,
+				origin: wgpu::Origin3d::ZERO,
+				aspect: wgpu::TextureAspect::All,
+			},
+			bytemuck::cast_slice(texels.texels()),
+			wgpu::ImageDataLayout {
+				offset: 0,
+				bytes_per_row: Some(4 * texels.size()),
+				rows_per_image: Some(texels.size()),
+			},
+			size,
+		);
+		Self {
+			view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+			size: texels.size(),
+			active: lut.is_some()
+		}
+	}
+}
+
+/// Blends from the first to the second LUT of the tonemap pass.
+struct LutFade {
+	elapsed: f32,
+	duration: f32
+}
+
+/// Draws the HDR scene target onto the surface, adding the bloom and applying the tonemapping curve and the palette swap.
+pub(crate) struct TonemapPass {
+	pipeline: wgpu::RenderPipeline,
+	bind_group_layout: wgpu::BindGroupLayout,
+	sampler: wgpu::Sampler,
+	uniform_buffer: wgpu::Buffer,
+	view: wgpu::TextureView,
+	size: (u32, u32),
+	palette: wgpu::TextureView,
+	palette_size: u32,
+	bloom: Option<BloomPass>,
+	no_bloom: wgpu::TextureView,
+	luts: [LutTexture; 2],
+	lut_fade: Option<LutFade>,
+	bind_group: wgpu::BindGroup
+}
+
+impl TonemapPass {
+	/// Whether the adapter can render to and sample from float textures.
+	pub fn is_supported(adapter: &wgpu::Adapter) -> bool {
+		adapter.get_texture_format_features(HDR_FORMAT).allowed_usages
+			.contains(wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING)
+	}
+
+	pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, surface_format: wgpu::TextureFormat, width: u32, height: u32) -> Self {
+		let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+			label: Some("Tonemap Shader"),
+			source: wgpu::ShaderSource::Wgsl(include_str!("tonemap.wgsl").into()),
+		});
+
+		let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+			entries: &[
+				wgpu::BindGroupLayoutEntry {
+					binding: 0,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Texture {
+						multisampled: false,
+						view_dimension: wgpu::TextureViewDimension::D2,
+						sample_type: wgpu::TextureSampleType::Float { filterable: true },
+					},
+					count: None,
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 1,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+					count: None,
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 2,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Buffer {
+						ty: wgpu::BufferBindingType::Uniform,
+						has_dynamic_offset: false,
+						min_binding_size: None,
+					},
+					count: None,
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 3,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Texture {
+						multisampled: false,
+						view_dimension: wgpu::TextureViewDimension::D2,
+						sample_type: wgpu::TextureSampleType::Float { filterable: false },
+					},
+					count: None,
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 4,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Texture {
+						multisampled: false,
+						view_dimension: wgpu::TextureViewDimension::D2,
+						sample_type: wgpu::TextureSampleType::Float { filterable: true },
+					},
+					count: None,
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 5,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Texture {
+						multisampled: false,
+						view_dimension: wgpu::TextureViewDimension::D3,
+						sample_type: wgpu::TextureSampleType::Float { filterable: true },
+					},
+					count: None,
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 6,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Texture {
+						multisampled: false,
+						view_dimension: wgpu::TextureViewDimension::D3,
+						sample_type: wgpu::TextureSampleType::Float { filterable: true },
+					},
+					count: None,
+				},
+			],
+			label: Some("tonemap_bind_group_layout"),
+		});
+
+		let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+			label: Some("Tonemap Pipeline Layout"),
+			bind_group_layouts: &[&bind_group_layout],
+			push_constant_ranges: &[],
+		});
+
+		let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+			label: Some("Tonemap Pipeline"),
+			layout: Some(&pipeline_layout),
+			vertex: wgpu::VertexState {
+				module: &shader,
+				entry_point: "vs_main",
+				buffers: &[],
+				compilation_options: Default::default(),
+			},
+			fragment: Some(wgpu::FragmentState {
+				module: &shader,
+				entry_point: "fs_main",
+				targets: &[Some(wgpu::ColorTargetState {
+					format: surface_format,
+					blend: None,
+					write_mask: wgpu::ColorWrites::ALL,
+				})],
+				compilation_options: Default::default(),
+			}),
+			primitive: wgpu::PrimitiveState::default(),
+			depth_stencil: None,
+			multisample: wgpu::MultisampleState::default(),
+			multiview: None,
+			cache: None,
+		});
+
+		let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+			label: Some("Tonemap Sampler"),
+			mag_filter: wgpu::FilterMode::Linear,
+			min_filter: wgpu::FilterMode::Linear,
+			..Default::default()
+		});
+
+		let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+			label: Some("Tonemap Buffer"),
+			size: std::mem::size_of::<TonemapUniform>() as u64,
+			usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+			mapped_at_creation: false,
+		});
+
+		let view = Self::create_target(device, width, height);
+		// the shader needs textures bound even if there is no palette or bloom
+		let palette = Self::create_palette(device, queue, &[[0; 4]]);
+		let no_bloom = Self::create_palette(device, queue, &[[0; 4]]);
+		let luts = [LutTexture::new(device, queue, None), LutTexture::new(device, queue, None)];
+		let bind_group = Self::create_bind_group(device, &bind_group_layout, &view, &sampler, &uniform_buffer, &palette, &no_bloom, &luts);
+
+		Self {
+			pipeline,
+			bind_group_layout,
+			sampler,
+			uniform_buffer,
+			view,
+			size: (width, height),
+			palette,
+			palette_size: 0,
+			bloom: None,
+			no_bloom,
+			luts,
+			lut_fade: None,
+			bind_group
+		}
+	}
+
+	/// A texture one pixel high with one pixel per color.
+	fn create_palette(device: &wgpu::Device, queue: &wgpu::Queue, colors: &[[u8; 4]]) -> wgpu::TextureView {
+		let size = wgpu::Extent3d {
+			width: colors.len() as u32,
+			height: 1,
+			depth_or_array_layers: 1,
+		};
+		let texture = device.create_texture(&wgpu::TextureDescriptor {
+			label: Some("Palette Texture"),
+			size,
+			mip_level_count: 1,
+			sample_count: 1,
+			dimension: wgpu::TextureDimension::D2,
+			format: wgpu::TextureFormat::Rgba8UnormSrgb,
+			usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+			view_formats: &[],
+		});
+		queue.write_texture(
+			wgpu::ImageCopyTexture {
+				texture: &texture,
+				mip_level: 0,
+				origin: wgpu::Origin3d::ZERO,
+				aspect: wgpu::TextureAspect::All,
+			},
+			bytemuck::cast_slice(colors),
+			wgpu::ImageDataLayout {
+				offset: 0,
+				bytes_per_row: Some(4 * colors.len() as u32),
+				rows_per_image: Some(1),
+			},
+			size,
+		);
+		texture.create_view(&wgpu::TextureViewDescriptor::default())
+	}
+
+	/// Swaps every color on screen with the closest one of `colors`. An empty slice turns the swap off.
+	pub fn set_palette(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, colors: &[[u8; 4]]) {
+		let max_size = device.limits().max_texture_dimension_2d as usize;
+		let colors = &colors[..colors.len().min(max_size)];
+		self.palette = Self::create_palette(device, queue, if colors.is_empty() { &[[0; 4]] } else { colors });
+		self.palette_size = colors.len() as u32;
+		self.rebuild_bind_group(device);
+	}
+
+	pub fn bloom(&self) -> Option<Bloom> {
+		self.bloom.as_ref().map(BloomPass::settings)
+	}
+
+	/// Turns the bloom on with `bloom`, or off with `None`.
+	pub fn set_bloom(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, bloom: Option<Bloom>) {
+		let (width, height) = self.size;
+		match (&mut self.bloom, bloom) {
+			(Some(pass), Some(bloom)) => pass.set_settings(device, queue, bloom, &self.view, width, height),
+			(None, Some(bloom)) => self.bloom = Some(BloomPass::new(device, queue, bloom, &self.view, width, height)),
+			(_, None) => self.bloom = None
+		}
+		self.rebuild_bind_group(device);
+	}
+
+	fn create_target(device: &wgpu::Device, width: u32, height: u32) -> wgpu::TextureView {
+		device.create_texture(&wgpu::TextureDescriptor {
+			label: Some("HDR Target"),
+			size: wgpu::Extent3d {
+				width: width.max(1),
+				height: height.max(1),
+				depth_or_array_layers: 1,
+			},
+			mip_level_count: 1,
+			sample_count: 1,
+			dimension: wgpu::TextureDimension::D2,
+			format: HDR_FORMAT,
+			usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+			view_formats: &[],
+		}).create_view(&wgpu::TextureViewDescriptor::default())
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	fn create_bind_group(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, view: &wgpu::TextureView, sampler: &wgpu::Sampler, uniform_buffer: &wgpu::Buffer, palette: &wgpu::TextureView, bloom: &wgpu::TextureView, luts: &[LutTexture; 2]) -> wgpu::BindGroup {
+		device.create_bind_group(&wgpu::BindGroupDescriptor {
+			layout,
+			entries: &[
+				wgpu::BindGroupEntry {
+					binding: 0,
+					resource: wgpu::BindingResource::TextureView(view),
+				},
+				wgpu::BindGroupEntry {
+					binding: 1,
+					resource: wgpu::BindingResource::Sampler(sampler),
+				},
+				wgpu::BindGroupEntry {
+					binding: 2,
+					resource: uniform_buffer.as_entire_binding(),
+				},
+				wgpu::BindGroupEntry {
+					binding: 3,
+					resource: wgpu::BindingResource::TextureView(palette),
+				},
+				wgpu::BindGroupEntry {
+					binding: 4,
+					resource: wgpu::BindingResource::TextureView(bloom),
+				},
+				wgpu::BindGroupEntry {
+					binding: 5,
+					resource: wgpu::BindingResource::TextureView(&luts[0].view),
+				},
+				wgpu::BindGroupEntry {
+					binding: 6,
+					resource: wgpu::BindingResource::TextureView(&luts[1].view),
+				},
+			],
+			label: Some("tonemap_bind_group"),
+		})
+	}
+
+	/// Grades the colors on screen with `lut` right away. `None` turns the grading off.
+	pub fn set_lut(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, lut: Option<&ColorLut>) {
+		self.luts = [LutTexture::new(device, queue, lut), LutTexture::new(device, queue, None)];
+		self.lut_fade = None;
+		self.rebuild_bind_group(device);
+	}
+
+	/// Blends over to grading with `lut` in `duration` seconds.
+	/// A fade that is still running is cut short, the new one starts from the LUT it was heading to.
+	pub fn crossfade_lut(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, lut: Option<&ColorLut>, duration: f32) {
+		if self.lut_fade.is_some() {
+			self.luts.swap(0, 1);
+		}
+		self.luts[1] = LutTexture::new(device, queue, lut);
+		self.lut_fade = Some(LutFade { elapsed: 0.0, duration: duration.max(0.0) });
+		self.rebuild_bind_group(device);
+	}
+
+	/// Moves a running crossfade forward by `dt` seconds, returning whether the blend changed.
+	pub fn advance_lut_fade(&mut self, device: &wgpu::Device, dt: f32) -> bool {
+		let Some(fade) = &mut self.lut_fade else {
+			return false;
+		};
+		fade.elapsed += dt;
+		if fade.elapsed >= fade.duration {
+			// the target becomes the only LUT, so an empty target turns the grading off
+			self.luts.swap(0, 1);
+			self.lut_fade = None;
+			self.rebuild_bind_group(device);
+		}
+		true
+	}
+
+	fn lut_blend(&self) -> f32 {
+		match &self.lut_fade {
+			Some(fade) if fade.duration > 0.0 => (fade.elapsed / fade.duration).clamp(0.0, 1.0),
+			Some(_) => 1.0,
+			None => 0.0
+		}
+	}
+
+	fn rebuild_bind_group(&mut self, device: &wgpu::Device) {
+		let bloom = self.bloom.as_ref().map_or(&self.no_bloom, BloomPass::view);
+		self.bind_group = Self::create_bind_group(device, &self.bind_group_layout, &self.view, &self.sampler, &self.uniform_buffer, &self.palette, bloom, &self.luts);
+	}
+
+	/// Recreates the scene target with the new size of the surface.
+	pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+		self.view = Self::create_target(device, width, height);
+		self.size = (width, height);
+		if let Some(bloom) = &mut self.bloom {
+			bloom.resize(device, &self.view, width, height);
+		}
+		self.rebuild_bind_group(device);
+	}
+
+	/// The view the scene is drawn to.
+	pub fn view(&self) -> &wgpu::TextureView {
+		&self.view
+	}
+
+	pub fn update(&self, queue: &wgpu::Queue, tonemapping: Tonemapping, exposure: f32, color_blind_filter: ColorBlindFilter) {
+		let uniform = TonemapUniform {
+			mode: tonemapping.mode(),
+			exposure,
+			palette_size: self.palette_size,
+			bloom_intensity: self.bloom().map_or(0.0, |bloom| bloom.intensity()),
+			grading: (self.luts[0].active || (self.lut_fade.is_some() && self.luts[1].active)) as u32,
+			lut_blend: self.lut_blend(),
+			lut_sizes: [self.luts[0].size as f32, self.luts[1].size as f32],
+			color_blind_filter: color_blind_filter.mode(),
+			_padding: [0; 3]
+		};
+		queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+	}
+
+	pub fn render(&self, encoder: &mut wgpu::CommandEncoder, surface_view: &wgpu::TextureView) {
+		if let Some(bloom) = &self.bloom {
+			bloom.render(encoder);
+		}
+
+		let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+			label: Some("Tonemap Pass"),
+			color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+				view: surface_view,
+				resolve_target: None,
+				ops: wgpu::Operations {
+					load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+					store: wgpu::StoreOp::Store,
+				},
+			})],
+			depth_stencil_attachment: None,
+			occlusion_query_set: None,
+			timestamp_writes: None,
+		});
+
+		render_pass.set_pipeline(&self.pipeline);
+		render_pass.set_bind_group(0, &self.bind_group, &[]);
+		render_pass.draw(0..3, 0..1);
+	}
+}
@@ -0,0 +1,291 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use image::RgbaImage;
+use comet_log::*;
+use comet_resources::video::Video;
+
+/// How many decoded frames may wait for their turn.
+const FRAME_QUEUE: usize = 2;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct VideoUniform {
+	scale: [f32; 2],
+	_padding: [f32; 2]
+}
+
+/// Plays a `Video` over the whole window. The frames are decoded ahead on a thread of their own,
+/// which skips the frames it is already too late for instead of falling further behind.
+pub(crate) struct VideoPlayer {
+	pipeline: wgpu::RenderPipeline,
+	uniform_buffer: wgpu::Buffer,
+	bind_group: wgpu::BindGroup,
+	texture: wgpu::Texture,
+	dimensions: (u32, u32),
+	frame_rate: f32,
+	duration: f32,
+	elapsed: f32,
+	frames: Receiver<(usize, RgbaImage)>,
+	next_frame: Option<(usize, RgbaImage)>,
+	wanted_frame: Arc<AtomicUsize>
+}
+
+impl VideoPlayer {
+	pub fn new(device: &wgpu::Device, surface_format: wgpu::TextureFormat, mut video: Video) -> Self {
+		let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+			label: Some("Video Shader"),
+			source: wgpu::ShaderSource::Wgsl(include_str!("video.wgsl").into()),
+		});
+
+		let (width, height) = video.dimensions();
+		// the frames are stored as sRGB, which an sRGB surface expects to get back as linear colors
+		let format = if surface_format.is_srgb() { wgpu::TextureFormat::Rgba8UnormSrgb } else { wgpu::TextureFormat::Rgba8Unorm };
+		let texture = device.create_texture(&wgpu::TextureDescriptor {
+			label: Some("Video Frame Texture"),
+			size: wgpu::Extent3d {
+				width: width.max(1),
+				height: height.max(1),
+				depth_or_array_layers: 1,
+			},
+			mip_level_count: 1,
+			sample_count: 1,
+			dimension: wgpu::TextureDimension::D2,
+			format,
+			usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+			view_formats: &[],
+		});
+
+		let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+			entries: &[
+				wgpu::BindGroupLayoutEntry {
+					binding: 0,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Texture {
+						multisampled: false,
+						view_dimension: wgpu::TextureViewDimension::D2,
+						sample_type: wgpu::TextureSampleType::Float { filterable: true },
+					},
+					count: None,
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 1,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+					count: None,
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 2,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Buffer {
+						ty: wgpu::BufferBindingType::Uniform,
+						has_dynamic_offset: false,
+						min_binding_size: None,
+					},
+					count: None,
+				},
+			],
+			label: Some("video_bind_group_layout"),
+		});
+
+		let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+			label: Some("Video Sampler"),
+			address_mode_u: wgpu::AddressMode::ClampToEdge,
+			address_mode_v: wgpu::AddressMode::ClampToEdge,
+			mag_filter: wgpu::FilterMode::Linear,
+			min_filter: wgpu::FilterMode::Linear,
+			..Default::default()
+		});
+
+		let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+			label: Some("Video Buffer"),
+			size: std::mem::size_of::<VideoUniform>() as u64,
+			usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+			mapped_at_creation: false,
+		});
+
+		let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+			layout: &bind_group_layout,
+			entries: &[
+				wgpu::BindGroupEntry {
+					binding: 0,
+					resource: wgpu::BindingResource::TextureView(&texture.create_view(&wgpu::TextureViewDescriptor::default())),
+				},
+				wgpu::BindGroupEntry {
+					binding: 1,
+					resource: wgpu::BindingResource::Sampler(&sampler),
+				},
+				wgpu::BindGroupEntry {
+					binding: 2,
+					resource: uniform_buffer.as_entire_binding(),
+				},
+			],
+			label: Some("video_bind_group"),
+		});
+
+		let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+			label: Some("Video Pipeline Layout"),
+			bind_group_layouts: &[&bind_group_layout],
+			push_constant_ranges: &[],
+		});
+
+		let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+			label: Some("Video Pipeline"),
+			layout: Some(&pipeline_layout),
+			vertex: wgpu::VertexState {
+				module: &shader,
+				entry_point: "vs_main",
+				buffers: &[],
+				compilation_options: Default::default(),
+			},
+			fragment: Some(wgpu::FragmentState {
+				module: &shader,
+				entry_point: "fs_main",
+				targets: &[Some(wgpu::ColorTargetState {
+					format: surface_format,
+					blend: None,
+					write_mask: wgpu::ColorWrites::ALL,
+				})],
+				compilation_options: Default::default(),
+			}),
+			primitive: wgpu::PrimitiveState::default(),
+			depth_stencil: None,
+			multisample: wgpu::MultisampleState::default(),
+			multiview: None,
+			cache: None,
+		});
+
+		let frame_rate = video.frame_rate();
+		let duration = video.duration();
+		let frame_count = video.frame_count();
+		let wanted_frame = Arc::new(AtomicUsize::new(0));
+		let (sender, frames) = mpsc::sync_channel(FRAME_QUEUE);
+		let wanted = wanted_frame.clone();
+		std::thread::spawn(move || {
+			let mut index = 0;
+			while index < frame_count {
+				index = index.max(wanted.load(Ordering::Relaxed));
+				match video.frame(index) {
+					Ok(frame) => {
+						// the player is gone once the video is stopped
+						if sender.send((index, frame)).is_err() {
+							return;
+						}
+					}
+					Err(e) => {
+						error!("{}", e);
+						return;
+					}
+				}
+				index += 1;
+			}
+		});
+
+		Self {
+			pipeline,
+			uniform_buffer,
+			bind_group,
+			texture,
+			dimensions: (width, height),
+			frame_rate,
+			duration,
+			elapsed: 0.0,
+			frames,
+			next_frame: None,
+			wanted_frame
+		}
+	}
+
+	/// How far into the video the player is in seconds.
+	pub fn elapsed(&self) -> f32 {
+		self.elapsed
+	}
+
+	pub fn duration(&self) -> f32 {
+		self.duration
+	}
+
+	/// Moves the video on by `dt` seconds and uploads the frame that is due.
+	/// Returns whether the video is still playing.
+	pub fn advance(&mut self, queue: &wgpu::Queue, dt: f32) -> bool {
+		self.elapsed += dt;
+		let due = (self.elapsed * self.frame_rate) as usize;
+		self.wanted_frame.store(due, Ordering::Relaxed);
+
+		let mut latest = None;
+		loop {
+			if self.next_frame.is_none() {
+				match self.frames.try_recv() {
+					Ok(frame) => self.next_frame = Some(frame),
+					Err(_) => break
+				}
+			}
+			match self.next_frame.take() {
+				Some((index, frame)) if index <= due => latest = Some(frame),
+				frame => {
+					self.next_frame = frame;
+					break;
+				}
+			}
+		}
+
+		if let Some(frame) = latest {
+			self.upload(queue, &frame);
+		}
+		self.elapsed < self.duration
+	}
+
+	fn upload(&self, queue: &wgpu::Queue, frame: &RgbaImage) {
+		let (width, height) = self.dimensions;
+		queue.write_texture(
+			wgpu::ImageCopyTexture {
+				texture: &self.texture,
+				mip_level: 0,
+				origin: wgpu::Origin3d::ZERO,
+				aspect: wgpu::TextureAspect::All,
+			},
+			frame,
+			wgpu::ImageDataLayout {
+				offset: 0,
+				bytes_per_row: Some(4 * width),
+				rows_per_image: Some(height),
+			},
+			wgpu::Extent3d {
+				width,
+				height,
+				depth_or_array_layers: 1,
+			},
+		);
+	}
+
+	/// Draws the current frame over the whole `target`, with black bars where the aspect ratios differ.
+	pub fn render(&self, queue: &wgpu::Queue, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView, target_size: (u32, u32)) {
+		let video_aspect = self.dimensions.0 as f32 / self.dimensions.1.max(1) as f32;
+		let target_aspect = target_size.0 as f32 / target_size.1.max(1) as f32;
+		let scale = if video_aspect > target_aspect {
+			[1.0, target_aspect / video_aspect]
+		} else {
+			[video_aspect / target_aspect, 1.0]
+		};
+		queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[VideoUniform { scale, _padding: [0.0; 2] }]));
+
+		let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+			label: Some("Video Pass"),
+			color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+				view: target,
+				resolve_target: None,
+				ops: wgpu::Operations {
+					load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+					store: wgpu::StoreOp::Store,
+				},
+			})],
+			depth_stencil_attachment: None,
+			occlusion_query_set: None,
+			timestamp_writes: None,
+		});
+
+		render_pass.set_pipeline(&self.pipeline);
+		render_pass.set_bind_group(0, &self.bind_group, &[]);
+		render_pass.draw(0..3, 0..1);
+	}
+}
@@ -0,0 +1,38 @@
+// Video: shows the current frame of a video over the whole window, letterboxed to keep its aspect ratio
+struct VideoUniform {
+    // the part of the window the frame covers on each axis
+    scale: vec2<f32>,
+    _padding: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var frame_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var frame_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> video: VideoUniform;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) position: vec2<f32>,
+};
+
+// a single triangle that covers the whole window
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    out.position = vec2<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0);
+    out.clip_position = vec4<f32>(out.position, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let frame = in.position / video.scale;
+    let uv = vec2<f32>(frame.x * 0.5 + 0.5, 0.5 - frame.y * 0.5);
+    let color = textureSampleLevel(frame_texture, frame_sampler, uv, 0.0);
+    // the bars around the frame stay black
+    let inside = all(abs(frame) <= vec2<f32>(1.0));
+    return select(vec4<f32>(0.0, 0.0, 0.0, 1.0), vec4<f32>(color.rgb, 1.0), inside);
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Mutex, OnceLock};
+#[cfg(target_os = "android")]
+use winit::platform::android::activity::AndroidApp;
+
+#[cfg(target_os = "android")]
+static ANDROID_APP: OnceLock<AndroidApp> = OnceLock::new();
+
+/// Gives the asset loader access to the APK. Called by `App::with_android_app`.
+#[cfg(target_os = "android")]
+pub fn set_android_app(app: AndroidApp) {
+	let _ = ANDROID_APP.set(app);
+}
+
+/// The bytes of the files read ahead of time with `cache_asset`, by path.
+static CACHE: OnceLock<Mutex<HashMap<String, Vec<u8>>>> = OnceLock::new();
+
+fn cache() -> std::sync::MutexGuard<'static, HashMap<String, Vec<u8>>> {
+	CACHE.get_or_init(|| Mutex::new(HashMap::new())).lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Reads the file into memory, so a later `read_asset` of it does not touch the disk. Safe to call from any thread.
+pub fn cache_asset(path: &str) -> io::Result<()> {
+	if is_asset_cached(path) {
+		return Ok(());
+	}
+	let bytes = read_uncached(path)?;
+	cache().insert(path.to_string(), bytes);
+	Ok(())
+}
+
+/// Frees the memory of a file cached with `cache_asset`.
+pub fn release_asset(path: &str) {
+	cache().remove(path);
+}
+
+pub fn is_asset_cached(path: &str) -> bool {
+	cache().contains_key(path)
+}
+
+/// Reads a file that ships with the game.
+/// On Android the file is read from the assets folder of the APK, on iOS from the app bundle
+/// and everywhere else relative to the working directory. Files cached with `cache_asset` come from memory.
+pub fn read_asset(path: &str) -> io::Result<Vec<u8>> {
+	if let Some(bytes) = cache().get(path) {
+		return Ok(bytes.clone());
+	}
+	read_uncached(path)
+}
+
+fn read_uncached(path: &str) -> io::Result<Vec<u8>> {
+	#[cfg(target_os = "android")]
+	{
+		use std::io::Read;
+		let app = ANDROID_APP.get().ok_or_else(|| io::Error::new(io::ErrorKind::Other, "The Android app was not set"))?;
+		let name = std::ffi::CString::new(path).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+		let mut asset = app.asset_manager().open(&name).ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.to_string()))?;
+		let mut bytes = Vec::new();
+		asset.read_to_end(&mut bytes)?;
+		Ok(bytes)
+	}
+
+	#[cfg(target_os = "ios")]
+	{
+		let bundle = std::env::current_exe()?.parent().map(|dir| dir.to_path_buf()).unwrap_or_default();
+		std::fs::read(bundle.join(path))
+	}
+
+	#[cfg(not(any(target_os = "android", target_os = "ios")))]
+	{
+		std::fs::read(path)
+	}
+}
+
+pub fn read_asset_to_string(path: &str) -> io::Result<String> {
+	String::from_utf8(read_asset(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
@@ -5,7 +5,7 @@ use std::{
 use wgpu::{naga, Device, FilterMode, Queue, ShaderModule, TextureFormat, TextureUsages};
 use wgpu::naga::ShaderStage;
 use crate::{texture, Texture};
-use crate::texture_atlas::{TextureAtlas, TextureRegion};
+use crate::texture_atlas::{TextureAtlas, TextureHandle, TextureRegion};
 
 pub struct GraphicResorceManager {
 	texture_atlas: TextureAtlas,
@@ -26,10 +26,19 @@ impl GraphicResorceManager {
 		&self.texture_atlas
 	}
 
-	pub fn texture_locations(&self) -> &HashMap<String, TextureRegion> {
+	pub fn texture_atlas_mut(&mut self) -> &mut TextureAtlas {
+		&mut self.texture_atlas
+	}
+
+	pub fn texture_locations(&self) -> &HashMap<TextureHandle, TextureRegion> {
 		&self.texture_atlas.textures()
 	}
 
+	/// Where the texture behind `handle` lies in the atlas.
+	pub fn texture_region(&self, handle: TextureHandle) -> Option<&TextureRegion> {
+		self.texture_atlas.region(handle)
+	}
+
 	pub fn data_files(&self) -> &HashMap<String, String> {
 		&self.data_files
 	}
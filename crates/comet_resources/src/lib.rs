@@ -7,6 +7,10 @@ pub mod texture;
 pub mod vertex;
 pub mod texture_atlas;
 pub mod graphic_resource_manager;
+pub mod assets;
+pub mod video;
+pub mod sprite_metadata;
+pub mod vector;
 mod material;
 /*use std::io::{BufReader, Cursor};
 use wgpu::util::DeviceExt;
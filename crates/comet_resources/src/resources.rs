@@ -4,7 +4,7 @@ use std::{
 
 use wgpu::{Device, FilterMode, Queue, TextureFormat, TextureUsages};
 use crate::{texture, Texture};
-use crate::texture_atlas::{TextureAtlas, TextureRegion};
+use crate::texture_atlas::{TextureAtlas, TextureHandle, TextureRegion};
 
 pub struct ResourceManager {
 	texture_atlas: TextureAtlas,
@@ -23,7 +23,7 @@ impl ResourceManager {
 		&self.texture_atlas
 	}
 
-	pub fn texture_locations(&self) -> &HashMap<String, TextureRegion> {
+	pub fn texture_locations(&self) -> &HashMap<TextureHandle, TextureRegion> {
 		&self.texture_atlas.textures()
 	}
 
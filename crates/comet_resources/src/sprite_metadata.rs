@@ -0,0 +1,108 @@
+use std::fmt;
+use serde_json::Value;
+
+/// Why sprite metadata could not be loaded.
+#[derive(Debug)]
+pub enum SpriteMetadataError {
+	Io(std::io::Error),
+	Json(serde_json::Error),
+	/// The file is valid JSON, but not what TexturePacker exports.
+	InvalidFormat(String)
+}
+
+impl fmt::Display for SpriteMetadataError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			SpriteMetadataError::Io(e) => write!(f, "Failed to read the sprite metadata: {}", e),
+			SpriteMetadataError::Json(e) => write!(f, "Invalid JSON in the sprite metadata: {}", e),
+			SpriteMetadataError::InvalidFormat(reason) => write!(f, "Invalid sprite metadata: {}", reason)
+		}
+	}
+}
+
+impl std::error::Error for SpriteMetadataError {}
+
+impl From<std::io::Error> for SpriteMetadataError {
+	fn from(e: std::io::Error) -> Self {
+		SpriteMetadataError::Io(e)
+	}
+}
+
+impl From<serde_json::Error> for SpriteMetadataError {
+	fn from(e: serde_json::Error) -> Self {
+		SpriteMetadataError::Json(e)
+	}
+}
+
+/// The pivot and trim of one sprite, as an artist set them up in TexturePacker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameMetadata {
+	/// The file name of the sprite, e.g. "hero/idle.png".
+	pub name: String,
+	/// The point the sprite is placed and rotated at, from 0 to 1 across the untrimmed image from its top left corner.
+	pub pivot: (f32, f32),
+	/// Where the top left corner of the trimmed image lies in the untrimmed one, in pixels.
+	pub trim_offset: (u32, u32),
+	/// The size of the image before its transparent border was trimmed away.
+	pub source_size: Option<(u32, u32)>
+}
+
+/// The frames of a TexturePacker data file in its JSON hash or JSON array format.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SpriteMetadata {
+	frames: Vec<FrameMetadata>
+}
+
+impl SpriteMetadata {
+	pub fn load(path: &str) -> Result<Self, SpriteMetadataError> {
+		let bytes = crate::assets::read_asset(path)?;
+		Self::from_json(&String::from_utf8_lossy(&bytes))
+	}
+
+	pub fn from_json(json: &str) -> Result<Self, SpriteMetadataError> {
+		let root: Value = serde_json::from_str(json)?;
+		let frames = match root.get("frames") {
+			Some(Value::Object(frames)) => frames.iter().map(|(name, frame)| parse_frame(name, frame)).collect::<Result<_, _>>()?,
+			Some(Value::Array(frames)) => frames.iter().map(|frame| {
+				let name = frame.get("filename").and_then(Value::as_str)
+					.ok_or_else(|| SpriteMetadataError::InvalidFormat("a frame has no filename".to_string()))?;
+				parse_frame(name, frame)
+			}).collect::<Result<_, _>>()?,
+			_ => return Err(SpriteMetadataError::InvalidFormat("there are no frames".to_string()))
+		};
+		Ok(Self { frames })
+	}
+
+	pub fn frames(&self) -> &[FrameMetadata] {
+		&self.frames
+	}
+
+	pub fn frame(&self, name: &str) -> Option<&FrameMetadata> {
+		self.frames.iter().find(|frame| frame.name == name)
+	}
+}
+
+fn parse_frame(name: &str, frame: &Value) -> Result<FrameMetadata, SpriteMetadataError> {
+	let number = |value: Option<&Value>, key: &str| value.and_then(|value| value.get(key)).and_then(Value::as_f64);
+	let pivot = frame.get("pivot");
+	let pivot = (number(pivot, "x").unwrap_or(0.5) as f32, number(pivot, "y").unwrap_or(0.5) as f32);
+
+	let trimmed = frame.get("trimmed").and_then(Value::as_bool).unwrap_or(false);
+	let (trim_offset, source_size) = if trimmed {
+		let sprite_source = frame.get("spriteSourceSize");
+		let source = frame.get("sourceSize");
+		match (number(sprite_source, "x"), number(sprite_source, "y"), number(source, "w"), number(source, "h")) {
+			(Some(x), Some(y), Some(w), Some(h)) => ((x as u32, y as u32), Some((w as u32, h as u32))),
+			_ => return Err(SpriteMetadataError::InvalidFormat(format!("the trimmed frame {} has no spriteSourceSize or sourceSize", name)))
+		}
+	} else {
+		((0, 0), None)
+	};
+
+	Ok(FrameMetadata {
+		name: name.to_string(),
+		pivot,
+		trim_offset,
+		source_size
+	})
+}
@@ -1,10 +1,47 @@
 use std::collections::HashMap;
-use std::path::Path;
 use std::time::Instant;
 use image::{DynamicImage, GenericImage, GenericImageView, ImageFormat};
 use comet_log::*;
 use wgpu::{Device, FilterMode, TextureFormat, TextureUsages};
 use crate::Texture;
+use crate::sprite_metadata::SpriteMetadata;
+
+/// A small, copyable reference to a texture in the atlas.
+/// It is the hash of the texture's path, so it can be created before the atlas is built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TextureHandle(u64);
+
+impl TextureHandle {
+	/// The handle of the texture loaded from `path`.
+	pub const fn from_name(path: &str) -> Self {
+		// FNV-1a, since it is simple enough to run at compile time
+		let bytes = path.as_bytes();
+		let mut hash: u64 = 0xcbf29ce484222325;
+		let mut i = 0;
+		while i < bytes.len() {
+			hash ^= bytes[i] as u64;
+			hash = hash.wrapping_mul(0x100000001b3);
+			i += 1;
+		}
+		Self(hash)
+	}
+
+	/// A plain white texture every atlas contains, for untextured geometry tinted by its vertex colors.
+	pub const WHITE: TextureHandle = TextureHandle::from_name(WHITE_TEXTURE);
+
+	pub fn id(&self) -> u64 {
+		self.0
+	}
+}
+
+/// The name `TextureHandle::WHITE` is the hash of. It is no path, so no file can take its place.
+const WHITE_TEXTURE: &str = "<white>";
+
+impl From<&str> for TextureHandle {
+	fn from(path: &str) -> Self {
+		Self::from_name(path)
+	}
+}
 
 #[derive(Debug)]
 pub struct TextureRegion {
@@ -12,7 +49,10 @@ pub struct TextureRegion {
 	y0: f32,
 	x1: f32,
 	y1: f32,
-	dimensions: (u32, u32)
+	dimensions: (u32, u32),
+	pivot: (f32, f32),
+	trim_offset: (u32, u32),
+	source_size: (u32, u32)
 }
 
 impl TextureRegion {
@@ -22,10 +62,28 @@ impl TextureRegion {
 			y0,
 			x1,
 			y1,
-			dimensions
+			dimensions,
+			pivot: (0.5, 0.5),
+			trim_offset: (0, 0),
+			source_size: dimensions
 		}
 	}
 
+	/// Places and rotates sprites of the texture at `pivot` instead of their center,
+	/// from 0 to 1 across the untrimmed image from its top left corner, e.g. `(0.5, 1.0)` for the feet of a character.
+	pub fn with_pivot(mut self, pivot: (f32, f32)) -> Self {
+		self.pivot = pivot;
+		self
+	}
+
+	/// Marks the texture as cut out of a larger image of `source_size` at `offset` pixels from its top left corner,
+	/// so trimming its transparent border does not move the sprite.
+	pub fn with_trim(mut self, offset: (u32, u32), source_size: (u32, u32)) -> Self {
+		self.trim_offset = offset;
+		self.source_size = source_size;
+		self
+	}
+
 	pub fn x0(&self) -> f32 {
 		self.x0
 	}
@@ -45,24 +103,75 @@ impl TextureRegion {
 	pub fn dimensions(&self) -> (u32, u32) {
 		self.dimensions
 	}
+
+	pub fn pivot(&self) -> (f32, f32) {
+		self.pivot
+	}
+
+	pub fn trim_offset(&self) -> (u32, u32) {
+		self.trim_offset
+	}
+
+	/// The size of the image before it was trimmed, the same as `dimensions` for untrimmed textures.
+	pub fn source_size(&self) -> (u32, u32) {
+		self.source_size
+	}
+
+	/// The corners of a sprite of the texture relative to its pivot in pixels, y pointing up:
+	/// `(left, bottom)` and `(right, top)`.
+	pub fn pivot_bounds(&self) -> ((f32, f32), (f32, f32)) {
+		let pivot_x = self.pivot.0 * self.source_size.0 as f32;
+		let pivot_y = self.pivot.1 * self.source_size.1 as f32;
+		let left = self.trim_offset.0 as f32 - pivot_x;
+		let top = pivot_y - self.trim_offset.1 as f32;
+		((left, top - self.dimensions.1 as f32), (left + self.dimensions.0 as f32, top))
+	}
 }
 
 #[derive(Debug)]
 pub struct TextureAtlas {
 	atlas: DynamicImage,
-	textures: HashMap<String, TextureRegion>,
+	textures: HashMap<TextureHandle, TextureRegion>,
+	paths: HashMap<TextureHandle, String>
 }
 
 impl TextureAtlas {
 	pub fn empty() -> Self {
 		Self {
 			atlas: DynamicImage::new(1,1, image::ColorType::Rgb8),
-			textures: HashMap::new()
+			textures: HashMap::new(),
+			paths: HashMap::new()
 		}
 	}
 
 	pub fn texture_paths(&self) -> Vec<String> {
-		self.textures.keys().map(|k| k.to_string()).collect()
+		self.paths.values().cloned().collect()
+	}
+
+	/// The path the texture behind `handle` was loaded from.
+	pub fn texture_path(&self, handle: TextureHandle) -> Option<&str> {
+		self.paths.get(&handle).map(String::as_str)
+	}
+
+	/// The size of the atlas textures of the given dimensions are packed into.
+	/// Textures of the same height share a row, the tallest row comes first.
+	pub fn packed_size(dimensions: &[(u32, u32)]) -> (u32, u32) {
+		let mut sorted = dimensions.to_vec();
+		sorted.sort_by(|a, b| b.1.cmp(&a.1));
+
+		let (mut width, mut height) = (0, 0);
+		let mut row: Option<(u32, u32)> = None;
+		for (texture_width, texture_height) in sorted {
+			row = match row {
+				Some((row_width, row_height)) if row_height == texture_height => Some((row_width + texture_width, row_height)),
+				_ => {
+					height += texture_height;
+					Some((texture_width, texture_height))
+				}
+			};
+			width = width.max(row.map_or(0, |(row_width, _)| row_width));
+		}
+		(width, height)
 	}
 
 	fn calculate_atlas_width(textures: &Vec<DynamicImage>) -> u32 {
@@ -110,19 +219,25 @@ impl TextureAtlas {
 	}
 
 	pub fn from_texture_paths(
-		paths: Vec<String>,
+		mut paths: Vec<String>,
 	) -> Self {
+		profile_scope!("atlas packing");
 		//let t0 = Instant::now();
 
 		let mut textures: Vec<DynamicImage> = Vec::new();
-		let mut regions: HashMap<String, TextureRegion> = HashMap::new();
+		let mut regions: HashMap<TextureHandle, TextureRegion> = HashMap::new();
 
 		info!("Loading textures...");
 
 		for path in &paths {
-			textures.push(image::open(&Path::new(path.as_str())).expect("Failed to load texture"));
+			let bytes = crate::assets::read_asset(path).expect("Failed to load texture");
+			textures.push(image::load_from_memory(&bytes).expect("Failed to load texture"));
 		}
 
+		// a few texels instead of one, so filtering at its center never reaches the neighbouring textures
+		textures.push(DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(3, 3, image::Rgba([255, 255, 255, 255]))));
+		paths.push(WHITE_TEXTURE.to_string());
+
 		info!("Textures loaded!");
 		info!("Sorting textures by height...");
 
@@ -149,7 +264,7 @@ impl TextureAtlas {
 			}
 			//base.copy_from(texture, x_offset, y_offset).expect("Nope, you propably failed the offets");
 			Self::insert_texture_at(&mut base, &texture, x_offset, y_offset);
-			regions.insert(path.to_string(), TextureRegion::new(
+			regions.insert(TextureHandle::from_name(path), TextureRegion::new(
 				x_offset as f32 / width as f32,
 				y_offset as f32 / height as f32,
 				(x_offset + texture.width()) as f32 / width as f32,
@@ -172,7 +287,11 @@ impl TextureAtlas {
 
 		TextureAtlas {
 			atlas: base,
-			textures: regions
+			textures: regions,
+			paths: sorted_paths.into_iter()
+				.filter(|path| path != WHITE_TEXTURE)
+				.map(|path| (TextureHandle::from_name(&path), path))
+				.collect()
 		}
 	}
 
@@ -180,7 +299,40 @@ impl TextureAtlas {
 		&self.atlas
 	}
 
-	pub fn textures(&self) -> &HashMap<String, TextureRegion> {
+	pub fn textures(&self) -> &HashMap<TextureHandle, TextureRegion> {
 		&self.textures
 	}
+
+	pub fn region(&self, handle: TextureHandle) -> Option<&TextureRegion> {
+		self.textures.get(&handle)
+	}
+
+	/// Gives the textures the pivots and trims of the frames in `metadata`. A frame belongs to the texture whose path
+	/// ends with its name, e.g. "hero/idle.png" to "resources/textures/hero/idle.png". Returns how many textures it matched.
+	pub fn apply_metadata(&mut self, metadata: &SpriteMetadata) -> usize {
+		let mut matched = 0;
+		for frame in metadata.frames() {
+			let Some(handle) = self.paths.iter()
+				.find(|(_, path)| path.replace('\\', "/").ends_with(&frame.name))
+				.map(|(handle, _)| *handle) else {
+				warn!("There is no texture for the frame {} in the texture atlas", frame.name);
+				continue;
+			};
+			if let Some(region) = self.textures.get_mut(&handle) {
+				region.pivot = frame.pivot;
+				region.trim_offset = frame.trim_offset;
+				region.source_size = frame.source_size.unwrap_or(region.dimensions);
+				matched += 1;
+			}
+		}
+		matched
+	}
+
+	/// The alpha of the pixel at `u`, `v` (0 to 1 from the top left corner) inside `region`.
+	pub fn alpha_at(&self, region: &TextureRegion, u: f32, v: f32) -> u8 {
+		let x = (region.x0() + (region.x1() - region.x0()) * u.clamp(0.0, 1.0)) * self.atlas.width() as f32;
+		let y = (region.y0() + (region.y1() - region.y0()) * v.clamp(0.0, 1.0)) * self.atlas.height() as f32;
+		let (x, y) = ((x as u32).min(self.atlas.width() - 1), (y as u32).min(self.atlas.height() - 1));
+		self.atlas.get_pixel(x, y).0[3]
+	}
 }
\ No newline at end of file
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use lyon::extra::parser::{ParserOptions, PathParser, Source};
+use lyon::geom::{point, Transform};
+use lyon::path::{Event, Path};
+use lyon::tessellation::{BuffersBuilder, FillOptions, FillTessellator, FillVertex, StrokeOptions, StrokeTessellator, StrokeVertex, VertexBuffers};
+
+/// Why a vector path could not be loaded or tessellated.
+#[derive(Debug)]
+pub enum VectorError {
+	Io(std::io::Error),
+	/// The path data of an SVG is malformed.
+	Parse(String),
+	/// The SVG file contains no `<path>` elements.
+	NoPaths,
+	Tessellation(String)
+}
+
+impl fmt::Display for VectorError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			VectorError::Io(e) => write!(f, "Failed to read the SVG: {}", e),
+			VectorError::Parse(reason) => write!(f, "Invalid SVG path data: {}", reason),
+			VectorError::NoPaths => write!(f, "The SVG contains no paths"),
+			VectorError::Tessellation(reason) => write!(f, "Failed to tessellate the path: {}", reason)
+		}
+	}
+}
+
+impl std::error::Error for VectorError {}
+
+impl From<std::io::Error> for VectorError {
+	fn from(e: std::io::Error) -> Self {
+		VectorError::Io(e)
+	}
+}
+
+/// Builds a `VectorPath` out of lines and curves, in world units with y pointing up.
+pub struct PathBuilder {
+	builder: lyon::path::Builder,
+	open: bool
+}
+
+impl PathBuilder {
+	pub fn new() -> Self {
+		Self {
+			builder: Path::builder(),
+			open: false
+		}
+	}
+
+	/// Starts a new sub path at `(x, y)`, leaving the current one open.
+	pub fn move_to(mut self, x: f32, y: f32) -> Self {
+		if self.open {
+			self.builder.end(false);
+		}
+		self.builder.begin(point(x, y));
+		self.open = true;
+		self
+	}
+
+	pub fn line_to(mut self, x: f32, y: f32) -> Self {
+		self.begin_if_closed();
+		self.builder.line_to(point(x, y));
+		self
+	}
+
+	pub fn quadratic_to(mut self, control: (f32, f32), to: (f32, f32)) -> Self {
+		self.begin_if_closed();
+		self.builder.quadratic_bezier_to(point(control.0, control.1), point(to.0, to.1));
+		self
+	}
+
+	pub fn cubic_to(mut self, control1: (f32, f32), control2: (f32, f32), to: (f32, f32)) -> Self {
+		self.begin_if_closed();
+		self.builder.cubic_bezier_to(point(control1.0, control1.1), point(control2.0, control2.1), point(to.0, to.1));
+		self
+	}
+
+	/// Closes the current sub path with a line back to where it started.
+	pub fn close(mut self) -> Self {
+		if self.open {
+			self.builder.end(true);
+			self.open = false;
+		}
+		self
+	}
+
+	pub fn build(mut self) -> VectorPath {
+		if self.open {
+			self.builder.end(false);
+		}
+		VectorPath::new(self.builder.build())
+	}
+
+	/// Drawing without `move_to` first starts at the origin.
+	fn begin_if_closed(&mut self) {
+		if !self.open {
+			self.builder.begin(point(0.0, 0.0));
+			self.open = true;
+		}
+	}
+}
+
+impl Default for PathBuilder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// A shape made of lines and curves that is turned into triangles with a `VectorTessellator`.
+#[derive(Debug, Clone)]
+pub struct VectorPath {
+	path: Path,
+	hash: u64
+}
+
+impl VectorPath {
+	fn new(path: Path) -> Self {
+		let mut hasher = DefaultHasher::new();
+		for event in path.iter() {
+			let points = match event {
+				Event::Begin { at } => vec![at],
+				Event::Line { to, .. } => vec![to],
+				Event::Quadratic { ctrl, to, .. } => vec![ctrl, to],
+				Event::Cubic { ctrl1, ctrl2, to, .. } => vec![ctrl1, ctrl2, to],
+				Event::End { close, .. } => {
+					close.hash(&mut hasher);
+					Vec::new()
+				}
+			};
+			points.len().hash(&mut hasher);
+			for point in points {
+				point.x.to_bits().hash(&mut hasher);
+				point.y.to_bits().hash(&mut hasher);
+			}
+		}
+
+		Self {
+			path,
+			hash: hasher.finish()
+		}
+	}
+
+	/// Parses the `d` attribute of an SVG `<path>`, e.g. `"M 0 0 L 10 0 L 10 10 Z"`.
+	/// SVG points y down, so the path is flipped to point y up like the world.
+	pub fn from_svg_data(data: &str) -> Result<Self, VectorError> {
+		let mut builder = Path::builder();
+		PathParser::new()
+			.parse(&ParserOptions::DEFAULT, &mut Source::new(data.chars()), &mut builder)
+			.map_err(|e| VectorError::Parse(e.to_string()))?;
+		Ok(Self::new(builder.build().transformed(&Transform::scale(1.0, -1.0))))
+	}
+
+	/// The paths of all `<path>` elements in a simple SVG file, in the order they appear.
+	/// Their styles, transforms and all other elements are ignored.
+	pub fn load_svg(path: &str) -> Result<Vec<Self>, VectorError> {
+		let bytes = crate::assets::read_asset(path)?;
+		let svg = String::from_utf8_lossy(&bytes);
+		let paths = svg.split("<path").skip(1)
+			.filter_map(|element| {
+				let element = &element[..element.find('>').unwrap_or(element.len())];
+				let start = element.find(" d=\"").map(|start| start + 4)
+					.or_else(|| element.find(" d='").map(|start| start + 4))?;
+				let quote = element.as_bytes()[start - 1] as char;
+				let length = element[start..].find(quote)?;
+				Some(Self::from_svg_data(&element[start..start + length]))
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+
+		if paths.is_empty() {
+			return Err(VectorError::NoPaths);
+		}
+		Ok(paths)
+	}
+
+	/// The path scaled around the origin, e.g. from the pixels of an SVG to world units.
+	pub fn scaled(self, factor: f32) -> Self {
+		Self::new(self.path.transformed(&Transform::scale(factor, factor)))
+	}
+
+	/// A hash of the lines and curves of the path, the same for equal paths.
+	pub fn hash(&self) -> u64 {
+		self.hash
+	}
+}
+
+/// How a `VectorPath` is turned into triangles. `tolerance` is how far the triangles may stray from curves, in world units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VectorStyle {
+	/// The inside of the path, by the non-zero rule.
+	Fill { tolerance: f32 },
+	/// A line of `width` along the path.
+	Stroke { width: f32, tolerance: f32 }
+}
+
+impl VectorStyle {
+	pub fn fill() -> Self {
+		VectorStyle::Fill { tolerance: FillOptions::DEFAULT_TOLERANCE }
+	}
+
+	pub fn stroke(width: f32) -> Self {
+		VectorStyle::Stroke { width, tolerance: StrokeOptions::DEFAULT_TOLERANCE }
+	}
+
+	pub fn with_tolerance(self, tolerance: f32) -> Self {
+		match self {
+			VectorStyle::Fill { .. } => VectorStyle::Fill { tolerance },
+			VectorStyle::Stroke { width, .. } => VectorStyle::Stroke { width, tolerance }
+		}
+	}
+
+	fn cache_key(&self, path: &VectorPath) -> u64 {
+		let mut hasher = DefaultHasher::new();
+		path.hash().hash(&mut hasher);
+		match self {
+			VectorStyle::Fill { tolerance } => (0u8, tolerance.to_bits(), 0u32).hash(&mut hasher),
+			VectorStyle::Stroke { width, tolerance } => (1u8, tolerance.to_bits(), width.to_bits()).hash(&mut hasher)
+		}
+		hasher.finish()
+	}
+}
+
+/// The triangles of a tessellated `VectorPath`, see `Mesh2D::from_tessellation`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Tessellation {
+	positions: Vec<[f32; 2]>,
+	/// Three indices into `positions` per triangle.
+	indices: Vec<u32>
+}
+
+impl Tessellation {
+	pub fn positions(&self) -> &[[f32; 2]] {
+		&self.positions
+	}
+
+	pub fn indices(&self) -> &[u32] {
+		&self.indices
+	}
+}
+
+/// Turns vector paths into triangles and keeps the results, so the same path in the same style is only tessellated once.
+#[derive(Default)]
+pub struct VectorTessellator {
+	fill: FillTessellator,
+	stroke: StrokeTessellator,
+	cache: HashMap<u64, Arc<Tessellation>>
+}
+
+impl VectorTessellator {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn tessellate(&mut self, path: &VectorPath, style: VectorStyle) -> Result<Arc<Tessellation>, VectorError> {
+		let key = style.cache_key(path);
+		if let Some(tessellation) = self.cache.get(&key) {
+			return Ok(tessellation.clone());
+		}
+
+		let mut buffers: VertexBuffers<[f32; 2], u32> = VertexBuffers::new();
+		let result = match style {
+			VectorStyle::Fill { tolerance } => self.fill.tessellate_path(
+				&path.path,
+				&FillOptions::tolerance(tolerance),
+				&mut BuffersBuilder::new(&mut buffers, |vertex: FillVertex| vertex.position().to_array())
+			),
+			VectorStyle::Stroke { width, tolerance } => self.stroke.tessellate_path(
+				&path.path,
+				&StrokeOptions::tolerance(tolerance).with_line_width(width),
+				&mut BuffersBuilder::new(&mut buffers, |vertex: StrokeVertex| vertex.position().to_array())
+			)
+		};
+		result.map_err(|e| VectorError::Tessellation(format!("{:?}", e)))?;
+
+		let tessellation = Arc::new(Tessellation {
+			positions: buffers.vertices,
+			indices: buffers.indices
+		});
+		self.cache.insert(key, tessellation.clone());
+		Ok(tessellation)
+	}
+
+	/// Forgets all tessellated paths, e.g. after leaving a level.
+	pub fn clear_cache(&mut self) {
+		self.cache.clear();
+	}
+
+	pub fn cached_count(&self) -> usize {
+		self.cache.len()
+	}
+}
@@ -5,7 +5,12 @@ use wgpu::Color;
 pub struct Vertex {
 	position: [f32; 3],
 	tex_coords: [f32; 2],
-	color: [f32; 4]
+	color: [f32; 4],
+	flash: [f32; 4],
+	region: [f32; 4],
+	scroll: [f32; 2],
+	material: [f32; 3],
+	outline: [f32; 4]
 }
 
 impl Vertex {
@@ -13,10 +18,43 @@ impl Vertex {
 		Self {
 			position,
 			tex_coords,
-			color
+			color,
+			flash: [0.0; 4],
+			region: [0.0; 4],
+			scroll: [0.0; 2],
+			material: [0.0; 3],
+			outline: [0.0; 4]
 		}
 	}
 
+	/// Mixes the color of the vertex towards the rgb of `flash` by its alpha, e.g. to flash a sprite white.
+	pub fn with_flash(mut self, flash: [f32; 4]) -> Self {
+		self.flash = flash;
+		self
+	}
+
+	/// Makes the texture coordinates relative to `region`, the corners `[x0, y0, x1, y1]` of a texture in the atlas,
+	/// and repeats the texture across it, e.g. `[0.0, 0.0] .. [2.0, 1.0]` shows it twice side by side.
+	/// Without a region the texture coordinates point into the atlas directly.
+	pub fn with_region(mut self, region: [f32; 4]) -> Self {
+		self.region = region;
+		self
+	}
+
+	/// Moves the texture coordinates by `scroll` per second in the vertex shader. Only makes sense together with `with_region`.
+	pub fn with_scroll(mut self, scroll: [f32; 2]) -> Self {
+		self.scroll = scroll;
+		self
+	}
+
+	/// Dissolves the sprite by `dissolve` from 0 to 1 and outlines it `outline_width` texels wide in the linear rgba `outline`.
+	pub fn with_material(mut self, dissolve: f32, outline_width: f32, outline: [f32; 4]) -> Self {
+		self.material[0] = dissolve;
+		self.material[1] = outline_width;
+		self.outline = outline;
+		self
+	}
+
 	pub fn set_position(&mut self, new_position: [f32;3]) {
 		self.position = new_position
 	}
@@ -29,6 +67,29 @@ impl Vertex {
 		self.color = new_color
 	}
 
+	pub fn set_flash(&mut self, new_flash: [f32; 4]) {
+		self.flash = new_flash
+	}
+
+	pub fn set_region(&mut self, new_region: [f32; 4]) {
+		self.region = new_region
+	}
+
+	pub fn set_scroll(&mut self, new_scroll: [f32; 2]) {
+		self.scroll = new_scroll
+	}
+
+	pub fn set_material(&mut self, dissolve: f32, outline_width: f32, outline: [f32; 4]) {
+		self.material[0] = dissolve;
+		self.material[1] = outline_width;
+		self.outline = outline
+	}
+
+	/// Desaturates the color of the vertex by `grayscale`, from 0 for none to 1 for fully gray.
+	pub fn set_grayscale(&mut self, grayscale: f32) {
+		self.material[2] = grayscale
+	}
+
 	pub fn desc() -> wgpu::VertexBufferLayout<'static> {
 		wgpu::VertexBufferLayout {
 			array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
@@ -48,6 +109,31 @@ impl Vertex {
 					offset: std::mem::size_of::<[f32; 5]>() as wgpu::BufferAddress,
 					shader_location: 2,
 					format: wgpu::VertexFormat::Float32x4,
+				},
+				wgpu::VertexAttribute {
+					offset: std::mem::size_of::<[f32; 9]>() as wgpu::BufferAddress,
+					shader_location: 3,
+					format: wgpu::VertexFormat::Float32x4,
+				},
+				wgpu::VertexAttribute {
+					offset: std::mem::size_of::<[f32; 13]>() as wgpu::BufferAddress,
+					shader_location: 4,
+					format: wgpu::VertexFormat::Float32x4,
+				},
+				wgpu::VertexAttribute {
+					offset: std::mem::size_of::<[f32; 17]>() as wgpu::BufferAddress,
+					shader_location: 5,
+					format: wgpu::VertexFormat::Float32x2,
+				},
+				wgpu::VertexAttribute {
+					offset: std::mem::size_of::<[f32; 19]>() as wgpu::BufferAddress,
+					shader_location: 6,
+					format: wgpu::VertexFormat::Float32x3,
+				},
+				wgpu::VertexAttribute {
+					offset: std::mem::size_of::<[f32; 22]>() as wgpu::BufferAddress,
+					shader_location: 7,
+					format: wgpu::VertexFormat::Float32x4,
 				}
 			]
 		}
@@ -0,0 +1,462 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::Path;
+use image::RgbaImage;
+
+/// Why a video could not be opened or decoded.
+#[derive(Debug)]
+pub enum VideoError {
+	Io(std::io::Error),
+	/// The file is not an AVI file or is broken.
+	InvalidFile(String),
+	/// The file uses a codec or sample format that cannot be decoded.
+	Unsupported(String),
+	/// A frame could not be decoded.
+	Decode(String)
+}
+
+impl fmt::Display for VideoError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			VideoError::Io(e) => write!(f, "Failed to read the video: {}", e),
+			VideoError::InvalidFile(reason) => write!(f, "Invalid video file: {}", reason),
+			VideoError::Unsupported(what) => write!(f, "Unsupported video: {}", what),
+			VideoError::Decode(e) => write!(f, "Failed to decode a video frame: {}", e)
+		}
+	}
+}
+
+impl std::error::Error for VideoError {}
+
+impl From<std::io::Error> for VideoError {
+	fn from(e: std::io::Error) -> Self {
+		VideoError::Io(e)
+	}
+}
+
+/// The sound track of a video as interleaved samples from -1.0 to 1.0.
+#[derive(Debug, Clone)]
+pub struct VideoAudio {
+	sample_rate: u32,
+	channels: u16,
+	samples: Vec<f32>
+}
+
+impl VideoAudio {
+	pub fn sample_rate(&self) -> u32 {
+		self.sample_rate
+	}
+
+	pub fn channels(&self) -> u16 {
+		self.channels
+	}
+
+	pub fn samples(&self) -> &[f32] {
+		&self.samples
+	}
+}
+
+trait VideoSource: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> VideoSource for T {}
+
+#[derive(PartialEq)]
+enum StreamKind {
+	Video,
+	Audio,
+	Other
+}
+
+struct Stream {
+	kind: StreamKind,
+	frame_rate: f32
+}
+
+struct AudioFormat {
+	tag: u16,
+	channels: u16,
+	sample_rate: u32,
+	bits: u16
+}
+
+/// How deep lists may nest. Real files go two levels deep, e.g. `movi` holding `rec ` lists.
+const MAX_LIST_DEPTH: usize = 4;
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_FLOAT: u16 = 3;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// What the chunks of an AVI file have told so far.
+#[derive(Default)]
+struct Parser {
+	dimensions: (u32, u32),
+	micros_per_frame: u32,
+	streams: Vec<Stream>,
+	codec: Option<[u8; 4]>,
+	audio_format: Option<AudioFormat>,
+	frames: Vec<Range<u64>>,
+	audio: Vec<u8>
+}
+
+/// A Motion JPEG video in an AVI file, e.g. an intro cutscene, with an optional PCM sound track.
+/// Such files come out of `ffmpeg -i intro.mp4 -c:v mjpeg -q:v 3 -c:a pcm_s16le intro.avi`.
+/// Only the positions of the frames are read up front, each frame is decoded when it is asked for.
+pub struct Video {
+	source: Box<dyn VideoSource>,
+	dimensions: (u32, u32),
+	frame_rate: f32,
+	frames: Vec<Range<u64>>,
+	audio: Option<VideoAudio>
+}
+
+impl Video {
+	/// Opens the video at `path`, reading its frames from the file as they are needed.
+	pub fn open(path: impl AsRef<Path>) -> Result<Self, VideoError> {
+		Self::from_source(Box::new(BufReader::new(File::open(path)?)))
+	}
+
+	/// Reads a video that is already in memory, e.g. from `include_bytes!`.
+	pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, VideoError> {
+		Self::from_source(Box::new(Cursor::new(bytes)))
+	}
+
+	fn from_source(mut source: Box<dyn VideoSource>) -> Result<Self, VideoError> {
+		let length = source.seek(SeekFrom::End(0))?;
+		let mut parser = Parser::default();
+
+		// files over a gigabyte continue in further RIFF chunks of the form AVIX
+		let mut position = 0;
+		while position + 12 <= length {
+			source.seek(SeekFrom::Start(position))?;
+			let (id, size) = read_chunk_header(&mut source)?;
+			let form = read_fourcc(&mut source)?;
+			let end = (position + 8 + size as u64).min(length);
+			match (&id, &form) {
+				(b"RIFF", b"AVI ") | (b"RIFF", b"AVIX") => parser.parse_chunks(source.as_mut(), position + 12, end, 0)?,
+				_ if position == 0 => return Err(VideoError::InvalidFile("not an AVI file".to_string())),
+				_ => {}
+			}
+			position = end + (size as u64 & 1);
+		}
+
+		let video_stream = parser.streams.iter().find(|stream| stream.kind == StreamKind::Video)
+			.ok_or_else(|| VideoError::InvalidFile("there is no video stream".to_string()))?;
+		let codec = parser.codec.unwrap_or(*b"????");
+		if !codec.eq_ignore_ascii_case(b"MJPG") {
+			return Err(VideoError::Unsupported(format!("the {} codec, only Motion JPEG (MJPG) can be played", String::from_utf8_lossy(&codec))));
+		}
+		if parser.frames.is_empty() {
+			return Err(VideoError::InvalidFile("the video has no frames".to_string()));
+		}
+
+		let frame_rate = match video_stream.frame_rate {
+			rate if rate > 0.0 => rate,
+			_ if parser.micros_per_frame > 0 => 1_000_000.0 / parser.micros_per_frame as f32,
+			_ => return Err(VideoError::InvalidFile("the frame rate is missing".to_string()))
+		};
+
+		let audio = match parser.audio_format.take() {
+			Some(format) if !parser.audio.is_empty() => Some(decode_audio(&format, &parser.audio)?),
+			_ => None
+		};
+
+		Ok(Self {
+			source,
+			dimensions: parser.dimensions,
+			frame_rate,
+			frames: parser.frames,
+			audio
+		})
+	}
+
+	/// The size of the frames in pixels.
+	pub fn dimensions(&self) -> (u32, u32) {
+		self.dimensions
+	}
+
+	/// The frames per second.
+	pub fn frame_rate(&self) -> f32 {
+		self.frame_rate
+	}
+
+	pub fn frame_count(&self) -> usize {
+		self.frames.len()
+	}
+
+	/// The length of the video in seconds.
+	pub fn duration(&self) -> f32 {
+		self.frames.len() as f32 / self.frame_rate
+	}
+
+	pub fn audio(&self) -> Option<&VideoAudio> {
+		self.audio.as_ref()
+	}
+
+	/// Takes the sound track out of the video, e.g. to hand it to the audio system.
+	pub fn take_audio(&mut self) -> Option<VideoAudio> {
+		self.audio.take()
+	}
+
+	/// Decodes the frame at `index`.
+	pub fn frame(&mut self, index: usize) -> Result<RgbaImage, VideoError> {
+		let range = self.frames.get(index).cloned()
+			.ok_or_else(|| VideoError::Decode(format!("there is no frame {}", index)))?;
+		let mut bytes = vec![0; (range.end - range.start) as usize];
+		self.source.seek(SeekFrom::Start(range.start))?;
+		self.source.read_exact(&mut bytes)?;
+
+		let frame = image::load_from_memory_with_format(&bytes, image::ImageFormat::Jpeg)
+			.map_err(|e| VideoError::Decode(e.to_string()))?
+			.to_rgba8();
+		if frame.dimensions() != self.dimensions {
+			return Err(VideoError::Decode(format!("frame {} is {:?} instead of {:?}", index, frame.dimensions(), self.dimensions)));
+		}
+		Ok(frame)
+	}
+}
+
+impl Parser {
+	fn parse_chunks(&mut self, source: &mut dyn VideoSource, start: u64, end: u64, depth: usize) -> Result<(), VideoError> {
+		if depth > MAX_LIST_DEPTH {
+			return Err(VideoError::InvalidFile("the lists are nested too deep".to_string()));
+		}
+		let mut position = start;
+		while position + 8 <= end {
+			source.seek(SeekFrom::Start(position))?;
+			let (id, size) = read_chunk_header(source)?;
+			let data = position + 8;
+			let chunk_end = (data + size as u64).min(end);
+
+			match &id {
+				b"LIST" => {
+					let list = read_fourcc(source)?;
+					if matches!(&list, b"hdrl" | b"strl" | b"movi" | b"rec ") {
+						self.parse_chunks(source, data + 4, chunk_end, depth + 1)?;
+					}
+				}
+				b"avih" => {
+					let header = read_bytes(source, 40)?;
+					self.micros_per_frame = u32_at(&header, 0);
+					self.dimensions = (u32_at(&header, 32), u32_at(&header, 36));
+				}
+				b"strh" => {
+					let header = read_bytes(source, 28)?;
+					let kind = match &header[0..4] {
+						b"vids" => StreamKind::Video,
+						b"auds" => StreamKind::Audio,
+						_ => StreamKind::Other
+					};
+					let (scale, rate) = (u32_at(&header, 20), u32_at(&header, 24));
+					let frame_rate = if scale > 0 { rate as f32 / scale as f32 } else { 0.0 };
+					self.streams.push(Stream { kind, frame_rate });
+				}
+				b"strf" => self.parse_stream_format(source)?,
+				_ => self.parse_data(source, &id, data..chunk_end)?
+			}
+
+			position = chunk_end + (size as u64 & 1);
+		}
+		Ok(())
+	}
+
+	/// Reads the format of the stream whose header came last.
+	fn parse_stream_format(&mut self, source: &mut dyn VideoSource) -> Result<(), VideoError> {
+		let index = self.streams.len().wrapping_sub(1);
+		let is_first = |kind| self.streams.iter().position(|stream| stream.kind == kind) == Some(index);
+		if is_first(StreamKind::Video) {
+			let header = read_bytes(source, 20)?;
+			self.codec = Some([header[16], header[17], header[18], header[19]]);
+			if self.dimensions == (0, 0) {
+				self.dimensions = (u32_at(&header, 4), (u32_at(&header, 8) as i32).unsigned_abs());
+			}
+		}
+		else if is_first(StreamKind::Audio) {
+			let header = read_bytes(source, 16)?;
+			self.audio_format = Some(AudioFormat {
+				tag: u16_at(&header, 0),
+				channels: u16_at(&header, 2),
+				sample_rate: u32_at(&header, 4),
+				bits: u16_at(&header, 14)
+			});
+		}
+		Ok(())
+	}
+
+	/// Keeps the chunks of the movie data, named after their stream like `00dc` for frames and `01wb` for sound.
+	fn parse_data(&mut self, source: &mut dyn VideoSource, id: &[u8; 4], data: Range<u64>) -> Result<(), VideoError> {
+		let Some(stream) = std::str::from_utf8(&id[0..2]).ok().and_then(|digits| digits.parse::<usize>().ok()) else {
+			return Ok(());
+		};
+		let kind = self.streams.get(stream).map(|stream| &stream.kind);
+		let is_first = |kind| self.streams.iter().position(|stream| stream.kind == kind) == Some(stream);
+
+		match (&id[2..4], kind) {
+			(b"dc" | b"db", Some(StreamKind::Video)) if is_first(StreamKind::Video) => {
+				// an empty chunk repeats the frame before it
+				let frame = match self.frames.last() {
+					Some(previous) if data.is_empty() => previous.clone(),
+					_ if data.is_empty() => return Ok(()),
+					_ => data
+				};
+				self.frames.push(frame);
+			}
+			(b"wb", Some(StreamKind::Audio)) if is_first(StreamKind::Audio) => {
+				let start = self.audio.len();
+				self.audio.resize(start + (data.end - data.start) as usize, 0);
+				source.read_exact(&mut self.audio[start..])?;
+			}
+			_ => {}
+		}
+		Ok(())
+	}
+}
+
+fn decode_audio(format: &AudioFormat, bytes: &[u8]) -> Result<VideoAudio, VideoError> {
+	let tag = match format.tag {
+		WAVE_FORMAT_EXTENSIBLE if format.bits == 32 => WAVE_FORMAT_FLOAT,
+		WAVE_FORMAT_EXTENSIBLE => WAVE_FORMAT_PCM,
+		tag => tag
+	};
+	let samples = match (tag, format.bits) {
+		(WAVE_FORMAT_PCM, 8) => bytes.iter().map(|&sample| (sample as f32 - 128.0) / 128.0).collect(),
+		(WAVE_FORMAT_PCM, 16) => bytes.chunks_exact(2).map(|sample| i16::from_le_bytes([sample[0], sample[1]]) as f32 / 32768.0).collect(),
+		(WAVE_FORMAT_FLOAT, 32) => bytes.chunks_exact(4).map(|sample| f32::from_le_bytes([sample[0], sample[1], sample[2], sample[3]])).collect(),
+		(tag, bits) => return Err(VideoError::Unsupported(format!("audio of format {:#x} with {} bits per sample, use 16 bit PCM", tag, bits)))
+	};
+	if format.channels == 0 || format.sample_rate == 0 {
+		return Err(VideoError::InvalidFile("the audio format is broken".to_string()));
+	}
+
+	Ok(VideoAudio {
+		sample_rate: format.sample_rate,
+		channels: format.channels,
+		samples
+	})
+}
+
+fn read_fourcc<R: Read + ?Sized>(source: &mut R) -> Result<[u8; 4], VideoError> {
+	let mut fourcc = [0; 4];
+	source.read_exact(&mut fourcc)?;
+	Ok(fourcc)
+}
+
+fn read_chunk_header<R: Read + ?Sized>(source: &mut R) -> Result<([u8; 4], u32), VideoError> {
+	let id = read_fourcc(source)?;
+	Ok((id, u32::from_le_bytes(read_fourcc(source)?)))
+}
+
+fn read_bytes<R: Read + ?Sized>(source: &mut R, count: usize) -> Result<Vec<u8>, VideoError> {
+	let mut bytes = vec![0; count];
+	source.read_exact(&mut bytes)
+		.map_err(|_| VideoError::InvalidFile("a header is cut short".to_string()))?;
+	Ok(bytes)
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+	u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+	u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+#[cfg(test)]
+mod tests {
+	use image::codecs::jpeg::JpegEncoder;
+	use image::Rgb;
+	use super::*;
+
+	fn chunk(id: &[u8; 4], data: &[u8]) -> Vec<u8> {
+		let mut bytes = id.to_vec();
+		bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+		bytes.extend_from_slice(data);
+		if data.len() % 2 == 1 {
+			bytes.push(0);
+		}
+		bytes
+	}
+
+	fn list(kind: &[u8; 4], children: &[Vec<u8>]) -> Vec<u8> {
+		let mut data = kind.to_vec();
+		children.iter().for_each(|child| data.extend_from_slice(child));
+		chunk(b"LIST", &data)
+	}
+
+	fn header(size: usize, fields: &[(usize, &[u8])]) -> Vec<u8> {
+		let mut header = vec![0; size];
+		for (offset, bytes) in fields {
+			header[*offset..*offset + bytes.len()].copy_from_slice(bytes);
+		}
+		header
+	}
+
+	fn jpeg(color: [u8; 3]) -> Vec<u8> {
+		let image = image::RgbImage::from_pixel(8, 4, Rgb(color));
+		let mut bytes = Vec::new();
+		JpegEncoder::new_with_quality(&mut bytes, 95).encode_image(&image).unwrap();
+		bytes
+	}
+
+	/// An 8x4 video at 10 frames per second of a red frame, the red frame again as an empty chunk,
+	/// and a blue frame inside a `rec ` list, with two chunks of 16 bit stereo PCM.
+	fn avi() -> Vec<u8> {
+		let video_header = header(56, &[(0, b"vids"), (4, b"MJPG"), (20, &1u32.to_le_bytes()), (24, &10u32.to_le_bytes())]);
+		let video_format = header(40, &[(0, &40u32.to_le_bytes()), (4, &8u32.to_le_bytes()), (8, &4u32.to_le_bytes()), (16, b"MJPG")]);
+		let audio_header = header(56, &[(0, b"auds"), (20, &1u32.to_le_bytes()), (24, &8000u32.to_le_bytes())]);
+		let audio_format = header(16, &[(0, &1u16.to_le_bytes()), (2, &2u16.to_le_bytes()), (4, &8000u32.to_le_bytes()), (14, &16u16.to_le_bytes())]);
+		let samples = |values: &[i16]| values.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<u8>>();
+
+		let mut data = b"AVI ".to_vec();
+		data.extend(list(b"hdrl", &[
+			chunk(b"avih", &header(56, &[(0, &100_000u32.to_le_bytes()), (32, &8u32.to_le_bytes()), (36, &4u32.to_le_bytes())])),
+			list(b"strl", &[chunk(b"strh", &video_header), chunk(b"strf", &video_format)]),
+			list(b"strl", &[chunk(b"strh", &audio_header), chunk(b"strf", &audio_format)])
+		]));
+		data.extend(list(b"movi", &[
+			chunk(b"00dc", &jpeg([255, 0, 0])),
+			chunk(b"01wb", &samples(&[16384, -16384])),
+			chunk(b"00dc", &[]),
+			list(b"rec ", &[chunk(b"00dc", &jpeg([0, 0, 255])), chunk(b"01wb", &samples(&[0, 32767]))])
+		]));
+		chunk(b"RIFF", &data)
+	}
+
+	#[test]
+	fn reads_frames_and_sound() {
+		let mut video = Video::from_bytes(avi()).unwrap();
+		assert_eq!(video.dimensions(), (8, 4));
+		assert_eq!(video.frame_rate(), 10.0);
+		assert_eq!(video.frame_count(), 3);
+		assert!((video.duration() - 0.3).abs() < 1e-6);
+
+		let first = video.frame(0).unwrap();
+		assert!(first.get_pixel(4, 2)[0] > 200 && first.get_pixel(4, 2)[2] < 50);
+		assert_eq!(video.frame(1).unwrap(), first);
+		let last = video.frame(2).unwrap();
+		assert!(last.get_pixel(4, 2)[2] > 200 && last.get_pixel(4, 2)[0] < 50);
+		assert!(matches!(video.frame(3), Err(VideoError::Decode(_))));
+
+		let audio = video.take_audio().unwrap();
+		assert_eq!((audio.sample_rate(), audio.channels()), (8000, 2));
+		assert_eq!(audio.samples(), &[0.5, -0.5, 0.0, 32767.0 / 32768.0]);
+		assert!(video.audio().is_none());
+	}
+
+	#[test]
+	fn rejects_other_files() {
+		let wave = chunk(b"RIFF", &[b"WAVE".as_slice(), &chunk(b"fmt ", &[0; 16])].concat());
+		assert!(matches!(Video::from_bytes(wave), Err(VideoError::InvalidFile(_))));
+		assert!(matches!(Video::from_bytes(b"not a video at all".to_vec()), Err(VideoError::InvalidFile(_))));
+	}
+
+	#[test]
+	fn rejects_lists_nested_too_deep() {
+		let mut nested = chunk(b"00dc", &jpeg([0, 255, 0]));
+		for _ in 0..=MAX_LIST_DEPTH + 1 {
+			nested = list(b"rec ", &[nested]);
+		}
+		let avi = chunk(b"RIFF", &[b"AVI ".as_slice(), &nested].concat());
+		assert!(matches!(Video::from_bytes(avi), Err(VideoError::InvalidFile(message)) if message.contains("nested")));
+	}
+}
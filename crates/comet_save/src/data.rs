@@ -0,0 +1,191 @@
+use comet_math::{Vec2, Vec3, Vec4};
+use crate::SaveError;
+
+/// Little endian byte buffer the save data is written into.
+pub struct SaveWriter {
+	bytes: Vec<u8>
+}
+
+impl Default for SaveWriter {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl SaveWriter {
+	pub fn new() -> Self {
+		Self {
+			bytes: Vec::new()
+		}
+	}
+
+	pub fn write_bytes(&mut self, bytes: &[u8]) {
+		self.bytes.extend_from_slice(bytes);
+	}
+
+	pub fn write<T: SaveData>(&mut self, value: &T) {
+		value.write(self);
+	}
+
+	pub fn into_bytes(self) -> Vec<u8> {
+		self.bytes
+	}
+}
+
+/// Reads back what a `SaveWriter` wrote.
+pub struct SaveReader<'a> {
+	bytes: &'a [u8],
+	position: usize
+}
+
+impl<'a> SaveReader<'a> {
+	pub fn new(bytes: &'a [u8]) -> Self {
+		Self {
+			bytes,
+			position: 0
+		}
+	}
+
+	pub fn read_bytes(&mut self, count: usize) -> Result<&'a [u8], SaveError> {
+		if self.position + count > self.bytes.len() {
+			return Err(SaveError::UnexpectedEnd);
+		}
+		let bytes = &self.bytes[self.position..self.position + count];
+		self.position += count;
+		Ok(bytes)
+	}
+
+	pub fn read<T: SaveData>(&mut self) -> Result<T, SaveError> {
+		T::read(self)
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.position >= self.bytes.len()
+	}
+}
+
+/// Types that can be written into and read from save files.
+/// Implement this for every component or game state that should be part of a save.
+pub trait SaveData: Sized {
+	fn write(&self, writer: &mut SaveWriter);
+	fn read(reader: &mut SaveReader) -> Result<Self, SaveError>;
+}
+
+macro_rules! impl_save_data_for_number {
+	($($t:ty),*) => {
+		$(
+			impl SaveData for $t {
+				fn write(&self, writer: &mut SaveWriter) {
+					writer.write_bytes(&self.to_le_bytes());
+				}
+
+				fn read(reader: &mut SaveReader) -> Result<Self, SaveError> {
+					let bytes = reader.read_bytes(std::mem::size_of::<$t>())?;
+					Ok(<$t>::from_le_bytes(bytes.try_into().unwrap()))
+				}
+			}
+		)*
+	};
+}
+
+impl_save_data_for_number!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+impl SaveData for bool {
+	fn write(&self, writer: &mut SaveWriter) {
+		writer.write(&(*self as u8));
+	}
+
+	fn read(reader: &mut SaveReader) -> Result<Self, SaveError> {
+		match reader.read::<u8>()? {
+			0 => Ok(false),
+			1 => Ok(true),
+			value => Err(SaveError::Corrupted(format!("{} is not a bool", value)))
+		}
+	}
+}
+
+impl SaveData for String {
+	fn write(&self, writer: &mut SaveWriter) {
+		writer.write(&(self.len() as u32));
+		writer.write_bytes(self.as_bytes());
+	}
+
+	fn read(reader: &mut SaveReader) -> Result<Self, SaveError> {
+		let len = reader.read::<u32>()? as usize;
+		let bytes = reader.read_bytes(len)?;
+		String::from_utf8(bytes.to_vec()).map_err(|_| SaveError::Corrupted("String is not valid UTF-8".to_string()))
+	}
+}
+
+impl<T: SaveData> SaveData for Vec<T> {
+	fn write(&self, writer: &mut SaveWriter) {
+		writer.write(&(self.len() as u32));
+		for item in self {
+			item.write(writer);
+		}
+	}
+
+	fn read(reader: &mut SaveReader) -> Result<Self, SaveError> {
+		let len = reader.read::<u32>()? as usize;
+		let mut items = Vec::with_capacity(len.min(1 << 16));
+		for _ in 0..len {
+			items.push(T::read(reader)?);
+		}
+		Ok(items)
+	}
+}
+
+impl<T: SaveData> SaveData for Option<T> {
+	fn write(&self, writer: &mut SaveWriter) {
+		match self {
+			Some(value) => {
+				writer.write(&true);
+				value.write(writer);
+			}
+			None => writer.write(&false)
+		}
+	}
+
+	fn read(reader: &mut SaveReader) -> Result<Self, SaveError> {
+		match reader.read::<bool>()? {
+			true => Ok(Some(T::read(reader)?)),
+			false => Ok(None)
+		}
+	}
+}
+
+impl SaveData for Vec2 {
+	fn write(&self, writer: &mut SaveWriter) {
+		writer.write(&self.x());
+		writer.write(&self.y());
+	}
+
+	fn read(reader: &mut SaveReader) -> Result<Self, SaveError> {
+		Ok(Vec2::new(reader.read()?, reader.read()?))
+	}
+}
+
+impl SaveData for Vec3 {
+	fn write(&self, writer: &mut SaveWriter) {
+		writer.write(&self.x());
+		writer.write(&self.y());
+		writer.write(&self.z());
+	}
+
+	fn read(reader: &mut SaveReader) -> Result<Self, SaveError> {
+		Ok(Vec3::new(reader.read()?, reader.read()?, reader.read()?))
+	}
+}
+
+impl SaveData for Vec4 {
+	fn write(&self, writer: &mut SaveWriter) {
+		writer.write(&self.x());
+		writer.write(&self.y());
+		writer.write(&self.z());
+		writer.write(&self.w());
+	}
+
+	fn read(reader: &mut SaveReader) -> Result<Self, SaveError> {
+		Ok(Vec4::new(reader.read()?, reader.read()?, reader.read()?, reader.read()?))
+	}
+}
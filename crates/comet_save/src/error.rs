@@ -0,0 +1,37 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum SaveError {
+	Io(std::io::Error),
+	/// The file is not a comet save file.
+	InvalidHeader,
+	/// The save was written by a newer version of the game than the one reading it.
+	UnsupportedVersion(u32),
+	/// There is no migration from this version to the next one.
+	MissingMigration(u32),
+	/// The data ended before everything could be read.
+	UnexpectedEnd,
+	/// The data is malformed, with a description of what went wrong.
+	Corrupted(String)
+}
+
+impl fmt::Display for SaveError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			SaveError::Io(e) => write!(f, "IO error: {}", e),
+			SaveError::InvalidHeader => write!(f, "Not a comet save file"),
+			SaveError::UnsupportedVersion(version) => write!(f, "Save version {} is newer than the supported version", version),
+			SaveError::MissingMigration(version) => write!(f, "No migration registered for save version {}", version),
+			SaveError::UnexpectedEnd => write!(f, "Save data ended unexpectedly"),
+			SaveError::Corrupted(reason) => write!(f, "Save data is corrupted: {}", reason)
+		}
+	}
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<std::io::Error> for SaveError {
+	fn from(e: std::io::Error) -> Self {
+		SaveError::Io(e)
+	}
+}
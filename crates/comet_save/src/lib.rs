@@ -0,0 +1,33 @@
+pub use error::*;
+pub use data::*;
+pub use save_file::*;
+pub use registry::*;
+
+mod error;
+mod data;
+mod save_file;
+mod registry;
+
+use std::path::PathBuf;
+
+/// The directory saves of the given app are stored in.
+/// This is `%APPDATA%\<app>` on Windows, `~/Library/Application Support/<app>` on macOS
+/// and `$XDG_DATA_HOME/<app>` (or `~/.local/share/<app>`) everywhere else.
+pub fn data_dir(app_name: &str) -> PathBuf {
+	let base = if cfg!(target_os = "windows") {
+		std::env::var_os("APPDATA").map(PathBuf::from)
+	} else if cfg!(target_os = "macos") {
+		std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library").join("Application Support"))
+	} else {
+		std::env::var_os("XDG_DATA_HOME")
+			.map(PathBuf::from)
+			.or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share")))
+	};
+
+	base.unwrap_or_else(|| PathBuf::from(".")).join(app_name)
+}
+
+/// The path of the save file for the given slot.
+pub fn slot_path(app_name: &str, slot: &str) -> PathBuf {
+	data_dir(app_name).join("saves").join(format!("{}.sav", slot))
+}
@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::path::Path;
+use comet_ecs::{Component, World};
+use comet_log::*;
+use crate::{SaveData, SaveError, SaveFile, SaveReader, SaveWriter};
+
+const ENTITIES_SECTION: &str = "entities";
+const SAVED_AT_SECTION: &str = "saved_at";
+
+pub type Migration = fn(&mut SaveFile) -> Result<(), SaveError>;
+type LoadComponent = fn(&mut World, &[u8], &HashMap<u32, u32>) -> Result<(), SaveError>;
+type LoadHook = fn(&mut World, &SaveFile) -> Result<(), SaveError>;
+
+struct SaveableComponent {
+	name: String,
+	save: fn(&World) -> Vec<u8>,
+	load: LoadComponent,
+	has: fn(&World, usize) -> bool
+}
+
+/// Keeps track of what goes into a save file and how older save files are upgraded.
+pub struct SaveRegistry {
+	version: u32,
+	compress: bool,
+	components: Vec<SaveableComponent>,
+	migrations: HashMap<u32, Migration>,
+	save_hooks: Vec<fn(&World, &mut SaveFile)>,
+	load_hooks: Vec<LoadHook>
+}
+
+impl Default for SaveRegistry {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl SaveRegistry {
+	pub fn new() -> Self {
+		Self {
+			version: 1,
+			compress: true,
+			components: Vec::new(),
+			migrations: HashMap::new(),
+			save_hooks: Vec::new(),
+			load_hooks: Vec::new()
+		}
+	}
+
+	pub fn version(&self) -> u32 {
+		self.version
+	}
+
+	/// Sets the current save version. Bump this whenever the layout of saved data changes
+	/// and register a migration from the previous version.
+	pub fn set_version(&mut self, version: u32) {
+		self.version = version;
+	}
+
+	pub fn compression(&self) -> bool {
+		self.compress
+	}
+
+	pub fn set_compression(&mut self, compress: bool) {
+		self.compress = compress;
+	}
+
+	/// Saves every `T` in the world under `name`. The name has to stay the same across versions
+	/// (or be renamed in a migration) so older saves can still be found.
+	pub fn register<T: Component + SaveData>(&mut self, name: &str) {
+		if self.components.iter().any(|component| component.name == name) {
+			warn!("A saveable component named {} is already registered", name);
+			return;
+		}
+
+		self.components.push(SaveableComponent {
+			name: name.to_string(),
+			save: save_component::<T>,
+			load: load_component::<T>,
+			has: has_component::<T>
+		});
+	}
+
+	/// Registers a migration that upgrades a save file from `from_version` to `from_version + 1`.
+	pub fn add_migration(&mut self, from_version: u32, migration: Migration) {
+		self.migrations.insert(from_version, migration);
+	}
+
+	/// Called on every save to write custom sections, e.g. game state that does not live in components.
+	pub fn on_save(&mut self, hook: fn(&World, &mut SaveFile)) {
+		self.save_hooks.push(hook);
+	}
+
+	/// Called on every load after all components were restored.
+	pub fn on_load(&mut self, hook: LoadHook) {
+		self.load_hooks.push(hook);
+	}
+
+	pub fn save_world(&self, world: &World) -> SaveFile {
+		let mut save_file = SaveFile::new(self.version);
+
+		let entities = world.entities().iter()
+			.enumerate()
+			.filter_map(|(id, entity)| entity.as_ref().map(|_| id as u32))
+			.collect::<Vec<u32>>();
+		save_file.set(ENTITIES_SECTION, &entities);
+		save_file.set(SAVED_AT_SECTION, &chrono::Local::now().to_rfc3339());
+
+		for component in &self.components {
+			save_file.set_section(&component.name, (component.save)(world));
+		}
+
+		for hook in &self.save_hooks {
+			hook(world, &mut save_file);
+		}
+
+		save_file
+	}
+
+	/// A hash over every registered component of every entity. Two worlds with the same state produce the same hash,
+	/// across runs and machines, which makes it usable for lockstep and replay verification.
+	pub fn state_hash(&self, world: &World) -> u64 {
+		// FNV-1a, since the hashers in std are not guaranteed to be stable across Rust versions.
+		let mut hash = 0xcbf29ce484222325u64;
+		let mut feed = |bytes: &[u8]| {
+			for byte in bytes {
+				hash ^= *byte as u64;
+				hash = hash.wrapping_mul(0x100000001b3);
+			}
+		};
+
+		for (id, entity) in world.entities().iter().enumerate() {
+			if entity.is_some() {
+				feed(&(id as u32).to_le_bytes());
+			}
+		}
+		for component in &self.components {
+			feed(component.name.as_bytes());
+			feed(&(component.save)(world));
+		}
+
+		hash
+	}
+
+	/// Upgrades the save file to the current version using the registered migrations.
+	pub fn migrate(&self, save_file: &mut SaveFile) -> Result<(), SaveError> {
+		if save_file.version() > self.version {
+			return Err(SaveError::UnsupportedVersion(save_file.version()));
+		}
+
+		while save_file.version() < self.version {
+			let from = save_file.version();
+			let migration = self.migrations.get(&from).ok_or(SaveError::MissingMigration(from))?;
+			migration(save_file)?;
+			save_file.set_version(from + 1);
+			info!("Migrated save file from version {} to {}", from, from + 1);
+		}
+
+		Ok(())
+	}
+
+	/// Replaces the saved entities in the world with the ones from the save file. Entities without any registered
+	/// component, e.g. the camera or UI spawned in setup, are kept. Entity ids are not preserved,
+	/// so components referencing other entities need an `on_load` hook.
+	pub fn load_world(&self, world: &mut World, mut save_file: SaveFile) -> Result<(), SaveError> {
+		self.migrate(&mut save_file)?;
+
+		let saved_entities = save_file.get::<Vec<u32>>(ENTITIES_SECTION)
+			.ok_or_else(|| SaveError::Corrupted("The save file has no entity list".to_string()))??;
+
+		let existing = world.entities().iter()
+			.enumerate()
+			.filter(|(id, entity)| entity.is_some() && self.components.iter().any(|component| (component.has)(world, *id)))
+			.map(|(id, _)| id)
+			.collect::<Vec<usize>>();
+		for id in existing {
+			world.delete_entity(id);
+		}
+
+		let mut id_map = HashMap::with_capacity(saved_entities.len());
+		for saved_id in saved_entities {
+			id_map.insert(saved_id, world.new_entity());
+		}
+
+		for component in &self.components {
+			match save_file.section(&component.name) {
+				Some(data) => (component.load)(world, data, &id_map)?,
+				None => warn!("The save file contains no data for {}", component.name)
+			}
+		}
+
+		for hook in &self.load_hooks {
+			hook(world, &save_file)?;
+		}
+
+		Ok(())
+	}
+
+	pub fn save_to(&self, world: &World, path: &Path) -> Result<(), SaveError> {
+		self.save_world(world).write_to(path, self.compress)?;
+		info!("Saved game to {}", path.display());
+		Ok(())
+	}
+
+	pub fn load_from(&self, world: &mut World, path: &Path) -> Result<(), SaveError> {
+		self.load_world(world, SaveFile::read_from(path)?)?;
+		info!("Loaded game from {}", path.display());
+		Ok(())
+	}
+}
+
+fn has_component<T: Component + SaveData>(world: &World, id: usize) -> bool {
+	world.components().contains_component(&T::type_id()) && world.components().get_component::<T>(id).is_some()
+}
+
+fn save_component<T: Component + SaveData>(world: &World) -> Vec<u8> {
+	let mut writer = SaveWriter::new();
+	if !world.components().contains_component(&T::type_id()) {
+		writer.write(&0u32);
+		return writer.into_bytes();
+	}
+
+	// Walking the entities instead of querying the archetype, which is removed once it runs empty.
+	let entities = world.entities().iter()
+		.enumerate()
+		.filter(|(id, entity)| entity.is_some() && world.components().get_component::<T>(*id).is_some())
+		.map(|(id, _)| id as u32)
+		.collect::<Vec<u32>>();
+	writer.write(&(entities.len() as u32));
+	for id in entities {
+		writer.write(&id);
+		world.get_component::<T>(id as usize).write(&mut writer);
+	}
+	writer.into_bytes()
+}
+
+fn load_component<T: Component + SaveData>(world: &mut World, data: &[u8], id_map: &HashMap<u32, u32>) -> Result<(), SaveError> {
+	if !world.components().contains_component(&T::type_id()) {
+		world.register_component::<T>();
+	}
+
+	let mut reader = SaveReader::new(data);
+	let count = reader.read::<u32>()?;
+	for _ in 0..count {
+		let saved_id = reader.read::<u32>()?;
+		let component = reader.read::<T>()?;
+		let id = *id_map.get(&saved_id)
+			.ok_or_else(|| SaveError::Corrupted(format!("{} references the unknown entity {}", T::type_name(), saved_id)))? as usize;
+
+		if world.components().get_component::<T>(id).is_some() {
+			*world.get_component_mut::<T>(id) = component;
+		} else {
+			world.add_component(id, component);
+		}
+	}
+	Ok(())
+}
@@ -0,0 +1,146 @@
+use std::io::{Read, Write};
+use std::path::Path;
+use flate2::Compression;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use crate::{SaveData, SaveError, SaveReader, SaveWriter};
+
+const MAGIC: &[u8; 8] = b"CMTSAVE\0";
+const FLAG_COMPRESSED: u8 = 1;
+
+/// The contents of a save file: the version of the game that wrote it and a list of named sections.
+/// Every registered component type and every custom piece of data gets its own section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveFile {
+	version: u32,
+	sections: Vec<(String, Vec<u8>)>
+}
+
+impl SaveFile {
+	pub fn new(version: u32) -> Self {
+		Self {
+			version,
+			sections: Vec::new()
+		}
+	}
+
+	pub fn version(&self) -> u32 {
+		self.version
+	}
+
+	/// Used by migrations to mark the file as upgraded.
+	pub fn set_version(&mut self, version: u32) {
+		self.version = version;
+	}
+
+	pub fn section(&self, name: &str) -> Option<&Vec<u8>> {
+		self.sections.iter().find(|(n, _)| n == name).map(|(_, data)| data)
+	}
+
+	pub fn section_names(&self) -> Vec<&str> {
+		self.sections.iter().map(|(name, _)| name.as_str()).collect()
+	}
+
+	/// Inserts or replaces a section.
+	pub fn set_section(&mut self, name: &str, data: Vec<u8>) {
+		match self.sections.iter_mut().find(|(n, _)| n == name) {
+			Some((_, existing)) => *existing = data,
+			None => self.sections.push((name.to_string(), data))
+		}
+	}
+
+	pub fn remove_section(&mut self, name: &str) -> Option<Vec<u8>> {
+		let index = self.sections.iter().position(|(n, _)| n == name)?;
+		Some(self.sections.remove(index).1)
+	}
+
+	pub fn rename_section(&mut self, from: &str, to: &str) {
+		if let Some(data) = self.remove_section(from) {
+			self.set_section(to, data);
+		}
+	}
+
+	/// Stores a single value as a section.
+	pub fn set<T: SaveData>(&mut self, name: &str, value: &T) {
+		let mut writer = SaveWriter::new();
+		value.write(&mut writer);
+		self.set_section(name, writer.into_bytes());
+	}
+
+	/// Reads a single value from a section written with `set`.
+	pub fn get<T: SaveData>(&self, name: &str) -> Option<Result<T, SaveError>> {
+		self.section(name).map(|data| SaveReader::new(data).read::<T>())
+	}
+
+	pub fn to_bytes(&self, compress: bool) -> Result<Vec<u8>, SaveError> {
+		let mut payload = SaveWriter::new();
+		payload.write(&(self.sections.len() as u32));
+		for (name, data) in &self.sections {
+			payload.write(name);
+			payload.write(&(data.len() as u32));
+			payload.write_bytes(data);
+		}
+		let payload = payload.into_bytes();
+
+		let mut bytes = Vec::with_capacity(payload.len() + 16);
+		bytes.extend_from_slice(MAGIC);
+		bytes.extend_from_slice(&self.version.to_le_bytes());
+
+		if compress {
+			bytes.push(FLAG_COMPRESSED);
+			let mut encoder = DeflateEncoder::new(bytes, Compression::default());
+			encoder.write_all(&payload)?;
+			Ok(encoder.finish()?)
+		} else {
+			bytes.push(0);
+			bytes.extend_from_slice(&payload);
+			Ok(bytes)
+		}
+	}
+
+	pub fn from_bytes(bytes: &[u8]) -> Result<Self, SaveError> {
+		let mut reader = SaveReader::new(bytes);
+		if reader.read_bytes(MAGIC.len()).map_err(|_| SaveError::InvalidHeader)? != MAGIC {
+			return Err(SaveError::InvalidHeader);
+		}
+
+		let version = reader.read::<u32>()?;
+		let flags = reader.read::<u8>()?;
+
+		let payload = if flags & FLAG_COMPRESSED != 0 {
+			let mut decompressed = Vec::new();
+			DeflateDecoder::new(&bytes[MAGIC.len() + 5..]).read_to_end(&mut decompressed)?;
+			decompressed
+		} else {
+			bytes[MAGIC.len() + 5..].to_vec()
+		};
+
+		let mut reader = SaveReader::new(&payload);
+		let count = reader.read::<u32>()?;
+		let mut save_file = Self::new(version);
+
+		for _ in 0..count {
+			let name = reader.read::<String>()?;
+			let len = reader.read::<u32>()? as usize;
+			let data = reader.read_bytes(len)?.to_vec();
+			save_file.sections.push((name, data));
+		}
+
+		Ok(save_file)
+	}
+
+	/// Writes the file atomically by writing to a temporary file first, so a crash never leaves a half written save behind.
+	pub fn write_to(&self, path: &Path, compress: bool) -> Result<(), SaveError> {
+		if let Some(parent) = path.parent() {
+			std::fs::create_dir_all(parent)?;
+		}
+		let temporary = path.with_extension("tmp");
+		std::fs::write(&temporary, self.to_bytes(compress)?)?;
+		std::fs::rename(&temporary, path)?;
+		Ok(())
+	}
+
+	pub fn read_from(path: &Path) -> Result<Self, SaveError> {
+		Self::from_bytes(&std::fs::read(path)?)
+	}
+}
@@ -0,0 +1,187 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use comet_ecs::{Component, Position2D, Transform2D, World};
+use comet_log::*;
+use comet_math::Vec2;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, FLOAT, INT};
+use crate::{key_from_name, ScriptEvent, ScriptInput};
+
+thread_local! {
+	// The world the scripts of this thread are currently allowed to touch.
+	static WORLD: Cell<*mut World> = Cell::new(std::ptr::null_mut());
+}
+
+/// Makes the world available to the script bindings for as long as the guard lives.
+pub(crate) struct WorldGuard<'a> {
+	previous: *mut World,
+	_world: PhantomData<&'a mut World>
+}
+
+impl<'a> WorldGuard<'a> {
+	pub(crate) fn new(world: &'a mut World) -> Self {
+		let previous = WORLD.with(|current| current.replace(world));
+		Self {
+			previous,
+			_world: PhantomData
+		}
+	}
+}
+
+impl Drop for WorldGuard<'_> {
+	fn drop(&mut self) {
+		WORLD.with(|current| current.set(self.previous));
+	}
+}
+
+fn with_world<R>(f: impl FnOnce(&mut World) -> R) -> Result<R, Box<EvalAltResult>> {
+	let world = WORLD.with(|current| current.get());
+	if world.is_null() {
+		return Err("The world can only be accessed while scripts are running".into());
+	}
+	// SAFETY: the pointer is only set by a `WorldGuard`, which holds the mutable borrow of the world until it is dropped.
+	Ok(f(unsafe { &mut *world }))
+}
+
+fn entity_exists(world: &World, id: usize) -> bool {
+	matches!(world.entities().get(id), Some(Some(_)))
+}
+
+/// How scripts read and write a component they know by name.
+#[derive(Clone, Copy)]
+pub(crate) struct ComponentAccess {
+	get: fn(&World, usize) -> Option<Dynamic>,
+	set: fn(&mut World, usize, Dynamic) -> bool,
+	remove: fn(&mut World, usize)
+}
+
+impl ComponentAccess {
+	pub(crate) fn of<T: Component + Clone>() -> Self {
+		Self {
+			get: get_component::<T>,
+			set: set_component::<T>,
+			remove: remove_component::<T>
+		}
+	}
+}
+
+fn get_component<T: Component + Clone>(world: &World, id: usize) -> Option<Dynamic> {
+	if !entity_exists(world, id) || !world.components().contains_component(&T::type_id()) {
+		return None;
+	}
+	world.components().get_component::<T>(id).map(|component| Dynamic::from(component.clone()))
+}
+
+fn set_component<T: Component + Clone>(world: &mut World, id: usize, value: Dynamic) -> bool {
+	let Some(component) = value.try_cast::<T>() else {
+		return false;
+	};
+	if !entity_exists(world, id) {
+		return false;
+	}
+	if !world.components().contains_component(&T::type_id()) {
+		world.register_component::<T>();
+	}
+
+	if world.components().get_component::<T>(id).is_some() {
+		*world.get_component_mut::<T>(id) = component;
+	} else {
+		world.add_component(id, component);
+	}
+	true
+}
+
+fn remove_component<T: Component + Clone>(world: &mut World, id: usize) {
+	if entity_exists(world, id)
+		&& world.components().contains_component(&T::type_id())
+		&& world.components().get_component::<T>(id).is_some() {
+		world.remove_component::<T>(id);
+	}
+}
+
+pub(crate) type ComponentTable = Rc<RefCell<HashMap<String, ComponentAccess>>>;
+
+fn lookup(components: &ComponentTable, name: &str) -> Result<ComponentAccess, Box<EvalAltResult>> {
+	components.borrow().get(name).copied().ok_or_else(|| format!("Unknown component {}", name).into())
+}
+
+fn key(name: &str) -> Result<comet_input::keyboard::Key, Box<EvalAltResult>> {
+	key_from_name(name).ok_or_else(|| format!("Unknown key {}", name).into())
+}
+
+pub(crate) fn register_world(engine: &mut Engine, components: ComponentTable) {
+	engine.register_fn("spawn", || with_world(|world| world.new_entity() as INT));
+	engine.register_fn("despawn", |id: INT| with_world(|world| {
+		if entity_exists(world, id as usize) {
+			world.delete_entity(id as usize);
+		}
+	}));
+	engine.register_fn("exists", |id: INT| with_world(|world| entity_exists(world, id as usize)));
+
+	let table = components.clone();
+	engine.register_fn("get_component", move |id: INT, name: &str| {
+		let access = lookup(&table, name)?;
+		with_world(|world| (access.get)(world, id as usize).unwrap_or(Dynamic::UNIT))
+	});
+
+	let table = components.clone();
+	engine.register_fn("has_component", move |id: INT, name: &str| {
+		let access = lookup(&table, name)?;
+		with_world(|world| (access.get)(world, id as usize).is_some())
+	});
+
+	let table = components.clone();
+	engine.register_fn("set_component", move |id: INT, name: &str, value: Dynamic| -> Result<(), Box<EvalAltResult>> {
+		let access = lookup(&table, name)?;
+		if with_world(|world| (access.set)(world, id as usize, value))? {
+			Ok(())
+		} else {
+			Err(format!("Could not set {} on entity {}", name, id).into())
+		}
+	});
+
+	let table = components;
+	engine.register_fn("remove_component", move |id: INT, name: &str| {
+		let access = lookup(&table, name)?;
+		with_world(|world| (access.remove)(world, id as usize))
+	});
+
+	engine.register_fn("emit", |name: &str, value: Dynamic| with_world(|world| world.send_event(ScriptEvent::new(name, value))));
+	engine.register_fn("emit", |name: &str| with_world(|world| world.send_event(ScriptEvent::new(name, Dynamic::UNIT))));
+	engine.register_fn("events", |name: &str| with_world(|world| {
+		world.read_events::<ScriptEvent>().into_iter()
+			.filter(|event| event.name() == name)
+			.map(|event| event.value().clone())
+			.collect::<Array>()
+	}));
+}
+
+pub(crate) fn register_input(engine: &mut Engine, input: Rc<RefCell<ScriptInput>>) {
+	let state = input.clone();
+	engine.register_fn("key_pressed", move |name: &str| Ok::<_, Box<EvalAltResult>>(state.borrow().key_pressed(key(name)?)));
+	let state = input.clone();
+	engine.register_fn("key_held", move |name: &str| Ok::<_, Box<EvalAltResult>>(state.borrow().key_held(key(name)?)));
+	let state = input;
+	engine.register_fn("key_released", move |name: &str| Ok::<_, Box<EvalAltResult>>(state.borrow().key_released(key(name)?)));
+}
+
+pub(crate) fn register_builtin_types(engine: &mut Engine) {
+	engine.on_print(|message| info!("{}", message));
+	engine.on_debug(|message, source, position| debug!("{} ({}:{})", message, source.unwrap_or("script"), position));
+
+	engine.register_type_with_name::<Vec2>("Vec2")
+		.register_fn("vec2", |x: FLOAT, y: FLOAT| Vec2::new(x as f32, y as f32))
+		.register_get_set("x", |v: &mut Vec2| v.x() as FLOAT, |v: &mut Vec2, x: FLOAT| v.set_x(x as f32))
+		.register_get_set("y", |v: &mut Vec2| v.y() as FLOAT, |v: &mut Vec2, y: FLOAT| v.set_y(y as f32));
+
+	engine.register_type_with_name::<Position2D>("Position2D")
+		.register_fn("position2d", |x: FLOAT, y: FLOAT| Position2D::from_vec(Vec2::new(x as f32, y as f32)))
+		.register_get_set("x", |p: &mut Position2D| p.x() as FLOAT, |p: &mut Position2D, x: FLOAT| p.set_x(x as f32))
+		.register_get_set("y", |p: &mut Position2D| p.y() as FLOAT, |p: &mut Position2D, y: FLOAT| p.set_y(y as f32));
+
+	engine.register_type_with_name::<Transform2D>("Transform2D")
+		.register_fn("transform2d", Transform2D::default)
+		.register_get_set("position", |t: &mut Transform2D| *t.position(), |t: &mut Transform2D, p: Position2D| *t.position_mut() = p)
+		.register_fn("translate", |t: &mut Transform2D, x: FLOAT, y: FLOAT| t.translate(Vec2::new(x as f32, y as f32)));
+}
@@ -0,0 +1,170 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::SystemTime;
+use comet_ecs::{Component, World};
+use comet_log::*;
+use rhai::{Dynamic, Engine, Scope, AST, FLOAT};
+use crate::bindings::{self, ComponentAccess, ComponentTable, WorldGuard};
+use crate::ScriptInput;
+
+/// How often the script files are checked for changes, in seconds.
+const HOT_RELOAD_INTERVAL: f32 = 0.5;
+
+struct Script {
+	path: PathBuf,
+	modified: Option<SystemTime>,
+	ast: Option<AST>,
+	scope: Scope<'static>,
+	has_update: bool
+}
+
+/// Runs Rhai scripts against the world.
+/// Every script is run once when it is loaded and then has its `update(dt)` function called on every update.
+/// Changed script files are reloaded automatically.
+pub struct ScriptEngine {
+	engine: Engine,
+	scripts: Vec<Script>,
+	components: ComponentTable,
+	input: Rc<RefCell<ScriptInput>>,
+	hot_reload: bool,
+	reload_timer: f32
+}
+
+impl ScriptEngine {
+	pub fn new() -> Self {
+		let components: ComponentTable = Rc::new(RefCell::new(HashMap::new()));
+		let input = Rc::new(RefCell::new(ScriptInput::new()));
+
+		let mut engine = Engine::new();
+		bindings::register_builtin_types(&mut engine);
+		bindings::register_world(&mut engine, components.clone());
+		bindings::register_input(&mut engine, input.clone());
+
+		let mut script_engine = Self {
+			engine,
+			scripts: Vec::new(),
+			components,
+			input,
+			hot_reload: true,
+			reload_timer: 0.0
+		};
+
+		script_engine.register_component::<comet_ecs::Position2D>("Position2D");
+		script_engine.register_component::<comet_ecs::Transform2D>("Transform2D");
+		script_engine
+	}
+
+	/// Makes `T` accessible to scripts through `get_component(entity, name)` and `set_component(entity, name, value)`.
+	/// Use `rhai_mut` to register getters and setters for its fields.
+	pub fn register_component<T: Component + Clone>(&mut self, name: &str) {
+		self.engine.register_type_with_name::<T>(name);
+		self.components.borrow_mut().insert(name.to_string(), ComponentAccess::of::<T>());
+	}
+
+	/// The underlying Rhai engine, for registering custom functions and types.
+	pub fn rhai_mut(&mut self) -> &mut Engine {
+		&mut self.engine
+	}
+
+	pub fn hot_reload(&self) -> bool {
+		self.hot_reload
+	}
+
+	pub fn set_hot_reload(&mut self, hot_reload: bool) {
+		self.hot_reload = hot_reload;
+	}
+
+	/// Loads the script at `path` and runs it once. Errors are logged and the script is retried when the file changes.
+	pub fn load(&mut self, world: &mut World, path: &str) {
+		let path = PathBuf::from(path);
+		if self.scripts.iter().any(|script| script.path == path) {
+			warn!("The script {} is already loaded", path.display());
+			return;
+		}
+
+		self.scripts.push(Script {
+			modified: modified_time(&path),
+			path,
+			ast: None,
+			scope: Scope::new(),
+			has_update: false
+		});
+
+		let index = self.scripts.len() - 1;
+		self.compile_and_run(world, index);
+	}
+
+	pub fn unload(&mut self, path: &str) {
+		let path = Path::new(path);
+		self.scripts.retain(|script| script.path != path);
+	}
+
+	pub fn loaded_scripts(&self) -> Vec<&Path> {
+		self.scripts.iter().map(|script| script.path.as_path()).collect()
+	}
+
+	/// Runs a piece of code once, e.g. from a debug console, and returns its result.
+	pub fn eval(&mut self, world: &mut World, code: &str) -> Result<Dynamic, String> {
+		let _guard = WorldGuard::new(world);
+		self.engine.eval::<Dynamic>(code).map_err(|e| e.to_string())
+	}
+
+	/// Reloads changed scripts and calls `update(dt)` on every script that defines it.
+	pub fn update(&mut self, world: &mut World, input: ScriptInput, dt: f32) {
+		*self.input.borrow_mut() = input;
+
+		if self.hot_reload {
+			self.reload_timer += dt;
+			if self.reload_timer >= HOT_RELOAD_INTERVAL {
+				self.reload_timer = 0.0;
+				self.reload_changed(world);
+			}
+		}
+
+		let _guard = WorldGuard::new(world);
+		for script in self.scripts.iter_mut().filter(|script| script.has_update) {
+			let Some(ast) = &script.ast else { continue };
+			if let Err(e) = self.engine.call_fn::<Dynamic>(&mut script.scope, ast, "update", (dt as FLOAT,)) {
+				error!("Error in {}: {}", script.path.display(), e);
+			}
+		}
+	}
+
+	fn reload_changed(&mut self, world: &mut World) {
+		for index in 0..self.scripts.len() {
+			let modified = modified_time(&self.scripts[index].path);
+			if modified != self.scripts[index].modified {
+				self.scripts[index].modified = modified;
+				info!("Reloading {}", self.scripts[index].path.display());
+				self.compile_and_run(world, index);
+			}
+		}
+	}
+
+	fn compile_and_run(&mut self, world: &mut World, index: usize) {
+		let script = &mut self.scripts[index];
+
+		let ast = match self.engine.compile_file(script.path.clone()) {
+			Ok(ast) => ast,
+			Err(e) => {
+				error!("Failed to compile {}: {}", script.path.display(), e);
+				return;
+			}
+		};
+
+		let _guard = WorldGuard::new(world);
+		script.scope = Scope::new();
+		if let Err(e) = self.engine.run_ast_with_scope(&mut script.scope, &ast) {
+			error!("Error in {}: {}", script.path.display(), e);
+		}
+
+		script.has_update = ast.iter_functions().any(|function| function.name == "update" && function.params.len() == 1);
+		script.ast = Some(ast);
+	}
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+	std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
@@ -0,0 +1,26 @@
+use rhai::Dynamic;
+
+/// An event sent by a script with `emit(name, value)`. Rust systems can read these from the world
+/// and send their own to notify scripts, which read them with `events(name)`.
+#[derive(Debug, Clone)]
+pub struct ScriptEvent {
+	name: String,
+	value: Dynamic
+}
+
+impl ScriptEvent {
+	pub fn new(name: &str, value: Dynamic) -> Self {
+		Self {
+			name: name.to_string(),
+			value
+		}
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn value(&self) -> &Dynamic {
+		&self.value
+	}
+}
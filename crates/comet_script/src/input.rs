@@ -0,0 +1,63 @@
+use comet_input::keyboard::Key;
+
+/// The keys scripts can query, by the name they use for them.
+pub const SCRIPT_KEYS: &[(&str, Key)] = &[
+	("A", Key::KeyA), ("B", Key::KeyB), ("C", Key::KeyC), ("D", Key::KeyD),
+	("E", Key::KeyE), ("F", Key::KeyF), ("G", Key::KeyG), ("H", Key::KeyH),
+	("I", Key::KeyI), ("J", Key::KeyJ), ("K", Key::KeyK), ("L", Key::KeyL),
+	("M", Key::KeyM), ("N", Key::KeyN), ("O", Key::KeyO), ("P", Key::KeyP),
+	("Q", Key::KeyQ), ("R", Key::KeyR), ("S", Key::KeyS), ("T", Key::KeyT),
+	("U", Key::KeyU), ("V", Key::KeyV), ("W", Key::KeyW), ("X", Key::KeyX),
+	("Y", Key::KeyY), ("Z", Key::KeyZ),
+	("0", Key::Digit0), ("1", Key::Digit1), ("2", Key::Digit2), ("3", Key::Digit3),
+	("4", Key::Digit4), ("5", Key::Digit5), ("6", Key::Digit6), ("7", Key::Digit7),
+	("8", Key::Digit8), ("9", Key::Digit9),
+	("Up", Key::ArrowUp), ("Down", Key::ArrowDown), ("Left", Key::ArrowLeft), ("Right", Key::ArrowRight),
+	("Space", Key::Space), ("Enter", Key::Enter), ("Escape", Key::Escape), ("Tab", Key::Tab),
+	("Backspace", Key::Backspace), ("Shift", Key::ShiftLeft), ("Control", Key::ControlLeft), ("Alt", Key::AltLeft),
+	("F1", Key::F1), ("F2", Key::F2), ("F3", Key::F3), ("F4", Key::F4),
+	("F5", Key::F5), ("F6", Key::F6), ("F7", Key::F7), ("F8", Key::F8),
+	("F9", Key::F9), ("F10", Key::F10), ("F11", Key::F11), ("F12", Key::F12)
+];
+
+pub fn key_from_name(name: &str) -> Option<Key> {
+	SCRIPT_KEYS.iter()
+		.find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+		.map(|(_, key)| *key)
+}
+
+/// A snapshot of the keyboard state handed to the scripts on every update.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptInput {
+	pressed: Vec<Key>,
+	held: Vec<Key>,
+	released: Vec<Key>
+}
+
+impl ScriptInput {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Builds the snapshot from the pressed, held and released state of every script key.
+	pub fn capture(pressed: impl Fn(Key) -> bool, held: impl Fn(Key) -> bool, released: impl Fn(Key) -> bool) -> Self {
+		let keys = SCRIPT_KEYS.iter().map(|(_, key)| *key);
+		Self {
+			pressed: keys.clone().filter(|key| pressed(*key)).collect(),
+			held: keys.clone().filter(|key| held(*key)).collect(),
+			released: keys.filter(|key| released(*key)).collect()
+		}
+	}
+
+	pub fn key_pressed(&self, key: Key) -> bool {
+		self.pressed.contains(&key)
+	}
+
+	pub fn key_held(&self, key: Key) -> bool {
+		self.held.contains(&key)
+	}
+
+	pub fn key_released(&self, key: Key) -> bool {
+		self.released.contains(&key)
+	}
+}
@@ -0,0 +1,10 @@
+pub use engine::*;
+pub use input::*;
+pub use events::*;
+
+mod engine;
+mod input;
+mod events;
+mod bindings;
+
+pub use rhai;
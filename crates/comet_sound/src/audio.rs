@@ -0,0 +1,384 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use kira::dsp::Frame;
+use kira::manager::{AudioManager, AudioManagerSettings};
+use kira::manager::backend::DefaultBackend;
+use kira::sound::static_sound::{StaticSoundData, StaticSoundHandle, StaticSoundSettings};
+use kira::track::effect::filter::FilterBuilder;
+use kira::track::effect::reverb::{ReverbBuilder, ReverbHandle};
+use kira::track::{TrackBuilder, TrackRoutes};
+use kira::tween::Tween;
+use comet_log::*;
+use comet_structs::SlotMap;
+use crate::{BeatEvent, Bus, MusicClock, SoundHandle, SoundId, StealPolicy};
+use crate::music::Music;
+use crate::spatial::SourceInstance;
+
+pub const MASTER_BUS: &str = "master";
+pub const MUSIC_BUS: &str = "music";
+pub const SFX_BUS: &str = "sfx";
+pub const VOICE_BUS: &str = "voice";
+/// The bus the reverb sends of all other buses go to. It only plays the reverb itself.
+pub const REVERB_BUS: &str = "reverb";
+
+/// Turns `target` down while anything plays on `trigger`, e.g. music under voice lines.
+struct Ducking {
+	target: String,
+	trigger: String,
+	volume: f32,
+	fade: Duration
+}
+
+struct PlayingSound {
+	clip: String,
+	bus: String,
+	started: u64,
+	handle: SoundHandle
+}
+
+/// The audio system of the app. Owns the loaded clips and the mixer buses.
+/// If no audio device is available, everything still works but nothing is heard.
+pub struct Audio {
+	pub(crate) manager: Option<AudioManager<DefaultBackend>>,
+	clips: HashMap<String, StaticSoundData>,
+	buses: HashMap<String, Bus>,
+	reverb: Option<ReverbHandle>,
+	duckings: Vec<Ducking>,
+	playing: SlotMap<SoundId, PlayingSound>,
+	started: u64,
+	polyphony: HashMap<String, (usize, StealPolicy)>,
+	pub(crate) sources: HashMap<usize, SourceInstance>,
+	pub(crate) virtualization_margin: f32,
+	pub(crate) music: Music,
+	pub(crate) clock: Option<MusicClock>,
+	pub(crate) beat_callbacks: Vec<Box<dyn FnMut(BeatEvent)>>,
+	pub(crate) beats: Vec<BeatEvent>,
+	pub(crate) paused: bool
+}
+
+impl Default for Audio {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Audio {
+	pub fn new() -> Self {
+		let manager = match AudioManager::<DefaultBackend>::new(AudioManagerSettings::default()) {
+			Ok(manager) => Some(manager),
+			Err(e) => {
+				error!("Failed to open an audio device, audio is disabled: {:?}", e);
+				None
+			}
+		};
+
+		let mut audio = Self {
+			buses: HashMap::new(),
+			reverb: None,
+			clips: HashMap::new(),
+			duckings: Vec::new(),
+			playing: SlotMap::new(),
+			started: 0,
+			polyphony: HashMap::new(),
+			sources: HashMap::new(),
+			virtualization_margin: 0.1,
+			music: Music::default(),
+			clock: None,
+			beat_callbacks: Vec::new(),
+			beats: Vec::new(),
+			paused: false,
+			manager
+		};
+
+		let master = audio.manager.as_ref().map(|manager| manager.main_track());
+		audio.buses.insert(MASTER_BUS.to_string(), Bus::new(MASTER_BUS, master, None, None));
+		audio.add_reverb_bus();
+		for name in [MUSIC_BUS, SFX_BUS, VOICE_BUS] {
+			audio.add_bus(name, MASTER_BUS);
+		}
+		audio
+	}
+
+	pub fn is_available(&self) -> bool {
+		self.manager.is_some()
+	}
+
+	/// Adds a bus that is routed into `parent`.
+	pub fn add_bus(&mut self, name: &str, parent: &str) {
+		if self.buses.contains_key(name) {
+			warn!("The audio bus {} already exists", name);
+			return;
+		}
+
+		let parent_track = self.buses.get(parent).and_then(|bus| bus.track()).map(|track| track.id());
+		if parent_track.is_none() && self.manager.is_some() {
+			warn!("The audio bus {} does not exist, routing {} into {}", parent, name, MASTER_BUS);
+		}
+
+		let reverb = self.buses.get(REVERB_BUS).and_then(|bus| bus.track()).map(|track| track.id());
+		let mut filter = None;
+		let track = self.manager.as_mut().and_then(|manager| {
+			let mut routes = match parent_track {
+				Some(parent) => TrackRoutes::parent(parent),
+				None => TrackRoutes::new()
+			};
+			if let Some(reverb) = reverb {
+				routes = routes.with_route(reverb, 0.0);
+			}
+			let mut builder = TrackBuilder::new().routes(routes);
+			filter = Some(builder.add_effect(FilterBuilder::new().cutoff(20_000.0).mix(0.0)));
+			manager.add_sub_track(builder)
+				.map_err(|e| error!("Failed to create the audio bus {}: {}", name, e))
+				.ok()
+		});
+
+		self.buses.insert(name.to_string(), Bus::new(name, track, filter, reverb));
+	}
+
+	fn add_reverb_bus(&mut self) {
+		let mut reverb = None;
+		let track = self.manager.as_mut().and_then(|manager| {
+			let mut builder = TrackBuilder::new();
+			reverb = Some(builder.add_effect(ReverbBuilder::new().mix(1.0)));
+			manager.add_sub_track(builder)
+				.map_err(|e| error!("Failed to create the reverb bus: {}", e))
+				.ok()
+		});
+		self.reverb = reverb;
+		self.buses.insert(REVERB_BUS.to_string(), Bus::new(REVERB_BUS, track, None, None));
+	}
+
+	/// Sets the room of the reverb bus. Higher feedback makes a longer tail,
+	/// higher damping a darker one. Both range from 0.0 to 1.0.
+	pub fn set_reverb(&mut self, feedback: f32, damping: f32, seconds: f32) {
+		let Some(reverb) = &mut self.reverb else { return };
+		let fade = Tween {
+			duration: Duration::from_secs_f32(seconds.max(0.0)),
+			..Default::default()
+		};
+		let result = reverb.set_feedback(feedback.clamp(0.0, 1.0) as f64, fade)
+			.and_then(|_| reverb.set_damping(damping.clamp(0.0, 1.0) as f64, fade));
+		if let Err(e) = result {
+			error!("Failed to set the reverb: {}", e);
+		}
+	}
+
+	pub fn has_bus(&self, name: &str) -> bool {
+		self.buses.contains_key(name)
+	}
+
+	/// The bus with the given name. Panics if there is no such bus.
+	pub fn bus(&mut self, name: &str) -> &mut Bus {
+		self.buses.get_mut(name).unwrap_or_else(|| panic!("There is no audio bus named {}!", name))
+	}
+
+	pub fn master(&mut self) -> &mut Bus {
+		self.bus(MASTER_BUS)
+	}
+
+	/// Turns `target` down to `volume` while a sound is playing on `trigger` and back up once it is quiet again.
+	pub fn add_ducking(&mut self, target: &str, trigger: &str, volume: f32, fade: f32) {
+		self.duckings.push(Ducking {
+			target: target.to_string(),
+			trigger: trigger.to_string(),
+			volume: volume.clamp(0.0, 1.0),
+			fade: Duration::from_secs_f32(fade.max(0.0))
+		});
+	}
+
+	pub fn remove_duckings(&mut self, target: &str) {
+		self.duckings.retain(|ducking| ducking.target != target);
+		if let Some(bus) = self.buses.get_mut(target) {
+			bus.set_duck(1.0, Duration::from_millis(250));
+		}
+	}
+
+	/// Loads an audio file (ogg, wav, mp3 or flac) under the given name.
+	pub fn load_audio(&mut self, name: &str, path: &str) {
+		match StaticSoundData::from_file(path, StaticSoundSettings::default()) {
+			Ok(data) => {
+				self.clips.insert(name.to_string(), data);
+			}
+			Err(e) => error!("Failed to load audio {}: {}", path, e)
+		}
+	}
+
+	/// Adds audio that was decoded elsewhere, e.g. on another thread, under the given name.
+	pub fn insert_audio(&mut self, name: &str, data: StaticSoundData) {
+		self.clips.insert(name.to_string(), data);
+	}
+
+	/// Loads interleaved samples from -1.0 to 1.0 under the given name, e.g. the sound track of a video.
+	/// Mono is played on both sides, channels past the second are dropped.
+	pub fn load_samples(&mut self, name: &str, sample_rate: u32, channels: u16, samples: &[f32]) {
+		if channels == 0 || sample_rate == 0 {
+			error!("Failed to load audio {}: it needs at least one channel and a sample rate", name);
+			return;
+		}
+
+		let frames = samples.chunks_exact(channels as usize)
+			.map(|frame| match frame {
+				[mono] => Frame::from_mono(*mono),
+				[left, right, ..] => Frame::new(*left, *right),
+				[] => Frame::ZERO
+			})
+			.collect();
+		self.clips.insert(name.to_string(), StaticSoundData {
+			sample_rate,
+			frames,
+			settings: StaticSoundSettings::default()
+		});
+	}
+
+	/// Forgets a loaded clip. Instances that are already playing keep going.
+	pub fn unload_audio(&mut self, name: &str) {
+		self.clips.remove(name);
+	}
+
+	/// How long the loaded clip is, in seconds.
+	pub(crate) fn clip_duration(&self, name: &str) -> Option<f64> {
+		self.clips.get(name).map(|clip| clip.duration().as_secs_f64())
+	}
+
+	pub fn is_loaded(&self, name: &str) -> bool {
+		self.clips.contains_key(name)
+	}
+
+	/// Limits how many instances of a clip can play at once. Playing it once more
+	/// makes room according to the policy, so many overlapping hits don't clip the mix.
+	pub fn set_polyphony(&mut self, name: &str, max: usize, policy: StealPolicy) {
+		self.polyphony.insert(name.to_string(), (max.max(1), policy));
+	}
+
+	pub fn remove_polyphony(&mut self, name: &str) {
+		self.polyphony.remove(name);
+	}
+
+	/// How many instances of a clip are currently playing.
+	pub fn instances(&self, name: &str) -> usize {
+		self.playing.values().filter(|sound| sound.clip == name && !sound.handle.is_stopped()).count()
+	}
+
+	/// Plays a loaded clip on the sfx bus.
+	/// Returns `None` if the clip could not be played or was rejected by its polyphony limit.
+	pub fn play_audio(&mut self, name: &str) -> Option<SoundHandle> {
+		self.play_audio_on(name, SFX_BUS)
+	}
+
+	/// Plays a loaded clip on the given bus.
+	pub fn play_audio_on(&mut self, name: &str, bus: &str) -> Option<SoundHandle> {
+		self.play_with_settings(name, bus, StaticSoundSettings::new())
+	}
+
+	pub(crate) fn play_with_settings(&mut self, name: &str, bus: &str, settings: StaticSoundSettings) -> Option<SoundHandle> {
+		if !self.make_room(name) {
+			return None;
+		}
+
+		let handle = self.play_clip(name, bus, settings)?;
+		self.started += 1;
+		let started = self.started;
+		let id = self.playing.insert_with_key(|id| PlayingSound {
+			clip: name.to_string(),
+			bus: bus.to_string(),
+			started,
+			handle: SoundHandle::new(id, handle)
+		});
+		Some(self.playing[id].handle.clone())
+	}
+
+	/// The handle of a sound that is still playing.
+	pub fn sound(&self, id: SoundId) -> Option<&SoundHandle> {
+		self.playing.get(id).map(|sound| &sound.handle).filter(|handle| !handle.is_stopped())
+	}
+
+	/// Stops instances of the clip until another one fits into its polyphony limit.
+	fn make_room(&mut self, name: &str) -> bool {
+		let Some(&(max, policy)) = self.polyphony.get(name) else { return true };
+		self.playing.retain(|_, sound| !sound.handle.is_stopped());
+
+		let mut instances = self.playing.iter()
+			.filter(|(_, sound)| sound.clip == name)
+			.map(|(id, sound)| (sound.started, id))
+			.collect::<Vec<(u64, SoundId)>>();
+		instances.sort_unstable();
+		if instances.len() < max {
+			return true;
+		}
+
+		let excess = instances.len() + 1 - max;
+		let stolen = match policy {
+			StealPolicy::Reject => return false,
+			StealPolicy::Oldest => instances.drain(..excess).collect::<Vec<(u64, SoundId)>>(),
+			StealPolicy::Newest => instances.split_off(instances.len() - excess)
+		};
+		for (_, id) in stolen {
+			if let Some(sound) = self.playing.remove(id) {
+				sound.handle.stop(0.02);
+			}
+		}
+		true
+	}
+
+	pub(crate) fn play_clip(&mut self, name: &str, bus: &str, mut settings: StaticSoundSettings) -> Option<StaticSoundHandle> {
+		let Some(clip) = self.clips.get(name) else {
+			error!("The audio {} is not loaded", name);
+			return None;
+		};
+		let manager = self.manager.as_mut()?;
+
+		match self.buses.get(bus).and_then(|bus| bus.track()) {
+			Some(track) => settings = settings.output_destination(track),
+			None => warn!("There is no audio bus named {}, playing {} on {}", bus, name, MASTER_BUS)
+		}
+
+		manager.play(clip.with_settings(settings))
+			.map_err(|e| error!("Failed to play {}: {}", name, e))
+			.ok()
+	}
+
+	/// Pauses or resumes all audio, e.g. while the app is in the background.
+	pub fn set_paused(&mut self, paused: bool) {
+		if self.paused == paused {
+			return;
+		}
+		self.paused = paused;
+
+		if let Some(manager) = &self.manager {
+			let tween = Tween {
+				duration: Duration::from_millis(100),
+				..Default::default()
+			};
+			let result = if paused { manager.pause(tween) } else { manager.resume(tween) };
+			if let Err(e) = result {
+				error!("Failed to {} audio: {}", if paused { "pause" } else { "resume" }, e);
+			}
+		}
+	}
+
+	pub fn is_paused(&self) -> bool {
+		self.paused
+	}
+
+	/// Moves the music queue along, fires the beats of the music clock, forgets finished sounds and applies the ducking rules. Called by the app after every update.
+	pub fn update(&mut self, dt: f32) {
+		self.update_music();
+		self.update_clock(dt);
+		self.advance_virtual_sources(dt);
+		self.playing.retain(|_, sound| !sound.handle.is_stopped());
+
+		let mut ducks: HashMap<&str, (f32, Duration)> = HashMap::new();
+		for ducking in &self.duckings {
+			let entry = ducks.entry(ducking.target.as_str()).or_insert((1.0, ducking.fade));
+			if self.playing.values().any(|sound| sound.bus == ducking.trigger) {
+				*entry = (entry.0.min(ducking.volume), ducking.fade);
+			}
+		}
+
+		for (target, (volume, fade)) in ducks {
+			if let Some(bus) = self.buses.get_mut(target) {
+				bus.set_duck(volume, fade);
+			}
+		}
+	}
+}
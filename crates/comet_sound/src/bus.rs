@@ -0,0 +1,148 @@
+use std::time::Duration;
+use kira::track::effect::filter::FilterHandle;
+use kira::track::{TrackHandle, TrackId};
+use kira::tween::Tween;
+use kira::Volume;
+use comet_log::*;
+
+/// The cutoff of a low-pass filter that lets everything through.
+const OPEN_CUTOFF: f64 = 20_000.0;
+
+/// A mixer bus every sound is routed through. Buses have their own volume and can be muted,
+/// which affects every sound playing on them and on the buses routed into them.
+/// Every bus except the master has a low-pass filter and a send to the reverb bus.
+pub struct Bus {
+	name: String,
+	track: Option<TrackHandle>,
+	filter: Option<FilterHandle>,
+	reverb: Option<TrackId>,
+	volume: f32,
+	muted: bool,
+	duck: f32,
+	low_pass: Option<f32>,
+	reverb_send: f32
+}
+
+fn tween(fade: Duration) -> Tween {
+	Tween {
+		duration: fade,
+		..Default::default()
+	}
+}
+
+impl Bus {
+	pub(crate) fn new(name: &str, track: Option<TrackHandle>, filter: Option<FilterHandle>, reverb: Option<TrackId>) -> Self {
+		Self {
+			name: name.to_string(),
+			track,
+			filter,
+			reverb,
+			volume: 1.0,
+			muted: false,
+			duck: 1.0,
+			low_pass: None,
+			reverb_send: 0.0
+		}
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub(crate) fn track(&self) -> Option<&TrackHandle> {
+		self.track.as_ref()
+	}
+
+	pub fn volume(&self) -> f32 {
+		self.volume
+	}
+
+	/// Sets the volume as a linear factor, where 1.0 is the original volume.
+	pub fn set_volume(&mut self, volume: f32) {
+		self.fade_volume(volume, 0.01);
+	}
+
+	/// Moves the volume to the given factor over `seconds`.
+	pub fn fade_volume(&mut self, volume: f32, seconds: f32) {
+		self.volume = volume.max(0.0);
+		self.apply(Duration::from_secs_f32(seconds.max(0.0)));
+	}
+
+	pub fn is_muted(&self) -> bool {
+		self.muted
+	}
+
+	pub fn set_muted(&mut self, muted: bool) {
+		self.muted = muted;
+		self.apply(Duration::from_millis(10));
+	}
+
+	pub fn mute(&mut self) {
+		self.set_muted(true);
+	}
+
+	pub fn unmute(&mut self) {
+		self.set_muted(false);
+	}
+
+	/// True while the bus is turned down by a ducking rule.
+	pub fn is_ducked(&self) -> bool {
+		self.duck < 1.0
+	}
+
+	pub(crate) fn set_duck(&mut self, duck: f32, fade: Duration) {
+		if self.duck != duck {
+			self.duck = duck;
+			self.apply(fade);
+		}
+	}
+
+	/// The cutoff frequency of the low-pass filter in Hz, if it is active.
+	pub fn low_pass(&self) -> Option<f32> {
+		self.low_pass
+	}
+
+	/// Muffles the bus by cutting off frequencies above `cutoff` Hz, e.g. for underwater or pause menu sounds.
+	/// `None` opens the filter again. The change is faded in over `seconds`.
+	pub fn set_low_pass(&mut self, cutoff: Option<f32>, seconds: f32) {
+		let Some(filter) = &mut self.filter else {
+			warn!("The audio bus {} has no low-pass filter", self.name);
+			return;
+		};
+
+		self.low_pass = cutoff.map(|cutoff| cutoff.max(10.0));
+		let fade = tween(Duration::from_secs_f32(seconds.max(0.0)));
+		let cutoff = self.low_pass.map(|cutoff| cutoff as f64).unwrap_or(OPEN_CUTOFF);
+		let mix = if self.low_pass.is_some() { 1.0 } else { 0.0 };
+		if let Err(e) = filter.set_cutoff(cutoff, fade).and_then(|_| filter.set_mix(mix, fade)) {
+			error!("Failed to set the low-pass filter of bus {}: {}", self.name, e);
+		}
+	}
+
+	pub fn reverb_send(&self) -> f32 {
+		self.reverb_send
+	}
+
+	/// How much of the bus is sent to the reverb bus, from 0.0 (dry) to 1.0. Faded over `seconds`.
+	pub fn set_reverb_send(&mut self, amount: f32, seconds: f32) {
+		let (Some(track), Some(reverb)) = (&self.track, self.reverb) else {
+			warn!("The audio bus {} has no reverb send", self.name);
+			return;
+		};
+
+		self.reverb_send = amount.clamp(0.0, 1.0);
+		let fade = tween(Duration::from_secs_f32(seconds.max(0.0)));
+		if let Err(e) = track.set_route(reverb, Volume::Amplitude(self.reverb_send as f64), fade) {
+			error!("Failed to set the reverb send of bus {}: {}", self.name, e);
+		}
+	}
+
+	fn apply(&mut self, fade: Duration) {
+		let volume = if self.muted { 0.0 } else { self.volume * self.duck };
+		if let Some(track) = &mut self.track {
+			if let Err(e) = track.set_volume(Volume::Amplitude(volume as f64), tween(fade)) {
+				error!("Failed to set the volume of bus {}: {}", self.name, e);
+			}
+		}
+	}
+}
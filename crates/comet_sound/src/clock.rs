@@ -0,0 +1,168 @@
+use kira::clock::{ClockHandle, ClockSpeed, ClockTime};
+use kira::sound::static_sound::StaticSoundSettings;
+use comet_log::*;
+use crate::{Audio, SoundHandle};
+
+/// Sent on every beat of the music clock, also as a world event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BeatEvent {
+	/// The number of beats since the clock started, starting at 0.
+	pub beat: u64,
+	/// The bar the beat is in, starting at 0.
+	pub bar: u64,
+	/// The position of the beat inside its bar, starting at 0.
+	pub beat_in_bar: u32
+}
+
+/// Which grid a quantized sound waits for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantize {
+	Beat,
+	Bar
+}
+
+/// A clock ticking once per beat of the music. It runs on the audio thread, so sounds
+/// scheduled on it start exactly on the beat no matter the frame rate.
+pub struct MusicClock {
+	bpm: f64,
+	beats_per_bar: u32,
+	handle: Option<ClockHandle>,
+	// used instead of the audio clock when there is no audio device
+	elapsed: f64,
+	last_beat: Option<u64>
+}
+
+impl MusicClock {
+	pub fn bpm(&self) -> f64 {
+		self.bpm
+	}
+
+	pub fn beats_per_bar(&self) -> u32 {
+		self.beats_per_bar
+	}
+
+	/// The position in beats since the clock started, including the fraction of the current beat.
+	pub fn position(&self) -> f64 {
+		match &self.handle {
+			Some(handle) => handle.time().ticks as f64 + handle.fractional_position(),
+			None => self.elapsed * self.bpm / 60.0
+		}
+	}
+
+	pub fn beat(&self) -> u64 {
+		self.position() as u64
+	}
+
+	pub fn bar(&self) -> u64 {
+		self.beat() / self.beats_per_bar as u64
+	}
+
+	pub fn beat_in_bar(&self) -> u32 {
+		(self.beat() % self.beats_per_bar as u64) as u32
+	}
+
+	/// How far into the current beat the clock is, from 0.0 to 1.0. Useful for pulsing visuals.
+	pub fn beat_fraction(&self) -> f64 {
+		self.position().fract()
+	}
+
+	/// The clock time of the next beat or bar.
+	fn next(&self, quantize: Quantize) -> Option<ClockTime> {
+		let handle = self.handle.as_ref()?;
+		let now = handle.time();
+		let ticks = match quantize {
+			Quantize::Beat => 1,
+			Quantize::Bar => self.beats_per_bar as u64 - now.ticks % self.beats_per_bar as u64
+		};
+		Some(now + ticks)
+	}
+
+	fn event(&self, beat: u64) -> BeatEvent {
+		BeatEvent {
+			beat,
+			bar: beat / self.beats_per_bar as u64,
+			beat_in_bar: (beat % self.beats_per_bar as u64) as u32
+		}
+	}
+}
+
+impl Audio {
+	/// Starts a music clock at the given tempo. Start it together with the music it should follow.
+	pub fn start_clock(&mut self, bpm: f64, beats_per_bar: u32) {
+		self.stop_clock();
+
+		let handle = self.manager.as_mut().and_then(|manager| {
+			manager.add_clock(ClockSpeed::TicksPerMinute(bpm.max(1.0)))
+				.map_err(|e| error!("Failed to create the music clock: {}", e))
+				.ok()
+		});
+		if let Some(handle) = &handle {
+			if let Err(e) = handle.start() {
+				error!("Failed to start the music clock: {}", e);
+			}
+		}
+
+		self.clock = Some(MusicClock {
+			bpm: bpm.max(1.0),
+			beats_per_bar: beats_per_bar.max(1),
+			handle,
+			elapsed: 0.0,
+			last_beat: None
+		});
+	}
+
+	pub fn stop_clock(&mut self) {
+		if let Some(handle) = self.clock.take().and_then(|clock| clock.handle) {
+			let _ = handle.stop();
+		}
+	}
+
+	pub fn clock(&self) -> Option<&MusicClock> {
+		self.clock.as_ref()
+	}
+
+	/// Calls the callback on every beat of the music clock.
+	pub fn on_beat<F: FnMut(BeatEvent) + 'static>(&mut self, callback: F) {
+		self.beat_callbacks.push(Box::new(callback));
+	}
+
+	/// The beats that happened during the last update.
+	pub fn beats(&self) -> &[BeatEvent] {
+		&self.beats
+	}
+
+	/// Plays a clip on the given bus starting exactly on the next beat or bar.
+	/// Without a running clock, the clip plays right away.
+	pub fn play_audio_quantized(&mut self, name: &str, bus: &str, quantize: Quantize) -> Option<SoundHandle> {
+		let mut settings = StaticSoundSettings::new();
+		match self.clock.as_ref().and_then(|clock| clock.next(quantize)) {
+			Some(time) => settings = settings.start_time(time),
+			None => warn!("There is no music clock running, playing {} right away", name)
+		}
+		self.play_with_settings(name, bus, settings)
+	}
+
+	pub(crate) fn update_clock(&mut self, dt: f32) {
+		self.beats.clear();
+		let Some(clock) = &mut self.clock else { return };
+		if !self.paused {
+			clock.elapsed += dt as f64;
+		}
+
+		let beat = clock.beat();
+		let first = match clock.last_beat {
+			Some(last) if last >= beat => return,
+			Some(last) => last + 1,
+			None => beat
+		};
+		clock.last_beat = Some(beat);
+
+		for beat in first..=beat {
+			let event = clock.event(beat);
+			self.beats.push(event);
+			for callback in &mut self.beat_callbacks {
+				callback(event);
+			}
+		}
+	}
+}
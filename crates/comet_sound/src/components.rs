@@ -0,0 +1,175 @@
+use comet_ecs::Component;
+use comet_ecs::math::Vec2;
+
+/// How the volume of a spatial sound decreases between its min and max distance.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Falloff {
+	/// Full volume up to the max distance.
+	None,
+	/// Fades out linearly from the min to the max distance.
+	#[default]
+	Linear,
+	/// Drops quickly near the min distance and slowly further away, like sound in the real world.
+	Inverse,
+	/// Stays loud for longer and then drops quickly towards the max distance.
+	Quadratic
+}
+
+impl Falloff {
+	/// The volume factor for a sound `distance` away from the listener.
+	pub fn attenuation(&self, distance: f32, min_distance: f32, max_distance: f32) -> f32 {
+		if distance <= min_distance {
+			return 1.0;
+		}
+		if distance >= max_distance {
+			return 0.0;
+		}
+
+		let t = (distance - min_distance) / (max_distance - min_distance).max(f32::EPSILON);
+		match self {
+			Falloff::None => 1.0,
+			Falloff::Linear => 1.0 - t,
+			Falloff::Inverse => {
+				// 1/distance, shifted so it reaches zero at the max distance
+				let min = min_distance.max(1.0);
+				let far = min / max_distance.max(min);
+				((min / distance.max(min)) - far) / (1.0 - far).max(f32::EPSILON)
+			}
+			Falloff::Quadratic => 1.0 - t * t
+		}
+	}
+}
+
+/// Plays a loaded clip from the position of its entity.
+/// The clip starts once `playing` is set and the entity is in the world. Non-looping sources stop playing by themselves.
+#[derive(Component)]
+pub struct AudioSource {
+	clip: &'static str,
+	bus: &'static str,
+	volume: f32,
+	pitch: f32,
+	looping: bool,
+	playing: bool,
+	spatial: bool,
+	min_distance: f32,
+	max_distance: f32,
+	falloff: Falloff
+}
+
+/// Marks the entity spatial sounds are heard from, usually the camera or the player.
+#[derive(Component)]
+pub struct AudioListener {
+	offset: Vec2
+}
+
+impl AudioSource {
+	pub fn new(clip: &'static str) -> Self {
+		Self {
+			clip,
+			bus: crate::SFX_BUS,
+			volume: 1.0,
+			pitch: 1.0,
+			looping: false,
+			playing: true,
+			spatial: true,
+			min_distance: 50.0,
+			max_distance: 1000.0,
+			falloff: Falloff::Linear
+		}
+	}
+
+	pub fn clip(&self) -> &'static str {
+		self.clip
+	}
+
+	pub fn set_clip(&mut self, clip: &'static str) {
+		self.clip = clip;
+	}
+
+	pub fn bus(&self) -> &'static str {
+		self.bus
+	}
+
+	pub fn set_bus(&mut self, bus: &'static str) {
+		self.bus = bus;
+	}
+
+	pub fn volume(&self) -> f32 {
+		self.volume
+	}
+
+	pub fn set_volume(&mut self, volume: f32) {
+		self.volume = volume.max(0.0);
+	}
+
+	pub fn pitch(&self) -> f32 {
+		self.pitch
+	}
+
+	/// Sets the playback rate, e.g. 2.0 plays an octave higher and twice as fast.
+	pub fn set_pitch(&mut self, pitch: f32) {
+		self.pitch = pitch.max(0.01);
+	}
+
+	pub fn is_looping(&self) -> bool {
+		self.looping
+	}
+
+	pub fn set_looping(&mut self, looping: bool) {
+		self.looping = looping;
+	}
+
+	pub fn is_playing(&self) -> bool {
+		self.playing
+	}
+
+	pub fn play(&mut self) {
+		self.playing = true;
+	}
+
+	pub fn stop(&mut self) {
+		self.playing = false;
+	}
+
+	pub fn is_spatial(&self) -> bool {
+		self.spatial
+	}
+
+	/// Non-spatial sources play at full volume in the center, no matter where they are.
+	pub fn set_spatial(&mut self, spatial: bool) {
+		self.spatial = spatial;
+	}
+
+	pub fn min_distance(&self) -> f32 {
+		self.min_distance
+	}
+
+	pub fn max_distance(&self) -> f32 {
+		self.max_distance
+	}
+
+	/// Below `min` the source plays at full volume, beyond `max` it can not be heard anymore.
+	pub fn set_distances(&mut self, min: f32, max: f32) {
+		self.min_distance = min.max(0.0);
+		self.max_distance = max.max(self.min_distance);
+	}
+
+	pub fn falloff(&self) -> Falloff {
+		self.falloff
+	}
+
+	pub fn set_falloff(&mut self, falloff: Falloff) {
+		self.falloff = falloff;
+	}
+}
+
+impl AudioListener {
+	pub fn offset(&self) -> Vec2 {
+		self.offset
+	}
+
+	/// Moves the listening point relative to the entity.
+	pub fn set_offset(&mut self, offset: Vec2) {
+		self.offset = offset;
+	}
+}
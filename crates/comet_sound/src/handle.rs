@@ -0,0 +1,123 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use kira::sound::static_sound::StaticSoundHandle;
+use kira::sound::{PlaybackRate, PlaybackState};
+use kira::tween::Tween;
+use kira::Volume;
+use comet_log::*;
+use comet_structs::new_key_type;
+
+/// What happens when a clip is played while it already plays as often as its polyphony limit allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StealPolicy {
+	/// Stops the instance that started first.
+	#[default]
+	Oldest,
+	/// Stops the instance that started last.
+	Newest,
+	/// Doesn't play the new instance.
+	Reject
+}
+
+new_key_type! {
+	/// Identifies a playing sound, see `Audio::sound`. Ids of finished sounds are never reused.
+	pub struct SoundId;
+}
+
+/// A single playing instance of a clip. Handles can be cloned and kept around;
+/// once the sound has finished, all methods do nothing.
+#[derive(Clone)]
+pub struct SoundHandle {
+	id: SoundId,
+	handle: Arc<Mutex<StaticSoundHandle>>
+}
+
+fn tween(seconds: f32) -> Tween {
+	Tween {
+		duration: Duration::from_secs_f32(seconds.max(0.0)),
+		..Default::default()
+	}
+}
+
+impl SoundHandle {
+	pub(crate) fn new(id: SoundId, handle: StaticSoundHandle) -> Self {
+		Self {
+			id,
+			handle: Arc::new(Mutex::new(handle))
+		}
+	}
+
+	pub fn id(&self) -> SoundId {
+		self.id
+	}
+
+	fn lock(&self) -> MutexGuard<'_, StaticSoundHandle> {
+		self.handle.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+
+	pub fn is_playing(&self) -> bool {
+		self.lock().state() == PlaybackState::Playing
+	}
+
+	pub fn is_stopped(&self) -> bool {
+		self.lock().state() == PlaybackState::Stopped
+	}
+
+	/// The playback position in seconds.
+	pub fn position(&self) -> f64 {
+		self.lock().position()
+	}
+
+	/// Fades the sound out over `fade` seconds and stops it for good.
+	pub fn stop(&self, fade: f32) {
+		if let Err(e) = self.lock().stop(tween(fade)) {
+			error!("Failed to stop a sound: {}", e);
+		}
+	}
+
+	pub fn pause(&self, fade: f32) {
+		if let Err(e) = self.lock().pause(tween(fade)) {
+			error!("Failed to pause a sound: {}", e);
+		}
+	}
+
+	pub fn resume(&self, fade: f32) {
+		if let Err(e) = self.lock().resume(tween(fade)) {
+			error!("Failed to resume a sound: {}", e);
+		}
+	}
+
+	/// Moves the volume to the given linear factor over `fade` seconds.
+	pub fn set_volume(&self, volume: f32, fade: f32) {
+		if let Err(e) = self.lock().set_volume(Volume::Amplitude(volume.max(0.0) as f64), tween(fade)) {
+			error!("Failed to set the volume of a sound: {}", e);
+		}
+	}
+
+	/// Sets the playback rate, e.g. 2.0 plays an octave higher and twice as fast.
+	pub fn set_pitch(&self, pitch: f32, fade: f32) {
+		if let Err(e) = self.lock().set_playback_rate(PlaybackRate::Factor(pitch.max(0.01) as f64), tween(fade)) {
+			error!("Failed to set the pitch of a sound: {}", e);
+		}
+	}
+
+	/// Pans the sound from 0.0 (left) over 0.5 (center) to 1.0 (right).
+	pub fn set_panning(&self, panning: f32, fade: f32) {
+		if let Err(e) = self.lock().set_panning(panning.clamp(0.0, 1.0) as f64, tween(fade)) {
+			error!("Failed to set the panning of a sound: {}", e);
+		}
+	}
+
+	/// Jumps to the given position in seconds.
+	pub fn seek_to(&self, position: f64) {
+		if let Err(e) = self.lock().seek_to(position.max(0.0)) {
+			error!("Failed to seek a sound: {}", e);
+		}
+	}
+
+	pub fn seek_by(&self, seconds: f64) {
+		if let Err(e) = self.lock().seek_by(seconds) {
+			error!("Failed to seek a sound: {}", e);
+		}
+	}
+}
@@ -0,0 +1,15 @@
+pub use audio::*;
+pub use bus::*;
+pub use clock::*;
+pub use components::*;
+pub use handle::*;
+
+mod audio;
+mod bus;
+mod clock;
+mod components;
+mod handle;
+mod music;
+mod spatial;
+
+pub use kira;
@@ -0,0 +1,156 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+use kira::sound::static_sound::{StaticSoundHandle, StaticSoundSettings};
+use kira::sound::PlaybackState;
+use kira::tween::Tween;
+use comet_ecs::math::random::random_int;
+use comet_log::*;
+use crate::{Audio, MUSIC_BUS};
+
+/// The crossfade used when a playlist moves on to its next track.
+const PLAYLIST_CROSSFADE: f32 = 1.0;
+
+struct MusicTrack {
+	name: String,
+	handle: StaticSoundHandle
+}
+
+/// The soundtrack state of the audio system: the current track, the queue and the playlist.
+#[derive(Default)]
+pub(crate) struct Music {
+	current: Option<MusicTrack>,
+	queue: VecDeque<String>,
+	playlist: Vec<String>,
+	shuffle: bool,
+	looping: bool
+}
+
+fn tween(seconds: f32) -> Tween {
+	Tween {
+		duration: Duration::from_secs_f32(seconds.max(0.0)),
+		..Default::default()
+	}
+}
+
+impl Audio {
+	/// Plays a track on the music bus, fading it in over `fade_in` seconds.
+	/// The track loops until other music is requested. Clears the queue and the playlist.
+	pub fn play_music(&mut self, track: &str, fade_in: f32) {
+		self.music.queue.clear();
+		self.music.playlist.clear();
+		self.start_music(track, fade_in, true);
+	}
+
+	/// Fades the current track out while `track` fades in. The queue is kept.
+	pub fn crossfade_to(&mut self, track: &str, duration: f32) {
+		let looping = self.music.queue.is_empty() && self.music.playlist.is_empty();
+		self.start_music(track, duration, looping);
+	}
+
+	/// Fades out and stops the music. Clears the queue and the playlist.
+	pub fn stop_music(&mut self, fade_out: f32) {
+		self.music.queue.clear();
+		self.music.playlist.clear();
+		self.fade_out_music(fade_out);
+	}
+
+	/// The name of the track that is currently playing.
+	pub fn current_music(&self) -> Option<&str> {
+		self.music.current.as_ref().map(|track| track.name.as_str())
+	}
+
+	/// Adds a track that plays once the current one has finished.
+	pub fn queue_music(&mut self, track: &str) {
+		self.music.queue.push_back(track.to_string());
+		if let Some(current) = &mut self.music.current {
+			// the current track has to end for the queue to move on
+			let _ = current.handle.set_loop_region(None);
+		} else {
+			self.next_music(PLAYLIST_CROSSFADE);
+		}
+	}
+
+	/// Plays the given tracks one after another, starting right away.
+	/// Looping playlists start over (and reshuffle) once every track has played.
+	pub fn set_playlist(&mut self, tracks: &[&str], shuffle: bool, looping: bool) {
+		self.music.playlist = tracks.iter().map(|track| track.to_string()).collect();
+		self.music.shuffle = shuffle;
+		self.music.looping = looping;
+		self.music.queue.clear();
+		self.fill_queue();
+		self.next_music(PLAYLIST_CROSSFADE);
+	}
+
+	pub fn set_shuffle(&mut self, shuffle: bool) {
+		self.music.shuffle = shuffle;
+	}
+
+	pub fn is_shuffled(&self) -> bool {
+		self.music.shuffle
+	}
+
+	/// Crossfades to the next track in the queue, or fades out if there is none.
+	pub fn skip_music(&mut self, fade: f32) {
+		self.next_music(fade);
+	}
+
+	pub(crate) fn update_music(&mut self) {
+		let finished = self.music.current.as_ref().is_some_and(|track| track.handle.state() == PlaybackState::Stopped);
+		if finished {
+			self.music.current = None;
+			self.next_music(PLAYLIST_CROSSFADE);
+		}
+	}
+
+	fn next_music(&mut self, fade: f32) {
+		if self.music.queue.is_empty() && self.music.looping {
+			self.fill_queue();
+		}
+
+		match self.music.queue.pop_front() {
+			Some(track) => {
+				// the last track of a plain queue keeps looping like `play_music` does
+				let looping = self.music.queue.is_empty() && self.music.playlist.is_empty();
+				self.start_music(&track, fade, looping);
+			}
+			None => self.fade_out_music(fade)
+		}
+	}
+
+	fn fill_queue(&mut self) {
+		let mut tracks = self.music.playlist.clone();
+		if self.music.shuffle {
+			for i in (1..tracks.len()).rev() {
+				tracks.swap(i, random_int(0..i as i32 + 1) as usize);
+			}
+		}
+		self.music.queue.extend(tracks);
+	}
+
+	fn start_music(&mut self, track: &str, fade: f32, looping: bool) {
+		if !self.is_loaded(track) {
+			error!("The music {} is not loaded", track);
+			return;
+		}
+		self.fade_out_music(fade);
+
+		let mut settings = StaticSoundSettings::new().fade_in_tween(tween(fade));
+		if looping {
+			settings = settings.loop_region(..);
+		}
+		if let Some(handle) = self.play_clip(track, MUSIC_BUS, settings) {
+			self.music.current = Some(MusicTrack {
+				name: track.to_string(),
+				handle
+			});
+		}
+	}
+
+	fn fade_out_music(&mut self, fade: f32) {
+		if let Some(mut track) = self.music.current.take() {
+			if let Err(e) = track.handle.stop(tween(fade)) {
+				error!("Failed to stop the music {}: {}", track.name, e);
+			}
+		}
+	}
+}
@@ -0,0 +1,239 @@
+use std::time::Duration;
+use kira::sound::static_sound::{StaticSoundHandle, StaticSoundSettings};
+use kira::sound::{PlaybackRate, PlaybackState};
+use kira::tween::Tween;
+use kira::Volume;
+use comet_ecs::{Component, Transform2D, World};
+use comet_ecs::math::Vec2;
+use comet_log::*;
+use crate::{Audio, AudioListener, AudioSource};
+
+/// How far to the side a sound has to be, relative to its max distance, to play only on one speaker.
+const FULL_PAN_DISTANCE: f32 = 0.5;
+
+/// A sound started for an `AudioSource`.
+pub(crate) struct SourceInstance {
+	clip: &'static str,
+	pitch: f32,
+	/// `None` while the source is virtual, too far from the listener to be heard, so it takes up no voice in the mixer.
+	handle: Option<StaticSoundHandle>,
+	/// How far into the clip a virtual source is, in seconds.
+	position: f64
+}
+
+impl SourceInstance {
+	fn is_virtual(&self) -> bool {
+		self.handle.is_none()
+	}
+}
+
+fn position_of(world: &World, id: usize) -> Option<Vec2> {
+	if !world.components().contains_component(&Transform2D::type_id()) {
+		return None;
+	}
+	world.components().get_component::<Transform2D>(id).map(|transform| Vec2::new(transform.position().x(), transform.position().y()))
+}
+
+fn listener_position(world: &World) -> Option<Vec2> {
+	if !world.components().contains_component(&AudioListener::type_id()) {
+		return None;
+	}
+	world.entities().iter().enumerate()
+		.filter(|(_, entity)| entity.is_some())
+		.find_map(|(id, _)| {
+			let listener = world.components().get_component::<AudioListener>(id)?;
+			Some(position_of(world, id).unwrap_or_default() + listener.offset())
+		})
+}
+
+/// How far a spatial source is from the listener.
+fn listener_distance(source: &AudioSource, position: Option<Vec2>, listener: Option<Vec2>) -> Option<f32> {
+	let (Some(position), Some(listener), true) = (position, listener, source.is_spatial()) else {
+		return None;
+	};
+	Some((position - listener).length())
+}
+
+/// The volume factor and panning (0.0 left, 0.5 center, 1.0 right) of a source as heard by the listener.
+fn spatialize(source: &AudioSource, position: Option<Vec2>, listener: Option<Vec2>) -> (f32, f32) {
+	let (Some(position), Some(listener), true) = (position, listener, source.is_spatial()) else {
+		return (1.0, 0.5);
+	};
+
+	let offset = position - listener;
+	let distance = (offset.x() * offset.x() + offset.y() * offset.y()).sqrt();
+	let attenuation = source.falloff().attenuation(distance, source.min_distance(), source.max_distance());
+	let pan = (offset.x() / (source.max_distance() * FULL_PAN_DISTANCE).max(f32::EPSILON)).clamp(-1.0, 1.0);
+	(attenuation, 0.5 + pan * 0.5)
+}
+
+fn tween(milliseconds: u64) -> Tween {
+	Tween {
+		duration: Duration::from_millis(milliseconds),
+		..Default::default()
+	}
+}
+
+impl Audio {
+	/// How much further than its max distance, as a fraction of it, a spatial source has to be before it is virtualized.
+	pub fn virtualization_margin(&self) -> f32 {
+		self.virtualization_margin
+	}
+
+	/// Spatial sources further from the listener than their max distance plus `margin` times it are virtualized:
+	/// their sound stops, freeing its voice, while their position in the clip keeps moving on.
+	/// Once they come back within their max distance they resume from there. The gap between the two distances keeps
+	/// sources at the edge from restarting over and over. Defaults to 0.1.
+	pub fn set_virtualization_margin(&mut self, margin: f32) {
+		self.virtualization_margin = margin.max(0.0);
+	}
+
+	/// How many playing `AudioSource`s are virtualized right now, e.g. for a debug overlay.
+	pub fn virtual_sources(&self) -> usize {
+		self.sources.values().filter(|instance| instance.is_virtual()).count()
+	}
+
+	/// Moves virtual sources along their clip as if they were heard.
+	pub(crate) fn advance_virtual_sources(&mut self, dt: f32) {
+		if self.paused {
+			return;
+		}
+		for instance in self.sources.values_mut().filter(|instance| instance.is_virtual()) {
+			instance.position += (dt * instance.pitch) as f64;
+		}
+	}
+
+	/// Starts the clip of `source` `position` seconds in.
+	fn start_source(&mut self, source: &AudioSource, volume: Volume, panning: f32, position: f64) -> Option<StaticSoundHandle> {
+		let mut settings = StaticSoundSettings::new()
+			.volume(volume)
+			.panning(panning as f64)
+			.playback_rate(PlaybackRate::Factor(source.pitch() as f64));
+		if source.is_looping() {
+			settings = settings.loop_region(..);
+		}
+		if position > 0.0 {
+			settings = settings.fade_in_tween(tween(50));
+		}
+		let mut handle = self.play_clip(source.clip(), source.bus(), settings)?;
+		if position > 0.0 {
+			let _ = handle.seek_to(position);
+		}
+		Some(handle)
+	}
+
+	/// Starts, stops and moves the sounds of every `AudioSource` in the world.
+	/// Spatial sources are attenuated and panned relative to the first `AudioListener` and virtualized when far out of its range.
+	pub fn update_sources(&mut self, world: &mut World) {
+		if !world.components().contains_component(&AudioSource::type_id()) {
+			self.stop_all_sources();
+			return;
+		}
+
+		let listener = listener_position(world);
+		let mut active = Vec::new();
+
+		for id in 0..world.entities().len() {
+			if world.entities()[id].is_none() {
+				continue;
+			}
+			let Some(source) = world.components().get_component::<AudioSource>(id).copied() else { continue };
+			if !source.is_playing() {
+				continue;
+			}
+
+			let duration = self.clip_duration(source.clip()).unwrap_or(0.0);
+			let finished = self.sources.get(&id).is_some_and(|instance| match &instance.handle {
+				Some(handle) => handle.state() == PlaybackState::Stopped,
+				None => instance.position >= duration
+			});
+			let clip_changed = self.sources.get(&id).is_some_and(|instance| instance.clip != source.clip());
+			if finished && !source.is_looping() {
+				self.sources.remove(&id);
+				world.get_component_mut::<AudioSource>(id).stop();
+				continue;
+			}
+			let restart = finished || clip_changed || !self.sources.contains_key(&id);
+
+			// virtual sources come back within the max distance, the others leave beyond the margin
+			let position = position_of(world, id);
+			let was_virtual = restart || self.sources.get(&id).is_some_and(SourceInstance::is_virtual);
+			let range = if was_virtual { source.max_distance() } else { source.max_distance() * (1.0 + self.virtualization_margin) };
+			if listener_distance(&source, position, listener).is_some_and(|distance| distance > range) {
+				match self.sources.get_mut(&id) {
+					Some(instance) if !restart => {
+						if let Some(mut handle) = instance.handle.take() {
+							instance.position = handle.position();
+							let _ = handle.stop(tween(50));
+						}
+						instance.pitch = source.pitch();
+					}
+					_ => {
+						if let Some(SourceInstance { handle: Some(mut old), .. }) = self.sources.remove(&id) {
+							let _ = old.stop(tween(10));
+						}
+						self.sources.insert(id, SourceInstance {
+							clip: source.clip(),
+							pitch: source.pitch(),
+							handle: None,
+							position: 0.0
+						});
+					}
+				}
+				active.push(id);
+				continue;
+			}
+
+			let (attenuation, panning) = spatialize(&source, position, listener);
+			let volume = Volume::Amplitude((source.volume() * attenuation) as f64);
+
+			if restart {
+				if let Some(SourceInstance { handle: Some(mut old), .. }) = self.sources.remove(&id) {
+					let _ = old.stop(tween(10));
+				}
+				if let Some(handle) = self.start_source(&source, volume, panning, 0.0) {
+					self.sources.insert(id, SourceInstance {
+						clip: source.clip(),
+						pitch: source.pitch(),
+						handle: Some(handle),
+						position: 0.0
+					});
+				}
+			} else if self.sources.get(&id).is_some_and(SourceInstance::is_virtual) {
+				let virtual_position = self.sources[&id].position;
+				let resume_at = if source.is_looping() && duration > 0.0 { virtual_position % duration } else { virtual_position };
+				let handle = self.start_source(&source, volume, panning, resume_at);
+				if let Some(instance) = self.sources.get_mut(&id) {
+					instance.handle = handle;
+					instance.pitch = source.pitch();
+				}
+			} else if let Some(SourceInstance { handle: Some(handle), pitch, .. }) = self.sources.get_mut(&id) {
+				if let Err(e) = handle.set_volume(volume, tween(50)) {
+					error_every!(1.0, "Failed to update audio source {}: {}", id, e);
+				}
+				let _ = handle.set_panning(panning as f64, tween(50));
+				if *pitch != source.pitch() {
+					*pitch = source.pitch();
+					let _ = handle.set_playback_rate(PlaybackRate::Factor(source.pitch() as f64), tween(50));
+				}
+			}
+			active.push(id);
+		}
+
+		self.sources.retain(|id, instance| {
+			let keep = active.contains(id);
+			if let (false, Some(handle)) = (keep, &mut instance.handle) {
+				let _ = handle.stop(tween(50));
+			}
+			keep
+		});
+	}
+
+	fn stop_all_sources(&mut self) {
+		for (_, instance) in self.sources.drain() {
+			if let Some(mut handle) = instance.handle {
+				let _ = handle.stop(tween(50));
+			}
+		}
+	}
+}
@@ -0,0 +1,155 @@
+use std::cell::RefCell;
+
+const FIRST_CHUNK: usize = 64;
+
+/// A bump allocator for values that all live until the next `reset`, e.g. per-frame scratch data.
+/// Allocating only moves a cursor in a chunk, and resetting keeps the memory for the next frame.
+pub struct Arena<T> {
+	chunks: RefCell<Vec<Vec<T>>>
+}
+
+impl<T> Default for Arena<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T> Arena<T> {
+	pub fn new() -> Self {
+		Self::with_capacity(FIRST_CHUNK)
+	}
+
+	pub fn with_capacity(capacity: usize) -> Self {
+		Self {
+			chunks: RefCell::new(vec![Vec::with_capacity(capacity.max(1))])
+		}
+	}
+
+	/// Moves a value into the arena.
+	#[allow(clippy::mut_from_ref)]
+	pub fn alloc(&self, value: T) -> &mut T {
+		let mut chunks = self.chunks.borrow_mut();
+		let chunk = Self::chunk_with_room(&mut chunks, 1);
+		chunk.push(value);
+		let value = chunk.last_mut().unwrap() as *mut T;
+		// chunks never grow past their capacity, so the value doesn't move until the arena is reset
+		unsafe { &mut *value }
+	}
+
+	/// Moves all values of the iterator into the arena next to each other.
+	/// The iterator must not allocate in the same arena.
+	#[allow(clippy::mut_from_ref)]
+	pub fn alloc_extend<I: IntoIterator<Item = T>>(&self, values: I) -> &mut [T] {
+		let values = values.into_iter();
+		let mut chunks = self.chunks.borrow_mut();
+		let mut start = Self::chunk_with_room(&mut chunks, values.size_hint().0).len();
+		for value in values {
+			let chunk = chunks.last_mut().unwrap();
+			if chunk.len() == chunk.capacity() {
+				// only the values of this call move to the larger chunk, earlier allocations stay where they are
+				let mut larger = Vec::with_capacity(chunk.capacity() * 2);
+				larger.extend(chunk.drain(start..));
+				chunks.push(larger);
+				start = 0;
+			}
+			chunks.last_mut().unwrap().push(value);
+		}
+		let slice = &mut chunks.last_mut().unwrap()[start..] as *mut [T];
+		unsafe { &mut *slice }
+	}
+
+	/// How many values the arena holds.
+	pub fn len(&self) -> usize {
+		self.chunks.borrow().iter().map(|chunk| chunk.len()).sum()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// How many values fit into the arena before it allocates another chunk.
+	pub fn capacity(&self) -> usize {
+		self.chunks.borrow().iter().map(|chunk| chunk.capacity()).sum()
+	}
+
+	/// Drops every value. The chunks are merged into one as large as all of them,
+	/// so a steady workload stops allocating after a few frames.
+	pub fn reset(&mut self) {
+		let chunks = self.chunks.get_mut();
+		let capacity = chunks.iter().map(|chunk| chunk.capacity()).sum::<usize>();
+		let mut largest = chunks.drain(..).max_by_key(|chunk| chunk.capacity()).unwrap_or_default();
+		largest.clear();
+		if largest.capacity() < capacity {
+			largest.reserve_exact(capacity);
+		}
+		chunks.push(largest);
+	}
+
+	fn chunk_with_room(chunks: &mut Vec<Vec<T>>, additional: usize) -> &mut Vec<T> {
+		let last = chunks.last().unwrap();
+		if last.capacity() - last.len() < additional {
+			let capacity = (last.capacity() * 2).max(additional);
+			chunks.push(Vec::with_capacity(capacity));
+		}
+		chunks.last_mut().unwrap()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::cell::Cell;
+	use std::rc::Rc;
+	use super::*;
+
+	/// Counts how often it was dropped.
+	struct Counted(Rc<Cell<usize>>);
+
+	impl Drop for Counted {
+		fn drop(&mut self) {
+			self.0.set(self.0.get() + 1);
+		}
+	}
+
+	#[test]
+	fn values_stay_in_place_while_the_arena_grows() {
+		let arena = Arena::with_capacity(2);
+		let first = arena.alloc(1);
+		let values = (2..10).map(|value| arena.alloc(value)).collect::<Vec<_>>();
+		*first += 100;
+		assert_eq!(*first, 101);
+		assert_eq!(values.into_iter().map(|value| *value).collect::<Vec<_>>(), (2..10).collect::<Vec<_>>());
+		assert_eq!(arena.len(), 9);
+	}
+
+	#[test]
+	fn extends_with_iterators_of_known_and_unknown_length() {
+		let arena = Arena::with_capacity(4);
+		let before = arena.alloc_extend([1, 2, 3]);
+		let exact = arena.alloc_extend(0..5);
+		let filtered = arena.alloc_extend((0..20).filter(|value| value % 2 == 0));
+		assert_eq!(before, &[1, 2, 3]);
+		assert_eq!(exact, &[0, 1, 2, 3, 4]);
+		assert_eq!(filtered, &[0, 2, 4, 6, 8, 10, 12, 14, 16, 18]);
+		assert!(arena.alloc_extend(std::iter::empty()).is_empty());
+		assert_eq!(arena.len(), 18);
+	}
+
+	#[test]
+	fn reset_drops_the_values_and_keeps_the_memory() {
+		let drops = Rc::new(Cell::new(0));
+		let mut arena = Arena::with_capacity(1);
+		arena.alloc_extend((0..5).map(|_| Counted(drops.clone())));
+		arena.alloc(Counted(drops.clone()));
+		let capacity = arena.capacity();
+
+		arena.reset();
+		assert_eq!(drops.get(), 6);
+		assert!(arena.is_empty());
+		assert!(arena.capacity() >= capacity);
+
+		arena.alloc_extend((0..capacity).map(|_| Counted(drops.clone())));
+		assert_eq!(arena.chunks.borrow().len(), 1);
+		drop(arena);
+		assert_eq!(drops.get(), 6 + capacity);
+	}
+}
@@ -0,0 +1,7 @@
+pub use arena::*;
+pub use small_vec::*;
+pub use slot_map::*;
+
+mod arena;
+mod small_vec;
+mod slot_map;
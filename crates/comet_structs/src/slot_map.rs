@@ -0,0 +1,252 @@
+use std::marker::PhantomData;
+use std::ops::{Index, IndexMut};
+
+/// The index and generation behind a slot map key.
+/// The default key has generation 0, which no slot ever has, so it never points to a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct KeyData {
+	index: u32,
+	generation: u32
+}
+
+impl KeyData {
+	pub fn index(&self) -> u32 {
+		self.index
+	}
+
+	pub fn generation(&self) -> u32 {
+		self.generation
+	}
+}
+
+/// A key type of a `SlotMap`. Declare new ones with `new_key_type!` so keys of different maps can't be mixed up.
+pub trait Key: Copy + Eq {
+	fn from_data(data: KeyData) -> Self;
+	fn data(&self) -> KeyData;
+}
+
+impl Key for KeyData {
+	fn from_data(data: KeyData) -> Self {
+		data
+	}
+
+	fn data(&self) -> KeyData {
+		*self
+	}
+}
+
+/// Declares a key type for a `SlotMap`, e.g. `new_key_type! { pub struct PassKey; }`.
+#[macro_export]
+macro_rules! new_key_type {
+    ($(#[$meta:meta])* $vis:vis struct $name:ident;) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+        $vis struct $name($crate::KeyData);
+
+        impl $crate::Key for $name {
+            fn from_data(data: $crate::KeyData) -> Self {
+                Self(data)
+            }
+
+            fn data(&self) -> $crate::KeyData {
+                self.0
+            }
+        }
+    };
+}
+
+struct Slot<V> {
+	generation: u32,
+	value: Option<V>
+}
+
+/// A map that hands out copyable keys for the values inserted into it.
+/// Removing a value bumps the generation of its slot, so old keys stop working even after the slot is reused.
+pub struct SlotMap<K: Key, V> {
+	slots: Vec<Slot<V>>,
+	free: Vec<u32>,
+	len: usize,
+	marker: PhantomData<K>
+}
+
+impl<K: Key, V> SlotMap<K, V> {
+	pub fn new() -> Self {
+		Self::with_capacity(0)
+	}
+
+	pub fn with_capacity(capacity: usize) -> Self {
+		Self {
+			slots: Vec::with_capacity(capacity),
+			free: Vec::new(),
+			len: 0,
+			marker: PhantomData
+		}
+	}
+
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	pub fn insert(&mut self, value: V) -> K {
+		self.insert_with_key(|_| value)
+	}
+
+	/// Inserts a value that needs to know its own key.
+	pub fn insert_with_key<F: FnOnce(K) -> V>(&mut self, f: F) -> K {
+		let index = match self.free.pop() {
+			Some(index) => index,
+			None => {
+				self.slots.push(Slot {
+					generation: 1,
+					value: None
+				});
+				(self.slots.len() - 1) as u32
+			}
+		};
+
+		let slot = &mut self.slots[index as usize];
+		let key = K::from_data(KeyData {
+			index,
+			generation: slot.generation
+		});
+		slot.value = Some(f(key));
+		self.len += 1;
+		key
+	}
+
+	pub fn remove(&mut self, key: K) -> Option<V> {
+		let data = key.data();
+		let slot = self.slots.get_mut(data.index as usize).filter(|slot| slot.generation == data.generation)?;
+		let value = slot.value.take()?;
+		slot.generation = slot.generation.wrapping_add(1).max(1);
+		self.free.push(data.index);
+		self.len -= 1;
+		Some(value)
+	}
+
+	pub fn contains_key(&self, key: K) -> bool {
+		self.get(key).is_some()
+	}
+
+	pub fn get(&self, key: K) -> Option<&V> {
+		let data = key.data();
+		self.slots.get(data.index as usize)
+			.filter(|slot| slot.generation == data.generation)
+			.and_then(|slot| slot.value.as_ref())
+	}
+
+	pub fn get_mut(&mut self, key: K) -> Option<&mut V> {
+		let data = key.data();
+		self.slots.get_mut(data.index as usize)
+			.filter(|slot| slot.generation == data.generation)
+			.and_then(|slot| slot.value.as_mut())
+	}
+
+	/// Removes every value. All keys handed out so far become invalid.
+	pub fn clear(&mut self) {
+		self.retain(|_, _| false);
+	}
+
+	/// Removes every value for which `keep` returns false.
+	pub fn retain<F: FnMut(K, &mut V) -> bool>(&mut self, mut keep: F) {
+		let keys = self.keys().collect::<Vec<K>>();
+		for key in keys {
+			if !keep(key, self.get_mut(key).unwrap()) {
+				self.remove(key);
+			}
+		}
+	}
+
+	/// Iterates over the keys and values in slot order.
+	pub fn iter(&self) -> impl Iterator<Item = (K, &V)> + '_ {
+		self.slots.iter().enumerate().filter_map(|(index, slot)| {
+			let value = slot.value.as_ref()?;
+			Some((K::from_data(KeyData { index: index as u32, generation: slot.generation }), value))
+		})
+	}
+
+	pub fn iter_mut(&mut self) -> impl Iterator<Item = (K, &mut V)> + '_ {
+		self.slots.iter_mut().enumerate().filter_map(|(index, slot)| {
+			let generation = slot.generation;
+			let value = slot.value.as_mut()?;
+			Some((K::from_data(KeyData { index: index as u32, generation }), value))
+		})
+	}
+
+	pub fn keys(&self) -> impl Iterator<Item = K> + '_ {
+		self.iter().map(|(key, _)| key)
+	}
+
+	pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
+		self.slots.iter().filter_map(|slot| slot.value.as_ref())
+	}
+
+	pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> + '_ {
+		self.slots.iter_mut().filter_map(|slot| slot.value.as_mut())
+	}
+}
+
+impl<K: Key, V> Default for SlotMap<K, V> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<K: Key, V> Index<K> for SlotMap<K, V> {
+	type Output = V;
+
+	fn index(&self, key: K) -> &V {
+		self.get(key).expect("The key does not point to a value in the slot map")
+	}
+}
+
+impl<K: Key, V> IndexMut<K> for SlotMap<K, V> {
+	fn index_mut(&mut self, key: K) -> &mut V {
+		self.get_mut(key).expect("The key does not point to a value in the slot map")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	new_key_type! {
+		struct TestKey;
+	}
+
+	#[test]
+	fn removed_keys_stay_invalid_after_the_slot_is_reused() {
+		let mut map = SlotMap::<TestKey, &str>::new();
+		let first = map.insert("first");
+		let second = map.insert("second");
+		assert_eq!(map.remove(first), Some("first"));
+		assert_eq!(map.remove(first), None);
+
+		let third = map.insert("third");
+		assert_eq!(third.data().index(), first.data().index());
+		assert_ne!(third.data().generation(), first.data().generation());
+		assert!(!map.contains_key(first));
+		assert_eq!(map.get(first), None);
+		assert_eq!(map[third], "third");
+		assert_eq!(map[second], "second");
+		assert_eq!(map.len(), 2);
+	}
+
+	#[test]
+	fn retain_frees_the_slots_it_removes() {
+		let mut map = SlotMap::<TestKey, u32>::new();
+		let keys = (0..6).map(|i| map.insert(i)).collect::<Vec<TestKey>>();
+		map.retain(|_, value| *value % 2 == 0);
+		assert_eq!(map.len(), 3);
+		assert_eq!(map.values().copied().collect::<Vec<u32>>(), vec![0, 2, 4]);
+		assert!(!map.contains_key(keys[1]));
+
+		let key = map.insert_with_key(|key| key.data().index());
+		assert_eq!(map[key], key.data().index());
+		assert_eq!(map.len(), 4);
+	}
+}
@@ -0,0 +1,288 @@
+use std::fmt::{Debug, Formatter};
+use std::mem::MaybeUninit;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+
+/// A vector that stores up to `N` elements inline and only allocates once it grows past that.
+/// Meant for the many short lists that are built and thrown away every frame.
+pub struct SmallVec<T, const N: usize> {
+	len: usize,
+	inline: [MaybeUninit<T>; N],
+	heap: Option<Vec<T>>
+}
+
+impl<T, const N: usize> SmallVec<T, N> {
+	pub fn new() -> Self {
+		Self {
+			len: 0,
+			inline: [const { MaybeUninit::uninit() }; N],
+			heap: None
+		}
+	}
+
+	/// Allocates right away if `capacity` doesn't fit inline.
+	pub fn with_capacity(capacity: usize) -> Self {
+		let mut vec = Self::new();
+		if capacity > N {
+			vec.heap = Some(Vec::with_capacity(capacity));
+		}
+		vec
+	}
+
+	/// Whether the elements have moved to the heap.
+	pub fn is_spilled(&self) -> bool {
+		self.heap.is_some()
+	}
+
+	pub fn len(&self) -> usize {
+		match &self.heap {
+			Some(heap) => heap.len(),
+			None => self.len
+		}
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	pub fn capacity(&self) -> usize {
+		match &self.heap {
+			Some(heap) => heap.capacity(),
+			None => N
+		}
+	}
+
+	pub fn push(&mut self, value: T) {
+		if let Some(heap) = &mut self.heap {
+			heap.push(value);
+		} else if self.len < N {
+			self.inline[self.len].write(value);
+			self.len += 1;
+		} else {
+			let mut heap = Vec::with_capacity((N * 2).max(4));
+			heap.extend(self.take_inline());
+			heap.push(value);
+			self.heap = Some(heap);
+		}
+	}
+
+	pub fn pop(&mut self) -> Option<T> {
+		if let Some(heap) = &mut self.heap {
+			return heap.pop();
+		}
+		if self.len == 0 {
+			return None;
+		}
+		self.len -= 1;
+		Some(unsafe { self.inline[self.len].assume_init_read() })
+	}
+
+	/// Removes the element at `index` and replaces it with the last one.
+	pub fn swap_remove(&mut self, index: usize) -> T {
+		let len = self.len();
+		assert!(index < len, "swap_remove index {} is out of bounds for length {}", index, len);
+		self.as_mut_slice().swap(index, len - 1);
+		self.pop().unwrap()
+	}
+
+	pub fn truncate(&mut self, len: usize) {
+		while self.len() > len {
+			self.pop();
+		}
+	}
+
+	/// Removes all elements. A spilled vector keeps its heap allocation.
+	pub fn clear(&mut self) {
+		match &mut self.heap {
+			Some(heap) => heap.clear(),
+			None => drop(self.take_inline())
+		}
+	}
+
+	pub fn as_slice(&self) -> &[T] {
+		match &self.heap {
+			Some(heap) => heap.as_slice(),
+			None => unsafe { std::slice::from_raw_parts(self.inline.as_ptr() as *const T, self.len) }
+		}
+	}
+
+	pub fn as_mut_slice(&mut self) -> &mut [T] {
+		match &mut self.heap {
+			Some(heap) => heap.as_mut_slice(),
+			None => unsafe { std::slice::from_raw_parts_mut(self.inline.as_mut_ptr() as *mut T, self.len) }
+		}
+	}
+
+	pub fn into_vec(mut self) -> Vec<T> {
+		match self.heap.take() {
+			Some(heap) => heap,
+			None => self.take_inline()
+		}
+	}
+
+	/// Moves the inline elements out, leaving the inline storage empty.
+	fn take_inline(&mut self) -> Vec<T> {
+		let len = std::mem::replace(&mut self.len, 0);
+		let mut elements = Vec::with_capacity(len);
+		for slot in &self.inline[..len] {
+			elements.push(unsafe { slot.assume_init_read() });
+		}
+		elements
+	}
+}
+
+impl<T, const N: usize> Drop for SmallVec<T, N> {
+	fn drop(&mut self) {
+		if self.heap.is_none() {
+			unsafe { ptr::drop_in_place(self.as_mut_slice()) };
+		}
+	}
+}
+
+impl<T, const N: usize> Default for SmallVec<T, N> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T, const N: usize> Deref for SmallVec<T, N> {
+	type Target = [T];
+
+	fn deref(&self) -> &[T] {
+		self.as_slice()
+	}
+}
+
+impl<T, const N: usize> DerefMut for SmallVec<T, N> {
+	fn deref_mut(&mut self) -> &mut [T] {
+		self.as_mut_slice()
+	}
+}
+
+impl<T: Clone, const N: usize> Clone for SmallVec<T, N> {
+	fn clone(&self) -> Self {
+		self.iter().cloned().collect()
+	}
+}
+
+impl<T: Debug, const N: usize> Debug for SmallVec<T, N> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		f.debug_list().entries(self.iter()).finish()
+	}
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for SmallVec<T, N> {
+	fn eq(&self, other: &Self) -> bool {
+		self.as_slice() == other.as_slice()
+	}
+}
+
+impl<T, const N: usize> Extend<T> for SmallVec<T, N> {
+	fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+		for value in iter {
+			self.push(value);
+		}
+	}
+}
+
+impl<T, const N: usize> FromIterator<T> for SmallVec<T, N> {
+	fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+		let mut vec = Self::new();
+		vec.extend(iter);
+		vec
+	}
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a SmallVec<T, N> {
+	type Item = &'a T;
+	type IntoIter = std::slice::Iter<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+impl<T, const N: usize> IntoIterator for SmallVec<T, N> {
+	type Item = T;
+	type IntoIter = std::vec::IntoIter<T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.into_vec().into_iter()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::cell::Cell;
+	use std::rc::Rc;
+	use super::*;
+
+	/// Counts how often it was dropped.
+	struct Counted(Rc<Cell<usize>>);
+
+	impl Drop for Counted {
+		fn drop(&mut self) {
+			self.0.set(self.0.get() + 1);
+		}
+	}
+
+	#[test]
+	fn spills_to_the_heap_past_the_inline_capacity() {
+		let mut vec = SmallVec::<u32, 4>::new();
+		vec.extend(0..4);
+		assert!(!vec.is_spilled());
+		assert_eq!(vec.capacity(), 4);
+
+		vec.push(4);
+		assert!(vec.is_spilled());
+		assert_eq!(vec.as_slice(), &[0, 1, 2, 3, 4]);
+		assert_eq!(vec.into_vec(), vec![0, 1, 2, 3, 4]);
+	}
+
+	#[test]
+	fn removes_inline_and_spilled() {
+		let mut vec = (0..3).collect::<SmallVec<u32, 4>>();
+		assert_eq!(vec.swap_remove(0), 0);
+		assert_eq!(vec.as_slice(), &[2, 1]);
+		assert_eq!(vec.pop(), Some(1));
+		assert_eq!(vec.pop(), Some(2));
+		assert_eq!(vec.pop(), None);
+
+		let mut vec = (0..6).collect::<SmallVec<u32, 4>>();
+		vec.truncate(2);
+		assert_eq!(vec.as_slice(), &[0, 1]);
+		vec.clear();
+		assert!(vec.is_empty());
+		assert!(vec.is_spilled());
+	}
+
+	#[test]
+	fn drops_every_element_once() {
+		let drops = Rc::new(Cell::new(0));
+		let mut vec = SmallVec::<Counted, 2>::new();
+		vec.push(Counted(drops.clone()));
+		vec.push(Counted(drops.clone()));
+		drop(vec.pop());
+		assert_eq!(drops.get(), 1);
+		drop(vec);
+		assert_eq!(drops.get(), 2);
+
+		let mut vec = SmallVec::<Counted, 2>::new();
+		for _ in 0..5 {
+			vec.push(Counted(drops.clone()));
+		}
+		vec.truncate(3);
+		assert_eq!(drops.get(), 4);
+		let elements = vec.into_vec();
+		assert_eq!(drops.get(), 4);
+		drop(elements);
+		assert_eq!(drops.get(), 7);
+
+		let mut vec = SmallVec::<Counted, 4>::new();
+		vec.push(Counted(drops.clone()));
+		vec.clear();
+		assert_eq!(drops.get(), 8);
+		drop(vec);
+		assert_eq!(drops.get(), 8);
+	}
+}
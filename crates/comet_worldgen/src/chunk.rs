@@ -0,0 +1,62 @@
+use comet_math::{Occluder, Vec2};
+use crate::TileId;
+
+/// A square of generated tiles, `size` tiles wide, stored row by row from the bottom left.
+/// Chunk `(x, y)` starts at the tile `(x * size, y * size)` of the world.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileChunk {
+	position: (i32, i32),
+	size: usize,
+	tiles: Vec<TileId>
+}
+
+impl TileChunk {
+	pub(crate) fn new(position: (i32, i32), size: usize, tiles: Vec<TileId>) -> Self {
+		Self {
+			position,
+			size,
+			tiles
+		}
+	}
+
+	/// Which chunk of the world this is, counted in chunks.
+	pub fn position(&self) -> (i32, i32) {
+		self.position
+	}
+
+	pub fn size(&self) -> usize {
+		self.size
+	}
+
+	/// The tile in the world the chunk starts at.
+	pub fn origin(&self) -> (i64, i64) {
+		(self.position.0 as i64 * self.size as i64, self.position.1 as i64 * self.size as i64)
+	}
+
+	/// The tile at `(x, y)` within the chunk, `None` outside of it.
+	pub fn tile(&self, x: usize, y: usize) -> Option<TileId> {
+		if x >= self.size || y >= self.size {
+			return None;
+		}
+		self.tiles.get(y * self.size + x).copied()
+	}
+
+	pub fn tiles(&self) -> &[TileId] {
+		&self.tiles
+	}
+
+	/// The walls around the tiles `is_solid` picks, for `VisibilityPolygon`. A tile is `tile_size` large in the world.
+	pub fn occluders(&self, tile_size: Vec2, is_solid: impl Fn(TileId) -> bool) -> Vec<Occluder> {
+		let (origin_x, origin_y) = self.origin();
+		let origin = Vec2::new(origin_x as f32 * tile_size.x(), origin_y as f32 * tile_size.y());
+		Occluder::from_tiles(origin, tile_size, self.size, self.size, |x, y| self.tile(x, y).is_some_and(&is_solid))
+	}
+
+	/// Every tile with its position in the world.
+	pub fn iter(&self) -> impl Iterator<Item = ((i64, i64), TileId)> + '_ {
+		let (origin_x, origin_y) = self.origin();
+		self.tiles.iter().enumerate().map(move |(i, &tile)| {
+			((origin_x + (i % self.size) as i64, origin_y + (i / self.size) as i64), tile)
+		})
+	}
+}
@@ -0,0 +1,106 @@
+use comet_math::noise::PerlinNoise;
+
+/// Bends the coordinates a `NoiseField` is sampled at by two more noises, which turns round blobs
+/// into twisted, more natural shapes like coastlines and rivers.
+#[derive(Debug, Clone)]
+pub struct DomainWarp {
+	strength: f32,
+	frequency: f64,
+	noise_x: PerlinNoise,
+	noise_y: PerlinNoise
+}
+
+impl DomainWarp {
+	/// Moves every sample up to `strength` tiles, with the offsets changing over about `1 / frequency` tiles.
+	pub fn new(strength: f32, frequency: f64) -> Self {
+		Self {
+			strength,
+			frequency,
+			noise_x: PerlinNoise::new(1, 1, frequency, 0),
+			noise_y: PerlinNoise::new(1, 1, frequency, 0)
+		}
+	}
+
+	pub fn strength(&self) -> f32 {
+		self.strength
+	}
+
+	pub fn frequency(&self) -> f64 {
+		self.frequency
+	}
+
+	fn seed(&mut self, seed: u32) {
+		self.noise_x.set_seed(seed.wrapping_mul(31).wrapping_add(7));
+		self.noise_y.set_seed(seed.wrapping_mul(131).wrapping_add(3));
+	}
+
+	/// `(x, y)` moved by the warp.
+	fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+		let offset = |noise: &PerlinNoise| ((noise.sample(x, y) - 0.5) * 2.0 * self.strength) as f64;
+		(x + offset(&self.noise_x), y + offset(&self.noise_y))
+	}
+}
+
+/// A value from 0 to 1 for every tile of the world, e.g. its height or moisture, that `TileRule`s test against.
+#[derive(Debug, Clone)]
+pub struct NoiseField {
+	noise: PerlinNoise,
+	octaves: u32,
+	persistence: f64,
+	warp: Option<DomainWarp>
+}
+
+impl NoiseField {
+	/// Perlin noise changing over about `1 / frequency` tiles, e.g. 0.02 for features 50 tiles across.
+	pub fn perlin(frequency: f64) -> Self {
+		Self {
+			noise: PerlinNoise::new(1, 1, frequency, 0),
+			octaves: 1,
+			persistence: 0.5,
+			warp: None
+		}
+	}
+
+	/// Adds finer detail with `octaves` layers of noise, each twice as fine and `persistence` times as strong.
+	pub fn with_octaves(mut self, octaves: u32, persistence: f64) -> Self {
+		self.octaves = octaves.max(1);
+		self.persistence = persistence;
+		self
+	}
+
+	pub fn with_warp(mut self, warp: DomainWarp) -> Self {
+		self.warp = Some(warp);
+		self
+	}
+
+	pub fn octaves(&self) -> u32 {
+		self.octaves
+	}
+
+	pub fn persistence(&self) -> f64 {
+		self.persistence
+	}
+
+	pub fn warp(&self) -> Option<&DomainWarp> {
+		self.warp.as_ref()
+	}
+
+	/// Gives the field its own seed, so fields with the same settings still differ.
+	pub(crate) fn seeded(mut self, seed: u32) -> Self {
+		self.noise.set_seed(seed);
+		if let Some(warp) = &mut self.warp {
+			warp.seed(seed);
+		}
+		self
+	}
+
+	/// The value of the field at the tile `(x, y)` of the world.
+	pub fn sample(&self, x: i64, y: i64) -> f32 {
+		let (x, y) = (x as f64, y as f64);
+		let (x, y) = match &self.warp {
+			Some(warp) => warp.apply(x, y),
+			None => (x, y)
+		};
+		self.noise.sample_with_octaves(x, y, self.octaves, self.persistence)
+	}
+}
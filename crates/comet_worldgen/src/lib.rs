@@ -0,0 +1,9 @@
+pub use field::*;
+pub use rule::*;
+pub use chunk::*;
+pub use worldgen::*;
+
+mod field;
+mod rule;
+mod chunk;
+mod worldgen;
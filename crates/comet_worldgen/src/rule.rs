@@ -0,0 +1,51 @@
+use std::ops::Range;
+
+/// The id of a kind of tile, e.g. an index into the tile set of a level.
+pub type TileId = u32;
+
+/// Places `tile` on every tile where all of its conditions hold. The rules of a `WorldGen` are tried in order
+/// and the first one that matches wins, so more specific rules go first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileRule {
+	tile: TileId,
+	conditions: Vec<(String, Range<f32>)>
+}
+
+impl TileRule {
+	/// A rule that places `tile` everywhere until conditions are added.
+	pub fn new(tile: TileId) -> Self {
+		Self {
+			tile,
+			conditions: Vec::new()
+		}
+	}
+
+	/// Only matches where the field called `field` is inside `range`, e.g. `("height", 0.0..0.4)` for water.
+	pub fn when(mut self, field: &str, range: Range<f32>) -> Self {
+		self.conditions.push((field.to_string(), range));
+		self
+	}
+
+	/// Only matches where the field called `field` is at least `threshold`.
+	pub fn above(self, field: &str, threshold: f32) -> Self {
+		self.when(field, threshold..f32::INFINITY)
+	}
+
+	/// Only matches where the field called `field` is below `threshold`.
+	pub fn below(self, field: &str, threshold: f32) -> Self {
+		self.when(field, f32::NEG_INFINITY..threshold)
+	}
+
+	pub fn tile(&self) -> TileId {
+		self.tile
+	}
+
+	pub fn conditions(&self) -> &[(String, Range<f32>)] {
+		&self.conditions
+	}
+
+	/// Whether the rule matches a tile, given a function looking up the value of a field there.
+	pub(crate) fn matches(&self, value_of: impl Fn(&str) -> Option<f32>) -> bool {
+		self.conditions.iter().all(|(field, range)| value_of(field).is_some_and(|value| range.contains(&value)))
+	}
+}
@@ -0,0 +1,139 @@
+use comet_log::*;
+use crate::{NoiseField, TileChunk, TileId, TileRule};
+
+/// Generates an endless tile world chunk by chunk out of named noise fields and rules placing tiles on them.
+/// The same seed always gives the same world, whichever chunks are generated in whichever order.
+///
+/// ```ignore
+/// let worldgen = WorldGen::new(42)
+///     .with_field("height", NoiseField::perlin(0.02).with_octaves(4, 0.5).with_warp(DomainWarp::new(8.0, 0.05)))
+///     .with_field("moisture", NoiseField::perlin(0.01))
+///     .with_rule(TileRule::new(WATER).below("height", 0.4))
+///     .with_rule(TileRule::new(SAND).below("height", 0.45))
+///     .with_rule(TileRule::new(FOREST).above("moisture", 0.6))
+///     .with_default_tile(GRASS);
+/// ```
+///
+/// Generating is pure and `WorldGen` is `Send`, so it can run on the thread pool of the app with `App::spawn_blocking`,
+/// handing the chunks back through an `EventSender`.
+#[derive(Debug, Clone)]
+pub struct WorldGen {
+	seed: u32,
+	chunk_size: usize,
+	fields: Vec<(String, NoiseField)>,
+	rules: Vec<TileRule>,
+	default_tile: TileId
+}
+
+impl WorldGen {
+	pub fn new(seed: u32) -> Self {
+		Self {
+			seed,
+			chunk_size: 32,
+			fields: Vec::new(),
+			rules: Vec::new(),
+			default_tile: 0
+		}
+	}
+
+	/// How many tiles wide a chunk is, 32 by default.
+	pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+		self.chunk_size = chunk_size.max(1);
+		self
+	}
+
+	/// Adds a field rules can test under `name`. Each field gets its own seed derived from the world seed and its name.
+	pub fn with_field(mut self, name: &str, field: NoiseField) -> Self {
+		let field = field.seeded(self.seed ^ name_hash(name));
+		match self.fields.iter_mut().find(|(existing, _)| existing == name) {
+			Some((_, existing)) => *existing = field,
+			None => self.fields.push((name.to_string(), field))
+		}
+		self
+	}
+
+	/// Adds a rule after the ones added before, see `TileRule`.
+	pub fn with_rule(mut self, rule: TileRule) -> Self {
+		for (field, _) in rule.conditions() {
+			if !self.fields.iter().any(|(name, _)| name == field) {
+				warn!("The tile rule for tile {} tests the field {}, which is not added (yet), it never matches without it", rule.tile(), field);
+			}
+		}
+		self.rules.push(rule);
+		self
+	}
+
+	/// The tile placed where no rule matches, 0 by default.
+	pub fn with_default_tile(mut self, default_tile: TileId) -> Self {
+		self.default_tile = default_tile;
+		self
+	}
+
+	pub fn seed(&self) -> u32 {
+		self.seed
+	}
+
+	pub fn chunk_size(&self) -> usize {
+		self.chunk_size
+	}
+
+	pub fn field(&self, name: &str) -> Option<&NoiseField> {
+		self.fields.iter().find(|(field, _)| field == name).map(|(_, field)| field)
+	}
+
+	pub fn rules(&self) -> &[TileRule] {
+		&self.rules
+	}
+
+	/// The tile at `(x, y)` in the world.
+	pub fn tile_at(&self, x: i64, y: i64) -> TileId {
+		let value_of = |name: &str| self.field(name).map(|field| field.sample(x, y));
+		self.rules.iter()
+			.find(|rule| rule.matches(value_of))
+			.map(TileRule::tile)
+			.unwrap_or(self.default_tile)
+	}
+
+	/// The chunk at `position`, counted in chunks.
+	pub fn generate_chunk(&self, position: (i32, i32)) -> TileChunk {
+		let size = self.chunk_size;
+		let (origin_x, origin_y) = (position.0 as i64 * size as i64, position.1 as i64 * size as i64);
+		let tiles = (0..size * size)
+			.map(|i| self.tile_at(origin_x + (i % size) as i64, origin_y + (i / size) as i64))
+			.collect();
+		TileChunk::new(position, size, tiles)
+	}
+
+	/// The chunks at `positions` in the same order, generated in parallel on all cores.
+	pub fn generate_chunks(&self, positions: &[(i32, i32)]) -> Vec<TileChunk> {
+		let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1).min(positions.len());
+		if threads <= 1 {
+			return positions.iter().map(|&position| self.generate_chunk(position)).collect();
+		}
+
+		let per_thread = positions.len().div_ceil(threads);
+		std::thread::scope(|scope| {
+			let workers: Vec<_> = positions.chunks(per_thread)
+				.map(|positions| scope.spawn(move || {
+					positions.iter().map(|&position| self.generate_chunk(position)).collect::<Vec<_>>()
+				}))
+				.collect();
+			workers.into_iter()
+				.flat_map(|worker| worker.join().expect("A world generation thread panicked"))
+				.collect()
+		})
+	}
+
+	/// Every chunk in the rectangle from `min` to `max`, both included, row by row from the bottom left.
+	pub fn generate_area(&self, min: (i32, i32), max: (i32, i32)) -> Vec<TileChunk> {
+		let positions: Vec<_> = (min.1..=max.1)
+			.flat_map(|y| (min.0..=max.0).map(move |x| (x, y)))
+			.collect();
+		self.generate_chunks(&positions)
+	}
+}
+
+/// A hash of a field name that stays the same across builds, unlike the hasher of the standard library.
+fn name_hash(name: &str) -> u32 {
+	name.bytes().fold(0x811c9dc5, |hash: u32, byte| (hash ^ byte as u32).wrapping_mul(0x01000193))
+}
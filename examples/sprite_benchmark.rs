@@ -0,0 +1,65 @@
+// Draws a large grid of sprites while the camera pans across it, logging the frame time.
+// Run with `cargo run --release --example sprite_benchmark`, add `-- --cpu` to compare against the CPU path.
+
+use comet::prelude::*;
+use comet::math::{Vec2, Vec3};
+
+const COLUMNS: usize = 400;
+const ROWS: usize = 250;
+const SPACING: f32 = 24.0;
+const TEXTURE: &str = "resources/textures/comet-16.png";
+
+struct Benchmark {
+	time: f32,
+	frame_times: f32,
+	samples: u32
+}
+
+fn setup(app: &mut App, renderer: &mut Renderer2D) {
+	renderer.set_texture_atlas(vec![TEXTURE.to_string()]);
+	info!("GPU culling: {}", renderer.is_gpu_culling());
+
+	let world = app.world_mut();
+	for row in 0..ROWS {
+		for column in 0..COLUMNS {
+			let entity = world.new_entity() as usize;
+			let mut transform = Transform2D::new();
+			transform.translate(Vec2::new(column as f32 * SPACING, row as f32 * SPACING));
+			world.add_component(entity, transform);
+			world.add_component(entity, Render2D::with_texture(TEXTURE));
+		}
+	}
+}
+
+fn update(app: &mut App, renderer: &mut Renderer2D, dt: f32) {
+	let frame_time = renderer.dt();
+	let benchmark = app.game_state_mut::<Benchmark>().unwrap();
+	benchmark.time += dt;
+	benchmark.frame_times += frame_time;
+	benchmark.samples += 1;
+
+	if benchmark.samples >= 120 {
+		info!("{} sprites, {:.2} ms per frame", COLUMNS * ROWS, benchmark.frame_times / benchmark.samples as f32 * 1000.0);
+		benchmark.frame_times = 0.0;
+		benchmark.samples = 0;
+	}
+
+	// sweeps over the grid, which is many screens wide, so most sprites are off screen
+	let extent = Vec2::new(COLUMNS as f32 * SPACING, ROWS as f32 * SPACING);
+	let time = benchmark.time * 0.1;
+	renderer.camera_mut().set_position(Vec3::new(
+		extent.x() * (0.5 + 0.5 * time.sin()),
+		extent.y() * (0.5 + 0.5 * (time * 0.7).cos()),
+		0.0
+	));
+}
+
+fn main() {
+	let cpu = std::env::args().any(|argument| argument == "--cpu");
+	App::new(App2D)
+		.with_title("Comet Sprite Benchmark")
+		.with_size(1280, 720)
+		.with_gpu_culling(!cpu)
+		.with_game_state(Benchmark { time: 0.0, frame_times: 0.0, samples: 0 })
+		.run::<Renderer2D>(setup, update)
+}
@@ -0,0 +1,18 @@
+use std::process::ExitCode;
+use comet::tools::validate_assets;
+
+/// Validates the assets below the given directory, or the current one, and lists the problems it found.
+fn main() -> ExitCode {
+	let root = std::env::args().nth(1).unwrap_or_else(|| ".".to_string());
+	let errors = validate_assets(&root);
+	if errors.is_empty() {
+		println!("All assets below {} are valid", root);
+		return ExitCode::SUCCESS;
+	}
+
+	for error in &errors {
+		eprintln!("{}", error);
+	}
+	eprintln!("Found {} problem{} with the assets below {}", errors.len(), if errors.len() == 1 { "" } else { "s" }, root);
+	ExitCode::FAILURE
+}
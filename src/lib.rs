@@ -7,6 +7,16 @@ pub use comet_app as app;
 pub use comet_colors as colors;
 pub use comet_input as input;
 pub use comet_log as log;
+pub use comet_save as save;
+pub use comet_i18n as i18n;
+pub use comet_sound as sound;
+pub use comet_structs as structs;
+pub use comet_worldgen as worldgen;
+pub use comet_dialogue as dialogue;
+#[cfg(feature = "scripting")]
+pub use comet_script as script;
+#[cfg(feature = "tools")]
+pub mod tools;
 
 pub mod prelude {
 	pub use comet_app::App;
@@ -16,4 +26,5 @@ pub mod prelude {
 	pub use comet_log::*;
 	pub use comet_colors::*;
 	pub use comet_ecs::*;
+	pub use comet_i18n::tr;
 }
\ No newline at end of file
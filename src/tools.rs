@@ -0,0 +1,184 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use comet_resources::texture_atlas::TextureAtlas;
+use comet_resources::video::Video;
+
+const IMAGE_EXTENSIONS: [&str; 4] = ["png", "jpg", "jpeg", "hdr"];
+const AUDIO_EXTENSIONS: [&str; 4] = ["ogg", "wav", "mp3", "flac"];
+const FONT_EXTENSIONS: [&str; 2] = ["ttf", "otf"];
+const SHADER_EXTENSIONS: [&str; 1] = ["wgsl"];
+const VIDEO_EXTENSIONS: [&str; 1] = ["avi"];
+const SOURCE_EXTENSIONS: [&str; 2] = ["rs", "rhai"];
+/// The largest texture the renderer can create, as it asks for the default limits of wgpu.
+const MAX_ATLAS_SIZE: u32 = 8192;
+
+/// A problem with an asset found by `validate_assets`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssetError {
+	/// `path` is referenced at `line` of `source` but does not exist.
+	Missing { path: String, source: PathBuf, line: usize },
+	Image { path: PathBuf, message: String },
+	Shader { path: PathBuf, message: String },
+	Audio { path: PathBuf, message: String },
+	Font { path: PathBuf, message: String },
+	Video { path: PathBuf, message: String },
+	/// The textures do not fit into an atlas the GPU can hold.
+	AtlasTooLarge { width: u32, height: u32, limit: u32 }
+}
+
+impl fmt::Display for AssetError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			AssetError::Missing { path, source, line } => write!(f, "{}:{}: {} does not exist", source.display(), line, path),
+			AssetError::Image { path, message } => write!(f, "{}: the image does not decode: {}", path.display(), message),
+			AssetError::Shader { path, message } => write!(f, "{}: the shader does not compile: {}", path.display(), message),
+			AssetError::Audio { path, message } => write!(f, "{}: invalid audio file: {}", path.display(), message),
+			AssetError::Font { path, message } => write!(f, "{}: invalid font: {}", path.display(), message),
+			AssetError::Video { path, message } => write!(f, "{}: invalid video: {}", path.display(), message),
+			AssetError::AtlasTooLarge { width, height, limit } => write!(f, "The textures pack into a {}x{} atlas, which is larger than {}x{}", width, height, limit, limit)
+		}
+	}
+}
+
+impl std::error::Error for AssetError {}
+
+/// Checks every asset below `root`, the directory the game runs from: images decode, shaders compile,
+/// audio, font and video files are what their extension says and all textures fit into one atlas.
+/// Paths to assets in string literals of the Rust and Rhai sources have to exist as well,
+/// relative to `root` or to the source file for `include_str!` and the like.
+/// `cargo run --features tools --bin comet-assets [root]` prints the problems and fails if there are any.
+pub fn validate_assets(root: impl AsRef<Path>) -> Vec<AssetError> {
+	let root = root.as_ref();
+	let mut files = Vec::new();
+	collect_files(root, &mut files);
+	files.sort();
+
+	let mut errors = Vec::new();
+	let mut texture_sizes = Vec::new();
+	for file in &files {
+		let extension = file.extension().and_then(|extension| extension.to_str()).unwrap_or_default().to_lowercase();
+		let extension = extension.as_str();
+		if IMAGE_EXTENSIONS.contains(&extension) {
+			match image::open(file) {
+				Ok(image) => texture_sizes.push((image.width(), image.height())),
+				Err(e) => errors.push(AssetError::Image { path: file.clone(), message: e.to_string() })
+			}
+		} else if SHADER_EXTENSIONS.contains(&extension) {
+			if let Err(message) = validate_shader(file) {
+				errors.push(AssetError::Shader { path: file.clone(), message });
+			}
+		} else if AUDIO_EXTENSIONS.contains(&extension) {
+			if let Err(message) = check_magic(file, extension) {
+				errors.push(AssetError::Audio { path: file.clone(), message });
+			}
+		} else if FONT_EXTENSIONS.contains(&extension) {
+			if let Err(message) = check_magic(file, extension) {
+				errors.push(AssetError::Font { path: file.clone(), message });
+			}
+		} else if VIDEO_EXTENSIONS.contains(&extension) {
+			if let Err(e) = Video::open(file) {
+				errors.push(AssetError::Video { path: file.clone(), message: e.to_string() });
+			}
+		} else if SOURCE_EXTENSIONS.contains(&extension) {
+			check_references(root, file, &mut errors);
+		}
+	}
+
+	let (width, height) = TextureAtlas::packed_size(&texture_sizes);
+	if width > MAX_ATLAS_SIZE || height > MAX_ATLAS_SIZE {
+		errors.push(AssetError::AtlasTooLarge { width, height, limit: MAX_ATLAS_SIZE });
+	}
+	errors
+}
+
+/// Every file below `dir`, leaving out build output and hidden directories.
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+	let Ok(entries) = std::fs::read_dir(dir) else { return };
+	for entry in entries.flatten() {
+		let path = entry.path();
+		let name = entry.file_name();
+		let name = name.to_string_lossy();
+		if path.is_dir() {
+			if name != "target" && !name.starts_with('.') {
+				collect_files(&path, files);
+			}
+		} else {
+			files.push(path);
+		}
+	}
+}
+
+fn validate_shader(path: &Path) -> Result<(), String> {
+	let source = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+	let module = naga::front::wgsl::parse_str(&source).map_err(|e| e.emit_to_string(&source))?;
+	naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all())
+		.validate(&module)
+		.map_err(|e| e.emit_to_string(&source))?;
+	Ok(())
+}
+
+/// Checks that the file starts with the signature of the format its extension names.
+fn check_magic(path: &Path, extension: &str) -> Result<(), String> {
+	let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+	let valid = match extension {
+		"ogg" => bytes.starts_with(b"OggS"),
+		"wav" => bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WAVE"),
+		"flac" => bytes.starts_with(b"fLaC"),
+		// either an ID3 tag or straight away the sync word of the first frame
+		"mp3" => bytes.starts_with(b"ID3") || (bytes.len() > 1 && bytes[0] == 0xFF && bytes[1] & 0xE0 == 0xE0),
+		"ttf" | "otf" => bytes.starts_with(&[0, 1, 0, 0]) || bytes.starts_with(b"OTTO") || bytes.starts_with(b"true") || bytes.starts_with(b"ttcf"),
+		_ => true
+	};
+	if valid {
+		Ok(())
+	} else {
+		Err(format!("it is not a {} file", extension))
+	}
+}
+
+/// Reports the asset paths in string literals of `source` that exist neither below `root` nor next to `source`.
+fn check_references(root: &Path, source: &Path, errors: &mut Vec<AssetError>) {
+	let Ok(text) = std::fs::read_to_string(source) else { return };
+	let directory = source.parent().unwrap_or(root);
+	for (index, line) in text.lines().enumerate() {
+		for literal in string_literals(line) {
+			if !is_asset_path(literal) {
+				continue;
+			}
+			if !root.join(literal).exists() && !directory.join(literal).exists() {
+				errors.push(AssetError::Missing { path: literal.to_string(), source: source.to_path_buf(), line: index + 1 });
+			}
+		}
+	}
+}
+
+/// The contents of the plain string literals on a line, skipping literals with escapes.
+fn string_literals(line: &str) -> Vec<&str> {
+	let mut literals = Vec::new();
+	let mut rest = line;
+	while let Some(start) = rest.find('"') {
+		let after = &rest[start + 1..];
+		let Some(end) = after.find('"') else { break };
+		let literal = &after[..end];
+		if !literal.contains('\\') {
+			literals.push(literal);
+		}
+		rest = &after[end + 1..];
+	}
+	literals
+}
+
+fn is_asset_path(literal: &str) -> bool {
+	// format strings and urls are not files
+	if literal.contains(['{', ' ']) || literal.contains("://") {
+		return false;
+	}
+	let Some((_, extension)) = literal.rsplit_once('.') else { return false };
+	let extension = extension.to_lowercase();
+	IMAGE_EXTENSIONS.iter()
+		.chain(&AUDIO_EXTENSIONS)
+		.chain(&FONT_EXTENSIONS)
+		.chain(&SHADER_EXTENSIONS)
+		.chain(&VIDEO_EXTENSIONS)
+		.any(|asset| *asset == extension)
+}